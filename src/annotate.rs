@@ -0,0 +1,111 @@
+//! GitHub Actions workflow annotations.
+//!
+//! With `--annotate`, or automatically when `GITHUB_ACTIONS=true`, skipped
+//! occurrences and verification failures are also emitted as
+//! `::warning file=...,line=...::message` / `::error ...` workflow commands,
+//! which GitHub renders inline on pull requests.
+
+use crate::console::outln;
+use crate::fs::display_path;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Severity of a workflow annotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Warning,
+    Error,
+}
+
+/// Enables annotations if requested or running under GitHub Actions.
+pub fn setup(requested: bool) {
+    let detected = std::env::var("GITHUB_ACTIONS").is_ok_and(|v| v == "true");
+    ENABLED.store(requested || detected, Ordering::Relaxed);
+}
+
+/// Returns `true` if annotations are emitted.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Emits a warning annotation, if enabled.
+pub fn warning(file: Option<&Path>, line: Option<usize>, message: &str) {
+    emit(Level::Warning, file, line, message);
+}
+
+/// Emits an error annotation, if enabled.
+pub fn error(file: Option<&Path>, line: Option<usize>, message: &str) {
+    emit(Level::Error, file, line, message);
+}
+
+fn emit(level: Level, file: Option<&Path>, line: Option<usize>, message: &str) {
+    if is_enabled() {
+        outln!("{}", format_command(level, file, line, message));
+    }
+}
+
+/// Formats a workflow command, escaping properties and message.
+fn format_command(level: Level, file: Option<&Path>, line: Option<usize>, message: &str) -> String {
+    let name = match level {
+        Level::Warning => "warning",
+        Level::Error => "error",
+    };
+
+    let mut properties = Vec::new();
+    if let Some(file) = file {
+        properties.push(format!("file={}", escape_property(&display_path(file))));
+    }
+    if let Some(line) = line {
+        properties.push(format!("line={}", line));
+    }
+
+    if properties.is_empty() {
+        format!("::{}::{}", name, escape_data(message))
+    } else {
+        format!(
+            "::{} {}::{}",
+            name,
+            properties.join(","),
+            escape_data(message)
+        )
+    }
+}
+
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_command_with_location() {
+        assert_eq!(
+            format_command(
+                Level::Warning,
+                Some(Path::new("src/lib.rs")),
+                Some(3),
+                "Not updated"
+            ),
+            "::warning file=src/lib.rs,line=3::Not updated"
+        );
+    }
+
+    #[test]
+    fn test_format_command_escapes_message() {
+        assert_eq!(
+            format_command(Level::Error, None, None, "failed: 100%\nsee log"),
+            "::error::failed: 100%25%0Asee log"
+        );
+    }
+}
@@ -0,0 +1,728 @@
+//! Stable library interface.
+//!
+//! This module is the supported way to use `cargo-rename` as a library and
+//! follows semver: items here only change in breaking releases. Every other
+//! module is an implementation detail of the CLI and may change at any time.
+//!
+//! A rename is split into two steps, so callers can inspect the changes before
+//! anything is written:
+//!
+//! ```no_run
+//! use cargo_rename::api::{self, RenameOptions, Workspace};
+//!
+//! # fn main() -> cargo_rename::Result<()> {
+//! let workspace = Workspace::load(None)?;
+//! let options = RenameOptions::new("old-crate").new_name("new-crate");
+//!
+//! let plan = api::plan(&workspace, &options)?;
+//! for (from, to) in plan.directory_moves() {
+//!     println!("move {} -> {}", from.display(), to.display());
+//! }
+//! for file in plan.files() {
+//!     print!("{}", file.diff());
+//! }
+//!
+//! let report = plan.apply()?;
+//! println!("{} files updated", report.files_updated);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`plan`] and [`apply`] never prompt or print. [`execute`] runs the interactive CLI
+//! flow instead, including confirmation (unless
+//! [`skip_confirmation`](RenameOptions::skip_confirmation) is set) and the
+//! printed summary.
+//!
+//! To embed a rename in another interface, [`RenameOptions`] runs both steps
+//! without reading stdin or writing to stdout or stderr: progress, the report
+//! sections and warnings the CLI would print, and the outcome go to a
+//! [`Reporter`], and a callback decides whether to apply the plan. Diagnostics
+//! still go to the [`log`](https://docs.rs/log) facade, if a logger is set.
+//!
+//! ```no_run
+//! use cargo_rename::api::{RenameOptions, Reporter, Step};
+//!
+//! struct Log;
+//!
+//! impl Reporter for Log {
+//!     fn progress(&mut self, step: Step) {
+//!         eprintln!("{:?}...", step);
+//!     }
+//! }
+//!
+//! # fn main() -> cargo_rename::Result<()> {
+//! let report = RenameOptions::new("old-crate")
+//!     .new_name("new-crate")
+//!     .reporter(Log)
+//!     .confirm(|plan| plan.changes().len() < 100)
+//!     .run()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`WorkspaceScan`] lists the files a rename looks at, grouped by
+//! [`FileKind`], with the same ignore rules: `.gitignore` is honored, `target`
+//! and `.git` are skipped, and hidden files are skipped except `.env` files.
+//! [`files_matching`] selects files by glob under the same rules.
+//!
+//! ```no_run
+//! use cargo_rename::api::{FileKind, Workspace, WorkspaceScan};
+//!
+//! # fn main() -> cargo_rename::Result<()> {
+//! let workspace = Workspace::load(None)?;
+//! let scan = WorkspaceScan::new(workspace.root());
+//! for manifest in scan.of_kind(FileKind::Manifest) {
+//!     println!("{}", manifest.display());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::config::WorkspaceConfig;
+use crate::console::{self, Line};
+use crate::fs::transaction::{Operation, Transaction};
+use crate::steps::rename::{
+    self, RenameArgs, StagedRename, apply_naming_policy, apply_rename, check_workspace_root,
+    load_metadata, resolve_package_at, resolve_relative_move, stage_rename,
+};
+use crate::verify::preflight_checks;
+use cargo_metadata::Metadata;
+use std::path::{Path, PathBuf};
+
+pub use crate::cargo::{DepKind, PathStyle};
+pub use crate::error::{RenameError, Result};
+pub use crate::rewrite::SourceRule;
+pub use crate::verify::{VerificationOutcome, verify_workspace};
+pub use crate::workspace_scan::{FileKind, ScannedFile, WorkspaceScan, files_matching};
+
+/// A Cargo workspace loaded with `cargo metadata`.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    metadata: Metadata,
+}
+
+impl Workspace {
+    /// Loads the workspace containing `manifest_path`, or the one above the
+    /// current directory if `None`.
+    ///
+    /// # Errors
+    ///
+    /// - `Io` if `manifest_path` does not exist or is a directory
+    /// - `Other` if `cargo metadata` fails
+    /// - `ExternalCommandTimeout` if it outlives the timeout
+    pub fn load(manifest_path: Option<&Path>) -> Result<Self> {
+        Ok(Self {
+            metadata: load_metadata(manifest_path)?,
+        })
+    }
+
+    /// Returns the workspace root directory.
+    pub fn root(&self) -> &Path {
+        self.metadata.workspace_root.as_std_path()
+    }
+
+    /// Returns the names of the workspace members.
+    pub fn members(&self) -> Vec<&str> {
+        self.metadata
+            .workspace_members
+            .iter()
+            .map(|id| self.metadata[id].name.as_str())
+            .collect()
+    }
+}
+
+/// A validated rename with all changes staged in memory.
+///
+/// Nothing is written until the plan is passed to [`apply`]. Dropping a plan
+/// discards it.
+#[must_use = "a plan does nothing until passed to `apply`"]
+pub struct RenamePlan {
+    staged: StagedRename,
+}
+
+impl RenamePlan {
+    /// Returns the current package name.
+    pub fn old_name(&self) -> &str {
+        &self.staged.args.old_name
+    }
+
+    /// Returns the package name after the rename.
+    pub fn new_name(&self) -> &str {
+        self.staged.new_name()
+    }
+
+    /// Returns the current package directory.
+    pub fn old_dir(&self) -> &Path {
+        &self.staged.old_dir
+    }
+
+    /// Returns the package directory after the rename.
+    pub fn new_dir(&self) -> &Path {
+        &self.staged.new_dir
+    }
+
+    /// Returns `true` if the rename changes nothing.
+    pub fn is_empty(&self) -> bool {
+        self.staged.txn.is_empty()
+    }
+
+    /// Returns one human-readable line per staged change, including the
+    /// manifests of external dependents. Edits of rename hooks are made in
+    /// [`apply`] and not listed.
+    pub fn changes(&self) -> Vec<String> {
+        let mut changes = self.staged.txn.preview();
+        for (_, external) in &self.staged.external {
+            changes.extend(external.preview());
+        }
+        changes
+    }
+
+    /// Returns the staged file updates, sorted by path.
+    pub fn files(&self) -> Vec<FileChange> {
+        let txn = &self.staged.txn;
+        let mut files: Vec<FileChange> = txn
+            .operations()
+            .iter()
+            .filter_map(|op| match op {
+                Operation::UpdateFile {
+                    path,
+                    original,
+                    new,
+                } => Some(FileChange {
+                    path: path.clone(),
+                    new_path: txn.moved_path(path),
+                    original: original.clone(),
+                    new: new.clone(),
+                }),
+                Operation::MoveDirectory { .. } => None,
+            })
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        files
+    }
+
+    /// Returns the staged directory moves as `(from, to)`, in the order they
+    /// are executed. File updates are applied before the moves.
+    pub fn directory_moves(&self) -> Vec<(PathBuf, PathBuf)> {
+        self.staged.txn.directory_moves()
+    }
+
+    /// Returns the files left alone that need a manual look, with the reason
+    /// (unparsable Rust files, conflicting crate roots, ...).
+    pub fn skipped(&self) -> &[(PathBuf, String)] {
+        self.staged.txn.skipped()
+    }
+
+    /// Writes the plan to disk; see [`apply`].
+    ///
+    /// # Errors
+    ///
+    /// The errors of [`apply`].
+    pub fn apply(self) -> Result<Report> {
+        apply(self)
+    }
+
+    /// Returns the source rewrite rules that changed each Rust file, by name
+    /// (`use-statement`, `qualified-path`, `doc-link`, `macro`, ...).
+    ///
+    /// Rules can be disabled with [`RenameOptions::disable_rules`].
+    pub fn matched_rules(&self) -> Vec<(&Path, &[String])> {
+        self.staged
+            .txn
+            .matched_rules()
+            .iter()
+            .map(|(path, rules)| (path.as_path(), rules.as_slice()))
+            .collect()
+    }
+}
+
+/// A file update staged in a [`RenamePlan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FileChange {
+    /// Current path of the file.
+    pub path: PathBuf,
+    /// Path of the file after the directory moves.
+    pub new_path: PathBuf,
+    /// Current content.
+    pub original: String,
+    /// Content after the rename.
+    pub new: String,
+}
+
+impl FileChange {
+    /// Returns the change as a unified diff without context lines.
+    pub fn diff(&self) -> String {
+        let mut out = format!(
+            "--- {}\n+++ {}\n",
+            self.path.display(),
+            self.new_path.display()
+        );
+        for hunk in diff_hunks(&self.original, &self.new) {
+            out.push_str(&hunk);
+        }
+        out
+    }
+}
+
+/// Returns the `@@` hunks turning `old` into `new`, line by line.
+///
+/// Lines are matched with a longest common subsequence after stripping the
+/// common prefix and suffix; changes too large for that are shown as one
+/// hunk replacing the whole middle.
+fn diff_hunks(old: &str, new: &str) -> Vec<String> {
+    const MAX_CELLS: usize = 4_000_000;
+
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    // `same[i][j]`: length of the common subsequence of a[i..] and b[j..];
+    // not allocated, all zeros, for changes too large to match
+    let mut same = Vec::new();
+    if a.len().saturating_mul(b.len()) <= MAX_CELLS {
+        same = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                same[i][j] = if a[i] == b[j] {
+                    same[i + 1][j + 1] + 1
+                } else {
+                    same[i + 1][j].max(same[i][j + 1])
+                };
+            }
+        }
+    }
+
+    let common = |i: usize, j: usize| same.get(i).map_or(0, |row: &Vec<usize>| row[j]);
+
+    let mut hunks = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] && common(i, j) == common(i + 1, j + 1) + 1 {
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        let (start_old, start_new) = (i, j);
+        let (mut removed, mut added) = (String::new(), String::new());
+        while i < a.len() || j < b.len() {
+            if i < a.len()
+                && j < b.len()
+                && a[i] == b[j]
+                && common(i, j) == common(i + 1, j + 1) + 1
+            {
+                break;
+            }
+            if i < a.len() && (j == b.len() || common(i + 1, j) >= common(i, j + 1)) {
+                removed.push_str(&format!("-{}\n", a[i]));
+                i += 1;
+            } else {
+                added.push_str(&format!("+{}\n", b[j]));
+                j += 1;
+            }
+        }
+        hunks.push(format!(
+            "@@ -{} +{} @@\n{}{}",
+            range(prefix + start_old, i - start_old),
+            range(prefix + start_new, j - start_new),
+            removed,
+            added
+        ));
+    }
+    hunks
+}
+
+/// Formats a hunk range: the 1-based start line and the count, or the line
+/// before an empty range.
+fn range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, count),
+    }
+}
+
+/// Outcome of an applied rename.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Report {
+    /// Package name before the rename.
+    pub old_name: String,
+    /// Package name after the rename.
+    pub new_name: String,
+    /// Package directory before the rename.
+    pub old_dir: PathBuf,
+    /// Package directory after the rename.
+    pub new_dir: PathBuf,
+    /// Number of files written.
+    pub files_updated: usize,
+    /// Number of directories moved.
+    pub dirs_moved: usize,
+    /// `true` if the plan was made with `dry_run` and nothing was written.
+    pub dry_run: bool,
+    /// Result of re-loading the workspace, unless nothing was written.
+    pub verification: Option<VerificationOutcome>,
+}
+
+/// Validates a rename and stages its changes without writing anything.
+///
+/// The old name must name a workspace member exactly; unlike the CLI, no
+/// close matches are offered. A workspace naming policy may change the new
+/// name; [`RenamePlan::new_name`] returns the one used. Nothing is printed:
+/// [`RenameOptions::run`] hands the report sections and warnings to its
+/// reporter, which is otherwise unused, like the confirmation callback.
+///
+/// # Errors
+///
+/// - `InvalidPath` if the directory of [`RenameOptions::at`] is not a member
+/// - `InvalidName` if the naming policy rejects the new name
+/// - Any preflight failure (`InvalidName`, `DirectoryExists`, `DirtyWorkspace`, ...)
+/// - `Multiple` if several preflight checks fail
+/// - Any error raised while staging edits
+pub fn plan(workspace: &Workspace, options: &RenameOptions) -> Result<RenamePlan> {
+    console::capture(|| stage(workspace, options)).0
+}
+
+fn stage(workspace: &Workspace, options: &RenameOptions) -> Result<RenamePlan> {
+    let metadata = &workspace.metadata;
+    let mut args = options.args.clone();
+    args.validate()?;
+    if let Some(at) = &args.at {
+        args.old_name = resolve_package_at(at, metadata)?;
+    }
+
+    let config = WorkspaceConfig::load(metadata)?;
+    apply_naming_policy(&mut args, metadata, &config)?;
+    resolve_relative_move(&mut args, metadata)?;
+    check_workspace_root(&args, metadata)?;
+    preflight_checks(&args, metadata)?;
+
+    let old_dir = metadata
+        .workspace_packages()
+        .into_iter()
+        .find(|p| p.name == args.old_name)
+        .map(|p| {
+            p.manifest_path
+                .parent()
+                .unwrap()
+                .as_std_path()
+                .to_path_buf()
+        })
+        .ok_or_else(|| RenameError::PackageNotFound(args.old_name.clone(), Vec::new()))?;
+    let workspace_root = workspace.root();
+
+    if args.would_change_anything(&old_dir, workspace_root)? {
+        return Ok(RenamePlan {
+            staged: stage_rename(args, metadata, &config)?,
+        });
+    }
+
+    Ok(RenamePlan {
+        staged: StagedRename {
+            txn: Transaction::new(args.dry_run),
+            args,
+            workspace_root: workspace_root.to_path_buf(),
+            new_dir: old_dir.clone(),
+            old_dir,
+            external: Vec::new(),
+            hooks: Vec::new(),
+        },
+    })
+}
+
+/// Writes a plan to disk, like the CLI after confirmation: verifies the
+/// workspace, updates the external dependents and records the rename in the
+/// workspace history. Nothing is printed, as with [`plan`].
+///
+/// # Errors
+///
+/// - `PartialFailure` if the commit failed; executed operations are rolled
+///   back and the error lists any that could not be
+pub fn apply(plan: RenamePlan) -> Result<Report> {
+    console::capture(|| write(plan)).0
+}
+
+fn write(plan: RenamePlan) -> Result<Report> {
+    let staged = plan.staged;
+    let old_name = staged.args.old_name.clone();
+    let new_name = staged.new_name().to_string();
+    let old_dir = staged.old_dir.clone();
+    let new_dir = staged.new_dir.clone();
+    let dry_run = staged.txn.is_dry_run();
+
+    if staged.txn.is_empty() && staged.external.is_empty() && staged.hooks.is_empty() {
+        return Ok(Report {
+            old_name,
+            new_name,
+            old_dir,
+            new_dir,
+            files_updated: 0,
+            dirs_moved: 0,
+            dry_run,
+            verification: None,
+        });
+    }
+
+    let applied = apply_rename(staged)?;
+
+    Ok(Report {
+        old_name,
+        new_name,
+        old_dir,
+        new_dir,
+        files_updated: applied.files_updated,
+        dirs_moved: applied.dirs_moved,
+        dry_run,
+        verification: applied.verification,
+    })
+}
+
+/// A step of [`RenameOptions::run`], reported before it starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Step {
+    /// Running `cargo metadata`.
+    LoadWorkspace,
+    /// Validating the rename and staging its changes ([`plan`]).
+    Plan,
+    /// Writing the plan and verifying the workspace ([`apply`]).
+    Apply,
+}
+
+/// Receives the output of [`RenameOptions::run`].
+///
+/// Every method does nothing by default.
+pub trait Reporter {
+    /// Called before each step.
+    fn progress(&mut self, step: Step) {
+        let _ = step;
+    }
+
+    /// Called for each line the CLI would print, without color codes.
+    /// `warning` is set for lines the CLI prints to stderr.
+    fn message(&mut self, text: &str, warning: bool) {
+        let _ = (text, warning);
+    }
+
+    /// Called with the staged plan, before it is confirmed.
+    fn plan(&mut self, plan: &RenamePlan) {
+        let _ = plan;
+    }
+
+    /// Called with the outcome after the plan was applied.
+    fn summary(&mut self, report: &Report) {
+        let _ = report;
+    }
+}
+
+/// A [`Reporter`] that ignores everything.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Silent;
+
+impl Reporter for Silent {}
+
+/// Builder for a rename, passed to [`plan`] and [`execute`] or run with a
+/// [`Reporter`] and a confirmation callback.
+#[must_use = "options do nothing until `run` is called"]
+pub struct RenameOptions {
+    manifest_path: Option<PathBuf>,
+    args: RenameArgs,
+    reporter: Box<dyn Reporter>,
+    confirm: Box<dyn FnMut(&RenamePlan) -> bool>,
+}
+
+impl RenameOptions {
+    /// Starts a rename of the workspace member `old_name`.
+    ///
+    /// The plan is applied unless a [`confirm`](Self::confirm) callback
+    /// declines it; nothing is reported unless a [`reporter`](Self::reporter)
+    /// is set.
+    pub fn new(old_name: impl Into<String>) -> Self {
+        Self {
+            manifest_path: None,
+            args: RenameArgs {
+                old_name: old_name.into(),
+                ..Default::default()
+            },
+            reporter: Box::new(Silent),
+            confirm: Box::new(|_| true),
+        }
+    }
+
+    /// Starts a rename of the workspace member in `dir`, its directory or
+    /// `Cargo.toml` (relative to the current directory), instead of naming it.
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        let mut options = Self::new("");
+        options.args.at = Some(dir.into());
+        options
+    }
+
+    /// Sets the new package name.
+    pub fn new_name(mut self, new_name: impl Into<String>) -> Self {
+        self.args.new_name = Some(new_name.into());
+        self
+    }
+
+    /// Moves the package to `dir`, relative to the workspace root, or to a
+    /// directory named after the new package name if `None`.
+    pub fn move_to(mut self, dir: Option<PathBuf>) -> Self {
+        self.args.outdir = Some(dir);
+        self
+    }
+
+    /// Selects the workspace by its `Cargo.toml` instead of the current
+    /// directory.
+    pub fn manifest_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.manifest_path = Some(path.into());
+        self
+    }
+
+    /// Stages and reports the rename without writing anything.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.args.dry_run = dry_run;
+        self
+    }
+
+    /// Allows the rename with uncommitted git changes.
+    pub fn allow_dirty(mut self, allow_dirty: bool) -> Self {
+        self.args.allow_dirty = allow_dirty;
+        self
+    }
+
+    /// Skips the confirmation prompt of [`execute`].
+    pub fn skip_confirmation(mut self, skip_confirmation: bool) -> Self {
+        self.args.skip_confirmation = skip_confirmation;
+        self
+    }
+
+    /// Runs the rename hooks and naming policy command configured in
+    /// `[workspace.metadata.cargo-rename]`. Off by default; hooks run in
+    /// [`apply`], so their edits are not part of the plan.
+    pub fn allow_commands(mut self, allow_commands: bool) -> Self {
+        self.args.allow_commands = allow_commands;
+        self
+    }
+
+    /// Records the rename in `.cargo-rename/history.json`. On by default,
+    /// except in CI.
+    pub fn record_history(mut self, record_history: bool) -> Self {
+        self.args.no_history = !record_history;
+        self
+    }
+
+    /// Sets how rewritten dependency `path` values are written.
+    pub fn path_style(mut self, path_style: PathStyle) -> Self {
+        self.args.path_style = path_style;
+        self
+    }
+
+    /// Only retargets dependencies of these kinds; the others keep the old
+    /// package under its old key.
+    pub fn only_kinds(mut self, kinds: impl IntoIterator<Item = DepKind>) -> Self {
+        self.args.only_kinds = kinds.into_iter().collect();
+        self
+    }
+
+    /// Leaves Rust sources matched only by these rewrite rules alone.
+    pub fn disable_rules(mut self, rules: impl IntoIterator<Item = SourceRule>) -> Self {
+        self.args.disable_rules = rules.into_iter().collect();
+        self
+    }
+
+    /// Sets the reporter receiving progress, messages and the outcome.
+    pub fn reporter(mut self, reporter: impl Reporter + 'static) -> Self {
+        self.reporter = Box::new(reporter);
+        self
+    }
+
+    /// Sets the callback deciding whether to apply a staged plan.
+    ///
+    /// It is not called for dry runs.
+    pub fn confirm(mut self, confirm: impl FnMut(&RenamePlan) -> bool + 'static) -> Self {
+        self.confirm = Box::new(confirm);
+        self
+    }
+
+    /// Loads the workspace, stages the rename and applies it if confirmed.
+    ///
+    /// Returns `None` if the confirmation callback declined the plan.
+    ///
+    /// # Errors
+    ///
+    /// The errors of [`Workspace::load`], [`plan`] and [`apply`].
+    pub fn run(mut self) -> Result<Option<Report>> {
+        self.reporter.progress(Step::LoadWorkspace);
+        let workspace = Workspace::load(self.manifest_path.as_deref())?;
+
+        self.reporter.progress(Step::Plan);
+        let (staged, lines) = console::capture(|| plan(&workspace, &self));
+        forward(self.reporter.as_mut(), &lines);
+        let plan = staged?;
+        self.reporter.plan(&plan);
+
+        if !self.args.dry_run && !(self.confirm)(&plan) {
+            return Ok(None);
+        }
+
+        self.reporter.progress(Step::Apply);
+        let (applied, lines) = console::capture(|| apply(plan));
+        forward(self.reporter.as_mut(), &lines);
+        let report = applied?;
+        self.reporter.summary(&report);
+
+        Ok(Some(report))
+    }
+}
+
+/// Runs the interactive CLI flow: the plan is printed and confirmed on the
+/// terminal (unless [`skip_confirmation`](RenameOptions::skip_confirmation)
+/// is set), then applied and summarized on stdout. The reporter and
+/// confirmation callback of `options` are not used.
+///
+/// # Errors
+///
+/// The errors of [`plan`] and [`apply`], and `Cancelled` if the rename is
+/// declined.
+pub fn execute(options: &RenameOptions) -> Result<()> {
+    let mut args = options.args.clone();
+    if args.manifest_path.is_none() {
+        args.manifest_path = options.manifest_path.clone();
+    }
+    rename::execute_selected(args)
+}
+
+/// Passes captured output lines to `reporter`.
+fn forward(reporter: &mut dyn Reporter, lines: &[Line]) {
+    for line in lines {
+        reporter.message(&line.text, line.stderr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_hunks() {
+        assert_eq!(
+            diff_hunks("a\nb\nc\n", "a\nx\nc\ny\n"),
+            ["@@ -2 +2 @@\n-b\n+x\n", "@@ -3,0 +4 @@\n+y\n"]
+        );
+
+        // Too large to match line by line: one hunk for the whole middle
+        let old: String = (0..2100).map(|i| format!("old {}\n", i)).collect();
+        let new: String = (0..2100).map(|i| format!("new {}\n", i)).collect();
+        let hunks = diff_hunks(&old, &new);
+        assert_eq!(hunks.len(), 1);
+        assert!(hunks[0].starts_with("@@ -1,2100 +1,2100 @@\n-old 0\n"));
+    }
+}
@@ -27,10 +27,78 @@
 //! # Package rename
 //! alias = { package = "my-crate", path = "../my-crate" }
 //!
-//! # Workspace inheritance
+//! # Workspace inheritance; only the key is renamed
 //! my-crate = { workspace = true }
+//!
+//! # Artifact dependency (nightly `bindeps`); artifact fields are kept as-is
+//! my-crate = { path = "../my-crate", artifact = "bin", target = "target" }
+//! ```
+//!
+//! ## Dependency Kinds
+//!
+//! With [`ManifestUpdate::kinds`], [`update_dependent_manifest`] only touches
+//! sections of the given [`DepKind`]s, e.g. just `[dev-dependencies]` (including target-specific
+//! ones) while `[dependencies]` keeps its key. [`pin_unselected_dependencies`]
+//! points those other entries at the renamed package:
+//!
+//! ```toml
+//! [dependencies]
+//! my-crate = { path = "../my-crate", package = "new-crate" }
+//!
+//! [dev-dependencies]
+//! new-crate = { path = "../my-crate" }
+//! ```
+//!
+//! ## Patches and Replacements
+//!
+//! [`update_patch_sections`] updates the overrides pointing at the package,
+//! which would otherwise fail the build with "patch not used":
+//!
+//! ```toml
+//! [patch.crates-io]
+//! my-crate = { path = "crates/my-crate" }
+//!
+//! [patch.crates-io.my-crate]
+//! path = "crates/my-crate"
+//!
+//! [replace]
+//! "my-crate:0.1.0" = { path = "crates/my-crate" }
+//! ```
+//!
+//! [`update_dependent_manifest`] leaves these sections alone.
+//!
+//! ## Other Crates of the Same Name
+//!
+//! Entries whose `path` resolves elsewhere than the package directory, or
+//! that come from `git`, name another crate and are left alone, in all
+//! sections. [`ManifestUpdate::other_sources`] rewrites them too:
+//!
+//! ```toml
+//! [dependencies]
+//! my-crate = { path = "../my-crate" }
+//! vendored = { package = "my-crate", path = "../vendor/my-crate" } # kept
+//! ```
+//!
+//! ## Path Styles
+//!
+//! Cargo resolves `path` against the manifest's directory. Rewritten paths
+//! keep the style of the value they replace ([`PathStyle::Preserve`]):
+//!
+//! ```toml
+//! # crates/app/Cargo.toml
+//! a = { path = "../a" }                # shortest relative path
+//! b = { path = "../../crates/b" }      # up to the workspace root, then down
+//! c = { path = "/src/ws/crates/c" }    # absolute
 //! ```
 //!
+//! Entries inheriting from `[workspace.dependencies]` (`workspace = true`)
+//! take their path from the root manifest. Only their key is renamed; a
+//! leftover `path` next to `workspace = true`, which Cargo ignores, is kept.
+//!
+//! Renaming the key of an artifact dependency also renames the
+//! `CARGO_BIN_FILE_<DEP>`-style variables Cargo sets for it; this is reported
+//! via [`DependencyReplacements::renames_artifact_key`].
+//!
 //! ## State Machine
 //!
 //! `TomlProcessor` tracks:
@@ -43,6 +111,12 @@
 //! - Dependency declarations (`my-crate = ...`)
 //! - Brace open/close
 //!
+//! Every section header resets the state, so a dependent declaring the crate
+//! in several sections (`[dependencies]`, `[dev-dependencies.my-crate]`,
+//! `[target.*.dependencies]`) gets each occurrence handled independently, and
+//! nothing carries over into unrelated tables like `[lib]`. Braces inside
+//! strings and comments are ignored.
+//!
 //! ## Guarantees
 //!
 //! - Preserves formatting, indentation, alignment
@@ -52,52 +126,444 @@
 //! - Normalizes paths to forward slashes
 
 use crate::error::Result;
+use crate::fs::display_path;
 use crate::fs::transaction::Transaction;
+use crate::fs::{check_resolves, normalize_path, shortest_relative};
+use crate::symbols;
+use clap::ValueEnum;
 use regex::Regex;
-use std::fs;
-use std::path::Path;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Range;
+use std::path::{Component, Path};
+use toml_edit::{DocumentMut, Item, Key};
+
+/// How rewritten dependency `path` values are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum PathStyle {
+    /// Keep the style of each original value
+    #[default]
+    Preserve,
+    /// Shortest path relative to the manifest (`../a`)
+    Relative,
+    /// Up to the workspace root, then down (`../../crates/a`)
+    FromRoot,
+}
+
+/// Kind of dependency table, as selected with `--only-kinds`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DepKind {
+    /// [dependencies]
+    Normal,
+    /// [dev-dependencies]
+    Dev,
+    /// [build-dependencies]
+    Build,
+}
+
+impl DepKind {
+    /// Returns the kind of a section label such as `dev-dependencies` or
+    /// `target.'cfg(unix)'.build-dependencies`, or `None` for other tables.
+    pub fn of_section(label: &str) -> Option<Self> {
+        if label.ends_with("dev-dependencies") {
+            Some(DepKind::Dev)
+        } else if label.ends_with("build-dependencies") {
+            Some(DepKind::Build)
+        } else if label.ends_with("dependencies") {
+            Some(DepKind::Normal)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the dependency table of this kind.
+    pub fn section(self) -> &'static str {
+        match self {
+            DepKind::Normal => "[dependencies]",
+            DepKind::Dev => "[dev-dependencies]",
+            DepKind::Build => "[build-dependencies]",
+        }
+    }
+
+    /// Returns the kind of dependency a source file can use, by its path
+    /// relative to the package: `build.rs` sees build-dependencies, `tests/`,
+    /// `benches/` and `examples/` see dev-dependencies, everything else
+    /// normal ones.
+    pub fn of_source(relative: &Path) -> Self {
+        match relative.components().next() {
+            Some(Component::Normal(first)) if first == "build.rs" => DepKind::Build,
+            Some(Component::Normal(first))
+                if first == "tests" || first == "benches" || first == "examples" =>
+            {
+                DepKind::Dev
+            }
+            _ => DepKind::Normal,
+        }
+    }
+}
+
+impl From<cargo_metadata::DependencyKind> for DepKind {
+    fn from(kind: cargo_metadata::DependencyKind) -> Self {
+        match kind {
+            cargo_metadata::DependencyKind::Development => DepKind::Dev,
+            cargo_metadata::DependencyKind::Build => DepKind::Build,
+            _ => DepKind::Normal,
+        }
+    }
+}
+
+/// The new location of the moved package, in every style.
+struct NewPaths {
+    style: PathStyle,
+    relative: String,
+    absolute: String,
+    /// `../` repeated up to the workspace root; `None` at the root itself or
+    /// outside the workspace.
+    root_prefix: Option<String>,
+    from_root: Option<String>,
+}
+
+impl NewPaths {
+    fn new(
+        manifest_dir: &Path,
+        new_dir: &Path,
+        workspace_root: &Path,
+        style: PathStyle,
+    ) -> Result<Self> {
+        let manifest_dir = normalize_path(manifest_dir);
+        let new_dir = normalize_path(new_dir);
+        let workspace_root = normalize_path(workspace_root);
+        let relative = shortest_relative(&new_dir, &manifest_dir)?;
+
+        let depth = manifest_dir
+            .strip_prefix(&workspace_root)
+            .ok()
+            .map(|rel| {
+                rel.components()
+                    .filter(|c| matches!(c, Component::Normal(_)))
+                    .count()
+            })
+            .filter(|&depth| depth > 0);
+        let root_prefix = depth.map(|depth| "../".repeat(depth));
+        let from_root = root_prefix.as_ref().and_then(|prefix| {
+            new_dir
+                .strip_prefix(&workspace_root)
+                .ok()
+                .map(|rel| format!("{}{}", prefix, slashes(rel)))
+        });
+        if let Some(from_root) = &from_root {
+            check_resolves(from_root, &manifest_dir, &new_dir)?;
+        }
+
+        Ok(Self {
+            style,
+            relative,
+            absolute: slashes(&new_dir),
+            root_prefix,
+            from_root,
+        })
+    }
+
+    /// Returns the new path to write in place of `original`.
+    fn replacement(&self, original: &str) -> &str {
+        let from_root = self.from_root.as_deref().unwrap_or(&self.relative);
+
+        match self.style {
+            PathStyle::Relative => &self.relative,
+            PathStyle::FromRoot => from_root,
+            PathStyle::Preserve if Path::new(original).is_absolute() => &self.absolute,
+            PathStyle::Preserve => {
+                let original = original.replace('\\', "/");
+                let climbs_to_root = self.root_prefix.as_ref().is_some_and(|prefix| {
+                    original
+                        .strip_prefix(prefix.as_str())
+                        .is_some_and(|rest| !rest.is_empty() && !rest.starts_with(".."))
+                });
+                if climbs_to_root {
+                    from_root
+                } else {
+                    &self.relative
+                }
+            }
+        }
+    }
+}
+
+fn slashes(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Lines changed in a dependent manifest, per section in file order.
+///
+/// Sections are named by their table header without the dependency key, e.g.
+/// `dev-dependencies` or `target.'cfg(unix)'.dependencies`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DependencyReplacements {
+    sections: Vec<(String, usize)>,
+    artifact_key: bool,
+}
+
+impl DependencyReplacements {
+    /// Returns `(section, count)` pairs in the order the sections appear.
+    pub fn sections(&self) -> &[(String, usize)] {
+        &self.sections
+    }
+
+    /// Returns the total number of changed lines.
+    pub fn total(&self) -> usize {
+        self.sections.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Returns `true` if the key of an artifact dependency was renamed.
+    ///
+    /// Aliased entries (`alias = { package = ... }`) keep their key, so only
+    /// entries keyed by the old name count.
+    pub fn renames_artifact_key(&self) -> bool {
+        self.artifact_key
+    }
+
+    fn record(&mut self, section: &str) {
+        match self.sections.iter_mut().find(|(name, _)| name == section) {
+            Some((_, count)) => *count += 1,
+            None => self.sections.push((section.to_string(), 1)),
+        }
+    }
+}
+
+impl fmt::Display for DependencyReplacements {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sections = self
+            .sections
+            .iter()
+            .map(|(section, count)| format!("{} {}{}", section, symbols::times(), count))
+            .collect::<Vec<_>>();
+        write!(f, "{}", sections.join(", "))
+    }
+}
+
+/// A rename or move of a package, as applied to the manifests referring to it.
+#[derive(Debug, Clone, Copy)]
+pub struct ManifestUpdate<'a> {
+    pub old_name: &'a str,
+    pub new_name: &'a str,
+    /// Package directory before the rename.
+    pub old_dir: &'a Path,
+    /// Package directory after the rename; `old_dir` if it is not moved.
+    pub new_dir: &'a Path,
+    /// Root of the workspace, for [`PathStyle::FromRoot`] and workspace fragments.
+    pub workspace_root: &'a Path,
+    /// Style of rewritten `path` values.
+    pub path_style: PathStyle,
+    /// Dependency kinds to update; empty for all.
+    pub kinds: &'a [DepKind],
+    pub name_changed: bool,
+    pub path_changed: bool,
+    /// Also rewrite entries on `old_name` whose `path` is not `old_dir` or
+    /// that come from `git`, i.e. treat every crate of that name as the
+    /// renamed one. For projects depending on a published rename.
+    pub other_sources: bool,
+}
+
+impl<'a> ManifestUpdate<'a> {
+    /// Describes renaming `old_name` in `old_dir` to `new_name` in `new_dir`,
+    /// for all dependency kinds with [`PathStyle::Preserve`].
+    pub fn new(
+        old_name: &'a str,
+        new_name: &'a str,
+        old_dir: &'a Path,
+        new_dir: &'a Path,
+        workspace_root: &'a Path,
+    ) -> Self {
+        Self {
+            old_name,
+            new_name,
+            old_dir,
+            new_dir,
+            workspace_root,
+            path_style: PathStyle::default(),
+            kinds: &[],
+            name_changed: old_name != new_name,
+            path_changed: old_dir != new_dir,
+            other_sources: false,
+        }
+    }
+}
 
 /// Updates dependency references in a package's `Cargo.toml`.
 ///
-/// Scans for references to `old_name` and updates to `new_name` and/or `new_dir`.
+/// Scans for references to `old_name` and updates to `new_name` and/or
+/// `new_dir`, in the dependency sections of `update.kinds`. Rewritten paths
+/// are written in `path_style`, relative to `workspace_root` for
+/// [`PathStyle::FromRoot`]. Returns the number of changed lines per section.
 pub fn update_dependent_manifest(
     manifest_path: &Path,
-    old_name: &str,
-    new_name: &str,
-    new_dir: &Path,
-    path_changed: bool,
-    name_changed: bool,
+    update: &ManifestUpdate,
     txn: &mut Transaction,
-) -> Result<()> {
-    let content = fs::read_to_string(manifest_path)?;
-    let original = content.clone();
+) -> Result<DependencyReplacements> {
+    update_manifest(manifest_path, update, false, txn)
+}
+
+/// Updates `[patch.<registry>]` and `[replace]` entries for the package:
+/// keys (`"old-name:0.1.0"` specs for `[replace]`), table headers and `path`
+/// values. Other sections are left alone.
+pub fn update_patch_sections(
+    manifest_path: &Path,
+    update: &ManifestUpdate,
+    txn: &mut Transaction,
+) -> Result<DependencyReplacements> {
+    update_manifest(manifest_path, update, true, txn)
+}
+
+/// Keeps the dependencies on the renamed package outside `update.kinds` resolving.
+///
+/// `--only-kinds` leaves their keys, and the sources using them, alone. Each
+/// path entry on `old_dir` in the other sections gets `package = "<new>"`
+/// and, if the package moved, its new path. Entries inheriting from
+/// `[workspace.dependencies]` cannot carry `package`, so they fail the
+/// rename. Returns the number of entries changed.
+pub fn pin_unselected_dependencies(
+    manifest_path: &Path,
+    update: &ManifestUpdate,
+    txn: &mut Transaction,
+) -> Result<usize> {
+    let content = txn.read_file(manifest_path)?;
+    let mut doc: DocumentMut = content.parse()?;
     let manifest_dir = manifest_path.parent().unwrap();
+    let old_dir = normalize_path(update.old_dir);
+
+    let mut paths: Vec<Vec<String>> = Vec::new();
+    for kind in [DepKind::Normal, DepKind::Dev, DepKind::Build] {
+        if update.kinds.contains(&kind) {
+            continue;
+        }
+        let table = kind.section().trim_matches(['[', ']']).to_string();
+        paths.push(vec![table.clone()]);
+        if let Some(targets) = doc.get("target").and_then(Item::as_table_like) {
+            for (target, _) in targets.iter() {
+                paths.push(vec![
+                    "target".to_string(),
+                    target.to_string(),
+                    table.clone(),
+                ]);
+            }
+        }
+    }
+
+    let mut pinned = 0;
+    for path in &paths {
+        let Some(table) = path
+            .iter()
+            .try_fold(doc.as_item_mut(), |item, key| item.get_mut(key))
+            .and_then(Item::as_table_like_mut)
+        else {
+            continue;
+        };
+
+        for (key, item) in table.iter_mut() {
+            let package = item.get("package").and_then(Item::as_str);
+            if package.unwrap_or(key.get()) != update.old_name {
+                continue;
+            }
+            if item.get("workspace").and_then(Item::as_bool) == Some(true) {
+                return Err(anyhow::anyhow!(
+                    "{} inherits '{}' from [workspace.dependencies] in [{}], outside --only-kinds; include that kind or declare the dependency by path",
+                    display_path(manifest_path),
+                    key.get(),
+                    path.join(".")
+                )
+                .into());
+            }
+            let Some(original) = item.get("path").and_then(Item::as_str) else {
+                continue;
+            };
+            if normalize_path(&manifest_dir.join(original)) != old_dir {
+                continue;
+            }
+
+            let new_path = if !update.path_changed {
+                None
+            } else if Path::new(original).is_absolute() {
+                Some(slashes(&normalize_path(update.new_dir)))
+            } else {
+                Some(shortest_relative(update.new_dir, manifest_dir)?)
+            };
+            let Some(entry) = item.as_table_like_mut() else {
+                continue;
+            };
+            if update.name_changed {
+                entry.insert("package", toml_edit::value(update.new_name));
+            }
+            if let Some(new_path) = new_path {
+                entry.insert("path", toml_edit::value(new_path));
+            }
+            if let Some(inline) = item.as_inline_table_mut() {
+                inline.fmt();
+            }
+            pinned += 1;
+        }
+    }
 
-    if !name_changed && !path_changed {
-        return Ok(());
+    if pinned > 0 {
+        txn.update_file(manifest_path.to_path_buf(), doc.to_string())?;
     }
+    Ok(pinned)
+}
+
+/// Updates either the dependency sections or, with `patches`, the patch
+/// sections of a manifest.
+fn update_manifest(
+    manifest_path: &Path,
+    update: &ManifestUpdate,
+    patches: bool,
+    txn: &mut Transaction,
+) -> Result<DependencyReplacements> {
+    let content = txn.read_file(manifest_path)?;
+    let original = content.clone();
+    let manifest_dir = manifest_path.parent().unwrap();
 
-    log::debug!("Updating dependent manifest: {}", manifest_path.display());
+    if !update.name_changed && !update.path_changed {
+        return Ok(DependencyReplacements::default());
+    }
 
-    let new_path_str = if path_changed {
-        let rel_path = pathdiff::diff_paths(new_dir, manifest_dir)
-            .ok_or_else(|| anyhow::anyhow!("Failed to calculate relative path"))?;
-        Some(rel_path.to_string_lossy().replace('\\', "/"))
+    log::debug!(
+        "Updating dependent manifest: {}",
+        display_path(manifest_path)
+    );
+
+    let new_paths = if update.path_changed {
+        Some(NewPaths::new(
+            manifest_dir,
+            update.new_dir,
+            update.workspace_root,
+            update.path_style,
+        )?)
     } else {
         None
     };
 
-    let mut processor = TomlProcessor::new(&content, old_name, new_name, new_path_str.as_deref());
-    let new_content = processor.process(name_changed, path_changed)?;
+    let mut processor = TomlProcessor::new(
+        &content,
+        update.old_name,
+        update.new_name,
+        new_paths.as_ref(),
+    );
+    processor.kinds = update.kinds;
+    processor.patches = patches;
+    processor.foreign_lines = foreign_entry_lines(&content, manifest_dir, update, patches);
+    let new_content = processor.process(update.name_changed, update.path_changed)?;
 
     if new_content != original {
         txn.update_file(manifest_path.to_path_buf(), new_content)?;
-        log::debug!("Updated: {}", manifest_path.display());
+        log::debug!(
+            "Updated: {} ({})",
+            display_path(manifest_path),
+            processor.replacements
+        );
     } else {
-        log::debug!("No changes: {}", manifest_path.display());
+        log::debug!("No changes: {}", display_path(manifest_path));
     }
 
-    Ok(())
+    Ok(processor.replacements)
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -106,21 +572,39 @@ enum DependencySection {
     DevDependencies,
     BuildDependencies,
     TargetDependencies(String),
+    /// `[patch.<registry>]`, with the registry name or URL.
+    Patch(String),
+    Replace,
 }
 
 struct TomlProcessor<'a> {
     lines: Vec<&'a str>,
     old_name: &'a str,
     new_name: &'a str,
-    new_path: Option<&'a str>,
+    new_paths: Option<&'a NewPaths>,
     had_trailing_newline: bool,
+    /// Dependency kinds to update; empty for all.
+    kinds: &'a [DepKind],
+    /// Process `[patch]` and `[replace]` instead of dependency sections.
+    patches: bool,
+    /// Lines of entries naming another crate of the same name, left as is.
+    foreign_lines: HashSet<usize>,
 
     // State
     current_section: Option<DependencySection>,
+    section_label: String,
     in_target_dep: bool,
     in_package_dep: bool,
     brace_depth: i32,
     multiline_table_dep: Option<String>,
+    in_package_table: bool,
+    /// The `[dependencies.name]` table or dotted keys of the section inherit
+    /// from the workspace.
+    inherited_section: bool,
+    /// The current inline entry inherits from the workspace.
+    inherited_dep: bool,
+
+    replacements: DependencyReplacements,
 }
 
 impl<'a> TomlProcessor<'a> {
@@ -128,116 +612,244 @@ impl<'a> TomlProcessor<'a> {
         content: &'a str,
         old_name: &'a str,
         new_name: &'a str,
-        new_path: Option<&'a str>,
+        new_paths: Option<&'a NewPaths>,
     ) -> Self {
         Self {
             lines: content.lines().collect(),
             old_name,
             new_name,
-            new_path,
+            new_paths,
             had_trailing_newline: content.ends_with('\n'),
+            kinds: &[],
+            patches: false,
+            foreign_lines: HashSet::new(),
             current_section: None,
+            section_label: String::new(),
             in_target_dep: false,
             in_package_dep: false,
             brace_depth: 0,
             multiline_table_dep: None,
+            in_package_table: false,
+            inherited_section: false,
+            inherited_dep: false,
+            replacements: DependencyReplacements::default(),
         }
     }
 
     fn process(&mut self, name_changed: bool, path_changed: bool) -> Result<String> {
-        let mut result_lines = Vec::new();
-        let search_dep = self.old_name;
         let lines_copy: Vec<String> = self.lines.iter().map(|s| s.to_string()).collect();
+        let mut result_lines = Vec::with_capacity(lines_copy.len());
 
-        for line in &lines_copy {
-            let mut modified_line = line.clone();
-            let trimmed = line.trim();
-
-            self.update_section(trimmed);
-
-            if self.is_section_header(trimmed) {
-                if name_changed {
-                    modified_line = self.rename_section_header(line)?;
-                }
-                self.reset_state();
-                result_lines.push(modified_line);
-                continue;
+        for (index, line) in lines_copy.iter().enumerate() {
+            let mut modified_line = self.process_line(index, line, name_changed, path_changed)?;
+            if self.foreign_lines.contains(&index) {
+                modified_line = line.clone();
             }
 
-            // Standalone path line in multi-line table
-            if self.brace_depth == 0
-                && trimmed.starts_with("path")
-                && self.is_in_target_context(search_dep)
-                && path_changed
-            {
-                modified_line = self.update_standalone_path(line)?;
-                result_lines.push(modified_line);
-                continue;
+            if modified_line != *line {
+                self.replacements.record(&self.section_label);
             }
+            result_lines.push(modified_line);
+        }
 
-            // Dependency declaration
-            if self.is_dependency_line(trimmed, search_dep) {
-                self.start_dependency_tracking(line, search_dep);
+        let mut result = result_lines.join("\n");
 
-                if name_changed {
-                    modified_line = self.rename_dependency_key(line)?;
-                }
-                if path_changed {
-                    modified_line = self.update_inline_path(&modified_line)?;
+        // `lines()` drops the final newline only, so trailing blank lines survive the join
+        if self.had_trailing_newline {
+            result.push('\n');
+        }
+
+        Ok(result)
+    }
+
+    fn process_line(
+        &mut self,
+        index: usize,
+        line: &str,
+        name_changed: bool,
+        path_changed: bool,
+    ) -> Result<String> {
+        let search_dep = self.old_name;
+        let trimmed = line.trim();
+
+        if self.is_section_header(trimmed) {
+            self.update_section(index, trimmed);
+            self.reset_state();
+            if self.section_excluded() {
+                return Ok(line.to_string());
+            }
+            if name_changed {
+                let renamed = self.rename_section_header(line)?;
+                if renamed != line && self.entry_has_field(index, false, is_artifact_field) {
+                    self.replacements.artifact_key = true;
                 }
+                return Ok(renamed);
+            }
+            return Ok(line.to_string());
+        }
 
-                result_lines.push(modified_line);
-                continue;
+        if self.section_excluded() {
+            return Ok(line.to_string());
+        }
+
+        // `[replace]` keys are package ID specs: "old-name:0.1.0"
+        if self.current_section == Some(DependencySection::Replace)
+            && self.brace_depth == 0
+            && self.is_replace_key(trimmed)
+        {
+            self.in_target_dep = true;
+            self.in_package_dep = false;
+            self.update_brace_depth(line);
+
+            let mut modified_line = line.to_string();
+            if name_changed {
+                modified_line = self.rename_replace_spec(line)?;
             }
+            if path_changed {
+                modified_line = self.update_inline_path(&modified_line)?;
+            }
+            return Ok(modified_line);
+        }
 
-            // Continuation of multi-line inline table
-            if self.brace_depth > 0 {
-                if path_changed {
-                    modified_line = self.update_inline_path(line)?;
+        // Standalone path line in multi-line table
+        if self.brace_depth == 0
+            && self.is_path_key(trimmed)
+            && self.is_in_target_context(search_dep)
+            && path_changed
+            && !self.inherited_section
+        {
+            return self.update_standalone_path(line);
+        }
+
+        // Dependency declaration
+        if self.is_dependency_line(trimmed, search_dep) {
+            self.inherited_dep =
+                self.inherited_section || self.entry_has_field(index, true, is_workspace_field);
+            self.start_dependency_tracking(line, search_dep);
+
+            let mut modified_line = line.to_string();
+            if name_changed {
+                modified_line = self.rename_dependency_key(line)?;
+                if self.entry_has_field(index, true, is_artifact_field) {
+                    self.replacements.artifact_key = true;
                 }
-                self.update_brace_depth(line);
-                result_lines.push(modified_line);
-                continue;
             }
+            if path_changed && !self.inherited_dep {
+                modified_line = self.update_inline_path(&modified_line)?;
+            }
+            return Ok(modified_line);
+        }
 
-            // Lines with package field
+        // Continuation of multi-line inline table
+        if self.brace_depth > 0 {
+            let mut modified_line = line.to_string();
             if name_changed && self.has_package_field(line) {
-                self.start_dependency_tracking(line, search_dep);
                 modified_line = self.rename_package_field(line)?;
+            }
+            if path_changed && (self.in_target_dep || self.in_package_dep) && !self.inherited_dep {
+                modified_line = self.update_inline_path(&modified_line)?;
+            }
+            self.update_brace_depth(line);
+            return Ok(modified_line);
+        }
 
-                if path_changed && self.has_path_field(line) {
-                    modified_line = self.update_inline_path(&modified_line)?;
-                }
+        // Lines with package field
+        if name_changed && self.has_package_field(line) {
+            self.start_dependency_tracking(line, search_dep);
+            let mut modified_line = self.rename_package_field(line)?;
 
-                result_lines.push(modified_line);
-                continue;
+            if path_changed && self.has_path_field(line) {
+                modified_line = self.update_inline_path(&modified_line)?;
             }
 
-            result_lines.push(modified_line);
+            return Ok(modified_line);
         }
 
-        let mut result = result_lines.join("\n");
-
-        if self.had_trailing_newline && !result.ends_with('\n') {
-            result.push('\n');
+        // Start of another entry; a multi-line alias may name the package
+        // after its path, so look ahead to the closing brace
+        self.update_brace_depth(line);
+        if self.brace_depth > 0 {
+            self.in_package_dep =
+                self.entry_has_field(index, true, |line| self.has_package_field(line));
         }
 
-        Ok(result)
+        Ok(line.to_string())
     }
 
-    fn update_section(&mut self, trimmed: &str) {
-        if !trimmed.starts_with('[') {
-            return;
+    /// Returns `true` if the current section is a dependency table of a kind
+    /// that is not selected, or a patch section unless processing patches
+    /// (and anything else if so).
+    fn section_excluded(&self) -> bool {
+        let patch = matches!(
+            self.current_section,
+            Some(DependencySection::Patch(_) | DependencySection::Replace)
+        );
+        if self.patches || patch {
+            return !(self.patches && patch);
         }
 
-        if let Some(section) = self.parse_section(trimmed) {
-            self.current_section = Some(section);
-            self.multiline_table_dep = None;
+        !self.kinds.is_empty()
+            && self.current_section.is_some()
+            && DepKind::of_section(&self.section_label)
+                .is_some_and(|kind| !self.kinds.contains(&kind))
+    }
+
+    fn update_section(&mut self, index: usize, trimmed: &str) {
+        let header = strip_comment(trimmed).trim();
+
+        self.current_section = self.parse_section(header);
+        self.section_label = section_label(header);
+        self.multiline_table_dep = None;
+        self.in_package_table = false;
+        self.inherited_section = false;
+
+        if self.current_section.is_some()
+            && let Some(dep_name) = self.extract_dep_from_section(header)
+        {
+            let dep_name = if self.current_section == Some(DependencySection::Replace) {
+                spec_name(&dep_name).to_string()
+            } else {
+                dep_name
+            };
+            self.in_package_table =
+                self.entry_has_field(index, false, |line| self.has_package_field(line));
+            self.inherited_section = self.entry_has_field(index, false, is_workspace_field);
+            self.multiline_table_dep = Some(dep_name);
+        } else if self.current_section.is_some() {
+            // Dotted keys: old-name.workspace = true
+            let dotted = format!(
+                r"^\s*{}\s*\.\s*workspace\s*=\s*true\b",
+                regex::escape(self.old_name)
+            );
+            if let Ok(re) = Regex::new(&dotted) {
+                self.inherited_section =
+                    self.entry_has_field(index, false, |line| re.is_match(line));
+            }
+        }
+    }
 
-            if let Some(dep_name) = self.extract_dep_from_section(trimmed) {
-                self.multiline_table_dep = Some(dep_name);
+    /// Returns `true` if a line of the entry starting at line `start`
+    /// matches `field`.
+    ///
+    /// The entry is a (possibly multi-line) inline table if `inline`,
+    /// otherwise a `[dependencies.name]` table.
+    fn entry_has_field(&self, start: usize, inline: bool, field: impl Fn(&str) -> bool) -> bool {
+        let mut depth = 0;
+
+        for (offset, line) in self.lines[start..].iter().enumerate() {
+            if !inline && offset > 0 && self.is_section_header(line.trim()) {
+                return false;
+            }
+            if field(line) {
+                return true;
+            }
+            depth += count_braces(line);
+            if inline && depth <= 0 {
+                return false;
             }
         }
+
+        false
     }
 
     fn parse_section(&self, header: &str) -> Option<DependencySection> {
@@ -257,6 +869,14 @@ impl<'a> TomlProcessor<'a> {
             return Some(DependencySection::TargetDependencies(target));
         }
 
+        if header == "[replace]" || header.starts_with("[replace.") {
+            return Some(DependencySection::Replace);
+        }
+        let patch = Regex::new(r#"^\[patch\.('[^']*'|"[^"]*"|[^.\]]+)"#).ok()?;
+        if let Some(caps) = patch.captures(header) {
+            return Some(DependencySection::Patch(caps[1].to_string()));
+        }
+
         None
     }
 
@@ -279,9 +899,10 @@ impl<'a> TomlProcessor<'a> {
     }
 
     fn extract_dep_from_section(&self, header: &str) -> Option<String> {
-        let pattern =
-            Regex::new(r"\[(?:dependencies|dev-dependencies|build-dependencies)\.([^\]]+)\]")
-                .ok()?;
+        let pattern = Regex::new(
+            r#"^\[(?:(?:target\.(?:'[^']*'|"[^"]*"|[^.\]]+)\.)?(?:dev-|build-)?dependencies|patch\.(?:'[^']*'|"[^"]*"|[^.\]]+)|replace)\.([^\]]+)\]"#,
+        )
+        .ok()?;
         pattern
             .captures(header)
             .and_then(|caps| caps.get(1))
@@ -289,18 +910,20 @@ impl<'a> TomlProcessor<'a> {
     }
 
     fn is_section_header(&self, trimmed: &str) -> bool {
-        trimmed.starts_with('[') && trimmed.ends_with(']')
+        let header = strip_comment(trimmed).trim_end();
+        header.starts_with('[') && header.ends_with(']')
     }
 
     fn reset_state(&mut self) {
         self.in_target_dep = false;
         self.in_package_dep = false;
+        self.inherited_dep = false;
         self.brace_depth = 0;
     }
 
     fn is_in_target_context(&self, target_dep: &str) -> bool {
         if let Some(ref dep) = self.multiline_table_dep {
-            return dep == target_dep;
+            return dep == target_dep || self.in_package_table;
         }
         self.in_target_dep || self.in_package_dep
     }
@@ -338,8 +961,7 @@ impl<'a> TomlProcessor<'a> {
     }
 
     fn update_brace_depth(&mut self, line: &str) {
-        self.brace_depth += line.matches('{').count() as i32;
-        self.brace_depth -= line.matches('}').count() as i32;
+        self.brace_depth += count_braces(line);
 
         if self.brace_depth == 0 {
             self.in_target_dep = false;
@@ -354,6 +976,19 @@ impl<'a> TomlProcessor<'a> {
             .unwrap_or(false)
     }
 
+    fn is_replace_key(&self, trimmed: &str) -> bool {
+        Regex::new(r#"^(["'])([^"']+)["']\s*="#)
+            .ok()
+            .and_then(|re| re.captures(trimmed))
+            .is_some_and(|caps| spec_name(&caps[2]) == self.old_name)
+    }
+
+    fn is_path_key(&self, trimmed: &str) -> bool {
+        Regex::new(r"^path\s*=")
+            .map(|re| re.is_match(trimmed))
+            .unwrap_or(false)
+    }
+
     fn has_path_field(&self, line: &str) -> bool {
         Regex::new(r#"\bpath\s*=\s*["']"#)
             .map(|re| re.is_match(line))
@@ -363,6 +998,20 @@ impl<'a> TomlProcessor<'a> {
     fn rename_section_header(&self, line: &str) -> Result<String> {
         let sections = ["dependencies", "dev-dependencies", "build-dependencies"];
 
+        let patch = format!(
+            r#"^(\s*\[patch\.(?:'[^']*'|"[^"]*"|[^.\]]+)\.){}]"#,
+            regex::escape(self.old_name)
+        );
+        let patch = Regex::new(&patch)?;
+        if patch.is_match(line) {
+            return Ok(patch
+                .replace(line, format!("${{1}}{}]", self.new_name))
+                .to_string());
+        }
+        if line.trim_start().starts_with("[replace.") {
+            return self.rename_replace_spec(line);
+        }
+
         for section in sections {
             let pattern = format!(
                 r"^(\s*\[(?:target\.[^]]+\.)?{}\.){}]",
@@ -383,13 +1032,13 @@ impl<'a> TomlProcessor<'a> {
     }
 
     fn rename_dependency_key(&self, line: &str) -> Result<String> {
-        // old-name.workspace = true
-        let ws_pattern = format!(r"^(\s*){}\.workspace\s*=", regex::escape(self.old_name));
-        if let Ok(re) = Regex::new(&ws_pattern)
+        // Dotted keys: old-name.workspace = true, old-name.path = "..."
+        let dotted_pattern = format!(r"^(\s*){}(\s*\.)", regex::escape(self.old_name));
+        if let Ok(re) = Regex::new(&dotted_pattern)
             && re.is_match(line)
         {
             return Ok(re
-                .replace(line, format!("${{1}}{}.workspace =", self.new_name))
+                .replace(line, format!("${{1}}{}${{2}}", self.new_name))
                 .to_string());
         }
 
@@ -406,6 +1055,18 @@ impl<'a> TomlProcessor<'a> {
         Ok(line.to_string())
     }
 
+    /// Renames the package in a quoted `[replace]` spec, keeping its source
+    /// URL and version: `"old-name:0.1.0"` → `"new-name:0.1.0"`.
+    fn rename_replace_spec(&self, line: &str) -> Result<String> {
+        let pattern = format!(
+            r#"(["'](?:[^"'#]*#)?){}([:@][^"']*)?(["'])"#,
+            regex::escape(self.old_name)
+        );
+        Ok(Regex::new(&pattern)?
+            .replace(line, format!("${{1}}{}${{2}}${{3}}", self.new_name))
+            .to_string())
+    }
+
     fn rename_package_field(&self, line: &str) -> Result<String> {
         // Double quotes
         let double = format!(r#"(\bpackage\s*=\s*"){}(")"#, regex::escape(self.old_name));
@@ -431,68 +1092,313 @@ impl<'a> TomlProcessor<'a> {
     }
 
     fn update_standalone_path(&self, line: &str) -> Result<String> {
-        if let Some(new_path) = self.new_path {
-            let pattern = r#"^(\s*path\s*=\s*)["'][^"']*["']"#;
-            if let Ok(re) = Regex::new(pattern) {
-                return Ok(re
-                    .replace(line, format!(r#"${{1}}"{}""#, new_path))
-                    .to_string());
-            }
-        }
-        Ok(line.to_string())
+        self.replace_path(line, r#"^(\s*path\s*=\s*)["']([^"']*)["']"#)
     }
 
     fn update_inline_path(&self, line: &str) -> Result<String> {
-        if let Some(new_path) = self.new_path {
-            if line.contains(&format!(r#"path = "{}""#, new_path)) {
-                return Ok(line.to_string());
-            }
+        self.replace_path(line, r#"(\bpath\s*=\s*)["']([^"']*)["']"#)
+    }
 
-            let pattern = r#"(\bpath\s*=\s*)["'][^"']*["']"#;
-            if let Ok(re) = Regex::new(pattern)
-                && re.is_match(line)
-            {
-                return Ok(re
-                    .replace(line, format!(r#"${{1}}"{}""#, new_path))
-                    .to_string());
-            }
+    /// Replaces the first `path` value matched by `pattern` (prefix in group
+    /// 1, value in group 2) with the new path in the value's style.
+    fn replace_path(&self, line: &str, pattern: &str) -> Result<String> {
+        let Some(new_paths) = self.new_paths else {
+            return Ok(line.to_string());
+        };
+        let re = Regex::new(pattern)?;
+        let Some(caps) = re.captures(line) else {
+            return Ok(line.to_string());
+        };
+
+        let new_path = new_paths.replacement(&caps[2]);
+        if &caps[2] == new_path {
+            return Ok(line.to_string());
         }
-        Ok(line.to_string())
+        Ok(re
+            .replace(line, |caps: &regex::Captures| {
+                format!(r#"{}"{}""#, &caps[1], new_path)
+            })
+            .to_string())
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    use tempfile::TempDir;
-
-    #[test]
-    fn test_multiline_inline_table() {
-        let input = r#"[dependencies]
-my-crate = {
-    path = "../old-path",
-    features = ["feat1", "feat2"]
+/// Returns the 1-based lines of a manifest that declare a dependency on the
+/// package `name`: by key, as `package = "name"`, or as a
+/// `[dependencies.name]` table.
+///
+/// A `name` key or table with a `package` field depends on another crate and
+/// is skipped.
+pub fn dependency_lines(content: &str, name: &str) -> Result<Vec<usize>> {
+    let name = regex::escape(name);
+    let header = Regex::new(&format!(
+        r#"^\s*\[[^\]]*dependencies\.["']?{}["']?\s*\]"#,
+        name
+    ))?;
+    let key = Regex::new(&format!(r#"^\s*["']?{}["']?\s*(?:\.|=)"#, name))?;
+    let package = Regex::new(&format!(r#"\bpackage\s*=\s*["']{}["']"#, name))?;
+    let any_package = Regex::new(r"(?:^|[{,])\s*package\s*=")?;
+
+    let mut lines = Vec::new();
+    let mut in_dependencies = false;
+    // Index in `lines` of the current `[dependencies.name]` header
+    let mut table = None;
+    for (index, line) in content.lines().enumerate() {
+        let line = strip_comment(line);
+        if line.trim_start().starts_with('[') {
+            in_dependencies = line.contains("dependencies");
+            table = None;
+            if header.is_match(line) {
+                table = Some(lines.len());
+                lines.push(index + 1);
+            }
+        } else if !in_dependencies {
+            continue;
+        } else if any_package.is_match(line) && !package.is_match(line) {
+            if let Some(table) = table.take() {
+                lines.remove(table);
+            }
+        } else if key.is_match(line) || package.is_match(line) {
+            lines.push(index + 1);
+        }
+    }
+    Ok(lines)
 }
-"#;
-        let expected = r#"[dependencies]
-my-crate = {
-    path = "../new-path",
-    features = ["feat1", "feat2"]
+
+/// Returns `true` if `line` sets the `artifact` field of a dependency.
+fn is_artifact_field(line: &str) -> bool {
+    Regex::new(r"(?:^|[{,])\s*artifact\s*=")
+        .map(|re| re.is_match(strip_comment(line)))
+        .unwrap_or(false)
 }
-"#;
 
-        let temp = TempDir::new().unwrap();
-        let pkg_dir = temp.path().join("my-pkg");
-        fs::create_dir(&pkg_dir).unwrap();
-        let manifest = pkg_dir.join("Cargo.toml");
-        fs::write(&manifest, input).unwrap();
+/// Returns `true` if `line` sets `workspace = true`, inline or on its own.
+fn is_workspace_field(line: &str) -> bool {
+    Regex::new(r"(?:^|[{,])\s*workspace\s*=\s*true\b")
+        .map(|re| re.is_match(strip_comment(line)))
+        .unwrap_or(false)
+}
 
-        let new_dir = temp.path().join("new-path");
+/// Yields the characters of `line` outside quoted strings, with their offsets.
+fn unquoted_chars(line: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut quote = None;
+    let mut escaped = false;
 
-        let mut txn = Transaction::new(false);
-        update_dependent_manifest(
-            &manifest, "my-crate", "my-crate", &new_dir, true, false, &mut txn,
+    line.char_indices().filter(move |&(_, c)| match quote {
+        Some('"') if escaped => {
+            escaped = false;
+            false
+        }
+        Some('"') if c == '\\' => {
+            escaped = true;
+            false
+        }
+        Some(q) => {
+            if c == q {
+                quote = None;
+            }
+            false
+        }
+        None if c == '"' || c == '\'' => {
+            quote = Some(c);
+            false
+        }
+        None => true,
+    })
+}
+
+/// Strips a trailing `#` comment, ignoring `#` inside strings.
+fn strip_comment(line: &str) -> &str {
+    match unquoted_chars(line).find(|&(_, c)| c == '#') {
+        Some((i, _)) => &line[..i],
+        None => line,
+    }
+}
+
+/// Returns opened minus closed braces outside strings and comments.
+fn count_braces(line: &str) -> i32 {
+    unquoted_chars(strip_comment(line))
+        .map(|(_, c)| match c {
+            '{' => 1,
+            '}' => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Returns the 0-based lines of the entries on `update.old_name` that name
+/// another crate of the same name: their `path` resolves elsewhere than
+/// `update.old_dir`, or they come from `git`.
+///
+/// With `patches`, the `[patch]` and `[replace]` entries are checked,
+/// otherwise the dependency tables. None are foreign with
+/// `update.other_sources`.
+fn foreign_entry_lines(
+    content: &str,
+    manifest_dir: &Path,
+    update: &ManifestUpdate,
+    patches: bool,
+) -> HashSet<usize> {
+    let mut lines = HashSet::new();
+    if update.other_sources {
+        return lines;
+    }
+    let Ok(doc) = toml_edit::Document::parse(content) else {
+        return lines;
+    };
+    let root = doc.as_item();
+
+    // (table, keys are `[replace]` specs)
+    let mut tables: Vec<(&Item, bool)> = Vec::new();
+    if patches {
+        if let Some(patch) = root.get("patch").and_then(Item::as_table_like) {
+            tables.extend(patch.iter().map(|(_, table)| (table, false)));
+        }
+        tables.extend(root.get("replace").map(|table| (table, true)));
+    } else {
+        let targets = root
+            .get("target")
+            .and_then(Item::as_table_like)
+            .into_iter()
+            .flat_map(|targets| targets.iter().map(|(_, target)| target));
+        for parent in std::iter::once(root).chain(targets) {
+            for name in DEPENDENCY_TABLES {
+                tables.extend(parent.get(name).map(|table| (table, false)));
+            }
+        }
+    }
+
+    let line_of = |offset: usize| content[..offset.min(content.len())].matches('\n').count();
+    for (table, replace) in tables {
+        let Some(table) = table.as_table_like() else {
+            continue;
+        };
+        for (key, item) in table.iter() {
+            let name = if replace {
+                spec_name(key)
+            } else {
+                item.get("package").and_then(Item::as_str).unwrap_or(key)
+            };
+            if name != update.old_name || !names_other_crate(item, manifest_dir, update.old_dir) {
+                continue;
+            }
+
+            let mut spans: Vec<Range<usize>> =
+                table.key(key).and_then(Key::span).into_iter().collect();
+            collect_spans(item, &mut spans);
+            for span in spans {
+                lines.extend(line_of(span.start)..=line_of(span.end.max(span.start + 1) - 1));
+            }
+        }
+    }
+
+    lines
+}
+
+/// Dependency table names, including the legacy underscore spellings.
+const DEPENDENCY_TABLES: [&str; 5] = [
+    "dependencies",
+    "dev-dependencies",
+    "dev_dependencies",
+    "build-dependencies",
+    "build_dependencies",
+];
+
+/// Returns `true` if a dependency entry is a `git` dependency or a path
+/// dependency on another directory than `dir`. Inherited entries are
+/// resolved by the workspace root, whatever `path` they carry.
+fn names_other_crate(item: &Item, manifest_dir: &Path, dir: &Path) -> bool {
+    if item.get("workspace").is_some() {
+        return false;
+    }
+    if item.get("git").is_some() {
+        return true;
+    }
+    let Some(path) = item.get("path").and_then(Item::as_str) else {
+        return false;
+    };
+    let resolved = manifest_dir.join(path);
+
+    match (std::fs::canonicalize(&resolved), std::fs::canonicalize(dir)) {
+        (Ok(resolved), Ok(dir)) => resolved != dir,
+        _ => normalize_path(&resolved) != normalize_path(dir),
+    }
+}
+
+/// Collects the spans of `item`, its keys and nested values.
+fn collect_spans(item: &Item, spans: &mut Vec<Range<usize>>) {
+    spans.extend(item.span());
+    if let Some(table) = item.as_table_like() {
+        for (key, value) in table.iter() {
+            spans.extend(table.key(key).and_then(Key::span));
+            collect_spans(value, spans);
+        }
+    }
+}
+
+/// Returns the package name of a `[replace]` spec like
+/// `"https://github.com/rust-lang/crates.io-index#old-name:0.1.0"`.
+fn spec_name(spec: &str) -> &str {
+    let spec = spec.trim_matches(|c| c == '"' || c == '\'');
+    let spec = spec.rsplit_once('#').map_or(spec, |(_, rest)| rest);
+    spec.split([':', '@']).next().unwrap_or(spec)
+}
+
+/// Names a section for [`DependencyReplacements`]: the table header without
+/// brackets and without a trailing dependency key.
+fn section_label(header: &str) -> String {
+    let inner = header.trim_start_matches('[').trim_end_matches(']').trim();
+    let pattern = Regex::new(
+        r#"^((?:target\.(?:'[^']*'|"[^"]*"|[^.]+)\.)?(?:dev-|build-)?dependencies|patch\.(?:'[^']*'|"[^"]*"|[^.]+)|replace)(?:\.|$)"#,
+    )
+    .expect("valid section pattern");
+
+    pattern
+        .captures(inner)
+        .and_then(|caps| caps.get(1))
+        .map_or(inner, |m| m.as_str())
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_multiline_inline_table() {
+        let input = r#"[dependencies]
+my-crate = {
+    path = "../old-path",
+    features = ["feat1", "feat2"]
+}
+"#;
+        let expected = r#"[dependencies]
+my-crate = {
+    path = "../new-path",
+    features = ["feat1", "feat2"]
+}
+"#;
+
+        let temp = TempDir::new().unwrap();
+        let pkg_dir = temp.path().join("my-pkg");
+        fs::create_dir(&pkg_dir).unwrap();
+        let manifest = pkg_dir.join("Cargo.toml");
+        fs::write(&manifest, input).unwrap();
+
+        let new_dir = temp.path().join("new-path");
+
+        let mut txn = Transaction::new(false);
+        update_dependent_manifest(
+            &manifest,
+            &ManifestUpdate::new(
+                "my-crate",
+                "my-crate",
+                &temp.path().join("old-path"),
+                &new_dir,
+                temp.path(),
+            ),
+            &mut txn,
         )
         .unwrap();
 
@@ -523,11 +1429,13 @@ other = "1.0"
         let mut txn = Transaction::new(false);
         update_dependent_manifest(
             &manifest,
-            "old-crate",
-            "new-crate",
-            &new_dir,
-            true,
-            true,
+            &ManifestUpdate::new(
+                "old-crate",
+                "new-crate",
+                &temp.path().join("old-path"),
+                &new_dir,
+                temp.path(),
+            ),
             &mut txn,
         )
         .unwrap();
@@ -563,11 +1471,13 @@ other = "1.0"
         let mut txn = Transaction::new(false);
         update_dependent_manifest(
             &manifest,
-            "old-crate",
-            "new-crate",
-            &new_dir,
-            true,
-            true,
+            &ManifestUpdate::new(
+                "old-crate",
+                "new-crate",
+                &temp.path().join("old-path"),
+                &new_dir,
+                temp.path(),
+            ),
             &mut txn,
         )
         .unwrap();
@@ -597,11 +1507,13 @@ new-crate = { path = "../new-path", version = "1.0" }
         let mut txn = Transaction::new(false);
         update_dependent_manifest(
             &manifest,
-            "old-crate",
-            "new-crate",
-            &new_dir,
-            true,
-            true,
+            &ManifestUpdate::new(
+                "old-crate",
+                "new-crate",
+                &temp.path().join("old-path"),
+                &new_dir,
+                temp.path(),
+            ),
             &mut txn,
         )
         .unwrap();
@@ -631,11 +1543,13 @@ new-crate = { path = "../new-path", optional = true }
         let mut txn = Transaction::new(false);
         update_dependent_manifest(
             &manifest,
-            "old-crate",
-            "new-crate",
-            &new_dir,
-            true,
-            true,
+            &ManifestUpdate::new(
+                "old-crate",
+                "new-crate",
+                &temp.path().join("old-path"),
+                &new_dir,
+                temp.path(),
+            ),
             &mut txn,
         )
         .unwrap();
@@ -667,11 +1581,13 @@ alias2 = { package = "new-crate", version = "1.0" }
         let mut txn = Transaction::new(false);
         update_dependent_manifest(
             &manifest,
-            "old-crate",
-            "new-crate",
-            &new_dir,
-            true,
-            true,
+            &ManifestUpdate::new(
+                "old-crate",
+                "new-crate",
+                &temp.path().join("old-path"),
+                &new_dir,
+                temp.path(),
+            ),
             &mut txn,
         )
         .unwrap();
@@ -697,11 +1613,155 @@ new-crate = { workspace = true, features = ["extra"] }
         let mut txn = Transaction::new(false);
         update_dependent_manifest(
             &manifest,
-            "old-crate",
-            "new-crate",
-            temp.path(), // path doesn't matter for workspace deps
-            false,       // don't change path
-            true,        // change name
+            &ManifestUpdate::new(
+                "old-crate",
+                "new-crate",
+                temp.path(),
+                temp.path(),
+                temp.path(),
+            ),
+            &mut txn,
+        )
+        .unwrap();
+
+        txn.commit().unwrap();
+        let result = fs::read_to_string(&manifest).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_workspace_inherited_entries_keep_paths() {
+        let input = r#"[dependencies]
+old-crate = { workspace = true, path = "../stale" }
+alias = { package = "old-crate", path = "../old-crate" }
+
+[dev-dependencies.old-crate]
+workspace = true
+path = "../stale"
+
+[build-dependencies]
+old-crate.workspace = true
+old-crate.path = "../stale"
+
+[target.'cfg(unix)'.dependencies]
+old-crate = {
+    path = "../stale",
+    workspace = true,
+}
+"#;
+        let expected = r#"[dependencies]
+new-crate = { workspace = true, path = "../stale" }
+alias = { package = "new-crate", path = "../libs/new-crate" }
+
+[dev-dependencies.new-crate]
+workspace = true
+path = "../stale"
+
+[build-dependencies]
+new-crate.workspace = true
+new-crate.path = "../stale"
+
+[target.'cfg(unix)'.dependencies]
+new-crate = {
+    path = "../stale",
+    workspace = true,
+}
+"#;
+
+        let temp = TempDir::new().unwrap();
+        let manifest = temp.path().join("app/Cargo.toml");
+        fs::create_dir_all(manifest.parent().unwrap()).unwrap();
+        fs::write(&manifest, input).unwrap();
+
+        let mut txn = Transaction::new(false);
+        update_dependent_manifest(
+            &manifest,
+            &ManifestUpdate::new(
+                "old-crate",
+                "new-crate",
+                &temp.path().join("old-crate"),
+                &temp.path().join("libs/new-crate"),
+                temp.path(),
+            ),
+            &mut txn,
+        )
+        .unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(fs::read_to_string(&manifest).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_occurrences_across_sections() {
+        let input = r#"[dependencies]
+old-crate = {
+    path = "../old-path",
+    version = "0.1.0",
+}
+
+[dev-dependencies.old-crate] # {mock feature
+path = "../old-path"
+
+[lib]
+path = "src/lib.rs"
+
+[target.'cfg(unix)'.dependencies]
+old-crate.path = "../old-path"
+
+[target.'cfg(windows)'.dependencies]
+alias = {
+    path = "../old-path",
+    package = "old-crate",
+}
+
+[build-dependencies]
+old-crate = { path = "../old-path" } # see {docs
+other = { path = "../other" }
+"#;
+        let expected = r#"[dependencies]
+new-crate = {
+    path = "../new-path",
+    version = "0.1.0",
+}
+
+[dev-dependencies.new-crate] # {mock feature
+path = "../new-path"
+
+[lib]
+path = "src/lib.rs"
+
+[target.'cfg(unix)'.dependencies]
+new-crate.path = "../new-path"
+
+[target.'cfg(windows)'.dependencies]
+alias = {
+    path = "../new-path",
+    package = "new-crate",
+}
+
+[build-dependencies]
+new-crate = { path = "../new-path" } # see {docs
+other = { path = "../other" }
+"#;
+
+        let temp = TempDir::new().unwrap();
+        let pkg_dir = temp.path().join("my-pkg");
+        fs::create_dir(&pkg_dir).unwrap();
+        let manifest = pkg_dir.join("Cargo.toml");
+        fs::write(&manifest, input).unwrap();
+
+        let new_dir = temp.path().join("new-path");
+
+        let mut txn = Transaction::new(false);
+        let replacements = update_dependent_manifest(
+            &manifest,
+            &ManifestUpdate::new(
+                "old-crate",
+                "new-crate",
+                &temp.path().join("old-path"),
+                &new_dir,
+                temp.path(),
+            ),
             &mut txn,
         )
         .unwrap();
@@ -709,5 +1769,721 @@ new-crate = { workspace = true, features = ["extra"] }
         txn.commit().unwrap();
         let result = fs::read_to_string(&manifest).unwrap();
         assert_eq!(result, expected);
+
+        assert_eq!(
+            replacements.sections(),
+            [
+                ("dependencies".to_string(), 2),
+                ("dev-dependencies".to_string(), 2),
+                ("target.'cfg(unix)'.dependencies".to_string(), 1),
+                ("target.'cfg(windows)'.dependencies".to_string(), 2),
+                ("build-dependencies".to_string(), 1),
+            ]
+        );
+        assert_eq!(replacements.total(), 8);
+    }
+
+    #[test]
+    fn test_same_name_elsewhere_is_kept() {
+        let input = r#"[dependencies]
+old-crate = { path = "../old-crate" }
+vendored = { package = "old-crate", path = "../vendor/old-crate" }
+forked = { package = "old-crate", git = "https://github.com/org/fork" }
+
+[target.'cfg(unix)'.dev-dependencies.unix]
+package = "old-crate"
+path = "../vendor/old-crate"
+"#;
+        let expected = r#"[dependencies]
+new-crate = { path = "../old-crate" }
+vendored = { package = "old-crate", path = "../vendor/old-crate" }
+forked = { package = "old-crate", git = "https://github.com/org/fork" }
+
+[target.'cfg(unix)'.dev-dependencies.unix]
+package = "old-crate"
+path = "../vendor/old-crate"
+"#;
+
+        let temp = TempDir::new().unwrap();
+        let pkg_dir = temp.path().join("my-pkg");
+        fs::create_dir(&pkg_dir).unwrap();
+        let manifest = pkg_dir.join("Cargo.toml");
+        fs::write(&manifest, input).unwrap();
+        let old_dir = temp.path().join("old-crate");
+
+        let mut txn = Transaction::new(false);
+        update_dependent_manifest(
+            &manifest,
+            &ManifestUpdate::new("old-crate", "new-crate", &old_dir, &old_dir, temp.path()),
+            &mut txn,
+        )
+        .unwrap();
+        txn.commit().unwrap();
+        assert_eq!(fs::read_to_string(&manifest).unwrap(), expected);
+
+        // Unless every crate of that name is the renamed one
+        let update = ManifestUpdate {
+            other_sources: true,
+            ..ManifestUpdate::new("new-crate", "newer", &old_dir, &old_dir, temp.path())
+        };
+        fs::write(&manifest, input.replace("old-crate", "new-crate")).unwrap();
+        let mut txn = Transaction::new(false);
+        update_dependent_manifest(&manifest, &update, &mut txn).unwrap();
+        txn.commit().unwrap();
+        let result = fs::read_to_string(&manifest).unwrap();
+        assert_eq!(
+            result.matches("package = \"newer\"").count(),
+            3,
+            "{}",
+            result
+        );
+    }
+
+    #[test]
+    fn test_alias_table_with_path_before_package() {
+        let input = r#"[dev-dependencies.alias]
+path = "../old-path"
+package = "old-crate"
+
+[dependencies.other]
+path = "../other"
+"#;
+        let expected = r#"[dev-dependencies.alias]
+path = "../new-path"
+package = "new-crate"
+
+[dependencies.other]
+path = "../other"
+"#;
+
+        let temp = TempDir::new().unwrap();
+        let pkg_dir = temp.path().join("my-pkg");
+        fs::create_dir(&pkg_dir).unwrap();
+        let manifest = pkg_dir.join("Cargo.toml");
+        fs::write(&manifest, input).unwrap();
+
+        let new_dir = temp.path().join("new-path");
+
+        let mut txn = Transaction::new(false);
+        update_dependent_manifest(
+            &manifest,
+            &ManifestUpdate::new(
+                "old-crate",
+                "new-crate",
+                &temp.path().join("old-path"),
+                &new_dir,
+                temp.path(),
+            ),
+            &mut txn,
+        )
+        .unwrap();
+
+        txn.commit().unwrap();
+        let result = fs::read_to_string(&manifest).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_artifact_dependencies() {
+        let input = r#"[build-dependencies]
+old-crate = { path = "../old-path", artifact = "bin", target = "x86_64-unknown-linux-gnu" }
+tool = { package = "old-crate", path = "../old-path", artifact = ["bin:old-crate", "cdylib"], lib = true }
+
+[dev-dependencies.old-crate]
+path = "../old-path"
+artifact = "bin:old-crate"
+target = "target"
+lib = true
+"#;
+        let expected = r#"[build-dependencies]
+new-crate = { path = "../new-path", artifact = "bin", target = "x86_64-unknown-linux-gnu" }
+tool = { package = "new-crate", path = "../new-path", artifact = ["bin:old-crate", "cdylib"], lib = true }
+
+[dev-dependencies.new-crate]
+path = "../new-path"
+artifact = "bin:old-crate"
+target = "target"
+lib = true
+"#;
+
+        let temp = TempDir::new().unwrap();
+        let pkg_dir = temp.path().join("my-pkg");
+        fs::create_dir(&pkg_dir).unwrap();
+        let manifest = pkg_dir.join("Cargo.toml");
+        fs::write(&manifest, input).unwrap();
+
+        let new_dir = temp.path().join("new-path");
+
+        let mut txn = Transaction::new(false);
+        let replacements = update_dependent_manifest(
+            &manifest,
+            &ManifestUpdate::new(
+                "old-crate",
+                "new-crate",
+                &temp.path().join("old-path"),
+                &new_dir,
+                temp.path(),
+            ),
+            &mut txn,
+        )
+        .unwrap();
+
+        txn.commit().unwrap();
+        let result = fs::read_to_string(&manifest).unwrap();
+        assert_eq!(result, expected);
+        assert!(replacements.renames_artifact_key());
+    }
+
+    #[test]
+    fn test_path_styles() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let manifest = root.join("crates/app/Cargo.toml");
+        fs::create_dir_all(manifest.parent().unwrap()).unwrap();
+        let new_dir = root.join("crates/core/new-crate");
+        let absolute = |dir: &str| root.join(dir).to_string_lossy().replace('\\', "/");
+
+        let input = format!(
+            "[dependencies]\nold-crate = {{ path = \"../old-crate\" }}\n\n\
+             [dev-dependencies]\nold-crate = {{ path = \"../../crates/old-crate\" }}\n\n\
+             [build-dependencies]\nold-crate = {{ path = \"{}\" }}\n",
+            absolute("crates/old-crate")
+        );
+        let expected = |paths: [&str; 3]| {
+            format!(
+                "[dependencies]\nold-crate = {{ path = \"{}\" }}\n\n\
+                 [dev-dependencies]\nold-crate = {{ path = \"{}\" }}\n\n\
+                 [build-dependencies]\nold-crate = {{ path = \"{}\" }}\n",
+                paths[0], paths[1], paths[2]
+            )
+        };
+        let from_root = "../../crates/core/new-crate";
+        let new_absolute = absolute("crates/core/new-crate");
+
+        for (style, paths) in [
+            (
+                PathStyle::Preserve,
+                ["../core/new-crate", from_root, new_absolute.as_str()],
+            ),
+            (
+                PathStyle::Relative,
+                [
+                    "../core/new-crate",
+                    "../core/new-crate",
+                    "../core/new-crate",
+                ],
+            ),
+            (PathStyle::FromRoot, [from_root, from_root, from_root]),
+        ] {
+            fs::write(&manifest, &input).unwrap();
+            let mut txn = Transaction::new(false);
+            update_dependent_manifest(
+                &manifest,
+                &ManifestUpdate {
+                    path_style: style,
+                    ..ManifestUpdate::new(
+                        "old-crate",
+                        "old-crate",
+                        &root.join("crates/old-crate"),
+                        &new_dir,
+                        root,
+                    )
+                },
+                &mut txn,
+            )
+            .unwrap();
+            txn.commit().unwrap();
+
+            assert_eq!(
+                fs::read_to_string(&manifest).unwrap(),
+                expected(paths),
+                "{:?}",
+                style
+            );
+        }
+    }
+
+    #[test]
+    fn test_paths_leaving_the_workspace_and_back() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("ws");
+        let manifest = root.join("crates/app/Cargo.toml");
+        fs::create_dir_all(manifest.parent().unwrap()).unwrap();
+        fs::write(
+            &manifest,
+            "[dependencies]\nold-crate = { path = \"../../../ws/crates/old-crate\" }\n\n\
+             [dev-dependencies]\nold-crate = { path = \"../../crates/x/../old-crate\" }\n\n\
+             [build-dependencies]\nold-crate = { path = \"./../old-crate\" }\n",
+        )
+        .unwrap();
+
+        let mut txn = Transaction::new(false);
+        update_dependent_manifest(
+            &manifest,
+            &ManifestUpdate::new(
+                "old-crate",
+                "old-crate",
+                &root.join("crates/old-crate"),
+                &root.join("crates/./core/../new-crate"),
+                &root,
+            ),
+            &mut txn,
+        )
+        .unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&manifest).unwrap(),
+            "[dependencies]\nold-crate = { path = \"../new-crate\" }\n\n\
+             [dev-dependencies]\nold-crate = { path = \"../../crates/new-crate\" }\n\n\
+             [build-dependencies]\nold-crate = { path = \"../new-crate\" }\n"
+        );
+    }
+
+    #[test]
+    fn test_dependency_lines() {
+        let content = r#"[package]
+name = "old-crate"
+
+[dependencies]
+old-crate = "1" # old-crate
+alias = { package = "old-crate", path = "../old" }
+other = "1"
+
+[features]
+old-crate = []
+
+[target.'cfg(unix)'.dev-dependencies.old-crate]
+path = "../old"
+
+[dev-dependencies]
+old-crate = { package = "other-crate", version = "1" }
+
+[build-dependencies.old-crate]
+version = "1"
+package = "other-crate"
+"#;
+
+        assert_eq!(dependency_lines(content, "old-crate").unwrap(), [5, 6, 12]);
+        assert_eq!(dependency_lines(content, "other-crate").unwrap(), [16, 20]);
+        assert!(dependency_lines(content, "third-crate").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_only_kinds() {
+        let input = r#"[dependencies]
+old-crate = { path = "../old-crate" }
+
+[dev-dependencies]
+old-crate = { path = "../old-crate", features = ["testing"] }
+
+[target.'cfg(unix)'.build-dependencies]
+old-crate = { path = "../old-crate" }
+"#;
+        let expected = r#"[dependencies]
+old-crate = { path = "../old-crate" }
+
+[dev-dependencies]
+new-crate = { path = "../old-crate", features = ["testing"] }
+
+[target.'cfg(unix)'.build-dependencies]
+new-crate = { path = "../old-crate" }
+"#;
+
+        let temp = TempDir::new().unwrap();
+        let manifest = temp.path().join("app/Cargo.toml");
+        fs::create_dir_all(manifest.parent().unwrap()).unwrap();
+        fs::write(&manifest, input).unwrap();
+
+        let mut txn = Transaction::new(false);
+        let replacements = update_dependent_manifest(
+            &manifest,
+            &ManifestUpdate {
+                kinds: &[DepKind::Dev, DepKind::Build],
+                ..ManifestUpdate::new(
+                    "old-crate",
+                    "new-crate",
+                    &temp.path().join("old-crate"),
+                    &temp.path().join("old-crate"),
+                    temp.path(),
+                )
+            },
+            &mut txn,
+        )
+        .unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(fs::read_to_string(&manifest).unwrap(), expected);
+        assert_eq!(replacements.total(), 2);
+
+        assert_eq!(DepKind::of_source(Path::new("build.rs")), DepKind::Build);
+        assert_eq!(DepKind::of_source(Path::new("tests/it.rs")), DepKind::Dev);
+        assert_eq!(
+            DepKind::of_source(Path::new("src/build.rs")),
+            DepKind::Normal
+        );
+    }
+
+    #[test]
+    fn test_patch_sections() {
+        let input = r#"[dependencies]
+old-crate = "0.1"
+
+[patch.crates-io]
+old-crate = { path = "crates/old-crate" }
+
+[patch.'https://github.com/org/repo'.old-crate]
+path = "crates/old-crate"
+
+[replace]
+"old-crate:0.1.0" = { path = "crates/old-crate" }
+
+[replace."https://github.com/rust-lang/crates.io-index#old-crate:0.1.0"]
+path = "crates/old-crate"
+
+[patch.'https://github.com/org/fork']
+old-crate = { path = "vendor/old-crate" } # a fork
+"#;
+        let expected = r#"[dependencies]
+old-crate = "0.1"
+
+[patch.crates-io]
+new-crate = { path = "crates/new-crate" }
+
+[patch.'https://github.com/org/repo'.new-crate]
+path = "crates/new-crate"
+
+[replace]
+"new-crate:0.1.0" = { path = "crates/new-crate" }
+
+[replace."https://github.com/rust-lang/crates.io-index#new-crate:0.1.0"]
+path = "crates/new-crate"
+
+[patch.'https://github.com/org/fork']
+old-crate = { path = "vendor/old-crate" } # a fork
+"#;
+
+        let temp = TempDir::new().unwrap();
+        let manifest = temp.path().join("Cargo.toml");
+        fs::write(&manifest, input).unwrap();
+
+        let mut txn = Transaction::new(false);
+        let replacements = update_patch_sections(
+            &manifest,
+            &ManifestUpdate::new(
+                "old-crate",
+                "new-crate",
+                &temp.path().join("crates/old-crate"),
+                &temp.path().join("crates/new-crate"),
+                temp.path(),
+            ),
+            &mut txn,
+        )
+        .unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(fs::read_to_string(&manifest).unwrap(), expected);
+        assert_eq!(
+            replacements.sections(),
+            &[
+                ("patch.crates-io".to_string(), 1),
+                ("patch.'https://github.com/org/repo'".to_string(), 2),
+                ("replace".to_string(), 3),
+            ]
+        );
+
+        // Dependency updates leave the patch sections alone
+        let mut txn = Transaction::new(false);
+        update_dependent_manifest(
+            &manifest,
+            &ManifestUpdate::new(
+                "new-crate",
+                "other-crate",
+                &temp.path().join("crates/new-crate"),
+                &temp.path().join("crates/new-crate"),
+                temp.path(),
+            ),
+            &mut txn,
+        )
+        .unwrap();
+        txn.commit().unwrap();
+        assert_eq!(fs::read_to_string(&manifest).unwrap(), expected);
+    }
+
+    /// Property tests: generated manifests with random whitespace, comments,
+    /// quote styles and section orders.
+    ///
+    /// Every generated line comes with the line expected after renaming
+    /// `my-crate` to `new-crate` and moving it from `../old-path` to
+    /// `../new-path`, so comparing the whole output checks both that only the
+    /// intended keys and paths change and that all other lines are kept
+    /// byte for byte.
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+        use proptest::sample::select;
+
+        const KINDS: [&str; 4] = [
+            "dependencies",
+            "dev-dependencies",
+            "build-dependencies",
+            "target.'cfg(unix)'.dependencies",
+        ];
+
+        /// Input lines and their expected output.
+        #[derive(Debug, Clone, Default)]
+        struct Lines {
+            input: Vec<String>,
+            expected: Vec<String>,
+        }
+
+        impl Lines {
+            fn kept(line: String) -> Self {
+                Self::changed(line.clone(), line)
+            }
+
+            fn changed(input: String, expected: String) -> Self {
+                Self {
+                    input: vec![input],
+                    expected: vec![expected],
+                }
+            }
+
+            fn concat(parts: impl IntoIterator<Item = Lines>) -> Self {
+                let mut lines = Self::default();
+                for part in parts {
+                    lines.input.extend(part.input);
+                    lines.expected.extend(part.expected);
+                }
+                lines
+            }
+        }
+
+        /// How a section declares `my-crate`.
+        #[derive(Debug, Clone, Copy)]
+        enum Form {
+            Absent,
+            Inline,
+            MultiLine,
+            Dotted,
+            Alias,
+            Table,
+        }
+
+        fn ws() -> impl Strategy<Value = &'static str> {
+            select(vec!["", " ", "  ", "\t"])
+        }
+
+        fn quote() -> impl Strategy<Value = char> {
+            select(vec!['"', '\''])
+        }
+
+        fn comment() -> impl Strategy<Value = String> {
+            prop_oneof![
+                Just(String::new()),
+                (
+                    ws(),
+                    select(vec!["# pinned", "# it's local", "# { not a table }"])
+                )
+                    .prop_map(|(ws, text)| format!("{}{}", ws, text)),
+            ]
+        }
+
+        /// Lines that mention neither the package nor its path, each key at
+        /// most once.
+        fn fillers() -> BoxedStrategy<Vec<Lines>> {
+            let comments = select(vec![
+                "",
+                "# dependencies",
+                "# my-crate = { path = \"../old-path\" }",
+            ])
+            .prop_map(|line| Lines::kept(line.to_string()));
+            let serde = (ws(), ws(), quote(), comment())
+                .prop_map(|(a, b, q, c)| Lines::kept(format!("serde{a}={b}{q}1.0{q}{c}")));
+            let prefixed = (ws(), quote(), comment()).prop_map(|(a, q, c)| {
+                Lines::kept(format!(
+                    "my-crate-extra = {{{a}path = {q}../my-crate-extra{q}{a}}}{c}"
+                ))
+            });
+            let multi_line = (ws(), quote()).prop_map(|(a, q)| {
+                Lines::concat([
+                    Lines::kept(format!("other{a}={a}{{")),
+                    Lines::kept(format!("{a}path = {q}../other{q},")),
+                    Lines::kept(format!("{a}features = [\"std\"]")),
+                    Lines::kept("}".to_string()),
+                ])
+            });
+
+            (
+                prop::option::of(comments),
+                prop::option::of(serde),
+                prop::option::of(prefixed),
+                prop::option::of(multi_line),
+            )
+                .prop_map(|(a, b, c, d)| [a, b, c, d].into_iter().flatten().collect::<Vec<_>>())
+                .prop_shuffle()
+                .boxed()
+        }
+
+        /// A `my-crate` entry in a dependency table.
+        fn entry(form: Form) -> BoxedStrategy<Lines> {
+            (ws(), ws(), ws(), quote(), comment()).prop_map(move |(a, b, i, q, c)| match form {
+                Form::Absent | Form::Table => Lines::default(),
+                Form::Inline => Lines::changed(
+                    format!("{i}my-crate{a}={b}{{{a}path{a}={b}{q}../old-path{q},{b}version = \"0.1\"{a}}}{c}"),
+                    format!("{i}new-crate = {{{a}path{a}={b}\"../new-path\",{b}version = \"0.1\"{a}}}{c}"),
+                ),
+                Form::MultiLine => Lines::concat([
+                    Lines::changed(
+                        format!("{i}my-crate{a}={b}{{{c}"),
+                        format!("{i}new-crate = {{{c}"),
+                    ),
+                    Lines::changed(
+                        format!("{i}{i}path{a}={b}{q}../old-path{q},{c}"),
+                        format!("{i}{i}path{a}={b}\"../new-path\",{c}"),
+                    ),
+                    Lines::kept(format!("{i}{i}features = [\"std\"]")),
+                    Lines::kept(format!("{i}}}{c}")),
+                ]),
+                Form::Dotted => Lines::concat([
+                    Lines::changed(
+                        format!("{i}my-crate.path{a}={b}{q}../old-path{q}{c}"),
+                        format!("{i}new-crate.path{a}={b}\"../new-path\"{c}"),
+                    ),
+                    Lines::changed(
+                        format!("{i}my-crate{a}.version = \"0.1\""),
+                        format!("{i}new-crate{a}.version = \"0.1\""),
+                    ),
+                ]),
+                Form::Alias => Lines::changed(
+                    format!("{i}alias{a}={b}{{ package{a}={b}{q}my-crate{q}, path = {q}../old-path{q} }}{c}"),
+                    format!("{i}alias{a}={b}{{ package{a}={b}{q}new-crate{q}, path = \"../new-path\" }}{c}"),
+                ),
+            })
+            .boxed()
+        }
+
+        /// The `[kind]` table and, for [`Form::Table`], `[kind.my-crate]`.
+        fn dependency_sections(kind: &'static str) -> BoxedStrategy<Vec<Lines>> {
+            select(vec![
+                Form::Absent,
+                Form::Inline,
+                Form::MultiLine,
+                Form::Dotted,
+                Form::Alias,
+                Form::Table,
+            ])
+            .prop_flat_map(move |form| {
+                (
+                    fillers(),
+                    entry(form),
+                    0..4usize,
+                    table(kind, form),
+                    comment(),
+                )
+            })
+            .prop_map(move |(mut lines, entry, position, table, c)| {
+                lines.insert(position.min(lines.len()), entry);
+                lines.insert(0, Lines::kept(format!("[{}]{}", kind, c)));
+                let mut sections = vec![Lines::concat(lines)];
+                sections.extend(table);
+                sections
+            })
+            .boxed()
+        }
+
+        /// A `[kind.my-crate]` table.
+        fn table(kind: &'static str, form: Form) -> BoxedStrategy<Option<Lines>> {
+            (ws(), ws(), quote(), comment(), fillers())
+                .prop_map(move |(a, b, q, c, fillers)| {
+                    if !matches!(form, Form::Table) {
+                        return None;
+                    }
+                    Some(Lines::concat([
+                        Lines::changed(
+                            format!("[{}.my-crate]{}", kind, c),
+                            format!("[{}.new-crate]{}", kind, c),
+                        ),
+                        Lines::concat(fillers),
+                        Lines::changed(
+                            format!("path{a}={b}{q}../old-path{q}{c}"),
+                            format!("path{a}={b}\"../new-path\"{c}"),
+                        ),
+                        Lines::kept("version = \"0.1\"".to_string()),
+                    ]))
+                })
+                .boxed()
+        }
+
+        /// A manifest with all sections in random order.
+        fn manifest() -> impl Strategy<Value = Lines> {
+            let other = vec![
+                Lines::concat([
+                    Lines::kept("[lib]".to_string()),
+                    Lines::kept("path = \"src/lib.rs\"".to_string()),
+                ]),
+                Lines::concat([
+                    Lines::kept("[features]".to_string()),
+                    Lines::kept("default = [\"std\"]".to_string()),
+                    Lines::kept("std = []".to_string()),
+                ]),
+            ];
+
+            KINDS
+                .map(dependency_sections)
+                .prop_map(move |sections| {
+                    let mut all: Vec<Lines> = sections.into_iter().flatten().collect();
+                    all.extend(other.clone());
+                    all
+                })
+                .prop_shuffle()
+                .prop_map(|sections| {
+                    let package = Lines::concat(
+                        ["[package]", "name = \"app\"", "version = \"0.1.0\""]
+                            .map(|line| Lines::kept(line.to_string())),
+                    );
+                    let mut lines = package;
+                    for section in sections {
+                        lines = Lines::concat([lines, Lines::kept(String::new()), section]);
+                    }
+                    lines
+                })
+        }
+
+        fn process(content: &str, old_name: &str, new_name: &str) -> String {
+            let new_paths = NewPaths::new(
+                Path::new("/ws/app"),
+                Path::new("/ws/new-path"),
+                Path::new("/ws"),
+                PathStyle::Preserve,
+            )
+            .unwrap();
+            TomlProcessor::new(content, old_name, new_name, Some(&new_paths))
+                .process(true, true)
+                .unwrap()
+        }
+
+        proptest! {
+            // The processor compiles its patterns per line; keep the default run short
+            #![proptest_config(ProptestConfig::with_cases(64))]
+
+            #[test]
+            fn test_rename_and_move(manifest in manifest()) {
+                let input = manifest.input.join("\n") + "\n";
+                let expected = manifest.expected.join("\n") + "\n";
+                prop_assert!(input.parse::<toml_edit::DocumentMut>().is_ok(), "invalid input:\n{}", input);
+
+                let output = process(&input, "my-crate", "new-crate");
+
+                prop_assert!(
+                    output.parse::<toml_edit::DocumentMut>().is_ok(),
+                    "invalid TOML:\n{}",
+                    output
+                );
+                prop_assert_eq!(&output, &expected);
+                // Nothing is left to rewrite
+                prop_assert_eq!(process(&output, "my-crate", "new-crate"), output);
+            }
+        }
     }
 }
@@ -0,0 +1,198 @@
+//! Path dependents outside the workspace.
+//!
+//! Sibling checkouts may depend on a workspace member by path:
+//!
+//! ```toml
+//! # ../other-repo/Cargo.toml
+//! old-crate = { path = "../workspace/crates/old-crate" }
+//! ```
+//!
+//! `cargo metadata` does not know about them, so `--notify-external DIR`
+//! scans the given directories for manifests whose path dependencies resolve
+//! to the renamed package and updates them like workspace members.
+//!
+//! The same goes for a workspace nested in another one (and `exclude`d
+//! there): members of the enclosing workspace may depend on the package by
+//! path. [`find_parent_workspace`] locates it, and `--include-parent-workspace`
+//! scans it like a `--notify-external` directory.
+
+use crate::cargo::dependency::{ManifestUpdate, update_dependent_manifest};
+use crate::cargo::version::for_each_dependency_table;
+use crate::cargo::workspace::update_workspace_manifest;
+use crate::error::Result;
+use crate::fs::display_path;
+use crate::fs::transaction::Transaction;
+use crate::workspace_scan;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item};
+
+/// Returns the manifests under `dir` with a path dependency on `package_dir`.
+///
+/// `skip` lists manifests already handled (workspace members and root).
+/// Manifests that fail to parse are skipped.
+pub fn find_external_dependents(
+    dir: &Path,
+    package_dir: &Path,
+    skip: &[PathBuf],
+) -> Result<Vec<PathBuf>> {
+    let dir = fs::canonicalize(dir)?;
+    let package_dir = fs::canonicalize(package_dir)?;
+    let skip: Vec<PathBuf> = skip
+        .iter()
+        .filter_map(|p| fs::canonicalize(p).ok())
+        .collect();
+    let manifests = workspace_scan::files_matching(&dir, &["**/Cargo.toml".to_string()])?;
+
+    Ok(manifests
+        .into_iter()
+        .filter(|manifest| !skip.contains(manifest))
+        .filter(|manifest| depends_on(manifest, &package_dir))
+        .collect())
+}
+
+/// Returns the manifest of the nearest workspace enclosing `workspace_root`.
+///
+/// Looks for a `Cargo.toml` with a `[workspace]` table in the directories
+/// above it. Manifests that fail to parse are skipped.
+pub fn find_parent_workspace(workspace_root: &Path) -> Option<PathBuf> {
+    workspace_root
+        .ancestors()
+        .skip(1)
+        .map(|dir| dir.join("Cargo.toml"))
+        .find(|manifest| {
+            fs::read_to_string(manifest)
+                .ok()
+                .and_then(|content| content.parse::<DocumentMut>().ok())
+                .is_some_and(|doc| doc.contains_key("workspace"))
+        })
+}
+
+/// Returns `true` if any dependency table of `manifest` points at `package_dir`.
+fn depends_on(manifest: &Path, package_dir: &Path) -> bool {
+    let Some(mut doc) = fs::read_to_string(manifest)
+        .ok()
+        .and_then(|content| content.parse::<DocumentMut>().ok())
+    else {
+        log::debug!("Skipping unparsable manifest: {}", display_path(manifest));
+        return false;
+    };
+    let manifest_dir = manifest.parent().unwrap();
+    let mut found = false;
+
+    for_each_dependency_table(&mut doc, &mut |table| {
+        found |= table.iter().any(|(_, item)| {
+            item.get("path")
+                .and_then(Item::as_str)
+                .and_then(|path| fs::canonicalize(manifest_dir.join(path)).ok())
+                .is_some_and(|path| path == package_dir)
+        });
+    });
+
+    found
+}
+
+/// Stages the dependency updates for an external manifest.
+///
+/// Dependency entries are rewritten like a workspace member's; a
+/// `[workspace.dependencies]` table is rewritten like a workspace root's.
+/// The manifest's own workspace root is unknown, so
+/// [`PathStyle::FromRoot`](crate::cargo::PathStyle::FromRoot) writes relative
+/// paths and `update.workspace_root` is ignored.
+pub fn update_external_manifest(
+    manifest: &Path,
+    update: &ManifestUpdate,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let update = ManifestUpdate {
+        workspace_root: manifest.parent().unwrap(),
+        ..*update
+    };
+    update_dependent_manifest(manifest, &update, txn)?;
+
+    let content = txn.read_file(manifest)?;
+    let has_workspace_deps = content.parse::<DocumentMut>().ok().is_some_and(|doc| {
+        doc.get("workspace")
+            .and_then(|ws| ws.get("dependencies"))
+            .is_some()
+    });
+    if has_workspace_deps {
+        update_workspace_manifest(manifest, &update, false, txn)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_finds_and_updates_external_dependents() {
+        let temp = TempDir::new().unwrap();
+        let old_dir = temp.path().join("ws/old-crate");
+        let new_dir = temp.path().join("ws/crates/new-crate");
+        fs::create_dir_all(&old_dir).unwrap();
+
+        let other = temp.path().join("other-repo");
+        fs::create_dir_all(other.join("unrelated")).unwrap();
+        let manifest = other.join("Cargo.toml");
+        fs::write(
+            &manifest,
+            "[package]\nname = \"other\"\n\n[dependencies]\nold-crate = { path = \"../ws/old-crate\" }\n",
+        )
+        .unwrap();
+        fs::write(
+            other.join("unrelated/Cargo.toml"),
+            "[package]\nname = \"unrelated\"\n\n[dependencies]\nold-crate = \"1\"\n",
+        )
+        .unwrap();
+
+        let found = find_external_dependents(&other, &old_dir, &[]).unwrap();
+        assert_eq!(found, vec![fs::canonicalize(&manifest).unwrap()]);
+
+        let mut txn = Transaction::new(false);
+        update_external_manifest(
+            &manifest,
+            &ManifestUpdate::new("old-crate", "new-crate", &old_dir, &new_dir, temp.path()),
+            &mut txn,
+        )
+        .unwrap();
+        txn.commit().unwrap();
+
+        assert!(
+            fs::read_to_string(&manifest)
+                .unwrap()
+                .contains("new-crate = { path = \"../ws/crates/new-crate\" }")
+        );
+    }
+
+    #[test]
+    fn test_find_parent_workspace() {
+        let temp = TempDir::new().unwrap();
+        let outer = temp.path().join("outer");
+        let inner = outer.join("vendor/inner");
+        fs::create_dir_all(&inner).unwrap();
+        fs::write(
+            outer.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"app\"]\nexclude = [\"vendor/inner\"]\n",
+        )
+        .unwrap();
+        fs::write(
+            outer.join("vendor/Cargo.toml"),
+            "[package]\nname = \"vendor\"\n",
+        )
+        .unwrap();
+        fs::write(inner.join("Cargo.toml"), "[workspace]\nmembers = []\n").unwrap();
+
+        assert_eq!(
+            find_parent_workspace(&inner),
+            Some(outer.join("Cargo.toml"))
+        );
+        assert_eq!(
+            find_parent_workspace(&outer.join("app")),
+            Some(outer.join("Cargo.toml"))
+        );
+    }
+}
@@ -0,0 +1,175 @@
+//! Dependency graph for batch renames.
+//!
+//! When several workspace members are renamed together and one depends on
+//! another, manifests shared between them must end up with *both* new names.
+//! Renames are staged into a single `Transaction` whose reads see earlier
+//! staged edits, so each file is transformed cumulatively and written once.
+//! This module validates the batch and orders it so dependencies are staged
+//! before their dependents.
+
+use crate::error::{RenameError, Result};
+use cargo_metadata::Metadata;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A set of package renames plus the dependency edges between them.
+#[derive(Debug, Clone, Default)]
+pub struct RenameGraph {
+    /// Old name → new name.
+    renames: BTreeMap<String, String>,
+    /// Renamed package → renamed packages it depends on.
+    dependencies: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl RenameGraph {
+    /// Builds the graph from workspace metadata.
+    pub fn new(metadata: &Metadata, renames: &[(String, String)]) -> Result<Self> {
+        let existing: Vec<String> = metadata
+            .workspace_packages()
+            .iter()
+            .map(|p| p.name.to_string())
+            .collect();
+
+        let edges = metadata.workspace_packages().into_iter().flat_map(|pkg| {
+            pkg.dependencies
+                .iter()
+                .map(move |dep| (pkg.name.to_string(), dep.name.clone()))
+        });
+
+        Self::from_edges(renames, &existing, edges)
+    }
+
+    /// Builds the graph from explicit `(package, dependency)` edges.
+    ///
+    /// Fails if two renames share an old or new name, if a new name collides
+    /// with a package that keeps its name, or if renames are chained
+    /// (`a → b` together with `b → c`).
+    pub fn from_edges(
+        renames: &[(String, String)],
+        existing: &[String],
+        edges: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self> {
+        let mut map = BTreeMap::new();
+        let mut targets = BTreeSet::new();
+
+        for (old, new) in renames {
+            if map.insert(old.clone(), new.clone()).is_some() {
+                return Err(RenameError::InvalidName(
+                    old.clone(),
+                    "package is renamed more than once".to_string(),
+                ));
+            }
+            if !targets.insert(new.clone()) {
+                return Err(RenameError::InvalidName(
+                    new.clone(),
+                    "multiple packages would be renamed to this name".to_string(),
+                ));
+            }
+        }
+
+        for (old, new) in &map {
+            if old != new && map.contains_key(new) {
+                return Err(RenameError::InvalidName(
+                    new.clone(),
+                    format!(
+                        "'{}' is renamed to '{}' while '{}' is itself renamed; rename in two steps",
+                        old, new, new
+                    ),
+                ));
+            }
+            if existing.contains(new) && !map.contains_key(new) {
+                return Err(RenameError::InvalidName(
+                    new.clone(),
+                    "a workspace package with this name already exists".to_string(),
+                ));
+            }
+        }
+
+        let mut dependencies: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for (pkg, dep) in edges {
+            if pkg != dep && map.contains_key(&pkg) && map.contains_key(&dep) {
+                dependencies.entry(pkg).or_default().insert(dep);
+            }
+        }
+
+        Ok(Self {
+            renames: map,
+            dependencies,
+        })
+    }
+
+    /// Returns the renames ordered so dependencies precede their dependents.
+    ///
+    /// Ties (and any dev-dependency cycles) fall back to name order.
+    pub fn ordered(&self) -> Vec<(String, String)> {
+        let mut remaining: BTreeMap<&str, BTreeSet<&str>> = self
+            .renames
+            .keys()
+            .map(|name| {
+                let deps = self
+                    .dependencies
+                    .get(name)
+                    .map(|d| d.iter().map(String::as_str).collect())
+                    .unwrap_or_default();
+                (name.as_str(), deps)
+            })
+            .collect();
+
+        let mut order = Vec::with_capacity(remaining.len());
+
+        while !remaining.is_empty() {
+            let next = remaining
+                .iter()
+                .find(|(_, deps)| deps.is_empty())
+                .map(|(name, _)| *name)
+                .unwrap_or_else(|| *remaining.keys().next().unwrap());
+
+            remaining.remove(next);
+            for deps in remaining.values_mut() {
+                deps.remove(next);
+            }
+            order.push((next.to_string(), self.renames[next].clone()));
+        }
+
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items
+            .iter()
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_orders_dependencies_first() {
+        let renames = pairs(&[("ws-app", "new-app"), ("ws-core", "new-core")]);
+        let edges = pairs(&[("ws-app", "ws-core"), ("ws-app", "serde")]);
+
+        let graph = RenameGraph::from_edges(&renames, &[], edges).unwrap();
+
+        assert_eq!(
+            graph.ordered(),
+            pairs(&[("ws-core", "new-core"), ("ws-app", "new-app")])
+        );
+    }
+
+    #[test]
+    fn test_rejects_chained_renames() {
+        let renames = pairs(&[("a", "b"), ("b", "c")]);
+        let result = RenameGraph::from_edges(&renames, &[], Vec::new());
+        assert!(matches!(result, Err(RenameError::InvalidName(..))));
+    }
+
+    #[test]
+    fn test_rejects_collision_with_existing_package() {
+        let renames = pairs(&[("a", "b")]);
+        let existing = vec!["a".to_string(), "b".to_string()];
+        let result = RenameGraph::from_edges(&renames, &existing, Vec::new());
+        assert!(matches!(result, Err(RenameError::InvalidName(..))));
+    }
+}
@@ -0,0 +1,361 @@
+//! Workspace discovery from `Cargo.toml` files alone.
+//!
+//! `cargo metadata` resolves the whole dependency graph and can take seconds
+//! on large workspaces. A manifest-only dry run needs just the member list,
+//! so it is read from the manifests directly. Anything this module cannot
+//! interpret the way Cargo would (`package.workspace`, unparsable manifests)
+//! or that needs workspace metadata (a naming policy, workspace fragments) makes [`ManifestWorkspace::load`]
+//! return `None`, and the caller falls back to `cargo metadata`.
+
+use cargo_metadata::semver::Version;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item};
+
+/// A workspace member read from its manifest.
+#[derive(Debug, Clone)]
+pub struct ManifestPackage {
+    pub name: String,
+    pub version: Version,
+    pub manifest_path: PathBuf,
+}
+
+/// A workspace as described by its manifests.
+#[derive(Debug, Clone)]
+pub struct ManifestWorkspace {
+    pub root: PathBuf,
+    pub members: Vec<ManifestPackage>,
+}
+
+impl ManifestWorkspace {
+    /// Loads the workspace containing `manifest_path`, or the one above the
+    /// current directory if `None`.
+    ///
+    /// Returns `None` if the workspace needs `cargo metadata` to be read
+    /// correctly; the reason is logged at debug level.
+    pub fn load(manifest_path: Option<&Path>) -> Option<Self> {
+        let start = match manifest_path {
+            Some(path) => path.to_path_buf(),
+            None => find_manifest(&std::env::current_dir().ok()?)?,
+        };
+        let start_doc = parse(&start)?;
+
+        if start_doc
+            .get("package")
+            .and_then(|pkg| pkg.get("workspace"))
+            .is_some()
+        {
+            return unsupported("package.workspace is set");
+        }
+
+        let root = if start_doc.contains_key("workspace") {
+            start.clone()
+        } else {
+            start
+                .parent()?
+                .ancestors()
+                .skip(1)
+                .map(|dir| dir.join("Cargo.toml"))
+                .filter(|path| path.is_file())
+                .find(|path| parse(path).is_some_and(|doc| doc.contains_key("workspace")))
+                .unwrap_or_else(|| start.clone())
+        };
+        let root_doc = if root == start {
+            start_doc
+        } else {
+            parse(&root)?
+        };
+        let root_dir = root.parent()?.to_path_buf();
+        let config = root_doc
+            .get("workspace")
+            .and_then(|ws| ws.get("metadata"))
+            .and_then(|meta| meta.get("cargo-rename"));
+        if config.and_then(|config| config.get("naming")).is_some() {
+            return unsupported("a naming policy is configured");
+        }
+        if config.and_then(|config| config.get("fragments")).is_some() {
+            return unsupported("workspace fragments are configured");
+        }
+
+        let mut manifests = Vec::new();
+        if root_doc.contains_key("package") {
+            manifests.push(root.clone());
+        }
+        let workspace = root_doc.get("workspace");
+        let exclude: Vec<PathBuf> = strings(workspace.and_then(|ws| ws.get("exclude")))
+            .iter()
+            .map(|dir| root_dir.join(dir))
+            .collect();
+        for pattern in strings(workspace.and_then(|ws| ws.get("members"))) {
+            for dir in expand_member(&root_dir, &pattern) {
+                let manifest = dir.join("Cargo.toml");
+                if manifest.is_file() && !exclude.iter().any(|ex| dir.starts_with(ex)) {
+                    manifests.push(manifest);
+                }
+            }
+        }
+        manifests.sort();
+        manifests.dedup();
+
+        let workspace_version = workspace
+            .and_then(|ws| ws.get("package"))
+            .and_then(|pkg| pkg.get("version"))
+            .and_then(Item::as_str)
+            .map(str::to_string);
+
+        let mut members = Vec::new();
+        for manifest_path in manifests {
+            let doc = parse(&manifest_path)?;
+            let package = doc.get("package")?;
+            let Some(name) = package.get("name").and_then(Item::as_str) else {
+                return unsupported("a member has no literal package.name");
+            };
+
+            let version = match package.get("version") {
+                None => "0.0.0".to_string(),
+                Some(version) => match version.as_str() {
+                    Some(version) => version.to_string(),
+                    None if version.get("workspace").and_then(Item::as_bool) == Some(true) => {
+                        workspace_version.clone()?
+                    }
+                    None => return unsupported("a member has an unreadable package.version"),
+                },
+            };
+
+            members.push(ManifestPackage {
+                name: name.to_string(),
+                version: Version::parse(&version).ok()?,
+                manifest_path,
+            });
+        }
+
+        Some(Self {
+            root: root_dir,
+            members,
+        })
+    }
+
+    /// Returns the member named `name`.
+    pub fn package(&self, name: &str) -> Option<&ManifestPackage> {
+        self.members.iter().find(|p| p.name == name)
+    }
+
+    /// Returns the workspace root manifest.
+    pub fn root_manifest(&self) -> PathBuf {
+        self.root.join("Cargo.toml")
+    }
+}
+
+/// Finds the closest `Cargo.toml` at or above `dir`.
+fn find_manifest(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join("Cargo.toml"))
+        .find(|path| path.is_file())
+}
+
+fn parse(path: &Path) -> Option<DocumentMut> {
+    let content = fs::read_to_string(path).ok()?;
+    match content.parse() {
+        Ok(doc) => Some(doc),
+        Err(_) => unsupported("a manifest does not parse"),
+    }
+}
+
+fn strings(item: Option<&Item>) -> Vec<String> {
+    item.and_then(Item::as_array)
+        .map(|array| {
+            array
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Expands a `workspace.members` entry to the directories it matches.
+///
+/// Only the directories below the entry's literal prefix are walked, and
+/// only as deep as the entry reaches unless it contains `**`.
+fn expand_member(root: &Path, entry: &str) -> Vec<PathBuf> {
+    let components: Vec<&str> = entry
+        .split('/')
+        .filter(|c| !c.is_empty() && *c != ".")
+        .collect();
+    let entry = components.join("/");
+    let Some(glob) = member_glob(&entry) else {
+        return vec![root.join(entry)];
+    };
+
+    let literal = components
+        .iter()
+        .take_while(|c| !c.contains(['*', '?', '[']))
+        .count();
+    let max_depth = if components.contains(&"**") {
+        usize::MAX
+    } else {
+        components.len()
+    };
+
+    let mut dirs = Vec::new();
+    let mut pending = vec![(root.join(components[..literal].join("/")), literal)];
+    while let Some((dir, depth)) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            if glob.is_match(&relative.to_string_lossy().replace('\\', "/")) {
+                dirs.push(path.clone());
+            }
+            if depth + 1 < max_depth {
+                pending.push((path, depth + 1));
+            }
+        }
+    }
+
+    dirs
+}
+
+/// Compiles a glob member like `crates/*` to a regex over `/`-separated
+/// paths, or returns `None` for a plain path.
+///
+/// As in cargo, `*` and `?` stay within one path component and `**` spans
+/// several.
+pub(crate) fn member_glob(entry: &str) -> Option<Regex> {
+    if !entry.contains(['*', '?', '[']) {
+        return None;
+    }
+
+    let mut pattern = String::from("^");
+    let mut chars = entry.trim_end_matches('/').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                pattern.push_str(".*");
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            '[' => {
+                pattern.push('[');
+                if chars.peek() == Some(&'!') {
+                    chars.next();
+                    pattern.push('^');
+                }
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    if c == '\\' || c == '^' {
+                        pattern.push('\\');
+                    }
+                    pattern.push(c);
+                }
+                pattern.push(']');
+            }
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+
+    Regex::new(&pattern).ok()
+}
+
+fn unsupported<T>(reason: &str) -> Option<T> {
+    log::debug!("Falling back to cargo metadata: {}", reason);
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_load_members_from_manifests() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        write(
+            &root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\", \"tools/cli\"]\nexclude = [\"crates/scratch\"]\n\n[workspace.package]\nversion = \"1.2.3\"\n",
+        );
+        write(
+            &root.join("crates/core/Cargo.toml"),
+            "[package]\nname = \"core\"\nversion.workspace = true\n",
+        );
+        write(
+            &root.join("crates/scratch/Cargo.toml"),
+            "[package]\nname = \"scratch\"\n",
+        );
+        write(
+            &root.join("tools/cli/Cargo.toml"),
+            "[package]\nname = \"cli\"\nversion = \"0.4.0\"\n",
+        );
+
+        let workspace = ManifestWorkspace::load(Some(&root.join("tools/cli/Cargo.toml"))).unwrap();
+
+        assert_eq!(workspace.root, root);
+        let names: Vec<_> = workspace.members.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["core", "cli"]);
+        assert_eq!(
+            workspace.package("core").unwrap().version,
+            Version::new(1, 2, 3)
+        );
+    }
+
+    #[test]
+    fn test_expand_member_globs() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        for dir in ["crates/a", "crates/b", "crates/nested/c", "libs/x/y"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let expand = |entry: &str| {
+            let mut dirs: Vec<String> = expand_member(root, entry)
+                .iter()
+                .map(|dir| {
+                    dir.strip_prefix(root)
+                        .unwrap()
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                })
+                .collect();
+            dirs.sort();
+            dirs
+        };
+
+        assert_eq!(
+            expand("crates/*"),
+            ["crates/a", "crates/b", "crates/nested"]
+        );
+        assert_eq!(expand("./crates/[ab]"), ["crates/a", "crates/b"]);
+        assert_eq!(
+            expand("crates/**"),
+            ["crates/a", "crates/b", "crates/nested", "crates/nested/c"]
+        );
+        assert_eq!(expand("*/x/?"), ["libs/x/y"]);
+        assert_eq!(expand("tools/cli"), ["tools/cli"]);
+    }
+
+    #[test]
+    fn test_member_glob() {
+        let glob = member_glob("crates/*").unwrap();
+        assert!(glob.is_match("crates/foo"));
+        assert!(!glob.is_match("crates/foo/bar"));
+        assert!(member_glob("libs/**").unwrap().is_match("libs/a/b"));
+        assert!(member_glob("crate-[ab]").unwrap().is_match("crate-b"));
+        assert!(!member_glob("crate-[!ab]").unwrap().is_match("crate-a"));
+        assert!(member_glob("crates/foo").is_none());
+    }
+}
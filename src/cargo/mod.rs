@@ -4,11 +4,42 @@
 //! - **`package`**: Renamed package's own manifest
 //! - **`workspace`**: Workspace-level configuration
 //! - **`dependency`**: Dependency references in other packages
+//! - **`manifests`**: Workspace discovery without `cargo metadata`
+//! - **`external`**: Path dependents outside the workspace (`--notify-external`,
+//!   `--include-parent-workspace`)
+//! - **`graph`**: Ordering of batch renames between dependent packages
+//! - **`normalize`**: Consistent dependency declarations (`normalize-deps`)
+//! - **`resolve`**: Workspace package vs. crates.io crate of the same name
+//! - **`version`**: Version requirements on the renamed package
 
 pub mod dependency;
+pub mod external;
+pub mod graph;
+pub mod manifests;
+pub mod normalize;
 pub mod package;
+pub mod resolve;
+pub mod version;
 pub mod workspace;
 
-pub use dependency::update_dependent_manifest;
-pub use package::update_package_name;
-pub use workspace::update_workspace_manifest;
+pub use dependency::{
+    DepKind, DependencyReplacements, ManifestUpdate, PathStyle, dependency_lines,
+    pin_unselected_dependencies, update_dependent_manifest, update_patch_sections,
+};
+pub use external::{find_external_dependents, find_parent_workspace, update_external_manifest};
+pub use graph::RenameGraph;
+pub use manifests::ManifestWorkspace;
+pub use normalize::{
+    Normalization, NormalizeTarget, normalize_manifest, update_feature_references,
+};
+pub use package::{
+    GlobReview, PackageMetadata, UrlUpdate, check_package_metadata, update_package_globs,
+    update_package_links, update_package_name, update_package_urls, update_package_version,
+    update_target_names, update_target_paths,
+};
+pub use resolve::{NameResolution, resolve_dependency_name, workspace_dependency_kinds};
+pub use version::{VersionMismatch, check_dependency_versions, dependent_manifests};
+pub use workspace::{
+    missing_member_manifests, update_workspace_fragment, update_workspace_manifest,
+    update_workspace_package_fields,
+};
@@ -0,0 +1,553 @@
+//! Consistent dependency declarations for `rename normalize-deps`.
+//!
+//! Dependents of a crate often declare it in different ways:
+//!
+//! ```toml
+//! # crates/app/Cargo.toml
+//! parser = { package = "my-crate", path = "../my-crate" }   # aliased key
+//!
+//! # crates/cli/Cargo.toml
+//! my-crate = { path = "../my-crate", features = ["cli"] }  # not inherited
+//! ```
+//!
+//! Normalizing rewrites every declaration to be keyed by the package name,
+//! and to inherit the `[workspace.dependencies]` entry where one exists:
+//!
+//! ```toml
+//! my-crate = { workspace = true }
+//! my-crate = { workspace = true, features = ["cli"] }
+//! ```
+//!
+//! Aliases in `[workspace.dependencies]` are normalized too, together with
+//! the members inheriting them. Only path dependencies resolving to the crate
+//! are switched to inheritance; registry and git declarations are left alone.
+//! A dependent whose key changes must also have its Rust sources rewritten,
+//! which the returned [`NormalizedDependency::renamed_from`] allows. Its
+//! `[features]` references to the old key (`parser/cli`, `parser?/cli`,
+//! `dep:parser`) are rewritten with the manifest.
+
+use crate::error::Result;
+use crate::fs::display_path;
+use crate::fs::transaction::Transaction;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Key, Table, Value};
+
+/// Dependency tables of a package manifest.
+const DEPENDENCY_KINDS: &[&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// Fields dropped when a declaration switches to `workspace = true`.
+const INHERITED_FIELDS: &[&str] = &["path", "version", "registry", "package"];
+
+/// A normalized declaration of the crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedDependency {
+    pub manifest: PathBuf,
+    /// Table containing the declaration, e.g. `dev-dependencies`.
+    pub section: String,
+    /// Previous key, if the declaration was aliased.
+    pub renamed_from: Option<String>,
+    /// `true` if the declaration now uses `workspace = true`.
+    pub inherited: bool,
+}
+
+/// A declaration that was left alone, with the reason.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeptDependency {
+    pub manifest: PathBuf,
+    pub section: String,
+    pub key: String,
+    pub reason: String,
+}
+
+/// Changes to one manifest.
+#[derive(Debug, Clone, Default)]
+pub struct Normalization {
+    pub normalized: Vec<NormalizedDependency>,
+    pub kept: Vec<KeptDependency>,
+}
+
+/// The crate whose declarations are normalized.
+pub struct NormalizeTarget<'a> {
+    pub name: &'a str,
+    pub dir: &'a Path,
+    /// Keys of `[workspace.dependencies]` entries for the crate, before normalization.
+    pub workspace_aliases: Vec<String>,
+    /// `true` if `[workspace.dependencies]` declares the crate.
+    pub inheritable: bool,
+    /// `default-features` of that entry.
+    pub workspace_default_features: bool,
+}
+
+impl<'a> NormalizeTarget<'a> {
+    /// Reads how the workspace root manifest declares the crate.
+    pub fn new(name: &'a str, dir: &'a Path, root_manifest: &Path) -> Result<Self> {
+        let mut target = Self {
+            name,
+            dir,
+            workspace_aliases: Vec::new(),
+            inheritable: false,
+            workspace_default_features: true,
+        };
+
+        let Ok(content) = fs::read_to_string(root_manifest) else {
+            return Ok(target);
+        };
+        let doc: DocumentMut = content.parse()?;
+        let Some(table) = doc
+            .get("workspace")
+            .and_then(|ws| ws.get("dependencies"))
+            .and_then(Item::as_table_like)
+        else {
+            return Ok(target);
+        };
+
+        for (key, item) in table.iter() {
+            if declared_package(key, item) == name {
+                target.inheritable = true;
+                target.workspace_default_features = default_features(item);
+                if key != name {
+                    target.workspace_aliases.push(key.to_string());
+                }
+            }
+        }
+
+        Ok(target)
+    }
+}
+
+/// Normalizes the declarations of `target` in one manifest.
+pub fn normalize_manifest(
+    manifest_path: &Path,
+    target: &NormalizeTarget,
+    txn: &mut Transaction,
+) -> Result<Normalization> {
+    let content = txn.read_file(manifest_path)?;
+    let mut doc: DocumentMut = content.parse()?;
+    let manifest_dir = manifest_path.parent().unwrap();
+    let mut normalization = Normalization::default();
+    let mut renamed_keys = Vec::new();
+
+    for path in dependency_tables(&doc) {
+        let section = section_label(&path);
+        let Some(table) = table_at(&mut doc, &path) else {
+            continue;
+        };
+        let in_workspace = path[0] == "workspace";
+
+        let keys: Vec<String> = table.iter().map(|(key, _)| key.to_string()).collect();
+        for key in keys {
+            let item = &table[key.as_str()];
+            let refers = declared_package(&key, item) == target.name
+                || (is_inherited(item) && target.workspace_aliases.contains(&key));
+            if !refers {
+                continue;
+            }
+
+            let mut renamed_from = None;
+            if key != target.name {
+                if table.contains_key(target.name) {
+                    normalization.kept.push(KeptDependency {
+                        manifest: manifest_path.to_path_buf(),
+                        section: section.clone(),
+                        key,
+                        reason: format!("'{}' is declared as well", target.name),
+                    });
+                    continue;
+                }
+                rename_key(table, &key, target.name);
+                if !in_workspace {
+                    renamed_keys.push(key.clone());
+                }
+                renamed_from = Some(key.clone());
+            }
+
+            let item = &mut table[target.name];
+            let mut changed = renamed_from.is_some();
+            if let Some(entry) = item.as_table_like_mut() {
+                changed |= entry.remove("package").is_some();
+            }
+
+            let mut inherited = false;
+            if !in_workspace
+                && target.inheritable
+                && !is_inherited(item)
+                && path_points_to(item, manifest_dir, target.dir)
+            {
+                // Cargo rejects opting out of default features the workspace entry enables
+                if !default_features(item) && target.workspace_default_features {
+                    normalization.kept.push(KeptDependency {
+                        manifest: manifest_path.to_path_buf(),
+                        section: section.clone(),
+                        key: target.name.to_string(),
+                        reason: "sets default-features = false, unlike the workspace entry"
+                            .to_string(),
+                    });
+                } else {
+                    inherit(item);
+                    inherited = true;
+                }
+            }
+
+            if changed || inherited {
+                normalization.normalized.push(NormalizedDependency {
+                    manifest: manifest_path.to_path_buf(),
+                    section: section.clone(),
+                    renamed_from,
+                    inherited,
+                });
+            }
+        }
+    }
+
+    renamed_keys.sort_unstable();
+    renamed_keys.dedup();
+    for key in renamed_keys {
+        rename_feature_references(&mut doc, &key, target.name);
+    }
+
+    let new_content = doc.to_string();
+    if new_content != content {
+        txn.update_file(manifest_path.to_path_buf(), new_content)?;
+        log::debug!(
+            "Normalized dependencies in: {}",
+            display_path(manifest_path)
+        );
+    }
+
+    Ok(normalization)
+}
+
+/// Rewrites the `[features]` references to a dependency key renamed in `manifest`.
+///
+/// Used after a rename has rewritten the dependency keys themselves; see
+/// [`rename_feature_references`].
+pub fn update_feature_references(
+    manifest: &Path,
+    old_key: &str,
+    new_key: &str,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let content = txn.read_file(manifest)?;
+    let mut doc: DocumentMut = content.parse()?;
+    rename_feature_references(&mut doc, old_key, new_key);
+
+    let new_content = doc.to_string();
+    if new_content != content {
+        txn.update_file(manifest.to_path_buf(), new_content)?;
+    }
+    Ok(())
+}
+
+/// Points `[features]` entries referring to dependency `old_key` at `new_key`.
+///
+/// Rewrites `old/feat`, `old?/feat`, `dep:old` and the implicit `old` feature
+/// of an optional dependency. Nothing is rewritten while a dependency table
+/// still declares `old_key`, and a bare `old` naming a declared feature is
+/// left alone.
+fn rename_feature_references(doc: &mut DocumentMut, old_key: &str, new_key: &str) {
+    if declares_dependency(doc, old_key) {
+        return;
+    }
+    let Some(features) = doc.get_mut("features").and_then(Item::as_table_like_mut) else {
+        return;
+    };
+    let declared = features.contains_key(old_key);
+
+    for (_, item) in features.iter_mut() {
+        let Some(values) = item.as_array_mut() else {
+            continue;
+        };
+        for value in values.iter_mut() {
+            let Some(reference) = value.as_str() else {
+                continue;
+            };
+            let renamed = if reference == old_key {
+                (!declared).then(|| new_key.to_string())
+            } else if reference.strip_prefix("dep:") == Some(old_key) {
+                Some(format!("dep:{}", new_key))
+            } else {
+                reference
+                    .strip_prefix(old_key)
+                    .filter(|rest| rest.starts_with('/') || rest.starts_with("?/"))
+                    .map(|rest| format!("{}{}", new_key, rest))
+            };
+
+            if let Some(renamed) = renamed {
+                let decor = value.decor().clone();
+                *value = Value::from(renamed);
+                *value.decor_mut() = decor;
+            }
+        }
+    }
+}
+
+/// Returns `true` if a package dependency table declares `key`.
+fn declares_dependency(doc: &DocumentMut, key: &str) -> bool {
+    dependency_tables(doc)
+        .iter()
+        .filter(|path| path[0] != "workspace")
+        .filter_map(|path| {
+            path.iter()
+                .try_fold(doc.as_item(), |item, segment| item.get(segment))
+        })
+        .any(|table| table.get(key).is_some())
+}
+
+/// Returns the package a dependency entry refers to.
+fn declared_package<'i>(key: &'i str, item: &'i Item) -> &'i str {
+    item.get("package").and_then(Item::as_str).unwrap_or(key)
+}
+
+fn is_inherited(item: &Item) -> bool {
+    item.get("workspace").and_then(Item::as_bool) == Some(true)
+}
+
+fn default_features(item: &Item) -> bool {
+    item.get("default-features")
+        .or_else(|| item.get("default_features"))
+        .and_then(Item::as_bool)
+        .unwrap_or(true)
+}
+
+/// Returns `true` if the entry is a path dependency on `dir`.
+fn path_points_to(item: &Item, manifest_dir: &Path, dir: &Path) -> bool {
+    let Some(path) = item.get("path").and_then(Item::as_str) else {
+        return false;
+    };
+    let resolved = manifest_dir.join(path);
+
+    match (fs::canonicalize(&resolved), fs::canonicalize(dir)) {
+        (Ok(resolved), Ok(dir)) => resolved == dir,
+        _ => resolved == dir,
+    }
+}
+
+/// Replaces the source fields of an entry with `workspace = true`.
+fn inherit(item: &mut Item) {
+    let Some(entry) = item.as_table_like_mut() else {
+        return;
+    };
+
+    let kept: Vec<(Key, Item)> = entry
+        .iter()
+        .filter(|(key, _)| !INHERITED_FIELDS.contains(key))
+        .filter_map(|(key, _)| entry.get_key_value(key))
+        .map(|(key, item)| (key.clone(), item.clone()))
+        .collect();
+
+    entry.clear();
+    entry.insert("workspace", Item::Value(Value::from(true)));
+    for (key, value) in kept {
+        entry.insert(key.get(), value);
+    }
+
+    if let Some(inline) = item.as_inline_table_mut() {
+        inline.fmt();
+    }
+}
+
+/// Renames `old` to `new` in `table`, keeping the entry's position and decor.
+fn rename_key(table: &mut Table, old: &str, new: &str) {
+    let entries: Vec<(Key, Item)> = table
+        .iter()
+        .filter_map(|(key, _)| table.get_key_value(key))
+        .map(|(key, item)| {
+            if key.get() == old {
+                let renamed = Key::new(new)
+                    .with_leaf_decor(key.leaf_decor().clone())
+                    .with_dotted_decor(key.dotted_decor().clone());
+                (renamed, item.clone())
+            } else {
+                (key.clone(), item.clone())
+            }
+        })
+        .collect();
+
+    table.clear();
+    for (key, item) in entries {
+        table.insert_formatted(&key, item);
+    }
+}
+
+/// Returns the key paths of all dependency tables in a manifest.
+fn dependency_tables(doc: &DocumentMut) -> Vec<Vec<String>> {
+    let mut paths: Vec<Vec<String>> = DEPENDENCY_KINDS
+        .iter()
+        .map(|kind| vec![kind.to_string()])
+        .collect();
+
+    if let Some(targets) = doc.get("target").and_then(Item::as_table_like) {
+        for (target, _) in targets.iter() {
+            for kind in DEPENDENCY_KINDS {
+                paths.push(vec![
+                    "target".to_string(),
+                    target.to_string(),
+                    kind.to_string(),
+                ]);
+            }
+        }
+    }
+    paths.push(vec!["workspace".to_string(), "dependencies".to_string()]);
+
+    paths
+}
+
+fn table_at<'d>(doc: &'d mut DocumentMut, path: &[String]) -> Option<&'d mut Table> {
+    let mut item = doc.as_item_mut();
+    for key in path {
+        item = item.as_table_like_mut()?.get_mut(key)?;
+    }
+    item.as_table_mut()
+}
+
+/// Formats a table path the way it appears in a header.
+fn section_label(path: &[String]) -> String {
+    match path {
+        [target, cfg, kind] if target == "target" => format!("target.'{}'.{}", cfg, kind),
+        _ => path.join("."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_normalize_aliases_and_inheritance() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        write(
+            &root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.dependencies]\nparser = { package = \"my-crate\", path = \"crates/my-crate\" }\n",
+        );
+        write(
+            &root.join("crates/my-crate/Cargo.toml"),
+            "[package]\nname = \"my-crate\"\n",
+        );
+        let app = root.join("crates/app/Cargo.toml");
+        write(
+            &app,
+            r#"[package]
+name = "app"
+
+[dependencies]
+serde = "1"
+parser.workspace = true # shared parser
+log = "0.4"
+
+[dev-dependencies]
+my-crate = { path = "../my-crate", features = ["test"] }
+
+[target.'cfg(unix)'.dependencies]
+my-crate = { path = "../my-crate", default-features = false }
+"#,
+        );
+
+        let crate_dir = root.join("crates/my-crate");
+        let target =
+            NormalizeTarget::new("my-crate", &crate_dir, &root.join("Cargo.toml")).unwrap();
+        assert_eq!(target.workspace_aliases, vec!["parser"]);
+
+        let mut txn = Transaction::new(false);
+        let ws = normalize_manifest(&root.join("Cargo.toml"), &target, &mut txn).unwrap();
+        let member = normalize_manifest(&app, &target, &mut txn).unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(ws.normalized.len(), 1);
+        assert!(
+            fs::read_to_string(root.join("Cargo.toml"))
+                .unwrap()
+                .contains("my-crate = { path = \"crates/my-crate\" }")
+        );
+
+        assert_eq!(
+            fs::read_to_string(&app).unwrap(),
+            r#"[package]
+name = "app"
+
+[dependencies]
+serde = "1"
+my-crate.workspace = true # shared parser
+log = "0.4"
+
+[dev-dependencies]
+my-crate = { workspace = true, features = ["test"] }
+
+[target.'cfg(unix)'.dependencies]
+my-crate = { path = "../my-crate", default-features = false }
+"#
+        );
+        let renamed: Vec<_> = member
+            .normalized
+            .iter()
+            .map(|n| (n.section.as_str(), n.renamed_from.as_deref(), n.inherited))
+            .collect();
+        assert_eq!(
+            renamed,
+            vec![
+                ("dependencies", Some("parser"), false),
+                ("dev-dependencies", None, true),
+            ]
+        );
+        assert_eq!(member.kept.len(), 1);
+        assert_eq!(member.kept[0].section, "target.'cfg(unix)'.dependencies");
+    }
+
+    #[test]
+    fn test_normalize_rewrites_feature_references() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        write(
+            &root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        );
+        write(
+            &root.join("crates/my-crate/Cargo.toml"),
+            "[package]\nname = \"my-crate\"\n",
+        );
+        let app = root.join("crates/app/Cargo.toml");
+        write(
+            &app,
+            r#"[package]
+name = "app"
+
+[dependencies]
+parser = { package = "my-crate", path = "../my-crate", optional = true }
+
+[features]
+cli = ["parser/cli", "parser?/color", "dep:parser"] # all spellings
+full = ["cli", "parser"]
+parserx = ["parserx/extra"]
+"#,
+        );
+
+        let crate_dir = root.join("crates/my-crate");
+        let target =
+            NormalizeTarget::new("my-crate", &crate_dir, &root.join("Cargo.toml")).unwrap();
+        let mut txn = Transaction::new(false);
+        normalize_manifest(&app, &target, &mut txn).unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&app).unwrap(),
+            r#"[package]
+name = "app"
+
+[dependencies]
+my-crate = { path = "../my-crate", optional = true }
+
+[features]
+cli = ["my-crate/cli", "my-crate?/color", "dep:my-crate"] # all spellings
+full = ["cli", "my-crate"]
+parserx = ["parserx/extra"]
+"#
+        );
+    }
+}
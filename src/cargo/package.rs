@@ -1,13 +1,22 @@
 //! Package manifest updates.
 //!
-//! Updates the `[package]` section of a crate's `Cargo.toml`.
+//! Updates the `[package]` section of a crate's `Cargo.toml` (name and,
+//! on request, version, URL fields, description, keywords, `links` and target
+//! names), and
+//! target `path` keys and `include`/`exclude` globs when the package
+//! directory moves.
 
-use crate::error::Result;
+use crate::error::{RenameError, Result};
 use crate::fs::transaction::Transaction;
-use std::fs;
-use std::path::Path;
+use crate::fs::{display_path, normalize_path};
+use crate::symbols;
+use regex::Regex;
+use std::path::{Path, PathBuf};
 use toml_edit::{DocumentMut, Item, Value};
 
+/// Target sections declared as arrays of tables (`[[bin]]`, `[[test]]`, ...).
+const TARGET_ARRAYS: &[&str] = &["bin", "test", "example", "bench"];
+
 /// Updates package name in `Cargo.toml`.
 ///
 /// Modifies only the `name` field, preserving formatting and comments.
@@ -16,7 +25,7 @@ pub fn update_package_name(
     new_name: &str,
     txn: &mut Transaction,
 ) -> Result<()> {
-    let content = fs::read_to_string(manifest_path)?;
+    let content = txn.read_file(manifest_path)?;
     let mut doc: DocumentMut = content.parse()?;
 
     doc["package"]["name"] = Item::Value(Value::from(new_name));
@@ -25,6 +34,694 @@ pub fn update_package_name(
     Ok(())
 }
 
+/// Sets `package.version` (`--set-version`) and returns the previous value.
+///
+/// A version inherited with `version.workspace = true` is replaced by the
+/// explicit one; the previous value is then `workspace`. Returns `None` if
+/// the version is already `version`.
+pub fn update_package_version(
+    manifest_path: &Path,
+    version: &str,
+    txn: &mut Transaction,
+) -> Result<Option<String>> {
+    let content = txn.read_file(manifest_path)?;
+    let mut doc: DocumentMut = content.parse()?;
+    let package = doc["package"]
+        .as_table_like_mut()
+        .ok_or_else(|| anyhow::anyhow!("No [package] in {}", display_path(manifest_path)))?;
+
+    let previous = match package.get("version") {
+        Some(item) if item.as_str() == Some(version) => return Ok(None),
+        Some(item) => item
+            .as_str()
+            .map_or_else(|| "workspace".to_string(), str::to_string),
+        // Cargo's default for packages without a version
+        None => "0.0.0".to_string(),
+    };
+
+    match package.get_mut("version").and_then(Item::as_value_mut) {
+        Some(value) if value.is_str() => {
+            let decor = value.decor().clone();
+            *value = Value::from(version);
+            *value.decor_mut() = decor;
+        }
+        _ => {
+            package.insert("version", Item::Value(Value::from(version)));
+        }
+    }
+
+    txn.update_file(manifest_path.to_path_buf(), doc.to_string())?;
+    Ok(Some(previous))
+}
+
+/// Renames `package.links` if it derives from the package name.
+///
+/// The value matches if it is `old_name` or its snake_case form, with or
+/// without a `-sys` suffix (`links = "foo"` for `foo-sys`), and takes the
+/// corresponding form of `new_name`. Returns the old and new value, or
+/// `None` if the manifest has no such `links` key.
+pub fn update_package_links(
+    manifest_path: &Path,
+    old_name: &str,
+    new_name: &str,
+    txn: &mut Transaction,
+) -> Result<Option<(String, String)>> {
+    let content = txn.read_file(manifest_path)?;
+    let mut doc: DocumentMut = content.parse()?;
+    let Some(links) = doc
+        .get_mut("package")
+        .and_then(|pkg| pkg.get_mut("links"))
+        .and_then(Item::as_value_mut)
+    else {
+        return Ok(None);
+    };
+    let Some(current) = links.as_str().map(str::to_string) else {
+        return Ok(None);
+    };
+
+    let strip_sys = |name: &str| {
+        name.strip_suffix("-sys")
+            .or_else(|| name.strip_suffix("_sys"))
+            .unwrap_or(name)
+            .to_string()
+    };
+    let candidates = [
+        (old_name.to_string(), new_name.to_string()),
+        (strip_sys(old_name), strip_sys(new_name)),
+    ];
+    let Some(updated) = candidates.iter().find_map(|(old, new)| {
+        if current == *old {
+            Some(new.clone())
+        } else if current == old.replace('-', "_") {
+            Some(new.replace('-', "_"))
+        } else {
+            None
+        }
+    }) else {
+        return Ok(None);
+    };
+    if updated == current {
+        return Ok(None);
+    }
+
+    let decor = links.decor().clone();
+    *links = Value::from(updated.as_str());
+    *links.decor_mut() = decor;
+    txn.update_file(manifest_path.to_path_buf(), doc.to_string())?;
+
+    Ok(Some((current, updated)))
+}
+
+/// Rewrites target `path` keys so they still resolve after a directory move.
+///
+/// Covers `[lib]`, `[[bin]]`, `[[test]]`, `[[example]]`, `[[bench]]` and
+/// `package.build`. Paths inside the package move with it and are kept;
+/// paths reaching outside it (`../shared/main.rs`) are rewritten relative to
+/// `new_dir`. Every declared path is then checked to exist.
+///
+/// Must be staged before the move, while the manifest is still at `old_dir`.
+pub fn update_target_paths(
+    manifest_path: &Path,
+    old_dir: &Path,
+    new_dir: &Path,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let content = txn.read_file(manifest_path)?;
+    let mut doc: DocumentMut = content.parse()?;
+    let mut changed = false;
+
+    let mut fix = |key: &str, value: &mut Value| -> Result<()> {
+        let Some(current) = value.as_str() else {
+            return Ok(());
+        };
+
+        let (resolved, updated) = relocate_path(current, old_dir, new_dir);
+        if !resolved.exists() {
+            return Err(RenameError::InvalidPath(
+                current.to_string(),
+                format!(
+                    "Target '{}' does not resolve to an existing file ({})",
+                    key,
+                    display_path(&resolved)
+                ),
+            ));
+        }
+
+        if let Some(updated) = updated {
+            log::info!(
+                "Updated target path {}: {} {} {}",
+                key,
+                current,
+                symbols::arrow(),
+                updated
+            );
+            let decor = value.decor().clone();
+            *value = Value::from(updated);
+            *value.decor_mut() = decor;
+            changed = true;
+        }
+
+        Ok(())
+    };
+
+    if let Some(path) = doc
+        .get_mut("lib")
+        .and_then(|lib| lib.get_mut("path"))
+        .and_then(Item::as_value_mut)
+    {
+        fix("lib.path", path)?;
+    }
+
+    for kind in TARGET_ARRAYS {
+        let Some(targets) = doc.get_mut(kind).and_then(Item::as_array_of_tables_mut) else {
+            continue;
+        };
+
+        for target in targets.iter_mut() {
+            if let Some(path) = target.get_mut("path").and_then(Item::as_value_mut) {
+                fix(&format!("{}.path", kind), path)?;
+            }
+        }
+    }
+
+    if let Some(build) = doc
+        .get_mut("package")
+        .and_then(|pkg| pkg.get_mut("build"))
+        .and_then(Item::as_value_mut)
+    {
+        fix("package.build", build)?;
+    }
+
+    if changed {
+        txn.update_file(manifest_path.to_path_buf(), doc.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Renames `[lib]`, `[[bin]]`, `[[test]]`, `[[example]]` and `[[bench]]`
+/// targets named after the package, and `package.default-run`.
+///
+/// A target matches if its name is `old_name` or its snake_case form, and
+/// takes the corresponding form of `new_name`. Array targets without a
+/// `path` whose source was inferred from the old name (`src/bin/old-crate.rs`,
+/// `examples/old_crate/main.rs`, ...) get that path written out, so they keep
+/// building from the same file.
+pub fn update_target_names(
+    manifest_path: &Path,
+    old_name: &str,
+    new_name: &str,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let content = txn.read_file(manifest_path)?;
+    let mut doc: DocumentMut = content.parse()?;
+    let package_dir = manifest_path.parent().unwrap_or(Path::new("."));
+    let old_snake = old_name.replace('-', "_");
+    let new_snake = new_name.replace('-', "_");
+    let renamed = |name: &str| match name {
+        _ if name == old_name => Some(new_name.to_string()),
+        _ if name == old_snake => Some(new_snake.clone()),
+        _ => None,
+    };
+    let mut changed = false;
+
+    let mut rename = |key: &str, value: &mut Value| -> Option<String> {
+        let current = value.as_str()?.to_string();
+        let updated = renamed(&current)?;
+        log::info!(
+            "Renamed target {}: {} {} {}",
+            key,
+            current,
+            symbols::arrow(),
+            updated
+        );
+        let decor = value.decor().clone();
+        *value = Value::from(updated);
+        *value.decor_mut() = decor;
+        changed = true;
+        Some(current)
+    };
+
+    if let Some(name) = doc
+        .get_mut("lib")
+        .and_then(|lib| lib.get_mut("name"))
+        .and_then(Item::as_value_mut)
+    {
+        rename("lib.name", name);
+    }
+
+    for kind in TARGET_ARRAYS {
+        let Some(targets) = doc.get_mut(kind).and_then(Item::as_array_of_tables_mut) else {
+            continue;
+        };
+
+        for target in targets.iter_mut() {
+            let Some(old) = target
+                .get_mut("name")
+                .and_then(Item::as_value_mut)
+                .and_then(|name| rename(&format!("{}.name", kind), name))
+            else {
+                continue;
+            };
+
+            if !target.contains_key("path")
+                && let Some(path) = inferred_target_path(package_dir, kind, &old)
+            {
+                target.insert("path", toml_edit::value(path));
+            }
+        }
+    }
+
+    if let Some(default_run) = doc
+        .get_mut("package")
+        .and_then(|pkg| pkg.get_mut("default-run"))
+        .and_then(Item::as_value_mut)
+    {
+        rename("package.default-run", default_run);
+    }
+
+    if changed {
+        txn.update_file(manifest_path.to_path_buf(), doc.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Returns the source file cargo infers for an array target named `name`,
+/// relative to the package, if it exists.
+///
+/// `src/main.rs` is not returned: cargo infers it for the binary named after
+/// the package, which keeps matching after the package is renamed.
+fn inferred_target_path(package_dir: &Path, kind: &str, name: &str) -> Option<String> {
+    let dir = match kind {
+        "bin" => "src/bin",
+        "test" => "tests",
+        "example" => "examples",
+        "bench" => "benches",
+        _ => return None,
+    };
+
+    [
+        format!("{}/{}.rs", dir, name),
+        format!("{}/{}/main.rs", dir, name),
+    ]
+    .into_iter()
+    .find(|path| package_dir.join(path).is_file())
+}
+
+/// An `include`/`exclude` pattern that reaches outside the package but could
+/// not be rewritten, e.g. because `..` follows a wildcard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobReview {
+    /// `package.include` or `package.exclude`.
+    pub key: String,
+    pub pattern: String,
+}
+
+/// Rewrites `package.include` and `package.exclude` patterns that escape the
+/// package directory so they still match after a directory move.
+///
+/// Only the literal prefix of a pattern (the components before the first
+/// wildcard) is relocated: `../LICENSE` and `../shared/**/*.rs` are updated,
+/// `src/**/../../LICENSE` cannot be and is returned for manual review.
+/// Negations (`!`) and anchors (`/`) are kept.
+///
+/// Must be staged before the move, while the manifest is still at `old_dir`.
+pub fn update_package_globs(
+    manifest_path: &Path,
+    old_dir: &Path,
+    new_dir: &Path,
+    txn: &mut Transaction,
+) -> Result<Vec<GlobReview>> {
+    let content = txn.read_file(manifest_path)?;
+    let mut doc: DocumentMut = content.parse()?;
+    let mut reviews = Vec::new();
+    let mut changed = false;
+
+    for field in ["include", "exclude"] {
+        let key = format!("package.{}", field);
+        let Some(patterns) = doc
+            .get_mut("package")
+            .and_then(|pkg| pkg.get_mut(field))
+            .and_then(Item::as_array_mut)
+        else {
+            continue;
+        };
+
+        for value in patterns.iter_mut() {
+            let Some(current) = value.as_str() else {
+                continue;
+            };
+
+            match relocate_glob(current, old_dir, new_dir) {
+                Ok(None) => {}
+                Ok(Some(updated)) => {
+                    log::info!(
+                        "Updated {}: {} {} {}",
+                        key,
+                        current,
+                        symbols::arrow(),
+                        updated
+                    );
+                    let decor = value.decor().clone();
+                    *value = Value::from(updated);
+                    *value.decor_mut() = decor;
+                    changed = true;
+                }
+                Err(()) => reviews.push(GlobReview {
+                    key: key.clone(),
+                    pattern: current.to_string(),
+                }),
+            }
+        }
+    }
+
+    if changed {
+        txn.update_file(manifest_path.to_path_buf(), doc.to_string())?;
+    }
+
+    Ok(reviews)
+}
+
+/// Relocates the literal prefix of a glob pattern across a package move.
+///
+/// Returns `Ok(None)` if the pattern stays valid, and `Err(())` if it
+/// reaches outside the package in a way that cannot be rewritten.
+fn relocate_glob(
+    pattern: &str,
+    old_dir: &Path,
+    new_dir: &Path,
+) -> std::result::Result<Option<String>, ()> {
+    let (negation, rest) = match pattern.strip_prefix('!') {
+        Some(rest) => ("!", rest),
+        None => ("", pattern),
+    };
+    if rest.starts_with('/') {
+        // Anchored at the package root, which moves with the package
+        return Ok(None);
+    }
+
+    let components: Vec<&str> = rest.split('/').collect();
+    let literal = components
+        .iter()
+        .position(|c| c.contains(['*', '?', '[', '{']))
+        .unwrap_or(components.len());
+
+    if components[literal..].contains(&"..") {
+        return Err(());
+    }
+    if !components[..literal].contains(&"..") {
+        return Ok(None);
+    }
+
+    let prefix = components[..literal].join("/");
+    let (_, updated) = relocate_path(&prefix, old_dir, new_dir);
+    let Some(updated) = updated else {
+        return Ok(None);
+    };
+
+    let mut parts = vec![updated.as_str()];
+    parts.extend(&components[literal..]);
+    parts.retain(|p| !p.is_empty());
+    Ok(Some(format!("{}{}", negation, parts.join("/"))))
+}
+
+/// How a `[package]` URL field is updated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlUpdate {
+    /// Replace path segments equal to the old name.
+    Rewrite,
+    /// Set the field to this URL.
+    Set(String),
+}
+
+/// Updates `repository`, `homepage` or `documentation` in `[package]`.
+///
+/// [`UrlUpdate::Rewrite`] replaces only whole path segments:
+/// `https://github.com/acme/old-crate` and `.../old-crate.git` are updated,
+/// `https://github.com/old-crate-org/tools` is not. docs.rs paths embedding
+/// the library name (`old_crate`) are updated too. Fields inherited with
+/// `field.workspace = true` are only replaced by [`UrlUpdate::Set`].
+pub fn update_package_urls(
+    manifest_path: &Path,
+    updates: &[(&str, UrlUpdate)],
+    old_name: &str,
+    new_name: &str,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let content = txn.read_file(manifest_path)?;
+    let mut doc: DocumentMut = content.parse()?;
+    let Some(package) = doc.get_mut("package").and_then(Item::as_table_like_mut) else {
+        return Ok(());
+    };
+    let mut changed = false;
+
+    for (field, update) in updates {
+        let current = package
+            .get(field)
+            .and_then(Item::as_str)
+            .map(str::to_string);
+
+        let updated = match (update, &current) {
+            (UrlUpdate::Set(url), _) => url.clone(),
+            (UrlUpdate::Rewrite, Some(current)) => {
+                replace_url_segments(current, old_name, new_name)
+            }
+            (UrlUpdate::Rewrite, None) => {
+                log::warn!(
+                    "package.{} is not set in {} (or is inherited from the workspace); not rewritten",
+                    field,
+                    display_path(manifest_path)
+                );
+                continue;
+            }
+        };
+
+        if current.as_deref() == Some(updated.as_str()) {
+            continue;
+        }
+
+        log::info!(
+            "Updated package.{}: {} {} {}",
+            field,
+            current.as_deref().unwrap_or("(unset)"),
+            symbols::arrow(),
+            updated
+        );
+        match package.get_mut(field).and_then(Item::as_value_mut) {
+            Some(value) if value.is_str() => {
+                let decor = value.decor().clone();
+                *value = Value::from(updated);
+                *value.decor_mut() = decor;
+            }
+            _ => {
+                package.insert(field, Item::Value(Value::from(updated)));
+            }
+        }
+        changed = true;
+    }
+
+    if changed {
+        txn.update_file(manifest_path.to_path_buf(), doc.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// `[package]` fields shown in the metadata report of a rename.
+const REPORTED_FIELDS: &[&str] = &["rust-version", "publish", "license", "description"];
+
+/// Publishing metadata of a renamed package.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageMetadata {
+    /// `rust-version`, `publish`, `license` and `description` as written,
+    /// for the fields that are set.
+    pub fields: Vec<(&'static str, String)>,
+    /// `description` and `keywords` entries that still mention the old name,
+    /// e.g. `keywords[1] "old-crate"`.
+    pub stale: Vec<String>,
+}
+
+/// Reads the publishing metadata of a package and finds `description` and
+/// `keywords` entries mentioning `old_name` (or its snake_case form) as a
+/// whole word.
+///
+/// With `update`, those mentions are replaced by `new_name` and staged.
+/// Fields inherited with `field.workspace = true` are reported but never
+/// rewritten.
+pub fn check_package_metadata(
+    manifest_path: &Path,
+    old_name: &str,
+    new_name: &str,
+    update: bool,
+    txn: &mut Transaction,
+) -> Result<PackageMetadata> {
+    let content = txn.read_file(manifest_path)?;
+    let mut doc: DocumentMut = content.parse()?;
+    let mut report = PackageMetadata::default();
+    let Some(package) = doc.get_mut("package").and_then(Item::as_table_like_mut) else {
+        return Ok(report);
+    };
+
+    for field in REPORTED_FIELDS {
+        let Some(item) = package.get(field) else {
+            continue;
+        };
+        let value = match item {
+            _ if is_workspace_inherited(item) => "(workspace)".to_string(),
+            Item::Value(Value::String(s)) => s.value().clone(),
+            Item::Value(Value::Array(registries)) => format!(
+                "registries: {}",
+                registries
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Item::Value(value) => value.to_string().trim().to_string(),
+            _ => continue,
+        };
+        report.fields.push((field, value));
+    }
+
+    let rewrites = name_rewrites(old_name, new_name)?;
+    let mut changed = false;
+    let mut check = |label: String, value: &mut Value| {
+        let Some(current) = value.as_str() else {
+            return;
+        };
+        let mut updated = current.to_string();
+        for (pattern, replacement) in &rewrites {
+            updated = pattern
+                .replace_all(&updated, replacement.as_str())
+                .into_owned();
+        }
+        if updated == current {
+            return;
+        }
+
+        report.stale.push(format!("{} \"{}\"", label, current));
+        if update {
+            log::info!(
+                "Updated package.{}: {} {} {}",
+                label,
+                current,
+                symbols::arrow(),
+                updated
+            );
+            let decor = value.decor().clone();
+            *value = Value::from(updated);
+            *value.decor_mut() = decor;
+            changed = true;
+        }
+    };
+
+    if let Some(value) = package.get_mut("description").and_then(Item::as_value_mut) {
+        check("description".to_string(), value);
+    }
+    if let Some(keywords) = package
+        .get_mut("keywords")
+        .and_then(Item::as_value_mut)
+        .and_then(Value::as_array_mut)
+    {
+        for (idx, value) in keywords.iter_mut().enumerate() {
+            check(format!("keywords[{}]", idx), value);
+        }
+    }
+
+    if changed {
+        txn.update_file(manifest_path.to_path_buf(), doc.to_string())?;
+    }
+
+    Ok(report)
+}
+
+/// Returns `true` for `field.workspace = true` and `field = { workspace = true }`.
+fn is_workspace_inherited(item: &Item) -> bool {
+    item.as_table_like()
+        .and_then(|t| t.get("workspace"))
+        .and_then(Item::as_bool)
+        .unwrap_or(false)
+}
+
+/// Whole-word patterns for the old name and its snake_case form, with their
+/// replacements.
+fn name_rewrites(old_name: &str, new_name: &str) -> Result<Vec<(Regex, String)>> {
+    let mut rewrites = vec![(
+        Regex::new(&format!(r"\b{}\b", regex::escape(old_name)))?,
+        new_name.to_string(),
+    )];
+
+    let old_snake = old_name.replace('-', "_");
+    if old_snake != old_name {
+        rewrites.push((
+            Regex::new(&format!(r"\b{}\b", regex::escape(&old_snake)))?,
+            new_name.replace('-', "_"),
+        ));
+    }
+
+    Ok(rewrites)
+}
+
+/// Replaces URL path segments equal to `old_name` (or its snake_case form).
+///
+/// The scheme, host, query and fragment are left untouched.
+pub(crate) fn replace_url_segments(url: &str, old_name: &str, new_name: &str) -> String {
+    let (prefix, rest) = match url.find("://") {
+        Some(idx) => {
+            let host_end = url[idx + 3..].find('/').map_or(url.len(), |i| idx + 3 + i);
+            url.split_at(host_end)
+        }
+        None => ("", url),
+    };
+    let (path, suffix) = rest.split_at(rest.find(['?', '#']).unwrap_or(rest.len()));
+
+    let old_snake = old_name.replace('-', "_");
+    let new_snake = new_name.replace('-', "_");
+
+    let path = path
+        .split('/')
+        .map(|segment| {
+            let (stem, ext) = match segment.strip_suffix(".git") {
+                Some(stem) => (stem, ".git"),
+                None => (segment, ""),
+            };
+            if stem == old_name {
+                format!("{}{}", new_name, ext)
+            } else if stem == old_snake {
+                format!("{}{}", new_snake, ext)
+            } else {
+                segment.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    format!("{}{}{}", prefix, path, suffix)
+}
+
+/// Resolves a manifest-relative path across a package move.
+///
+/// Returns the file's current location (for existence checks) and, if the
+/// path leaves the package directory, its replacement relative to `new_dir`.
+fn relocate_path(path: &str, old_dir: &Path, new_dir: &Path) -> (PathBuf, Option<String>) {
+    let declared = Path::new(path);
+    if declared.is_absolute() {
+        return (declared.to_path_buf(), None);
+    }
+
+    let resolved = normalize_path(&old_dir.join(declared));
+    if resolved.starts_with(old_dir) {
+        return (resolved, None);
+    }
+
+    let updated = pathdiff::diff_paths(&resolved, new_dir)
+        .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+        .filter(|rel| rel != path);
+
+    (resolved, updated)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -51,6 +748,75 @@ mod tests {
         assert!(result.contains("version = \"0.1.0\""));
     }
 
+    #[test]
+    fn test_update_package_links() {
+        let temp = TempDir::new().unwrap();
+        let manifest = temp.path().join("Cargo.toml");
+
+        for (links, expected) in [
+            ("foo", Some("bar")),
+            ("foo-sys", Some("bar-sys")),
+            ("foo_sys", Some("bar_sys")),
+            ("z", None),
+        ] {
+            fs::write(
+                &manifest,
+                format!(
+                    "[package]\nname = \"foo-sys\"\nlinks = \"{}\" # native\n",
+                    links
+                ),
+            )
+            .unwrap();
+
+            let mut txn = Transaction::new(false);
+            let updated = update_package_links(&manifest, "foo-sys", "bar-sys", &mut txn).unwrap();
+            txn.commit().unwrap();
+
+            assert_eq!(
+                updated,
+                expected.map(|new| (links.to_string(), new.to_string()))
+            );
+            let content = fs::read_to_string(&manifest).unwrap();
+            assert!(content.contains(&format!(
+                "links = \"{}\" # native",
+                expected.unwrap_or(links)
+            )));
+        }
+    }
+
+    #[test]
+    fn test_update_package_version() {
+        let temp = TempDir::new().unwrap();
+        let manifest = temp.path().join("Cargo.toml");
+
+        for (version, previous, expected) in [
+            (
+                "version = \"1.4.2\" # bumped by CI\n",
+                Some("1.4.2"),
+                "version = \"0.1.0\" # bumped by CI\n",
+            ),
+            (
+                "version.workspace = true\n",
+                Some("workspace"),
+                "version = \"0.1.0\"\n",
+            ),
+            ("", Some("0.0.0"), "version = \"0.1.0\"\n"),
+            ("version = \"0.1.0\"\n", None, "version = \"0.1.0\"\n"),
+        ] {
+            fs::write(&manifest, format!("[package]\nname = \"app\"\n{}", version)).unwrap();
+
+            let mut txn = Transaction::new(false);
+            let updated = update_package_version(&manifest, "0.1.0", &mut txn).unwrap();
+            txn.commit().unwrap();
+
+            assert_eq!(updated.as_deref(), previous);
+            assert_eq!(
+                fs::read_to_string(&manifest).unwrap(),
+                format!("[package]\nname = \"app\"\n{}", expected)
+            );
+        }
+    }
+
     #[test]
     fn test_preserves_comments() {
         let temp = TempDir::new().unwrap();
@@ -71,4 +837,271 @@ version = "0.1.0"
         assert!(result.contains("# Important"));
         assert!(result.contains("name = \"new-name\""));
     }
+
+    #[test]
+    fn test_update_target_paths_outside_package() {
+        let temp = TempDir::new().unwrap();
+        let old_dir = temp.path().join("old-crate");
+        let new_dir = temp.path().join("crates/new-crate");
+        fs::create_dir_all(old_dir.join("src")).unwrap();
+        fs::create_dir_all(temp.path().join("shared")).unwrap();
+        fs::write(old_dir.join("src/lib.rs"), "").unwrap();
+        fs::write(temp.path().join("shared/tool.rs"), "fn main() {}").unwrap();
+
+        let manifest = old_dir.join("Cargo.toml");
+        fs::write(
+            &manifest,
+            r#"[package]
+name = "old-crate"
+autobins = false
+
+[lib]
+path = "src/lib.rs"
+
+[[bin]]
+name = "tool"
+path = "../shared/tool.rs" # shared entry point
+"#,
+        )
+        .unwrap();
+
+        let mut txn = Transaction::new(false);
+        update_target_paths(&manifest, &old_dir, &new_dir, &mut txn).unwrap();
+        txn.commit().unwrap();
+
+        let result = fs::read_to_string(&manifest).unwrap();
+        assert!(result.contains(r#"path = "src/lib.rs""#));
+        assert!(result.contains(r#"path = "../../shared/tool.rs" # shared entry point"#));
+    }
+
+    #[test]
+    fn test_update_target_paths_rejects_missing_target() {
+        let temp = TempDir::new().unwrap();
+        let old_dir = temp.path().join("old-crate");
+        fs::create_dir_all(&old_dir).unwrap();
+
+        let manifest = old_dir.join("Cargo.toml");
+        fs::write(
+            &manifest,
+            "[package]\nname = \"old-crate\"\n\n[[test]]\nname = \"it\"\npath = \"tests/it.rs\"\n",
+        )
+        .unwrap();
+
+        let mut txn = Transaction::new(false);
+        let result = update_target_paths(&manifest, &old_dir, &temp.path().join("new"), &mut txn);
+        assert!(matches!(result, Err(RenameError::InvalidPath(..))));
+    }
+
+    #[test]
+    fn test_update_package_globs() {
+        let temp = TempDir::new().unwrap();
+        let old_dir = temp.path().join("old-crate");
+        let new_dir = temp.path().join("crates/new-crate");
+        fs::create_dir_all(&old_dir).unwrap();
+
+        let manifest = old_dir.join("Cargo.toml");
+        fs::write(
+            &manifest,
+            r#"[package]
+name = "old-crate"
+include = [
+    "src/**",
+    "../LICENSE", # shared license
+    "../shared/**/*.rs",
+]
+exclude = ["!../shared/keep.rs", "/target", "src/**/../../notes.md"]
+"#,
+        )
+        .unwrap();
+
+        let mut txn = Transaction::new(false);
+        let reviews = update_package_globs(&manifest, &old_dir, &new_dir, &mut txn).unwrap();
+        txn.commit().unwrap();
+
+        let result = fs::read_to_string(&manifest).unwrap();
+        assert!(result.contains(r#""src/**","#));
+        assert!(result.contains(r#""../../LICENSE", # shared license"#));
+        assert!(result.contains(r#""../../shared/**/*.rs""#));
+        assert!(result.contains(r#""!../../shared/keep.rs""#));
+        assert!(result.contains(r#""/target""#));
+        assert_eq!(
+            reviews,
+            vec![GlobReview {
+                key: "package.exclude".to_string(),
+                pattern: "src/**/../../notes.md".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_replace_url_segments() {
+        let cases = [
+            (
+                "https://github.com/acme/old-crate",
+                "https://github.com/acme/new-crate",
+            ),
+            (
+                "https://github.com/acme/old-crate.git",
+                "https://github.com/acme/new-crate.git",
+            ),
+            (
+                "https://docs.rs/old-crate/latest/old_crate/?search=old-crate",
+                "https://docs.rs/new-crate/latest/new_crate/?search=old-crate",
+            ),
+            (
+                "https://github.com/old-crate-org/tools",
+                "https://github.com/old-crate-org/tools",
+            ),
+            ("https://old-crate.dev/", "https://old-crate.dev/"),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(
+                replace_url_segments(input, "old-crate", "new-crate"),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_update_package_urls() {
+        let temp = TempDir::new().unwrap();
+        let manifest = temp.path().join("Cargo.toml");
+        fs::write(
+            &manifest,
+            r#"[package]
+name = "new-crate"
+repository = "https://github.com/acme/old-crate" # upstream
+homepage.workspace = true
+"#,
+        )
+        .unwrap();
+
+        let mut txn = Transaction::new(false);
+        update_package_urls(
+            &manifest,
+            &[
+                ("repository", UrlUpdate::Rewrite),
+                ("homepage", UrlUpdate::Rewrite),
+                (
+                    "documentation",
+                    UrlUpdate::Set("https://docs.example.com".into()),
+                ),
+            ],
+            "old-crate",
+            "new-crate",
+            &mut txn,
+        )
+        .unwrap();
+        txn.commit().unwrap();
+
+        let result = fs::read_to_string(&manifest).unwrap();
+        assert!(result.contains(r#"repository = "https://github.com/acme/new-crate" # upstream"#));
+        assert!(result.contains("homepage.workspace = true"));
+        assert!(result.contains(r#"documentation = "https://docs.example.com""#));
+    }
+
+    #[test]
+    fn test_update_target_names() {
+        let temp = TempDir::new().unwrap();
+        let manifest = temp.path().join("Cargo.toml");
+        fs::create_dir_all(temp.path().join("src/bin")).unwrap();
+        fs::write(temp.path().join("src/bin/old-crate.rs"), "fn main() {}").unwrap();
+        fs::write(
+            &manifest,
+            r#"[package]
+name = "new-crate"
+default-run = "old-crate"
+
+[lib]
+name = "old_crate" # keep
+
+[[bin]]
+name = "old-crate"
+
+[[bin]]
+name = "other"
+
+[[example]]
+name = "old_crate"
+path = "examples/demo.rs"
+"#,
+        )
+        .unwrap();
+
+        let mut txn = Transaction::new(false);
+        update_target_names(&manifest, "old-crate", "new-crate", &mut txn).unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&manifest).unwrap(),
+            r#"[package]
+name = "new-crate"
+default-run = "new-crate"
+
+[lib]
+name = "new_crate" # keep
+
+[[bin]]
+name = "new-crate"
+path = "src/bin/old-crate.rs"
+
+[[bin]]
+name = "other"
+
+[[example]]
+name = "new_crate"
+path = "examples/demo.rs"
+"#
+        );
+    }
+
+    #[test]
+    fn test_check_package_metadata() {
+        let temp = TempDir::new().unwrap();
+        let manifest = temp.path().join("Cargo.toml");
+        let input = r#"[package]
+name = "new-crate"
+rust-version = "1.75"
+publish = ["internal"]
+license.workspace = true
+description = "Helpers for old-crate users; see old_crate::prelude" # short
+keywords = ["old-crate", "parsing", "gold-crate"]
+"#;
+        fs::write(&manifest, input).unwrap();
+
+        let mut txn = Transaction::new(false);
+        let report =
+            check_package_metadata(&manifest, "old-crate", "new-crate", false, &mut txn).unwrap();
+        assert!(txn.is_empty());
+        assert_eq!(
+            report.fields,
+            vec![
+                ("rust-version", "1.75".to_string()),
+                ("publish", "registries: internal".to_string()),
+                ("license", "(workspace)".to_string()),
+                (
+                    "description",
+                    "Helpers for old-crate users; see old_crate::prelude".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            report.stale,
+            vec![
+                r#"description "Helpers for old-crate users; see old_crate::prelude""#,
+                r#"keywords[0] "old-crate""#,
+            ]
+        );
+
+        check_package_metadata(&manifest, "old-crate", "new-crate", true, &mut txn).unwrap();
+        txn.commit().unwrap();
+
+        let result = fs::read_to_string(&manifest).unwrap();
+        assert!(result.contains(
+            r#"description = "Helpers for new-crate users; see new_crate::prelude" # short"#
+        ));
+        assert!(result.contains(r#"keywords = ["new-crate", "parsing", "gold-crate"]"#));
+        assert!(result.contains("license.workspace = true"));
+    }
 }
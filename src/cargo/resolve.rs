@@ -0,0 +1,78 @@
+//! Which crate a member's dependency on the renamed name points at.
+//!
+//! Short package names (`log`, `rand`, `time`) are shared with popular
+//! crates.io crates, so a member may depend on `log` without meaning the
+//! workspace package. A word-boundary rewrite of `log::` would then break
+//! its unrelated references. Dependencies are classified with the resolved
+//! `cargo metadata`: a dependency is the workspace package only if it is a
+//! path dependency on the package's directory.
+
+use super::DepKind;
+use cargo_metadata::Package;
+use std::path::Path;
+
+/// How a workspace member refers to the renamed package's name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameResolution {
+    /// No dependency has the name, as package or as key.
+    Unrelated,
+    /// Every dependency with the name is the renamed workspace package.
+    Workspace,
+    /// Every dependency with the name is another crate (crates.io, git or
+    /// a different path).
+    External,
+    /// Both, e.g. the workspace package as a dependency and the crates.io
+    /// crate of the same name as a build-dependency.
+    Ambiguous,
+}
+
+/// Classifies the dependencies of `member` named `name` (as package or key),
+/// where `package_dir` is the directory of the renamed workspace package.
+pub fn resolve_dependency_name(member: &Package, name: &str, package_dir: &Path) -> NameResolution {
+    let snake = name.replace('-', "_");
+    let (mut workspace, mut external) = (false, false);
+
+    for dep in &member.dependencies {
+        let key = dep.rename.as_deref().unwrap_or(&dep.name);
+        if dep.name != name && key.replace('-', "_") != snake {
+            continue;
+        }
+
+        if dep
+            .path
+            .as_ref()
+            .is_some_and(|path| path.as_std_path() == package_dir)
+        {
+            workspace = true;
+        } else {
+            external = true;
+        }
+    }
+
+    match (workspace, external) {
+        (false, false) => NameResolution::Unrelated,
+        (true, false) => NameResolution::Workspace,
+        (false, true) => NameResolution::External,
+        (true, true) => NameResolution::Ambiguous,
+    }
+}
+
+/// Returns the kinds of the dependencies of `member` on the renamed workspace
+/// package in `package_dir`, each kind once.
+pub fn workspace_dependency_kinds(member: &Package, package_dir: &Path) -> Vec<DepKind> {
+    let mut kinds = Vec::new();
+
+    for dep in &member.dependencies {
+        let kind = DepKind::from(dep.kind);
+        if dep
+            .path
+            .as_ref()
+            .is_some_and(|path| path.as_std_path() == package_dir)
+            && !kinds.contains(&kind)
+        {
+            kinds.push(kind);
+        }
+    }
+
+    kinds
+}
@@ -0,0 +1,246 @@
+//! Version requirement checks for renamed path dependencies.
+//!
+//! Dependents often pin a renamed crate by both path and version:
+//!
+//! ```toml
+//! new-crate = { version = "0.3", path = "../new-crate" }
+//! ```
+//!
+//! The name is rewritten, but the requirement is kept and may no longer match
+//! the versions published under the new name. This pass compares every such
+//! requirement with the renamed package's `version` and reports mismatches,
+//! or rewrites them to the exact version with `--sync-versions`.
+
+use crate::error::Result;
+use crate::fs::display_path;
+use crate::fs::transaction::Transaction;
+use cargo_metadata::semver::{Version, VersionReq};
+use std::path::PathBuf;
+use toml_edit::{DocumentMut, Item, TableLike, Value};
+
+/// Dependency tables checked in each manifest and `[target.*]` table.
+const DEPENDENCY_TABLES: &[&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// A dependency whose version requirement does not match the renamed package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionMismatch {
+    /// Manifest declaring the dependency.
+    pub manifest: PathBuf,
+    /// Dependency key (differs from the package name for aliases).
+    pub key: String,
+    /// The requirement as written.
+    pub requirement: String,
+}
+
+/// Checks path dependencies on `package` in `manifests` against `version`.
+///
+/// With `sync`, mismatched requirements are replaced by `version` and staged.
+/// Manifests and requirements that fail to parse are left alone.
+pub fn check_dependency_versions(
+    manifests: &[PathBuf],
+    package: &str,
+    version: &Version,
+    sync: bool,
+    txn: &mut Transaction,
+) -> Result<Vec<VersionMismatch>> {
+    let mut mismatches = Vec::new();
+
+    for manifest in manifests {
+        let content = txn.read_file(manifest)?;
+        let Ok(mut doc) = content.parse::<DocumentMut>() else {
+            log::debug!("Skipping unparsable manifest: {}", display_path(manifest));
+            continue;
+        };
+        let mut found = Vec::new();
+
+        for_each_dependency_table(&mut doc, &mut |table| {
+            check_table(table, package, version, sync, &mut found);
+        });
+
+        if found.is_empty() {
+            continue;
+        }
+
+        if sync {
+            txn.update_file(manifest.clone(), doc.to_string())?;
+            log::debug!("Synced version requirements in {}", display_path(manifest));
+        }
+
+        mismatches.extend(found.into_iter().map(|(key, requirement)| VersionMismatch {
+            manifest: manifest.clone(),
+            key,
+            requirement,
+        }));
+    }
+
+    Ok(mismatches)
+}
+
+pub(crate) fn for_each_dependency_table(
+    doc: &mut DocumentMut,
+    f: &mut impl FnMut(&mut dyn TableLike),
+) {
+    let root = doc.as_table_mut();
+
+    for name in DEPENDENCY_TABLES {
+        if let Some(table) = root.get_mut(name).and_then(Item::as_table_like_mut) {
+            f(table);
+        }
+    }
+
+    if let Some(targets) = root.get_mut("target").and_then(Item::as_table_like_mut) {
+        for (_, target) in targets.iter_mut() {
+            let Some(target) = target.as_table_like_mut() else {
+                continue;
+            };
+            for name in DEPENDENCY_TABLES {
+                if let Some(table) = target.get_mut(name).and_then(Item::as_table_like_mut) {
+                    f(table);
+                }
+            }
+        }
+    }
+
+    if let Some(table) = root
+        .get_mut("workspace")
+        .and_then(Item::as_table_like_mut)
+        .and_then(|ws| ws.get_mut("dependencies"))
+        .and_then(Item::as_table_like_mut)
+    {
+        f(table);
+    }
+}
+
+fn check_table(
+    table: &mut dyn TableLike,
+    package: &str,
+    version: &Version,
+    sync: bool,
+    found: &mut Vec<(String, String)>,
+) {
+    for (key, item) in table.iter_mut() {
+        let Some(dep) = item.as_table_like_mut() else {
+            continue;
+        };
+
+        let name = dep
+            .get("package")
+            .and_then(Item::as_str)
+            .unwrap_or(key.get());
+        if name != package || !dep.contains_key("path") {
+            continue;
+        }
+
+        let Some(value) = dep.get_mut("version").and_then(Item::as_value_mut) else {
+            continue;
+        };
+        let Some(requirement) = value.as_str().map(str::to_string) else {
+            continue;
+        };
+
+        match VersionReq::parse(&requirement) {
+            Ok(req) if !req.matches(version) => {
+                if sync {
+                    let decor = value.decor().clone();
+                    *value = Value::from(version.to_string());
+                    *value.decor_mut() = decor;
+                }
+                found.push((key.get().to_string(), requirement));
+            }
+            Ok(_) => {}
+            Err(e) => log::debug!("Skipping unparsable requirement '{}': {}", requirement, e),
+        }
+    }
+}
+
+/// Returns the manifests that may declare a dependency on a package.
+pub fn dependent_manifests(metadata: &cargo_metadata::Metadata, package: &str) -> Vec<PathBuf> {
+    let mut manifests: Vec<PathBuf> = metadata
+        .workspace_packages()
+        .iter()
+        .filter(|p| p.name != package)
+        .map(|p| p.manifest_path.as_std_path().to_path_buf())
+        .collect();
+
+    let root = metadata.workspace_root.as_std_path().join("Cargo.toml");
+    if root.exists() && !manifests.contains(&root) {
+        manifests.push(root);
+    }
+
+    manifests.sort();
+    manifests.dedup();
+    manifests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const MANIFEST: &str = r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+new-crate = { version = "0.3", path = "../new-crate" } # pinned
+alias = { package = "new-crate", version = "0.4", path = "../new-crate" }
+registry = { package = "new-crate", version = "0.1" }
+
+[target.'cfg(unix)'.dev-dependencies]
+new-crate = { version = "0.1", path = "../new-crate" }
+"#;
+
+    fn setup() -> (TempDir, PathBuf) {
+        let temp = TempDir::new().unwrap();
+        let manifest = temp.path().join("Cargo.toml");
+        fs::write(&manifest, MANIFEST).unwrap();
+        (temp, manifest)
+    }
+
+    #[test]
+    fn test_reports_mismatched_path_dependencies() {
+        let (_temp, manifest) = setup();
+        let mut txn = Transaction::new(false);
+
+        let mismatches = check_dependency_versions(
+            std::slice::from_ref(&manifest),
+            "new-crate",
+            &Version::new(0, 4, 2),
+            false,
+            &mut txn,
+        )
+        .unwrap();
+
+        let found: Vec<_> = mismatches
+            .iter()
+            .map(|m| (m.key.as_str(), m.requirement.as_str()))
+            .collect();
+        assert_eq!(found, vec![("new-crate", "0.3"), ("new-crate", "0.1")]);
+        assert!(txn.is_empty());
+    }
+
+    #[test]
+    fn test_sync_rewrites_requirements() {
+        let (_temp, manifest) = setup();
+        let mut txn = Transaction::new(false);
+
+        check_dependency_versions(
+            std::slice::from_ref(&manifest),
+            "new-crate",
+            &Version::new(0, 4, 2),
+            true,
+            &mut txn,
+        )
+        .unwrap();
+        txn.commit().unwrap();
+
+        let result = fs::read_to_string(&manifest).unwrap();
+        assert!(
+            result.contains(r#"new-crate = { version = "0.4.2", path = "../new-crate" } # pinned"#)
+        );
+        assert!(result.contains(r#"version = "0.4", path"#));
+        assert!(result.contains(r#"registry = { package = "new-crate", version = "0.1" }"#));
+        assert_eq!(result.matches(r#"version = "0.4.2""#).count(), 2);
+    }
+}
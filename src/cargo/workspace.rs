@@ -1,49 +1,132 @@
 //! Workspace-level `Cargo.toml` updates.
 //!
 //! Updates workspace manifests when a package is renamed or moved:
-//! - `[workspace.members]` array
+//! - `[workspace.members]` array, adding an explicit entry when a package
+//!   covered by a glob (`crates/*`) moves where no glob reaches
+//! - `workspace.default-members` and `workspace.exclude` paths, including
+//!   paths nested inside the moved package
 //! - `[workspace.dependencies]` keys and paths
+//! - `[workspace.package]` URL fields (when renaming the workspace itself)
+//!
+//! Monorepos that generate the members list into a separate file (merged into
+//! the root manifest by their tooling) can list such workspace fragments:
+//!
+//! ```toml
+//! [workspace.metadata.cargo-rename]
+//! fragments = ["tools/members.toml"]
+//! ```
+//!
+//! Fragments receive the same members, dependency key and path rewrites as
+//! the root manifest, with paths relative to the workspace root.
 //!
 //! Preserves quote styles and normalizes paths to forward slashes.
 
+use crate::cargo::dependency::ManifestUpdate;
+use crate::cargo::manifests::member_glob;
+use crate::cargo::package::replace_url_segments;
 use crate::error::Result;
 use crate::fs::transaction::Transaction;
+use crate::fs::{display_path, normalize_path, shortest_relative};
+use crate::symbols;
 use regex::Regex;
-use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Value};
+
+/// Returns the manifests of workspace members that are missing on disk.
+///
+/// In sparse checkouts, members listed in the workspace may not be
+/// materialized while `cargo metadata` still succeeds.
+pub fn missing_member_manifests(metadata: &cargo_metadata::Metadata) -> Vec<PathBuf> {
+    metadata
+        .workspace_packages()
+        .iter()
+        .map(|p| p.manifest_path.as_std_path())
+        .filter(|path| !path.exists())
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+/// `[workspace.package]` fields that commonly embed the workspace name.
+const WORKSPACE_URL_FIELDS: &[&str] = &["repository", "homepage", "documentation"];
 
 /// Updates workspace manifest when a package is renamed or moved.
 ///
 /// Handles three updates:
-/// 1. Workspace members: `[workspace.members]` paths
+/// 1. Workspace members: `[workspace.members]`, `default-members` and
+///    `exclude` paths
 /// 2. Dependency key: `old-name = ...` → `new-name = ...`
 /// 3. Dependency path: `path = "..."` within definition
 ///
-/// Preserves quote styles (single/double) and normalizes paths to `/`.
-#[allow(clippy::too_many_arguments)]
+/// Members are only updated with `should_update_members`. Preserves quote
+/// styles (single/double) and normalizes paths to `/`.
 pub fn update_workspace_manifest(
     root_path: &Path,
-    old_name: &str,
-    new_name: &str,
-    old_dir: &Path,
-    new_dir: &Path,
+    update: &ManifestUpdate,
     should_update_members: bool,
-    path_changed: bool,
-    name_changed: bool,
     txn: &mut Transaction,
 ) -> Result<()> {
-    let mut content = fs::read_to_string(root_path)?;
+    update_workspace_file(
+        root_path,
+        root_path.parent().unwrap(),
+        update,
+        should_update_members,
+        txn,
+    )
+}
+
+/// Applies the workspace manifest updates to a workspace fragment.
+///
+/// Like [`update_workspace_manifest`], but paths in the fragment are relative
+/// to `update.workspace_root` rather than to the fragment itself, and members
+/// are updated whenever the package moves. A missing fragment is skipped with
+/// a warning, since it may be generated on demand.
+pub fn update_workspace_fragment(
+    fragment: &Path,
+    update: &ManifestUpdate,
+    txn: &mut Transaction,
+) -> Result<()> {
+    if !fragment.is_file() {
+        log::warn!(
+            "Workspace fragment not found, skipping: {}",
+            display_path(fragment)
+        );
+        return Ok(());
+    }
+
+    update_workspace_file(
+        fragment,
+        update.workspace_root,
+        update,
+        update.path_changed,
+        txn,
+    )
+}
+
+/// Rewrites members, dependency keys and paths in a workspace TOML file.
+///
+/// Paths are resolved against `root_dir`.
+fn update_workspace_file(
+    path: &Path,
+    root_dir: &Path,
+    update: &ManifestUpdate,
+    should_update_members: bool,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let ManifestUpdate {
+        old_name,
+        new_name,
+        old_dir,
+        new_dir,
+        name_changed,
+        path_changed,
+        ..
+    } = *update;
+    let mut content = txn.read_file(path)?;
     let original = content.clone();
 
     if should_update_members {
-        let root_dir = root_path.parent().unwrap();
-        let old_rel = pathdiff::diff_paths(old_dir, root_dir)
-            .ok_or_else(|| anyhow::anyhow!("Failed to calculate relative path"))?;
-        let new_rel = pathdiff::diff_paths(new_dir, root_dir)
-            .ok_or_else(|| anyhow::anyhow!("Failed to calculate relative path"))?;
-
-        let old_str = old_rel.to_string_lossy().replace('\\', "/");
-        let new_str = new_rel.to_string_lossy().replace('\\', "/");
+        let old_str = shortest_relative(old_dir, root_dir)?;
+        let new_str = shortest_relative(new_dir, root_dir)?;
 
         // Use regex for proper matching (handles special characters in paths)
         // Match both single and double quotes
@@ -61,8 +144,16 @@ pub fn update_workspace_manifest(
                 })
                 .to_string();
 
-            log::info!("Updated workspace.members: {} → {}", old_str, new_str);
+            log::info!(
+                "Updated workspace.members: {} {} {}",
+                old_str,
+                symbols::arrow(),
+                new_str
+            );
         }
+
+        content = update_path_lists(&content, &old_str, &new_str)?;
+        content = add_unglobbed_member(&content, &old_str, &new_str)?;
     }
 
     // Update workspace.dependencies key name
@@ -73,8 +164,9 @@ pub fn update_workspace_manifest(
                 .replace_all(&content, format!("${{1}}{} = ", new_name))
                 .to_string();
             log::info!(
-                "Renamed workspace dependency key: {} → {}",
+                "Renamed workspace dependency key: {} {} {}",
                 old_name,
+                symbols::arrow(),
                 new_name
             );
         }
@@ -82,23 +174,28 @@ pub fn update_workspace_manifest(
 
     // Update path within the dependency
     if path_changed {
-        let root_dir = root_path.parent().unwrap();
-        let old_rel = pathdiff::diff_paths(old_dir, root_dir)
-            .ok_or_else(|| anyhow::anyhow!("Failed to calculate relative path"))?;
-        let new_rel = pathdiff::diff_paths(new_dir, root_dir)
-            .ok_or_else(|| anyhow::anyhow!("Failed to calculate relative path"))?;
-
-        let old_path = old_rel.to_string_lossy().replace('\\', "/");
-        let new_path = new_rel.to_string_lossy().replace('\\', "/");
-
-        // Match: path = "..." or path = '...'
-        let pattern = format!(r#"(\bpath\s*=\s*)(["']){}(["'])"#, regex::escape(&old_path));
-
-        if let Ok(re) = Regex::new(&pattern)
-            && re.is_match(&content)
+        let old_path = shortest_relative(old_dir, root_dir)?;
+        let new_path = shortest_relative(new_dir, root_dir)?;
+
+        // Match: path = "..." or path = '...', also when written with `..`
+        // detours such as "../workspace/crates/old"
+        let old_dir = normalize_path(old_dir);
+        let re = Regex::new(r#"(\bpath\s*=\s*)(["'])([^"']*)(["'])"#)?;
+        let points_to_old = |value: &str| {
+            value == old_path
+                || (Path::new(value).is_relative()
+                    && normalize_path(&root_dir.join(value)) == old_dir)
+        };
+
+        if re
+            .captures_iter(&content)
+            .any(|caps| points_to_old(&caps[3]))
         {
             content = re
                 .replace_all(&content, |caps: &regex::Captures| {
+                    if !points_to_old(&caps[3]) {
+                        return caps[0].to_string();
+                    }
                     format!(
                         r#"{prefix}{quote}{new}{quote}"#,
                         prefix = &caps[1],
@@ -109,15 +206,180 @@ pub fn update_workspace_manifest(
                 .to_string();
 
             log::info!(
-                "Updated workspace dependency path: {} → {}",
+                "Updated workspace dependency path: {} {} {}",
                 old_path,
+                symbols::arrow(),
                 new_path
             );
         }
     }
 
     if content != original {
-        txn.update_file(root_path.to_path_buf(), content)?;
+        txn.update_file(path.to_path_buf(), content)?;
+    }
+
+    Ok(())
+}
+
+/// `[workspace]` arrays whose entries are paths that follow a moved package.
+const PATH_LISTS: &[&str] = &["default-members", "exclude"];
+
+/// Rewrites `workspace.default-members` and `workspace.exclude` entries that
+/// are `old_path` or lie inside it, e.g. an excluded `old/fuzz` workspace.
+///
+/// Without this, `cargo build` at the root picks the wrong default set after
+/// a move. Fragments list the arrays at the top level instead of under
+/// `[workspace]`. Literal (single-quoted) strings stay literal.
+fn update_path_lists(content: &str, old_path: &str, new_path: &str) -> Result<String> {
+    let mut doc: DocumentMut = content.parse()?;
+    let workspace = if doc.contains_key("workspace") {
+        &mut doc["workspace"]
+    } else {
+        doc.as_item_mut()
+    };
+
+    let mut changed = false;
+    for key in PATH_LISTS {
+        let Some(entries) = workspace.get_mut(key).and_then(Item::as_array_mut) else {
+            continue;
+        };
+
+        for entry in entries.iter_mut() {
+            let Some(current) = entry.as_str() else {
+                continue;
+            };
+            let updated = match current.strip_prefix(old_path) {
+                Some("") => new_path.to_string(),
+                Some(rest) if rest.starts_with('/') => format!("{}{}", new_path, rest),
+                _ => continue,
+            };
+
+            log::info!(
+                "Updated workspace.{}: {} {} {}",
+                key,
+                current,
+                symbols::arrow(),
+                updated
+            );
+            let literal = entry
+                .clone()
+                .decorated("", "")
+                .to_string()
+                .starts_with('\'');
+            let decor = entry.decor().clone();
+            *entry = if literal && !updated.contains('\'') {
+                format!("'{}'", updated).parse()?
+            } else {
+                Value::from(updated)
+            };
+            *entry.decor_mut() = decor;
+            changed = true;
+        }
+    }
+
+    Ok(if changed {
+        doc.to_string()
+    } else {
+        content.to_string()
+    })
+}
+
+/// Adds `new_path` to `members` if the package was only covered by a glob
+/// member (`crates/*`) and no member covers its new location.
+///
+/// Globs are kept, since they usually cover other packages. A package moved
+/// into a glob-covered directory keeps its explicit entry; cargo accepts a
+/// member listed both ways.
+fn add_unglobbed_member(content: &str, old_path: &str, new_path: &str) -> Result<String> {
+    let mut doc: DocumentMut = content.parse()?;
+    let workspace = if doc.contains_key("workspace") {
+        &mut doc["workspace"]
+    } else {
+        doc.as_item_mut()
+    };
+    let Some(members) = workspace.get_mut("members").and_then(Item::as_array_mut) else {
+        return Ok(content.to_string());
+    };
+
+    let entries: Vec<&str> = members.iter().filter_map(Value::as_str).collect();
+    let covers = |path: &str| {
+        entries.iter().any(|entry| {
+            *entry == path || member_glob(entry).is_some_and(|glob| glob.is_match(path))
+        })
+    };
+    if !covers(old_path) || covers(new_path) {
+        return Ok(content.to_string());
+    }
+
+    log::info!(
+        "Added workspace member {} (no glob member covers it)",
+        new_path
+    );
+    // Keep one entry per line in multi-line arrays
+    let prefix = members
+        .iter()
+        .last()
+        .and_then(|last| last.decor().prefix())
+        .and_then(|prefix| prefix.as_str())
+        .filter(|prefix| prefix.contains('\n'))
+        .map(str::to_string);
+    members.push(new_path);
+    if let Some(prefix) = prefix
+        && let Some(added) = members.iter_mut().last()
+    {
+        added.decor_mut().set_prefix(prefix);
+    }
+
+    Ok(doc.to_string())
+}
+
+/// Rewrites `[workspace.package]` URL fields that embed the workspace name.
+///
+/// Replaces URL path segments equal to `old_name` in `repository`, `homepage`
+/// and `documentation`, as `--set-repository` does for a package; hosts and
+/// segments merely containing the name are left alone. Preserves formatting
+/// and comments.
+pub fn update_workspace_package_fields(
+    root_path: &Path,
+    old_name: &str,
+    new_name: &str,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let content = txn.read_file(root_path)?;
+    let mut doc: DocumentMut = content.parse()?;
+
+    let Some(package) = doc.get_mut("workspace").and_then(|w| w.get_mut("package")) else {
+        return Ok(());
+    };
+
+    let mut changed = false;
+    for field in WORKSPACE_URL_FIELDS {
+        let Some(value) = package.get_mut(field).and_then(Item::as_value_mut) else {
+            continue;
+        };
+        let Some(current) = value.as_str() else {
+            continue;
+        };
+
+        let updated = replace_url_segments(current, old_name, new_name);
+        if updated != current {
+            log::info!(
+                "Updated workspace.package.{}: {} {} {}",
+                field,
+                current,
+                symbols::arrow(),
+                updated
+            );
+
+            let decor = value.decor().clone();
+            *value = Value::from(updated);
+            *value.decor_mut() = decor;
+            changed = true;
+        }
+    }
+
+    if changed {
+        txn.update_file(root_path.to_path_buf(), doc.to_string())?;
     }
 
     Ok(())
@@ -145,13 +407,8 @@ members = ["crates/old-crate", "crates/other"]
         let mut txn = Transaction::new(false);
         update_workspace_manifest(
             &workspace_toml,
-            "old-crate",
-            "new-crate",
-            &old_dir,
-            &new_dir,
-            true, // update members
-            true, // path changed
-            true, // name changed
+            &ManifestUpdate::new("old-crate", "new-crate", &old_dir, &new_dir, temp.path()),
+            true,
             &mut txn,
         )
         .unwrap();
@@ -163,6 +420,131 @@ members = ["crates/old-crate", "crates/other"]
         assert!(!result.contains("old-crate"));
     }
 
+    #[test]
+    fn test_update_default_members_and_exclude() {
+        let temp = TempDir::new().unwrap();
+        let workspace_toml = temp.path().join("Cargo.toml");
+
+        fs::write(
+            &workspace_toml,
+            r#"[workspace]
+members = ["crates/old-crate", "crates/other"]
+default-members = ["crates/old-crate"] # build by default
+exclude = ['crates/old-crate/fuzz', "crates/old-crate-extras"]
+"#,
+        )
+        .unwrap();
+
+        let mut txn = Transaction::new(false);
+        update_workspace_manifest(
+            &workspace_toml,
+            &ManifestUpdate::new(
+                "old-crate",
+                "new-crate",
+                &temp.path().join("crates/old-crate"),
+                &temp.path().join("libs/new-crate"),
+                temp.path(),
+            ),
+            true,
+            &mut txn,
+        )
+        .unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&workspace_toml).unwrap(),
+            r#"[workspace]
+members = ["libs/new-crate", "crates/other"]
+default-members = ["libs/new-crate"] # build by default
+exclude = ['libs/new-crate/fuzz', "crates/old-crate-extras"]
+"#
+        );
+    }
+
+    #[test]
+    fn test_move_out_of_glob_member() {
+        let temp = TempDir::new().unwrap();
+        let workspace_toml = temp.path().join("Cargo.toml");
+        let update = |old: &str, new: &str| {
+            let mut txn = Transaction::new(false);
+            update_workspace_manifest(
+                &workspace_toml,
+                &ManifestUpdate::new(
+                    "foo",
+                    "foo",
+                    &temp.path().join(old),
+                    &temp.path().join(new),
+                    temp.path(),
+                ),
+                true,
+                &mut txn,
+            )
+            .unwrap();
+            txn.commit().unwrap();
+            fs::read_to_string(&workspace_toml).unwrap()
+        };
+
+        fs::write(
+            &workspace_toml,
+            "[workspace]\nmembers = [\n    \"crates/*\",\n    \"tools/cli\",\n]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            update("crates/foo", "libs/foo"),
+            "[workspace]\nmembers = [\n    \"crates/*\",\n    \"tools/cli\",\n    \"libs/foo\",\n]\n"
+        );
+
+        // Still covered by the glob, or moved back under it: nothing to add
+        fs::write(&workspace_toml, "[workspace]\nmembers = [\"crates/*\"]\n").unwrap();
+        assert_eq!(
+            update("crates/foo", "crates/bar"),
+            "[workspace]\nmembers = [\"crates/*\"]\n"
+        );
+        fs::write(
+            &workspace_toml,
+            "[workspace]\nmembers = [\"crates/*\", \"libs/foo\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            update("libs/foo", "crates/foo"),
+            "[workspace]\nmembers = [\"crates/*\", \"crates/foo\"]\n"
+        );
+    }
+
+    #[test]
+    fn test_update_workspace_fragment() {
+        let temp = TempDir::new().unwrap();
+        let fragment = temp.path().join("tools/members.toml");
+        fs::create_dir_all(fragment.parent().unwrap()).unwrap();
+        fs::write(
+            &fragment,
+            r#"members = ["crates/old-crate", "crates/other"]
+
+[dependencies]
+old-crate = { path = "crates/old-crate" }
+"#,
+        )
+        .unwrap();
+
+        let old_dir = temp.path().join("crates/old-crate");
+        let new_dir = temp.path().join("crates/new-crate");
+        let update = ManifestUpdate::new("old-crate", "new-crate", &old_dir, &new_dir, temp.path());
+        let mut txn = Transaction::new(false);
+        update_workspace_fragment(&fragment, &update, &mut txn).unwrap();
+        update_workspace_fragment(&temp.path().join("tools/missing.toml"), &update, &mut txn)
+            .unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&fragment).unwrap(),
+            r#"members = ["crates/new-crate", "crates/other"]
+
+[dependencies]
+new-crate = { path = "crates/new-crate" }
+"#
+        );
+    }
+
     #[test]
     fn test_update_workspace_members_single_quotes() {
         let temp = TempDir::new().unwrap();
@@ -179,12 +561,7 @@ members = ['crates/old-crate', 'crates/other']
         let mut txn = Transaction::new(false);
         update_workspace_manifest(
             &workspace_toml,
-            "old-crate",
-            "new-crate",
-            &old_dir,
-            &new_dir,
-            true,
-            true,
+            &ManifestUpdate::new("old-crate", "new-crate", &old_dir, &new_dir, temp.path()),
             true,
             &mut txn,
         )
@@ -212,13 +589,8 @@ old-crate = { path = "crates/old-crate" }
         let mut txn = Transaction::new(false);
         update_workspace_manifest(
             &workspace_toml,
-            "old-crate",
-            "new-crate",
-            &old_dir,
-            &new_dir,
-            false, // don't update members
-            true,  // path changed
-            true,  // name changed
+            &ManifestUpdate::new("old-crate", "new-crate", &old_dir, &new_dir, temp.path()),
+            false,
             &mut txn,
         )
         .unwrap();
@@ -245,12 +617,7 @@ members = ["crates/old-crate", 'crates/other']
         let mut txn = Transaction::new(false);
         update_workspace_manifest(
             &workspace_toml,
-            "old-crate",
-            "new-crate",
-            &old_dir,
-            &new_dir,
-            true,
-            true,
+            &ManifestUpdate::new("old-crate", "new-crate", &old_dir, &new_dir, temp.path()),
             true,
             &mut txn,
         )
@@ -280,12 +647,7 @@ members = ["crates/different"]
         let mut txn = Transaction::new(false);
         update_workspace_manifest(
             &workspace_toml,
-            "old-crate",
-            "new-crate",
-            &old_dir,
-            &new_dir,
-            true,
-            true,
+            &ManifestUpdate::new("old-crate", "new-crate", &old_dir, &new_dir, temp.path()),
             true,
             &mut txn,
         )
@@ -294,4 +656,30 @@ members = ["crates/different"]
         // Should not stage any changes if no match
         assert_eq!(txn.len(), 0);
     }
+
+    #[test]
+    fn test_update_workspace_package_fields() {
+        let temp = TempDir::new().unwrap();
+        let workspace_toml = temp.path().join("Cargo.toml");
+
+        let input = r#"[workspace.package]
+repository = "https://github.com/acme/my-ws"  # canonical repo
+homepage = "https://my-ws.dev/my-ws-tools"
+documentation = "https://docs.rs/my-ws/latest"
+license = "MIT"
+"#;
+        fs::write(&workspace_toml, input).unwrap();
+
+        let mut txn = Transaction::new(false);
+        update_workspace_package_fields(&workspace_toml, "my-ws", "new-ws", &mut txn).unwrap();
+        txn.commit().unwrap();
+
+        let result = fs::read_to_string(&workspace_toml).unwrap();
+        assert!(
+            result.contains(r#"repository = "https://github.com/acme/new-ws"  # canonical repo"#)
+        );
+        assert!(result.contains(r#"homepage = "https://my-ws.dev/my-ws-tools""#));
+        assert!(result.contains(r#"documentation = "https://docs.rs/new-ws/latest""#));
+        assert!(result.contains(r#"license = "MIT""#));
+    }
 }
@@ -2,7 +2,8 @@
 //!
 //! Defines the CLI structure using `clap`. Actual rename logic is in `steps/rename.rs`.
 
-use clap::{ColorChoice, Parser, Subcommand};
+use clap::{Args, ColorChoice, CommandFactory, Parser, Subcommand};
+use std::ffi::OsString;
 
 /// Top-level cargo subcommand.
 ///
@@ -20,6 +21,8 @@ pub struct CargoCli {
     pub command: CargoCommand,
 
     /// Control color output
+    ///
+    /// Falls back to CARGO_TERM_COLOR, then NO_COLOR, when set to auto.
     #[arg(
         long,
         value_name = "WHEN",
@@ -49,8 +52,104 @@ pub struct CargoCli {
         display_order = 102
     )]
     pub verbose: u8,
+
+    /// Show absolute, OS-native paths instead of workspace-relative ones
+    #[arg(long, global = true, display_order = 103)]
+    pub absolute_paths: bool,
+
+    /// Emit GitHub Actions annotations (automatic when GITHUB_ACTIONS=true)
+    #[arg(long, global = true, display_order = 104)]
+    pub annotate: bool,
+
+    /// Timeout in seconds for external commands like `cargo metadata` (0 disables)
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = crate::process::DEFAULT_TIMEOUT_SECS,
+        global = true,
+        display_order = 105
+    )]
+    pub timeout: u64,
+
+    /// Scan all hidden files and directories (.github/, .config/)
+    #[arg(long, global = true, display_order = 106)]
+    pub include_hidden: bool,
+
+    /// Skip all hidden files, including .env files
+    #[arg(
+        long,
+        global = true,
+        conflicts_with = "include_hidden",
+        display_order = 107
+    )]
+    pub exclude_hidden: bool,
+
+    /// Print failures as a JSON object on stderr
+    ///
+    /// Implied by `rename --json`.
+    #[arg(long, global = true, display_order = 108)]
+    pub json_errors: bool,
+
+    /// Use ASCII markers instead of emoji and symbols (automatic on non-UTF-8 terminals)
+    #[arg(long, global = true, display_order = 109)]
+    pub ascii: bool,
 }
 
+impl CargoCli {
+    /// Parses `args` like [`Parser::try_parse_from`], reading a subcommand
+    /// name as `OLD_NAME` if the rest doesn't parse as that subcommand.
+    ///
+    /// `cargo rename log new-log` renames a package named `log`, while
+    /// `cargo rename log` still shows the history. For a subcommand whose
+    /// arguments fit too (`validate-name`, `migrate-imports`), separate the
+    /// names with `--` (`cargo rename -- check new-check`) or use `--at`.
+    pub fn try_parse_args<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+        let error = match Self::try_parse_from(&args) {
+            Ok(cli) => return Ok(cli),
+            Err(error) => error,
+        };
+
+        let command = Self::command();
+        let Some(rename) = command.find_subcommand("rename") else {
+            return Err(error);
+        };
+        let Some(index) = args.iter().skip(2).position(|arg| {
+            arg.to_str()
+                .is_some_and(|arg| rename.find_subcommand(arg).is_some())
+        }) else {
+            return Err(error);
+        };
+        let index = index + 2;
+
+        // Any other token would be a subcommand again
+        let name = args[index].to_string_lossy().into_owned();
+        let mut args = args;
+        args[index] = OsString::from(format!("{SHADOWED}{name}"));
+        let Ok(mut cli) = Self::try_parse_from(&args) else {
+            return Err(error);
+        };
+        let CargoCommand::Rename(RenameCommand {
+            args: Some(rename), ..
+        }) = &mut cli.command
+        else {
+            return Err(error);
+        };
+        if rename.old_name.strip_prefix(SHADOWED) != Some(name.as_str()) {
+            return Err(error);
+        }
+        rename.old_name = name;
+        Ok(cli)
+    }
+}
+
+/// Prefix hiding a subcommand name passed as `OLD_NAME` from the parser.
+const SHADOWED: &str = "\u{0}";
+
 #[derive(Subcommand)]
 pub enum CargoCommand {
     /// Perform a coordinated, all-or-nothing rename of a Cargo package
@@ -67,5 +166,202 @@ pub enum CargoCommand {
     /// By default, only the package name is renamed. Use --move to relocate the directory.
     /// No files are modified until you confirm the operation."
     #[clap(verbatim_doc_comment)]
-    Rename(crate::steps::rename::RenameArgs),
+    Rename(RenameCommand),
+}
+
+/// The `rename` command: either a package rename or one of its subcommands.
+///
+/// Subcommand names take precedence over `OLD_NAME`, unless the arguments
+/// don't fit the subcommand (see [`CargoCli::try_parse_args`]).
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct RenameCommand {
+    #[command(subcommand)]
+    pub action: Option<RenameAction>,
+
+    #[command(flatten)]
+    pub args: Option<crate::steps::rename::RenameArgs>,
+}
+
+#[derive(Subcommand)]
+pub enum RenameAction {
+    /// Rename the workspace itself
+    ///
+    /// Updates [workspace.package] URLs (repository, homepage, documentation)
+    /// and workspace-level documentation. With --members, also renames member
+    /// packages prefixed with the old workspace name.
+    #[clap(verbatim_doc_comment)]
+    Workspace(crate::steps::workspace::WorkspaceArgs),
+
+    /// Check files for references to crates renamed in this workspace
+    ///
+    /// Looks up the renamed crates in .cargo-rename/history.json and reports
+    /// every reference to an old name in the given Rust files and manifests
+    /// as FILE:LINE:COLUMN. Exits with status 1 if any is found, for use as a
+    /// pre-commit hook, which appends the staged files:
+    ///   cargo rename check --files src/lib.rs Cargo.toml
+    #[clap(verbatim_doc_comment)]
+    Check(crate::steps::check::CheckArgs),
+
+    /// Show locally recorded usage statistics
+    ///
+    /// Statistics are only collected for runs with --record-stats and are
+    /// stored in .cargo-rename/stats.json. Nothing leaves your machine.
+    #[clap(verbatim_doc_comment)]
+    Stats(crate::steps::stats::StatsArgs),
+
+    /// List workspace members and the members depending on them
+    ///
+    /// Shows each member's directory and how many workspace crates depend on
+    /// it directly or transitively, i.e. what renaming it would touch. Pass
+    /// --output json for the full lists.
+    #[clap(verbatim_doc_comment)]
+    List(crate::steps::list::ListArgs),
+
+    /// Show the history of renames applied to this workspace
+    ///
+    /// Every applied rename is recorded in .cargo-rename/history.json with
+    /// its directories, a timestamp and the git HEAD it was made on.
+    #[clap(verbatim_doc_comment)]
+    Log(crate::steps::log::LogArgs),
+
+    /// Generate a synthetic workspace for reproducing rename bugs
+    ///
+    /// Members member-1 to member-N form a dependency chain. Add manifest
+    /// shapes with --with-target-deps and --with-aliases.
+    #[clap(verbatim_doc_comment)]
+    Fixture(crate::steps::fixture::FixtureArgs),
+
+    /// Check whether a name can be used as a package name
+    ///
+    /// Runs the same checks as a rename (crates.io naming rules, reserved
+    /// names, Rust keywords) and exits with status 1 if any fails. Use
+    /// --check-crates-io to also reject names already published.
+    #[clap(verbatim_doc_comment)]
+    ValidateName(crate::steps::validate_name::ValidateNameArgs),
+
+    /// Make all dependents declare a package the same way
+    ///
+    /// Rewrites aliased keys (`alias = { package = "NAME" }`) to the package
+    /// name, updating the dependents' Rust sources, and switches path
+    /// dependencies to `workspace = true` where [workspace.dependencies]
+    /// declares the package. The package itself is not renamed.
+    #[clap(verbatim_doc_comment)]
+    NormalizeDeps(crate::steps::normalize_deps::NormalizeDepsArgs),
+
+    /// Update a project outside the workspace to a renamed dependency
+    ///
+    /// Rewrites dependency keys and `package` fields naming OLD_NAME in every
+    /// Cargo.toml under --path, and Rust references (`use old_name::...`)
+    /// with the same rules as a rename. Nothing is moved.
+    #[clap(verbatim_doc_comment)]
+    MigrateImports(crate::steps::migrate_imports::MigrateImportsArgs),
+
+    /// Re-apply or revert the source edits recorded with --trace-edits
+    ///
+    /// Edits are replayed per file in trace order (reverse order with
+    /// --revert). Every edit is checked against the file first, so nothing is
+    /// written if a file changed since the trace was recorded.
+    #[clap(verbatim_doc_comment)]
+    ReplayTrace(crate::steps::replay_trace::ReplayTraceArgs),
+
+    /// Print the JSON schema of the `--json` summary
+    ///
+    /// The summary carries a `schema_version`, bumped on incompatible
+    /// changes. Vendor the schema to validate the output in external tooling.
+    #[clap(verbatim_doc_comment)]
+    Schema,
+}
+
+/// Resolves the effective color choice.
+///
+/// Precedence, highest first:
+///
+/// 1. `--color always|never`
+/// 2. `CARGO_TERM_COLOR=always|never`
+/// 3. `NO_COLOR` (any non-empty value disables color)
+/// 4. Terminal detection (`auto`)
+pub fn resolve_color(
+    flag: ColorChoice,
+    cargo_term_color: Option<&str>,
+    no_color: Option<&str>,
+) -> ColorChoice {
+    if flag != ColorChoice::Auto {
+        return flag;
+    }
+
+    match cargo_term_color.map(str::trim) {
+        Some("always") => return ColorChoice::Always,
+        Some("never") => return ColorChoice::Never,
+        _ => {}
+    }
+
+    if no_color.is_some_and(|v| !v.is_empty()) {
+        return ColorChoice::Never;
+    }
+
+    ColorChoice::Auto
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn old_name(args: &[&str]) -> Option<String> {
+        match CargoCli::try_parse_args(args).unwrap().command {
+            CargoCommand::Rename(RenameCommand {
+                args: Some(args), ..
+            }) => Some(args.old_name),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_subcommand_name_as_old_name() {
+        assert_eq!(
+            old_name(&["cargo", "rename", "log", "new-log", "--dry-run"]),
+            Some("log".to_string())
+        );
+        assert_eq!(
+            old_name(&["cargo", "rename", "--color", "never", "list", "new-list"]),
+            Some("list".to_string())
+        );
+        assert_eq!(
+            old_name(&["cargo", "rename", "--dry-run", "--", "validate-name", "x"]),
+            Some("validate-name".to_string())
+        );
+
+        // Arguments fitting the subcommand run it
+        assert_eq!(old_name(&["cargo", "rename", "log"]), None);
+        assert_eq!(old_name(&["cargo", "rename", "validate-name", "x"]), None);
+        assert!(CargoCli::try_parse_args(["cargo", "rename", "log", "--bogus"]).is_err());
+    }
+
+    #[test]
+    fn test_resolve_color_flag_wins() {
+        assert_eq!(
+            resolve_color(ColorChoice::Always, Some("never"), Some("1")),
+            ColorChoice::Always
+        );
+    }
+
+    #[test]
+    fn test_resolve_color_cargo_term_color_before_no_color() {
+        assert_eq!(
+            resolve_color(ColorChoice::Auto, Some("always"), Some("1")),
+            ColorChoice::Always
+        );
+        assert_eq!(
+            resolve_color(ColorChoice::Auto, Some("auto"), Some("1")),
+            ColorChoice::Never
+        );
+    }
+
+    #[test]
+    fn test_resolve_color_defaults_to_auto() {
+        assert_eq!(
+            resolve_color(ColorChoice::Auto, None, Some("")),
+            ColorChoice::Auto
+        );
+    }
 }
@@ -0,0 +1,137 @@
+//! Workspace configuration in `[workspace.metadata.cargo-rename]`.
+//!
+//! All settings live in one table of the workspace root `Cargo.toml`:
+//!
+//! ```toml
+//! [workspace.metadata.cargo-rename]
+//! fragments = ["tools/members.toml"]
+//!
+//! [workspace.metadata.cargo-rename.html]
+//! comments = true
+//!
+//! [workspace.metadata.cargo-rename.naming]
+//! pattern = "^acme-[a-z0-9-]+$"
+//!
+//! [[workspace.metadata.cargo-rename.hooks]]
+//! glob = "**/*.bzl"
+//! command = ["python3", "tools/rename_hook.py"]
+//! ```
+//!
+//! [`WorkspaceConfig::load`] reads and validates the table once per rename;
+//! each setting is documented with the module using it: workspace fragments
+//! in [`crate::cargo::workspace`], the HTML policy in [`crate::rewrite::html`],
+//! the naming policy in [`crate::verify::policy`] and hooks in
+//! [`crate::rewrite::hooks`].
+
+use crate::error::Result;
+use crate::rewrite::{HookConfig, HtmlPolicy};
+use crate::verify::NamingPolicy;
+use cargo_metadata::Metadata;
+use regex::Regex;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// The `[workspace.metadata.cargo-rename]` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceConfig {
+    /// Workspace fragments, relative to the workspace root.
+    pub fragments: Vec<PathBuf>,
+    /// Policy for HTML embedded in Markdown documentation.
+    pub html: HtmlPolicy,
+    /// Policy for new package names.
+    pub naming: Option<NamingPolicy>,
+    /// Rewrite hooks for custom file types.
+    pub hooks: Vec<HookConfig>,
+}
+
+impl WorkspaceConfig {
+    /// Reads the configuration of the workspace described by `metadata`.
+    ///
+    /// Returns the defaults if the table is missing.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the table does not deserialize, a hook has an empty command
+    /// or the naming pattern is not a valid regex.
+    pub fn load(metadata: &Metadata) -> Result<Self> {
+        let Some(config) = metadata.workspace_metadata.get("cargo-rename") else {
+            return Ok(Self::default());
+        };
+
+        let config: Self = serde_json::from_value(config.clone())
+            .map_err(|e| anyhow::anyhow!("Invalid [workspace.metadata.cargo-rename]: {}", e))?;
+
+        if let Some(hook) = config.hooks.iter().find(|h| h.command.is_empty()) {
+            return Err(anyhow::anyhow!("Hook for '{}' has an empty command", hook.glob).into());
+        }
+        if let Some(pattern) = config.naming.as_ref().and_then(|n| n.pattern.as_ref()) {
+            Regex::new(pattern)?;
+        }
+
+        Ok(config)
+    }
+
+    /// Returns the workspace fragments as absolute paths.
+    pub fn fragment_paths(&self, workspace_root: &Path) -> Vec<PathBuf> {
+        self.fragments
+            .iter()
+            .map(|path| workspace_root.join(path))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: serde_json::Value) -> Result<WorkspaceConfig> {
+        let mut metadata: Metadata = serde_json::from_value(serde_json::json!({
+            "packages": [],
+            "workspace_members": [],
+            "resolve": null,
+            "target_directory": "/ws/target",
+            "version": 1,
+            "workspace_root": "/ws",
+        }))
+        .unwrap();
+        metadata.workspace_metadata = serde_json::json!({ "cargo-rename": json });
+        WorkspaceConfig::load(&metadata)
+    }
+
+    #[test]
+    fn test_load() {
+        let loaded = config(serde_json::json!({
+            "fragments": ["tools/members.toml"],
+            "html": { "comments": true },
+            "naming": { "pattern": "^acme-" },
+            "hooks": [{ "glob": "*.bzl", "command": ["hook"] }],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            loaded.fragment_paths(Path::new("/ws")),
+            [PathBuf::from("/ws/tools/members.toml")]
+        );
+        assert!(loaded.html.comments);
+        assert_eq!(
+            loaded.html.skip_elements,
+            HtmlPolicy::default().skip_elements
+        );
+        assert_eq!(loaded.naming.unwrap().pattern.as_deref(), Some("^acme-"));
+        assert_eq!(loaded.hooks[0].command, ["hook"]);
+
+        assert_eq!(
+            config(serde_json::json!({})).unwrap(),
+            WorkspaceConfig::default()
+        );
+        assert!(config(serde_json::json!({ "html": { "unknown": 1 } })).is_err());
+        assert!(config(serde_json::json!({ "naming": { "pattern": "(" } })).is_err());
+        assert!(
+            config(serde_json::json!({ "hooks": [{ "glob": "*", "command": [] }] }))
+                .unwrap_err()
+                .to_string()
+                .contains("empty command")
+        );
+    }
+}
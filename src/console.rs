@@ -0,0 +1,165 @@
+//! User-facing output of the rename steps.
+//!
+//! Report sections and warnings printed while a rename is staged or applied
+//! go through [`outln!`] and [`errln!`]. By default they are printed to
+//! stdout and stderr like `println!`/`eprintln!`. Within [`capture`], they
+//! are collected instead, without color codes, so the library API can hand
+//! them to a reporter while the standard streams stay untouched.
+//!
+//! With `rename --json`, [`set_stdout_to_stderr`] sends the report to stderr
+//! as well, and [`write_result`] prints the summary as the only output on
+//! stdout.
+
+use regex::Regex;
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static STDOUT_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static CAPTURED: RefCell<Option<Vec<Line>>> = const { RefCell::new(None) };
+}
+
+/// A line of output collected by [`capture`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    /// The text, without color codes or trailing newline.
+    pub text: String,
+    /// `true` for lines printed to stderr (warnings and errors).
+    pub stderr: bool,
+}
+
+/// Runs `f`, collecting the lines it prints instead of printing them.
+///
+/// Nested captures collect into the outermost one.
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<Line>) {
+    if is_capturing() {
+        return (f(), Vec::new());
+    }
+
+    CAPTURED.with(|captured| *captured.borrow_mut() = Some(Vec::new()));
+    let result = f();
+    let lines = CAPTURED.with(|captured| captured.borrow_mut().take().unwrap_or_default());
+    (result, lines)
+}
+
+/// Returns `true` within [`capture`], where nothing may be printed.
+pub fn is_capturing() -> bool {
+    CAPTURED.with(|captured| captured.borrow().is_some())
+}
+
+/// Prints the output of [`outln!`] on stderr, keeping stdout for
+/// [`write_result`].
+pub fn set_stdout_to_stderr(enabled: bool) {
+    STDOUT_TO_STDERR.store(enabled, Ordering::Relaxed);
+}
+
+/// Prints `text` to stdout or stderr, or collects it if capturing.
+pub fn write_line(text: String, stderr: bool) {
+    write(
+        text,
+        stderr,
+        stderr || STDOUT_TO_STDERR.load(Ordering::Relaxed),
+    );
+}
+
+/// Prints a machine-readable result on stdout, even after
+/// [`set_stdout_to_stderr`], or collects it if capturing.
+pub fn write_result(text: String) {
+    write(text, false, false);
+}
+
+/// Prints an interactive prompt without a trailing newline, on the stream
+/// [`outln!`] uses.
+pub fn write_prompt(text: String) -> io::Result<()> {
+    if STDOUT_TO_STDERR.load(Ordering::Relaxed) {
+        eprint!("{}", text);
+        io::stderr().flush()
+    } else {
+        print!("{}", text);
+        io::stdout().flush()
+    }
+}
+
+fn write(text: String, stderr: bool, to_stderr: bool) {
+    let text = CAPTURED.with(|captured| match captured.borrow_mut().as_mut() {
+        Some(lines) => {
+            let ansi = Regex::new(r"\x1b\[[0-9;]*m").expect("valid ANSI pattern");
+            // `println!("\n{}", heading)` separates sections with a blank line
+            for line in text.split('\n') {
+                lines.push(Line {
+                    text: ansi.replace_all(line, "").into_owned(),
+                    stderr,
+                });
+            }
+            None
+        }
+        None => Some(text),
+    });
+
+    match text {
+        Some(text) if to_stderr => eprintln!("{}", text),
+        Some(text) => println!("{}", text),
+        None => {}
+    }
+}
+
+/// Like `println!`, but collected within [`capture`].
+macro_rules! outln {
+    () => {
+        $crate::console::write_line(String::new(), false)
+    };
+    ($($arg:tt)*) => {
+        $crate::console::write_line(format!($($arg)*), false)
+    };
+}
+
+/// Like `eprintln!`, but collected within [`capture`].
+macro_rules! errln {
+    () => {
+        $crate::console::write_line(String::new(), true)
+    };
+    ($($arg:tt)*) => {
+        $crate::console::write_line(format!($($arg)*), true)
+    };
+}
+
+pub(crate) use {errln, outln};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_collects_lines_without_colors() {
+        let (value, lines) = capture(|| {
+            outln!("\n{}", "\x1b[1mHeading:\x1b[0m");
+            errln!("{} careful", "\x1b[1;33mwarning:\x1b[0m");
+            7
+        });
+
+        assert_eq!(value, 7);
+        assert_eq!(
+            lines,
+            [
+                Line {
+                    text: String::new(),
+                    stderr: false
+                },
+                Line {
+                    text: "Heading:".to_string(),
+                    stderr: false
+                },
+                Line {
+                    text: "warning: careful".to_string(),
+                    stderr: true
+                },
+            ]
+        );
+
+        // Nothing is collected once the capture ends
+        let ((), lines) = capture(|| ());
+        assert!(lines.is_empty());
+    }
+}
@@ -1,21 +1,50 @@
 //! Error types for cargo-rename.
 //!
 //! All operations return `Result<T>` which aliases `Result<T, RenameError>`.
+//! With `--json-errors`, [`report`] prints failures as an [`ErrorReport`].
 
+use crate::fs::copy::format_bytes;
+use crate::fs::transaction::AffectedOperation;
+use crate::fs::{PartialFailure, display_path};
+use crate::symbols;
+use serde::Serialize;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use thiserror::Error;
 
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
 /// Errors from rename operations.
+///
+/// New variants may be added in minor releases.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum RenameError {
-    /// Package not found in workspace.
-    #[error("Package '{0}' not found")]
-    PackageNotFound(String),
+    /// Package not found in workspace, with close matches (if any).
+    #[error("Package '{0}' not found{hint}", hint = did_you_mean(.1))]
+    PackageNotFound(String, Vec<String>),
+
+    /// Package not found, but the rename history knows its new name.
+    #[error("Package '{0}' not found: it was renamed to '{1}' {2}")]
+    PackageRenamed(String, String, String),
 
     /// Target directory already exists.
     #[error("Target directory already exists: {0}")]
     DirectoryExists(PathBuf),
 
+    /// The moved directory contains other workspace packages.
+    #[error(
+        "Directory {0} also contains {packages}; moving it would relocate them too",
+        packages = .1.join(", ")
+    )]
+    SharedDirectory(PathBuf, Vec<String>),
+
+    /// A workspace member is not checked out (e.g. sparse checkout).
+    #[error(
+        "Workspace member is not checked out: {0} (skip it by dropping --strict-materialization)"
+    )]
+    NotMaterialized(PathBuf),
+
     /// Invalid package name.
     #[error("Invalid package name '{0}': {1}")]
     InvalidName(String, String),
@@ -24,18 +53,80 @@ pub enum RenameError {
     #[error("Invalid path '{0}': {1}")]
     InvalidPath(String, String),
 
+    /// A staged manifest edit produced invalid TOML.
+    #[error("Edited manifest is not valid TOML: {0}\n{1}")]
+    InvalidManifest(String, String),
+
+    /// `cargo metadata` resolved another workspace root than the one pinned
+    /// with `--workspace-root`.
+    #[error("Workspace root is {1}, not {0} as pinned with --workspace-root")]
+    WorkspaceRootMismatch(PathBuf, PathBuf),
+
+    /// A manifest or other file that must be edited is not valid UTF-8.
+    #[error("File is not valid UTF-8: {0}")]
+    NotUtf8(PathBuf),
+
+    /// An external command (`cargo`, `git`) did not finish in time.
+    #[error("Command `{0}` timed out after {1}s (raise it with --timeout <SECS>, or 0 to disable)")]
+    ExternalCommandTimeout(String, u64),
+
+    /// The destination filesystem of a move cannot fit the package.
+    #[error(
+        "Not enough free space to move into {0}: {needed} needed, {available} available",
+        needed = format_bytes(*.1),
+        available = format_bytes(*.2)
+    )]
+    InsufficientSpace(PathBuf, u64, u64),
+
+    /// Files of a moved package would exceed the platform's path length limit.
+    #[error(
+        "{count} path{s} would exceed the {1}-character limit after the move \
+         (pass --allow-long-paths if long paths are enabled):\n{list}",
+        count = .0.len(),
+        s = if .0.len() == 1 { "" } else { "s" },
+        list = path_list(.0)
+    )]
+    PathTooLong(Vec<PathBuf>, usize),
+
+    /// A rename hook exited with an error or produced invalid output.
+    #[error("Rename hook `{0}` failed: {1}")]
+    HookFailed(String, String),
+
+    /// `cargo check` failed after rehearsing the rename with `--sandbox`.
+    #[error("cargo check failed in the sandbox; the workspace was not changed:\n{0}")]
+    SandboxCheckFailed(String),
+
     /// Workspace verification failed after rename.
     #[error("Workspace verification failed: {0}")]
     VerificationFailed(String),
 
-    /// Rollback failed after commit error.
-    #[error("Rollback failed: {0}")]
-    RollbackFailed(String),
+    /// A commit failed midway; carries what was applied and rolled back.
+    #[error("{0}")]
+    PartialFailure(Box<PartialFailure>),
+
+    /// The command line did not parse.
+    #[error("Invalid arguments: {0}")]
+    InvalidArguments(String),
 
     /// Uncommitted changes in git workspace.
     #[error("Workspace has uncommitted changes")]
     DirtyWorkspace,
 
+    /// Several independent checks failed (e.g. during preflight).
+    #[error("{} problems found:\n{}", .0.len(), bullet_list(.0))]
+    Multiple(Vec<RenameError>),
+
+    /// The rename would change nothing and `--fail-if-noop` was passed.
+    #[error("Nothing to do: {0}")]
+    NothingToDo(String),
+
+    /// `rename check` found references to renamed crates.
+    #[error(
+        "{0} reference{s} to renamed crates found",
+        s = if *.0 == 1 { "" } else { "s" }
+    )]
+    StaleReferences(usize),
+
     /// User declined confirmation.
     ///
     /// Not a failure—used for control flow when user cancels.
@@ -63,5 +154,289 @@ pub enum RenameError {
     Other(#[from] anyhow::Error),
 }
 
+impl RenameError {
+    /// Combines collected errors: `Ok` if empty, the error itself if there is
+    /// one, [`RenameError::Multiple`] otherwise.
+    pub fn aggregate(mut errors: Vec<RenameError>) -> Result<()> {
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(RenameError::Multiple(errors)),
+        }
+    }
+
+    /// Stable, kebab-case identifier of the error kind.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::PackageNotFound(..) => "package-not-found",
+            Self::PackageRenamed(..) => "package-renamed",
+            Self::DirectoryExists(_) => "directory-exists",
+            Self::SharedDirectory(..) => "shared-directory",
+            Self::NotMaterialized(_) => "not-materialized",
+            Self::InvalidName(..) => "invalid-name",
+            Self::InvalidPath(..) => "invalid-path",
+            Self::InvalidManifest(..) => "invalid-manifest",
+            Self::WorkspaceRootMismatch(..) => "workspace-root-mismatch",
+            Self::NotUtf8(_) => "not-utf8",
+            Self::ExternalCommandTimeout(..) => "command-timeout",
+            Self::InsufficientSpace(..) => "insufficient-space",
+            Self::PathTooLong(..) => "path-too-long",
+            Self::HookFailed(..) => "hook-failed",
+            Self::SandboxCheckFailed(_) => "sandbox-check-failed",
+            Self::VerificationFailed(_) => "verification-failed",
+            Self::PartialFailure(_) => "partial-failure",
+            Self::InvalidArguments(_) => "invalid-arguments",
+            Self::DirtyWorkspace => "dirty-workspace",
+            Self::Multiple(_) => "multiple",
+            Self::NothingToDo(_) => "nothing-to-do",
+            Self::StaleReferences(_) => "stale-references",
+            Self::Cancelled => "cancelled",
+            Self::Io(_) => "io",
+            Self::Toml(_) => "toml",
+            Self::Metadata(_) => "metadata",
+            Self::Regex(_) => "regex",
+            Self::Other(_) => "other",
+        }
+    }
+
+    /// Paths the error is about, if any.
+    ///
+    /// For a failed commit, these are the paths left in a changed state.
+    pub fn paths(&self) -> Vec<String> {
+        match self {
+            Self::DirectoryExists(path)
+            | Self::SharedDirectory(path, _)
+            | Self::NotMaterialized(path)
+            | Self::NotUtf8(path)
+            | Self::InsufficientSpace(path, ..) => vec![display_path(path)],
+            Self::PathTooLong(paths, _) => paths.iter().map(|p| display_path(p)).collect(),
+            Self::WorkspaceRootMismatch(pinned, detected) => {
+                vec![display_path(pinned), display_path(detected)]
+            }
+            Self::InvalidPath(path, _) | Self::InvalidManifest(path, _) => vec![path.clone()],
+            Self::PartialFailure(report) => report
+                .unrecoverable
+                .iter()
+                .flat_map(|(op, _)| match op {
+                    AffectedOperation::UpdateFile { path } => vec![display_path(path)],
+                    AffectedOperation::MoveDirectory { from, to } => {
+                        vec![display_path(from), display_path(to)]
+                    }
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// A suggested next step for the user, if there is one.
+    pub fn remediation(&self) -> Option<String> {
+        let hint = match self {
+            Self::PackageNotFound(_, suggestions) if suggestions.len() == 1 => {
+                format!("use '{}'", suggestions[0])
+            }
+            Self::PackageNotFound(_, suggestions) if !suggestions.is_empty() => {
+                format!("use one of: {}", suggestions.join(", "))
+            }
+            Self::PackageNotFound(..) => "check the name with `cargo metadata`".to_string(),
+            Self::PackageRenamed(_, new, _) => format!("use the new name '{}'", new),
+            Self::DirectoryExists(_) => "choose another target directory or remove it".to_string(),
+            Self::SharedDirectory(..) => {
+                "keep the directory (drop --move), or pass --force-shared-dir to move them along"
+                    .to_string()
+            }
+            Self::NotMaterialized(_) => {
+                "check out the member or drop --strict-materialization".to_string()
+            }
+            Self::WorkspaceRootMismatch(..) => {
+                "point --manifest-path at the intended workspace, or drop --workspace-root"
+                    .to_string()
+            }
+            Self::NotUtf8(_) => "convert the file to UTF-8".to_string(),
+            Self::ExternalCommandTimeout(..) => {
+                "raise it with --timeout <SECS>, or 0 to disable".to_string()
+            }
+            Self::InsufficientSpace(..) => "free up space on the destination".to_string(),
+            Self::PathTooLong(..) => {
+                "choose a shorter directory, or pass --allow-long-paths if long paths are enabled"
+                    .to_string()
+            }
+            Self::PartialFailure(report) if !report.is_recovered() => {
+                "restore the listed paths manually, e.g. with `git checkout`".to_string()
+            }
+            Self::InvalidArguments(_) => "see `cargo rename --help` for usage".to_string(),
+            Self::DirtyWorkspace => {
+                "commit or stash your changes, or pass --allow-dirty".to_string()
+            }
+            Self::NothingToDo(_) => "drop --fail-if-noop to accept no-op renames".to_string(),
+            Self::StaleReferences(_) => "use the current names listed above".to_string(),
+            _ => return None,
+        };
+        Some(hint)
+    }
+
+    /// Outcome of the rollback after a failed commit: `complete` or
+    /// `incomplete`. `None` if no changes were committed.
+    pub fn rollback(&self) -> Option<&'static str> {
+        match self {
+            Self::PartialFailure(report) if report.is_recovered() => Some("complete"),
+            Self::PartialFailure(_) => Some("incomplete"),
+            _ => None,
+        }
+    }
+}
+
+/// Machine-readable form of a [`RenameError`], printed with `--json-errors`.
+#[derive(Debug, Serialize)]
+pub struct ErrorReport {
+    pub code: &'static str,
+    pub message: String,
+    pub paths: Vec<String>,
+    pub remediation: Option<String>,
+    pub rollback: Option<&'static str>,
+    /// The individual problems of a [`RenameError::Multiple`].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ErrorReport>,
+}
+
+impl From<&RenameError> for ErrorReport {
+    fn from(error: &RenameError) -> Self {
+        let errors = match error {
+            RenameError::Multiple(errors) => errors.iter().map(ErrorReport::from).collect(),
+            _ => Vec::new(),
+        };
+
+        Self {
+            code: error.code(),
+            message: error.to_string(),
+            paths: error.paths(),
+            remediation: error.remediation(),
+            rollback: error.rollback(),
+            errors,
+        }
+    }
+}
+
+/// Reports failures as a single JSON object on stderr.
+pub fn set_json_errors(enabled: bool) {
+    JSON_ERRORS.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns `true` if failures are reported as JSON.
+pub fn json_errors() -> bool {
+    JSON_ERRORS.load(Ordering::Relaxed)
+}
+
+/// Prints a failure to stderr, as JSON with `--json-errors`.
+pub fn report(error: &RenameError) {
+    if json_errors() {
+        let report = serde_json::to_string(&ErrorReport::from(error))
+            .expect("error report serialization cannot fail");
+        eprintln!("{}", report);
+    } else {
+        eprintln!("Error: {}", error);
+    }
+}
+
+/// Formats errors as an indented bullet list, one per line.
+fn bullet_list(errors: &[RenameError]) -> String {
+    errors
+        .iter()
+        .map(|e| {
+            format!(
+                "  {} {}",
+                symbols::bullet(),
+                e.to_string().replace('\n', "\n    ")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Number of paths listed by [`path_list`].
+const PATH_LIST_LIMIT: usize = 10;
+
+/// Formats paths as an indented bullet list, eliding all but the first few.
+fn path_list(paths: &[PathBuf]) -> String {
+    let mut lines: Vec<String> = paths
+        .iter()
+        .take(PATH_LIST_LIMIT)
+        .map(|p| format!("  {} {}", symbols::bullet(), crate::fs::display_path(p)))
+        .collect();
+    if paths.len() > PATH_LIST_LIMIT {
+        lines.push(format!(
+            "  {} ... and {} more",
+            symbols::bullet(),
+            paths.len() - PATH_LIST_LIMIT
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Formats a "did you mean" hint for a list of suggestions.
+fn did_you_mean(suggestions: &[String]) -> String {
+    match suggestions {
+        [] => String::new(),
+        [one] => format!(". Did you mean '{}'?", one),
+        many => format!(
+            ". Did you mean one of: {}?",
+            many.iter()
+                .map(|s| format!("'{}'", s))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
 /// Result type alias for cargo-rename operations.
 pub type Result<T> = std::result::Result<T, RenameError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate_keeps_single_error() {
+        assert!(RenameError::aggregate(Vec::new()).is_ok());
+        assert!(matches!(
+            RenameError::aggregate(vec![RenameError::DirtyWorkspace]),
+            Err(RenameError::DirtyWorkspace)
+        ));
+    }
+
+    #[test]
+    fn test_multiple_lists_every_error() {
+        let err = RenameError::aggregate(vec![
+            RenameError::InvalidName("1abc".into(), "must start with a letter".into()),
+            RenameError::DirtyWorkspace,
+        ])
+        .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "2 problems found:\n  • Invalid package name '1abc': must start with a letter\n  • Workspace has uncommitted changes"
+        );
+    }
+
+    #[test]
+    fn test_error_report() {
+        let err = RenameError::aggregate(vec![
+            RenameError::DirectoryExists(PathBuf::from("crates/new")),
+            RenameError::DirtyWorkspace,
+        ])
+        .unwrap_err();
+
+        let json = serde_json::to_value(ErrorReport::from(&err)).unwrap();
+
+        assert_eq!(json["code"], "multiple");
+        assert_eq!(json["rollback"], serde_json::Value::Null);
+        assert_eq!(json["errors"][0]["code"], "directory-exists");
+        assert_eq!(json["errors"][0]["paths"][0], "crates/new");
+        assert_eq!(json["errors"][1]["code"], "dirty-workspace");
+        assert!(
+            json["errors"][1]["remediation"]
+                .as_str()
+                .unwrap()
+                .contains("--allow-dirty")
+        );
+    }
+}
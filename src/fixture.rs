@@ -0,0 +1,231 @@
+//! Synthetic workspaces for reproducing rename bugs.
+//!
+//! `cargo rename fixture` writes a small workspace with the manifest shapes a
+//! bug report needs, so it can be attached as a command line instead of a
+//! tarball. Members are named `member-1` to `member-N`; each one depends on
+//! the previous member by path and calls into it, so the generated workspace
+//! builds and renaming `member-1` touches every dependent.
+//!
+//! Optional shapes:
+//!
+//! - **Target dependencies**: `[target.'cfg(unix)'.dependencies]` entries
+//! - **Aliases**: `first = { package = "member-1", ... }` renamed dependencies
+//!   (from `member-3` onwards)
+//!
+//! The same generator backs integration tests that need these shapes.
+
+use crate::error::{RenameError, Result};
+use std::fs;
+use std::path::Path;
+
+/// Manifest shapes included in a generated workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixtureOptions {
+    /// Number of member packages (at least 1).
+    pub members: usize,
+    /// Add `[target.'cfg(unix)'.dependencies]` on the previous member.
+    pub target_deps: bool,
+    /// Depend on `member-1` under the alias `first` from `member-3` onwards.
+    ///
+    /// `member-2` already depends on it by name, and Cargo rejects depending
+    /// on one package under two names.
+    pub aliases: bool,
+}
+
+impl Default for FixtureOptions {
+    fn default() -> Self {
+        Self {
+            members: 3,
+            target_deps: false,
+            aliases: false,
+        }
+    }
+}
+
+/// Returns the package name of the `index`-th member (1-based).
+pub fn member_name(index: usize) -> String {
+    format!("member-{}", index)
+}
+
+/// Writes a fixture workspace to `out_dir`.
+///
+/// # Errors
+///
+/// - `DirectoryExists` if `out_dir` exists and is not empty
+/// - `Other` if `members` is 0
+/// - `Io` if a file cannot be written
+pub fn generate(options: &FixtureOptions, out_dir: &Path) -> Result<()> {
+    if options.members == 0 {
+        return Err(anyhow::anyhow!("A fixture needs at least one member").into());
+    }
+
+    if out_dir.exists() && fs::read_dir(out_dir)?.next().is_some() {
+        return Err(RenameError::DirectoryExists(out_dir.to_path_buf()));
+    }
+
+    fs::create_dir_all(out_dir)?;
+    fs::write(out_dir.join("Cargo.toml"), workspace_manifest(options))?;
+    fs::write(out_dir.join("README.md"), readme(options))?;
+
+    for index in 1..=options.members {
+        let dir = out_dir.join("crates").join(member_name(index));
+        fs::create_dir_all(dir.join("src"))?;
+        fs::write(dir.join("Cargo.toml"), member_manifest(options, index))?;
+        fs::write(dir.join("src/lib.rs"), member_source(options, index))?;
+    }
+
+    Ok(())
+}
+
+fn workspace_manifest(options: &FixtureOptions) -> String {
+    let members = (1..=options.members)
+        .map(|i| format!("    \"crates/{}\",\n", member_name(i)))
+        .collect::<String>();
+
+    format!(
+        r#"[workspace]
+members = [
+{members}]
+resolver = "2"
+
+[workspace.package]
+version = "0.1.0"
+edition = "2021"
+
+[workspace.dependencies]
+{first} = {{ path = "crates/{first}", version = "0.1.0" }}
+"#,
+        members = members,
+        first = member_name(1)
+    )
+}
+
+fn member_manifest(options: &FixtureOptions, index: usize) -> String {
+    let mut manifest = format!(
+        r#"[package]
+name = "{}"
+version.workspace = true
+edition.workspace = true
+"#,
+        member_name(index)
+    );
+
+    if index == 1 {
+        return manifest;
+    }
+
+    let previous = member_name(index - 1);
+    let first = member_name(1);
+
+    manifest.push_str("\n[dependencies]\n");
+    if index == 2 {
+        manifest.push_str(&format!("{}.workspace = true\n", first));
+    } else {
+        manifest.push_str(&format!(
+            "{0} = {{ path = \"../{0}\", version = \"0.1.0\" }}\n",
+            previous
+        ));
+    }
+    if options.aliases && index > 2 {
+        manifest.push_str(&format!(
+            "first = {{ package = \"{0}\", path = \"../{0}\" }}\n",
+            first
+        ));
+    }
+
+    if options.target_deps {
+        manifest.push_str(&format!(
+            "\n[target.'cfg(unix)'.dependencies]\n{0} = {{ path = \"../{0}\" }}\n",
+            previous
+        ));
+    }
+
+    manifest
+}
+
+fn member_source(options: &FixtureOptions, index: usize) -> String {
+    let name = member_name(index);
+    let mut source = format!(
+        "//! Fixture crate `{name}`.\n\npub fn name() -> &'static str {{\n    \"{name}\"\n}}\n",
+        name = name
+    );
+
+    if index == 1 {
+        return source;
+    }
+
+    let previous = member_name(index - 1).replace('-', "_");
+    source.push_str(&format!(
+        "\n/// Calls [`{0}::name`].\npub fn previous() -> &'static str {{\n    {0}::name()\n}}\n",
+        previous
+    ));
+
+    if options.aliases && index > 2 {
+        source.push_str("\npub use first::name as first_name;\n");
+    }
+
+    source
+}
+
+fn readme(options: &FixtureOptions) -> String {
+    let mut shapes = Vec::new();
+    if options.target_deps {
+        shapes.push("target dependencies");
+    }
+    if options.aliases {
+        shapes.push("aliased dependencies");
+    }
+
+    format!(
+        "# Rename fixture\n\nA workspace of {} members generated by `cargo rename fixture`{}.\n\
+         Each member depends on the previous one; `{}` is the root of the chain.\n",
+        options.members,
+        if shapes.is_empty() {
+            String::new()
+        } else {
+            format!(" with {}", shapes.join(" and "))
+        },
+        member_name(1)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_generates_requested_shapes() {
+        let temp = TempDir::new().unwrap();
+        let options = FixtureOptions {
+            members: 3,
+            target_deps: true,
+            aliases: true,
+        };
+
+        generate(&options, temp.path()).unwrap();
+
+        let root = fs::read_to_string(temp.path().join("Cargo.toml")).unwrap();
+        assert!(root.contains("\"crates/member-3\""));
+
+        let last = fs::read_to_string(temp.path().join("crates/member-3/Cargo.toml")).unwrap();
+        assert!(last.contains("member-2 = { path = \"../member-2\", version = \"0.1.0\" }"));
+        assert!(last.contains("first = { package = \"member-1\", path = \"../member-1\" }"));
+        assert!(last.contains("[target.'cfg(unix)'.dependencies]"));
+
+        let second = fs::read_to_string(temp.path().join("crates/member-2/Cargo.toml")).unwrap();
+        assert!(!second.contains("first ="));
+
+        let first = fs::read_to_string(temp.path().join("crates/member-1/Cargo.toml")).unwrap();
+        assert!(!first.contains("[dependencies]"));
+    }
+
+    #[test]
+    fn test_refuses_non_empty_directory() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("keep.txt"), "").unwrap();
+
+        let result = generate(&FixtureOptions::default(), temp.path());
+        assert!(matches!(result, Err(RenameError::DirectoryExists(_))));
+    }
+}
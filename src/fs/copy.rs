@@ -0,0 +1,284 @@
+//! Directory copies for moves across filesystems.
+//!
+//! `rename()` cannot cross filesystems, so such moves copy the tree and then
+//! delete the source. Before anything is written, the source is scanned for
+//! its file count and size and the destination is checked for free space, so
+//! a full disk fails the commit up front instead of leaving a half-copied tree
+//! for rollback to clean up. Copies then report progress on stderr.
+//!
+//! Free space is read with `df -Pk` on Unix; elsewhere the check is skipped.
+//!
+//! Files are copied with `fs::copy`, which goes through the platform copy
+//! APIs: `fclonefileat`/`fcopyfile` on macOS keep extended attributes,
+//! resource forks and ACLs, and `CopyFileExW` on Windows keeps attributes and
+//! alternate data streams (ACLs are inherited from the destination). Elsewhere
+//! only contents and permissions are kept.
+
+use crate::console;
+use crate::error::{RenameError, Result};
+use crate::fs::display::display_path;
+use crate::process;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::process::Command;
+
+/// File count and total size of a directory tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TreeSize {
+    pub files: u64,
+    pub bytes: u64,
+}
+
+/// Sums the regular files below `dir`. Symlinks are not followed.
+pub fn scan_tree(dir: &Path) -> Result<TreeSize> {
+    let mut size = TreeSize::default();
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            let sub = scan_tree(&entry.path())?;
+            size.files += sub.files;
+            size.bytes += sub.bytes;
+        } else {
+            size.files += 1;
+            size.bytes += entry.metadata()?.len();
+        }
+    }
+
+    Ok(size)
+}
+
+/// Fails with `InsufficientSpace` if the filesystem holding `to` cannot fit
+/// `size`.
+///
+/// `to` may not exist yet; its closest existing ancestor is checked. If free
+/// space cannot be determined, the check passes.
+pub fn check_free_space(to: &Path, size: TreeSize) -> Result<()> {
+    let Some(existing) = to.ancestors().find(|p| p.exists()) else {
+        return Ok(());
+    };
+
+    match available_space(existing) {
+        Some(available) if available < size.bytes => Err(RenameError::InsufficientSpace(
+            to.to_path_buf(),
+            size.bytes,
+            available,
+        )),
+        Some(_) => Ok(()),
+        None => {
+            log::debug!(
+                "Could not determine free space at {}",
+                display_path(existing)
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Returns the bytes available to unprivileged users on the filesystem of `dir`.
+fn available_space(dir: &Path) -> Option<u64> {
+    if !cfg!(unix) {
+        return None;
+    }
+
+    let output = process::output(Command::new("df").arg("-Pk").arg(dir)).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    parse_df_available(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parses the "Available" column (in KiB) of POSIX `df -Pk` output.
+fn parse_df_available(output: &str) -> Option<u64> {
+    let kib = output
+        .lines()
+        .nth(1)?
+        .split_whitespace()
+        .nth(3)?
+        .parse::<u64>()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+/// Copies the tree at `from` to `to`, reporting progress against `total`.
+pub fn copy_dir(from: &Path, to: &Path, total: Option<TreeSize>) -> Result<()> {
+    let mut progress = total.map(|total| Progress::new(from, total));
+    copy_dir_recursive(from, to, &mut progress)?;
+
+    if let Some(progress) = progress {
+        progress.finish();
+    }
+    Ok(())
+}
+
+fn copy_dir_recursive(from: &Path, to: &Path, progress: &mut Option<Progress>) -> Result<()> {
+    fs::create_dir_all(to)?;
+
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let from_path = entry.path();
+        let to_path = to.join(entry.file_name());
+
+        if file_type.is_dir() {
+            copy_dir_recursive(&from_path, &to_path, progress)?;
+        } else {
+            let bytes = fs::copy(&from_path, &to_path)?;
+            if let Some(progress) = progress {
+                progress.advance(bytes);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Copy progress, redrawn on one stderr line when it is a terminal.
+struct Progress {
+    total: TreeSize,
+    done: TreeSize,
+    interactive: bool,
+}
+
+impl Progress {
+    fn new(from: &Path, total: TreeSize) -> Self {
+        log::info!(
+            "Copying {} files ({}) from {}",
+            total.files,
+            format_bytes(total.bytes),
+            display_path(from)
+        );
+
+        Self {
+            total,
+            done: TreeSize::default(),
+            // A progress line can't be captured, so the library API gets none
+            interactive: io::stderr().is_terminal() && !console::is_capturing(),
+        }
+    }
+
+    fn advance(&mut self, bytes: u64) {
+        self.done.files += 1;
+        self.done.bytes += bytes;
+
+        if self.interactive {
+            eprint!(
+                "\r  Copying {}/{} files ({}/{})",
+                self.done.files,
+                self.total.files,
+                format_bytes(self.done.bytes),
+                format_bytes(self.total.bytes)
+            );
+            let _ = io::stderr().flush();
+        }
+    }
+
+    fn finish(self) {
+        if self.interactive {
+            eprintln!();
+        }
+    }
+}
+
+/// Formats a byte count with a binary unit, e.g. `1.5 MiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_scan_and_copy_tree() {
+        let temp = TempDir::new().unwrap();
+        let from = temp.path().join("from");
+        fs::create_dir_all(from.join("src/nested")).unwrap();
+        fs::write(from.join("Cargo.toml"), "12345").unwrap();
+        fs::write(from.join("src/nested/lib.rs"), "123").unwrap();
+
+        let size = scan_tree(&from).unwrap();
+        assert_eq!(size, TreeSize { files: 2, bytes: 8 });
+
+        let to = temp.path().join("deep/to");
+        copy_dir(&from, &to, Some(size)).unwrap();
+        assert_eq!(
+            fs::read_to_string(to.join("src/nested/lib.rs")).unwrap(),
+            "123"
+        );
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_copy_keeps_extended_attributes() {
+        let temp = TempDir::new().unwrap();
+        let from = temp.path().join("from");
+        fs::create_dir_all(&from).unwrap();
+        let file = from.join("lib.rs");
+        fs::write(&file, "").unwrap();
+        let status = Command::new("xattr")
+            .args(["-w", "com.example.tag", "kept"])
+            .arg(&file)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let to = temp.path().join("to");
+        copy_dir(&from, &to, None).unwrap();
+
+        let output = Command::new("xattr")
+            .args(["-p", "com.example.tag"])
+            .arg(to.join("lib.rs"))
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "kept");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_copy_keeps_alternate_data_streams() {
+        let temp = TempDir::new().unwrap();
+        let from = temp.path().join("from");
+        fs::create_dir_all(&from).unwrap();
+        fs::write(from.join("lib.rs"), "").unwrap();
+        fs::write(from.join("lib.rs:tag"), "kept").unwrap();
+
+        let to = temp.path().join("to");
+        copy_dir(&from, &to, None).unwrap();
+
+        assert_eq!(fs::read_to_string(to.join("lib.rs:tag")).unwrap(), "kept");
+    }
+
+    #[test]
+    fn test_parse_df_output() {
+        let output = "Filesystem 1024-blocks Used Available Capacity Mounted on\n\
+                      /dev/sda1 1000 600 400 60% /\n";
+        assert_eq!(parse_df_available(output), Some(400 * 1024));
+        assert_eq!(parse_df_available("garbage"), None);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
@@ -0,0 +1,106 @@
+//! Path formatting for user-facing output.
+//!
+//! Paths in messages, logs and errors are shown relative to the workspace root
+//! with forward slashes, so output is identical across operating systems.
+//! `--absolute-paths` restores absolute, OS-native paths.
+
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ABSOLUTE_PATHS: AtomicBool = AtomicBool::new(false);
+static DISPLAY_ROOT: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Shows absolute, OS-native paths instead of workspace-relative ones.
+pub fn set_absolute_paths(enabled: bool) {
+    ABSOLUTE_PATHS.store(enabled, Ordering::Relaxed);
+}
+
+/// Sets the directory that displayed paths are relative to.
+///
+/// Called once the workspace root is known. Until then, paths are shown as-is.
+pub fn set_display_root(root: &Path) {
+    if let Ok(mut guard) = DISPLAY_ROOT.write() {
+        *guard = Some(root.to_path_buf());
+    }
+}
+
+/// Formats a path for display, relative to the workspace root.
+pub fn display_path(path: &Path) -> String {
+    let root = DISPLAY_ROOT.read().ok().and_then(|guard| guard.clone());
+
+    match root {
+        Some(root) => display_relative(path, &root),
+        None if ABSOLUTE_PATHS.load(Ordering::Relaxed) => path.display().to_string(),
+        None => forward_slashes(path),
+    }
+}
+
+/// Formats a path for display, relative to an explicit root.
+pub fn display_relative(path: &Path, root: &Path) -> String {
+    if ABSOLUTE_PATHS.load(Ordering::Relaxed) {
+        return path.display().to_string();
+    }
+
+    relative_to(path, root)
+}
+
+/// Formats a path for display, relative to the current directory.
+///
+/// Used where the workspace root itself is shown, which is `.` relative to
+/// the workspace root.
+pub fn display_from_cwd(path: &Path) -> String {
+    if ABSOLUTE_PATHS.load(Ordering::Relaxed) {
+        return path.display().to_string();
+    }
+
+    match std::env::current_dir() {
+        Ok(cwd) => relative_to(&cwd.join(path), &cwd),
+        Err(_) => forward_slashes(path),
+    }
+}
+
+/// Returns `path` relative to `root` with forward slashes.
+///
+/// Relative inputs and paths that cannot be expressed relative to `root` are
+/// returned with normalized separators only.
+fn relative_to(path: &Path, root: &Path) -> String {
+    if path.is_relative() {
+        return forward_slashes(path);
+    }
+
+    match pathdiff::diff_paths(path, root) {
+        Some(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+        Some(rel) => forward_slashes(&rel),
+        None => forward_slashes(path),
+    }
+}
+
+fn forward_slashes(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_to_root() {
+        let root = Path::new("/work/ws");
+
+        assert_eq!(
+            relative_to(Path::new("/work/ws/crates/a/Cargo.toml"), root),
+            "crates/a/Cargo.toml"
+        );
+        assert_eq!(relative_to(Path::new("/work/ws"), root), ".");
+        assert_eq!(relative_to(Path::new("/work/other"), root), "../other");
+    }
+
+    #[test]
+    fn test_relative_input_normalizes_separators() {
+        assert_eq!(
+            relative_to(Path::new(r"crates\a"), Path::new("/work/ws")),
+            "crates/a"
+        );
+    }
+}
@@ -3,6 +3,17 @@
 //! Provides atomic file and directory operations that can be committed
 //! or rolled back as a unit.
 
+pub mod copy;
+pub mod display;
+pub mod path;
+pub mod sandbox;
 pub mod transaction;
+pub mod vfs;
 
-pub use transaction::{Operation, Transaction, TransactionStats};
+pub use display::{display_from_cwd, display_path, display_relative};
+pub use path::{check_resolves, normalize_path, shortest_relative};
+pub use transaction::{
+    AffectedOperation, Category, ChangeCounts, Operation, PackageChanges, PartialFailure,
+    Transaction, TransactionStats,
+};
+pub use vfs::{MemoryFs, RealFs, Vfs};
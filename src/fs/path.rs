@@ -0,0 +1,91 @@
+//! Lexical path arithmetic for rewritten manifest paths.
+//!
+//! Cargo resolves `path` values by joining them to the manifest directory and
+//! dropping `.` and `..` components without touching the file system. Paths
+//! written by a rename are computed and checked the same way, so a value like
+//! `../../workspace/crates/old` that leaves the workspace and comes back is
+//! replaced by the shortest equivalent (`../new`).
+
+use crate::error::Result;
+use std::path::{Component, Path, PathBuf};
+
+/// Lexically resolves `.` and `..` components.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                result.pop();
+            }
+            other => result.push(other),
+        }
+    }
+    result
+}
+
+/// Returns the shortest path from `base` to `target`, with forward slashes.
+///
+/// Both are normalized first. Fails if the result does not resolve back to
+/// `target` from `base`.
+pub fn shortest_relative(target: &Path, base: &Path) -> Result<String> {
+    let target = normalize_path(target);
+    let base = normalize_path(base);
+    let relative = pathdiff::diff_paths(&target, &base)
+        .ok_or_else(|| anyhow::anyhow!("Failed to calculate relative path"))?;
+    let relative = relative.to_string_lossy().replace('\\', "/");
+
+    check_resolves(&relative, &base, &target)?;
+    Ok(relative)
+}
+
+/// Fails unless `value`, joined to `base`, lexically resolves to `target`.
+pub fn check_resolves(value: &str, base: &Path, target: &Path) -> Result<()> {
+    if normalize_path(&base.join(value)) == normalize_path(target) {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "Rewritten path '{}' does not resolve to {} from {}",
+            value,
+            target.display(),
+            base.display()
+        )
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shortest_relative() {
+        let shortest = |target: &str, base: &str| {
+            shortest_relative(Path::new(target), Path::new(base)).unwrap()
+        };
+
+        assert_eq!(shortest("/ws/crates/new", "/ws/crates/app"), "../new");
+        assert_eq!(
+            shortest("/ws/crates/./x/../new", "/ws/app"),
+            "../crates/new"
+        );
+        assert_eq!(
+            shortest("/ws/crates/new", "/ws/crates/app/../../tools/cli"),
+            "../../crates/new"
+        );
+        assert_eq!(shortest("/ws/new", "/ws"), "new");
+
+        assert_eq!(
+            normalize_path(Path::new("/ws/app/../../ws/crates/old")),
+            Path::new("/ws/crates/old")
+        );
+        assert!(
+            check_resolves(
+                "../../workspace/crates/new",
+                Path::new("/ws/app"),
+                Path::new("/ws/crates/new")
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! Throwaway workspace copies for `--sandbox`.
+//!
+//! The workspace is copied to a temporary directory, without `target` and
+//! `.git` directories, so a rename can be rehearsed and built there before
+//! the real tree is touched. The copy is removed when the [`Sandbox`] is
+//! dropped.
+
+use crate::error::Result;
+use crate::fs::display_path;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A temporary copy of a workspace.
+#[derive(Debug)]
+pub struct Sandbox {
+    source: PathBuf,
+    root: PathBuf,
+}
+
+impl Sandbox {
+    /// Copies the workspace at `workspace_root` to a new temporary directory.
+    pub fn create(workspace_root: &Path) -> Result<Self> {
+        let name = workspace_root
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "workspace".to_string());
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let root = std::env::temp_dir()
+            .join(format!(
+                "cargo-rename-sandbox-{}-{}",
+                std::process::id(),
+                stamp
+            ))
+            .join(name);
+        if root.exists() {
+            fs::remove_dir_all(&root)?;
+        }
+
+        let sandbox = Self {
+            source: workspace_root.to_path_buf(),
+            root,
+        };
+        log::info!("Copying workspace to sandbox {}", sandbox.root.display());
+        copy_tree(workspace_root, &sandbox.root)?;
+        Ok(sandbox)
+    }
+
+    /// Returns the root of the copy.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Maps a path inside the original workspace to its copy.
+    pub fn map(&self, path: &Path) -> PathBuf {
+        match path.strip_prefix(&self.source) {
+            Ok(rel) => self.root.join(rel),
+            Err(_) => path.to_path_buf(),
+        }
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let dir = self.root.parent().unwrap_or(&self.root);
+        if let Err(e) = fs::remove_dir_all(dir) {
+            log::warn!("Failed to remove sandbox {}: {}", display_path(dir), e);
+        }
+    }
+}
+
+/// Copies `from` to `to`, skipping `target` and `.git` directories.
+fn copy_tree(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let name = entry.file_name();
+        let to_path = to.join(&name);
+
+        if file_type.is_dir() {
+            if name == "target" || name == ".git" {
+                continue;
+            }
+            copy_tree(&entry.path(), &to_path)?;
+        } else if name != ".git" {
+            fs::copy(entry.path(), &to_path)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sandbox_copies_sources_only() {
+        let temp = TempDir::new().unwrap();
+        let ws = temp.path().join("ws");
+        fs::create_dir_all(ws.join("target/debug")).unwrap();
+        fs::create_dir_all(ws.join(".git")).unwrap();
+        fs::create_dir_all(ws.join("crate-a/src")).unwrap();
+        fs::write(ws.join("Cargo.toml"), "[workspace]\n").unwrap();
+        fs::write(ws.join("crate-a/src/lib.rs"), "").unwrap();
+
+        let sandbox = Sandbox::create(&ws).unwrap();
+        let root = sandbox.root().to_path_buf();
+
+        assert!(root.join("Cargo.toml").exists());
+        assert!(sandbox.map(&ws.join("crate-a/src/lib.rs")).exists());
+        assert!(!root.join("target").exists());
+        assert!(!root.join(".git").exists());
+
+        drop(sandbox);
+        assert!(!root.exists());
+    }
+}
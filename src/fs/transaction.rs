@@ -3,6 +3,10 @@
 //! Coordinates multiple file updates and directory moves that must succeed
 //! or fail as a unit.
 //!
+//! All file system access goes through a [`Vfs`]: [`Transaction::new`] uses
+//! the real file system, [`Transaction::with_fs`] any other implementation,
+//! such as the in-memory [`MemoryFs`](crate::fs::vfs::MemoryFs) for tests.
+//!
 //! ## Execution Guarantees
 //!
 //! - **Atomicity**: All operations succeed, or all are rolled back
@@ -33,13 +37,131 @@
 //! # }
 //! ```
 
+use crate::annotate;
+use crate::console::outln;
 use crate::error::{RenameError, Result};
-
+use crate::fs::copy::TreeSize;
+use crate::fs::display::{display_path, display_relative};
+use crate::fs::vfs::{RealFs, Vfs};
+use crate::rewrite::conflicts::Conflict;
+use crate::rewrite::trace::TracedEdit;
+use crate::symbols;
+use crate::verify::validate_move_not_nested;
+
+use clap::ValueEnum;
 use colored::Colorize;
-use std::collections::{HashMap, HashSet};
-use std::fs;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+/// Summary section of a staged [`Operation`].
+///
+/// All but the package manifest and the directory move can be left out with
+/// `--skip-category`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum Category {
+    /// The renamed package's `Cargo.toml`.
+    #[value(skip)]
+    Package,
+    /// The directory move.
+    #[value(skip)]
+    Directory,
+    /// Other manifests: dependents and the workspace root.
+    Dependencies,
+    /// Rust sources and other rewritten files (headers, `.env`, hooks).
+    Source,
+    /// Markdown and text documentation.
+    Docs,
+    /// Tool configs (`deny.toml`, `cbindgen.toml`, ...).
+    Configs,
+}
+
+/// The paths touched by an [`Operation`], without file contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AffectedOperation {
+    UpdateFile { path: PathBuf },
+    MoveDirectory { from: PathBuf, to: PathBuf },
+}
+
+impl From<&Operation> for AffectedOperation {
+    fn from(op: &Operation) -> Self {
+        match op {
+            Operation::UpdateFile { path, .. } => Self::UpdateFile { path: path.clone() },
+            Operation::MoveDirectory { from, to } => Self::MoveDirectory {
+                from: from.clone(),
+                to: to.clone(),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for AffectedOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UpdateFile { path } => write!(f, "update {}", display_path(path)),
+            Self::MoveDirectory { from, to } => {
+                write!(
+                    f,
+                    "move {} {} {}",
+                    display_path(from),
+                    symbols::arrow(),
+                    display_path(to)
+                )
+            }
+        }
+    }
+}
+
+/// Per-operation results of a commit (or rollback) that failed midway.
+///
+/// Lists what was applied, what was restored, and what is left in a changed
+/// state, so callers can give precise recovery instructions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialFailure {
+    /// What stopped the operation (`Commit failed: ...`).
+    pub cause: String,
+    /// Number of staged operations.
+    pub total: usize,
+    /// Operations applied before the failure, in execution order.
+    pub executed: Vec<AffectedOperation>,
+    /// Executed operations that were undone.
+    pub rolled_back: Vec<AffectedOperation>,
+    /// Executed operations that could not be undone, with the reason.
+    pub unrecoverable: Vec<(AffectedOperation, String)>,
+}
+
+impl PartialFailure {
+    /// Returns `true` if every executed operation was rolled back.
+    pub fn is_recovered(&self) -> bool {
+        self.unrecoverable.is_empty()
+    }
+}
+
+impl std::fmt::Display for PartialFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\n{} of {} operations were applied, {} rolled back",
+            self.cause,
+            self.executed.len(),
+            self.total,
+            self.rolled_back.len()
+        )?;
+
+        if self.is_recovered() {
+            return write!(f, "; the workspace was restored");
+        }
+
+        write!(f, ". Not restored:")?;
+        for (op, reason) in &self.unrecoverable {
+            write!(f, "\n  {} {}: {}", symbols::bullet(), op, reason)?;
+        }
+        write!(
+            f,
+            "\nRestore these paths from version control (e.g. `git checkout -- <path>`)"
+        )
+    }
+}
+
 /// A file system operation that can be committed or rolled back.
 #[derive(Debug, Clone)]
 pub enum Operation {
@@ -78,26 +200,74 @@ enum TransactionState {
 ///
 /// When `dry_run = true`, operations are validated but not executed.
 #[must_use = "Transaction must be committed or rolled back"]
-pub struct Transaction {
+pub struct Transaction<F: Vfs = RealFs> {
+    fs: F,
     operations: Vec<Operation>,
     dry_run: bool,
     state: TransactionState,
     executed_indices: Vec<usize>,
     path_redirects: HashMap<PathBuf, PathBuf>,
+    preserve_doc_mtimes: bool,
+    skipped: Vec<(PathBuf, String)>,
+    notes: HashMap<PathBuf, String>,
+    rules: BTreeMap<PathBuf, Vec<String>>,
+    edits: Vec<TracedEdit>,
+    conflicts: Vec<Conflict>,
+    package_manifests: HashSet<PathBuf>,
+    packages: Vec<(String, PathBuf)>,
+}
+
+/// Staged file updates within one workspace package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageChanges {
+    /// Package name, or `None` for files outside every package.
+    pub package: Option<String>,
+    pub files: Vec<PathBuf>,
 }
 
 impl Transaction {
-    /// Creates a new transaction.
+    /// Creates a new transaction on the real file system.
     pub fn new(dry_run: bool) -> Self {
+        Self::with_fs(RealFs, dry_run)
+    }
+}
+
+impl<F: Vfs> Transaction<F> {
+    /// Creates a new transaction on `fs`.
+    pub fn with_fs(fs: F, dry_run: bool) -> Self {
         Self {
+            fs,
             operations: Vec::new(),
             dry_run,
             state: TransactionState::Building,
             executed_indices: Vec::new(),
             path_redirects: HashMap::new(),
+            preserve_doc_mtimes: false,
+            skipped: Vec::new(),
+            notes: HashMap::new(),
+            rules: BTreeMap::new(),
+            edits: Vec::new(),
+            conflicts: Vec::new(),
+            package_manifests: HashSet::new(),
+            packages: Vec::new(),
         }
     }
 
+    /// Returns the file system the transaction operates on.
+    pub fn fs(&self) -> &F {
+        &self.fs
+    }
+
+    /// Keeps the modification time of documentation files that are rewritten.
+    ///
+    /// Only `.md`, `.markdown` and `.txt` files are affected, so cargo does not
+    /// rebuild for doc-only edits. Crates that embed such files (for example via
+    /// `#![doc = include_str!("../README.md")]`) will not see the change until
+    /// they are rebuilt for another reason.
+    pub fn preserve_doc_mtimes(&mut self, enabled: bool) {
+        self.preserve_doc_mtimes = enabled;
+    }
+
     /// Validates all staged operations.
     ///
     /// Checks:
@@ -105,6 +275,7 @@ impl Transaction {
     /// - Source paths exist
     /// - Files are writable
     /// - Target directories don't exist
+    /// - Directory moves don't nest source and target
     fn validate(&self) -> Result<()> {
         let mut file_paths = HashSet::new();
         let mut dir_moves = HashMap::new();
@@ -115,35 +286,37 @@ impl Transaction {
                     if !file_paths.insert(path.clone()) {
                         return Err(RenameError::Other(anyhow::anyhow!(
                             "Duplicate file operation: {}",
-                            path.display()
+                            display_path(path)
                         )));
                     }
 
-                    if !path.exists() {
+                    if !self.fs.exists(path) {
                         return Err(RenameError::Io(std::io::Error::new(
                             std::io::ErrorKind::NotFound,
-                            format!("File no longer exists: {}", path.display()),
+                            format!("File no longer exists: {}", display_path(path)),
                         )));
                     }
 
-                    if let Ok(metadata) = fs::metadata(path) {
-                        if metadata.permissions().readonly() {
+                    if let Ok(metadata) = self.fs.metadata(path) {
+                        if metadata.readonly {
                             return Err(RenameError::Io(std::io::Error::new(
                                 std::io::ErrorKind::PermissionDenied,
-                                format!("File is read-only: {}", path.display()),
+                                format!("File is read-only: {}", display_path(path)),
                             )));
                         }
                     }
                 }
                 Operation::MoveDirectory { from, to } => {
-                    if !from.exists() {
+                    validate_move_not_nested(from, to)?;
+
+                    if !self.fs.exists(from) {
                         return Err(RenameError::Io(std::io::Error::new(
                             std::io::ErrorKind::NotFound,
-                            format!("Directory no longer exists: {}", from.display()),
+                            format!("Directory no longer exists: {}", display_path(from)),
                         )));
                     }
 
-                    if to.exists() {
+                    if self.fs.exists(to) {
                         return Err(RenameError::DirectoryExists(to.clone()));
                     }
 
@@ -157,10 +330,11 @@ impl Transaction {
             for (from, to) in &dir_moves {
                 if file_path.starts_with(from) {
                     log::debug!(
-                        "File {} will move with directory {} → {}",
-                        file_path.display(),
-                        from.display(),
-                        to.display()
+                        "File {} will move with directory {} {} {}",
+                        display_path(file_path),
+                        display_path(from),
+                        symbols::arrow(),
+                        display_path(to)
                     );
                 }
             }
@@ -169,6 +343,142 @@ impl Transaction {
         Ok(())
     }
 
+    /// Records a path that was deliberately left out, shown in the summary.
+    pub fn record_skipped(&mut self, path: PathBuf, reason: &str) {
+        log::warn!("Skipped {}: {}", display_path(&path), reason);
+        annotate::warning(Some(&path), None, &format!("Skipped: {}", reason));
+        self.skipped.push((path, reason.to_string()));
+    }
+
+    /// Returns the paths recorded with [`Transaction::record_skipped`].
+    pub fn skipped(&self) -> &[(PathBuf, String)] {
+        &self.skipped
+    }
+
+    /// Attaches a short note to a staged file, shown next to it in the summary.
+    pub fn add_note(&mut self, path: PathBuf, note: String) {
+        self.notes.insert(path, note);
+    }
+
+    /// Records the names of the rewrite rules that produced a staged file.
+    pub fn record_rules(&mut self, path: PathBuf, rules: Vec<String>) {
+        self.rules.insert(path, rules);
+    }
+
+    /// Returns the rules recorded with [`Transaction::record_rules`], by file.
+    pub fn matched_rules(&self) -> &BTreeMap<PathBuf, Vec<String>> {
+        &self.rules
+    }
+
+    /// Records the source rule replacements that produced a staged file.
+    pub fn record_edits(&mut self, edits: Vec<TracedEdit>) {
+        self.edits.extend(edits);
+    }
+
+    /// Returns the edits recorded with [`Transaction::record_edits`], in order.
+    pub fn traced_edits(&self) -> &[TracedEdit] {
+        &self.edits
+    }
+
+    /// Records a file that was flagged instead of rewritten.
+    pub fn record_conflict(&mut self, conflict: Conflict) {
+        self.conflicts.push(conflict);
+    }
+
+    /// Returns the files recorded with [`Transaction::record_conflict`].
+    pub fn conflicts(&self) -> &[Conflict] {
+        &self.conflicts
+    }
+
+    /// Registers the workspace packages as `(name, directory)`, so the summary
+    /// can group changed files by the package that owns them.
+    pub fn set_packages(&mut self, packages: Vec<(String, PathBuf)>) {
+        self.packages = packages;
+    }
+
+    /// Groups staged file updates by owning package.
+    ///
+    /// A file belongs to the package with the deepest directory containing
+    /// it. Packages are sorted by name, with files outside every package last.
+    /// Empty if no packages were registered.
+    pub fn changes_by_package(&self) -> Vec<PackageChanges> {
+        if self.packages.is_empty() {
+            return Vec::new();
+        }
+
+        let mut groups: Vec<PackageChanges> = Vec::new();
+        for op in &self.operations {
+            let Operation::UpdateFile { path, .. } = op else {
+                continue;
+            };
+            let package = self
+                .packages
+                .iter()
+                .filter(|(_, dir)| path.starts_with(dir))
+                .max_by_key(|(_, dir)| dir.components().count())
+                .map(|(name, _)| name.clone());
+
+            match groups.iter_mut().find(|g| g.package == package) {
+                Some(group) => group.files.push(path.clone()),
+                None => groups.push(PackageChanges {
+                    package,
+                    files: vec![path.clone()],
+                }),
+            }
+        }
+
+        for group in &mut groups {
+            group.files.sort();
+        }
+        groups.sort_by(|a, b| match (&a.package, &b.package) {
+            (Some(a), Some(b)) => a.cmp(b),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        });
+        groups
+    }
+
+    /// Marks the manifest of a renamed package, listed separately in the summary.
+    ///
+    /// Without any marked manifests, the summary guesses from directory names.
+    pub fn mark_package_manifest(&mut self, path: PathBuf) {
+        self.package_manifests.insert(path);
+    }
+
+    /// Returns the summary section a staged operation is listed under.
+    pub fn category(&self, op: &Operation) -> Category {
+        let Operation::UpdateFile { path, .. } = op else {
+            return Category::Directory;
+        };
+
+        if path.file_name().is_some_and(|n| n == "Cargo.toml") {
+            if self.package_manifests.contains(path) {
+                Category::Package
+            } else {
+                Category::Dependencies
+            }
+        } else if is_doc_file(path) {
+            Category::Docs
+        } else if path.extension().is_some_and(|e| e == "toml") {
+            Category::Configs
+        } else {
+            Category::Source
+        }
+    }
+
+    /// Drops the staged operations in `categories` (`--skip-category`) and
+    /// returns how many were dropped.
+    pub fn drop_categories(&mut self, categories: &[Category]) -> usize {
+        let before = self.operations.len();
+        let keep: Vec<bool> = self
+            .operations
+            .iter()
+            .map(|op| !categories.contains(&self.category(op)))
+            .collect();
+        let mut keep = keep.into_iter();
+        self.operations.retain(|_| keep.next().unwrap_or(true));
+        before - self.operations.len()
+    }
+
     pub fn len(&self) -> usize {
         self.operations.len()
     }
@@ -177,24 +487,63 @@ impl Transaction {
         self.operations.is_empty()
     }
 
+    /// Returns true if the transaction only previews its operations.
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
     /// Returns true if successfully committed.
     pub fn is_committed(&self) -> bool {
         self.state == TransactionState::Committed
     }
 
+    /// Returns the staged operations in execution order.
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
     /// Returns human-readable preview of operations.
     pub fn preview(&self) -> Vec<String> {
         self.operations
             .iter()
             .map(|op| match op {
-                Operation::UpdateFile { path, .. } => format!("Update: {}", path.display()),
+                Operation::UpdateFile { path, .. } => format!("Update: {}", display_path(path)),
                 Operation::MoveDirectory { from, to } => {
-                    format!("Move: {} → {}", from.display(), to.display())
+                    format!(
+                        "Move: {} {} {}",
+                        display_path(from),
+                        symbols::arrow(),
+                        display_path(to)
+                    )
                 }
             })
             .collect()
     }
 
+    /// Checks that every staged `Cargo.toml` still parses as TOML.
+    ///
+    /// Catches broken line-based manifest edits before anything is written.
+    pub fn check_manifests(&self) -> Result<()> {
+        for op in &self.operations {
+            let Operation::UpdateFile { path, new, .. } = op else {
+                continue;
+            };
+
+            if path.file_name().and_then(|n| n.to_str()) != Some("Cargo.toml") {
+                continue;
+            }
+
+            if let Err(e) = new.parse::<toml_edit::DocumentMut>() {
+                return Err(RenameError::InvalidManifest(
+                    display_path(path),
+                    e.to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Prints categorized summary to stdout.
     ///
     /// Groups:
@@ -202,57 +551,74 @@ impl Transaction {
     /// - Dependencies (other Cargo.toml)
     /// - Source code (.rs)
     /// - Documentation (.md, .txt)
+    /// - Tool configs (other .toml, e.g. deny.toml)
     /// - Directory moves
     ///
     /// Paths are relative to `workspace_root` with forward slashes.
     pub fn print_summary(&self, old_name: &str, new_name: &str, workspace_root: &Path) {
+        let display_path = |path: &Path| display_relative(path, workspace_root);
+
+        if !self.skipped.is_empty() {
+            outln!(
+                "\n{} Skipped ({})",
+                symbols::warning().yellow().bold(),
+                self.skipped.len()
+            );
+            for (path, reason) in &self.skipped {
+                outln!(
+                    "   {} {} {}",
+                    symbols::bullet(),
+                    display_path(path),
+                    format!("({})", reason).dimmed()
+                );
+            }
+        }
+
         if self.operations.is_empty() {
-            println!("\n{}", "No changes needed".yellow());
+            outln!("\n{}", "No changes needed".yellow());
             return;
         }
 
-        let display_path = |path: &Path| -> String {
-            let relative =
-                pathdiff::diff_paths(path, workspace_root).unwrap_or_else(|| path.to_path_buf());
-            relative.to_string_lossy().replace('\\', "/")
-        };
-
         // Categorize operations
         let mut package_manifests = HashSet::new();
-        let mut workspace_manifests = HashSet::new();
+        let mut workspace_manifests = HashMap::new();
         let mut source_files = HashSet::new();
         let mut doc_files = HashSet::new();
+        let mut tool_configs = HashSet::new();
         let mut dir_moves = Vec::new();
 
         for op in &self.operations {
+            let mut category = self.category(op);
             match op {
                 Operation::UpdateFile { path, .. } => {
-                    let file_name = path.file_name().unwrap().to_string_lossy();
-                    let display = display_path(path);
-
-                    if file_name == "Cargo.toml" {
-                        // Determine if this is the renamed package's manifest
-                        if path
+                    // Without marked manifests, guess the package's from its directory
+                    if category == Category::Dependencies
+                        && self.package_manifests.is_empty()
+                        && path
                             .parent()
                             .and_then(|p| p.file_name())
-                            .map(|n| {
-                                n.to_string_lossy() == old_name || n.to_string_lossy() == new_name
-                            })
-                            .unwrap_or(false)
-                        {
+                            .is_some_and(|n| n == old_name || n == new_name)
+                    {
+                        category = Category::Package;
+                    }
+
+                    let display = display_path(path);
+                    match category {
+                        Category::Package => {
                             package_manifests.insert(display);
-                        } else {
-                            workspace_manifests.insert(display);
                         }
-                    } else if path.extension().and_then(|e| e.to_str()) == Some("rs") {
-                        source_files.insert(display);
-                    } else if matches!(
-                        path.extension().and_then(|e| e.to_str()),
-                        Some("md") | Some("txt")
-                    ) {
-                        doc_files.insert(display);
-                    } else {
-                        source_files.insert(display);
+                        Category::Dependencies => {
+                            workspace_manifests.insert(display, self.notes.get(path));
+                        }
+                        Category::Docs => {
+                            doc_files.insert(display);
+                        }
+                        Category::Configs => {
+                            tool_configs.insert(display);
+                        }
+                        Category::Source | Category::Directory => {
+                            source_files.insert(display);
+                        }
                     }
                 }
                 Operation::MoveDirectory { from, to } => {
@@ -266,56 +632,63 @@ impl Transaction {
         let mut workspace_manifests: Vec<_> = workspace_manifests.into_iter().collect();
         let mut source_files: Vec<_> = source_files.into_iter().collect();
         let mut doc_files: Vec<_> = doc_files.into_iter().collect();
+        let mut tool_configs: Vec<_> = tool_configs.into_iter().collect();
 
         package_manifests.sort();
         workspace_manifests.sort();
         source_files.sort();
         doc_files.sort();
+        tool_configs.sort();
 
         // Print header
         if self.dry_run {
-            println!("\n{}", "DRY RUN - No changes will be made".yellow().bold());
+            outln!("\n{}", "DRY RUN - No changes will be made".yellow().bold());
         } else {
-            println!("\n{}", "Changes applied:".green().bold());
+            outln!("\n{}", "Changes applied:".green().bold());
         }
 
         // Package manifests
         if !package_manifests.is_empty() {
-            println!("\n{} Package manifest", "📦".bold());
+            outln!("\n{} Package manifest", symbols::icon("📦").bold());
             for path in &package_manifests {
                 if self.dry_run {
-                    println!("   • {}", path.dimmed());
+                    outln!("   {} {}", symbols::bullet(), path.dimmed());
                 } else {
-                    println!("   {} {}", "✓".green(), path.dimmed());
+                    outln!("   {} {}", symbols::check().green(), path.dimmed());
                 }
             }
         }
 
         // Directory moves
         if !dir_moves.is_empty() {
-            println!("\n{} Directory", "📁".bold());
+            outln!("\n{} Directory", symbols::icon("📁").bold());
             for (from, to) in dir_moves {
-                let from_rel = pathdiff::diff_paths(from, workspace_root)
-                    .unwrap_or_else(|| from.to_path_buf());
-                let to_rel =
-                    pathdiff::diff_paths(to, workspace_root).unwrap_or_else(|| to.to_path_buf());
-
-                let from_display = from_rel.to_string_lossy().replace('\\', "/");
-                let to_display = to_rel.to_string_lossy().replace('\\', "/");
+                let from_display = display_path(from);
+                let to_display = display_path(to);
 
                 if self.dry_run {
-                    println!("   {} → {}", from_display.yellow(), to_display.green());
+                    outln!(
+                        "   {} {} {}",
+                        from_display.yellow(),
+                        symbols::arrow(),
+                        to_display.green()
+                    );
                 } else {
-                    println!("   {} → {}", from_display, to_display.green());
+                    outln!(
+                        "   {} {} {}",
+                        from_display,
+                        symbols::arrow(),
+                        to_display.green()
+                    );
                 }
             }
         }
 
         // Workspace manifests (dependencies)
         if !workspace_manifests.is_empty() {
-            println!(
+            outln!(
                 "\n{} Dependencies ({} file{})",
-                "🔗".bold(),
+                symbols::icon("🔗").bold(),
                 workspace_manifests.len(),
                 if workspace_manifests.len() == 1 {
                     ""
@@ -323,20 +696,31 @@ impl Transaction {
                     "s"
                 }
             );
-            for path in workspace_manifests.iter().take(5) {
+            for (path, note) in workspace_manifests.iter().take(5) {
+                let note = note.map(|n| format!(" ({})", n)).unwrap_or_default();
                 if self.dry_run {
-                    println!("   • {}", path.dimmed());
+                    outln!(
+                        "   {} {}{}",
+                        symbols::bullet(),
+                        path.dimmed(),
+                        note.dimmed()
+                    );
                 } else {
-                    println!("   {} {}", "✓".green(), path.dimmed());
+                    outln!(
+                        "   {} {}{}",
+                        symbols::check().green(),
+                        path.dimmed(),
+                        note.dimmed()
+                    );
                 }
             }
             if workspace_manifests.len() > 5 {
-                println!(
+                outln!(
                     "   {} {} more...",
                     if self.dry_run {
-                        "•".to_string()
+                        symbols::bullet().to_string()
                     } else {
-                        "✓".green().to_string()
+                        symbols::check().green().to_string()
                     },
                     workspace_manifests.len() - 5
                 );
@@ -345,26 +729,26 @@ impl Transaction {
 
         // Source files
         if !source_files.is_empty() {
-            println!(
+            outln!(
                 "\n{} Source code ({} file{})",
-                "📝".bold(),
+                symbols::icon("📝").bold(),
                 source_files.len(),
                 if source_files.len() == 1 { "" } else { "s" }
             );
             for path in source_files.iter().take(8) {
                 if self.dry_run {
-                    println!("   • {}", path.dimmed());
+                    outln!("   {} {}", symbols::bullet(), path.dimmed());
                 } else {
-                    println!("   {} {}", "✓".green(), path.dimmed());
+                    outln!("   {} {}", symbols::check().green(), path.dimmed());
                 }
             }
             if source_files.len() > 8 {
-                println!(
+                outln!(
                     "   {} {} more...",
                     if self.dry_run {
-                        "•".to_string()
+                        symbols::bullet().to_string()
                     } else {
-                        "✓".green().to_string()
+                        symbols::check().green().to_string()
                     },
                     source_files.len() - 8
                 );
@@ -373,53 +757,102 @@ impl Transaction {
 
         // Documentation files
         if !doc_files.is_empty() {
-            println!(
+            outln!(
                 "\n{} Documentation ({} file{})",
-                "📄".bold(),
+                symbols::icon("📄").bold(),
                 doc_files.len(),
                 if doc_files.len() == 1 { "" } else { "s" }
             );
             for path in doc_files.iter().take(5) {
                 if self.dry_run {
-                    println!("   • {}", path.dimmed());
+                    outln!("   {} {}", symbols::bullet(), path.dimmed());
                 } else {
-                    println!("   {} {}", "✓".green(), path.dimmed());
+                    outln!("   {} {}", symbols::check().green(), path.dimmed());
                 }
             }
             if doc_files.len() > 5 {
-                println!(
+                outln!(
                     "   {} {} more...",
                     if self.dry_run {
-                        "•".to_string()
+                        symbols::bullet().to_string()
                     } else {
-                        "✓".green().to_string()
+                        symbols::check().green().to_string()
                     },
                     doc_files.len() - 5
                 );
             }
         }
 
+        // Tool configs
+        if !tool_configs.is_empty() {
+            outln!(
+                "\n{} Tool configs ({} file{})",
+                symbols::icon("🔧").bold(),
+                tool_configs.len(),
+                if tool_configs.len() == 1 { "" } else { "s" }
+            );
+            for path in &tool_configs {
+                if self.dry_run {
+                    outln!("   {} {}", symbols::bullet(), path.dimmed());
+                } else {
+                    outln!("   {} {}", symbols::check().green(), path.dimmed());
+                }
+            }
+        }
+
+        // Changed files per package, once several packages are touched
+        let by_package = self.changes_by_package();
+        if by_package.len() > 1 {
+            outln!("\n{} By package", symbols::icon("🗂").bold());
+            for group in &by_package {
+                let (label, base) = match &group.package {
+                    Some(name) => (
+                        name.as_str(),
+                        self.packages
+                            .iter()
+                            .find(|(n, _)| n == name)
+                            .map(|(_, dir)| dir.as_path()),
+                    ),
+                    None => ("(workspace)", None),
+                };
+                let files: Vec<String> = group
+                    .files
+                    .iter()
+                    .map(|path| match base.and_then(|b| path.strip_prefix(b).ok()) {
+                        Some(rel) => rel.to_string_lossy().replace('\\', "/"),
+                        None => display_path(path),
+                    })
+                    .collect();
+                let shown = files.iter().take(6).cloned().collect::<Vec<_>>().join(", ");
+                let more = match files.len() {
+                    n if n > 6 => format!(" +{} more", n - 6),
+                    _ => String::new(),
+                };
+                outln!("   {}: {}{}", label.bold(), shown.dimmed(), more.dimmed());
+            }
+        }
+
         // Summary footer
-        println!();
+        outln!();
         let num_ops = self.operations.len();
         if self.dry_run {
-            println!(
+            outln!(
                 "{} {} will be modified. Run without {} to apply.",
                 num_ops.to_string().cyan().bold(),
                 if num_ops > 1 { "files" } else { "file" },
                 "--dry-run".cyan()
             );
         } else {
-            println!(
+            outln!(
                 "{} Successfully completed {} operations",
-                "✓".green().bold(),
+                symbols::check().green().bold(),
                 self.operations.len()
             );
         }
     }
 }
 
-impl Drop for Transaction {
+impl<F: Vfs> Drop for Transaction<F> {
     fn drop(&mut self) {
         if self.state == TransactionState::Building && !self.operations.is_empty() && !self.dry_run
         {
@@ -428,7 +861,7 @@ impl Drop for Transaction {
     }
 }
 
-impl Transaction {
+impl<F: Vfs> Transaction<F> {
     /// Stages a directory move.
     ///
     /// Not executed until `commit()`. Moves execute after all file updates.
@@ -439,19 +872,26 @@ impl Transaction {
             )));
         }
 
-        if to.exists() {
+        validate_move_not_nested(&from, &to)?;
+
+        if self.fs.exists(&to) {
             return Err(RenameError::DirectoryExists(to));
         }
 
-        if !from.exists() {
+        if !self.fs.exists(&from) {
             return Err(RenameError::Io(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
-                format!("Source directory does not exist: {}", from.display()),
+                format!("Source directory does not exist: {}", display_path(&from)),
             )));
         }
 
         if self.dry_run {
-            log::info!("Would move: {} → {}", from.display(), to.display());
+            log::info!(
+                "Would move: {} {} {}",
+                display_path(&from),
+                symbols::arrow(),
+                display_path(&to)
+            );
         }
 
         self.path_redirects.insert(from.clone(), to.clone());
@@ -470,23 +910,36 @@ impl Transaction {
             )));
         }
 
-        log::debug!("Staging update for: {}", path.display());
+        log::debug!("Staging update for: {}", display_path(&path));
+
+        // Fold repeated edits of the same file into the already staged operation
+        if let Some(Operation::UpdateFile { new, .. }) = self
+            .operations
+            .iter_mut()
+            .find(|op| matches!(op, Operation::UpdateFile { path: p, .. } if *p == path))
+        {
+            *new = new_content;
+            return Ok(());
+        }
 
-        let original = fs::read_to_string(&path).map_err(|e| {
-            log::error!("Failed to read {}: {}", path.display(), e);
+        let original = self.fs.read_to_string(&path).map_err(|e| {
+            log::error!("Failed to read {}: {}", display_path(&path), e);
+            if e.kind() == std::io::ErrorKind::InvalidData {
+                return RenameError::NotUtf8(path.clone());
+            }
             RenameError::Io(std::io::Error::new(
                 e.kind(),
-                format!("Failed to read {}: {}", path.display(), e),
+                format!("Failed to read {}: {}", display_path(&path), e),
             ))
         })?;
 
         if original == new_content {
-            log::debug!("Content unchanged, skipping: {}", path.display());
+            log::debug!("Content unchanged, skipping: {}", display_path(&path));
             return Ok(());
         }
 
         if self.dry_run {
-            log::info!("Would update: {}", path.display());
+            log::info!("Would update: {}", display_path(&path));
         }
 
         self.operations.push(Operation::UpdateFile {
@@ -498,6 +951,70 @@ impl Transaction {
         Ok(())
     }
 
+    /// Reads a file as it will look after commit.
+    ///
+    /// Returns the staged content if the file already has a pending update,
+    /// otherwise the current on-disk content. Lets several passes edit the
+    /// same file within one transaction.
+    ///
+    /// A path inside the destination of a staged directory move is read from
+    /// the source, so the moved tree can be inspected before commit. Fails
+    /// with [`RenameError::NotUtf8`] for files that are not valid UTF-8.
+    pub fn read_file(&self, path: &Path) -> Result<String> {
+        let path = self.redirect(path);
+        let staged = self.operations.iter().find_map(|op| match op {
+            Operation::UpdateFile { path: p, new, .. } if *p == path => Some(new.clone()),
+            _ => None,
+        });
+
+        match staged {
+            Some(content) => Ok(content),
+            None => match self.fs.read_to_string(&path) {
+                Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+                    Err(RenameError::NotUtf8(path))
+                }
+                result => Ok(result?),
+            },
+        }
+    }
+
+    /// Reads a file for a source or documentation pass, or returns `None` to
+    /// leave it alone.
+    ///
+    /// Files that are not valid UTF-8 (binary fixtures, vendored assets) are
+    /// recorded with [`Transaction::record_skipped`], once per file; other
+    /// read errors are only logged. Manifests are read with
+    /// [`Transaction::read_file`] instead, so they fail the rename.
+    pub fn read_text(&mut self, path: &Path) -> Option<String> {
+        match self.read_file(path) {
+            Ok(content) => Some(content),
+            Err(RenameError::NotUtf8(_)) => {
+                if !self.skipped.iter().any(|(skipped, _)| skipped == path) {
+                    self.record_skipped(path.to_path_buf(), "not valid UTF-8");
+                }
+                None
+            }
+            Err(e) => {
+                log::debug!("Skipping file (read error): {} - {}", display_path(path), e);
+                None
+            }
+        }
+    }
+
+    /// Maps a path inside a staged move destination back to its source.
+    ///
+    /// Once a real commit has moved the directories, paths are returned unchanged.
+    fn redirect(&self, path: &Path) -> PathBuf {
+        if self.state == TransactionState::Committed && !self.dry_run {
+            return path.to_path_buf();
+        }
+
+        self.path_redirects
+            .iter()
+            .find_map(|(from, to)| path.strip_prefix(to).ok().map(|rest| from.join(rest)))
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+
     /// Commits all staged operations atomically.
     ///
     /// Order:
@@ -524,74 +1041,153 @@ impl Transaction {
             return Err(e);
         }
 
-        // Separate ops by type
-        let mut file_ops = Vec::new();
-        let mut dir_ops = Vec::new();
+        let copy_sizes = match self.check_cross_filesystem_moves() {
+            Ok(sizes) => sizes,
+            Err(e) => {
+                self.state = TransactionState::Failed;
+                return Err(e);
+            }
+        };
+
+        // Execute file updates FIRST, directory moves SECOND
+        let (file_ops, dir_ops): (Vec<usize>, Vec<usize>) = (0..self.operations.len())
+            .partition(|&idx| matches!(self.operations[idx], Operation::UpdateFile { .. }));
+
+        for idx in file_ops.into_iter().chain(dir_ops) {
+            if let Err(e) = self.execute(idx, copy_sizes.get(&idx).copied()) {
+                return Err(self.fail_commit(e));
+            }
+            self.executed_indices.push(idx);
+        }
+
+        self.state = TransactionState::Committed;
+        Ok(())
+    }
+
+    /// Scans the source of every move that has to copy across filesystems and
+    /// checks the destination has room for it.
+    ///
+    /// Returns the scanned size per operation index, used to report progress.
+    fn check_cross_filesystem_moves(&self) -> Result<HashMap<usize, TreeSize>> {
+        let mut sizes = HashMap::new();
 
         for (idx, op) in self.operations.iter().enumerate() {
-            match op {
-                Operation::UpdateFile { .. } => file_ops.push(idx),
-                Operation::MoveDirectory { .. } => dir_ops.push(idx),
+            let Operation::MoveDirectory { from, to } = op else {
+                continue;
+            };
+            if self.is_same_filesystem(from, to)? {
+                continue;
             }
+
+            sizes.insert(idx, self.fs.check_copy(from, to)?);
         }
 
-        // Execute file updates FIRST
-        for &idx in &file_ops {
-            if let Some(Operation::UpdateFile { path, new, .. }) = self.operations.get(idx) {
-                fs::write(path, new).map_err(|e| {
+        Ok(sizes)
+    }
+
+    /// Applies a single staged operation.
+    ///
+    /// `copy_size` is the scanned size of a directory that has to be copied.
+    fn execute(&self, idx: usize, copy_size: Option<TreeSize>) -> Result<()> {
+        match &self.operations[idx] {
+            Operation::UpdateFile { path, new, .. } => {
+                let mtime = if self.preserve_doc_mtimes && is_doc_file(path) {
+                    self.fs.metadata(path).ok().and_then(|m| m.modified)
+                } else {
+                    None
+                };
+
+                self.fs.write(path, new).map_err(|e| {
                     RenameError::Io(std::io::Error::new(
                         e.kind(),
-                        format!("Failed to write {}: {}", path.display(), e),
+                        format!("Failed to write {}: {}", display_path(path), e),
                     ))
                 })?;
-                self.executed_indices.push(idx);
-                log::debug!("Updated: {}", path.display());
-            }
-        }
 
-        // Execute directory moves SECOND
-        for &idx in &dir_ops {
-            if let Some(Operation::MoveDirectory { from, to }) = self.operations.get(idx) {
+                if let Some(mtime) = mtime
+                    && let Err(e) = self.fs.set_modified(path, mtime)
+                {
+                    log::warn!("Could not restore mtime of {}: {}", display_path(path), e);
+                }
+
+                log::debug!("Updated: {}", display_path(path));
+            }
+            Operation::MoveDirectory { from, to } => {
                 if let Some(parent) = to.parent() {
-                    fs::create_dir_all(parent)?;
+                    self.fs.create_dir_all(parent)?;
                 }
 
-                if Self::is_same_filesystem(from, to)? {
-                    fs::rename(from, to).map_err(|e| {
+                if self.is_same_filesystem(from, to)? {
+                    self.fs.rename(from, to).map_err(|e| {
                         RenameError::Io(std::io::Error::new(
                             e.kind(),
                             format!(
-                                "Failed to move {} → {}: {}",
-                                from.display(),
-                                to.display(),
+                                "Failed to move {} {} {}: {}",
+                                display_path(from),
+                                symbols::arrow(),
+                                display_path(to),
                                 e
                             ),
                         ))
                     })?;
                 } else {
-                    Self::copy_dir_recursive(from, to)?;
-                    fs::remove_dir_all(from)?;
+                    self.fs.copy_dir(from, to, copy_size)?;
+                    self.fs.remove_dir_all(from)?;
                 }
 
-                self.executed_indices.push(idx);
-                log::info!("Moved: {} → {}", from.display(), to.display());
+                log::info!(
+                    "Moved: {} {} {}",
+                    display_path(from),
+                    symbols::arrow(),
+                    display_path(to)
+                );
             }
         }
 
-        self.state = TransactionState::Committed;
         Ok(())
     }
 
+    /// Rolls back the operations executed before `cause` stopped the commit.
+    ///
+    /// Returns `cause` itself if nothing was executed, otherwise a
+    /// [`RenameError::PartialFailure`] describing every executed operation.
+    fn fail_commit(&mut self, cause: RenameError) -> RenameError {
+        self.state = TransactionState::Failed;
+
+        if self.executed_indices.is_empty() {
+            return cause;
+        }
+
+        log::warn!(
+            "Commit failed, rolling back {} operations",
+            self.executed_indices.len()
+        );
+        let report = self.undo_executed(format!("Commit failed: {}", cause));
+        RenameError::PartialFailure(Box::new(report))
+    }
+
     /// Manually rolls back a committed transaction.
     ///
     /// Reverses operations in LIFO order. Only works on committed transactions.
+    ///
+    /// # Errors
+    ///
+    /// `PartialFailure` listing the operations that could not be undone.
     pub fn rollback(&mut self) -> Result<()> {
         match self.state {
             TransactionState::Building => Ok(()),
             TransactionState::Committed if self.dry_run => Ok(()),
             TransactionState::Committed => {
                 self.executed_indices = (0..self.operations.len()).collect();
-                self.rollback_partial()
+                let report = self.undo_executed("Rollback failed".to_string());
+
+                if report.is_recovered() {
+                    self.state = TransactionState::RolledBack;
+                    log::info!("Rollback completed");
+                    Ok(())
+                } else {
+                    Err(RenameError::PartialFailure(Box::new(report)))
+                }
             }
             TransactionState::Failed => Err(RenameError::Other(anyhow::anyhow!(
                 "Transaction failed; rollback already attempted"
@@ -602,94 +1198,78 @@ impl Transaction {
         }
     }
 
-    /// Rolls back executed operations only.
-    fn rollback_partial(&mut self) -> Result<()> {
-        let mut errors = Vec::new();
+    /// Reverses executed operations in LIFO order, recording each result.
+    fn undo_executed(&self, cause: String) -> PartialFailure {
+        let mut report = PartialFailure {
+            cause,
+            total: self.operations.len(),
+            executed: Vec::new(),
+            rolled_back: Vec::new(),
+            unrecoverable: Vec::new(),
+        };
+
+        for &idx in &self.executed_indices {
+            report
+                .executed
+                .push(AffectedOperation::from(&self.operations[idx]));
+        }
 
         for &idx in self.executed_indices.iter().rev() {
-            if let Some(op) = self.operations.get(idx) {
-                let result = match op {
-                    Operation::UpdateFile { path, original, .. } => fs::write(path, original)
-                        .map_err(|e| format!("Failed to restore {}: {}", path.display(), e)),
-                    Operation::MoveDirectory { from, to } => {
-                        if to.exists() {
-                            if Self::is_same_filesystem(to, from).unwrap_or(true) {
-                                fs::rename(to, from).map_err(|e| {
-                                    format!("Failed to move back {}: {}", to.display(), e)
-                                })
-                            } else {
-                                Self::copy_dir_recursive(to, from)
-                                    .and_then(|_| fs::remove_dir_all(to).map_err(Into::into))
-                                    .map_err(|e| {
-                                        format!("Failed to restore {}: {}", from.display(), e)
-                                    })
-                            }
-                        } else {
-                            Ok(())
-                        }
+            let op = &self.operations[idx];
+            let result = match op {
+                Operation::UpdateFile { path, original, .. } => {
+                    self.fs.write(path, original).map_err(|e| e.to_string())
+                }
+                Operation::MoveDirectory { from, to } => {
+                    if !self.fs.exists(to) {
+                        Ok(())
+                    } else if self.is_same_filesystem(to, from).unwrap_or(true) {
+                        self.fs.rename(to, from).map_err(|e| e.to_string())
+                    } else {
+                        self.fs
+                            .copy_dir(to, from, None)
+                            .and_then(|_| self.fs.remove_dir_all(to).map_err(Into::into))
+                            .map_err(|e| e.to_string())
                     }
-                };
-
-                if let Err(e) = result {
-                    errors.push(e);
                 }
+            };
+
+            match result {
+                Ok(()) => report.rolled_back.push(AffectedOperation::from(op)),
+                Err(e) => report.unrecoverable.push((AffectedOperation::from(op), e)),
             }
         }
 
-        if errors.is_empty() {
-            self.state = TransactionState::RolledBack;
-            log::info!("Rollback completed");
-            Ok(())
-        } else {
-            Err(RenameError::RollbackFailed(errors.join("; ")))
-        }
+        report
     }
 
     /// Checks if paths are on same filesystem.
     ///
     /// Determines if atomic `rename()` is possible, or if cross-filesystem
     /// copy+delete is required.
-    fn is_same_filesystem(path1: &Path, path2: &Path) -> Result<bool> {
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::MetadataExt;
-            let meta1 = fs::metadata(path1)?;
-            let meta2_parent = path2.parent().unwrap_or(path2);
-            let meta2 = fs::metadata(meta2_parent)?;
-            Ok(meta1.dev() == meta2.dev())
-        }
-
-        #[cfg(not(unix))]
-        {
-            let path1_str = path1.to_string_lossy();
-            let path2_str = path2.to_string_lossy();
-
-            if path1_str.len() >= 2 && path2_str.len() >= 2 {
-                Ok(path1_str.chars().next() == path2_str.chars().next())
-            } else {
-                Ok(true)
-            }
+    fn is_same_filesystem(&self, path1: &Path, path2: &Path) -> Result<bool> {
+        let meta1 = self.fs.metadata(path1)?;
+        // The destination's parent may not be created yet
+        let existing = path2
+            .ancestors()
+            .skip(1)
+            .find(|p| self.fs.exists(p))
+            .unwrap_or(path2);
+        let meta2 = self.fs.metadata(existing)?;
+
+        if let (Some(dev1), Some(dev2)) = (meta1.device, meta2.device) {
+            return Ok(dev1 == dev2);
         }
-    }
-
-    /// Recursively copies directory tree.
-    fn copy_dir_recursive(from: &Path, to: &Path) -> Result<()> {
-        fs::create_dir_all(to)?;
 
-        for entry in fs::read_dir(from)? {
-            let entry = entry?;
-            let file_type = entry.file_type()?;
-            let from_path = entry.path();
-            let to_path = to.join(entry.file_name());
+        // Without device ids, compare drive prefixes
+        let path1_str = path1.to_string_lossy();
+        let path2_str = path2.to_string_lossy();
 
-            if file_type.is_dir() {
-                Self::copy_dir_recursive(&from_path, &to_path)?;
-            } else {
-                fs::copy(&from_path, &to_path)?;
-            }
+        if path1_str.len() >= 2 && path2_str.len() >= 2 {
+            Ok(path1_str.chars().next() == path2_str.chars().next())
+        } else {
+            Ok(true)
         }
-
-        Ok(())
     }
 }
 
@@ -701,7 +1281,95 @@ pub struct TransactionStats {
     pub total: usize,
 }
 
-impl Transaction {
+/// Staged file updates by kind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChangeCounts {
+    /// `Cargo.toml` files.
+    pub manifests: usize,
+    /// `.rs` files.
+    pub sources: usize,
+    /// `.md`, `.markdown` and `.txt` files.
+    pub docs: usize,
+    /// Tool configs and files rewritten by hooks.
+    pub other: usize,
+}
+
+impl<F: Vfs> Transaction<F> {
+    /// Counts the staged file updates by kind.
+    pub fn change_counts(&self) -> ChangeCounts {
+        let mut counts = ChangeCounts::default();
+
+        for path in self.staged_files() {
+            if path.file_name().is_some_and(|n| n == "Cargo.toml") {
+                counts.manifests += 1;
+            } else if path.extension().is_some_and(|e| e == "rs") {
+                counts.sources += 1;
+            } else if is_doc_file(&path) {
+                counts.docs += 1;
+            } else {
+                counts.other += 1;
+            }
+        }
+
+        counts
+    }
+
+    /// Returns the paths of all staged file updates, sorted.
+    pub fn staged_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = self
+            .operations
+            .iter()
+            .filter_map(|op| match op {
+                Operation::UpdateFile { path, .. } => Some(path.clone()),
+                Operation::MoveDirectory { .. } => None,
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Returns the staged directory moves as `(from, to)`.
+    pub fn directory_moves(&self) -> Vec<(PathBuf, PathBuf)> {
+        self.operations
+            .iter()
+            .filter_map(|op| match op {
+                Operation::MoveDirectory { from, to } => Some((from.clone(), to.clone())),
+                Operation::UpdateFile { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Returns each updated file at its location after the directory moves,
+    /// with the content the transaction writes, sorted by path.
+    pub fn written_files(&self) -> Vec<(PathBuf, &str)> {
+        let mut files: Vec<(PathBuf, &str)> = self
+            .operations
+            .iter()
+            .filter_map(|op| match op {
+                Operation::UpdateFile { path, new, .. } => {
+                    Some((self.moved_path(path), new.as_str()))
+                }
+                Operation::MoveDirectory { .. } => None,
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Returns where `path` ends up after the staged directory moves.
+    pub fn moved_path(&self, path: &Path) -> PathBuf {
+        self.directory_moves()
+            .into_iter()
+            .find_map(|(from, to)| path.strip_prefix(&from).ok().map(|rest| to.join(rest)))
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+
+    /// Drops all staged operations without applying them, e.g. when the
+    /// user declines the confirmation prompt.
+    pub fn discard(mut self) {
+        self.operations.clear();
+    }
+
     /// Returns operation statistics.
     pub fn stats(&self) -> TransactionStats {
         let mut files_updated = 0;
@@ -722,12 +1390,196 @@ impl Transaction {
     }
 }
 
+/// Returns `true` for documentation files that cargo never compiles directly.
+fn is_doc_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("md") | Some("markdown") | Some("txt")
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fs::vfs::MemoryFs;
     use std::fs;
+    use std::time::{Duration, SystemTime};
     use tempfile::TempDir;
 
+    #[test]
+    fn test_memory_fs_commit_moves_updated_files() {
+        let fs = MemoryFs::new();
+        fs.add_file("/ws/old-crate/Cargo.toml", "name = \"old-crate\"");
+        fs.add_file("/ws/old-crate/src/lib.rs", "pub fn f() {}");
+
+        let mut txn = Transaction::with_fs(fs, false);
+        txn.update_file(
+            PathBuf::from("/ws/old-crate/Cargo.toml"),
+            "name = \"new-crate\"".to_string(),
+        )
+        .unwrap();
+        txn.move_directory(
+            PathBuf::from("/ws/old-crate"),
+            PathBuf::from("/ws/new-crate"),
+        )
+        .unwrap();
+
+        // Before commit, the new location reads through the staged move
+        assert_eq!(
+            txn.read_file(Path::new("/ws/new-crate/Cargo.toml"))
+                .unwrap(),
+            "name = \"new-crate\""
+        );
+        assert_eq!(
+            txn.read_file(Path::new("/ws/new-crate/src/lib.rs"))
+                .unwrap(),
+            "pub fn f() {}"
+        );
+
+        txn.commit().unwrap();
+        assert_eq!(
+            txn.fs().files(),
+            vec![
+                PathBuf::from("/ws/new-crate/Cargo.toml"),
+                PathBuf::from("/ws/new-crate/src/lib.rs"),
+            ]
+        );
+        assert_eq!(
+            txn.fs()
+                .file(Path::new("/ws/new-crate/Cargo.toml"))
+                .unwrap(),
+            "name = \"new-crate\""
+        );
+    }
+
+    #[test]
+    fn test_memory_fs_rollback_after_failed_write() {
+        let fs = MemoryFs::new();
+        fs.add_file("/ws/a.toml", "a = 1");
+        fs.add_file("/ws/b.toml", "b = 1");
+        fs.add_file("/ws/c.toml", "c = 1");
+        fs.fail_on("/ws/c.toml");
+
+        let mut txn = Transaction::with_fs(fs, false);
+        for (name, content) in [("a", "a = 2"), ("b", "b = 2"), ("c", "c = 2")] {
+            txn.update_file(PathBuf::from(format!("/ws/{name}.toml")), content.into())
+                .unwrap();
+        }
+
+        let Err(RenameError::PartialFailure(report)) = txn.commit() else {
+            panic!("expected a partial failure");
+        };
+        assert_eq!(report.executed.len(), 2);
+        assert_eq!(report.rolled_back.len(), 2);
+        assert!(report.is_recovered());
+        assert_eq!(txn.fs().file(Path::new("/ws/a.toml")).unwrap(), "a = 1");
+        assert_eq!(txn.fs().file(Path::new("/ws/b.toml")).unwrap(), "b = 1");
+    }
+
+    #[test]
+    fn test_memory_fs_unrecoverable_rollback() {
+        let fs = MemoryFs::new();
+        fs.add_file("/ws/Cargo.toml", "members = [\"old\"]");
+        fs.add_file("/ws/old/Cargo.toml", "");
+
+        let mut txn = Transaction::with_fs(fs, false);
+        txn.update_file(
+            PathBuf::from("/ws/Cargo.toml"),
+            "members = [\"new\"]".into(),
+        )
+        .unwrap();
+        txn.move_directory(PathBuf::from("/ws/old"), PathBuf::from("/ws/new"))
+            .unwrap();
+        txn.commit().unwrap();
+
+        // The manifest can no longer be written, but the move is undone
+        txn.fs().fail_on("/ws/Cargo.toml");
+        let Err(RenameError::PartialFailure(report)) = txn.rollback() else {
+            panic!("expected a partial failure");
+        };
+        assert_eq!(
+            report.rolled_back,
+            vec![AffectedOperation::MoveDirectory {
+                from: PathBuf::from("/ws/old"),
+                to: PathBuf::from("/ws/new"),
+            }]
+        );
+        assert_eq!(report.unrecoverable.len(), 1);
+        assert!(txn.fs().file(Path::new("/ws/old/Cargo.toml")).is_some());
+    }
+
+    #[test]
+    fn test_changes_by_package() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        for file in [
+            "README.md",
+            "crate-b/Cargo.toml",
+            "crate-b/src/lib.rs",
+            "crate-a/Cargo.toml",
+        ] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, "old").unwrap();
+        }
+
+        let mut txn = Transaction::new(true);
+        txn.set_packages(vec![
+            ("root".to_string(), root.to_path_buf()),
+            ("crate-b".to_string(), root.join("crate-b")),
+            ("crate-a".to_string(), root.join("crate-a")),
+        ]);
+        for file in [
+            "crate-b/src/lib.rs",
+            "README.md",
+            "crate-b/Cargo.toml",
+            "crate-a/Cargo.toml",
+        ] {
+            txn.update_file(root.join(file), "new".to_string()).unwrap();
+        }
+
+        let groups = txn.changes_by_package();
+        let names: Vec<_> = groups.iter().map(|g| g.package.as_deref()).collect();
+        assert_eq!(names, vec![Some("crate-a"), Some("crate-b"), Some("root")]);
+        assert_eq!(
+            groups[1].files,
+            vec![
+                root.join("crate-b/Cargo.toml"),
+                root.join("crate-b/src/lib.rs")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_change_counts() {
+        let temp = TempDir::new().unwrap();
+        let mut txn = Transaction::new(true);
+        for file in [
+            "Cargo.toml",
+            "src/lib.rs",
+            "src/main.rs",
+            "README.md",
+            "deny.toml",
+        ] {
+            let path = temp.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, "old").unwrap();
+            txn.update_file(path, "new".to_string()).unwrap();
+        }
+
+        assert_eq!(
+            txn.change_counts(),
+            ChangeCounts {
+                manifests: 1,
+                sources: 2,
+                docs: 1,
+                other: 1,
+            }
+        );
+        assert_eq!(txn.staged_files()[0], temp.path().join("Cargo.toml"));
+        txn.discard();
+    }
+
     #[test]
     fn test_new_transaction() {
         let txn = Transaction::new(false);
@@ -767,6 +1619,25 @@ mod tests {
         assert_eq!(txn.len(), 0);
     }
 
+    #[test]
+    fn test_update_file_twice_merges_operation() {
+        let temp = TempDir::new().unwrap();
+        let file_path = temp.path().join("test.txt");
+        fs::write(&file_path, "original").unwrap();
+
+        let mut txn = Transaction::new(false);
+        txn.update_file(file_path.clone(), "first".to_string())
+            .unwrap();
+        assert_eq!(txn.read_file(&file_path).unwrap(), "first");
+
+        txn.update_file(file_path.clone(), "second".to_string())
+            .unwrap();
+        assert_eq!(txn.len(), 1);
+
+        txn.commit().unwrap();
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "second");
+    }
+
     #[test]
     fn test_update_file_nonexistent_fails() {
         let temp = TempDir::new().unwrap();
@@ -813,6 +1684,31 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_move_directory_into_itself_fails() {
+        let temp = TempDir::new().unwrap();
+        let from = temp.path().join("old_dir");
+        let to = from.join("nested");
+        fs::create_dir(&from).unwrap();
+
+        let mut txn = Transaction::new(false);
+        let result = txn.move_directory(from, to);
+
+        assert!(matches!(result, Err(RenameError::InvalidPath(..))));
+    }
+
+    #[test]
+    fn test_move_directory_onto_ancestor_fails() {
+        let temp = TempDir::new().unwrap();
+        let from = temp.path().join("crates").join("old_dir");
+        fs::create_dir_all(&from).unwrap();
+
+        let mut txn = Transaction::new(false);
+        let result = txn.move_directory(from, temp.path().join("crates"));
+
+        assert!(matches!(result, Err(RenameError::InvalidPath(..))));
+    }
+
     #[test]
     fn test_dry_run_does_not_modify_files() {
         let temp = TempDir::new().unwrap();
@@ -998,6 +1894,53 @@ mod tests {
         assert!(dir_to.exists());
     }
 
+    #[test]
+    fn test_preserve_doc_mtimes() {
+        let temp = TempDir::new().unwrap();
+        let readme = temp.path().join("README.md");
+        let lib = temp.path().join("lib.rs");
+        let past = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+
+        for path in [&readme, &lib] {
+            fs::write(path, "old").unwrap();
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(past)
+                .unwrap();
+        }
+
+        let mut txn = Transaction::new(false);
+        txn.preserve_doc_mtimes(true);
+        txn.update_file(readme.clone(), "new".into()).unwrap();
+        txn.update_file(lib.clone(), "new".into()).unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(fs::read_to_string(&readme).unwrap(), "new");
+        assert_eq!(fs::metadata(&readme).unwrap().modified().unwrap(), past);
+        assert_ne!(fs::metadata(&lib).unwrap().modified().unwrap(), past);
+    }
+
+    #[test]
+    fn test_check_manifests_rejects_invalid_toml() {
+        let temp = TempDir::new().unwrap();
+        let manifest = temp.path().join("Cargo.toml");
+        fs::write(&manifest, "[package]\nname = \"old\"\n").unwrap();
+
+        let mut txn = Transaction::new(false);
+        txn.update_file(manifest.clone(), "[package]\nname = \"new\"\n".into())
+            .unwrap();
+        assert!(txn.check_manifests().is_ok());
+
+        txn.update_file(manifest, "[package\nname = \"new\"\n".into())
+            .unwrap();
+        assert!(matches!(
+            txn.check_manifests(),
+            Err(RenameError::InvalidManifest(..))
+        ));
+    }
+
     #[test]
     fn test_print_summary_empty() {
         let temp = TempDir::new().unwrap();
@@ -1020,6 +1963,88 @@ mod tests {
         txn.print_summary("old", "new", temp.path());
     }
 
+    #[test]
+    fn test_drop_categories() {
+        let fs = MemoryFs::new();
+        for path in [
+            "/ws/Cargo.toml",
+            "/ws/old/Cargo.toml",
+            "/ws/old/src/lib.rs",
+            "/ws/old/README.md",
+            "/ws/deny.toml",
+        ] {
+            fs.add_file(path, "old");
+        }
+
+        let mut txn = Transaction::with_fs(fs, true);
+        txn.mark_package_manifest(PathBuf::from("/ws/old/Cargo.toml"));
+        for path in [
+            "/ws/Cargo.toml",
+            "/ws/old/Cargo.toml",
+            "/ws/old/src/lib.rs",
+            "/ws/old/README.md",
+            "/ws/deny.toml",
+        ] {
+            txn.update_file(PathBuf::from(path), "new".to_string())
+                .unwrap();
+        }
+        txn.move_directory(PathBuf::from("/ws/old"), PathBuf::from("/ws/new"))
+            .unwrap();
+
+        let categories: Vec<Category> =
+            txn.operations().iter().map(|op| txn.category(op)).collect();
+        assert_eq!(
+            categories,
+            [
+                Category::Dependencies,
+                Category::Package,
+                Category::Source,
+                Category::Docs,
+                Category::Configs,
+                Category::Directory
+            ]
+        );
+
+        assert_eq!(txn.drop_categories(&[Category::Docs, Category::Configs]), 2);
+        assert_eq!(
+            txn.staged_files(),
+            [
+                PathBuf::from("/ws/Cargo.toml"),
+                PathBuf::from("/ws/old/Cargo.toml"),
+                PathBuf::from("/ws/old/src/lib.rs")
+            ]
+        );
+        assert_eq!(txn.directory_moves().len(), 1);
+    }
+
+    #[test]
+    fn test_non_utf8_files() {
+        let temp = TempDir::new().unwrap();
+        let manifest = temp.path().join("Cargo.toml");
+        fs::write(&manifest, b"[package]\n# caf\xe9\nname = \"old\"\n").unwrap();
+        let fixture = temp.path().join("fixture.rs");
+        fs::write(&fixture, b"// \xff\xfe old_crate\n").unwrap();
+
+        let mut txn = Transaction::new(false);
+        assert!(matches!(
+            txn.read_file(&manifest),
+            Err(RenameError::NotUtf8(path)) if path == manifest
+        ));
+        assert!(matches!(
+            txn.update_file(manifest.clone(), String::new()),
+            Err(RenameError::NotUtf8(path)) if path == manifest
+        ));
+
+        // Skipped once, however many passes look at it
+        assert_eq!(txn.read_text(&fixture), None);
+        assert_eq!(txn.read_text(&fixture), None);
+        assert_eq!(
+            txn.skipped(),
+            [(fixture.clone(), "not valid UTF-8".to_string())]
+        );
+        assert!(txn.is_empty());
+    }
+
     #[test]
     fn test_categorization_in_summary() {
         let temp = TempDir::new().unwrap();
@@ -1162,4 +2187,71 @@ mod tests {
             // Expected behavior
         }
     }
+
+    #[test]
+    fn test_commit_failure_rolls_back_executed_operations() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("file.txt");
+        let dir = temp.path().join("old-dir");
+        let blocker = temp.path().join("blocker");
+
+        fs::write(&file, "original").unwrap();
+        fs::create_dir(&dir).unwrap();
+        fs::write(&blocker, "").unwrap();
+
+        let mut txn = Transaction::new(false);
+        txn.update_file(file.clone(), "updated".to_string())
+            .unwrap();
+        // The parent of the target is a file, so the move fails after the update
+        txn.move_directory(dir.clone(), blocker.join("new-dir"))
+            .unwrap();
+
+        match txn.commit() {
+            Err(RenameError::PartialFailure(report)) => {
+                assert!(report.is_recovered());
+                assert_eq!(report.total, 2);
+                assert_eq!(
+                    report.executed,
+                    vec![AffectedOperation::UpdateFile { path: file.clone() }]
+                );
+                assert_eq!(report.rolled_back, report.executed);
+            }
+            other => panic!("expected partial failure, got {:?}", other),
+        }
+
+        assert_eq!(fs::read_to_string(&file).unwrap(), "original");
+        assert!(dir.exists());
+    }
+
+    #[test]
+    fn test_partial_failure_lists_unrecoverable_operations() {
+        let report = PartialFailure {
+            cause: "Commit failed: disk full".to_string(),
+            total: 3,
+            executed: vec![
+                AffectedOperation::UpdateFile {
+                    path: PathBuf::from("a/Cargo.toml"),
+                },
+                AffectedOperation::UpdateFile {
+                    path: PathBuf::from("b/Cargo.toml"),
+                },
+            ],
+            rolled_back: vec![AffectedOperation::UpdateFile {
+                path: PathBuf::from("a/Cargo.toml"),
+            }],
+            unrecoverable: vec![(
+                AffectedOperation::UpdateFile {
+                    path: PathBuf::from("b/Cargo.toml"),
+                },
+                "permission denied".to_string(),
+            )],
+        };
+
+        let message = report.to_string();
+        assert!(message.starts_with(
+            "Commit failed: disk full\n2 of 3 operations were applied, 1 rolled back"
+        ));
+        assert!(message.contains("update b/Cargo.toml: permission denied"));
+        assert!(message.contains("git checkout"));
+    }
 }
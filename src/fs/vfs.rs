@@ -0,0 +1,422 @@
+//! File system abstraction used by [`Transaction`](crate::fs::Transaction).
+//!
+//! [`RealFs`] forwards to `std::fs`. [`MemoryFs`] keeps files and directories
+//! in memory, so transactions (including directory moves and rollback after a
+//! failed write) can be exercised without touching the disk.
+
+use crate::error::Result;
+use crate::fs::copy::{self, TreeSize};
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::SystemTime;
+
+/// The subset of file metadata a transaction looks at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metadata {
+    pub is_dir: bool,
+    pub readonly: bool,
+    pub modified: Option<SystemTime>,
+    /// Device the path lives on, where the platform exposes it.
+    pub device: Option<u64>,
+}
+
+/// File system operations needed to stage, commit and roll back a transaction.
+pub trait Vfs {
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+
+    fn write(&self, path: &Path, contents: &str) -> io::Result<()>;
+
+    /// Renames a file or directory; both paths must be on the same device.
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata>;
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    fn set_modified(&self, path: &Path, mtime: SystemTime) -> io::Result<()>;
+
+    /// Scans `from` and checks the device of `to` has room for a copy of it.
+    fn check_copy(&self, from: &Path, to: &Path) -> Result<TreeSize>;
+
+    /// Copies the directory tree `from` to `to`, for moves across devices.
+    fn copy_dir(&self, from: &Path, to: &Path, size: Option<TreeSize>) -> Result<()>;
+
+    fn exists(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok()
+    }
+}
+
+/// The real file system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RealFs;
+
+impl Vfs for RealFs {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn write(&self, path: &Path, contents: &str) -> io::Result<()> {
+        fs::write(path, contents)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let meta = fs::metadata(path)?;
+
+        #[cfg(unix)]
+        let device = {
+            use std::os::unix::fs::MetadataExt;
+            Some(meta.dev())
+        };
+        #[cfg(not(unix))]
+        let device = None;
+
+        Ok(Metadata {
+            is_dir: meta.is_dir(),
+            readonly: meta.permissions().readonly(),
+            modified: meta.modified().ok(),
+            device,
+        })
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir_all(path)
+    }
+
+    fn set_modified(&self, path: &Path, mtime: SystemTime) -> io::Result<()> {
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .and_then(|f| f.set_modified(mtime))
+    }
+
+    fn check_copy(&self, from: &Path, to: &Path) -> Result<TreeSize> {
+        let size = copy::scan_tree(from)?;
+        copy::check_free_space(to, size)?;
+        Ok(size)
+    }
+
+    fn copy_dir(&self, from: &Path, to: &Path, size: Option<TreeSize>) -> Result<()> {
+        copy::copy_dir(from, to, size)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MemoryFile {
+    content: String,
+    readonly: bool,
+    modified: SystemTime,
+}
+
+#[derive(Debug, Default)]
+struct MemoryTree {
+    files: BTreeMap<PathBuf, MemoryFile>,
+    dirs: BTreeSet<PathBuf>,
+    failing: BTreeSet<PathBuf>,
+}
+
+impl MemoryTree {
+    fn is_dir(&self, path: &Path) -> bool {
+        path.parent().is_none() || path.as_os_str().is_empty() || self.dirs.contains(path)
+    }
+
+    fn add_ancestors(&mut self, path: &Path) {
+        for dir in path.ancestors().skip(1) {
+            if !dir.as_os_str().is_empty() {
+                self.dirs.insert(dir.to_path_buf());
+            }
+        }
+    }
+
+    fn check_writable(&self, path: &Path) -> io::Result<()> {
+        if self.failing.contains(path) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("injected failure: {}", path.display()),
+            ));
+        }
+        if self.files.get(path).is_some_and(|f| f.readonly) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("read-only: {}", path.display()),
+            ));
+        }
+        Ok(())
+    }
+
+    fn parent_exists(&self, path: &Path) -> io::Result<()> {
+        match path.parent() {
+            Some(parent) if !self.is_dir(parent) => Err(not_found(parent)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Paths of the files and directories strictly below `dir`.
+    fn descendants(&self, dir: &Path) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let below = |p: &&PathBuf| p.starts_with(dir) && p.as_path() != dir;
+        (
+            self.files.keys().filter(below).cloned().collect(),
+            self.dirs.iter().filter(below).cloned().collect(),
+        )
+    }
+}
+
+/// An in-memory file system.
+///
+/// Every path reports the same device, so directory moves always rename.
+/// [`MemoryFs::fail_on`] makes writes and renames of a path fail, to test
+/// rollback.
+#[derive(Debug, Default)]
+pub struct MemoryFs {
+    tree: Mutex<MemoryTree>,
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn tree(&self) -> MutexGuard<'_, MemoryTree> {
+        self.tree.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Adds a file, creating its parent directories.
+    pub fn add_file(&self, path: impl Into<PathBuf>, content: &str) {
+        let path = path.into();
+        let mut tree = self.tree();
+        tree.add_ancestors(&path);
+        tree.files.insert(
+            path,
+            MemoryFile {
+                content: content.to_string(),
+                readonly: false,
+                modified: SystemTime::now(),
+            },
+        );
+    }
+
+    /// Adds a directory and its parents.
+    pub fn add_dir(&self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        let mut tree = self.tree();
+        tree.add_ancestors(&path);
+        tree.dirs.insert(path);
+    }
+
+    /// Marks an existing file read-only.
+    pub fn set_readonly(&self, path: &Path) {
+        if let Some(file) = self.tree().files.get_mut(path) {
+            file.readonly = true;
+        }
+    }
+
+    /// Makes every later write to or rename of `path` fail.
+    pub fn fail_on(&self, path: impl Into<PathBuf>) {
+        self.tree().failing.insert(path.into());
+    }
+
+    /// Returns the content of a file, or `None` if it does not exist.
+    pub fn file(&self, path: &Path) -> Option<String> {
+        self.tree().files.get(path).map(|f| f.content.clone())
+    }
+
+    /// Returns all file paths, sorted.
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.tree().files.keys().cloned().collect()
+    }
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("no such file or directory: {}", path.display()),
+    )
+}
+
+impl Vfs for MemoryFs {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        self.file(path).ok_or_else(|| not_found(path))
+    }
+
+    fn write(&self, path: &Path, contents: &str) -> io::Result<()> {
+        let mut tree = self.tree();
+        tree.check_writable(path)?;
+        tree.parent_exists(path)?;
+        if tree.is_dir(path) {
+            return Err(io::Error::new(
+                io::ErrorKind::IsADirectory,
+                format!("is a directory: {}", path.display()),
+            ));
+        }
+        tree.files.insert(
+            path.to_path_buf(),
+            MemoryFile {
+                content: contents.to_string(),
+                readonly: false,
+                modified: SystemTime::now(),
+            },
+        );
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut tree = self.tree();
+        tree.check_writable(from)?;
+        tree.check_writable(to)?;
+        tree.parent_exists(to)?;
+        if tree.files.contains_key(to) || tree.dirs.contains(to) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("already exists: {}", to.display()),
+            ));
+        }
+
+        if let Some(file) = tree.files.remove(from) {
+            tree.files.insert(to.to_path_buf(), file);
+            return Ok(());
+        }
+        if !tree.dirs.remove(from) {
+            return Err(not_found(from));
+        }
+
+        let (files, dirs) = tree.descendants(from);
+        for path in files {
+            let file = tree.files.remove(&path).unwrap();
+            let rest = path.strip_prefix(from).unwrap();
+            tree.files.insert(to.join(rest), file);
+        }
+        for path in dirs {
+            tree.dirs.remove(&path);
+            tree.dirs.insert(to.join(path.strip_prefix(from).unwrap()));
+        }
+        tree.dirs.insert(to.to_path_buf());
+        Ok(())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let tree = self.tree();
+        if let Some(file) = tree.files.get(path) {
+            return Ok(Metadata {
+                is_dir: false,
+                readonly: file.readonly,
+                modified: Some(file.modified),
+                device: Some(0),
+            });
+        }
+        if tree.is_dir(path) {
+            return Ok(Metadata {
+                is_dir: true,
+                readonly: false,
+                modified: None,
+                device: Some(0),
+            });
+        }
+        Err(not_found(path))
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.add_dir(path);
+        Ok(())
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut tree = self.tree();
+        if !tree.dirs.remove(path) {
+            return Err(not_found(path));
+        }
+        let (files, dirs) = tree.descendants(path);
+        for file in files {
+            tree.files.remove(&file);
+        }
+        for dir in dirs {
+            tree.dirs.remove(&dir);
+        }
+        Ok(())
+    }
+
+    fn set_modified(&self, path: &Path, mtime: SystemTime) -> io::Result<()> {
+        match self.tree().files.get_mut(path) {
+            Some(file) => {
+                file.modified = mtime;
+                Ok(())
+            }
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn check_copy(&self, from: &Path, _to: &Path) -> Result<TreeSize> {
+        let tree = self.tree();
+        let (files, _) = tree.descendants(from);
+        Ok(TreeSize {
+            files: files.len() as u64,
+            bytes: files
+                .iter()
+                .map(|f| tree.files[f].content.len() as u64)
+                .sum(),
+        })
+    }
+
+    fn copy_dir(&self, from: &Path, to: &Path, _size: Option<TreeSize>) -> Result<()> {
+        let mut tree = self.tree();
+        let (files, dirs) = tree.descendants(from);
+        tree.add_ancestors(to);
+        tree.dirs.insert(to.to_path_buf());
+        for dir in dirs {
+            tree.dirs.insert(to.join(dir.strip_prefix(from).unwrap()));
+        }
+        for path in files {
+            let file = tree.files[&path].clone();
+            tree.files
+                .insert(to.join(path.strip_prefix(from).unwrap()), file);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_fs_moves_directory_trees() {
+        let fs = MemoryFs::new();
+        fs.add_file("/ws/old/Cargo.toml", "[package]");
+        fs.add_file("/ws/old/src/lib.rs", "pub fn f() {}");
+        fs.add_file("/ws/other/lib.rs", "");
+
+        fs.rename(Path::new("/ws/old"), Path::new("/ws/new"))
+            .unwrap();
+
+        assert_eq!(
+            fs.files(),
+            vec![
+                PathBuf::from("/ws/new/Cargo.toml"),
+                PathBuf::from("/ws/new/src/lib.rs"),
+                PathBuf::from("/ws/other/lib.rs"),
+            ]
+        );
+        assert!(fs.metadata(Path::new("/ws/new/src")).unwrap().is_dir);
+        assert!(!fs.exists(Path::new("/ws/old")));
+
+        let err = fs
+            .rename(Path::new("/ws/new"), Path::new("/ws/other"))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+
+        let err = fs.write(Path::new("/ws/missing/lib.rs"), "").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
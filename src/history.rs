@@ -0,0 +1,331 @@
+//! History of completed renames.
+//!
+//! Every applied rename is appended to `<workspace>/.cargo-rename/history.json`
+//! with its directories, a timestamp and the git `HEAD` it was made on.
+//! `cargo rename log` prints the history, and referencing an old name fails
+//! with a hint pointing at its current name. Nothing is recorded with
+//! `--no-history` or in CI, where the file would only dirty the checkout.
+
+use crate::error::Result;
+use crate::fs::display_path;
+use crate::process;
+use crate::stats::STATE_DIR;
+use crate::symbols;
+
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// File name of the history file inside [`STATE_DIR`].
+pub const HISTORY_FILE: &str = "history.json";
+
+/// A single completed rename.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub old_name: String,
+    pub new_name: String,
+    /// Package directory before the rename, relative to the workspace root.
+    pub old_dir: PathBuf,
+    /// Package directory after the rename, relative to the workspace root.
+    pub new_dir: PathBuf,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// Commit checked out when the rename was made, if in a git repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_head: Option<String>,
+}
+
+impl HistoryEntry {
+    /// Creates an entry stamped with the current time and git `HEAD`.
+    pub fn new(
+        workspace_root: &Path,
+        old_name: &str,
+        new_name: &str,
+        old_dir: &Path,
+        new_dir: &Path,
+    ) -> Self {
+        let relative = |dir: &Path| match dir.strip_prefix(workspace_root) {
+            Ok(rel) if rel.as_os_str().is_empty() => PathBuf::from("."),
+            Ok(rel) => rel.to_path_buf(),
+            Err(_) => dir.to_path_buf(),
+        };
+
+        Self {
+            old_name: old_name.to_string(),
+            new_name: new_name.to_string(),
+            old_dir: relative(old_dir),
+            new_dir: relative(new_dir),
+            timestamp: now(),
+            git_head: git_head(workspace_root),
+        }
+    }
+}
+
+/// All renames recorded for a workspace, oldest first.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenameHistory {
+    pub renames: Vec<HistoryEntry>,
+}
+
+/// Returns the path of the history file for a workspace.
+pub fn history_path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(STATE_DIR).join(HISTORY_FILE)
+}
+
+impl RenameHistory {
+    /// Loads the history for a workspace. Returns an empty history if none exists.
+    pub fn load(workspace_root: &Path) -> Result<Self> {
+        let path = history_path(workspace_root);
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", display_path(&path), e).into())
+    }
+
+    /// Writes the history for a workspace, creating the state directory if needed.
+    pub fn save(&self, workspace_root: &Path) -> Result<()> {
+        let path = history_path(workspace_root);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| anyhow::anyhow!("Failed to serialize rename history: {}", e))?;
+        fs::write(&path, content + "\n")?;
+        Ok(())
+    }
+
+    /// Looks up what `name` was renamed to, following chained renames.
+    ///
+    /// Returns the most recent rename away from `name` and the name it goes by
+    /// now (`a → b → c` yields the `a → b` entry and `c`).
+    pub fn renamed_to(&self, name: &str) -> Option<(&HistoryEntry, String)> {
+        let first = self.latest_rename_of(name)?;
+
+        let mut current = first.new_name.clone();
+        let mut seen = vec![name.to_string()];
+
+        while let Some(next) = self.renames_after(&current, first.timestamp) {
+            if seen.contains(&next.new_name) {
+                break;
+            }
+            seen.push(current);
+            current = next.new_name.clone();
+        }
+
+        Some((first, current))
+    }
+
+    /// Returns the names `name` went by before, most recent first
+    /// (`a → b → c` yields `b` and `a` for `c`).
+    pub fn former_names(&self, name: &str) -> Vec<String> {
+        let mut names = vec![name.to_string()];
+
+        for entry in self.renames.iter().rev() {
+            if names.contains(&entry.new_name) && !names.contains(&entry.old_name) {
+                names.push(entry.old_name.clone());
+            }
+        }
+
+        names.split_off(1)
+    }
+
+    fn latest_rename_of(&self, name: &str) -> Option<&HistoryEntry> {
+        self.renames
+            .iter()
+            .rev()
+            .find(|e| e.old_name == name && e.new_name != name)
+    }
+
+    fn renames_after(&self, name: &str, timestamp: u64) -> Option<&HistoryEntry> {
+        self.latest_rename_of(name)
+            .filter(|e| e.timestamp >= timestamp)
+    }
+
+    /// Prints the history to stdout, most recent first.
+    pub fn print_log(&self, workspace_root: &Path) {
+        println!(
+            "\n{} {}",
+            "Rename history:".bold().cyan(),
+            display_path(&history_path(workspace_root)).dimmed()
+        );
+
+        for entry in self.renames.iter().rev() {
+            let head = entry
+                .git_head
+                .as_deref()
+                .map(|h| h.get(..7).unwrap_or(h))
+                .unwrap_or("-");
+
+            println!(
+                "  {} {} {} {}  {}",
+                head.dimmed(),
+                entry.old_name.yellow(),
+                symbols::arrow(),
+                entry.new_name.green(),
+                age_of(entry).dimmed()
+            );
+            if entry.old_dir != entry.new_dir {
+                println!(
+                    "    {} {} {}",
+                    display_path(&entry.old_dir),
+                    symbols::arrow(),
+                    display_path(&entry.new_dir)
+                );
+            }
+        }
+    }
+}
+
+/// Whether applied renames are recorded: not with `--no-history`, nor when
+/// the `CI` environment variable is set (and not `false` or `0`).
+pub fn enabled(no_history: bool) -> bool {
+    !no_history && !is_ci(std::env::var("CI").ok().as_deref())
+}
+
+fn is_ci(value: Option<&str>) -> bool {
+    value.is_some_and(|value| !matches!(value, "" | "0" | "false"))
+}
+
+/// Appends entries to the history of a workspace.
+///
+/// Failures are logged but never abort the surrounding operation.
+pub fn record(workspace_root: &Path, entries: Vec<HistoryEntry>) {
+    if entries.is_empty() {
+        return;
+    }
+
+    let result = RenameHistory::load(workspace_root).and_then(|mut history| {
+        history.renames.extend(entries);
+        history.save(workspace_root)
+    });
+
+    if let Err(e) = result {
+        log::warn!("Could not record rename history: {}", e);
+    }
+}
+
+/// Formats a duration in seconds as "3 days ago".
+pub fn format_age(secs: u64) -> String {
+    let (count, unit) = match secs {
+        0..60 => return "just now".to_string(),
+        60..3600 => (secs / 60, "minute"),
+        3600..86400 => (secs / 3600, "hour"),
+        _ => (secs / 86400, "day"),
+    };
+
+    if count == 1 {
+        format!("1 {} ago", unit)
+    } else {
+        format!("{} {}s ago", count, unit)
+    }
+}
+
+/// Returns the age of an entry as "3 days ago".
+pub fn age_of(entry: &HistoryEntry) -> String {
+    format_age(now().saturating_sub(entry.timestamp))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn git_head(workspace_root: &Path) -> Option<String> {
+    let output = process::output(
+        Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .current_dir(workspace_root),
+    )
+    .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|head| !head.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_ci() {
+        assert!(is_ci(Some("true")));
+        assert!(is_ci(Some("1")));
+        assert!(!is_ci(Some("false")));
+        assert!(!is_ci(Some("")));
+        assert!(!is_ci(None));
+    }
+
+    fn entry(old: &str, new: &str, timestamp: u64) -> HistoryEntry {
+        HistoryEntry {
+            old_name: old.to_string(),
+            new_name: new.to_string(),
+            old_dir: PathBuf::from(old),
+            new_dir: PathBuf::from(old),
+            timestamp,
+            git_head: None,
+        }
+    }
+
+    #[test]
+    fn test_record_appends() {
+        let temp = TempDir::new().unwrap();
+
+        record(temp.path(), vec![entry("a", "b", 1)]);
+        record(temp.path(), vec![entry("b", "c", 2)]);
+
+        let history = RenameHistory::load(temp.path()).unwrap();
+        assert_eq!(
+            history.renames,
+            vec![entry("a", "b", 1), entry("b", "c", 2)]
+        );
+    }
+
+    #[test]
+    fn test_renamed_to_follows_chain() {
+        let history = RenameHistory {
+            renames: vec![entry("a", "b", 1), entry("b", "c", 2), entry("x", "y", 3)],
+        };
+
+        let (first, current) = history.renamed_to("a").unwrap();
+        assert_eq!(first.new_name, "b");
+        assert_eq!(current, "c");
+        assert!(history.renamed_to("c").is_none());
+        assert_eq!(history.former_names("c"), vec!["b", "a"]);
+        assert!(history.former_names("y").contains(&"x".to_string()));
+        assert!(history.former_names("a").is_empty());
+    }
+
+    #[test]
+    fn test_renamed_to_ignores_older_renames_of_reused_name() {
+        // `b` was renamed away before `a` took its name
+        let history = RenameHistory {
+            renames: vec![entry("b", "z", 1), entry("a", "b", 2)],
+        };
+
+        assert_eq!(history.renamed_to("a").unwrap().1, "b");
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(5), "just now");
+        assert_eq!(format_age(60), "1 minute ago");
+        assert_eq!(format_age(7200), "2 hours ago");
+        assert_eq!(format_age(3 * 86400 + 10), "3 days ago");
+    }
+}
@@ -4,7 +4,8 @@
 //! It handles the necessary updates across Cargo.toml, source code, and the
 //! file system to ensure the project remains compilable. This includes:
 //!
-//! - **Manifests**: Updating `[package].name` and dependency entries in the workspace.
+//! - **Manifests**: Updating `[package].name`, dependency entries and `[patch]`/`[replace]`
+//!   overrides in the workspace.
 //! - **Source Code**: Rewriting `use` statements and qualified paths.
 //! - **Filesystem**: Optionally moving the package directory to match the new name.
 //!
@@ -39,20 +40,188 @@
 //! # Move to a nested path
 //! cargo rename old-crate --move libs/core/new-crate
 //!
+//! # Move relative to the package's current parent (crates/old-crate -> libs/new-crate)
+//! cargo rename old-crate new-crate --move-relative ../libs/new-crate
+//!
+//! # Select the package by its directory instead of its name
+//! cargo rename --at crates/old-crate new-crate
+//!
+//! # Rename a package named like a subcommand (`log new-log` alone works too)
+//! cargo rename -- validate-name new-name
+//!
+//! # Keep directory names independent of package names (never hint at --move)
+//! cargo rename old-crate new-crate --keep-dir-name
+//!
 //! # Preview changes without writing anything
 //! cargo rename old-crate new-crate --dry-run
 //!
+//! # Rename and `cargo check` a temporary copy first; apply only if it builds
+//! cargo rename old-crate new-crate --sandbox
+//!
+//! # Apply, then `cargo check` the workspace; roll back if it fails
+//! cargo rename old-crate new-crate --verify check
+//!
+//! # Fast preview of Cargo.toml edits only (reads manifests, skips cargo metadata)
+//! cargo rename old-crate new-crate --manifests-only --dry-run
+//!
+//! # Update manifests only and print rust-analyzer SSR rules for the source changes
+//! cargo rename old-crate new-crate --emit-ssr
+//!
+//! # Also update path dependents in a sibling checkout outside the workspace
+//! cargo rename old-crate new-crate --move --notify-external ../other-repo
+//!
+//! # Same, but in the rename's transaction: rolled back with it if anything fails
+//! cargo rename old-crate new-crate --move --scan-dirs ../test-harness ../benchmarks
+//!
+//! # In a workspace nested in (and excluded from) another one: also update the
+//! # outer workspace's path dependencies (otherwise they are only reported)
+//! cargo rename old-crate new-crate --move --include-parent-workspace
+//!
 //! # Skip confirmation prompt
 //! cargo rename old-crate new-crate --yes
 //!
 //! # Allow operation with uncommitted git changes
 //! cargo rename old-crate new-crate --allow-dirty
+//!
+//! # Rename the workspace itself (root docs and [workspace.package] URLs)
+//! cargo rename workspace new-name --from old-name
+//!
+//! # ...and rename members prefixed with the old workspace name
+//! cargo rename workspace new-name --from old-name --members
+//!
+//! # Also rename OLD_CRATE_* environment variables (string literals, docs, .env files)
+//! cargo rename old-crate new-crate --rename-env-prefix
+//!
+//! # Renaming a -sys crate: also rename `links` and DEP_OLD_* reads in dependents' build scripts
+//! cargo rename old-sys new-sys --rename-links
+//!
+//! # Keep dependents' sources as they are: add `extern crate new_crate as old_crate;` to their crate roots
+//! cargo rename old-crate new-crate --via-alias
+//!
+//! # Leave generated code alone (gitignore globs, relative to the workspace or each package)
+//! cargo rename old-crate new-crate --exclude "benches/generated/**" --exclude "examples/legacy/*"
+//!
+//! # Also rewrite tracing/log targets and RUST_LOG filters (target: "old_crate", old_crate=debug)
+//! cargo rename old-crate new-crate --update-log-targets
+//!
+//! # Also update cbindgen.toml and C header include guards (OLD_CRATE_H)
+//! cargo rename old-crate new-crate --update-ffi
+//!
+//! # Remove `pub use old_crate as alias;` shims left by earlier renames
+//! cargo rename old-crate new-crate --drop-shims
+//!
+//! # Also update non-badge link URLs in Markdown docs
+//! cargo rename old-crate new-crate --docs-update text,badges,urls
+//!
+//! # Update docs in every member, not just the package and its dependents
+//! cargo rename old-crate new-crate --docs-scope workspace
+//!
+//! # Retarget only dev-dependencies; the others keep the old crate
+//! cargo rename old-crate new-crate --only-kinds dev
+//!
+//! # Leave out whole summary sections (as shown by --dry-run), e.g. docs and tool configs
+//! cargo rename old-crate new-crate --skip-category docs,configs
+//!
+//! # Match "Old-Crate" and "OLD-CRATE" in docs too, keeping their casing
+//! cargo rename old-crate new-crate --ignore-case-docs
+//!
+//! # Also update a custom tool config (deny.toml, release.toml, release-plz.toml, .config/nextest.toml,
+//! # .cargo/config.toml, codecov.yml, tarpaulin.toml and others are automatic)
+//! cargo rename old-crate new-crate --tool-config ci/crates.toml
+//!
+//! # Note the rename under "Unreleased" in the package's CHANGELOG.md
+//! cargo rename old-crate new-crate --changelog-entry
+//!
+//! # Keep README/doc mtimes so doc-only edits don't trigger rebuilds
+//! cargo rename old-crate new-crate --preserve-mtime
+//!
+//! # Rewrite `version = "..."` on path dependencies that no longer match the crate
+//! cargo rename old-crate new-crate --sync-versions
+//!
+//! # Restart versioning under the new name; dependents' path + version requirements follow
+//! cargo rename old-crate new-crate --set-version 0.1.0
+//!
+//! # Also replace "old-crate" in [package] description and keywords
+//! cargo rename old-crate new-crate --update-description
+//!
+//! # From a nested directory: fail instead of renaming in an enclosing workspace
+//! # (the plan and confirmation prompt always show the workspace root and manifest used)
+//! cargo rename old-crate new-crate --workspace-root ../..
+//!
+//! # Write moved dependency paths as ../../crates/new-crate (default: keep each path's style)
+//! cargo rename old-crate new-crate --move crates/new-crate --path-style from-root
+//!
+//! # Rewrite https://github.com/acme/old-crate to .../new-crate in [package]
+//! cargo rename old-crate new-crate --set-repository --set-homepage
+//!
+//! # In a sparse checkout, fail instead of skipping members that are not checked out
+//! cargo rename old-crate new-crate --strict-materialization
+//!
+//! # Record local usage statistics and print them
+//! cargo rename old-crate new-crate --record-stats
+//! cargo rename stats
+//!
+//! # In scripts: fail when nothing would change, and print a JSON summary
+//! # ({"schema_version":1,"status":"applied"|"dry-run"|"noop",...}) as the only output on stdout
+//! cargo rename old-crate new-crate --fail-if-noop --json
+//!
+//! # Print the JSON schema of that summary, to vendor and validate against
+//! cargo rename schema > cargo-rename.schema.json
+//!
+//! # Stage every edit and write it to .cargo-rename/transaction.json, without committing
+//! cargo rename old-crate new-crate --stop-after stage
+//!
+//! # Run formatters/hooks afterwards and list renamed files they changed
+//! cargo rename old-crate new-crate --run-hooks "cargo fmt" --run-hooks "pre-commit run --all-files"
+//!
+//! # Record every source replacement, then undo one file's edits to bisect a bad rewrite
+//! cargo rename old-crate new-crate --trace-edits trace.jsonl
+//! cargo rename replay-trace trace.jsonl --revert --file crates/app/src/lib.rs
+//!
+//! # Pick a rename target: members, their directories and how many crates depend on them
+//! cargo rename list
+//! cargo rename list --output json
+//!
+//! # Show renames applied to this workspace (.cargo-rename/history.json)
+//! cargo rename log
+//!
+//! # Fail if the given files still reference a renamed crate (for pre-commit hooks)
+//! cargo rename check --files src/lib.rs crates/app/Cargo.toml
+//!
+//! # Generate a synthetic workspace to reproduce a bug report
+//! cargo rename fixture --members 3 --with-target-deps --with-aliases repro/
+//!
+//! # Check a candidate name before renaming (exit status 1 if unusable)
+//! cargo rename validate-name new-crate --check-crates-io --json
+//!
+//! # Make dependents declare a crate consistently (package name as key,
+//! # workspace = true where [workspace.dependencies] has it), without renaming it
+//! cargo rename normalize-deps old-crate
+//!
+//! # After publishing the rename: update a downstream project's dependency keys
+//! # and `use old_crate::...` paths (no workspace needed, nothing is moved)
+//! cargo rename migrate-imports old-crate new-crate --path ../downstream-app
 //! ```
 //!
 //! ## CLI Reference
 //!
 //! ```bash
 //! Usage: cargo rename [OPTIONS] <OLD_NAME> [NEW_NAME]
+//!        cargo rename [OPTIONS] --at <DIR> [NEW_NAME]
+//!        cargo rename <COMMAND>
+//!
+//! Commands:
+//!   workspace        Rename the workspace itself
+//!   check            Check files for references to crates renamed in this workspace
+//!   stats            Show locally recorded usage statistics
+//!   list             List workspace members and the members depending on them
+//!   log              Show the history of renames applied to this workspace
+//!   fixture          Generate a synthetic workspace for reproducing rename bugs
+//!   validate-name    Check whether a name can be used as a package name
+//!   normalize-deps   Make all dependents declare a package the same way
+//!   migrate-imports  Update a project outside the workspace to a renamed dependency
+//!   replay-trace     Re-apply or revert the source edits recorded with --trace-edits
+//!   schema           Print the JSON schema of the `--json` summary
 //!
 //! Arguments:
 //!   <OLD_NAME>  Current name of the package
@@ -60,42 +229,366 @@
 //!
 //! Options:
 //!       --move [<DIR>]          Move the package to a new directory
+//!       --move-relative <DIR>   Move the package to DIR, relative to its current parent directory
+//!       --keep-dir-name         Never move the package directory, and don't hint at moving it
+//!       --at <DIR>              Select the package by its directory or Cargo.toml instead of OLD_NAME
 //!       --manifest-path <PATH>  Path to workspace Cargo.toml
+//!       --workspace-root <DIR>  Refuse to run unless the workspace root is DIR
 //!   -n, --dry-run               Preview changes without applying them
+//!       --sandbox               Rehearse the rename in a temporary copy of the workspace first
+//!       --verify <COMMAND>      Build the workspace after the rename, rolling it back on failure
 //!   -y, --yes                   Skip interactive confirmation
 //!       --allow-dirty           Allow operation with uncommitted git changes
+//!       --allow-long-paths      Skip the path length check for moves
+//!       --force-shared-dir      Move the directory even if it contains other workspace packages
+//!       --rename-env-prefix     Rewrite OLD_NAME_* environment variables in the renamed package
+//!       --rename-links          Rename `links` and DEP_OLD_* reads in dependents' build scripts
+//!       --update-log-targets    Rewrite tracing/log targets and RUST_LOG filters naming the crate
+//!       --update-ffi            Update cbindgen.toml and C header include guards (OLD_CRATE_H)
+//!       --drop-shims            Remove `pub use old_crate as alias;` shims left by earlier renames
+//!       --force-rewrite-unparsable
+//!                               Rewrite Rust files that fail to parse instead of skipping them
+//!       --edit-conflicts        Open skipped unparsable Rust files in $VISUAL or $EDITOR
+//!       --disable-rules <RULES> Source rewrite rules to skip, e.g. `macro,doc-link`
+//!       --exclude <GLOB>        Skip files matching GLOB when rewriting sources (repeatable)
+//!       --via-alias             Alias the old name at dependents' crate roots instead of rewriting them
+//!       --manifests-only        Only update Cargo.toml files
+//!       --emit-ssr              Print rust-analyzer SSR rules instead of rewriting Rust sources
+//!       --notify-external <DIR> Also update path dependents in DIR, outside the workspace (repeatable)
+//!       --scan-dirs <PATH>...   Also update path dependents in PATH, outside the workspace, atomically
+//!       --include-parent-workspace
+//!                               Also update path dependents in the workspace enclosing this one
+//!       --only-kinds <KINDS>    Only retarget dependencies of these kinds: normal, dev, build
+//!       --skip-category <CATEGORIES>
+//!                               Leave out summary categories: dependencies, source, docs, configs
+//!       --docs-update <CATEGORIES>
+//!                               Documentation categories to update [default: text,badges]
+//!       --docs-scope <SCOPE>    Members whose documentation is updated [default: dependents]
+//!       --ignore-case-docs      Match the crate name in documentation case-insensitively
+//!       --experimental-ast-manifest-check
+//!                               Check that every edited Cargo.toml still parses before committing
+//!       --tool-config <PATH>    Additional tool config file to update (repeatable)
+//!       --changelog-entry       Note the rename in the package's CHANGELOG.md, under Unreleased
+//!       --workspace-fragment <PATH>
+//!                               Additional workspace fragment to update like the root manifest (repeatable)
+//!       --preserve-mtime        Keep the modification time of rewritten documentation files
+//!       --no-hooks              Skip the rename hooks configured in [workspace.metadata.cargo-rename]
+//!       --allow-commands        Run the commands configured in [workspace.metadata.cargo-rename]
+//!       --sync-versions         Rewrite dependents' version requirements to match the renamed package
+//!       --set-version <VERSION> Set the renamed package's version, e.g. to restart at 0.1.0
+//!       --path-style <STYLE>    How to write dependents' moved `path` values [default: preserve]
+//!       --strict-materialization
+//!                               Fail if a workspace member is not checked out
+//!       --set-repository [<URL>]
+//!                               Replace the old name in the package's repository URL, or set it to URL
+//!       --set-homepage [<URL>]  Replace the old name in the package's homepage URL, or set it to URL
+//!       --set-documentation [<URL>]
+//!                               Replace the old name in the package's documentation URL, or set it to URL
+//!       --update-description    Replace the old name in the package's description and keywords
+//!       --rename-targets        Also rename targets named after the package
+//!       --record-stats          Record local usage statistics in .cargo-rename/stats.json
+//!       --no-history            Do not record the rename in .cargo-rename/history.json
+//!       --fail-if-noop          Exit with an error if the rename would change nothing
+//!       --json                  Print a JSON summary as the only output on stdout
+//!       --stop-after <PHASE>    Stop after a phase of the rename, for debugging [possible values:
+//!                               plan, stage, commit]
+//!       --run-hooks <CMD>       Run CMD after the rename and report edits it reverted (repeatable)
+//!       --trace-edits <FILE>    Write every source rule replacement to FILE as JSON lines
 //!       --color <WHEN>          Control color output [default: auto] [possible values:
 //!                               auto, always, never]
 //!   -q, --quiet...              Decrease logging verbosity
 //!   -v, --verbose...            Increase logging verbosity (-v, -vv, -vvv)
+//!       --absolute-paths        Show absolute, OS-native paths instead of workspace-relative ones
+//!       --annotate              Emit GitHub Actions annotations (automatic when GITHUB_ACTIONS=true)
+//!       --timeout <SECS>        Timeout in seconds for external commands like `cargo metadata`
+//!                               (0 disables) [default: 300]
+//!       --include-hidden        Scan all hidden files and directories (.github/, .config/)
+//!       --exclude-hidden        Skip all hidden files, including .env files
+//!       --json-errors           Print failures as a JSON object on stderr
+//!       --ascii                 Use ASCII markers instead of emoji and symbols (automatic on non-UTF-8 terminals)
 //!   -h, --help                  Print help (see more with '--help')
 //!   -V, --version               Print version
 //! ```
 //!
+//! With `--color auto` (the default), `CARGO_TERM_COLOR=always|never` is honored
+//! first, then `NO_COLOR`.
+//!
+//! `--ascii` replaces `✓`, `✗`, `⚠`, `ℹ`, `→`, `•` and the summary emoji with
+//! `[ok]`, `[error]`, `[warn]`, `[info]`, `->`, `-` and `#`. It is enabled
+//! automatically when `LC_ALL`, `LC_CTYPE` or `LANG` names a non-UTF-8 locale
+//! (e.g. `LANG=C`), and on Windows consoles other than Windows Terminal unless the
+//! locale says UTF-8.
+//!
+//! `cargo metadata` and `git` are killed if they run longer than `--timeout`
+//! (e.g. `cargo metadata` stuck fetching git dependencies on a broken network).
+//! A timeout before the rename aborts it without touching any files; a timeout
+//! during post-rename verification is only reported as a warning.
+//!
+//! `--verify check` runs `cargo check --workspace --all-targets` once the rename
+//! is written, and `--verify test` runs `cargo test --workspace`. If the command
+//! fails or times out, the rename is rolled back, `Cargo.lock` included, and the
+//! command exits with its errors. Unlike `--sandbox`, this builds the real workspace, so path
+//! dependencies outside it resolve and the build cache is reused.
+//!
+//! Rust files that fail to parse are skipped with a warning. `--edit-conflicts`
+//! opens each of them in `$VISUAL` or `$EDITOR` before the confirmation prompt,
+//! at the first reference (`+LINE`), with the suggested replacement in a
+//! `// cargo-rename: suggested:` comment above every referencing line;
+//! references inside string literals are flagged as ambiguous. The comments are
+//! removed afterwards, and the edit joins the rename if the file then parses or
+//! no longer references the old name. Otherwise the file is opened again; saving
+//! it unchanged gives up and keeps the edit in a temporary copy. The editor
+//! setting runs through the shell, so it may quote paths and carry arguments.
+//!
+//! Source, documentation and config files that are not valid UTF-8 (binary
+//! fixtures, vendored assets) are left alone and listed as skipped in the summary.
+//! A manifest that is not valid UTF-8 fails the rename with its path.
+//!
+//! Hidden files and directories (names starting with `.`) are skipped, except
+//! `.env` / `.env.*` files. `--include-hidden` scans all of them except `.git`;
+//! `--exclude-hidden` skips `.env` files too. The rule is the same on every
+//! platform: the Windows hidden attribute is ignored.
+//!
+//! With `--json-errors` (implied by `rename --json`), a failure is printed as a
+//! single JSON object on stderr instead of colored text:
+//!
+//! ```json
+//! {"code":"dirty-workspace","message":"Workspace has uncommitted changes","paths":[],"remediation":"commit or stash your changes, or pass --allow-dirty","rollback":null}
+//! ```
+//!
+//! `rollback` is `complete` or `incomplete` when a commit failed midway and was
+//! rolled back, and `null` when nothing was written. Preflight checks that find
+//! several problems report the code `multiple` with one entry per problem in
+//! `errors`. Arguments that do not parse are reported with the code
+//! `invalid-arguments`; `--help` and `--version` still print as usual.
+//!
+//! A rename lists the crate's `rust-version`, `publish`, `license` and
+//! `description` under "Package metadata", and flags `description` or `keywords`
+//! entries that still contain the old name as a whole word. `--update-description`
+//! replaces them; fields inherited from the workspace are left alone.
+//!
+//! `--rename-targets` also renames the `[lib]`, `[[bin]]`, `[[example]]`,
+//! `[[test]]` and `[[bench]]` targets and the `default-run` key named after the
+//! package. Targets whose source file was inferred from the old name get an
+//! explicit `path`, so they keep building from the same file.
+//!
+//! In Markdown docs, crates.io, docs.rs and shields.io URLs are rewritten per URL
+//! component, so badges and registry links follow the new name. Reference labels
+//! that name the crate in snake_case (``[`old_crate::Config`]: https://docs.rs/...``)
+//! are renamed together with the links that use them.
+//!
+//! In `.cargo/config.toml`, `[patch]` entries and `-p old-crate` / `--package
+//! old-crate` arguments in `[alias]` commands are renamed, and patch paths follow
+//! a move (relative to the directory containing `.cargo`).
+//!
+//! Release tooling follows the new name: package entries in `release.toml` and
+//! `release-plz.toml`, and tag templates that start with the crate name
+//! (`git_tag_name = "old-crate-v{{ version }}"`, `tag-prefix = "old-crate-"`),
+//! also in the `[workspace.metadata.workspaces]` (cargo-workspaces) and
+//! `[*.metadata.release]` (cargo-release) tables. `--changelog-entry` adds
+//! "Renamed the package from `old-crate` to `new-crate`." to the package's
+//! `CHANGELOG.md`, below `### Changed` in the Unreleased section, which is created
+//! above the latest release if missing.
+//!
+//! Documentation is rewritten in the renamed package and the members that depend
+//! on it, so another team's docs that merely mention the crate stay as they are.
+//! `--docs-scope package` narrows this to the package itself, `--docs-scope
+//! workspace` widens it to every member. Rust sources are rewritten in every
+//! member regardless. The plan and the confirmation prompt show the scope.
+//!
+//! `--only-kinds` scopes a staged migration to some dependency kinds, e.g.
+//! `--only-kinds dev` moves the tests to the new crate while `[dependencies]`
+//! keep using the old one. Only the selected sections of dependent manifests are
+//! updated, and only the sources that can use them: `build.rs` for build,
+//! `tests/`, `benches/` and `examples/` for dev, everything else for normal.
+//! Members that keep a dependency of another kind on the package get a warning,
+//! and those entries keep their key with `package = "<new>"` so the workspace
+//! still resolves. Entries of other kinds inheriting from
+//! `[workspace.dependencies]` cannot carry `package` and fail the rename. The
+//! renamed package itself is always updated in full.
+//!
+//! With `--json`, stdout holds only the summary, as one JSON object; the report,
+//! warnings and the confirmation prompt go to stderr.
+//!
+//! The `--json` summary is a stable interface: `schema_version` (currently 1) is
+//! bumped whenever a field is removed, renamed or changes meaning, while new
+//! fields may appear without a bump. `cargo rename schema` prints the JSON schema
+//! of the current version.
+//!
+//! Applied renames are appended to `.cargo-rename/history.json`. Passing a name
+//! that has since been renamed fails with its current name, e.g.
+//! `Package 'foo' not found: it was renamed to 'bar' 3 days ago`. `--no-history`
+//! skips the entry, and nothing is recorded when the `CI` environment variable is
+//! set, so CI runs do not leave the file behind.
+//!
+//! `--stop-after` halts the pipeline for troubleshooting: `plan` prints the
+//! package and directory change after preflight checks, `stage` dumps every staged
+//! operation (file path, original and new content, directory moves) as JSON, and
+//! `commit` applies the edits but skips verification, the history entry and
+//! `--notify-external` manifests.
+//!
+//! `--run-hooks` catches formatters and pre-commit hooks that undo the rename.
+//! Each command runs in the workspace root after the edits are applied (split on
+//! whitespace, no shell), then every file the rename wrote is read back. Files
+//! whose content changed or that were removed are listed as a warning; nothing is
+//! rolled back.
+//!
+//! `--trace-edits trace.jsonl` writes one JSON object per source replacement:
+//! the file (relative to the workspace root, after any move), the rule, the byte
+//! span of the match and the text before and after. Spans refer to the file as it
+//! was just before that edit, so a file's edits apply in trace order. `cargo
+//! rename replay-trace trace.jsonl` re-applies them, `--revert` undoes them, and
+//! `--file` limits either to some files; every edit is checked against the file
+//! before anything is written. Manifest and documentation edits are not traced.
+//!
+//! `--preserve-mtime` only applies to `.md`, `.markdown` and `.txt` files. Crates
+//! that embed them (e.g. `#![doc = include_str!("../README.md")]`) will keep stale
+//! docs until something else triggers a rebuild.
+//!
+//! ### Rename hooks
+//!
+//! Files in formats cargo-rename doesn't know can be rewritten by an external
+//! program, configured in the workspace root `Cargo.toml`:
+//!
+//! ```toml
+//! [[workspace.metadata.cargo-rename.hooks]]
+//! glob = "**/*.bzl"
+//! command = ["python3", "tools/rename_hook.py"]
+//! timeout = 10  # seconds, defaults to --timeout
+//! ```
+//!
+//! For each matching file the command receives a JSON object on stdin
+//! (`old_name`, `new_name`, `old_dir`, `new_dir`, `path`, `content`) and prints
+//! the rewritten content to stdout. Hooks are commands from the repository, so
+//! they only run with `--allow-commands`, and only once the rename is confirmed;
+//! without it they are skipped with a warning. The result is staged with all
+//! other edits, so a failing or timed out hook aborts the rename without changing
+//! anything. `--no-hooks` skips them.
+//!
+//! ### HTML in Markdown
+//!
+//! HTML embedded in Markdown docs is rewritten per attribute: `alt`, `title` and
+//! `aria-label` values are treated as prose, `href` and `src` as URLs (following
+//! `--docs-update`), and every other attribute (`id`, `class`, `data-*`) is left
+//! alone. Comments and `<script>`/`<style>` blocks are never touched. The policy
+//! can be changed in the workspace root `Cargo.toml`:
+//!
+//! ```toml
+//! [workspace.metadata.cargo-rename.html]
+//! comments = true                          # rewrite inside <!-- -->
+//! text-attributes = ["alt", "figure.id"]   # `element.attribute` scopes
+//! url-attributes = ["href", "src", "srcset"]
+//! skip-elements = ["script", "style", "kbd"]
+//! ```
+//!
+//! ### Naming policy
+//!
+//! New package names can be checked, or adjusted, against an organization's
+//! conventions:
+//!
+//! ```toml
+//! [workspace.metadata.cargo-rename.naming]
+//! pattern = "^acme-[a-z0-9-]+$"
+//! message = "package names must start with `acme-`"
+//! command = ["tools/name-policy.sh"]  # optional
+//! ```
+//!
+//! The command sees `CARGO_RENAME_OLD_NAME` and `CARGO_RENAME_NEW_NAME`, and may
+//! print a replacement name (e.g. `acme-parser` for `parser`). A non-zero exit
+//! rejects the name with the command's stderr as the reason. Like hooks, the
+//! command only runs with `--allow-commands`. The final name must match
+//! `pattern`, otherwise the rename fails with `message`.
+//!
+//! ### Workspace fragments
+//!
+//! Monorepos that generate the members list (or `[workspace.dependencies]`) into
+//! a separate file merged by their tooling can have it updated like the root
+//! manifest:
+//!
+//! ```toml
+//! [workspace.metadata.cargo-rename]
+//! fragments = ["tools/members.toml"]
+//! ```
+//!
+//! Paths in fragments are relative to the workspace root. `--workspace-fragment`
+//! adds one for a single run; missing fragments are skipped with a warning.
+//!
+//! On a move, `members`, `default-members` and `exclude` entries naming the
+//! package directory follow it, in the root manifest and in fragments alike.
+//! `default-members` and `exclude` entries inside the package, such as an excluded
+//! `crates/old-crate/fuzz` workspace, are moved along with it. A package covered
+//! only by a glob member (`crates/*`) that moves where no member reaches gets an
+//! explicit `members` entry; the glob is kept.
+//!
+//! A move is refused if the package directory also contains other workspace
+//! members (`crates/old-crate/inner`), since they would be relocated without their
+//! dependents being updated. `--force-shared-dir` moves them along anyway.
+//!
+//! ### Pre-commit hook
+//!
+//! `cargo rename check --files FILE...` reports references to crates renamed in
+//! the workspace (per `.cargo-rename/history.json`) as `FILE:LINE:COLUMN` and exits
+//! with status 1 if there are any. Rust files are matched with the rules a rename
+//! rewrites, manifests by their dependency declarations; other files are ignored.
+//! Old names that belong to a workspace member again are not checked. To block
+//! commits that reintroduce an old name with the [pre-commit](https://pre-commit.com)
+//! framework:
+//!
+//! ```yaml
+//! repos:
+//!   - repo: https://github.com/ekkolon/cargo-rename
+//!     rev: v0.3.0
+//!     hooks:
+//!       - id: cargo-rename-check
+//! ```
+//!
+//! Crates using a `--via-alias` alias keep the old paths on purpose; exclude them
+//! in the hook's `exclude` pattern.
+//!
 //! ## Library Usage
 //!
-//! You can also use `cargo-rename` programmatically.
+//! You can also use `cargo-rename` programmatically. The [`api`] module is the
+//! stable interface and follows semver; all other modules are internal and may
+//! change in any release.
 //!
 //! ```no_run
-//! use cargo_rename::{execute, RenameArgs};
+//! use cargo_rename::api::{self, RenameOptions, Workspace};
 //! use std::path::PathBuf;
 //!
 //! # fn main() -> cargo_rename::Result<()> {
-//! let args = RenameArgs {
-//!     old_name: "old-crate".into(),
-//!     new_name: Some("new-crate".into()),
-//!     outdir: Some(Some(PathBuf::from("libs/new-crate"))),
-//!     manifest_path: None,
-//!     dry_run: false,
-//!     skip_confirmation: true,
-//!     allow_dirty: false,
-//! };
-//!
-//! execute(args)?;
+//! let workspace = Workspace::load(None)?;
+//! let options = RenameOptions::new("old-crate")
+//!     .new_name("new-crate")
+//!     .move_to(Some(PathBuf::from("libs/new-crate")));
+//!
+//! // Validate and stage without writing anything
+//! let plan = api::plan(&workspace, &options)?;
+//! for (from, to) in plan.directory_moves() {
+//!     println!("move {} -> {}", from.display(), to.display());
+//! }
+//! for file in plan.files() {
+//!     print!("{}", file.diff());
+//! }
+//!
+//! let report = plan.apply()?;
+//! println!("{} files updated", report.files_updated);
 //! # Ok(())
 //! # }
 //! ```
 //!
+//! [`api::RenamePlan::files`] returns each staged file update with its current
+//! and new path and content; `changes` lists them as one line each, and
+//! `skipped` the files that need a manual look. [`api::execute`] runs the
+//! interactive CLI flow instead (confirmation prompt unless
+//! `skip_confirmation` is set, printed summary).
+//!
+//! [`api::WorkspaceScan`] lists the files a rename looks at, grouped by
+//! [`api::FileKind`] (manifests, Rust sources, docs, configs, headers, `.env`
+//! files), with the same ignore rules as the CLI; [`api::files_matching`]
+//! selects files by glob. [`api::verify_workspace`] checks that the workspace
+//! still loads after you write a plan's files yourself, and returns the error
+//! output and suggested fixes if it does not.
+//!
 //! ## Safety Checks
 //!
 //! By default, the tool enforces these checks before running:
@@ -105,45 +598,163 @@
 //!
 //! ## Scope and Limitations
 //!
-//! - **Binaries**: `[[bin]]` targets are not renamed to preserve binary compatibility.
+//! - **Binaries**: `[[bin]]` targets are not renamed to preserve binary compatibility,
+//!   unless `--rename-targets` is passed.
 //! - **Macros**: Identifiers generated dynamically inside macros may not be detected.
+//! - **Package globs**: `include`/`exclude` patterns with `..` after a wildcard
+//!   (`src/**/../x`) are reported for manual review instead of rewritten on a move.
+//! - **Artifact dependencies**: `artifact`, `lib` and `target` fields of nightly
+//!   bindeps entries are kept as-is, including `bin:<name>` selectors. Dependents'
+//!   `CARGO_BIN_FILE_OLD_CRATE*`-style variables follow a renamed dependency key.
+//! - **String literals**: crate paths in strings, such as
+//!   `#[deprecated(note = "use old_crate::v2::parse")]` or error messages, are
+//!   rewritten by the `qualified-path` rule. A bare `old_crate` without `::` in a
+//!   string is left alone.
+//! - **Shared names**: a workspace crate may share its name with a crates.io crate
+//!   (`log`, `rand`, `time`). Members whose dependency of that name is another
+//!   crate (crates.io, git or a different path) are skipped, manifest and sources
+//!   alike. A member depending on both keeps its manifest entries on the other
+//!   crate (a `path` elsewhere or `git`); its sources are rewritten with a
+//!   warning to review them.
+//! - **Local paths**: `crate::`, `self::` and `super::` paths, including
+//!   visibility restrictions like `pub(in crate::internal)`, are left alone unless
+//!   they continue through `old_crate::`. `pub(in old_crate::internal)` follows
+//!   the rename.
 
-pub mod cli;
+pub mod api;
 pub mod error;
-pub mod steps;
 
-// Internal modules
+// Internal modules, public only for the binary and tests. Not covered by
+// semver; use `api` instead.
+#[doc(hidden)]
+pub mod annotate;
+#[doc(hidden)]
 pub mod cargo;
+#[doc(hidden)]
+pub mod cli;
+#[doc(hidden)]
+pub mod config;
+#[doc(hidden)]
+pub mod console;
+#[doc(hidden)]
+pub mod fixture;
+#[doc(hidden)]
 pub mod fs;
+#[doc(hidden)]
+pub mod history;
+#[doc(hidden)]
+pub mod process;
+#[doc(hidden)]
 pub mod rewrite;
+#[doc(hidden)]
+pub mod stats;
+#[doc(hidden)]
+pub mod steps;
+#[doc(hidden)]
+pub mod symbols;
+#[doc(hidden)]
 pub mod verify;
+#[doc(hidden)]
+pub mod workspace_scan;
 
 pub use error::{RenameError, Result};
 pub use steps::rename::{RenameArgs, execute};
 
-use clap::Parser;
 use log::LevelFilter;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Exits with clap's output, or returns the error for the JSON reporter if
+/// `--json-errors` or `--json` is on the command line.
+///
+/// The flags are looked up in the raw arguments since parsing failed.
+fn argument_error(e: clap::Error) -> RenameError {
+    use clap::error::ErrorKind;
+
+    let json = std::env::args_os()
+        .skip(1)
+        .take_while(|arg| arg != "--")
+        .any(|arg| arg == "--json-errors" || arg == "--json");
+    if !json
+        || matches!(
+            e.kind(),
+            ErrorKind::DisplayHelp
+                | ErrorKind::DisplayVersion
+                | ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand
+        )
+    {
+        e.exit();
+    }
+
+    error::set_json_errors(true);
+    // First line of clap's message, without the `error: ` prefix and usage
+    let rendered = e.render().to_string();
+    let message = rendered.lines().next().unwrap_or_default();
+    RenameError::InvalidArguments(message.trim_start_matches("error: ").to_string())
+}
+
 /// Main entry point.
 ///
 /// Parses CLI args, sets up logging, and executes the rename.
 pub fn run() -> Result<()> {
-    let cargo_args = cli::CargoCli::parse();
+    let cargo_args = cli::CargoCli::try_parse_args(std::env::args_os()).map_err(argument_error)?;
 
-    setup_logging(cargo_args.verbose, cargo_args.quiet);
-    setup_colors(cargo_args.color);
+    let color = cli::resolve_color(
+        cargo_args.color,
+        std::env::var("CARGO_TERM_COLOR").ok().as_deref(),
+        std::env::var("NO_COLOR").ok().as_deref(),
+    );
+
+    let json = matches!(&cargo_args.command, cli::CargoCommand::Rename(cmd) if cmd.args.as_ref().is_some_and(|a| a.json));
+    let json_errors = cargo_args.json_errors || json;
+    error::set_json_errors(json_errors);
+    // Keep stdout parseable: only the JSON summary is printed there
+    console::set_stdout_to_stderr(json);
+
+    // Keep stderr parseable: only the error report is printed, unless logs are requested
+    let quiet = if json_errors && cargo_args.verbose == 0 {
+        1
+    } else {
+        cargo_args.quiet
+    };
+    setup_logging(cargo_args.verbose, quiet, color);
+    setup_colors(color);
+    fs::display::set_absolute_paths(cargo_args.absolute_paths);
+    annotate::setup(cargo_args.annotate);
+    symbols::setup(cargo_args.ascii);
+    process::set_timeout(cargo_args.timeout);
+    workspace_scan::set_hidden_files(workspace_scan::HiddenFiles::from_flags(
+        cargo_args.include_hidden,
+        cargo_args.exclude_hidden,
+    ));
 
     match cargo_args.command {
-        cli::CargoCommand::Rename(args) => steps::rename::execute(args),
+        cli::CargoCommand::Rename(cmd) => match (cmd.action, cmd.args) {
+            (Some(cli::RenameAction::Workspace(args)), _) => steps::workspace::execute(args),
+            (Some(cli::RenameAction::Check(args)), _) => steps::check::execute(args),
+            (Some(cli::RenameAction::Stats(args)), _) => steps::stats::execute(args),
+            (Some(cli::RenameAction::List(args)), _) => steps::list::execute(args),
+            (Some(cli::RenameAction::Log(args)), _) => steps::log::execute(args),
+            (Some(cli::RenameAction::Fixture(args)), _) => steps::fixture::execute(args),
+            (Some(cli::RenameAction::ValidateName(args)), _) => steps::validate_name::execute(args),
+            (Some(cli::RenameAction::NormalizeDeps(args)), _) => {
+                steps::normalize_deps::execute(args)
+            }
+            (Some(cli::RenameAction::MigrateImports(args)), _) => {
+                steps::migrate_imports::execute(args)
+            }
+            (Some(cli::RenameAction::ReplayTrace(args)), _) => steps::replay_trace::execute(args),
+            (Some(cli::RenameAction::Schema), _) => steps::schema::execute(),
+            (None, Some(args)) => steps::rename::execute(args),
+            (None, None) => unreachable!("clap requires OLD_NAME without a subcommand"),
+        },
     }
 }
 
 /// Configures logging verbosity.
 ///
 /// Levels: `-v` (warn), `-vv` (info), `-vvv` (debug), `-vvvv` (trace), `-q` (off).
-fn setup_logging(verbose: u8, quiet: u8) {
+fn setup_logging(verbose: u8, quiet: u8, color: clap::ColorChoice) {
     let log_level = if quiet > 0 {
         LevelFilter::Off
     } else {
@@ -156,13 +767,22 @@ fn setup_logging(verbose: u8, quiet: u8) {
         }
     };
 
+    let write_style = match color {
+        clap::ColorChoice::Always => env_logger::WriteStyle::Always,
+        clap::ColorChoice::Never => env_logger::WriteStyle::Never,
+        clap::ColorChoice::Auto => env_logger::WriteStyle::Auto,
+    };
+
     env_logger::Builder::new()
         .filter_level(log_level)
+        .write_style(write_style)
         .format_timestamp(None)
         .init();
 }
 
 /// Configures colored output.
+///
+/// `choice` is already resolved against `CARGO_TERM_COLOR` and `NO_COLOR`.
 fn setup_colors(choice: clap::ColorChoice) {
     use colored::control;
 
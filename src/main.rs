@@ -4,7 +4,7 @@ use std::process;
 
 fn main() {
     if let Err(e) = cargo_rename::run() {
-        eprintln!("Error: {}", e);
+        cargo_rename::error::report(&e);
         process::exit(1);
     }
 }
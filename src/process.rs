@@ -0,0 +1,183 @@
+//! External command execution with a timeout.
+//!
+//! `cargo metadata` resolves git dependencies over the network and can hang
+//! for minutes on a broken connection. Every external command (`cargo`,
+//! `git`) is run through [`output`], which kills the child once the timeout
+//! set with `--timeout` expires and returns
+//! [`RenameError::ExternalCommandTimeout`].
+
+use crate::error::{RenameError, Result};
+use std::io::{Read, Write};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Default timeout in seconds.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// Timeout in seconds; `0` disables it.
+static TIMEOUT_SECS: AtomicU64 = AtomicU64::new(DEFAULT_TIMEOUT_SECS);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Sets the timeout for external commands. `0` disables it.
+pub fn set_timeout(secs: u64) {
+    TIMEOUT_SECS.store(secs, Ordering::Relaxed);
+}
+
+/// Returns the configured timeout, or `None` if disabled.
+pub fn timeout() -> Option<Duration> {
+    match TIMEOUT_SECS.load(Ordering::Relaxed) {
+        0 => None,
+        secs => Some(Duration::from_secs(secs)),
+    }
+}
+
+/// Runs `cmd` to completion with the configured timeout, capturing output.
+///
+/// # Errors
+///
+/// - `ExternalCommandTimeout` if the command outlives the timeout
+/// - `Io` if the command could not be spawned
+pub fn output(cmd: &mut Command) -> Result<Output> {
+    output_with_timeout(cmd, timeout())
+}
+
+/// Runs `cmd` to completion, killing it once `limit` has elapsed.
+pub fn output_with_timeout(cmd: &mut Command, limit: Option<Duration>) -> Result<Output> {
+    match limit {
+        Some(_) => run(cmd, None, limit),
+        None => Ok(cmd.output()?),
+    }
+}
+
+/// Runs `cmd` with `input` on stdin, killing it once `limit` has elapsed.
+pub fn output_with_input(
+    cmd: &mut Command,
+    input: Vec<u8>,
+    limit: Option<Duration>,
+) -> Result<Output> {
+    run(cmd, Some(input), limit)
+}
+
+fn run(cmd: &mut Command, input: Option<Vec<u8>>, limit: Option<Duration>) -> Result<Output> {
+    let stdin = if input.is_some() {
+        Stdio::piped()
+    } else {
+        Stdio::null()
+    };
+
+    let mut child = cmd
+        .stdin(stdin)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Feed and drain pipes on separate threads so neither side blocks on a full pipe
+    if let (Some(mut pipe), Some(input)) = (child.stdin.take(), input) {
+        thread::spawn(move || {
+            // The child may exit without reading everything
+            let _ = pipe.write_all(&input);
+        });
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = limit.and_then(|limit| Instant::now().checked_add(limit));
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if let (Some(deadline), Some(limit)) = (deadline, limit)
+            && Instant::now() >= deadline
+        {
+            kill(&mut child);
+            return Err(RenameError::ExternalCommandTimeout(
+                describe(cmd),
+                limit.as_secs(),
+            ));
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn drain(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+fn kill(child: &mut Child) {
+    if let Err(e) = child.kill() {
+        log::debug!("Failed to kill timed out process: {}", e);
+    }
+    // Reap the child so it does not linger as a zombie
+    let _ = child.wait();
+}
+
+/// Formats a command line for error messages, e.g. `cargo metadata --no-deps`.
+fn describe(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|s| s.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_captures_output_within_timeout() {
+        let output = output_with_timeout(
+            Command::new("sh").args(["-c", "echo out; echo err >&2"]),
+            Some(Duration::from_secs(10)),
+        )
+        .unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+    }
+
+    #[test]
+    fn test_passes_input_on_stdin() {
+        let output = output_with_input(
+            &mut Command::new("cat"),
+            b"old-crate".to_vec(),
+            Some(Duration::from_secs(10)),
+        )
+        .unwrap();
+
+        assert_eq!(output.stdout, b"old-crate");
+    }
+
+    #[test]
+    fn test_kills_command_on_timeout() {
+        let start = Instant::now();
+        let result = output_with_timeout(
+            Command::new("sleep").arg("30"),
+            Some(Duration::from_millis(100)),
+        );
+
+        match result {
+            Err(RenameError::ExternalCommandTimeout(command, _)) => {
+                assert_eq!(command, "sleep 30");
+            }
+            other => panic!("expected timeout, got {:?}", other),
+        }
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,321 @@
+//! Compatibility aliases in place of source rewrites (`--via-alias`).
+//!
+//! Rewriting every `old_crate::` path in the dependents of a widely used
+//! crate makes for a huge diff. Instead, each crate root of a dependent
+//! (library, binaries, tests, examples, benches, build script) that can see
+//! the dependency gets a single line:
+//!
+//! ```rust,ignore
+//! extern crate new_crate as old_crate;
+//! ```
+//!
+//! inserted after its inner attributes and doc comments. This puts
+//! `old_crate` back into the extern prelude, so paths in every module keep
+//! resolving. A root-level `use new_crate as old_crate;` would only cover the
+//! root module in the 2018+ editions, hence `extern crate` in all of them. An
+//! existing `extern crate old_crate;` is turned into the alias in place.
+//!
+//! Roots that do not parse, or already declare something named `old_crate`,
+//! are flagged instead of edited.
+
+use crate::cargo::DepKind;
+use crate::error::Result;
+use crate::fs::display_path;
+use crate::fs::transaction::Transaction;
+use crate::workspace_scan::{self, FileKind};
+use cargo_metadata::{Metadata, Target};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Crate roots changed or flagged by [`insert_crate_aliases`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AliasReport {
+    /// Roots that got the alias.
+    pub inserted: Vec<PathBuf>,
+    /// Roots left alone, with the reason.
+    pub flagged: Vec<(PathBuf, String)>,
+}
+
+/// Adds `extern crate new_snake as old_snake;` to the crate roots of every
+/// member depending on the package in `package_dir` by its name.
+///
+/// Members that depend on it under another key (`alias = { package = ... }`)
+/// need no alias. A target gets one only if the dependency is available to it
+/// (a build-dependency for build scripts, a dev-dependency for tests, ...)
+/// and Rust files of its kind ([`DepKind::of_source`]) mention `old_snake`.
+pub fn insert_crate_aliases(
+    metadata: &Metadata,
+    old_name: &str,
+    new_name: &str,
+    package_dir: &Path,
+    txn: &mut Transaction,
+) -> Result<AliasReport> {
+    let old_snake = old_name.replace('-', "_");
+    let new_snake = new_name.replace('-', "_");
+    let mention = Regex::new(&format!(r"\b{}\b", regex::escape(&old_snake)))?;
+    let mut report = AliasReport::default();
+
+    for member in metadata.workspace_packages() {
+        if member.name == old_name {
+            continue;
+        }
+        let kinds: Vec<DepKind> = member
+            .dependencies
+            .iter()
+            .filter(|dep| {
+                dep.rename.is_none()
+                    && dep
+                        .path
+                        .as_ref()
+                        .is_some_and(|path| path.as_std_path() == package_dir)
+            })
+            .map(|dep| DepKind::from(dep.kind))
+            .collect();
+        if kinds.is_empty() {
+            continue;
+        }
+
+        let pkg_root = member.manifest_path.parent().unwrap().as_std_path();
+        let mut mentioned = Vec::new();
+        for file in workspace_scan::files_under(pkg_root) {
+            if file.kind != FileKind::Rust {
+                continue;
+            }
+            let kind = DepKind::of_source(file.path.strip_prefix(pkg_root).unwrap_or(&file.path));
+            if !mentioned.contains(&kind)
+                && txn
+                    .read_text(&file.path)
+                    .is_some_and(|content| mention.is_match(&content))
+            {
+                mentioned.push(kind);
+            }
+        }
+
+        for target in &member.targets {
+            let kind = target_kind(target);
+            let available = match kind {
+                DepKind::Normal => kinds.contains(&DepKind::Normal),
+                DepKind::Dev => kinds.contains(&DepKind::Normal) || kinds.contains(&DepKind::Dev),
+                DepKind::Build => kinds.contains(&DepKind::Build),
+            };
+            if !available || !mentioned.contains(&kind) {
+                continue;
+            }
+
+            let root = target.src_path.as_std_path();
+            let Some(content) = txn.read_text(root) else {
+                continue;
+            };
+            match add_alias(&content, &old_snake, &new_snake) {
+                Ok(Some(updated)) => {
+                    txn.update_file(root.to_path_buf(), updated)?;
+                    log::debug!("Added crate alias to {}", display_path(root));
+                    report.inserted.push(root.to_path_buf());
+                }
+                Ok(None) => {}
+                Err(reason) => report.flagged.push((root.to_path_buf(), reason)),
+            }
+        }
+    }
+
+    report.inserted.sort();
+    report.inserted.dedup();
+    report.flagged.sort();
+    report.flagged.dedup();
+    Ok(report)
+}
+
+/// Returns the kind of dependency a target can use.
+fn target_kind(target: &Target) -> DepKind {
+    if target.is_custom_build() {
+        DepKind::Build
+    } else if target.is_test() || target.is_bench() || target.is_example() {
+        DepKind::Dev
+    } else {
+        DepKind::Normal
+    }
+}
+
+/// Adds the alias to a crate root.
+///
+/// Returns `None` if the alias is already there, or the reason the root
+/// cannot take it.
+fn add_alias(
+    content: &str,
+    old_snake: &str,
+    new_snake: &str,
+) -> std::result::Result<Option<String>, String> {
+    let file = syn::parse_file(content).map_err(|_| "does not parse".to_string())?;
+    let alias = format!("extern crate {} as {};", new_snake, old_snake);
+
+    for item in &file.items {
+        match item {
+            syn::Item::ExternCrate(krate)
+                if krate.ident == new_snake
+                    && krate
+                        .rename
+                        .as_ref()
+                        .is_some_and(|(_, name)| name == old_snake) =>
+            {
+                return Ok(None);
+            }
+            syn::Item::ExternCrate(krate) if krate.ident == old_snake => {
+                // `extern crate old_crate;` or `extern crate old_crate as x;`
+                let pattern = Regex::new(&format!(
+                    r"(?m)^(\s*(?:pub(?:\([^)]*\))?\s+)?extern\s+crate\s+){}(\s*;|\s+as\b)",
+                    regex::escape(old_snake)
+                ))
+                .expect("valid extern crate pattern");
+                let updated = pattern
+                    .replace(content, |caps: &regex::Captures| {
+                        if caps[2].trim() == ";" {
+                            format!("{}{} as {}{}", &caps[1], new_snake, old_snake, &caps[2])
+                        } else {
+                            format!("{}{}{}", &caps[1], new_snake, &caps[2])
+                        }
+                    })
+                    .into_owned();
+                return Ok((updated != content).then_some(updated));
+            }
+            item if declared_names(item).iter().any(|name| name == old_snake) => {
+                return Err(format!("already declares `{}`", old_snake));
+            }
+            _ => {}
+        }
+    }
+
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let at = header_end(&lines);
+    let mut updated: String = lines[..at].concat();
+    if !updated.is_empty() && !updated.ends_with('\n') {
+        updated.push('\n');
+    }
+    updated.push_str(&alias);
+    updated.push('\n');
+    if lines.get(at).is_some_and(|line| !line.trim().is_empty()) {
+        updated.push('\n');
+    }
+    updated.push_str(&lines[at..].concat());
+    Ok(Some(updated))
+}
+
+/// Returns the index of the first line after the shebang, inner attributes
+/// and inner doc comments at the top of a file.
+fn header_end(lines: &[&str]) -> usize {
+    let mut end = 0;
+    let mut depth = 0i32;
+    let mut in_block_doc = false;
+
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if in_block_doc {
+            in_block_doc = !trimmed.contains("*/");
+            end = index + 1;
+        } else if depth > 0 {
+            depth += brackets(trimmed);
+            end = index + 1;
+        } else if trimmed.starts_with("#![") {
+            depth = brackets(trimmed);
+            end = index + 1;
+        } else if trimmed.starts_with("//!") {
+            end = index + 1;
+        } else if trimmed.starts_with("/*!") {
+            in_block_doc = !trimmed.contains("*/");
+            end = index + 1;
+        } else if index == 0 && trimmed.starts_with("#!") {
+            end = 1;
+        } else if !trimmed.is_empty() && !trimmed.starts_with("//") {
+            break;
+        }
+    }
+
+    end
+}
+
+/// Returns opened minus closed square brackets on a line.
+fn brackets(line: &str) -> i32 {
+    line.chars()
+        .map(|c| match c {
+            '[' => 1,
+            ']' => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Returns the type-namespace names an item declares at the crate root.
+fn declared_names(item: &syn::Item) -> Vec<String> {
+    fn use_names(tree: &syn::UseTree, names: &mut Vec<String>) {
+        match tree {
+            syn::UseTree::Path(path) => use_names(&path.tree, names),
+            syn::UseTree::Name(name) => names.push(name.ident.to_string()),
+            syn::UseTree::Rename(rename) => names.push(rename.rename.to_string()),
+            syn::UseTree::Group(group) => {
+                group.items.iter().for_each(|tree| use_names(tree, names))
+            }
+            syn::UseTree::Glob(_) => {}
+        }
+    }
+
+    match item {
+        syn::Item::Mod(item) => vec![item.ident.to_string()],
+        syn::Item::Struct(item) => vec![item.ident.to_string()],
+        syn::Item::Enum(item) => vec![item.ident.to_string()],
+        syn::Item::Union(item) => vec![item.ident.to_string()],
+        syn::Item::Trait(item) => vec![item.ident.to_string()],
+        syn::Item::TraitAlias(item) => vec![item.ident.to_string()],
+        syn::Item::Type(item) => vec![item.ident.to_string()],
+        syn::Item::ExternCrate(item) => item
+            .rename
+            .as_ref()
+            .map_or(vec![item.ident.to_string()], |(_, name)| {
+                vec![name.to_string()]
+            }),
+        syn::Item::Use(item) => {
+            let mut names = Vec::new();
+            use_names(&item.tree, &mut names);
+            names
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_alias() {
+        let add = |content: &str| add_alias(content, "old_crate", "new_crate");
+
+        assert_eq!(
+            add("//! Docs\n#![deny(\n    missing_docs\n)]\n\nuse old_crate::f;\n"),
+            Ok(Some(
+                "//! Docs\n#![deny(\n    missing_docs\n)]\nextern crate new_crate as old_crate;\n\nuse old_crate::f;\n"
+                    .to_string()
+            ))
+        );
+        assert_eq!(
+            add("use old_crate::f;\n"),
+            Ok(Some(
+                "extern crate new_crate as old_crate;\n\nuse old_crate::f;\n".to_string()
+            ))
+        );
+        assert_eq!(
+            add("#[macro_use]\nextern crate old_crate;\n"),
+            Ok(Some(
+                "#[macro_use]\nextern crate new_crate as old_crate;\n".to_string()
+            ))
+        );
+        assert_eq!(add("extern crate new_crate as old_crate;\n"), Ok(None));
+        assert_eq!(
+            add("mod old_crate;\n"),
+            Err("already declares `old_crate`".to_string())
+        );
+        assert_eq!(
+            add("use other::{x as old_crate};\n"),
+            Err("already declares `old_crate`".to_string())
+        );
+        assert_eq!(add("fn f( {\n"), Err("does not parse".to_string()));
+    }
+}
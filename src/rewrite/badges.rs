@@ -0,0 +1,141 @@
+//! URL-aware rewriting of badge and registry links.
+//!
+//! Badge URLs embed the crate name in several shapes:
+//!
+//! ```text
+//! https://img.shields.io/crates/v/old-crate.svg       path segment with extension
+//! https://img.shields.io/badge/old--crate-1.0-blue    shields.io escaped dashes
+//! https://docs.rs/old-crate/latest/old_crate/         library name in snake_case
+//! https://img.shields.io/crates/d/x?label=old-crate   query value
+//! ```
+//!
+//! A whole-word regex over the URL misses the escaped form and also matches
+//! prefixes of other crates (`old-crate-derive`), so badge URLs are rewritten
+//! component by component instead. Hosts and fragments are never changed.
+
+/// Rewrites the crate name in a badge or registry URL.
+pub(crate) fn rewrite_badge_url(url: &str, old_kebab: &str, new_kebab: &str) -> String {
+    let old_snake = old_kebab.replace('-', "_");
+    let new_snake = new_kebab.replace('-', "_");
+
+    let (prefix, rest) = match url.find("://") {
+        Some(idx) => {
+            let host_end = url[idx + 3..].find('/').map_or(url.len(), |i| idx + 3 + i);
+            url.split_at(host_end)
+        }
+        None => ("", url),
+    };
+    let (rest, fragment) = rest.split_at(rest.find('#').unwrap_or(rest.len()));
+    let (path, query) = rest.split_at(rest.find('?').unwrap_or(rest.len()));
+
+    let rename = |value: &str| {
+        if value == old_kebab {
+            Some(new_kebab.to_string())
+        } else if value == old_snake {
+            Some(new_snake.clone())
+        } else {
+            None
+        }
+    };
+
+    let static_badge = path.starts_with("/badge/");
+    let path = path
+        .split('/')
+        .map(|segment| {
+            let (stem, ext) = segment.split_at(segment.find('.').unwrap_or(segment.len()));
+            match rename(stem) {
+                Some(name) => format!("{}{}", name, ext),
+                None if static_badge => replace_escaped(segment, old_kebab, new_kebab),
+                None => segment.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let query = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) => match rename(value) {
+                Some(name) => format!("{}={}", key, name),
+                None => pair.to_string(),
+            },
+            None => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+
+    format!("{}{}{}{}", prefix, path, query, fragment)
+}
+
+/// Replaces the crate name in a shields.io static badge segment.
+///
+/// Such segments are `label-message-color`, with literal dashes and
+/// underscores doubled. The name must span whole dash-separated parts.
+fn replace_escaped(segment: &str, old: &str, new: &str) -> String {
+    let escape = |s: &str| s.replace('-', "--").replace('_', "__");
+    let (old, new) = (escape(old), escape(new));
+
+    let mut result = String::with_capacity(segment.len());
+    let mut rest = segment;
+    while let Some(idx) = rest.find(&old) {
+        let before = &rest[..idx];
+        let after = &rest[idx + old.len()..];
+        let starts_part = before.is_empty() || (before.ends_with('-') && !before.ends_with("--"));
+        let ends_part = after.is_empty()
+            || after.starts_with('.')
+            || (after.starts_with('-') && !after.starts_with("--"));
+
+        result.push_str(before);
+        result.push_str(if starts_part && ends_part { &new } else { &old });
+        rest = after;
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_badge_urls() {
+        let cases = [
+            (
+                "https://img.shields.io/crates/v/old-crate.svg",
+                "https://img.shields.io/crates/v/new-crate.svg",
+            ),
+            (
+                "https://img.shields.io/crates/d/old-crate?label=old-crate&style=flat",
+                "https://img.shields.io/crates/d/new-crate?label=new-crate&style=flat",
+            ),
+            (
+                "https://img.shields.io/badge/old--crate-1.0-blue",
+                "https://img.shields.io/badge/new--crate-1.0-blue",
+            ),
+            (
+                "https://img.shields.io/badge/old--crate--derive-1.0-blue",
+                "https://img.shields.io/badge/old--crate--derive-1.0-blue",
+            ),
+            (
+                "https://docs.rs/old-crate/badge.svg",
+                "https://docs.rs/new-crate/badge.svg",
+            ),
+            (
+                "https://docs.rs/old-crate/latest/old_crate/struct.Parser.html#method.new",
+                "https://docs.rs/new-crate/latest/new_crate/struct.Parser.html#method.new",
+            ),
+            (
+                "https://crates.io/crates/old-crate-derive",
+                "https://crates.io/crates/old-crate-derive",
+            ),
+            (
+                "https://crates.io/api/v1/crates/old-crate/downloads",
+                "https://crates.io/api/v1/crates/new-crate/downloads",
+            ),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(rewrite_badge_url(input, "old-crate", "new-crate"), expected);
+        }
+    }
+}
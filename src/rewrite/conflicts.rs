@@ -0,0 +1,336 @@
+//! Manual resolution of files the rename could not rewrite.
+//!
+//! Rust files that do not parse are skipped rather than rewritten blindly
+//! (see `--force-rewrite-unparsable`). With `--edit-conflicts`, each such file
+//! is opened in `$VISUAL` or `$EDITOR` before the confirmation prompt. The
+//! editor setting is run through the shell, so it may quote its program and
+//! carry arguments, as in git. The editor gets a temporary copy, opened at
+//! the first reference, with the suggested replacement in a comment above
+//! every line that references the old name. References inside a string
+//! literal are flagged as ambiguous instead, since they may not name the
+//! crate at all. Those comments are removed again when the editor exits.
+//!
+//! An edit is staged in the transaction only if the file then parses or no
+//! longer references the old name. Otherwise the copy is opened again, until
+//! it is saved valid, unchanged from the original, or unchanged from the
+//! previous attempt; in the last case the file is left as it was and the
+//! copy is kept for reference.
+
+use crate::console::errln;
+use crate::error::Result;
+use crate::fs::display_path;
+use crate::fs::transaction::Transaction;
+use crate::symbols;
+
+use colored::Colorize;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Prefix of the suggestion comments inserted for the editor.
+const MARKER: &str = "// cargo-rename: ";
+
+/// A file the rename flagged instead of rewriting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub path: PathBuf,
+    /// Why the file was not rewritten.
+    pub reason: String,
+    /// 1-based lines referencing the old name, with the suggested rewrite.
+    pub suggestions: Vec<(usize, String)>,
+}
+
+/// What happened to a conflict opened in the editor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditOutcome {
+    /// The edit was staged.
+    Staged,
+    /// The file was saved without changes.
+    Unchanged,
+    /// The edit failed validation and was discarded; it is kept in the
+    /// temporary copy.
+    Rejected(String),
+}
+
+/// Opens each conflict in the user's editor and stages valid edits on `txn`.
+///
+/// # Errors
+///
+/// Fails if neither `$VISUAL` nor `$EDITOR` is set, or if the editor cannot
+/// be started or exits unsuccessfully.
+pub fn edit_conflicts(
+    conflicts: &[Conflict],
+    old_name: &str,
+    txn: &mut Transaction,
+) -> Result<Vec<EditOutcome>> {
+    if conflicts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("--edit-conflicts needs $VISUAL or $EDITOR to be set"))?;
+
+    edit_with(&editor, conflicts, old_name, txn)
+}
+
+/// [`edit_conflicts`] with the editor setting resolved.
+fn edit_with(
+    editor: &str,
+    conflicts: &[Conflict],
+    old_name: &str,
+    txn: &mut Transaction,
+) -> Result<Vec<EditOutcome>> {
+    let old_snake = old_name.replace('-', "_");
+    let occurrence = Regex::new(&format!(r"\b{}\b", regex::escape(&old_snake)))?;
+
+    let mut outcomes = Vec::with_capacity(conflicts.len());
+    for (idx, conflict) in conflicts.iter().enumerate() {
+        let original = txn.read_file(&conflict.path)?;
+        let (annotated, first_line) = annotate(&original, &conflict.suggestions, &occurrence);
+
+        let file_name = conflict
+            .path
+            .file_name()
+            .map_or("conflict.rs".into(), |name| name.to_string_lossy());
+        let scratch = std::env::temp_dir().join(format!(
+            "cargo-rename-{}-{}-{}",
+            std::process::id(),
+            idx,
+            file_name
+        ));
+        std::fs::write(&scratch, annotated)?;
+
+        let mut previous: Option<String> = None;
+        let outcome = loop {
+            let edited = run_editor(editor, &scratch, first_line)
+                .and_then(|()| Ok(std::fs::read_to_string(&scratch)?));
+            let edited = match edited {
+                Ok(edited) => strip_markers(&edited),
+                Err(e) => {
+                    let _ = std::fs::remove_file(&scratch);
+                    return Err(e);
+                }
+            };
+
+            if edited == original {
+                break EditOutcome::Unchanged;
+            }
+            if syn::parse_file(&edited).is_ok() || !occurrence.is_match(&edited) {
+                txn.update_file(conflict.path.clone(), edited)?;
+                break EditOutcome::Staged;
+            }
+
+            let reason = format!("still does not parse and references '{}'", old_snake);
+            if previous.as_ref() == Some(&edited) {
+                break EditOutcome::Rejected(format!(
+                    "{}; the edit is kept in {}",
+                    reason,
+                    display_path(&scratch)
+                ));
+            }
+            errln!(
+                "   {} {}: {}; reopening it (save it unchanged to give up)",
+                symbols::cross().red(),
+                display_path(&conflict.path),
+                reason
+            );
+            previous = Some(edited);
+        };
+
+        if !matches!(outcome, EditOutcome::Rejected(_)) {
+            let _ = std::fs::remove_file(&scratch);
+        }
+        log::debug!("{}: {:?}", display_path(&conflict.path), outcome);
+        outcomes.push(outcome);
+    }
+
+    Ok(outcomes)
+}
+
+/// Inserts a suggestion comment above each suggested line, flagged as
+/// ambiguous if `occurrence` matches inside a string literal there.
+///
+/// Returns the annotated content and the 1-based line of the first comment.
+fn annotate(content: &str, suggestions: &[(usize, String)], occurrence: &Regex) -> (String, usize) {
+    let mut annotated = String::with_capacity(content.len());
+
+    for (idx, line) in content.split_inclusive('\n').enumerate() {
+        if let Some((_, suggestion)) = suggestions.iter().find(|(number, _)| *number == idx + 1) {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            let label = if occurrence
+                .find_iter(line)
+                .any(|m| in_string(&line[..m.start()]))
+            {
+                "ambiguous, in a string literal; suggested"
+            } else {
+                "suggested"
+            };
+            annotated.push_str(&format!(
+                "{}{}{}: {}\n",
+                indent,
+                MARKER,
+                label,
+                suggestion.trim()
+            ));
+        }
+        annotated.push_str(line);
+    }
+
+    let first_line = suggestions
+        .iter()
+        .map(|(number, _)| *number)
+        .min()
+        .unwrap_or(1);
+    (annotated, first_line)
+}
+
+/// Returns `true` if a string literal is open at the end of `prefix`, a line
+/// up to a match.
+fn in_string(prefix: &str) -> bool {
+    let mut open = false;
+    let mut chars = prefix.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if open => {
+                chars.next();
+            }
+            '"' => open = !open,
+            _ => {}
+        }
+    }
+    open
+}
+
+/// Removes the comments added by [`annotate`].
+fn strip_markers(content: &str) -> String {
+    content
+        .split_inclusive('\n')
+        .filter(|line| !line.trim_start().starts_with(MARKER))
+        .collect()
+}
+
+/// Runs `editor` on `path`, opened at `line`.
+///
+/// `editor` is a shell command, so it may carry arguments and quotes
+/// (`code --wait`, `"/opt/My Editor/edit"`). `+LINE` is understood by vi,
+/// nano and emacs.
+fn run_editor(editor: &str, path: &Path, line: usize) -> Result<()> {
+    #[cfg(unix)]
+    let mut command = {
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(format!("{} \"$@\"", editor))
+            .arg(editor);
+        command
+    };
+    #[cfg(not(unix))]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(editor);
+        command
+    };
+
+    let status = command
+        .arg(format!("+{}", line))
+        .arg(path)
+        .status()
+        .map_err(|e| anyhow::anyhow!("Failed to start editor '{}': {}", editor, e))?;
+
+    if !status.success() {
+        return Err(anyhow::anyhow!("Editor '{}' exited with {}", editor, status).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotate_and_strip_markers() {
+        let content = "fn f() {\n    old_crate::run(\n}\n";
+        let suggestions = vec![(2, "    new_crate::run(".to_string())];
+
+        let occurrence = Regex::new(r"\bold_crate\b").unwrap();
+
+        let (annotated, first_line) = annotate(content, &suggestions, &occurrence);
+
+        assert_eq!(
+            annotated,
+            "fn f() {\n    // cargo-rename: suggested: new_crate::run(\n    old_crate::run(\n}\n"
+        );
+        assert_eq!(first_line, 2);
+        assert_eq!(strip_markers(&annotated), content);
+
+        let content = "let s = \"a \\\" old_crate\";\nlet t = \"\\\\\"; old_crate::run();\n";
+        let suggestions = vec![
+            (1, "let s = \"a \\\" new_crate\";".to_string()),
+            (2, "let t = \"\\\\\"; new_crate::run();".to_string()),
+        ];
+        let (annotated, _) = annotate(content, &suggestions, &occurrence);
+        let labels: Vec<&str> = annotated
+            .lines()
+            .filter_map(|line| line.strip_prefix(MARKER))
+            .map(|line| line.split(':').next().unwrap())
+            .collect();
+        assert_eq!(
+            labels,
+            ["ambiguous, in a string literal; suggested", "suggested"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rejected_edit_is_reopened_and_kept() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let file = temp.path().join("lib.rs");
+        std::fs::write(&file, "fn f( { old_crate::run() }\n").unwrap();
+
+        // An editor whose path needs quoting, appending a line per run
+        let dir = temp.path().join("my editor");
+        std::fs::create_dir(&dir).unwrap();
+        let script = dir.join("edit.sh");
+        let log = temp.path().join("runs");
+        std::fs::write(
+            &script,
+            format!(
+                "#!/bin/sh\necho \"$1 $2\" >> '{}'\nsed 's/run()/go()/' \"$3\" > \"$3.tmp\" && mv \"$3.tmp\" \"$3\"\n",
+                log.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let editor = format!("'{}' --wait", script.display());
+
+        let conflicts = [Conflict {
+            path: file.clone(),
+            reason: "does not parse".to_string(),
+            suggestions: vec![(1, "fn f( { new_crate::run() }".to_string())],
+        }];
+        let mut txn = Transaction::new(false);
+        let outcomes = edit_with(&editor, &conflicts, "old-crate", &mut txn).unwrap();
+
+        // Rejected, reopened once, then given up on when saved unchanged
+        assert_eq!(
+            std::fs::read_to_string(&log).unwrap(),
+            "--wait +1\n--wait +1\n"
+        );
+        let [EditOutcome::Rejected(reason)] = outcomes.as_slice() else {
+            panic!("expected a rejected edit: {:?}", outcomes);
+        };
+        let kept = reason.split("kept in ").nth(1).unwrap();
+        assert!(
+            std::fs::read_to_string(kept)
+                .unwrap()
+                .contains("old_crate::go()")
+        );
+        let _ = std::fs::remove_file(kept);
+        assert!(txn.is_empty());
+    }
+}
@@ -0,0 +1,303 @@
+//! Environment variable prefix rewriting for crate renames.
+//!
+//! Crates commonly read settings from variables prefixed with their name
+//! (`OLD_CRATE_LOG`, `OLD_CRATE_CONFIG`). This opt-in pass renames such
+//! variables within the renamed package:
+//!
+//! - **`.rs`**: only inside string literals (`env::var("OLD_CRATE_LOG")`)
+//! - **`.md`**: anywhere in the text
+//! - **`.env`, `.env.*`**: anywhere in the file
+//!
+//! Constants and other identifiers are left untouched.
+//!
+//! Separately, [`update_artifact_env_vars`] follows a renamed artifact
+//! dependency key in a dependent's sources: Cargo names the variables it sets
+//! for `artifact = "bin"` dependencies after the key
+//! (`CARGO_BIN_FILE_OLD_CRATE_tool`).
+//!
+//! [`update_links_env_vars`] follows a renamed `links` key in a dependent's
+//! build script, which reads the metadata of the native library as
+//! `DEP_<LINKS>_<KEY>`.
+
+use crate::error::Result;
+use crate::fs::display_path;
+use crate::fs::transaction::Transaction;
+use crate::workspace_scan::{self, FileKind};
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// A single environment variable rename within one file.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EnvVarRename {
+    pub file: PathBuf,
+    pub old: String,
+    pub new: String,
+}
+
+/// Rewrites `OLD_CRATE_*` environment variable names in a package.
+///
+/// Returns each distinct rename per file, for reporting.
+pub fn update_env_prefix(
+    pkg_root: &Path,
+    old_name: &str,
+    new_name: &str,
+    txn: &mut Transaction,
+) -> Result<Vec<EnvVarRename>> {
+    let old_prefix = env_prefix(old_name);
+    let new_prefix = env_prefix(new_name);
+
+    let var_pattern = Regex::new(&format!(r"\b{}_[A-Z0-9_]+\b", regex::escape(&old_prefix)))?;
+    let literal_pattern = Regex::new(r#""(?:[^"\\]|\\.)*""#)?;
+
+    let mut renames = BTreeSet::new();
+
+    for file in workspace_scan::files_under(pkg_root) {
+        if !matches!(file.kind, FileKind::Rust | FileKind::Doc | FileKind::Env) {
+            continue;
+        }
+
+        let path = file.path.as_path();
+        let is_rust = file.kind == FileKind::Rust;
+
+        let Some(content) = txn.read_text(path) else {
+            continue;
+        };
+
+        let mut replace_vars = |text: &str| -> String {
+            var_pattern
+                .replace_all(text, |caps: &regex::Captures| {
+                    let old = &caps[0];
+                    let new = format!("{}{}", new_prefix, &old[old_prefix.len()..]);
+                    renames.insert(EnvVarRename {
+                        file: path.to_path_buf(),
+                        old: old.to_string(),
+                        new: new.clone(),
+                    });
+                    new
+                })
+                .into_owned()
+        };
+
+        let new_content = if is_rust {
+            literal_pattern
+                .replace_all(&content, |caps: &regex::Captures| replace_vars(&caps[0]))
+                .into_owned()
+        } else {
+            replace_vars(&content)
+        };
+
+        if new_content != content {
+            txn.update_file(path.to_path_buf(), new_content)?;
+            log::debug!("Updated env vars in: {}", display_path(path));
+        }
+    }
+
+    Ok(renames.into_iter().collect())
+}
+
+/// Rewrites `CARGO_{BIN,CDYLIB,STATICLIB}_{DIR,FILE}_OLD_CRATE*` variable
+/// names in the string literals of a dependent package's Rust sources.
+///
+/// The binary name suffix (`_tool`) is kept. Returns each distinct rename per
+/// file, for reporting.
+pub fn update_artifact_env_vars(
+    pkg_root: &Path,
+    old_name: &str,
+    new_name: &str,
+    txn: &mut Transaction,
+) -> Result<Vec<EnvVarRename>> {
+    let var_pattern = Regex::new(&format!(
+        r"\b(CARGO_(?:BIN|CDYLIB|STATICLIB)_(?:DIR|FILE)_){}(_[A-Za-z0-9_-]+)?\b",
+        regex::escape(&env_prefix(old_name))
+    ))?;
+    let literal_pattern = Regex::new(r#""(?:[^"\\]|\\.)*""#)?;
+    let new_dep = env_prefix(new_name);
+
+    let mut renames = BTreeSet::new();
+
+    for file in workspace_scan::files_under(pkg_root) {
+        if file.kind != FileKind::Rust {
+            continue;
+        }
+        let path = file.path.as_path();
+        let Some(content) = txn.read_text(path) else {
+            continue;
+        };
+
+        let new_content = literal_pattern
+            .replace_all(&content, |caps: &regex::Captures| {
+                var_pattern
+                    .replace_all(&caps[0], |var: &regex::Captures| {
+                        let new = format!(
+                            "{}{}{}",
+                            &var[1],
+                            new_dep,
+                            var.get(2).map_or("", |m| m.as_str())
+                        );
+                        renames.insert(EnvVarRename {
+                            file: path.to_path_buf(),
+                            old: var[0].to_string(),
+                            new: new.clone(),
+                        });
+                        new
+                    })
+                    .into_owned()
+            })
+            .into_owned();
+
+        if new_content != content {
+            txn.update_file(path.to_path_buf(), new_content)?;
+            log::debug!("Updated artifact env vars in: {}", display_path(path));
+        }
+    }
+
+    Ok(renames.into_iter().collect())
+}
+
+/// Rewrites `DEP_OLD_LINKS_*` variable names in the string literals of a
+/// build script.
+///
+/// Returns each distinct rename, for reporting.
+pub fn update_links_env_vars(
+    build_script: &Path,
+    old_links: &str,
+    new_links: &str,
+    txn: &mut Transaction,
+) -> Result<Vec<EnvVarRename>> {
+    let var_pattern = Regex::new(&format!(
+        r"\bDEP_{}_([A-Z0-9_]+)\b",
+        regex::escape(&env_prefix(old_links))
+    ))?;
+    let literal_pattern = Regex::new(r#""(?:[^"\\]|\\.)*""#)?;
+    let new_prefix = format!("DEP_{}_", env_prefix(new_links));
+
+    let Some(content) = txn.read_text(build_script) else {
+        return Ok(Vec::new());
+    };
+
+    let mut renames = BTreeSet::new();
+    let new_content = literal_pattern
+        .replace_all(&content, |caps: &regex::Captures| {
+            var_pattern
+                .replace_all(&caps[0], |var: &regex::Captures| {
+                    let new = format!("{}{}", new_prefix, &var[1]);
+                    renames.insert(EnvVarRename {
+                        file: build_script.to_path_buf(),
+                        old: var[0].to_string(),
+                        new: new.clone(),
+                    });
+                    new
+                })
+                .into_owned()
+        })
+        .into_owned();
+
+    if new_content != content {
+        txn.update_file(build_script.to_path_buf(), new_content)?;
+        log::debug!("Updated links env vars in: {}", display_path(build_script));
+    }
+
+    Ok(renames.into_iter().collect())
+}
+
+/// Converts a package name to its SCREAMING_SNAKE_CASE env var prefix.
+fn env_prefix(name: &str) -> String {
+    name.replace('-', "_").to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_env_prefix() {
+        assert_eq!(env_prefix("old-crate"), "OLD_CRATE");
+        assert_eq!(env_prefix("my_crate"), "MY_CRATE");
+    }
+
+    #[test]
+    fn test_rewrites_literals_docs_and_env_files() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir(root.join("src")).unwrap();
+
+        let lib = root.join("src/lib.rs");
+        fs::write(
+            &lib,
+            "const OLD_CRATE_LIMIT: u32 = 1;\nfn f() { std::env::var(\"OLD_CRATE_LOG\").ok(); }\n",
+        )
+        .unwrap();
+        let readme = root.join("README.md");
+        fs::write(&readme, "Set `OLD_CRATE_CONFIG` to a path.\n").unwrap();
+        let env = root.join(".env.example");
+        fs::write(&env, "OLD_CRATE_LOG=debug\nMY_OLD_CRATE_X=1\n").unwrap();
+
+        let mut txn = Transaction::new(false);
+        let renames = update_env_prefix(root, "old-crate", "new-crate", &mut txn).unwrap();
+        txn.commit().unwrap();
+
+        let lib_content = fs::read_to_string(&lib).unwrap();
+        assert!(lib_content.contains("const OLD_CRATE_LIMIT"));
+        assert!(lib_content.contains("\"NEW_CRATE_LOG\""));
+
+        assert!(
+            fs::read_to_string(&readme)
+                .unwrap()
+                .contains("`NEW_CRATE_CONFIG`")
+        );
+        assert_eq!(
+            fs::read_to_string(&env).unwrap(),
+            "NEW_CRATE_LOG=debug\nMY_OLD_CRATE_X=1\n"
+        );
+
+        assert_eq!(renames.len(), 3);
+    }
+
+    #[test]
+    fn test_rewrites_artifact_env_vars() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir(root.join("src")).unwrap();
+
+        let build = root.join("build.rs");
+        fs::write(
+            &build,
+            "fn main() {\n    let bin = env!(\"CARGO_BIN_FILE_OLD_CRATE_old-tool\");\n    let dir = std::env::var(\"CARGO_CDYLIB_DIR_OLD_CRATE\");\n    let other = env!(\"CARGO_BIN_FILE_OTHER_OLD_CRATE\");\n}\n",
+        )
+        .unwrap();
+
+        let mut txn = Transaction::new(false);
+        let renames = update_artifact_env_vars(root, "old-crate", "new-crate", &mut txn).unwrap();
+        txn.commit().unwrap();
+
+        let content = fs::read_to_string(&build).unwrap();
+        assert!(content.contains("\"CARGO_BIN_FILE_NEW_CRATE_old-tool\""));
+        assert!(content.contains("\"CARGO_CDYLIB_DIR_NEW_CRATE\""));
+        assert!(content.contains("\"CARGO_BIN_FILE_OTHER_OLD_CRATE\""));
+        assert_eq!(renames.len(), 2);
+    }
+
+    #[test]
+    fn test_rewrites_links_env_vars() {
+        let temp = TempDir::new().unwrap();
+        let build = temp.path().join("build.rs");
+        fs::write(
+            &build,
+            "fn main() {\n    let include = std::env::var(\"DEP_OLD_LIB_INCLUDE\");\n    let root = env!(\"DEP_OLD_LIB_ROOT\");\n    let other = std::env::var(\"DEP_OLD_LIBS_ROOT\");\n}\n",
+        )
+        .unwrap();
+
+        let mut txn = Transaction::new(false);
+        let renames = update_links_env_vars(&build, "old-lib", "new-lib", &mut txn).unwrap();
+        txn.commit().unwrap();
+
+        let content = fs::read_to_string(&build).unwrap();
+        assert!(content.contains("\"DEP_NEW_LIB_INCLUDE\""));
+        assert!(content.contains("\"DEP_NEW_LIB_ROOT\""));
+        assert!(content.contains("\"DEP_OLD_LIBS_ROOT\""));
+        assert_eq!(renames.len(), 2);
+    }
+}
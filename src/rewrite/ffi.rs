@@ -0,0 +1,169 @@
+//! C header naming updates for FFI crates.
+//!
+//! `cdylib`/`staticlib` crates often generate C headers with cbindgen. The
+//! crate name shows up in `cbindgen.toml` (`include_guard = "OLD_CRATE_H"`,
+//! `header`, `includes`) and in the include guards of generated headers.
+//! This opt-in pass (`--update-ffi`) renames:
+//!
+//! - **`cbindgen.toml`**: `old-crate`, `old_crate` and `OLD_CRATE` forms
+//! - **`.h`, `.hh`, `.hpp`**: include guard identifiers (`OLD_CRATE_H`,
+//!   `__OLD_CRATE_FFI_HPP__`) on `#ifndef`, `#define` and `#endif` lines
+//!
+//! Header file names are left alone; regenerate headers to rename them.
+
+use crate::error::Result;
+use crate::fs::display_path;
+use crate::fs::transaction::Transaction;
+use crate::workspace_scan::{self, FileKind};
+use regex::Regex;
+use std::path::Path;
+
+/// Updates cbindgen configuration and header include guards in a package.
+pub fn update_ffi(
+    pkg_root: &Path,
+    old_name: &str,
+    new_name: &str,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let forms = NameForms::new(old_name, new_name)?;
+
+    for file in workspace_scan::files_under(pkg_root) {
+        let path = file.path.as_path();
+        let is_config = file.kind == FileKind::Config
+            && path.file_name().and_then(|n| n.to_str()) == Some("cbindgen.toml");
+        let is_header = file.kind == FileKind::Header;
+
+        if !(is_config || is_header) {
+            continue;
+        }
+
+        let Some(content) = txn.read_text(path) else {
+            continue;
+        };
+
+        let new_content = if is_config {
+            forms.replace_all(&content)
+        } else {
+            content
+                .split_inclusive('\n')
+                .map(|line| {
+                    if is_guard_line(line) {
+                        forms.replace_guard(line)
+                    } else {
+                        line.to_string()
+                    }
+                })
+                .collect()
+        };
+
+        if new_content != content {
+            txn.update_file(path.to_path_buf(), new_content)?;
+            log::debug!("Updated FFI file: {}", display_path(path));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns `true` for preprocessor lines that carry an include guard.
+fn is_guard_line(line: &str) -> bool {
+    let line = line.trim_start();
+    ["#ifndef", "#define", "#endif", "#if !defined"]
+        .iter()
+        .any(|directive| line.starts_with(directive))
+}
+
+/// Kebab, snake and SCREAMING_SNAKE forms of a rename.
+struct NameForms {
+    kebab: (Regex, String),
+    snake: (Regex, String),
+    screaming: (Regex, String),
+    guard: (Regex, String),
+}
+
+impl NameForms {
+    fn new(old_name: &str, new_name: &str) -> Result<Self> {
+        let old_snake = old_name.replace('-', "_");
+        let new_snake = new_name.replace('-', "_");
+
+        // `_` is a word character, so `OLD_CRATE_H` needs an explicit suffix group
+        let form = |old: &str, new: String| -> Result<(Regex, String)> {
+            let pattern = Regex::new(&format!(r"\b{}(_|\b)", regex::escape(old)))?;
+            Ok((pattern, format!("{}${{1}}", new)))
+        };
+
+        Ok(Self {
+            kebab: form(old_name, new_name.to_string())?,
+            snake: form(&old_snake, new_snake.clone())?,
+            screaming: form(&old_snake.to_uppercase(), new_snake.to_uppercase())?,
+            guard: (
+                Regex::new(&format!(
+                    r"\b(_*){}((?:_[A-Z0-9]+)*_H(?:H|PP)?_*)\b",
+                    regex::escape(&old_snake.to_uppercase())
+                ))?,
+                format!("${{1}}{}${{2}}", new_snake.to_uppercase()),
+            ),
+        })
+    }
+
+    fn replace_all(&self, text: &str) -> String {
+        let text = self.kebab.0.replace_all(text, self.kebab.1.as_str());
+        let text = self.snake.0.replace_all(&text, self.snake.1.as_str());
+        self.replace_screaming(&text)
+    }
+
+    fn replace_screaming(&self, text: &str) -> String {
+        self.screaming
+            .0
+            .replace_all(text, self.screaming.1.as_str())
+            .into_owned()
+    }
+
+    fn replace_guard(&self, line: &str) -> String {
+        self.guard
+            .0
+            .replace_all(line, self.guard.1.as_str())
+            .into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_updates_cbindgen_config_and_guards() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir(root.join("include")).unwrap();
+
+        let config = root.join("cbindgen.toml");
+        fs::write(
+            &config,
+            "include_guard = \"OLD_CRATE_H\"\nheader = \"/* old-crate bindings */\"\nincludes = [\"old_crate_types.h\"]\n",
+        )
+        .unwrap();
+
+        let header = root.join("include/old_crate.h");
+        fs::write(
+            &header,
+            "#ifndef OLD_CRATE_H\n#define OLD_CRATE_H\n#define OLD_CRATE_VERSION 1\n\n#endif /* OLD_CRATE_H */\n",
+        )
+        .unwrap();
+
+        let mut txn = Transaction::new(false);
+        update_ffi(root, "old-crate", "new-crate", &mut txn).unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&config).unwrap(),
+            "include_guard = \"NEW_CRATE_H\"\nheader = \"/* new-crate bindings */\"\nincludes = [\"new_crate_types.h\"]\n"
+        );
+        assert_eq!(
+            fs::read_to_string(&header).unwrap(),
+            "#ifndef NEW_CRATE_H\n#define NEW_CRATE_H\n#define OLD_CRATE_VERSION 1\n\n#endif /* NEW_CRATE_H */\n"
+        );
+    }
+}
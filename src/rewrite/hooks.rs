@@ -0,0 +1,242 @@
+//! External rewrite hooks for custom file types.
+//!
+//! Formats this tool doesn't know about can be handled by an external
+//! executable, configured in the workspace root `Cargo.toml`:
+//!
+//! ```toml
+//! [[workspace.metadata.cargo-rename.hooks]]
+//! glob = "**/*.bzl"
+//! command = ["python3", "tools/rename_hook.py"]
+//! timeout = 10  # seconds, defaults to --timeout
+//! ```
+//!
+//! For every file matching `glob` (gitignore syntax, relative to the
+//! workspace root), `command` runs in the workspace root and receives a JSON
+//! request on stdin:
+//!
+//! ```json
+//! { "old_name": "old-crate", "new_name": "new-crate",
+//!   "old_dir": "crates/old-crate", "new_dir": "crates/new-crate",
+//!   "path": "tools/defs.bzl", "content": "..." }
+//! ```
+//!
+//! It prints the rewritten content to stdout; the result is staged like any
+//! other edit. `CARGO_RENAME_OLD_NAME`, `CARGO_RENAME_NEW_NAME` and
+//! `CARGO_RENAME_FILE` are also set. A non-zero exit, non-UTF-8 output or an
+//! expired timeout aborts the rename. `--no-hooks` skips all hooks.
+//!
+//! Hooks are repository commands, so they only run with `--allow-commands`,
+//! after the rename is confirmed.
+
+use crate::error::{RenameError, Result};
+use crate::fs::display_path;
+use crate::fs::transaction::Transaction;
+use crate::process;
+use crate::workspace_scan;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+/// A configured hook.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HookConfig {
+    /// Files to run the hook on.
+    pub glob: String,
+    /// Program and arguments.
+    pub command: Vec<String>,
+    /// Timeout in seconds; `0` disables it. Defaults to `--timeout`.
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+/// The rename a hook is run for.
+#[derive(Debug, Clone, Copy)]
+pub struct HookContext<'a> {
+    pub old_name: &'a str,
+    pub new_name: &'a str,
+    pub old_dir: &'a Path,
+    pub new_dir: &'a Path,
+}
+
+/// JSON request written to a hook's stdin.
+#[derive(Serialize)]
+struct HookRequest<'a> {
+    old_name: &'a str,
+    new_name: &'a str,
+    old_dir: String,
+    new_dir: String,
+    path: String,
+    content: &'a str,
+}
+
+/// Runs every hook on its matching files and stages the results.
+pub fn run_hooks(
+    hooks: &[HookConfig],
+    workspace_root: &Path,
+    context: HookContext,
+    txn: &mut Transaction,
+) -> Result<()> {
+    for hook in hooks {
+        let files =
+            workspace_scan::files_matching(workspace_root, std::slice::from_ref(&hook.glob))?;
+        log::debug!("Hook '{}' matched {} files", hook.glob, files.len());
+
+        for path in files {
+            run_hook(hook, workspace_root, context, &path, txn)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn run_hook(
+    hook: &HookConfig,
+    workspace_root: &Path,
+    context: HookContext,
+    path: &Path,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let Some(content) = txn.read_text(path) else {
+        return Ok(());
+    };
+
+    let request = HookRequest {
+        old_name: context.old_name,
+        new_name: context.new_name,
+        old_dir: relative_to(context.old_dir, workspace_root),
+        new_dir: relative_to(context.new_dir, workspace_root),
+        path: relative_to(path, workspace_root),
+        content: &content,
+    };
+    let input = serde_json::to_vec(&request)
+        .map_err(|e| anyhow::anyhow!("Failed to serialize hook request: {}", e))?;
+
+    let mut cmd = Command::new(program_path(&hook.command[0], workspace_root));
+    cmd.args(&hook.command[1..])
+        .current_dir(workspace_root)
+        .env("CARGO_RENAME_OLD_NAME", context.old_name)
+        .env("CARGO_RENAME_NEW_NAME", context.new_name)
+        .env("CARGO_RENAME_FILE", path);
+
+    let timeout = match hook.timeout {
+        Some(0) => None,
+        Some(secs) => Some(Duration::from_secs(secs)),
+        None => process::timeout(),
+    };
+
+    let failed = |reason: String| RenameError::HookFailed(hook.command.join(" "), reason);
+
+    let output = process::output_with_input(&mut cmd, input, timeout)?;
+    if !output.status.success() {
+        return Err(failed(format!(
+            "{} ({}): {}",
+            display_path(path),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let new_content = String::from_utf8(output.stdout)
+        .map_err(|_| failed(format!("{}: output is not valid UTF-8", display_path(path))))?;
+
+    if new_content != content {
+        txn.update_file(path.to_path_buf(), new_content)?;
+        log::debug!("Hook updated: {}", display_path(path));
+    }
+
+    Ok(())
+}
+
+/// Formats `path` relative to `root` with forward slashes, independent of
+/// `--absolute-paths`, so hooks see the same request on every platform.
+fn relative_to(path: &Path, root: &Path) -> String {
+    match pathdiff::diff_paths(path, root) {
+        Some(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+        Some(rel) => rel.to_string_lossy().replace('\\', "/"),
+        None => path.to_string_lossy().replace('\\', "/"),
+    }
+}
+
+/// Resolves relative program paths (`tools/hook.sh`) against the workspace root.
+///
+/// Bare names (`python3`) are left for `PATH` lookup.
+fn program_path(program: &str, workspace_root: &Path) -> PathBuf {
+    let path = Path::new(program);
+    if path.is_relative() && path.components().count() > 1 {
+        workspace_root.join(path)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn hook(script: &str) -> HookConfig {
+        HookConfig {
+            glob: "*.cfg".to_string(),
+            command: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+            timeout: Some(10),
+        }
+    }
+
+    fn context(root: &Path) -> HookContext<'_> {
+        HookContext {
+            old_name: "old-crate",
+            new_name: "new-crate",
+            old_dir: root,
+            new_dir: root,
+        }
+    }
+
+    #[test]
+    fn test_hook_output_is_staged() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("app.cfg"), "crate = old-crate\n").unwrap();
+        fs::write(root.join("other.txt"), "old-crate\n").unwrap();
+
+        let hooks = [hook(
+            r#"grep -q '"path":"app.cfg"' && printf 'crate = %s\n' "$CARGO_RENAME_NEW_NAME""#,
+        )];
+
+        let mut txn = Transaction::new(false);
+        run_hooks(&hooks, root, context(root), &mut txn).unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(root.join("app.cfg")).unwrap(),
+            "crate = new-crate\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("other.txt")).unwrap(),
+            "old-crate\n"
+        );
+    }
+
+    #[test]
+    fn test_failing_hook_aborts() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("app.cfg"), "crate = old-crate\n").unwrap();
+
+        let hooks = [hook("echo 'unsupported' >&2; exit 3")];
+
+        let mut txn = Transaction::new(false);
+        let result = run_hooks(&hooks, root, context(root), &mut txn);
+
+        match result {
+            Err(RenameError::HookFailed(_, reason)) => {
+                assert!(reason.contains("app.cfg"));
+                assert!(reason.contains("unsupported"));
+            }
+            other => panic!("expected hook failure, got {:?}", other),
+        }
+        assert!(txn.is_empty());
+    }
+}
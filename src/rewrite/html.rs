@@ -0,0 +1,219 @@
+//! Policies for HTML embedded in Markdown documentation.
+//!
+//! READMEs mix Markdown with raw HTML. Whole-word replacement is right for
+//! prose-like attributes (`<img alt="old-crate logo">`) but wrong for
+//! identifiers owned by code or tooling (`id`, `class`, `data-*`, comment
+//! markers). Documents are split into spans, and each span is rewritten
+//! according to an [`HtmlPolicy`]:
+//!
+//! - **Text between tags**: treated as Markdown
+//! - **Text attributes** (`alt`, `title`, `aria-label`): rewritten as prose
+//! - **URL attributes** (`href`, `src`): follow the `urls`/`badges` categories
+//! - **Other attributes**, **comments** and **skipped elements**
+//!   (`<script>`, `<style>`): left untouched
+//!
+//! The policy can be changed in the workspace root `Cargo.toml`:
+//!
+//! ```toml
+//! [workspace.metadata.cargo-rename.html]
+//! comments = true                          # rewrite inside <!-- -->
+//! text-attributes = ["alt", "figure.id"]   # `element.attribute` scopes
+//! url-attributes = ["href", "src", "srcset"]
+//! skip-elements = ["script", "style", "kbd"]
+//! ```
+
+use crate::error::Result;
+use regex::Regex;
+use serde::Deserialize;
+
+/// How crate names inside embedded HTML are treated.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct HtmlPolicy {
+    /// Rewrite inside `<!-- -->` comments.
+    pub comments: bool,
+    /// Attributes rewritten as prose, optionally scoped as `element.attribute`.
+    pub text_attributes: Vec<String>,
+    /// Attributes rewritten as URLs, optionally scoped as `element.attribute`.
+    pub url_attributes: Vec<String>,
+    /// Elements whose content is left untouched.
+    pub skip_elements: Vec<String>,
+}
+
+impl Default for HtmlPolicy {
+    fn default() -> Self {
+        let strings = |items: &[&str]| items.iter().map(|s| s.to_string()).collect();
+
+        Self {
+            comments: false,
+            text_attributes: strings(&["alt", "title", "aria-label"]),
+            url_attributes: strings(&["href", "src"]),
+            skip_elements: strings(&["script", "style"]),
+        }
+    }
+}
+
+/// A span of a document and how it should be rewritten.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Span<'a> {
+    /// Markdown, possibly containing URLs.
+    Markdown(&'a str),
+    /// Prose without URLs.
+    Text(&'a str),
+    /// A single URL.
+    Url(&'a str),
+    /// Left as is.
+    Verbatim(&'a str),
+}
+
+/// Splits `content` into spans according to `policy`.
+///
+/// Tags without `name=value` attributes stay part of the surrounding
+/// Markdown, so `<old-crate>` placeholders and generics like `Vec<T>` are
+/// rewritten as before.
+pub(crate) fn split<'a>(content: &'a str, policy: &HtmlPolicy) -> Result<Vec<Span<'a>>> {
+    let skipped = policy
+        .skip_elements
+        .iter()
+        .map(|el| {
+            let el = regex::escape(el);
+            format!(r"<{el}\b[^>]*>.*?</{el}\s*>")
+        })
+        .collect::<Vec<_>>();
+    let skipped = if skipped.is_empty() {
+        String::new()
+    } else {
+        format!("|(?P<skip>{})", skipped.join("|"))
+    };
+    let html = Regex::new(&format!(
+        r#"(?is)(?P<comment><!--.*?-->){}|<(?P<tag>[a-z][a-z0-9-]*)(?P<attrs>\s(?:"[^"]*"|'[^']*'|[^"'<>])*)>"#,
+        skipped
+    ))?;
+    let attribute = Regex::new(
+        r#"(?P<name>[A-Za-z_:][\w:.-]*)\s*=\s*(?:"(?P<dq>[^"]*)"|'(?P<sq>[^']*)'|(?P<bare>[^\s"'=<>`]+))"#,
+    )?;
+
+    let mut spans = Vec::new();
+    let mut last = 0;
+
+    for caps in html.captures_iter(content) {
+        let whole = caps.get(0).expect("match always has group 0");
+        spans.push(Span::Markdown(&content[last..whole.start()]));
+        last = whole.end();
+
+        if let Some(comment) = caps.name("comment") {
+            spans.push(if policy.comments {
+                Span::Markdown(comment.as_str())
+            } else {
+                Span::Verbatim(comment.as_str())
+            });
+            continue;
+        }
+
+        if caps.name("skip").is_some() {
+            spans.push(Span::Verbatim(whole.as_str()));
+            continue;
+        }
+
+        let tag = &caps["tag"];
+        let attrs = caps.name("attrs").expect("tag always has attributes");
+        if !attribute.is_match(attrs.as_str()) {
+            // Not an HTML tag after all (`<T as Trait>`)
+            spans.push(Span::Markdown(whole.as_str()));
+            continue;
+        }
+        let mut pos = whole.start();
+
+        for attr in attribute.captures_iter(attrs.as_str()) {
+            let value = ["dq", "sq", "bare"]
+                .iter()
+                .find_map(|name| attr.name(name))
+                .expect("one value group always matches");
+            let start = attrs.start() + value.start();
+            let end = attrs.start() + value.end();
+
+            spans.push(Span::Verbatim(&content[pos..start]));
+            let text = &content[start..end];
+            spans.push(if lists(&policy.text_attributes, tag, &attr["name"]) {
+                Span::Text(text)
+            } else if lists(&policy.url_attributes, tag, &attr["name"]) {
+                Span::Url(text)
+            } else {
+                Span::Verbatim(text)
+            });
+            pos = end;
+        }
+
+        spans.push(Span::Verbatim(&content[pos..whole.end()]));
+    }
+
+    spans.push(Span::Markdown(&content[last..]));
+    spans.retain(|span| !span_text(span).is_empty());
+    Ok(spans)
+}
+
+/// Returns `true` if `list` names `attribute`, globally or scoped to `element`.
+fn lists(list: &[String], element: &str, attribute: &str) -> bool {
+    list.iter().any(|entry| match entry.split_once('.') {
+        Some((el, attr)) => {
+            el.eq_ignore_ascii_case(element) && attr.eq_ignore_ascii_case(attribute)
+        }
+        None => entry.eq_ignore_ascii_case(attribute),
+    })
+}
+
+fn span_text<'a>(span: &Span<'a>) -> &'a str {
+    match *span {
+        Span::Markdown(s) | Span::Text(s) | Span::Url(s) | Span::Verbatim(s) => s,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classifies_attributes() {
+        let input = r#"<img src="logo.svg" alt="old-crate logo" class="old-crate">"#;
+        let spans = split(input, &HtmlPolicy::default()).unwrap();
+
+        assert!(spans.contains(&Span::Url("logo.svg")));
+        assert!(spans.contains(&Span::Text("old-crate logo")));
+        assert!(spans.contains(&Span::Verbatim("old-crate")));
+    }
+
+    #[test]
+    fn test_comments_and_skipped_elements_are_verbatim() {
+        let input = "a <!-- old-crate --> b <script>old_crate()</script> c <old-crate>";
+        let spans = split(input, &HtmlPolicy::default()).unwrap();
+
+        assert_eq!(
+            spans,
+            vec![
+                Span::Markdown("a "),
+                Span::Verbatim("<!-- old-crate -->"),
+                Span::Markdown(" b "),
+                Span::Verbatim("<script>old_crate()</script>"),
+                Span::Markdown(" c <old-crate>"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scoped_attributes() {
+        let policy = HtmlPolicy {
+            text_attributes: vec!["figure.id".to_string()],
+            ..HtmlPolicy::default()
+        };
+        let input = r#"<figure id="old-crate"><div id="old-crate"></div></figure>"#;
+        let spans = split(input, &policy).unwrap();
+
+        assert_eq!(
+            spans
+                .iter()
+                .filter(|s| matches!(s, Span::Text("old-crate")))
+                .count(),
+            1
+        );
+    }
+}
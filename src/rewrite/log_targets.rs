@@ -0,0 +1,141 @@
+//! Log target rewriting for crate renames.
+//!
+//! `tracing` and `log` default a record's target to its module path, so
+//! explicit targets and filters name the crate in snake_case:
+//!
+//! ```text
+//! tracing::info!(target: "old_crate", ...)            .rs: macro target
+//! EnvFilter::new("info,old_crate::db=trace")          .rs: filter directive in a string literal
+//! RUST_LOG=old_crate=debug cargo run                  .md: filter directive anywhere
+//! ```
+//!
+//! This opt-in pass (`--update-log-targets`) rewrites targets that are the
+//! old name or a module path below it, across the whole workspace. Targets of
+//! other crates that merely start with the name (`old_crate_derive`) are kept.
+
+use crate::error::Result;
+use crate::fs::display_path;
+use crate::fs::transaction::Transaction;
+use crate::workspace_scan::{self, FileKind};
+use regex::{Captures, Regex};
+use std::path::{Path, PathBuf};
+
+/// Log targets rewritten in one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogTargetEdit {
+    pub file: PathBuf,
+    pub count: usize,
+}
+
+/// Rewrites log targets and filter directives naming the old crate under `root`.
+pub fn update_log_targets(
+    root: &Path,
+    old_name: &str,
+    new_name: &str,
+    txn: &mut Transaction,
+) -> Result<Vec<LogTargetEdit>> {
+    let old = regex::escape(&old_name.replace('-', "_"));
+    let new = new_name.replace('-', "_");
+
+    // target: "old_crate" / target: "old_crate::module"
+    let macro_target = Regex::new(&format!(r#"(\btarget\s*:\s*"){}((?:::\w+)*")"#, old))?;
+    // old_crate=debug, old_crate::db[span]=trace, preceded by a directive separator
+    let directive = Regex::new(&format!(
+        r#"(?m)(^|[\s,"'`=]){}((?:::\w+)*(?:\[[^\]]*\])?=[A-Za-z]+)"#,
+        old
+    ))?;
+    let literal = Regex::new(r#""(?:[^"\\]|\\.)*""#)?;
+
+    let mut edits = Vec::new();
+
+    for file in workspace_scan::files_under(root) {
+        if !matches!(file.kind, FileKind::Rust | FileKind::Doc) {
+            continue;
+        }
+        let path = file.path.as_path();
+        let Some(content) = txn.read_text(path) else {
+            continue;
+        };
+
+        let mut count = 0;
+        let mut rename = |caps: &Captures| {
+            count += 1;
+            format!("{}{}{}", &caps[1], new, &caps[2])
+        };
+
+        let new_content = if file.kind == FileKind::Rust {
+            let content = macro_target.replace_all(&content, &mut rename).into_owned();
+            literal
+                .replace_all(&content, |lit: &Captures| {
+                    directive.replace_all(&lit[0], &mut rename).into_owned()
+                })
+                .into_owned()
+        } else {
+            directive.replace_all(&content, &mut rename).into_owned()
+        };
+
+        if new_content != content {
+            txn.update_file(path.to_path_buf(), new_content)?;
+            log::debug!("Updated log targets in: {}", display_path(path));
+            edits.push(LogTargetEdit {
+                file: path.to_path_buf(),
+                count,
+            });
+        }
+    }
+
+    Ok(edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rewrites_targets_and_directives() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir(root.join("src")).unwrap();
+
+        let main = root.join("src/main.rs");
+        fs::write(
+            &main,
+            r#"fn main() {
+    tracing::info!(target: "old_crate", "start");
+    log::debug!(target: "old_crate::db", "query");
+    log::debug!(target: "old_crate_derive", "kept");
+    let filter = EnvFilter::new("info,old_crate=debug,old_crate_derive=trace");
+    let old_crate = 1;
+}
+"#,
+        )
+        .unwrap();
+        let readme = root.join("README.md");
+        fs::write(
+            &readme,
+            "Run `RUST_LOG=old_crate::db=trace cargo run`.\nThe old_crate crate logs a lot.\n",
+        )
+        .unwrap();
+
+        let mut txn = Transaction::new(false);
+        let edits = update_log_targets(root, "old-crate", "new-crate", &mut txn).unwrap();
+        txn.commit().unwrap();
+
+        let main = fs::read_to_string(&main).unwrap();
+        assert!(main.contains(r#"target: "new_crate", "start""#));
+        assert!(main.contains(r#"target: "new_crate::db""#));
+        assert!(main.contains(r#"target: "old_crate_derive""#));
+        assert!(main.contains(r#""info,new_crate=debug,old_crate_derive=trace""#));
+        assert!(main.contains("let old_crate = 1;"));
+        assert_eq!(
+            fs::read_to_string(&readme).unwrap(),
+            "Run `RUST_LOG=new_crate::db=trace cargo run`.\nThe old_crate crate logs a lot.\n"
+        );
+
+        let counts: Vec<_> = edits.iter().map(|e| e.count).collect();
+        assert_eq!(edits.len(), 2);
+        assert_eq!(counts.iter().sum::<usize>(), 4);
+    }
+}
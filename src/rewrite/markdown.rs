@@ -0,0 +1,447 @@
+//! Markdown-aware documentation rewriting.
+//!
+//! Splits a document into URL spans and prose, so each can be updated
+//! independently:
+//!
+//! - **`text`**: prose, headings, link text and code spans
+//! - **`badges`**: URLs pointing at crates.io, docs.rs, shields.io and similar
+//! - **`urls`**: every other URL (blog posts, issues, repositories)
+//!
+//! URL spans are link destinations (`[text](url)`), reference definitions
+//! (`[id]: url`), autolinks (`<url>`) and bare `http(s)://` URLs. Badge URLs
+//! are rewritten per URL component (see [`crate::rewrite::badges`]); when the
+//! `badges` category is off, badges that still name the old crate are logged.
+//!
+//! Reference labels that name the crate in snake_case, such as
+//! ``[`old_crate::Config`]: https://docs.rs/...``, are renamed with `text`,
+//! together with every `[label]` that uses them, so links keep resolving.
+//!
+//! With `ignore_case`, prose matches regardless of case and the replacement
+//! follows the casing of each occurrence (`Old-crate` → `New-crate`).
+//!
+//! Embedded HTML is split off first and handled according to the
+//! [`HtmlPolicy`]; see [`crate::rewrite::html`].
+
+use crate::error::Result;
+use crate::rewrite::badges::rewrite_badge_url;
+use crate::rewrite::html::{self, HtmlPolicy, Span};
+use clap::ValueEnum;
+use regex::Regex;
+
+/// Hosts whose links are treated as badges rather than ordinary URLs.
+const BADGE_HOSTS: &[&str] = &[
+    "crates.io",
+    "docs.rs",
+    "img.shields.io",
+    "shields.io",
+    "deps.rs",
+    "lib.rs",
+];
+
+/// Category of documentation occurrence that may be rewritten.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DocsCategory {
+    /// Prose, headings, link text and code spans
+    Text,
+    /// URLs that are not badges
+    Urls,
+    /// crates.io, docs.rs and shields.io badge URLs
+    Badges,
+}
+
+/// Workspace members whose documentation is rewritten.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum DocsScope {
+    /// The renamed package only
+    Package,
+    /// The renamed package and the members depending on it
+    #[default]
+    Dependents,
+    /// Every workspace member
+    Workspace,
+}
+
+impl DocsScope {
+    /// Returns the scope's name as accepted by `--docs-scope`.
+    pub fn name(self) -> String {
+        self.to_possible_value()
+            .expect("no scope is skipped")
+            .get_name()
+            .to_string()
+    }
+
+    /// Describes the members in scope, for plan output.
+    pub fn description(self) -> &'static str {
+        match self {
+            DocsScope::Package => "the renamed package only",
+            DocsScope::Dependents => "the renamed package and members depending on it",
+            DocsScope::Workspace => "every workspace member",
+        }
+    }
+}
+
+/// Controls which documentation occurrences are rewritten.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocOptions {
+    pub text: bool,
+    pub urls: bool,
+    pub badges: bool,
+    /// Match prose case-insensitively, preserving each occurrence's casing.
+    pub ignore_case: bool,
+    /// Treatment of embedded HTML.
+    pub html: HtmlPolicy,
+    /// Members whose documentation is rewritten.
+    pub scope: DocsScope,
+}
+
+impl Default for DocOptions {
+    /// Updates prose and badges, leaves other URLs alone.
+    fn default() -> Self {
+        Self {
+            text: true,
+            urls: false,
+            badges: true,
+            ignore_case: false,
+            html: HtmlPolicy::default(),
+            scope: DocsScope::default(),
+        }
+    }
+}
+
+impl DocOptions {
+    /// Builds options from `--docs-update` categories.
+    ///
+    /// `None` selects the defaults.
+    pub fn from_categories(categories: Option<&[DocsCategory]>) -> Self {
+        let Some(categories) = categories else {
+            return Self::default();
+        };
+
+        Self {
+            text: categories.contains(&DocsCategory::Text),
+            urls: categories.contains(&DocsCategory::Urls),
+            badges: categories.contains(&DocsCategory::Badges),
+            ..Self::default()
+        }
+    }
+}
+
+/// Rewrites crate name occurrences in a Markdown document.
+///
+/// `old_kebab` is matched as a whole word. Inside URLs the snake_case form is
+/// matched as well, since docs.rs paths embed the library name.
+pub fn rewrite_markdown(
+    content: &str,
+    old_kebab: &str,
+    new_kebab: &str,
+    options: &DocOptions,
+) -> Result<String> {
+    let old_snake = old_kebab.replace('-', "_");
+    let new_snake = new_kebab.replace('-', "_");
+
+    let word = Regex::new(&format!(r"\b{}\b", regex::escape(old_kebab)))?;
+    let text_word = if options.ignore_case {
+        Regex::new(&format!(r"(?i)\b{}\b", regex::escape(old_kebab)))?
+    } else {
+        word.clone()
+    };
+    let replace_text = |text: &str| {
+        text_word
+            .replace_all(text, |caps: &regex::Captures| {
+                match_case(&caps[0], new_kebab)
+            })
+            .into_owned()
+    };
+    let snake_word = Regex::new(&format!(r"\b{}\b", regex::escape(&old_snake)))?;
+    let url_span = Regex::new(
+        r#"(?m)\]\((?P<link>[^)\s]+)|<(?P<auto>[a-z]+://[^>\s]+)>|^\s*\[[^\]]+\]:\s*(?P<def>\S+)|(?P<bare>https?://[^\s)\]>"'<]+)"#,
+    )?;
+
+    let labels = snake_labels(content, &snake_word, &new_snake)?;
+
+    let push_text = |result: &mut String, text: &str| {
+        if options.text {
+            let mut text = replace_text(text);
+            for (old, new) in &labels {
+                text = text.replace(old.as_str(), new);
+            }
+            result.push_str(&text);
+        } else {
+            result.push_str(text);
+        }
+    };
+    let push_url = |result: &mut String, url: &str| {
+        if is_badge_url(url) {
+            let rewritten = rewrite_badge_url(url, old_kebab, new_kebab);
+            if options.badges {
+                result.push_str(&rewritten);
+            } else {
+                if rewritten != url {
+                    log::warn!(
+                        "Badge URL still refers to '{}': {} (add `badges` to --docs-update to fix it)",
+                        old_kebab,
+                        url
+                    );
+                }
+                result.push_str(url);
+            }
+            return;
+        }
+
+        if options.urls {
+            let replaced = word.replace_all(url, new_kebab);
+            result.push_str(&snake_word.replace_all(&replaced, new_snake.as_str()));
+        } else {
+            result.push_str(url);
+        }
+    };
+
+    let mut result = String::with_capacity(content.len());
+
+    for span in html::split(content, &options.html)? {
+        let markdown = match span {
+            Span::Markdown(markdown) => markdown,
+            Span::Text(text) => {
+                push_text(&mut result, text);
+                continue;
+            }
+            Span::Url(url) => {
+                push_url(&mut result, url);
+                continue;
+            }
+            Span::Verbatim(text) => {
+                result.push_str(text);
+                continue;
+            }
+        };
+
+        let mut last = 0;
+        for caps in url_span.captures_iter(markdown) {
+            let url = ["link", "auto", "def", "bare"]
+                .iter()
+                .find_map(|name| caps.name(name))
+                .expect("one URL group always matches");
+
+            push_text(&mut result, &markdown[last..url.start()]);
+            push_url(&mut result, url.as_str());
+            last = url.end();
+        }
+        push_text(&mut result, &markdown[last..]);
+    }
+
+    Ok(result)
+}
+
+/// Returns the reference definition labels naming the crate in snake_case,
+/// as `([old label], [new label])` pairs.
+///
+/// Kebab-case labels are covered by the prose rewrite already.
+fn snake_labels(
+    content: &str,
+    snake_word: &Regex,
+    new_snake: &str,
+) -> Result<Vec<(String, String)>> {
+    let definition = Regex::new(r"(?m)^\s{0,3}(\[[^\[\]\n]+\]):")?;
+
+    let mut labels: Vec<(String, String)> = Vec::new();
+    for caps in definition.captures_iter(content) {
+        let label = &caps[1];
+        if snake_word.is_match(label) && !labels.iter().any(|(old, _)| old == label) {
+            labels.push((
+                label.to_string(),
+                snake_word.replace_all(label, new_snake).into_owned(),
+            ));
+        }
+    }
+
+    Ok(labels)
+}
+
+/// Applies the casing pattern of `found` to `replacement`.
+///
+/// - `old-crate` → `new-crate` (lowercase)
+/// - `OLD-CRATE` → `NEW-CRATE` (uppercase)
+/// - `Old-crate` → `New-crate` (capitalized)
+/// - `Old-Crate` → `New-Crate` (title case per segment)
+///
+/// Any other pattern returns `replacement` unchanged.
+pub fn match_case(found: &str, replacement: &str) -> String {
+    let is_lower = |s: &str| !s.chars().any(|c| c.is_uppercase());
+    let is_upper = |s: &str| !s.chars().any(|c| c.is_lowercase());
+    let is_capitalized = |s: &str| {
+        let mut chars = s.chars();
+        chars.next().is_some_and(|c| c.is_uppercase()) && is_lower(chars.as_str())
+    };
+    let capitalize = |s: &str| {
+        let mut chars = s.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    };
+
+    if is_lower(found) {
+        return replacement.to_string();
+    }
+
+    if is_upper(found) && found.chars().any(|c| c.is_uppercase()) {
+        return replacement.to_uppercase();
+    }
+
+    let segments: Vec<&str> = found.split(['-', '_']).collect();
+    if segments.len() > 1 && segments.iter().all(|s| is_capitalized(s)) {
+        return replacement
+            .split_inclusive(['-', '_'])
+            .map(capitalize)
+            .collect();
+    }
+
+    if is_capitalized(found) {
+        return capitalize(replacement);
+    }
+
+    replacement.to_string()
+}
+
+/// Returns `true` if the URL points at a known badge or registry host.
+fn is_badge_url(url: &str) -> bool {
+    let without_scheme = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let host = without_scheme.split(['/', '?', '#']).next().unwrap_or("");
+    let host = host.strip_prefix("www.").unwrap_or(host);
+
+    BADGE_HOSTS.contains(&host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const README: &str = r#"# old-crate
+
+[![Crates.io](https://img.shields.io/crates/v/old-crate.svg)](https://crates.io/crates/old-crate)
+[Docs](https://docs.rs/old-crate/latest/old_crate/)
+
+Read the [old-crate announcement](https://blog.example.com/old-crate-released).
+"#;
+
+    #[test]
+    fn test_default_updates_text_and_badges_only() {
+        let result =
+            rewrite_markdown(README, "old-crate", "new-crate", &DocOptions::default()).unwrap();
+
+        assert!(result.contains("# new-crate"));
+        assert!(result.contains("https://img.shields.io/crates/v/new-crate.svg"));
+        assert!(result.contains("https://crates.io/crates/new-crate"));
+        assert!(result.contains("https://docs.rs/new-crate/latest/new_crate/"));
+        assert!(result.contains("[new-crate announcement]"));
+        assert!(result.contains("https://blog.example.com/old-crate-released"));
+    }
+
+    #[test]
+    fn test_urls_only() {
+        let options = DocOptions::from_categories(Some(&[DocsCategory::Urls]));
+        let result = rewrite_markdown(README, "old-crate", "new-crate", &options).unwrap();
+
+        assert!(result.contains("# old-crate"));
+        assert!(result.contains("https://crates.io/crates/old-crate"));
+        assert!(result.contains("https://blog.example.com/new-crate-released"));
+    }
+
+    #[test]
+    fn test_reference_style_badges() {
+        let input = "[![crates.io][crates-badge]][crates-url]\n\n\
+                     [crates-badge]: https://img.shields.io/badge/old--crate-1.0-orange\n\
+                     [crates-url]: https://crates.io/crates/old-crate-derive\n";
+        let result =
+            rewrite_markdown(input, "old-crate", "new-crate", &DocOptions::default()).unwrap();
+
+        assert!(
+            result.contains("[crates-badge]: https://img.shields.io/badge/new--crate-1.0-orange")
+        );
+        assert!(result.contains("[crates-url]: https://crates.io/crates/old-crate-derive"));
+    }
+
+    #[test]
+    fn test_snake_case_reference_labels() {
+        let input = "See [`old_crate::run`] and [the crate][old_crate].\n\n\
+                     [`old_crate::run`]: https://docs.rs/old-crate/latest/old_crate/fn.run.html\n\
+                     [old_crate]: https://crates.io/crates/old-crate\n";
+        let result =
+            rewrite_markdown(input, "old-crate", "new-crate", &DocOptions::default()).unwrap();
+
+        assert_eq!(
+            result,
+            "See [`new_crate::run`] and [the crate][new_crate].\n\n\
+             [`new_crate::run`]: https://docs.rs/new-crate/latest/new_crate/fn.run.html\n\
+             [new_crate]: https://crates.io/crates/new-crate\n"
+        );
+
+        // Without a definition, a bracketed snake_case name is left alone
+        let result = rewrite_markdown(
+            "Use [old_crate] here.\n",
+            "old-crate",
+            "new-crate",
+            &DocOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(result, "Use [old_crate] here.\n");
+    }
+
+    #[test]
+    fn test_match_case() {
+        assert_eq!(match_case("old-crate", "new-crate"), "new-crate");
+        assert_eq!(match_case("OLD-CRATE", "new-crate"), "NEW-CRATE");
+        assert_eq!(match_case("Old-crate", "new-crate"), "New-crate");
+        assert_eq!(match_case("Old-Crate", "new-crate"), "New-Crate");
+        assert_eq!(match_case("oLd-crate", "new-crate"), "new-crate");
+    }
+
+    #[test]
+    fn test_ignore_case_preserves_casing() {
+        let options = DocOptions {
+            ignore_case: true,
+            ..DocOptions::default()
+        };
+        let input = "Old-crate provides parsing. Use old-crate or OLD-CRATE.\n";
+        let result = rewrite_markdown(input, "old-crate", "new-crate", &options).unwrap();
+
+        assert_eq!(
+            result,
+            "New-crate provides parsing. Use new-crate or NEW-CRATE.\n"
+        );
+    }
+
+    #[test]
+    fn test_case_sensitive_by_default() {
+        let input = "Old-crate provides parsing.\n";
+        let result =
+            rewrite_markdown(input, "old-crate", "new-crate", &DocOptions::default()).unwrap();
+
+        assert_eq!(result, input);
+    }
+
+    #[test]
+    fn test_embedded_html_follows_policy() {
+        let input = r#"<p align="center">
+  <img src="https://img.shields.io/crates/v/old-crate.svg" alt="old-crate logo" id="old-crate">
+</p>
+<!-- old-crate:start -->
+old-crate is fast.
+"#;
+        let result =
+            rewrite_markdown(input, "old-crate", "new-crate", &DocOptions::default()).unwrap();
+
+        assert!(result.contains(r#"src="https://img.shields.io/crates/v/new-crate.svg""#));
+        assert!(result.contains(r#"alt="new-crate logo""#));
+        assert!(result.contains(r#"id="old-crate""#));
+        assert!(result.contains("<!-- old-crate:start -->"));
+        assert!(result.contains("new-crate is fast."));
+    }
+
+    #[test]
+    fn test_is_badge_url() {
+        assert!(is_badge_url("https://crates.io/crates/foo"));
+        assert!(is_badge_url("https://img.shields.io/crates/v/foo.svg"));
+        assert!(!is_badge_url("https://github.com/acme/foo"));
+    }
+}
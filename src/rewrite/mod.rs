@@ -1,5 +1,34 @@
 //! Source code rewriting for package renames.
 
+pub mod alias;
+pub mod badges;
+pub mod conflicts;
+pub mod env;
+pub mod ffi;
+pub mod hooks;
+pub mod html;
+pub mod log_targets;
+pub mod markdown;
 pub mod rust;
+pub mod ssr;
+pub mod tools;
+pub mod trace;
 
-pub use rust::update_source_code;
+pub use alias::{AliasReport, insert_crate_aliases};
+pub use conflicts::{Conflict, EditOutcome, edit_conflicts};
+pub use env::{update_artifact_env_vars, update_env_prefix, update_links_env_vars};
+pub use ffi::update_ffi;
+pub use hooks::{HookConfig, HookContext, run_hooks};
+pub use html::HtmlPolicy;
+pub use log_targets::{LogTargetEdit, update_log_targets};
+pub use markdown::{DocOptions, DocsCategory, DocsScope};
+pub use rust::{
+    CompatShim, Reference, SourceOptions, SourceRule, find_references, migrate_sources,
+    update_dependency_key, update_source_code, update_workspace_docs,
+};
+pub use ssr::{SsrPlan, ssr_rules};
+pub use tools::{
+    add_changelog_entry, find_tool_configs, update_release_metadata, update_tool_config_paths,
+    update_tool_configs,
+};
+pub use trace::{TracedEdit, load_trace, replay_edits, write_trace};
@@ -22,68 +22,400 @@
 //! #[old_crate::attribute]           // Attributes
 //! /// See [`old_crate::Type`]       // Doc links
 //! extern crate old_crate;           // 2015 edition
+//! pub(in old_crate::internal) fn f() // Visibility paths
+//! #[deprecated(note = "use old_crate::v2")] // Paths in string literals
 //! ```
+//!
+//! `crate::`, `self::` and `super::` paths, including `pub(in crate::internal)`,
+//! only change where they continue through `old_crate::`.
+//!
+//! Patterns are not aware of string literals, so paths in deprecation notes and
+//! messages follow the rename. Only `old_crate::` counts as a path there; a
+//! bare `old_crate` in a string is kept.
+//!
+//! Each pattern is a named [`SourceRule`]. The rules that changed a file are
+//! recorded on the transaction, and individual rules can be disabled to
+//! track down false positives.
+//!
+//! ## Compatibility Shims
+//!
+//! Earlier renames often leave `pub use new_crate as old_crate;` re-exports
+//! behind. A re-export counts as such a shim only if its alias is one of the
+//! crate's former names (from the rename history), or if the line ends in a
+//! `// cargo-rename: shim` comment; any other `pub use old_crate as alias;`
+//! is an ordinary re-export. When the crate is renamed again, shims are
+//! rewritten like any other `use` (so the chain `a → b → c` keeps working)
+//! and reported as [`CompatShim`]s. With `drop_shims`, they are removed
+//! instead, together with the attributes and doc comments directly above them.
+//!
+//! ## Unparsable Files
+//!
+//! Files that `syn` cannot parse (often macro-heavy code) are skipped by
+//! default, since a broken file makes it hard to tell what a match means.
+//! With `force_unparsable`, they are rewritten with the same patterns and a
+//! warning is emitted for each.
 
+use crate::annotate;
+use crate::cargo::{DepKind, NameResolution, resolve_dependency_name, workspace_dependency_kinds};
 use crate::error::Result;
+use crate::fs::display_path;
 use crate::fs::transaction::Transaction;
+use crate::rewrite::conflicts::Conflict;
+use crate::rewrite::markdown::{DocOptions, DocsScope, rewrite_markdown};
+use crate::rewrite::trace::{TracedEdit, replace_all_traced};
+use crate::symbols;
+use crate::workspace_scan::{self, ExcludeGlobs, FileKind};
 use cargo_metadata::Metadata;
+use clap::ValueEnum;
 use regex::Regex;
-use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// A pattern used to rewrite references in Rust sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum SourceRule {
+    /// `use old_crate::...`
+    UseStatement,
+    /// `use ::old_crate;`
+    AbsoluteUse,
+    /// `extern crate old_crate;`
+    ExternCrate,
+    /// `pub(in old_crate::module)`
+    Visibility,
+    /// `old_crate::path`
+    QualifiedPath,
+    /// `::old_crate::path`
+    AbsolutePath,
+    /// `#[old_crate::attr]`, `#[derive(old_crate::Derive)]`
+    Attribute,
+    /// `#[old_crate(...)]`
+    AttributeArgs,
+    /// [`old_crate::Type`] in doc comments
+    DocLink,
+    /// `use old_crate::{self, ...}`
+    UseSelf,
+    /// `r#old_crate`
+    RawIdentifier,
+    /// `old_crate_something!`
+    Macro,
+}
+
+impl SourceRule {
+    /// Returns the rule's name as accepted by `--disable-rules`.
+    pub fn name(self) -> String {
+        self.to_possible_value()
+            .expect("no rule is skipped")
+            .get_name()
+            .to_string()
+    }
+}
+
+/// A `pub use old_crate as alias;` re-export of the renamed crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompatShim {
+    /// File containing the shim.
+    pub path: PathBuf,
+    /// 1-based line of the `pub use` in the original file.
+    pub line: usize,
+    /// Name the crate is re-exported as.
+    pub alias: String,
+    /// `true` if the shim was removed.
+    pub dropped: bool,
+}
+
+/// A reference to a crate found by [`find_references`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reference {
+    /// 1-based line.
+    pub line: usize,
+    /// 1-based column, in characters.
+    pub column: usize,
+    /// The rule that would rewrite it.
+    pub rule: SourceRule,
+}
+
+/// Finds the references to crate `name` that a rename of it would rewrite.
+///
+/// Used by `rename check` to flag names that have since been renamed. Each
+/// occurrence is reported once, with the first rule matching it.
+pub fn find_references(content: &str, name: &str) -> Result<Vec<Reference>> {
+    let snake = name.replace('-', "_");
+    let patterns = RenamePatterns::new(&snake, &snake, &[])?;
+    let mut found: Vec<(usize, SourceRule)> = Vec::new();
+
+    for (rule, pattern, _) in &patterns.replacements {
+        for m in pattern.find_iter(content) {
+            let Some(offset) = m.as_str().find(&snake).map(|at| m.start() + at) else {
+                continue;
+            };
+            if !found.iter().any(|(other, _)| *other == offset) {
+                found.push((offset, *rule));
+            }
+        }
+    }
+    found.sort();
+
+    Ok(found
+        .into_iter()
+        .map(|(offset, rule)| {
+            let before = &content[..offset];
+            let line_start = before.rfind('\n').map_or(0, |at| at + 1);
+            Reference {
+                line: before.matches('\n').count() + 1,
+                column: before[line_start..].chars().count() + 1,
+                rule,
+            }
+        })
+        .collect())
+}
+
+/// How [`update_source_code`] rewrites the workspace.
+#[derive(Debug, Clone, Copy)]
+pub struct SourceOptions<'a> {
+    /// Documentation occurrences to rewrite.
+    pub docs: &'a DocOptions,
+    /// Remove the compatibility shims found.
+    pub drop_shims: bool,
+    /// Rewrite Rust files that do not parse.
+    pub force_unparsable: bool,
+    /// Rules that are not applied.
+    pub disabled_rules: &'a [SourceRule],
+    /// Globs of files to leave untouched.
+    pub exclude: &'a [String],
+    /// Dependency kinds whose sources are rewritten; empty for all.
+    pub kinds: &'a [DepKind],
+    /// Only rewrite the renamed package itself (see `--via-alias`).
+    pub package_only: bool,
+    /// Names the crate went by before, whose re-exports are shims.
+    pub former_names: &'a [String],
+}
 
 /// Updates source code references in workspace packages.
 ///
 /// Scans all `.rs` and `.md` files, applying regex replacements for the renamed crate.
+/// Markdown files are rewritten according to `options.docs`. Returns the
+/// compatibility shims found, which are removed if `drop_shims` is set. Rust
+/// files that do not parse are skipped unless `force_unparsable` is set. Rules
+/// in `disabled_rules` are not applied; the rules that changed each Rust file
+/// are recorded with [`Transaction::record_rules`]. Files matching an
+/// `exclude` glob, relative to the workspace root or their package, are
+/// left untouched. Members whose dependency of that name is another crate
+/// (crates.io `log` next to a workspace `log`) are skipped entirely.
+/// Documentation is only rewritten in the members selected by `docs.scope`.
+/// With non-empty `kinds`, other members only get the Rust files that can
+/// use a dependency of those kinds ([`DepKind::of_source`]), and members
+/// without such a dependency on the package are skipped. With `package_only`,
+/// only the renamed package itself is rewritten.
 pub fn update_source_code(
     metadata: &Metadata,
     old_name: &str,
     new_name: &str,
+    options: &SourceOptions,
     txn: &mut Transaction,
-) -> Result<()> {
+) -> Result<Vec<CompatShim>> {
     let old_snake = old_name.replace('-', "_");
     let new_snake = new_name.replace('-', "_");
-
-    let patterns = RenamePatterns::new(&old_snake, &new_snake)?;
-
-    for member in metadata.workspace_packages() {
+    let docs = options.docs;
+
+    let mut patterns = RenamePatterns::new(&old_snake, &new_snake, options.disabled_rules)?;
+    patterns.former_names = options
+        .former_names
+        .iter()
+        .map(|name| name.replace('-', "_"))
+        .collect();
+    let members = metadata.workspace_packages();
+    let excludes = ExcludeGlobs::new(
+        std::iter::once(metadata.workspace_root.as_std_path()).chain(
+            members
+                .iter()
+                .filter_map(|m| m.manifest_path.parent())
+                .map(|dir| dir.as_std_path()),
+        ),
+        options.exclude,
+    )?;
+    let package_dir = members
+        .iter()
+        .find(|m| m.name == old_name)
+        .and_then(|m| m.manifest_path.parent())
+        .map(|dir| dir.as_std_path().to_path_buf());
+    let mut shims = Vec::new();
+    let doc_dirs = DocDirs::new(&members, old_name, package_dir.as_deref(), docs.scope);
+
+    for member in &members {
         let pkg_root = member
             .manifest_path
             .parent()
             .expect("manifest path must have parent");
+        if options.package_only && member.name != old_name {
+            continue;
+        }
 
-        walk_package(pkg_root.as_std_path(), &patterns, txn)?;
+        // `old_crate::` names another crate here (e.g. crates.io `log`)
+        if let Some(dir) = &package_dir
+            && resolve_dependency_name(member, old_name, dir) == NameResolution::External
+        {
+            txn.record_skipped(
+                pkg_root.as_std_path().to_path_buf(),
+                &format!("'{}' is a different crate in {}", old_name, member.name),
+            );
+            continue;
+        }
+
+        // The renamed package itself is always rewritten in full
+        let source_kinds = if member.name == old_name {
+            &[]
+        } else {
+            options.kinds
+        };
+        if !source_kinds.is_empty()
+            && let Some(dir) = &package_dir
+            && !workspace_dependency_kinds(member, dir)
+                .iter()
+                .any(|kind| source_kinds.contains(kind))
+        {
+            log::debug!("Skipping {} (no dependency of --only-kinds)", member.name);
+            continue;
+        }
+
+        walk_package(
+            pkg_root.as_std_path(),
+            &patterns,
+            &SourceOptions {
+                kinds: source_kinds,
+                ..*options
+            },
+            &doc_dirs,
+            &excludes,
+            &mut shims,
+            txn,
+        )?;
+    }
+
+    Ok(shims)
+}
+
+/// Updates documentation files at the workspace root.
+///
+/// Walks the workspace root for `.md` files, skipping member package
+/// directories (those are covered by [`update_source_code`]).
+pub fn update_workspace_docs(
+    metadata: &Metadata,
+    old_name: &str,
+    new_name: &str,
+    docs: &DocOptions,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let old_snake = old_name.replace('-', "_");
+    let new_snake = new_name.replace('-', "_");
+
+    let patterns = RenamePatterns::new(&old_snake, &new_snake, &[])?;
+    let workspace_root = metadata.workspace_root.as_std_path();
+
+    let member_roots: Vec<PathBuf> = metadata
+        .workspace_packages()
+        .iter()
+        .filter_map(|p| p.manifest_path.parent())
+        .map(|p| p.as_std_path().to_path_buf())
+        .filter(|p| p != workspace_root)
+        .collect();
+
+    for file in workspace_scan::files_under(workspace_root) {
+        if file.kind == FileKind::Doc && !member_roots.iter().any(|m| file.path.starts_with(m)) {
+            update_doc_file(&file.path, &patterns, docs, txn)?;
+        }
     }
 
     Ok(())
 }
 
+/// Updates references to a renamed crate in a project outside the workspace.
+///
+/// Used by `migrate-imports`: Rust files under `root` are rewritten with the
+/// same rules as a rename, except `disabled_rules`. Files that fail to parse
+/// are skipped.
+pub fn migrate_sources(
+    root: &Path,
+    old_name: &str,
+    new_name: &str,
+    disabled_rules: &[SourceRule],
+    txn: &mut Transaction,
+) -> Result<()> {
+    let old_snake = old_name.replace('-', "_");
+    let new_snake = new_name.replace('-', "_");
+    let patterns = RenamePatterns::new(&old_snake, &new_snake, disabled_rules)?;
+
+    for file in workspace_scan::files_under(root) {
+        if file.kind == FileKind::Rust {
+            update_rust_file(&file.path, &patterns, false, false, txn)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Updates references to a dependency whose key changed in one package.
+///
+/// Used by `normalize-deps` when `alias = { package = "crate" }` becomes
+/// `crate = ...`: Rust files under `pkg_root` (excluding the nested package
+/// directories in `skip`) are rewritten from `old_key` to `new_key`.
+/// Documentation and crate-named macros are left alone, since they name the
+/// package rather than the key.
+pub fn update_dependency_key(
+    pkg_root: &Path,
+    skip: &[PathBuf],
+    old_key: &str,
+    new_key: &str,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let old_snake = old_key.replace('-', "_");
+    let new_snake = new_key.replace('-', "_");
+    let patterns = RenamePatterns::new(&old_snake, &new_snake, &[SourceRule::Macro])?;
+
+    for file in workspace_scan::files_under(pkg_root) {
+        if file.kind == FileKind::Rust && !skip.iter().any(|dir| file.path.starts_with(dir)) {
+            update_rust_file(&file.path, &patterns, false, false, txn)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Trailing comment that marks a re-export as a compatibility shim.
+pub const SHIM_MARKER: &str = "cargo-rename: shim";
+
 /// Compiled regex patterns for crate references.
 struct RenamePatterns {
     old_snake: String,
     new_snake: String,
-    replacements: Vec<(Regex, String)>,
+    occurrence: Regex,
+    shim: Regex,
+    /// Former names, in snake case, that mark a re-export as a shim.
+    former_names: Vec<String>,
+    replacements: Vec<(SourceRule, Regex, String)>,
 }
 
 impl RenamePatterns {
-    /// Compiles all patterns for the rename operation.
-    fn new(old_snake: &str, new_snake: &str) -> Result<Self> {
+    /// Compiles all patterns for the rename operation, except `disabled` ones.
+    fn new(old_snake: &str, new_snake: &str, disabled: &[SourceRule]) -> Result<Self> {
         let old_escaped = regex::escape(old_snake);
         let mut replacements = Vec::new();
 
         // 1. Use statements: use old_crate
         replacements.push((
+            SourceRule::UseStatement,
             Regex::new(&format!(r"\b(use\s+){old}(::|;|\s+as)", old = old_escaped))?,
             format!("${{1}}{new}${{2}}", new = new_snake),
         ));
 
         // 2. Absolute paths (2015/2018): ::old_crate
         replacements.push((
+            SourceRule::AbsoluteUse,
             Regex::new(&format!(r"\b(::{old})(::|;|\s+as)", old = old_escaped))?,
             format!("${{1}}{new}${{2}}", new = new_snake),
         ));
 
         // 3. Extern crate (2015): extern crate old_crate
         replacements.push((
+            SourceRule::ExternCrate,
             Regex::new(&format!(
                 r"\b(extern\s+crate\s+){old}(::|;|\s+as)",
                 old = old_escaped
@@ -91,111 +423,278 @@ impl RenamePatterns {
             format!("${{1}}{new}${{2}}", new = new_snake),
         ));
 
-        // 4. Qualified paths: old_crate::path
+        // 4. Visibility paths: pub(in old_crate::internal)
+        replacements.push((
+            SourceRule::Visibility,
+            Regex::new(&format!(
+                r"(\bpub\s*\(\s*in\s+(?:::)?){old}(::|\s*\))",
+                old = old_escaped
+            ))?,
+            format!("${{1}}{new}${{2}}", new = new_snake),
+        ));
+
+        // 5. Qualified paths: old_crate::path
         replacements.push((
+            SourceRule::QualifiedPath,
             Regex::new(&format!(r"\b{old}(::)", old = old_escaped))?,
             format!("{new}${{1}}", new = new_snake),
         ));
 
-        // 5. Absolute paths: ::old_crate::
+        // 6. Absolute paths: ::old_crate::
         replacements.push((
+            SourceRule::AbsolutePath,
             Regex::new(&format!(r"(::){old}(::)", old = old_escaped))?,
             format!("${{1}}{new}${{2}}", new = new_snake),
         ));
 
-        // 6. Attributes: #[old_crate::attr] or #[derive(old_crate::Derive)]
+        // 7. Attributes: #[old_crate::attr] or #[derive(old_crate::Derive)]
         replacements.push((
+            SourceRule::Attribute,
             Regex::new(&format!(r"(#\[(?:derive\()?){old}(::)", old = old_escaped))?,
             format!("${{1}}{new}${{2}}", new = new_snake),
         ));
 
-        // 7. Attributes with parens: #[old_crate(...)]
+        // 8. Attributes with parens: #[old_crate(...)]
         replacements.push((
+            SourceRule::AttributeArgs,
             Regex::new(&format!(r"(#\[){old}(\()", old = old_escaped))?,
             format!("${{1}}{new}${{2}}", new = new_snake),
         ));
 
-        // 8. Doc links: [`old_crate::Type`] or [`old_crate`]
+        // 9. Doc links: [`old_crate::Type`] or [`old_crate`]
         replacements.push((
+            SourceRule::DocLink,
             Regex::new(&format!(r"(`){old}([::`\]])", old = old_escaped))?,
             format!("${{1}}{new}${{2}}", new = new_snake),
         ));
 
-        // 9. Use with self: use old_crate::{self, ...}
+        // 10. Use with self: use old_crate::{self, ...}
         replacements.push((
+            SourceRule::UseSelf,
             Regex::new(&format!(r"\b(use\s+){old}(::self\b)", old = old_escaped))?,
             format!("${{1}}{new}${{2}}${{3}}", new = new_snake),
         ));
 
-        // 10. Raw identifiers: r#old_crate
+        // 11. Raw identifiers: r#old_crate
         replacements.push((
+            SourceRule::RawIdentifier,
             Regex::new(&format!(r"\br#{old}\b", old = old_escaped))?,
             format!("r#{new}", new = new_snake),
         ));
 
-        // 11. Crate-specific macros: old_crate_something!
+        // 12. Crate-specific macros: old_crate_something!
         replacements.push((
+            SourceRule::Macro,
             Regex::new(&format!(r"\b{old}([a-z_][a-z0-9_]*)!", old = old_escaped))?,
             format!("{new}${{1}}", new = new_snake),
         ));
 
+        replacements.retain(|(rule, _, _)| !disabled.contains(rule));
+
         Ok(Self {
             old_snake: old_snake.to_string(),
             new_snake: new_snake.to_string(),
+            occurrence: Regex::new(&format!(r"\b{old}\b", old = old_escaped))?,
+            shim: Regex::new(&format!(
+                r"^\s*pub\s+use\s+(?:::)?{old}\s+as\s+(\w+)\s*;\s*(?://(.*))?\s*$",
+                old = old_escaped
+            ))?,
+            former_names: Vec::new(),
             replacements,
         })
     }
 
-    /// Applies all patterns to content.
+    /// Applies all patterns to the content of `path`.
     ///
-    /// Returns `Some(modified)` if any pattern matched, `None` otherwise.
-    fn apply(&self, content: &str) -> Option<String> {
+    /// Returns the modified content, the rules that matched and every
+    /// replacement made, or `None` if no pattern matched.
+    fn apply(
+        &self,
+        content: &str,
+        path: &Path,
+    ) -> Option<(String, Vec<SourceRule>, Vec<TracedEdit>)> {
         let mut result = content.to_string();
-        let mut changed = false;
+        let mut matched = Vec::new();
+        let mut edits = Vec::new();
 
-        for (pattern, replacement) in &self.replacements {
+        for (rule, pattern, replacement) in &self.replacements {
             if pattern.is_match(&result) {
-                result = pattern.replace_all(&result, replacement).to_string();
-                changed = true;
+                let (replaced, rule_edits) =
+                    replace_all_traced(pattern, replacement, &result, path, &rule.name());
+                result = replaced;
+                edits.extend(rule_edits);
+                matched.push(*rule);
             }
         }
 
-        if changed { Some(result) } else { None }
+        if matched.is_empty() {
+            None
+        } else {
+            Some((result, matched, edits))
+        }
+    }
+
+    /// Finds compatibility shims in `content`, removing them if `drop` is set.
+    ///
+    /// Only `pub use old_crate as alias;` lines aliasing a former name or
+    /// marked with [`SHIM_MARKER`] are shims. Returns the content to rewrite
+    /// and the `(line, alias)` of every shim.
+    fn find_shims(&self, content: &str, drop: bool) -> (String, Vec<(usize, String)>) {
+        let mut kept: Vec<&str> = Vec::new();
+        let mut shims = Vec::new();
+
+        for (idx, line) in content.split_inclusive('\n').enumerate() {
+            let Some(caps) = self.shim.captures(line).filter(|caps| {
+                self.former_names.iter().any(|name| *name == caps[1])
+                    || caps
+                        .get(2)
+                        .is_some_and(|comment| comment.as_str().trim() == SHIM_MARKER)
+            }) else {
+                kept.push(line);
+                continue;
+            };
+            shims.push((idx + 1, caps[1].to_string()));
+
+            if !drop {
+                kept.push(line);
+                continue;
+            }
+            // Attributes and docs belong to the removed item
+            while kept.last().is_some_and(|prev| {
+                let prev = prev.trim_start();
+                prev.starts_with("#[") || prev.starts_with("///")
+            }) {
+                kept.pop();
+            }
+        }
+
+        (kept.concat(), shims)
+    }
+
+    /// Returns each line with a whole-word occurrence of the old name
+    /// (1-based), rewritten on its own.
+    fn suggestions(&self, content: &str, path: &Path) -> Vec<(usize, String)> {
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| self.occurrence.is_match(line))
+            .map(|(idx, line)| {
+                let rewritten = self
+                    .apply(line, path)
+                    .map_or_else(|| line.to_string(), |(line, _, _)| line);
+                (idx + 1, rewritten)
+            })
+            .collect()
+    }
+
+    /// Returns the 1-based line of the first whole-word occurrence of the old name.
+    fn first_occurrence_line(&self, content: &str) -> Option<usize> {
+        content
+            .lines()
+            .position(|line| self.occurrence.is_match(line))
+            .map(|idx| idx + 1)
     }
 }
 
-/// Recursively walks a package directory, processing source files.
-fn walk_package(root: &Path, patterns: &RenamePatterns, txn: &mut Transaction) -> Result<()> {
-    let walker = ignore::WalkBuilder::new(root)
-        .hidden(true)
-        .git_ignore(true)
-        .git_exclude(true)
-        .git_global(true)
-        .filter_entry(|e| {
-            let name = e.file_name().to_str();
-            // Skip target and .git directories
-            !(name == Some("target") || name == Some(".git"))
-        })
-        .build();
+/// Member directories whose documentation is in the docs scope.
+struct DocDirs {
+    members: Vec<PathBuf>,
+    in_scope: Vec<PathBuf>,
+}
 
-    for entry in walker {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(e) => {
-                log::debug!("Skipping entry due to error: {}", e);
+impl DocDirs {
+    /// Selects the members in `scope` for a rename of the package in `package_dir`.
+    fn new(
+        members: &[&cargo_metadata::Package],
+        old_name: &str,
+        package_dir: Option<&Path>,
+        scope: DocsScope,
+    ) -> Self {
+        let mut dirs = Vec::new();
+        let mut in_scope = Vec::new();
+
+        for member in members {
+            let Some(dir) = member.manifest_path.parent().map(|d| d.as_std_path()) else {
                 continue;
+            };
+            let selected = match (scope, package_dir) {
+                (DocsScope::Workspace, _) => true,
+                (_, Some(package_dir)) if dir == package_dir => true,
+                (DocsScope::Dependents, Some(package_dir)) => matches!(
+                    resolve_dependency_name(member, old_name, package_dir),
+                    NameResolution::Workspace | NameResolution::Ambiguous
+                ),
+                _ => false,
+            };
+
+            dirs.push(dir.to_path_buf());
+            if selected {
+                in_scope.push(dir.to_path_buf());
             }
-        };
+        }
 
-        // Process only regular files
-        if !entry.file_type().is_some_and(|ft| ft.is_file()) {
-            continue;
+        Self {
+            members: dirs,
+            in_scope,
         }
+    }
 
-        let path = entry.path();
-        match path.extension().and_then(|s| s.to_str()) {
-            Some("rs") => update_rust_file(path, patterns, txn)?,
-            Some("md") => update_doc_file(path, patterns, txn)?,
+    /// Returns `true` if the innermost member containing `file` is in scope.
+    fn includes(&self, file: &Path) -> bool {
+        self.members
+            .iter()
+            .filter(|dir| file.starts_with(dir))
+            .max_by_key(|dir| dir.components().count())
+            .is_some_and(|owner| self.in_scope.contains(owner))
+    }
+}
+
+/// Processes the source and documentation files of a package directory.
+fn walk_package(
+    root: &Path,
+    patterns: &RenamePatterns,
+    options: &SourceOptions,
+    doc_dirs: &DocDirs,
+    excludes: &ExcludeGlobs,
+    shims: &mut Vec<CompatShim>,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let SourceOptions {
+        docs,
+        drop_shims,
+        force_unparsable,
+        kinds,
+        ..
+    } = *options;
+    for file in workspace_scan::files_under(root) {
+        if excludes.is_excluded(&file.path) {
+            log::debug!("Excluded: {}", display_path(&file.path));
+            continue;
+        }
+        match file.kind {
+            FileKind::Rust
+                if !kinds.is_empty()
+                    && !kinds.contains(&DepKind::of_source(
+                        file.path.strip_prefix(root).unwrap_or(&file.path),
+                    )) =>
+            {
+                log::debug!("Outside --only-kinds: {}", display_path(&file.path))
+            }
+            FileKind::Rust => shims.extend(update_rust_file(
+                &file.path,
+                patterns,
+                drop_shims,
+                force_unparsable,
+                txn,
+            )?),
+            FileKind::Doc if doc_dirs.includes(&file.path) => {
+                update_doc_file(&file.path, patterns, docs, txn)?
+            }
+            FileKind::Doc => log::debug!(
+                "Outside --docs-scope {}: {}",
+                docs.scope.name(),
+                display_path(&file.path)
+            ),
             _ => {}
         }
     }
@@ -203,55 +702,116 @@ fn walk_package(root: &Path, patterns: &RenamePatterns, txn: &mut Transaction) -
     Ok(())
 }
 
-/// Updates a single Rust source file.
-fn update_rust_file(path: &Path, patterns: &RenamePatterns, txn: &mut Transaction) -> Result<()> {
-    let content = match fs::read_to_string(path) {
-        Ok(c) => c,
-        Err(e) => {
-            log::debug!("Skipping file (read error): {} - {}", path.display(), e);
-            return Ok(());
-        }
+/// Updates a single Rust source file and returns the shims found in it.
+fn update_rust_file(
+    path: &Path,
+    patterns: &RenamePatterns,
+    drop_shims: bool,
+    force_unparsable: bool,
+    txn: &mut Transaction,
+) -> Result<Vec<CompatShim>> {
+    let Some(content) = txn.read_text(path) else {
+        return Ok(Vec::new());
     };
 
     if syn::parse_file(&content).is_err() {
-        log::debug!("Skipping file (invalid syntax): {}", path.display());
-        return Ok(());
+        let Some(line) = patterns.first_occurrence_line(&content) else {
+            log::debug!("Skipping file (invalid syntax): {}", display_path(path));
+            return Ok(Vec::new());
+        };
+
+        if !force_unparsable {
+            log::debug!("Skipping file (invalid syntax): {}", display_path(path));
+            txn.record_conflict(Conflict {
+                path: path.to_path_buf(),
+                reason: "invalid syntax".to_string(),
+                suggestions: patterns.suggestions(&content, path),
+            });
+            annotate::warning(
+                Some(path),
+                Some(line),
+                &format!(
+                    "Skipped (invalid syntax): references to '{}' were not updated; \
+                     pass --force-rewrite-unparsable to rewrite them anyway",
+                    patterns.old_snake
+                ),
+            );
+            return Ok(Vec::new());
+        }
+
+        log::warn!(
+            "Rewriting {} without a syntax check (file does not parse)",
+            display_path(path)
+        );
+        annotate::warning(
+            Some(path),
+            Some(line),
+            "Rewritten without a syntax check (invalid syntax); review the changes",
+        );
     }
 
-    if let Some(new_content) = patterns.apply(&content) {
+    let (stripped, shims) = patterns.find_shims(&content, drop_shims);
+    let (new_content, rules, edits) =
+        patterns
+            .apply(&stripped, path)
+            .unwrap_or((stripped, Vec::new(), Vec::new()));
+
+    if new_content != content {
+        let rules: Vec<String> = rules.into_iter().map(SourceRule::name).collect();
         txn.update_file(path.to_path_buf(), new_content)?;
-        log::debug!("Updated Rust file: {}", path.display());
+        log::debug!(
+            "Updated Rust file: {} [{}]",
+            display_path(path),
+            rules.join(", ")
+        );
+        for edit in &edits {
+            log::trace!(
+                "{}:{} [{}] {:?} {} {:?}",
+                display_path(path),
+                edit.start,
+                edit.rule,
+                edit.before,
+                symbols::arrow(),
+                edit.after
+            );
+        }
+        txn.record_rules(path.to_path_buf(), rules);
+        txn.record_edits(edits);
     }
 
-    Ok(())
+    Ok(shims
+        .into_iter()
+        .map(|(line, alias)| CompatShim {
+            path: path.to_path_buf(),
+            line,
+            alias,
+            dropped: drop_shims,
+        })
+        .collect())
 }
 
 /// Updates a documentation file (.md or .txt).
 ///
-/// Replaces kebab-case crate names (for Markdown/docs).
-fn update_doc_file(path: &Path, patterns: &RenamePatterns, txn: &mut Transaction) -> Result<()> {
-    let content = match fs::read_to_string(path) {
-        Ok(c) => c,
-        Err(e) => {
-            log::debug!("Skipping file (read error): {} - {}", path.display(), e);
-            return Ok(());
-        }
+/// Replaces kebab-case crate names in the categories selected by `docs`.
+fn update_doc_file(
+    path: &Path,
+    patterns: &RenamePatterns,
+    docs: &DocOptions,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let Some(content) = txn.read_text(path) else {
+        return Ok(());
     };
 
     // Convert snake_case to kebab-case for Markdown
     let old_kebab = patterns.old_snake.replace('_', "-");
     let new_kebab = patterns.new_snake.replace('_', "-");
 
-    // Match whole words only
-    let doc_pattern = Regex::new(&format!(r"\b{}\b", regex::escape(&old_kebab)))?;
-
-    if doc_pattern.is_match(&content) {
-        let new_content = doc_pattern.replace_all(&content, &new_kebab).into_owned();
+    let new_content = rewrite_markdown(&content, &old_kebab, &new_kebab, docs)?;
 
-        if new_content != content {
-            txn.update_file(path.to_path_buf(), new_content)?;
-            log::debug!("Updated doc file: {}", path.display());
-        }
+    if new_content != content {
+        txn.update_file(path.to_path_buf(), new_content)?;
+        log::debug!("Updated doc file: {}", display_path(path));
     }
 
     Ok(())
@@ -0,0 +1,259 @@
+//! rust-analyzer structural search-replace (SSR) rules for `--emit-ssr`.
+//!
+//! Instead of rewriting Rust sources, cargo-rename can print one SSR rule per
+//! distinct path into the renamed crate:
+//!
+//! ```text
+//! old_crate::parser::Parser ==>> new_crate::parser::Parser
+//! old_crate::parse ==>> new_crate::parse
+//! ```
+//!
+//! Use groups are expanded (`use old_crate::{a, b::C}` yields rules for
+//! `old_crate::a` and `old_crate::b::C`). References SSR cannot express, such
+//! as `extern crate old_crate;`, `use old_crate as alias;` or crate-named
+//! macros (`old_crate_log!`), are returned as [`ManualEdit`]s instead.
+
+use crate::error::Result;
+use crate::fs::display_path;
+use crate::workspace_scan::{self, FileKind};
+use cargo_metadata::Metadata;
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+
+/// A reference to the old crate that needs a manual edit.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ManualEdit {
+    pub path: PathBuf,
+    /// 1-based line of the reference.
+    pub line: usize,
+}
+
+/// SSR rules and manual edits for a crate rename.
+#[derive(Debug, Default)]
+pub struct SsrPlan {
+    pub rules: Vec<String>,
+    pub manual: Vec<ManualEdit>,
+}
+
+/// Collects SSR rules for the Rust sources of all workspace packages.
+///
+/// Files that fail to parse are skipped, like in [`update_source_code`].
+///
+/// [`update_source_code`]: crate::rewrite::update_source_code
+pub fn ssr_rules(metadata: &Metadata, old_name: &str, new_name: &str) -> Result<SsrPlan> {
+    let old_snake = old_name.replace('-', "_");
+    let new_snake = new_name.replace('-', "_");
+    let escaped = regex::escape(&old_snake);
+    let occurrence = Regex::new(&format!(r"\b{}\b", escaped))?;
+    let crate_macro = Regex::new(&format!(r"\b{}[a-z_][a-z0-9_]*!", escaped))?;
+
+    let mut paths = BTreeSet::new();
+    let mut manual = BTreeSet::new();
+
+    for member in metadata.workspace_packages() {
+        let root = member.manifest_path.parent().unwrap().as_std_path();
+        for file in workspace_scan::files_under(root) {
+            if file.kind != FileKind::Rust {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(&file.path) else {
+                continue;
+            };
+            if syn::parse_file(&content).is_err() {
+                log::debug!(
+                    "Skipping file (invalid syntax): {}",
+                    display_path(&file.path)
+                );
+                continue;
+            }
+
+            for line in collect_paths(&content, &occurrence, &crate_macro, &mut paths) {
+                manual.insert(ManualEdit {
+                    path: file.path.clone(),
+                    line,
+                });
+            }
+        }
+    }
+
+    Ok(SsrPlan {
+        rules: paths
+            .into_iter()
+            .map(|path| {
+                format!(
+                    "{old}::{path} ==>> {new}::{path}",
+                    old = old_snake,
+                    new = new_snake
+                )
+            })
+            .collect(),
+        manual: manual.into_iter().collect(),
+    })
+}
+
+/// Adds the crate-relative paths referenced in `content` to `paths`.
+///
+/// Returns the lines of references that are not paths into the crate.
+fn collect_paths(
+    content: &str,
+    occurrence: &Regex,
+    crate_macro: &Regex,
+    paths: &mut BTreeSet<String>,
+) -> Vec<usize> {
+    let line_of = |offset: usize| content[..offset].matches('\n').count() + 1;
+    let mut manual = Vec::new();
+
+    for m in occurrence.find_iter(content) {
+        let found = match content[m.end()..].strip_prefix("::") {
+            Some(rest) => expand(use_tree(rest)),
+            None => Vec::new(),
+        };
+        // The crate itself (`use old_crate::{self}`, `old_crate::*`) is not a path SSR can match
+        if found.is_empty() || found.iter().any(String::is_empty) {
+            manual.push(line_of(m.start()));
+        }
+        paths.extend(found.into_iter().filter(|p| !p.is_empty()));
+    }
+    for m in crate_macro.find_iter(content) {
+        manual.push(line_of(m.start()));
+    }
+
+    manual.sort_unstable();
+    manual.dedup();
+    manual
+}
+
+/// Returns the path or use tree at the start of `text`.
+///
+/// Stops at the first character that cannot continue a path outside braces,
+/// and before generic arguments (`::<`).
+fn use_tree(text: &str) -> &str {
+    let mut depth = 0;
+
+    for (idx, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return &text[..idx],
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return &text[..idx + 1];
+                }
+            }
+            _ if depth > 0 => {}
+            ':' if text[idx..].starts_with("::<") => return &text[..idx],
+            c if c.is_alphanumeric() || c == '_' || c == ':' || c == '*' || c == '#' => {}
+            _ => return &text[..idx],
+        }
+    }
+
+    if depth == 0 { text } else { "" }
+}
+
+/// Expands a use tree relative to the crate into full paths.
+///
+/// `self` and globs expand to the path leading up to them, so the empty path
+/// stands for the crate itself. Paths that end in `::` (a truncated match)
+/// expand to nothing.
+fn expand(tree: &str) -> Vec<String> {
+    let tree = tree.trim();
+
+    if let Some(inner) = tree.strip_prefix('{').and_then(|t| t.strip_suffix('}')) {
+        return split_top_level(inner)
+            .into_iter()
+            .flat_map(expand)
+            .collect();
+    }
+
+    // Drop a trailing `as alias`
+    let tree = find_top_level(tree, " as ").map_or(tree, |idx| &tree[..idx]);
+
+    let (head, rest) = match find_top_level(tree, "::") {
+        Some(idx) => (&tree[..idx], Some(&tree[idx + 2..])),
+        None => (tree, None),
+    };
+    let head = head.strip_prefix("r#").unwrap_or(head);
+
+    match (head, rest) {
+        ("self" | "*", None) => vec![String::new()],
+        ("", _) | (_, Some("")) => Vec::new(),
+        (head, None) => vec![head.to_string()],
+        (head, Some(rest)) => expand(rest)
+            .into_iter()
+            .map(|tail| {
+                if tail.is_empty() {
+                    head.to_string()
+                } else {
+                    format!("{}::{}", head, tail)
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Splits `text` at commas outside braces, dropping empty items.
+fn split_top_level(text: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut rest = text;
+
+    while let Some(idx) = find_top_level(rest, ",") {
+        items.push(&rest[..idx]);
+        rest = &rest[idx + 1..];
+    }
+    items.push(rest);
+
+    items.into_iter().filter(|i| !i.trim().is_empty()).collect()
+}
+
+/// Finds `needle` in `text` outside braces.
+fn find_top_level(text: &str, needle: &str) -> Option<usize> {
+    let mut depth = 0;
+
+    for (idx, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ if depth == 0 && text[idx..].starts_with(needle) => return Some(idx),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_paths() {
+        let content = r#"extern crate old_crate;
+use old_crate::{self, parser::{Parser, Error as ParseError}, prelude::*};
+use old_crate as oc;
+
+fn main() {
+    let v: Vec<old_crate::Token> = old_crate::lex::tokens::<u8>("x");
+    old_crate_log!("done");
+}
+"#;
+        let occurrence = Regex::new(r"\bold_crate\b").unwrap();
+        let crate_macro = Regex::new(r"\bold_crate[a-z_][a-z0-9_]*!").unwrap();
+        let mut paths = BTreeSet::new();
+
+        let manual = collect_paths(content, &occurrence, &crate_macro, &mut paths);
+
+        assert_eq!(
+            paths.into_iter().collect::<Vec<_>>(),
+            vec![
+                "Token",
+                "lex::tokens",
+                "parser::Error",
+                "parser::Parser",
+                "prelude",
+            ]
+        );
+        assert_eq!(manual, vec![1, 2, 3, 7]);
+    }
+}
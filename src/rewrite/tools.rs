@@ -0,0 +1,634 @@
+//! Tool configuration rewriting for crate renames.
+//!
+//! Supply-chain, release and test tooling lists crates by name or path:
+//!
+//! - **`deny.toml`**: license exceptions, `bans.skip`, `bans.deny`
+//! - **`about.toml`**: per-crate clarifications
+//! - **`release.toml`**, **`release-plz.toml`**: per-package release
+//!   configuration and tag templates
+//! - **`.config/nextest.toml`**: filter expressions (`package(old-crate)`)
+//! - **`.cargo/config.toml`**: `[patch]` entries and `-p old-crate` in
+//!   `[alias]` commands
+//! - **`codecov.yml`**, **`tarpaulin.toml`**: flags, components and path lists
+//!
+//! These files are looked up in the workspace root and every member directory.
+//! Additional files can be passed with `--tool-config`. Only exact names are
+//! replaced: quoted strings (`"old-crate"`), keys (`[old-crate]`,
+//! `old-crate = ...`, YAML `old-crate:`), YAML scalars (`name: old-crate`),
+//! `-p`/`--package` arguments and nextest `package()`, `deps()`, `rdeps()` and
+//! `binary_id()` terms. When the package moves, paths to its directory are
+//! rewritten too, relative to the file (or, for `.cargo/config.toml`, to the
+//! directory containing `.cargo`). Comment lines are left untouched.
+//!
+//! Tag templates (`tag_name = "old-crate-v{{ version }}"`, `tag-prefix`,
+//! `git_release_name`) are updated where they start with the crate name, in
+//! these files and in the `[workspace.metadata.workspaces]` and
+//! `[*.metadata.release]` tables read by cargo-workspaces and cargo-release.
+//! [`add_changelog_entry`] notes the rename in the package's `CHANGELOG.md`.
+
+use crate::error::Result;
+use crate::fs::display_path;
+use crate::fs::transaction::Transaction;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Tool config files scanned by default, relative to each scanned directory.
+pub const KNOWN_TOOL_CONFIGS: &[&str] = &[
+    "deny.toml",
+    "about.toml",
+    "release.toml",
+    "release-plz.toml",
+    ".release-plz.toml",
+    ".config/nextest.toml",
+    ".cargo/config.toml",
+    ".cargo/config",
+    "tarpaulin.toml",
+    ".tarpaulin.toml",
+    "codecov.yml",
+    ".codecov.yml",
+];
+
+/// Returns the tool config files that exist under `dirs`, plus `extra` files.
+pub fn find_tool_configs(dirs: &[PathBuf], extra: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = dirs
+        .iter()
+        .flat_map(|dir| KNOWN_TOOL_CONFIGS.iter().map(move |name| dir.join(name)))
+        .chain(extra.iter().cloned())
+        .filter(|path| path.is_file())
+        .collect();
+
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// Replaces exact occurrences of `old_name` in tool config files.
+pub fn update_tool_configs(
+    files: &[PathBuf],
+    old_name: &str,
+    new_name: &str,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let patterns = NamePatterns::new(old_name, new_name)?;
+
+    for path in files {
+        let is_yaml = matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("yml" | "yaml")
+        );
+        rewrite_lines(path, txn, |line| patterns.apply(line, is_yaml))?;
+    }
+
+    Ok(())
+}
+
+/// Updates tag templates in the release tool tables of Cargo manifests.
+///
+/// Only lines in `[workspace.metadata.workspaces]`, `[package.metadata.workspaces]`
+/// and the corresponding `metadata.release` tables are changed.
+pub fn update_release_metadata(
+    manifests: &[PathBuf],
+    old_name: &str,
+    new_name: &str,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let patterns = NamePatterns::new(old_name, new_name)?;
+    let release_table =
+        Regex::new(r"^\s*\[\s*(?:workspace|package)\.metadata\.(?:workspaces|release)\s*\]")?;
+
+    for path in manifests {
+        let mut in_release_table = false;
+        rewrite_lines(path, txn, |line| {
+            if line.trim_start().starts_with('[') {
+                in_release_table = release_table.is_match(line);
+                return line.to_string();
+            }
+            if in_release_table {
+                patterns.apply_tag(line).into_owned()
+            } else {
+                line.to_string()
+            }
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Adds a "Renamed from" entry under the Unreleased section of the
+/// `CHANGELOG.md` in `package_dir`.
+///
+/// The entry goes below an existing `### Changed` heading of the section, or
+/// directly below the `## [Unreleased]` heading, which is added above the
+/// first release if missing. Returns the changelog, or `None` if there is
+/// none.
+pub fn add_changelog_entry(
+    package_dir: &Path,
+    old_name: &str,
+    new_name: &str,
+    txn: &mut Transaction,
+) -> Result<Option<PathBuf>> {
+    let path = package_dir.join("CHANGELOG.md");
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let Some(content) = txn.read_text(&path) else {
+        return Ok(None);
+    };
+    let entry = format!(
+        "- Renamed the package from `{}` to `{}`.\n",
+        old_name, new_name
+    );
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let is_release = |line: &str| line.starts_with("## ");
+    let unreleased = lines
+        .iter()
+        .position(|line| is_release(line) && line.to_ascii_lowercase().contains("unreleased"));
+
+    let (at, insert) = match unreleased {
+        Some(heading) => {
+            let end = lines[heading + 1..]
+                .iter()
+                .position(|line| is_release(line))
+                .map_or(lines.len(), |offset| heading + 1 + offset);
+            let changed = lines[heading + 1..end]
+                .iter()
+                .position(|line| line.trim().eq_ignore_ascii_case("### Changed"))
+                .map(|offset| heading + 1 + offset);
+            let below = changed.unwrap_or(heading) + 1;
+            if lines.get(below).is_some_and(|line| line.trim().is_empty()) {
+                (below + 1, entry)
+            } else {
+                (below, format!("\n{}", entry))
+            }
+        }
+        None => match lines.iter().position(|line| is_release(line)) {
+            Some(first) => (first, format!("## [Unreleased]\n\n{}\n", entry)),
+            None => (
+                lines.len(),
+                format!(
+                    "{}## [Unreleased]\n\n{}",
+                    if content.is_empty() || content.ends_with("\n\n") {
+                        ""
+                    } else if content.ends_with('\n') {
+                        "\n"
+                    } else {
+                        "\n\n"
+                    },
+                    entry
+                ),
+            ),
+        },
+    };
+
+    let mut new_content: String = lines[..at].concat();
+    new_content.push_str(&insert);
+    new_content.push_str(&lines[at..].concat());
+    txn.update_file(path.clone(), new_content)?;
+    log::debug!("Added rename entry to {}", display_path(&path));
+
+    Ok(Some(path))
+}
+
+/// Rewrites paths to a moved package directory in tool config files.
+///
+/// Paths are matched relative to each config file's directory, as whole path
+/// prefixes (`crates/old-crate`, `./crates/old-crate/**`), never as substrings
+/// of longer paths or names.
+pub fn update_tool_config_paths(
+    files: &[PathBuf],
+    old_dir: &Path,
+    new_dir: &Path,
+    txn: &mut Transaction,
+) -> Result<()> {
+    for path in files {
+        let Some(config_dir) = config_base(path) else {
+            continue;
+        };
+        let (Some(old_rel), Some(new_rel)) = (
+            relative_path(old_dir, config_dir),
+            relative_path(new_dir, config_dir),
+        ) else {
+            continue;
+        };
+        // Configs inside the moved package refer to it as "." and are unaffected
+        if old_rel.is_empty() || old_rel.starts_with("..") {
+            continue;
+        }
+
+        let pattern = Regex::new(&format!(
+            r#"(^|[\s"'\[,=])(\./)?{}(/|["'\s,\]]|$)"#,
+            regex::escape(&old_rel)
+        ))?;
+        let replacement = format!("${{1}}${{2}}{}${{3}}", new_rel);
+
+        rewrite_lines(path, txn, |line| {
+            pattern.replace_all(line, replacement.as_str()).into_owned()
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Applies `rewrite` to every non-comment line of a file and stages the result.
+fn rewrite_lines(
+    path: &Path,
+    txn: &mut Transaction,
+    mut rewrite: impl FnMut(&str) -> String,
+) -> Result<()> {
+    let Some(content) = txn.read_text(path) else {
+        return Ok(());
+    };
+
+    let new_content = content
+        .split_inclusive('\n')
+        .map(|line| {
+            if line.trim_start().starts_with('#') {
+                line.to_string()
+            } else {
+                rewrite(line)
+            }
+        })
+        .collect::<String>();
+
+    if new_content != content {
+        txn.update_file(path.to_path_buf(), new_content)?;
+        log::debug!("Updated tool config: {}", display_path(path));
+    }
+
+    Ok(())
+}
+
+/// Returns the directory paths in a config file are relative to.
+///
+/// Cargo resolves paths in `.cargo/config.toml` against the parent of the
+/// `.cargo` directory; other tools against the file's own directory.
+fn config_base(path: &Path) -> Option<&Path> {
+    let dir = path.parent()?;
+    if dir.file_name().is_some_and(|name| name == ".cargo") {
+        dir.parent()
+    } else {
+        Some(dir)
+    }
+}
+
+fn relative_path(path: &Path, base: &Path) -> Option<String> {
+    pathdiff::diff_paths(path, base).map(|p| p.to_string_lossy().replace('\\', "/"))
+}
+
+/// Compiled patterns for exact-name replacements.
+struct NamePatterns {
+    new_name: String,
+    quoted: Regex,
+    key: Regex,
+    filter: Regex,
+    yaml_key: Regex,
+    yaml_scalar: Regex,
+    tag: Regex,
+    package_arg: Regex,
+}
+
+impl NamePatterns {
+    fn new(old_name: &str, new_name: &str) -> Result<Self> {
+        let escaped = regex::escape(old_name);
+
+        Ok(Self {
+            new_name: new_name.to_string(),
+            quoted: Regex::new(&format!(r#"(["']){}(["'])"#, escaped))?,
+            key: Regex::new(&format!(r"(^\s*|[\[.{{,]\s*){}(\s*[=.\]])", escaped))?,
+            // nextest filterset: package(old-crate), rdeps(=old-crate), binary_id(old-crate::bin/x)
+            filter: Regex::new(&format!(
+                r"\b((?:package|deps|rdeps|binary_id)\(\s*=?){}(\s*\)|::)",
+                escaped
+            ))?,
+            yaml_key: Regex::new(&format!(r"^(\s*(?:-\s+)?){}(\s*:)", escaped))?,
+            yaml_scalar: Regex::new(&format!(
+                r"^(\s*(?:-\s+(?:[\w-]+:\s+)?|[\w-]+:\s+)){}(\s*)$",
+                escaped
+            ))?,
+            // alias: "test -p old-crate", ["run", "--package=old-crate"]
+            package_arg: Regex::new(&format!(
+                r#"((?:^|[\s"'\[,])(?:-p|--package)(?:\s+|=|["']\s*,\s*["'])){}([\s"',\]]|$)"#,
+                escaped
+            ))?,
+            // tag_name = "old-crate-v{{ version }}", tag-prefix = "old-crate-"
+            tag: Regex::new(&format!(
+                r#"^(\s*(?:(?:git[-_])?(?:tag|release)[-_](?:name|prefix)|individual[-_]tag[-_]prefix)\s*=\s*["']){}(-v|-\{{|-?["']|[@/{{]|_v)"#,
+                escaped
+            ))?,
+        })
+    }
+
+    fn apply(&self, line: &str, is_yaml: bool) -> String {
+        let new_name = self.new_name.as_str();
+
+        let line = self.quoted.replace_all(line, |caps: &regex::Captures| {
+            if caps[1] == caps[2] {
+                format!("{}{}{}", &caps[1], new_name, &caps[2])
+            } else {
+                caps[0].to_string()
+            }
+        });
+        let line = self
+            .filter
+            .replace_all(&line, format!("${{1}}{}${{2}}", new_name));
+        let line = self
+            .package_arg
+            .replace_all(&line, format!("${{1}}{}${{2}}", new_name));
+
+        if is_yaml {
+            let line = self
+                .yaml_key
+                .replace_all(&line, format!("${{1}}{}${{2}}", new_name));
+            self.yaml_scalar
+                .replace_all(&line, format!("${{1}}{}${{2}}", new_name))
+                .into_owned()
+        } else {
+            let line = self.apply_tag(&line).into_owned();
+            self.key
+                .replace_all(&line, format!("${{1}}{}${{2}}", new_name))
+                .into_owned()
+        }
+    }
+
+    /// Renames the crate at the start of a tag template value.
+    fn apply_tag<'a>(&self, line: &'a str) -> std::borrow::Cow<'a, str> {
+        self.tag
+            .replace(line, format!("${{1}}{}${{2}}", self.new_name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_updates_exact_names_only() {
+        let temp = TempDir::new().unwrap();
+        let deny = temp.path().join("deny.toml");
+        fs::write(
+            &deny,
+            r#"# old-crate is vendored
+[bans]
+skip = [{ name = "old-crate" }, { name = "old-crate-derive" }]
+
+[[licenses.exceptions]]
+name = "old-crate"
+allow = ["MPL-2.0"]
+
+[old-crate]
+clarify = true
+"#,
+        )
+        .unwrap();
+
+        let files = find_tool_configs(&[temp.path().to_path_buf()], &[]);
+        assert_eq!(files, vec![deny.clone()]);
+
+        let mut txn = Transaction::new(false);
+        update_tool_configs(&files, "old-crate", "new-crate", &mut txn).unwrap();
+        txn.commit().unwrap();
+
+        let result = fs::read_to_string(&deny).unwrap();
+        assert!(result.starts_with("# old-crate is vendored"));
+        assert!(result.contains(r#"{ name = "new-crate" }, { name = "old-crate-derive" }"#));
+        assert!(result.contains("name = \"new-crate\"\nallow"));
+        assert!(result.contains("[new-crate]"));
+    }
+
+    #[test]
+    fn test_updates_nextest_filters() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join(".config")).unwrap();
+        let nextest = temp.path().join(".config/nextest.toml");
+        fs::write(
+            &nextest,
+            r#"[[profile.default.overrides]]
+filter = "package(old-crate) | rdeps(=old-crate) | package(old-crate-macros)"
+retries = 2
+
+[[profile.ci.overrides]]
+filter = "binary_id(old-crate::bin/cli) & test(old-crate)"
+"#,
+        )
+        .unwrap();
+
+        let files = find_tool_configs(&[temp.path().to_path_buf()], &[]);
+        assert_eq!(files, vec![nextest.clone()]);
+
+        let mut txn = Transaction::new(false);
+        update_tool_configs(&files, "old-crate", "new-crate", &mut txn).unwrap();
+        txn.commit().unwrap();
+
+        let result = fs::read_to_string(&nextest).unwrap();
+        assert!(result.contains(
+            r#"filter = "package(new-crate) | rdeps(=new-crate) | package(old-crate-macros)""#
+        ));
+        assert!(result.contains(r#"filter = "binary_id(new-crate::bin/cli) & test(old-crate)""#));
+    }
+
+    #[test]
+    fn test_updates_codecov_names_and_paths() {
+        let temp = TempDir::new().unwrap();
+        let codecov = temp.path().join("codecov.yml");
+        fs::write(
+            &codecov,
+            r#"flags:
+  old-crate:
+    paths:
+      - crates/old-crate/
+      - crates/old-crate-macros/
+component_management:
+  individual_components:
+    - component_id: old-crate
+      paths:
+        - "./crates/old-crate/**"
+ignore:
+  - "vendor/crates/old-crate"
+"#,
+        )
+        .unwrap();
+
+        let files = find_tool_configs(&[temp.path().to_path_buf()], &[]);
+        let mut txn = Transaction::new(false);
+        update_tool_configs(&files, "old-crate", "new-crate", &mut txn).unwrap();
+        update_tool_config_paths(
+            &files,
+            &temp.path().join("crates/old-crate"),
+            &temp.path().join("libs/new-crate"),
+            &mut txn,
+        )
+        .unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&codecov).unwrap(),
+            r#"flags:
+  new-crate:
+    paths:
+      - libs/new-crate/
+      - crates/old-crate-macros/
+component_management:
+  individual_components:
+    - component_id: new-crate
+      paths:
+        - "./libs/new-crate/**"
+ignore:
+  - "vendor/crates/old-crate"
+"#
+        );
+    }
+
+    #[test]
+    fn test_updates_release_tag_templates() {
+        let temp = TempDir::new().unwrap();
+        let release_plz = temp.path().join("release-plz.toml");
+        fs::write(
+            &release_plz,
+            r#"[[package]]
+name = "old-crate"
+git_tag_name = "old-crate-v{{ version }}"
+git_release_name = "old-crate-derive-v{{ version }}"
+"#,
+        )
+        .unwrap();
+        let manifest = temp.path().join("Cargo.toml");
+        fs::write(
+            &manifest,
+            r#"[workspace.metadata.workspaces]
+individual-tag-prefix = "old-crate@"
+
+[workspace.metadata.release]
+tag-prefix = "old-crate-"
+
+[workspace.metadata.other]
+tag-prefix = "old-crate-"
+"#,
+        )
+        .unwrap();
+
+        let files = find_tool_configs(&[temp.path().to_path_buf()], &[]);
+        assert_eq!(files, vec![release_plz.clone()]);
+
+        let mut txn = Transaction::new(false);
+        update_tool_configs(&files, "old-crate", "new-crate", &mut txn).unwrap();
+        update_release_metadata(
+            std::slice::from_ref(&manifest),
+            "old-crate",
+            "new-crate",
+            &mut txn,
+        )
+        .unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&release_plz).unwrap(),
+            r#"[[package]]
+name = "new-crate"
+git_tag_name = "new-crate-v{{ version }}"
+git_release_name = "old-crate-derive-v{{ version }}"
+"#
+        );
+        assert_eq!(
+            fs::read_to_string(&manifest).unwrap(),
+            r#"[workspace.metadata.workspaces]
+individual-tag-prefix = "new-crate@"
+
+[workspace.metadata.release]
+tag-prefix = "new-crate-"
+
+[workspace.metadata.other]
+tag-prefix = "old-crate-"
+"#
+        );
+    }
+
+    #[test]
+    fn test_add_changelog_entry() {
+        let temp = TempDir::new().unwrap();
+        let changelog = temp.path().join("CHANGELOG.md");
+        let entry = "- Renamed the package from `old-crate` to `new-crate`.\n";
+        let add = |content: &str| {
+            fs::write(&changelog, content).unwrap();
+            let mut txn = Transaction::new(false);
+            add_changelog_entry(temp.path(), "old-crate", "new-crate", &mut txn).unwrap();
+            txn.commit().unwrap();
+            fs::read_to_string(&changelog).unwrap()
+        };
+
+        assert_eq!(
+            add(
+                "# Changelog\n\n## [Unreleased]\n\n### Added\n\n- X\n\n### Changed\n\n- Y\n\n## [1.0.0]\n"
+            ),
+            format!(
+                "# Changelog\n\n## [Unreleased]\n\n### Added\n\n- X\n\n### Changed\n\n{}- Y\n\n## [1.0.0]\n",
+                entry
+            )
+        );
+        assert_eq!(
+            add("# Changelog\n\n## [1.0.0]\n\n- Initial\n"),
+            format!(
+                "# Changelog\n\n## [Unreleased]\n\n{}\n## [1.0.0]\n\n- Initial\n",
+                entry
+            )
+        );
+        assert_eq!(
+            add("# Changelog\n"),
+            format!("# Changelog\n\n## [Unreleased]\n\n{}", entry)
+        );
+
+        let mut txn = Transaction::new(false);
+        fs::remove_file(&changelog).unwrap();
+        assert_eq!(
+            add_changelog_entry(temp.path(), "old-crate", "new-crate", &mut txn).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_updates_cargo_config_patch_and_aliases() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join(".cargo")).unwrap();
+        let config = temp.path().join(".cargo/config.toml");
+        fs::write(
+            &config,
+            r#"[alias]
+t = "test -p old-crate --features x"
+r = ["run", "--package", "old-crate"]
+m = "build -p old-crate-macros"
+
+[patch.crates-io]
+old-crate = { path = "crates/old-crate" }
+"#,
+        )
+        .unwrap();
+
+        let files = find_tool_configs(&[temp.path().to_path_buf()], &[]);
+        assert_eq!(files, vec![config.clone()]);
+
+        let mut txn = Transaction::new(false);
+        update_tool_configs(&files, "old-crate", "new-crate", &mut txn).unwrap();
+        update_tool_config_paths(
+            &files,
+            &temp.path().join("crates/old-crate"),
+            &temp.path().join("libs/new-crate"),
+            &mut txn,
+        )
+        .unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&config).unwrap(),
+            r#"[alias]
+t = "test -p new-crate --features x"
+r = ["run", "--package", "new-crate"]
+m = "build -p old-crate-macros"
+
+[patch.crates-io]
+new-crate = { path = "libs/new-crate" }
+"#
+        );
+    }
+}
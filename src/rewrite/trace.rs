@@ -0,0 +1,188 @@
+//! Traces of the regex replacements applied to Rust sources.
+//!
+//! With `--trace-edits FILE`, every replacement made by a source rule is
+//! written to FILE as one JSON object per line: the file (relative to the
+//! workspace root, at its location after the rename), the rule, the byte span
+//! of the match, and the text before and after. Spans refer to the file as it
+//! was just before that edit, so a file's edits apply one after another in
+//! trace order. `cargo rename replay-trace` re-applies or reverts them.
+//!
+//! Only source rule replacements are traced. Manifest and documentation edits,
+//! and compatibility shims removed with `--drop-shims`, are not.
+
+use crate::error::Result;
+use crate::fs::display_path;
+use crate::fs::transaction::Transaction;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A single replacement made by a source rule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TracedEdit {
+    pub file: PathBuf,
+    /// Name of the rule, as accepted by `--disable-rules`.
+    pub rule: String,
+    /// Byte offset of the match, in the file as it was before this edit.
+    pub start: usize,
+    /// Byte offset just past the match.
+    pub end: usize,
+    pub before: String,
+    pub after: String,
+}
+
+/// Replaces every match of `pattern` in `content`, like
+/// [`Regex::replace_all`], and returns the edits made.
+pub fn replace_all_traced(
+    pattern: &Regex,
+    replacement: &str,
+    content: &str,
+    file: &Path,
+    rule: &str,
+) -> (String, Vec<TracedEdit>) {
+    let mut result = String::with_capacity(content.len());
+    let mut edits = Vec::new();
+    let mut last = 0;
+
+    for caps in pattern.captures_iter(content) {
+        let m = caps.get(0).expect("group 0 always matches");
+        let mut after = String::new();
+        caps.expand(replacement, &mut after);
+
+        result.push_str(&content[last..m.start()]);
+        // Earlier edits shifted this match by the difference in length
+        let start = result.len();
+        result.push_str(&after);
+        last = m.end();
+
+        edits.push(TracedEdit {
+            file: file.to_path_buf(),
+            rule: rule.to_string(),
+            start,
+            end: start + m.len(),
+            before: m.as_str().to_string(),
+            after,
+        });
+    }
+    result.push_str(&content[last..]);
+
+    (result, edits)
+}
+
+/// Writes the edits recorded on `txn` to `path` as JSON lines.
+///
+/// Files are written relative to `workspace_root`, at their location after
+/// the staged directory moves.
+pub fn write_trace(path: &Path, workspace_root: &Path, txn: &Transaction) -> Result<()> {
+    let mut lines = String::new();
+
+    for edit in txn.traced_edits() {
+        let moved = txn.moved_path(&edit.file);
+        let file = moved
+            .strip_prefix(workspace_root)
+            .map_or(moved.clone(), Path::to_path_buf);
+        let record = TracedEdit {
+            file,
+            ..edit.clone()
+        };
+        let line = serde_json::to_string(&record)
+            .map_err(|e| anyhow::anyhow!("Failed to serialize edit trace: {}", e))?;
+        lines.push_str(&line);
+        lines.push('\n');
+    }
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, lines)?;
+    Ok(())
+}
+
+/// Reads a trace written by [`write_trace`].
+pub fn load_trace(path: &Path) -> Result<Vec<TracedEdit>> {
+    let content = std::fs::read_to_string(path)?;
+
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                anyhow::anyhow!("Failed to parse {}:{}: {}", display_path(path), idx + 1, e).into()
+            })
+        })
+        .collect()
+}
+
+/// Applies `edits` of one file to `content` in order, or undoes them in
+/// reverse order if `revert` is set.
+///
+/// Fails if the text at an edit's span is not what the trace expects, e.g.
+/// because the file was changed after the rename.
+pub fn replay_edits(content: &str, edits: &[TracedEdit], revert: bool) -> Result<String> {
+    let mut content = content.to_string();
+    let mut ordered: Vec<&TracedEdit> = edits.iter().collect();
+    if revert {
+        ordered.reverse();
+    }
+
+    for edit in ordered {
+        let (from, to) = if revert {
+            (&edit.after, &edit.before)
+        } else {
+            (&edit.before, &edit.after)
+        };
+        let span = edit.start..edit.start + from.len();
+
+        if content.get(span.clone()) != Some(from.as_str()) {
+            return Err(anyhow::anyhow!(
+                "{}: expected {:?} at byte {} ({} edit); the file does not match the trace",
+                display_path(&edit.file),
+                from,
+                edit.start,
+                edit.rule
+            )
+            .into());
+        }
+        content.replace_range(span, to);
+    }
+
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traced_edits_replay_and_revert() {
+        let pattern = Regex::new(r"\bold_crate(::)").unwrap();
+        let original = "use old_crate::a;\nfn f() { old_crate::b(); }\n";
+
+        let (rewritten, edits) = replace_all_traced(
+            &pattern,
+            "new_crate_long${1}",
+            original,
+            Path::new("src/lib.rs"),
+            "qualified-path",
+        );
+
+        assert_eq!(
+            rewritten,
+            "use new_crate_long::a;\nfn f() { new_crate_long::b(); }\n"
+        );
+        assert_eq!(
+            rewritten,
+            pattern.replace_all(original, "new_crate_long${1}")
+        );
+        assert_eq!(edits.len(), 2);
+        assert_eq!((edits[0].start, edits[0].end), (4, 15));
+        // Shifted by the five bytes the first edit added
+        assert_eq!(edits[1].start, 32);
+
+        assert_eq!(replay_edits(original, &edits, false).unwrap(), rewritten);
+        assert_eq!(replay_edits(&rewritten, &edits, true).unwrap(), original);
+        assert!(replay_edits(&rewritten, &edits, false).is_err());
+    }
+}
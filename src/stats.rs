@@ -0,0 +1,141 @@
+//! Local-only usage statistics.
+//!
+//! Opt-in via `--record-stats`. Counts accumulate in
+//! `<workspace>/.cargo-rename/stats.json` and are printed by `cargo rename stats`.
+//! Nothing is ever sent over the network.
+
+use crate::error::Result;
+use crate::fs::TransactionStats;
+use crate::fs::display_path;
+
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory (relative to the workspace root) holding local tool state.
+pub const STATE_DIR: &str = ".cargo-rename";
+
+/// File name of the statistics file inside [`STATE_DIR`].
+pub const STATS_FILE: &str = "stats.json";
+
+/// Accumulated usage counters for a workspace.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageStats {
+    /// Package renames and moves performed.
+    pub renames: u64,
+    /// Workspace renames performed.
+    pub workspace_renames: u64,
+    /// Files rewritten across all runs.
+    pub files_touched: u64,
+    /// Directories moved across all runs.
+    pub dirs_moved: u64,
+    /// Commits that failed and were rolled back.
+    pub rollbacks: u64,
+}
+
+/// Returns the path of the statistics file for a workspace.
+pub fn stats_path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(STATE_DIR).join(STATS_FILE)
+}
+
+impl UsageStats {
+    /// Loads statistics for a workspace. Returns defaults if none were recorded.
+    pub fn load(workspace_root: &Path) -> Result<Self> {
+        let path = stats_path(workspace_root);
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", display_path(&path), e).into())
+    }
+
+    /// Writes statistics for a workspace, creating the state directory if needed.
+    pub fn save(&self, workspace_root: &Path) -> Result<()> {
+        let path = stats_path(workspace_root);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| anyhow::anyhow!("Failed to serialize statistics: {}", e))?;
+        fs::write(&path, content + "\n")?;
+        Ok(())
+    }
+
+    /// Adds the file and directory counts of a committed transaction.
+    pub fn add_transaction(&mut self, stats: TransactionStats) {
+        self.files_touched += stats.files_updated as u64;
+        self.dirs_moved += stats.dirs_moved as u64;
+    }
+
+    /// Prints a human-readable report to stdout.
+    pub fn print_report(&self, workspace_root: &Path) {
+        println!(
+            "\n{} {}",
+            "Rename statistics:".bold().cyan(),
+            display_path(&stats_path(workspace_root)).dimmed()
+        );
+        println!("  {:<20} {}", "Renames performed", self.renames);
+        println!("  {:<20} {}", "Workspace renames", self.workspace_renames);
+        println!("  {:<20} {}", "Files touched", self.files_touched);
+        println!("  {:<20} {}", "Directories moved", self.dirs_moved);
+        println!("  {:<20} {}", "Rollbacks", self.rollbacks);
+    }
+}
+
+/// Loads, updates and saves the statistics for a workspace.
+///
+/// Failures are logged but never abort the surrounding operation.
+pub fn record(workspace_root: &Path, update: impl FnOnce(&mut UsageStats)) {
+    let result = UsageStats::load(workspace_root).and_then(|mut stats| {
+        update(&mut stats);
+        stats.save(workspace_root)
+    });
+
+    if let Err(e) = result {
+        log::warn!("Could not record usage statistics: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_missing_returns_default() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(
+            UsageStats::load(temp.path()).unwrap(),
+            UsageStats::default()
+        );
+    }
+
+    #[test]
+    fn test_record_accumulates() {
+        let temp = TempDir::new().unwrap();
+
+        for _ in 0..2 {
+            record(temp.path(), |s| {
+                s.renames += 1;
+                s.add_transaction(TransactionStats {
+                    files_updated: 3,
+                    dirs_moved: 1,
+                    total: 4,
+                });
+            });
+        }
+
+        let stats = UsageStats::load(temp.path()).unwrap();
+        assert_eq!(stats.renames, 2);
+        assert_eq!(stats.files_touched, 6);
+        assert_eq!(stats.dirs_moved, 2);
+        assert!(stats_path(temp.path()).exists());
+    }
+}
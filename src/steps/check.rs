@@ -0,0 +1,136 @@
+//! Checking files for references to renamed crates (`rename check --files`).
+//!
+//! Meant for pre-commit hooks: the hook passes the staged files, and every
+//! reference to a crate renamed in this workspace (per the history journal)
+//! fails the commit with its location. Rust files are matched with the same
+//! rules a rename rewrites; manifests with the dependency declarations a
+//! rename updates. Other files are ignored.
+//!
+//! Old names that belong to a workspace member again are not checked.
+
+use crate::cargo::dependency_lines;
+use crate::error::{RenameError, Result};
+use crate::history::RenameHistory;
+use crate::rewrite::find_references;
+use crate::steps::rename::load_metadata;
+use crate::workspace_scan::FileKind;
+
+use clap::Parser;
+use colored::Colorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Arguments for the `rename check` subcommand.
+#[derive(Parser, Debug, Clone, Default)]
+pub struct CheckArgs {
+    /// Files to check (as passed by pre-commit)
+    #[arg(long, value_name = "FILE", num_args = 1.., required = true)]
+    pub files: Vec<PathBuf>,
+
+    /// Path to workspace Cargo.toml (searches upward if not specified)
+    #[arg(long, value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+}
+
+/// A reference to an old crate name in a checked file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleReference {
+    pub path: PathBuf,
+    pub line: usize,
+    /// 1-based column; `None` for manifest lines.
+    pub column: Option<usize>,
+    pub old_name: String,
+    /// The name the crate goes by now.
+    pub new_name: String,
+}
+
+/// Checks the files and prints the references found.
+///
+/// # Errors
+///
+/// `StaleReferences` if any is found, so the process exits 1.
+pub fn execute(args: CheckArgs) -> Result<()> {
+    let metadata = load_metadata(args.manifest_path.as_deref())?;
+    let history = RenameHistory::load(metadata.workspace_root.as_std_path())?;
+    let members: Vec<&str> = metadata
+        .workspace_packages()
+        .iter()
+        .map(|p| p.name.as_str())
+        .collect();
+
+    let mut renamed: Vec<(String, String)> = Vec::new();
+    for entry in &history.renames {
+        let old_name = entry.old_name.as_str();
+        if members.contains(&old_name) || renamed.iter().any(|(name, _)| name == old_name) {
+            continue;
+        }
+        if let Some((_, current)) = history.renamed_to(old_name) {
+            renamed.push((old_name.to_string(), current));
+        }
+    }
+
+    let mut stale = Vec::new();
+    for path in &args.files {
+        stale.extend(check_file(path, &renamed)?);
+    }
+
+    for reference in &stale {
+        let location = match reference.column {
+            Some(column) => format!("{}:{}:{}", reference.path.display(), reference.line, column),
+            None => format!("{}:{}", reference.path.display(), reference.line),
+        };
+        println!(
+            "{}: '{}' was renamed to '{}'",
+            location.bold(),
+            reference.old_name.yellow(),
+            reference.new_name.green()
+        );
+    }
+
+    if stale.is_empty() {
+        Ok(())
+    } else {
+        Err(RenameError::StaleReferences(stale.len()))
+    }
+}
+
+/// Returns the references to the `(old, current)` names in a file.
+///
+/// Missing files (deleted in the commit) and files that are not UTF-8 are
+/// skipped.
+fn check_file(path: &Path, renamed: &[(String, String)]) -> Result<Vec<StaleReference>> {
+    let kind = FileKind::of(path);
+    if !matches!(kind, Some(FileKind::Rust | FileKind::Manifest)) || renamed.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Ok(content) = fs::read_to_string(path) else {
+        log::debug!("Skipping unreadable file: {}", path.display());
+        return Ok(Vec::new());
+    };
+
+    let mut stale = Vec::new();
+    for (old_name, new_name) in renamed {
+        let locations: Vec<(usize, Option<usize>)> = if kind == Some(FileKind::Rust) {
+            find_references(&content, old_name)?
+                .into_iter()
+                .map(|r| (r.line, Some(r.column)))
+                .collect()
+        } else {
+            dependency_lines(&content, old_name)?
+                .into_iter()
+                .map(|line| (line, None))
+                .collect()
+        };
+
+        stale.extend(locations.into_iter().map(|(line, column)| StaleReference {
+            path: path.to_path_buf(),
+            line,
+            column,
+            old_name: old_name.clone(),
+            new_name: new_name.clone(),
+        }));
+    }
+
+    stale.sort_by_key(|r| (r.line, r.column));
+    Ok(stale)
+}
@@ -0,0 +1,57 @@
+//! Generation of synthetic workspaces for bug reports and tests.
+
+use crate::error::Result;
+use crate::fixture::{FixtureOptions, generate, member_name};
+use crate::fs::display_path;
+use crate::symbols;
+
+use clap::Parser;
+use colored::Colorize;
+use std::path::PathBuf;
+
+/// Arguments for the `rename fixture` subcommand.
+#[derive(Parser, Debug, Clone)]
+pub struct FixtureArgs {
+    /// Directory to create the workspace in (must be empty or missing)
+    #[arg(value_name = "OUT_DIR")]
+    pub out_dir: PathBuf,
+
+    /// Number of member packages
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    pub members: usize,
+
+    /// Add [target.'cfg(unix)'.dependencies] on the previous member
+    #[arg(long)]
+    pub with_target_deps: bool,
+
+    /// Depend on member-1 under an alias from member-3 onwards (first = { package = "member-1" })
+    #[arg(long)]
+    pub with_aliases: bool,
+}
+
+/// Writes the fixture workspace and prints how to reproduce a rename in it.
+pub fn execute(args: FixtureArgs) -> Result<()> {
+    let options = FixtureOptions {
+        members: args.members,
+        target_deps: args.with_target_deps,
+        aliases: args.with_aliases,
+    };
+
+    generate(&options, &args.out_dir)?;
+
+    println!(
+        "{} {} members in {}",
+        format!("{} Generated fixture with", symbols::check())
+            .green()
+            .bold(),
+        options.members,
+        display_path(&args.out_dir)
+    );
+    println!(
+        "\nTry: cargo rename {} renamed-member --manifest-path {}",
+        member_name(1),
+        display_path(&args.out_dir.join("Cargo.toml"))
+    );
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+//! Listing of rename candidates with their reverse-dependency impact.
+//!
+//! Prints every workspace member with its directory and the workspace crates
+//! depending on it, directly or through other members, to gauge how much a
+//! rename touches before running it.
+
+use crate::error::Result;
+use crate::fs::display_relative;
+use crate::steps::rename::load_metadata;
+
+use cargo_metadata::Metadata;
+use clap::{Parser, ValueEnum};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+/// Output format of `rename list`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned table
+    #[default]
+    Text,
+    /// JSON array on stdout
+    Json,
+}
+
+/// Arguments for the `rename list` subcommand.
+#[derive(Parser, Debug, Clone, Default)]
+pub struct ListArgs {
+    /// Path to workspace Cargo.toml (searches upward if not specified)
+    #[arg(long, value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Output format
+    #[arg(long, value_name = "FORMAT", default_value = "text")]
+    pub output: OutputFormat,
+}
+
+/// A workspace member and the members a rename of it would touch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Candidate {
+    pub name: String,
+    pub version: String,
+    /// Package directory, relative to the workspace root.
+    pub directory: String,
+    /// Members depending on it directly, by path, sorted.
+    pub dependents: Vec<String>,
+    /// Members depending on it directly or through other members, sorted.
+    pub transitive_dependents: Vec<String>,
+}
+
+/// Prints the workspace members and their dependents.
+pub fn execute(args: ListArgs) -> Result<()> {
+    let metadata = load_metadata(args.manifest_path.as_deref())?;
+    let candidates = candidates(&metadata);
+
+    match args.output {
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&candidates)
+                .map_err(|e| anyhow::anyhow!("Failed to serialize candidates: {}", e))?;
+            println!("{}", json);
+        }
+        OutputFormat::Text => print_table(&candidates),
+    }
+    Ok(())
+}
+
+/// Returns the workspace members sorted by name, with their dependents.
+///
+/// Only dependencies on the member's own directory count, so a crates.io
+/// crate of the same name is not mistaken for it.
+pub fn candidates(metadata: &Metadata) -> Vec<Candidate> {
+    let workspace_root = metadata.workspace_root.as_std_path();
+    let members = metadata.workspace_packages();
+
+    // Member → members depending on it directly
+    let mut reverse: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for member in &members {
+        let dir = member.manifest_path.parent().unwrap();
+        let entry = reverse.entry(member.name.as_str()).or_default();
+        for other in &members {
+            if other.name != member.name
+                && other
+                    .dependencies
+                    .iter()
+                    .any(|dep| dep.path.as_deref() == Some(dir))
+            {
+                entry.insert(other.name.as_str());
+            }
+        }
+    }
+
+    let mut candidates: Vec<Candidate> = members
+        .iter()
+        .map(|member| {
+            let direct = &reverse[member.name.as_str()];
+
+            let mut transitive = BTreeSet::new();
+            let mut queue: Vec<&str> = direct.iter().copied().collect();
+            while let Some(name) = queue.pop() {
+                if name != member.name.as_str() && transitive.insert(name) {
+                    queue.extend(reverse.get(name).into_iter().flatten().copied());
+                }
+            }
+
+            Candidate {
+                name: member.name.to_string(),
+                version: member.version.to_string(),
+                directory: display_relative(
+                    member.manifest_path.parent().unwrap().as_std_path(),
+                    workspace_root,
+                ),
+                dependents: direct.iter().map(|name| name.to_string()).collect(),
+                transitive_dependents: transitive.iter().map(|name| name.to_string()).collect(),
+            }
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.name.cmp(&b.name));
+    candidates
+}
+
+fn print_table(candidates: &[Candidate]) {
+    let name_width = candidates
+        .iter()
+        .map(|c| c.name.len())
+        .chain([7])
+        .max()
+        .unwrap_or_default();
+    let dir_width = candidates
+        .iter()
+        .map(|c| c.directory.len())
+        .chain([9])
+        .max()
+        .unwrap_or_default();
+
+    println!(
+        "{}",
+        format!(
+            "{:name_width$}  {:dir_width$}  {:>6}  {:>10}",
+            "PACKAGE", "DIRECTORY", "DIRECT", "TRANSITIVE"
+        )
+        .bold()
+    );
+    for candidate in candidates {
+        println!(
+            "{:name_width$}  {:dir_width$}  {:>6}  {:>10}",
+            candidate.name,
+            candidate.directory,
+            candidate.dependents.len(),
+            candidate.transitive_dependents.len()
+        );
+    }
+}
@@ -0,0 +1,31 @@
+//! Reporting of the recorded rename history.
+
+use crate::error::Result;
+use crate::history::{RenameHistory, history_path};
+use crate::steps::rename::load_metadata;
+
+use clap::Parser;
+use colored::Colorize;
+use std::path::PathBuf;
+
+/// Arguments for the `rename log` subcommand.
+#[derive(Parser, Debug, Clone, Default)]
+pub struct LogArgs {
+    /// Path to workspace Cargo.toml (searches upward if not specified)
+    #[arg(long, value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+}
+
+/// Prints the rename history of the current workspace.
+pub fn execute(args: LogArgs) -> Result<()> {
+    let metadata = load_metadata(args.manifest_path.as_deref())?;
+    let workspace_root = metadata.workspace_root.as_std_path();
+
+    if !history_path(workspace_root).exists() {
+        println!("{}", "No renames recorded yet.".yellow());
+        return Ok(());
+    }
+
+    RenameHistory::load(workspace_root)?.print_log(workspace_root);
+    Ok(())
+}
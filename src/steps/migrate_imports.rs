@@ -0,0 +1,158 @@
+//! Orchestration logic for `rename migrate-imports`.
+//!
+//! Brings a downstream project outside the workspace up to date with a
+//! published rename: dependency keys (`old-crate = "1"`, `package =
+//! "old-crate"`) and Rust references (`use old_crate::...`) are rewritten
+//! with the same rules as a rename, together with `[features]` references to
+//! the renamed keys (`old-crate/std`, `dep:old-crate`). Nothing is moved, and the project does
+//! not need to build or resolve with `cargo metadata`.
+
+use crate::cargo::{ManifestUpdate, update_external_manifest, update_feature_references};
+use crate::error::{RenameError, Result};
+use crate::fs::display::set_display_root;
+use crate::fs::transaction::Transaction;
+use crate::rewrite::{SourceRule, migrate_sources};
+use crate::steps::rename::{handle_commit_error, handle_staging_error};
+use crate::symbols;
+use crate::verify::{check_git_status, confirm_migrate_imports, validate_package_name};
+use crate::workspace_scan;
+
+use clap::Parser;
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+/// Arguments for the `rename migrate-imports` subcommand.
+#[derive(Parser, Debug, Clone, Default)]
+pub struct MigrateImportsArgs {
+    /// Name of the package before the rename
+    #[arg(value_name = "OLD_NAME")]
+    pub old_name: String,
+
+    /// Name of the package after the rename
+    #[arg(value_name = "NEW_NAME")]
+    pub new_name: String,
+
+    /// Project to migrate (all Cargo.toml and Rust files below it)
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    pub path: PathBuf,
+
+    /// Preview changes without applying them
+    #[arg(long, short = 'n')]
+    pub dry_run: bool,
+
+    /// Skip interactive confirmation
+    #[arg(long = "yes", short = 'y')]
+    pub skip_confirmation: bool,
+
+    /// Allow operation with uncommitted git changes
+    #[arg(long)]
+    pub allow_dirty: bool,
+
+    /// Source rewrite rules to skip, e.g. `macro,doc-link`
+    #[arg(long, value_name = "RULES", value_delimiter = ',')]
+    pub disable_rules: Vec<SourceRule>,
+}
+
+/// Migrates a downstream project to the renamed package.
+///
+/// ## Phases
+///
+/// 1. Validate the new name and the project directory
+/// 2. Check git status
+/// 3. Stage manifest and source edits
+/// 4. User confirmation (unless `--yes`)
+/// 5. Commit atomically
+pub fn execute(args: MigrateImportsArgs) -> Result<()> {
+    validate_package_name(&args.new_name)?;
+    if args.old_name == args.new_name {
+        return Err(RenameError::NothingToDo(format!(
+            "'{}' is both the old and the new name",
+            args.old_name
+        )));
+    }
+    if !args.path.is_dir() {
+        return Err(RenameError::InvalidPath(
+            args.path.display().to_string(),
+            "not a directory".to_string(),
+        ));
+    }
+    let root = args.path.canonicalize()?;
+    set_display_root(&root);
+
+    if !args.allow_dirty
+        && let Err(e) = check_git_status(&root)
+    {
+        log::info!("Hint: Use --allow-dirty to bypass this check");
+        return Err(e);
+    }
+
+    let mut txn = Transaction::new(args.dry_run);
+
+    if let Err(e) = stage_migration(&args, &root, &mut txn) {
+        return handle_staging_error(e, txn, args.dry_run);
+    }
+
+    if txn.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "No changes needed: nothing under {} refers to '{}'",
+                args.path.display(),
+                args.old_name
+            )
+            .yellow()
+        );
+        return Ok(());
+    }
+
+    if !confirm_migrate_imports(&args, &txn)? {
+        txn.discard();
+        println!("\n{}", "Operation cancelled.".yellow());
+        return Err(RenameError::Cancelled);
+    }
+
+    if let Err(e) = txn.commit() {
+        return handle_commit_error(e);
+    }
+
+    txn.print_summary(&args.old_name, &args.new_name, &root);
+
+    if !args.dry_run {
+        println!(
+            "\n{} {} {} {}",
+            format!("{} Migrated imports", symbols::check())
+                .green()
+                .bold(),
+            args.old_name.yellow(),
+            symbols::arrow(),
+            args.new_name.green().bold()
+        );
+    }
+
+    Ok(())
+}
+
+/// Stages the dependency key edits of every manifest and the source edits under `root`.
+fn stage_migration(args: &MigrateImportsArgs, root: &Path, txn: &mut Transaction) -> Result<()> {
+    let manifests = workspace_scan::files_matching(root, &["**/Cargo.toml".to_string()])?;
+
+    for manifest in &manifests {
+        let dir = manifest.parent().unwrap();
+        // Directories are unchanged, so only names are rewritten, whatever
+        // the source of the published crate
+        let update = ManifestUpdate {
+            other_sources: true,
+            ..ManifestUpdate::new(&args.old_name, &args.new_name, dir, dir, root)
+        };
+        update_external_manifest(manifest, &update, txn)?;
+        update_feature_references(manifest, &args.old_name, &args.new_name, txn)?;
+    }
+
+    migrate_sources(
+        root,
+        &args.old_name,
+        &args.new_name,
+        &args.disable_rules,
+        txn,
+    )
+}
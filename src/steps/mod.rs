@@ -1 +1,12 @@
+pub mod check;
+pub mod fixture;
+pub mod list;
+pub mod log;
+pub mod migrate_imports;
+pub mod normalize_deps;
 pub mod rename;
+pub mod replay_trace;
+pub mod schema;
+pub mod stats;
+pub mod validate_name;
+pub mod workspace;
@@ -0,0 +1,259 @@
+//! Orchestration logic for `rename normalize-deps`.
+//!
+//! Rewrites how dependents declare a package without renaming it: the
+//! dependency-rewriting half of a rename, run on its own as a cleanup task.
+//! Running it twice changes nothing the second time.
+
+use crate::cargo::{Normalization, NormalizeTarget, normalize_manifest};
+use crate::error::{RenameError, Result};
+use crate::fs::display_path;
+use crate::fs::transaction::Transaction;
+use crate::rewrite::update_dependency_key;
+use crate::steps::rename::{
+    handle_commit_error, handle_staging_error, load_metadata, package_dirs,
+};
+use crate::symbols;
+use crate::verify::{
+    VerificationOutcome, check_git_status, confirm_normalize_deps, suggest_similar_names,
+    verify_workspace,
+};
+
+use cargo_metadata::Metadata;
+use clap::Parser;
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+/// Arguments for the `rename normalize-deps` subcommand.
+#[derive(Parser, Debug, Clone, Default)]
+pub struct NormalizeDepsArgs {
+    /// Package whose dependents are normalized
+    #[arg(value_name = "NAME")]
+    pub name: String,
+
+    /// Path to workspace Cargo.toml (searches upward if not specified)
+    #[arg(long, value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Preview changes without applying them
+    #[arg(long, short = 'n')]
+    pub dry_run: bool,
+
+    /// Skip interactive confirmation
+    #[arg(long = "yes", short = 'y')]
+    pub skip_confirmation: bool,
+
+    /// Allow operation with uncommitted git changes
+    #[arg(long)]
+    pub allow_dirty: bool,
+}
+
+/// Normalizes the declarations of a package across the workspace.
+///
+/// ## Phases
+///
+/// 1. Load metadata via `cargo metadata`
+/// 2. Check git status
+/// 3. Stage manifest and source edits
+/// 4. User confirmation (unless `--yes`)
+/// 5. Commit atomically, rolling back if `cargo metadata` rejects the result
+pub fn execute(args: NormalizeDepsArgs) -> Result<()> {
+    let metadata = load_metadata(args.manifest_path.as_deref())?;
+    let workspace_root = metadata.workspace_root.as_std_path();
+
+    let package = metadata
+        .workspace_packages()
+        .into_iter()
+        .find(|p| p.name == args.name.as_str())
+        .ok_or_else(|| {
+            let members = metadata.workspace_packages();
+            let suggestions =
+                suggest_similar_names(&args.name, members.iter().map(|p| p.name.as_str()));
+            RenameError::PackageNotFound(args.name.clone(), suggestions)
+        })?;
+    let package_dir = package.manifest_path.parent().unwrap().as_std_path();
+
+    if !args.allow_dirty
+        && let Err(e) = check_git_status(workspace_root)
+    {
+        log::info!("Hint: Use --allow-dirty to bypass this check");
+        return Err(e);
+    }
+
+    let mut txn = Transaction::new(args.dry_run);
+    txn.set_packages(package_dirs(&metadata));
+
+    let normalization = match stage_normalization(&metadata, &args.name, package_dir, &mut txn) {
+        Ok(normalization) => normalization,
+        Err(e) => return handle_staging_error(e, txn, args.dry_run),
+    };
+
+    if txn.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "No changes needed: dependents already declare '{}' consistently",
+                args.name
+            )
+            .yellow()
+        );
+        report_kept(&normalization);
+        return Ok(());
+    }
+
+    report_normalization(&normalization, &args.name);
+
+    if !confirm_normalize_deps(&args, &txn)? {
+        txn.discard();
+        println!("\n{}", "Operation cancelled.".yellow());
+        return Err(RenameError::Cancelled);
+    }
+
+    if let Err(e) = txn.commit() {
+        return handle_commit_error(e);
+    }
+
+    if !args.dry_run {
+        verify_normalization(workspace_root, &mut txn)?;
+    }
+
+    txn.print_summary(&args.name, &args.name, workspace_root);
+
+    if !args.dry_run {
+        println!(
+            "\n{} {}",
+            format!("{} Normalized dependencies on", symbols::check())
+                .green()
+                .bold(),
+            args.name.green().bold()
+        );
+    }
+
+    Ok(())
+}
+
+/// Rolls back the normalization unless `cargo metadata` still loads the workspace.
+fn verify_normalization(workspace_root: &Path, txn: &mut Transaction) -> Result<()> {
+    let outcome = match verify_workspace(workspace_root, false) {
+        Ok(outcome) => outcome,
+        Err(e) => VerificationOutcome {
+            ok: false,
+            stderr: e.to_string(),
+            suggested_fixes: Vec::new(),
+        },
+    };
+    if outcome.ok {
+        log::info!("{} Workspace verification passed", symbols::check());
+        return Ok(());
+    }
+
+    txn.rollback()?;
+    eprintln!("{} No changes were applied.", symbols::info().blue().bold());
+
+    Err(RenameError::VerificationFailed(format!(
+        "`cargo metadata` failed after normalizing, which was rolled back:\n{}",
+        outcome.stderr
+    )))
+}
+
+/// Stages the normalized manifests and the source edits for renamed keys.
+fn stage_normalization(
+    metadata: &Metadata,
+    name: &str,
+    package_dir: &Path,
+    txn: &mut Transaction,
+) -> Result<Normalization> {
+    let workspace_root = metadata.workspace_root.as_std_path();
+    let root_manifest = workspace_root.join("Cargo.toml");
+    let target = NormalizeTarget::new(name, package_dir, &root_manifest)?;
+
+    let mut manifests = vec![root_manifest.clone()];
+    manifests.extend(
+        metadata
+            .workspace_packages()
+            .iter()
+            .map(|p| p.manifest_path.as_std_path().to_path_buf())
+            .filter(|path| *path != root_manifest),
+    );
+
+    let member_dirs: Vec<PathBuf> = manifests
+        .iter()
+        .filter_map(|m| m.parent())
+        .map(Path::to_path_buf)
+        .collect();
+
+    let mut normalization = Normalization::default();
+    for manifest in &manifests {
+        // Sparse checkouts may lack some members
+        if !manifest.exists() {
+            continue;
+        }
+        let result = normalize_manifest(manifest, &target, txn)?;
+
+        let dir = manifest.parent().unwrap();
+        let nested: Vec<PathBuf> = member_dirs
+            .iter()
+            .filter(|d| d.as_path() != dir && d.starts_with(dir))
+            .cloned()
+            .collect();
+        // Keys in [workspace.dependencies] are only visible through members inheriting them
+        let mut aliases: Vec<&str> = result
+            .normalized
+            .iter()
+            .filter(|n| n.section != "workspace.dependencies")
+            .filter_map(|n| n.renamed_from.as_deref())
+            .collect();
+        aliases.sort_unstable();
+        aliases.dedup();
+        for alias in aliases {
+            update_dependency_key(dir, &nested, alias, name, txn)?;
+        }
+
+        normalization.normalized.extend(result.normalized);
+        normalization.kept.extend(result.kept);
+    }
+
+    Ok(normalization)
+}
+
+fn report_normalization(normalization: &Normalization, name: &str) {
+    if !normalization.normalized.is_empty() {
+        println!("\n{}", "Normalized declarations:".bold());
+    }
+    for entry in &normalization.normalized {
+        let mut changes = Vec::new();
+        if let Some(alias) = &entry.renamed_from {
+            changes.push(format!("{} {} {}", alias, symbols::arrow(), name));
+        }
+        if entry.inherited {
+            changes.push("workspace = true".to_string());
+        }
+        if changes.is_empty() {
+            changes.push("dropped redundant package field".to_string());
+        }
+        println!(
+            "   {} [{}] {}",
+            display_path(&entry.manifest),
+            entry.section,
+            changes.join(", ")
+        );
+    }
+
+    report_kept(normalization);
+}
+
+fn report_kept(normalization: &Normalization) {
+    if normalization.kept.is_empty() {
+        return;
+    }
+
+    println!("\n{} Left as is:", symbols::warning().yellow().bold());
+    for kept in &normalization.kept {
+        println!(
+            "   {} [{}] {}: {}",
+            display_path(&kept.manifest),
+            kept.section,
+            kept.key,
+            kept.reason.dimmed()
+        );
+    }
+}
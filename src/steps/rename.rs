@@ -2,21 +2,63 @@
 //!
 //! All file system modifications go through a `Transaction` for atomicity.
 
-use crate::cargo::{update_dependent_manifest, update_package_name, update_workspace_manifest};
-use crate::error::{RenameError, Result};
-use crate::fs::transaction::Transaction;
-use crate::rewrite::update_source_code;
-use crate::verify::{confirm_operation, preflight_checks};
+use crate::annotate;
+use crate::cargo::{
+    DepKind, DependencyReplacements, GlobReview, ManifestUpdate, ManifestWorkspace, NameResolution,
+    PackageMetadata, PathStyle, UrlUpdate, VersionMismatch, check_dependency_versions,
+    check_package_metadata, dependent_manifests, find_external_dependents, find_parent_workspace,
+    missing_member_manifests, pin_unselected_dependencies, resolve_dependency_name,
+    update_dependent_manifest, update_external_manifest, update_package_globs,
+    update_package_links, update_package_name, update_package_urls, update_package_version,
+    update_patch_sections, update_target_names, update_target_paths, update_workspace_fragment,
+    update_workspace_manifest, workspace_dependency_kinds,
+};
+use crate::config::WorkspaceConfig;
+use crate::console::{self, errln, outln};
+use crate::error::{self, RenameError, Result};
+use crate::fs::display::{display_from_cwd, display_path, set_display_root};
+use crate::fs::path::normalize_path;
+use crate::fs::sandbox::Sandbox;
+use crate::fs::transaction::{Category, Operation, Transaction};
+use crate::history::{self, HistoryEntry, RenameHistory};
+use crate::process;
+use crate::rewrite::env::EnvVarRename;
+use crate::rewrite::{
+    AliasReport, CompatShim, Conflict, DocOptions, DocsCategory, DocsScope, EditOutcome,
+    HookConfig, HookContext, SourceOptions, SourceRule, SsrPlan, add_changelog_entry,
+    edit_conflicts, find_tool_configs, insert_crate_aliases, run_hooks, ssr_rules,
+    update_artifact_env_vars, update_env_prefix, update_ffi, update_links_env_vars,
+    update_log_targets, update_release_metadata, update_source_code, update_tool_config_paths,
+    update_tool_configs, write_trace,
+};
+use crate::stats;
+use crate::symbols;
+use crate::verify::{
+    DriftKind, VerificationOutcome, VerifyCommand, check_git_status, check_hook_drift,
+    collect_preflight_errors, confirm_operation, pick_package, preflight_checks,
+    run_verify_command, suggest_similar_names, validate_crate_identifier, validate_package_name,
+    verify_workspace,
+};
+use crate::workspace_scan;
 
 use cargo_metadata::MetadataCommand;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use colored::Colorize;
-use std::path::{Path, PathBuf};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
 
 /// Arguments for the `rename` subcommand.
-#[derive(Parser, Debug, Clone)]
+#[derive(Parser, Debug, Clone, Default)]
 pub struct RenameArgs {
     /// Current name of the package
+    #[arg(
+        required_unless_present = "at",
+        default_value = "",
+        hide_default_value = true
+    )]
     pub old_name: String,
 
     /// New name for the package (optional if only moving)
@@ -31,14 +73,70 @@ pub struct RenameArgs {
     #[arg(long = "move", value_name = "DIR", verbatim_doc_comment)]
     pub outdir: Option<Option<PathBuf>>,
 
+    /// Move the package to DIR, relative to its current parent directory
+    ///
+    /// Examples, for a package in crates/old:
+    ///   --move-relative new          Move to crates/new/
+    ///   --move-relative api/new      Move to crates/api/new/
+    ///   --move-relative ../libs/new  Move to libs/new/
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = ["outdir", "keep_dir_name"],
+        verbatim_doc_comment
+    )]
+    pub move_relative: Option<PathBuf>,
+
+    /// Never move the package directory, and don't hint at moving it
+    ///
+    /// For workspaces whose directory names are deliberately independent of
+    /// package names. Prints the final package → directory mapping instead.
+    #[arg(long, conflicts_with = "outdir", verbatim_doc_comment)]
+    pub keep_dir_name: bool,
+
+    /// Select the package by its directory or Cargo.toml instead of OLD_NAME
+    ///
+    /// The only positional argument is then the new name:
+    ///   cargo rename --at crates/foo new-name
+    #[arg(long, value_name = "DIR", verbatim_doc_comment)]
+    pub at: Option<PathBuf>,
+
     /// Path to workspace Cargo.toml (searches upward if not specified)
     #[arg(long, value_name = "PATH")]
     pub manifest_path: Option<PathBuf>,
 
+    /// Refuse to run unless the workspace root is DIR
+    ///
+    /// Loads DIR/Cargo.toml unless --manifest-path is given, and fails if
+    /// cargo resolves another root, e.g. an enclosing workspace.
+    #[arg(long, value_name = "DIR", verbatim_doc_comment)]
+    pub workspace_root: Option<PathBuf>,
+
     /// Preview changes without applying them
     #[arg(long, short = 'n')]
     pub dry_run: bool,
 
+    /// Rehearse the rename in a temporary copy of the workspace first
+    ///
+    /// The copy is renamed and built with `cargo check`; the real workspace
+    /// is only changed if that succeeds (and, without --yes, once confirmed).
+    /// With --dry-run, stops after the rehearsal. Path dependencies outside
+    /// the workspace do not resolve in the copy; --move, --tool-config and
+    /// --workspace-fragment paths outside it are refused, and no
+    /// --trace-edits file is written for the rehearsal.
+    #[arg(long, verbatim_doc_comment)]
+    pub sandbox: bool,
+
+    /// Build the workspace after the rename, rolling it back on failure
+    ///
+    /// check: cargo check --workspace --all-targets
+    /// test:  cargo test --workspace
+    ///
+    /// Runs after the changes are written, unlike --sandbox, and within
+    /// --timeout. Ignored with --dry-run.
+    #[arg(long, value_name = "COMMAND", verbatim_doc_comment)]
+    pub verify: Option<VerifyCommand>,
+
     /// Skip interactive confirmation
     #[arg(long = "yes", short = 'y')]
     pub skip_confirmation: bool,
@@ -46,12 +144,560 @@ pub struct RenameArgs {
     /// Allow operation with uncommitted git changes
     #[arg(long)]
     pub allow_dirty: bool,
+
+    /// Skip the path length check for moves
+    ///
+    /// By default, a move fails if any file would exceed the platform's path
+    /// limit (260 characters on Windows). Use this where long paths are
+    /// enabled.
+    #[arg(long, verbatim_doc_comment)]
+    pub allow_long_paths: bool,
+
+    /// Move the directory even if it contains other workspace packages
+    ///
+    /// Those packages move along with it; manifests referring to them by
+    /// path are not updated.
+    #[arg(long, verbatim_doc_comment)]
+    pub force_shared_dir: bool,
+
+    /// Rewrite OLD_NAME_* environment variables in the renamed package
+    ///
+    /// Covers string literals in .rs files, Markdown docs and .env files.
+    #[arg(long)]
+    pub rename_env_prefix: bool,
+
+    /// Rename `links` and DEP_OLD_* reads in dependents' build scripts
+    ///
+    /// For -sys crates whose `links` value derives from the package name
+    /// (`links = "foo"` for foo-sys). This changes the native library
+    /// linkage contract: other crates linking the same library by its old
+    /// `links` name no longer conflict with this one.
+    #[arg(long, verbatim_doc_comment)]
+    pub rename_links: bool,
+
+    /// Rewrite `tracing`/`log` targets and filters naming the old crate
+    ///
+    /// Covers `target: "old_name"` in .rs files and filter directives such
+    /// as `old_name=debug` in string literals and Markdown docs, across the
+    /// workspace.
+    #[arg(long, verbatim_doc_comment)]
+    pub update_log_targets: bool,
+
+    /// Update cbindgen.toml and C header include guards (OLD_CRATE_H)
+    #[arg(long)]
+    pub update_ffi: bool,
+
+    /// Remove `pub use old_crate as alias;` shims left by earlier renames
+    ///
+    /// Only re-exports under one of the crate's former names (from the
+    /// rename history) or ending in `// cargo-rename: shim` are shims. By
+    /// default they are rewritten to re-export the new name and listed.
+    #[arg(long)]
+    pub drop_shims: bool,
+
+    /// Rewrite Rust files that fail to parse instead of skipping them
+    ///
+    /// Macro-heavy files can trip the parser. Their references are then
+    /// rewritten with the regular patterns only, and each file is reported.
+    #[arg(long)]
+    pub force_rewrite_unparsable: bool,
+
+    /// Open skipped unparsable Rust files in $VISUAL or $EDITOR
+    ///
+    /// Each file is opened at its first reference, with the suggested
+    /// replacement in a comment above every referencing line. The edit is
+    /// included in the rename if the file then parses or no longer references
+    /// the old name; otherwise it is opened again until saved unchanged.
+    /// Ignored with --dry-run.
+    #[arg(
+        long,
+        conflicts_with = "force_rewrite_unparsable",
+        verbatim_doc_comment
+    )]
+    pub edit_conflicts: bool,
+
+    /// Source rewrite rules to skip, e.g. `macro,doc-link`
+    ///
+    /// Rules: use-statement, absolute-use, extern-crate, visibility,
+    /// qualified-path, absolute-path, attribute, attribute-args, doc-link,
+    /// use-self, raw-identifier, macro. `-vv` logs the rules that changed each file.
+    #[arg(
+        long,
+        value_name = "RULES",
+        value_delimiter = ',',
+        verbatim_doc_comment
+    )]
+    pub disable_rules: Vec<SourceRule>,
+
+    /// Skip files matching GLOB when rewriting sources (repeatable)
+    ///
+    /// Gitignore syntax, relative to the workspace root or to each package:
+    ///   --exclude "benches/**" --exclude "examples/legacy/*"
+    #[arg(long, value_name = "GLOB", verbatim_doc_comment)]
+    pub exclude: Vec<String>,
+
+    /// Alias the old name at dependents' crate roots instead of rewriting them
+    ///
+    /// Adds `extern crate new_crate as old_crate;` to each crate root of a
+    /// dependent that uses the package, leaving its other sources as they
+    /// are. Crate roots that do not parse or already declare `old_crate` are
+    /// listed for manual review. The renamed package itself is rewritten
+    /// as usual.
+    #[arg(long, conflicts_with = "only_kinds", verbatim_doc_comment)]
+    pub via_alias: bool,
+
+    /// Only update Cargo.toml files
+    ///
+    /// Source code, documentation, tool configs and hooks are left alone, so
+    /// without --dry-run a warning notes that the workspace may not build
+    /// until they are updated. With --dry-run, the workspace is read from its manifests instead of
+    /// `cargo metadata` when possible, which is much faster on large
+    /// workspaces.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "rename_env_prefix",
+            "update_log_targets",
+            "update_ffi",
+            "drop_shims",
+            "force_rewrite_unparsable"
+        ],
+        verbatim_doc_comment
+    )]
+    pub manifests_only: bool,
+
+    /// Print rust-analyzer SSR rules instead of rewriting Rust sources
+    ///
+    /// Prints one structural search-replace rule per path into the renamed
+    /// crate (`old_crate::a::B ==>> new_crate::a::B`), for applying source
+    /// changes from the IDE. Like --manifests-only, only Cargo.toml files
+    /// and the directory move are handled.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "rename_env_prefix",
+            "update_log_targets",
+            "update_ffi",
+            "drop_shims",
+            "force_rewrite_unparsable"
+        ],
+        verbatim_doc_comment
+    )]
+    pub emit_ssr: bool,
+
+    /// Also update path dependents in DIR, outside the workspace (repeatable)
+    ///
+    /// For sibling checkouts that depend on the package by path. Each DIR is
+    /// updated in its own transaction after the rename is applied.
+    #[arg(long, value_name = "DIR", verbatim_doc_comment)]
+    pub notify_external: Vec<PathBuf>,
+
+    /// Also update path dependents in PATH, outside the workspace, atomically
+    ///
+    /// Like --notify-external, but the manifests found are updated in the
+    /// rename's own transaction: committed and rolled back with it.
+    #[arg(long, value_name = "PATH", num_args = 1.., verbatim_doc_comment)]
+    pub scan_dirs: Vec<PathBuf>,
+
+    /// Also update path dependents in the workspace enclosing this one
+    ///
+    /// For a workspace nested in (and excluded from) another one. Without
+    /// it, dependents found there are only reported.
+    #[arg(long, verbatim_doc_comment)]
+    pub include_parent_workspace: bool,
+
+    /// Documentation categories to update [default: text,badges]
+    ///
+    /// text: prose, headings and link text
+    /// urls: link URLs other than badges
+    /// badges: crates.io, docs.rs and shields.io URLs
+    #[arg(
+        long,
+        value_name = "CATEGORIES",
+        value_delimiter = ',',
+        verbatim_doc_comment
+    )]
+    pub docs_update: Option<Vec<DocsCategory>>,
+
+    /// Members whose documentation is updated [default: dependents]
+    ///
+    /// package:    the renamed package only
+    /// dependents: the renamed package and members depending on it
+    /// workspace:  every workspace member
+    #[arg(long, value_name = "SCOPE", verbatim_doc_comment)]
+    pub docs_scope: Option<DocsScope>,
+
+    /// Only retarget dependencies of these kinds: normal, dev, build
+    ///
+    /// Sources are filtered to match: build.rs for build, tests/, benches/
+    /// and examples/ for dev, everything else for normal. The renamed
+    /// package itself is always updated in full.
+    #[arg(
+        long,
+        value_name = "KINDS",
+        value_delimiter = ',',
+        verbatim_doc_comment
+    )]
+    pub only_kinds: Vec<DepKind>,
+
+    /// Leave out summary categories, e.g. `docs,configs`
+    ///
+    /// Categories: dependencies, source, docs, configs, as listed in the
+    /// summary (--dry-run shows what each one covers). The package manifest
+    /// and the directory move are always applied. Skipping dependencies
+    /// fails if any dependent manifest would change.
+    #[arg(
+        long,
+        value_name = "CATEGORIES",
+        value_delimiter = ',',
+        verbatim_doc_comment
+    )]
+    pub skip_category: Vec<Category>,
+
+    /// Match the crate name in documentation case-insensitively
+    ///
+    /// Preserves the casing of each occurrence (Old-crate → New-crate).
+    #[arg(long)]
+    pub ignore_case_docs: bool,
+
+    /// Check that every edited Cargo.toml still parses before committing
+    #[arg(long)]
+    pub experimental_ast_manifest_check: bool,
+
+    /// Keep the modification time of rewritten documentation files
+    ///
+    /// Avoids rebuilds for doc-only edits (.md, .markdown, .txt). Crates that
+    /// include such files via include_str! will not notice the change.
+    #[arg(long)]
+    pub preserve_mtime: bool,
+
+    /// Additional tool config file to update (repeatable)
+    ///
+    /// deny.toml, about.toml, release.toml, release-plz.toml,
+    /// .config/nextest.toml, .cargo/config.toml, codecov.yml and
+    /// tarpaulin.toml are always checked.
+    #[arg(long = "tool-config", value_name = "PATH")]
+    pub tool_configs: Vec<PathBuf>,
+
+    /// Note the rename in the package's CHANGELOG.md, under Unreleased
+    #[arg(long)]
+    pub changelog_entry: bool,
+
+    /// Additional workspace fragment to update like the root manifest (repeatable)
+    ///
+    /// For members lists generated into a separate file, relative to the
+    /// workspace root. Also read from `fragments` in
+    /// [workspace.metadata.cargo-rename].
+    #[arg(long = "workspace-fragment", value_name = "PATH", verbatim_doc_comment)]
+    pub workspace_fragments: Vec<PathBuf>,
+
+    /// Skip the rename hooks configured in [workspace.metadata.cargo-rename]
+    #[arg(long)]
+    pub no_hooks: bool,
+
+    /// Run the commands configured in [workspace.metadata.cargo-rename]
+    ///
+    /// Rename hooks and the naming policy command come from the repository,
+    /// so they are skipped with a warning unless this is set. Hooks run
+    /// once the rename is confirmed.
+    #[arg(long)]
+    pub allow_commands: bool,
+
+    /// Rewrite dependents' version requirements to match the renamed package
+    ///
+    /// Without this flag, mismatched requirements are only reported.
+    #[arg(long)]
+    pub sync_versions: bool,
+
+    /// Set the renamed package's version, e.g. to restart at 0.1.0
+    ///
+    /// Dependents' `path` dependencies with a version requirement that no
+    /// longer matches are updated to VERSION, as with --sync-versions.
+    #[arg(long, value_name = "VERSION", verbatim_doc_comment)]
+    pub set_version: Option<cargo_metadata::semver::Version>,
+
+    /// How to write dependents' moved `path` values
+    ///
+    /// preserve: keep each value's style (shortest, via the workspace root,
+    ///           or absolute)
+    /// relative: shortest path relative to the manifest (`../a`)
+    /// from-root: up to the workspace root, then down (`../../crates/a`)
+    #[arg(
+        long,
+        value_enum,
+        value_name = "STYLE",
+        default_value_t = PathStyle::Preserve,
+        hide_possible_values = true,
+        verbatim_doc_comment
+    )]
+    pub path_style: PathStyle,
+
+    /// Fail if a workspace member is not checked out
+    ///
+    /// By default, members missing on disk (sparse checkouts) are skipped
+    /// and listed in the summary.
+    #[arg(long)]
+    pub strict_materialization: bool,
+
+    /// Replace the old name in the package's repository URL, or set it to URL
+    ///
+    /// Only whole path segments are replaced:
+    ///   https://github.com/acme/old-crate → https://github.com/acme/new-crate
+    #[arg(long, value_name = "URL", verbatim_doc_comment)]
+    pub set_repository: Option<Option<String>>,
+
+    /// Replace the old name in the package's homepage URL, or set it to URL
+    #[arg(long, value_name = "URL")]
+    pub set_homepage: Option<Option<String>>,
+
+    /// Replace the old name in the package's documentation URL, or set it to URL
+    #[arg(long, value_name = "URL")]
+    pub set_documentation: Option<Option<String>>,
+
+    /// Replace the old name in the package's description and keywords
+    ///
+    /// Whole words only, in the old-crate and old_crate forms.
+    #[arg(long, verbatim_doc_comment)]
+    pub update_description: bool,
+
+    /// Also rename targets named after the package
+    ///
+    /// Covers [lib], [[bin]], [[example]], [[test]] and [[bench]] names and
+    /// package.default-run. By default binaries keep their names.
+    #[arg(long, verbatim_doc_comment)]
+    pub rename_targets: bool,
+
+    /// Record local usage statistics in .cargo-rename/stats.json
+    #[arg(long)]
+    pub record_stats: bool,
+
+    /// Do not record the rename in .cargo-rename/history.json
+    ///
+    /// The history is never recorded when the CI environment variable is set.
+    #[arg(long)]
+    pub no_history: bool,
+
+    /// Exit with an error if the rename would change nothing
+    ///
+    /// By default, a rename to the current name and directory prints a note
+    /// and succeeds.
+    #[arg(long, verbatim_doc_comment)]
+    pub fail_if_noop: bool,
+
+    /// Print a JSON summary as the only output on stdout
+    ///
+    /// The report and prompts go to stderr instead, so stdout parses as
+    /// one JSON object. `status` is "applied", "dry-run" or "noop". The format is versioned
+    /// by `schema_version`; `cargo rename schema` prints its JSON schema.
+    #[arg(long, verbatim_doc_comment)]
+    pub json: bool,
+
+    /// Stop after a phase of the rename, for debugging
+    ///
+    /// plan:   print the plan and exit before staging any edit
+    /// stage:  stage all edits and write them to .cargo-rename/transaction.json
+    /// commit: apply the edits, then skip verification, history and
+    ///         --notify-external manifests
+    #[arg(long, value_name = "PHASE", verbatim_doc_comment)]
+    pub stop_after: Option<StopAfter>,
+
+    /// Run CMD after the rename and report edits it reverted (repeatable)
+    ///
+    /// For formatters and pre-commit hooks that may undo the rename:
+    ///   --run-hooks "cargo fmt" --run-hooks "pre-commit run --all-files"
+    /// Advisory only: changed files are listed, nothing is rolled back.
+    #[arg(long, value_name = "CMD", verbatim_doc_comment)]
+    pub run_hooks: Vec<String>,
+
+    /// Write every source rule replacement to FILE as JSON lines
+    ///
+    /// One line per match: file, rule, byte span, text before and after.
+    /// Replay or revert a file's edits with `cargo rename replay-trace`.
+    /// With -vvvv, the edits are also logged.
+    #[arg(long, value_name = "FILE", verbatim_doc_comment)]
+    pub trace_edits: Option<PathBuf>,
+}
+
+/// Phase after which `--stop-after` halts the rename.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StopAfter {
+    /// After preflight checks, before staging
+    Plan,
+    /// After staging, before confirmation and commit
+    Stage,
+    /// After committing, before verification
+    Commit,
+}
+
+/// File name of the transaction dump inside [`stats::STATE_DIR`].
+pub const TRANSACTION_FILE: &str = "transaction.json";
+
+/// Staged operations written by `--stop-after stage`.
+#[derive(Debug, Serialize)]
+struct TransactionDump<'a> {
+    old_name: &'a str,
+    new_name: &'a str,
+    old_dir: String,
+    new_dir: String,
+    operations: Vec<DumpedOperation<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "op", rename_all = "kebab-case")]
+enum DumpedOperation<'a> {
+    UpdateFile {
+        path: String,
+        original: &'a str,
+        new: &'a str,
+    },
+    MoveDirectory {
+        from: String,
+        to: String,
+    },
+}
+
+impl<'a> TransactionDump<'a> {
+    fn new(
+        txn: &'a Transaction,
+        old_name: &'a str,
+        new_name: &'a str,
+        old_dir: &Path,
+        new_dir: &Path,
+    ) -> Self {
+        let operations = txn
+            .operations()
+            .iter()
+            .map(|op| match op {
+                Operation::UpdateFile {
+                    path,
+                    original,
+                    new,
+                } => DumpedOperation::UpdateFile {
+                    path: display_path(path),
+                    original,
+                    new,
+                },
+                Operation::MoveDirectory { from, to } => DumpedOperation::MoveDirectory {
+                    from: display_path(from),
+                    to: display_path(to),
+                },
+            })
+            .collect();
+
+        Self {
+            old_name,
+            new_name,
+            old_dir: display_path(old_dir),
+            new_dir: display_path(new_dir),
+            operations,
+        }
+    }
+
+    /// Writes the dump to `.cargo-rename/transaction.json` and returns its path.
+    fn save(&self, workspace_root: &Path) -> Result<PathBuf> {
+        let path = workspace_root.join(stats::STATE_DIR).join(TRANSACTION_FILE);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| anyhow::anyhow!("Failed to serialize transaction: {}", e))?;
+        std::fs::write(&path, content + "\n")?;
+        Ok(path)
+    }
+}
+
+/// Version of the `--json` summary format.
+///
+/// Bumped when a field is removed, renamed or changes meaning. New fields may
+/// be added without a bump, so consumers should ignore unknown ones.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// JSON summary printed with `--json`.
+///
+/// Paths are shown as in human output: relative to the workspace root unless
+/// `--absolute-paths` is set.
+#[derive(Debug, Serialize, JsonSchema)]
+#[schemars(title = "cargo-rename summary")]
+struct Summary<'a> {
+    /// Version of this format, see `cargo rename schema`.
+    schema_version: u32,
+    status: SummaryStatus,
+    old_name: &'a str,
+    new_name: &'a str,
+    /// Package directory before the rename.
+    old_dir: String,
+    /// Package directory after the rename.
+    new_dir: String,
+    files_updated: usize,
+    dirs_moved: usize,
+    /// Changed files per owning package.
+    packages: Vec<PackageSummary>,
+    /// Source rewrite rules that changed each Rust file.
+    rules: BTreeMap<String, Vec<String>>,
+}
+
+/// Outcome reported in the summary.
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+enum SummaryStatus {
+    /// The edits were written.
+    Applied,
+    /// `--dry-run`: the edits were computed but not written.
+    DryRun,
+    /// The package already has the requested name and directory.
+    Noop,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct PackageSummary {
+    /// `null` for files outside every package.
+    name: Option<String>,
+    files: Vec<String>,
+}
+
+/// Returns the JSON schema of the `--json` summary.
+pub fn summary_schema() -> schemars::Schema {
+    schemars::schema_for!(Summary<'static>)
+}
+
+/// Returns the rules recorded on `txn`, keyed by display path.
+fn rule_summary(txn: &Transaction) -> BTreeMap<String, Vec<String>> {
+    txn.matched_rules()
+        .iter()
+        .map(|(path, rules)| (display_path(path), rules.clone()))
+        .collect()
+}
+
+impl PackageSummary {
+    fn from_txn(txn: &Transaction) -> Vec<Self> {
+        txn.changes_by_package()
+            .into_iter()
+            .map(|group| Self {
+                name: group.package,
+                files: group.files.iter().map(|f| display_path(f)).collect(),
+            })
+            .collect()
+    }
+}
+
+impl Summary<'_> {
+    fn print(&self) -> Result<()> {
+        let json = serde_json::to_string(self)
+            .map_err(|e| anyhow::anyhow!("Failed to serialize summary: {}", e))?;
+        console::write_result(json);
+        Ok(())
+    }
 }
 
 impl RenameArgs {
     /// Returns `true` if package should be moved.
     pub fn should_move(&self) -> bool {
-        self.outdir.is_some()
+        self.outdir.is_some() || self.move_relative.is_some()
+    }
+
+    /// Returns `true` if only manifests and the directory move are handled
+    /// (`--manifests-only` or `--emit-ssr`).
+    pub fn skips_sources(&self) -> bool {
+        self.manifests_only || self.emit_ssr
     }
 
     /// Returns the effective new name (defaults to old_name if not specified).
@@ -59,10 +705,32 @@ impl RenameArgs {
         self.new_name.as_deref().unwrap_or(&self.old_name)
     }
 
+    /// Moves the positional arguments into place when `--at` selects the package.
+    ///
+    /// Clap fills `OLD_NAME` first, so `--at DIR NEW_NAME` arrives with the new
+    /// name in `old_name`. It stays empty until the package is resolved.
+    pub fn shift_positionals_for_at(&mut self) -> Result<()> {
+        if self.at.is_none() {
+            return Ok(());
+        }
+
+        if self.new_name.is_some() {
+            return Err(RenameError::Other(anyhow::anyhow!(
+                "--at replaces OLD_NAME; pass only NEW_NAME"
+            )));
+        }
+
+        if !self.old_name.is_empty() {
+            self.new_name = Some(std::mem::take(&mut self.old_name));
+        }
+
+        Ok(())
+    }
+
     /// Validates the arguments are coherent.
     pub fn validate(&self) -> Result<()> {
         // Case 1: Neither name nor move specified
-        if self.new_name.is_none() && self.outdir.is_none() {
+        if self.new_name.is_none() && !self.should_move() {
             return Err(RenameError::Other(anyhow::anyhow!(
                 "Must specify either NEW_NAME or --move DIR"
             )));
@@ -75,352 +743,2379 @@ impl RenameArgs {
             )));
         }
 
-        Ok(())
+        Ok(())
+    }
+
+    /// Calculates the new directory path.
+    ///
+    /// Returns `None` if package stays in same directory.
+    ///
+    /// ## Behavior
+    /// - `--move`: Renames directory to `effective_new_name()` in same parent
+    /// - `--move <path>`: Moves to `workspace_root/<path>`
+    /// - `--move-relative <path>`: Moves to `<path>` in the same parent, unless
+    ///   already turned into `--move` by `resolve_relative_move`
+    pub fn calculate_new_dir(&self, old_dir: &Path, workspace_root: &Path) -> Option<PathBuf> {
+        if !self.should_move() {
+            return None;
+        }
+        if let Some(dir) = &self.move_relative {
+            return Some(old_dir.parent().unwrap_or(workspace_root).join(dir));
+        }
+
+        Some(match &self.outdir {
+            Some(Some(custom_path)) => workspace_root.join(custom_path),
+            Some(None) => {
+                // Use effective new name (handles rename-only and move-only cases)
+                old_dir
+                    .parent()
+                    .unwrap_or(workspace_root)
+                    .join(self.effective_new_name())
+            }
+            None => unreachable!(),
+        })
+    }
+
+    /// Returns the manifest the workspace was loaded from: `--manifest-path`,
+    /// or the root manifest found by searching upward.
+    pub fn manifest_used(&self, workspace_root: &Path) -> PathBuf {
+        self.manifest_path
+            .clone()
+            .unwrap_or_else(|| workspace_root.join("Cargo.toml"))
+    }
+
+    /// Returns the `[package]` URL updates requested with `--set-*`.
+    pub fn url_updates(&self) -> Vec<(&'static str, UrlUpdate)> {
+        [
+            ("repository", &self.set_repository),
+            ("homepage", &self.set_homepage),
+            ("documentation", &self.set_documentation),
+        ]
+        .into_iter()
+        .filter_map(|(field, flag)| {
+            let update = match flag.as_ref()? {
+                Some(url) => UrlUpdate::Set(url.clone()),
+                None => UrlUpdate::Rewrite,
+            };
+            Some((field, update))
+        })
+        .collect()
+    }
+
+    /// Returns the documentation rewrite options selected by `--docs-update`,
+    /// `--docs-scope` and `--ignore-case-docs`.
+    pub fn doc_options(&self) -> DocOptions {
+        DocOptions {
+            ignore_case: self.ignore_case_docs,
+            scope: self.docs_scope.unwrap_or_default(),
+            ..DocOptions::from_categories(self.docs_update.as_deref())
+        }
+    }
+
+    /// Returns the manifest update for renaming the package to `new_name`
+    /// in `new_dir`, with the selected `--path-style` and `--only-kinds`.
+    pub fn manifest_update<'a>(
+        &'a self,
+        new_name: &'a str,
+        old_dir: &'a Path,
+        new_dir: &'a Path,
+        workspace_root: &'a Path,
+    ) -> ManifestUpdate<'a> {
+        ManifestUpdate {
+            path_style: self.path_style,
+            kinds: &self.only_kinds,
+            ..ManifestUpdate::new(&self.old_name, new_name, old_dir, new_dir, workspace_root)
+        }
+    }
+
+    /// Check if operation would actually change anything.
+    pub fn would_change_anything(&self, current_dir: &Path, workspace_root: &Path) -> Result<bool> {
+        let name_changed = self
+            .new_name
+            .as_ref()
+            .map(|n| n != &self.old_name)
+            .unwrap_or(false);
+
+        let path_changed =
+            if let Some(new_dir) = self.calculate_new_dir(current_dir, workspace_root) {
+                new_dir != current_dir
+            } else {
+                false
+            };
+
+        Ok(name_changed || path_changed)
+    }
+}
+
+/// Executes a package rename operation.
+///
+/// ## Phases
+///
+/// 1. Validate arguments
+/// 2. Load metadata via `cargo metadata`
+/// 3. Pre-flight checks (validation, git status)
+/// 4. Stage operations in transaction
+/// 5. User confirmation with the staged file counts (unless `--yes`)
+/// 6. Commit atomically
+/// 7. Verify workspace with `cargo metadata`
+///
+/// Returns error if any phase fails. Attempts rollback if commit fails.
+pub fn execute(mut args: RenameArgs) -> Result<()> {
+    args.shift_positionals_for_at()?;
+    execute_selected(args)
+}
+
+/// Runs [`execute`] once `--at` no longer holds a positional argument, i.e.
+/// `new_name` is the new name with and without `--at`.
+pub(crate) fn execute_selected(mut args: RenameArgs) -> Result<()> {
+    args.validate()?;
+    if args.manifest_path.is_none()
+        && let Some(root) = &args.workspace_root
+    {
+        args.manifest_path = Some(root.join("Cargo.toml"));
+    }
+
+    if args.dry_run
+        && args.manifests_only
+        && args.stop_after.is_none()
+        && !args.should_move()
+        && args.at.is_none()
+        && args.workspace_fragments.is_empty()
+        && args.skip_category.is_empty()
+        && args.workspace_root.is_none()
+        && args.scan_dirs.is_empty()
+        && let Some(workspace) = ManifestWorkspace::load(args.manifest_path.as_deref())
+        && workspace.package(&args.old_name).is_some()
+    {
+        return preview_manifests_only(&args, &workspace);
+    }
+
+    if let Some(at) = args.at.clone() {
+        let manifest = package_manifest(&at)?;
+        // Load the workspace the package belongs to, not the one above the cwd
+        let metadata = load_metadata(Some(
+            args.manifest_path.as_deref().unwrap_or(manifest.as_path()),
+        ))?;
+        args.old_name = resolve_package_at(&at, &metadata)?;
+        log::info!("Selected '{}' at {}", args.old_name, display_path(&at));
+        return execute_resolved(args, metadata);
+    }
+
+    let metadata = load_metadata(args.manifest_path.as_deref())?;
+    args.old_name = match resolve_old_name(&args, &metadata) {
+        Ok(name) => name,
+        Err(e @ (RenameError::PackageNotFound(..) | RenameError::PackageRenamed(..))) => {
+            // Report the lookup failure (with its hints) alongside the other checks
+            let mut errors = collect_preflight_errors(&args, &metadata);
+            errors.retain(|err| !matches!(err, RenameError::PackageNotFound(..)));
+            errors.insert(0, e);
+            return RenameError::aggregate(errors);
+        }
+        Err(e) => return Err(e),
+    };
+
+    execute_resolved(args, metadata)
+}
+
+/// Runs the rename once `OLD_NAME` names a package in `metadata`.
+fn execute_resolved(mut args: RenameArgs, metadata: cargo_metadata::Metadata) -> Result<()> {
+    check_workspace_root(&args, &metadata)?;
+    let config = WorkspaceConfig::load(&metadata)?;
+    apply_naming_policy(&mut args, &metadata, &config)?;
+    resolve_relative_move(&mut args, &metadata)?;
+
+    // Report a no-op before preflight, so e.g. a dirty tree doesn't turn it into an error
+    if let Some(pkg) = metadata
+        .workspace_packages()
+        .into_iter()
+        .find(|p| p.name == args.old_name)
+    {
+        let old_dir = pkg.manifest_path.parent().unwrap().as_std_path();
+        if !args.would_change_anything(old_dir, metadata.workspace_root.as_std_path())? {
+            return report_noop(&args, old_dir);
+        }
+    }
+
+    preflight_checks(&args, &metadata)?;
+
+    if args.sandbox {
+        rehearse_in_sandbox(&args, &metadata)?;
+        if args.dry_run {
+            return Ok(());
+        }
+    }
+
+    if args.stop_after == Some(StopAfter::Plan) {
+        let old_dir = package_dir(&args, &metadata)?;
+        let workspace_root = metadata.workspace_root.as_std_path();
+        let new_dir = args
+            .calculate_new_dir(&old_dir, workspace_root)
+            .unwrap_or_else(|| old_dir.clone());
+        report_plan(&args, workspace_root, &old_dir, &new_dir);
+        return Ok(());
+    }
+
+    let staged = stage_rename(args, &metadata, &config)?;
+
+    if staged.args.stop_after == Some(StopAfter::Stage) {
+        let dump = TransactionDump::new(
+            &staged.txn,
+            &staged.args.old_name,
+            staged.new_name(),
+            &staged.old_dir,
+            &staged.new_dir,
+        );
+        let path = dump.save(&staged.workspace_root)?;
+        outln!(
+            "{} Staged {} operations, written to {} (--stop-after stage)",
+            symbols::info().blue().bold(),
+            staged.txn.len(),
+            display_path(&path)
+        );
+        staged.discard();
+        return Ok(());
+    }
+
+    // Everything is staged in memory, so declining is cheap
+    let external = staged.external.iter().map(|(_, t)| t.len()).sum();
+    if !staged.hooks.is_empty() {
+        outln!(
+            "{} {} rename hook(s) will run on their files once confirmed",
+            symbols::info().blue().bold(),
+            staged.hooks.len()
+        );
+    }
+    if !confirm_operation(&staged.args, &staged.workspace_root, &staged.txn, external)? {
+        staged.discard();
+        outln!("\n{}", "Operation cancelled.".yellow());
+        return Err(RenameError::Cancelled);
+    }
+
+    apply_rename(staged)?;
+    Ok(())
+}
+
+/// A rename staged in memory by [`stage_rename`], written by
+/// [`apply_rename`].
+pub(crate) struct StagedRename {
+    pub(crate) args: RenameArgs,
+    pub(crate) workspace_root: PathBuf,
+    pub(crate) old_dir: PathBuf,
+    pub(crate) new_dir: PathBuf,
+    pub(crate) txn: Transaction,
+    /// `--notify-external` and enclosing workspace updates, by directory.
+    pub(crate) external: Vec<(PathBuf, Transaction)>,
+    /// Rename hooks to run once the rename is confirmed.
+    pub(crate) hooks: Vec<HookConfig>,
+}
+
+impl StagedRename {
+    pub(crate) fn new_name(&self) -> &str {
+        self.args.effective_new_name()
+    }
+
+    /// Discards the staged changes.
+    pub(crate) fn discard(self) {
+        self.txn.discard();
+        for (_, external) in self.external {
+            external.discard();
+        }
+    }
+}
+
+/// Outcome of [`apply_rename`].
+pub(crate) struct AppliedRename {
+    pub(crate) files_updated: usize,
+    pub(crate) dirs_moved: usize,
+    /// Result of re-loading the workspace, unless nothing was written.
+    pub(crate) verification: Option<VerificationOutcome>,
+}
+
+/// Returns the directory of the package `args.old_name`.
+fn package_dir(args: &RenameArgs, metadata: &cargo_metadata::Metadata) -> Result<PathBuf> {
+    // Not `metadata.packages`: a crates.io crate may share the name
+    metadata
+        .workspace_packages()
+        .into_iter()
+        .find(|p| p.name == args.old_name)
+        .map(|p| {
+            p.manifest_path
+                .parent()
+                .unwrap()
+                .as_std_path()
+                .to_path_buf()
+        })
+        .ok_or_else(|| RenameError::PackageNotFound(args.old_name.clone(), Vec::new()))
+}
+
+/// Stages the rename of a resolved and preflight-checked package: the
+/// workspace edits, the external dependents and `--edit-conflicts` edits.
+///
+/// Shared by the CLI and the library API, which confirm the result their
+/// own way before passing it to [`apply_rename`].
+pub(crate) fn stage_rename(
+    args: RenameArgs,
+    metadata: &cargo_metadata::Metadata,
+    config: &WorkspaceConfig,
+) -> Result<StagedRename> {
+    let old_dir = package_dir(&args, metadata)?;
+    let old_manifest_path = old_dir.join("Cargo.toml");
+    let workspace_root = metadata.workspace_root.as_std_path();
+
+    log::debug!("Package '{}' at: {}", args.old_name, display_path(&old_dir));
+
+    let new_dir = args
+        .calculate_new_dir(&old_dir, workspace_root)
+        .unwrap_or_else(|| old_dir.clone());
+
+    log::debug!("New directory: {}", display_path(&new_dir));
+
+    let new_name = args.effective_new_name();
+    let mut txn = Transaction::new(args.dry_run);
+    txn.preserve_doc_mtimes(args.preserve_mtime);
+    txn.set_packages(package_dirs(metadata));
+
+    let update = args.manifest_update(new_name, &old_dir, &new_dir, workspace_root);
+    if args.manifests_only && !args.dry_run && update.name_changed {
+        let msg = format!(
+            "--manifests-only leaves Rust sources and docs referring to '{}'; the workspace may not build until they are updated",
+            args.old_name.replace('-', "_")
+        );
+        errln!("{} {}", symbols::warning().yellow().bold(), msg.yellow());
+        annotate::warning(None, None, &msg);
+    }
+
+    if let Err(e) = stage_rename_operations(
+        &args,
+        metadata,
+        config,
+        &old_manifest_path,
+        &update,
+        &mut txn,
+    ) {
+        return handle_staging_error(e, txn, args.dry_run);
+    }
+
+    let external =
+        match stage_external_dependents(&args, metadata, new_name, &old_dir, &new_dir, &txn) {
+            Ok(external) => external,
+            Err(e) => return handle_staging_error(e, txn, args.dry_run),
+        };
+
+    let hooks = confirmed_hooks(&args, config);
+
+    let mut staged = StagedRename {
+        args,
+        workspace_root: workspace_root.to_path_buf(),
+        old_dir,
+        new_dir,
+        txn,
+        external,
+        hooks,
+    };
+
+    if staged.args.edit_conflicts && !staged.args.dry_run {
+        let conflicts = staged.txn.conflicts().to_vec();
+        match edit_conflicts(&conflicts, &staged.args.old_name, &mut staged.txn) {
+            Ok(outcomes) => report_conflict_edits(&conflicts, &outcomes),
+            Err(e) => {
+                let dry_run = staged.args.dry_run;
+                for (_, external) in staged.external {
+                    external.discard();
+                }
+                return handle_staging_error(e, staged.txn, dry_run);
+            }
+        }
+    }
+
+    Ok(staged)
+}
+
+/// Returns the configured rename hooks if `--allow-commands` is set, and
+/// warns about the skipped ones otherwise.
+fn confirmed_hooks(args: &RenameArgs, config: &WorkspaceConfig) -> Vec<HookConfig> {
+    if args.no_hooks || args.skips_sources() || config.hooks.is_empty() {
+        return Vec::new();
+    }
+
+    if !args.allow_commands {
+        let msg = format!(
+            "Skipping {} rename hook(s) configured in [workspace.metadata.cargo-rename]; pass --allow-commands to run them",
+            config.hooks.len()
+        );
+        errln!("{} {}", symbols::warning().yellow().bold(), msg.yellow());
+        annotate::warning(None, None, &msg);
+        return Vec::new();
+    }
+
+    config.hooks.clone()
+}
+
+/// Writes a staged rename, then records and reports it: the edit trace,
+/// hook drift, verification (rolled back if `--verify` fails), history,
+/// stats, the summary and the external dependents. Rename hooks are run
+/// and staged first.
+pub(crate) fn apply_rename(staged: StagedRename) -> Result<AppliedRename> {
+    let StagedRename {
+        args,
+        workspace_root,
+        old_dir,
+        new_dir,
+        mut txn,
+        external,
+        hooks,
+    } = staged;
+    let workspace_root = workspace_root.as_path();
+    let new_name = args.effective_new_name();
+
+    if !hooks.is_empty() {
+        log::info!("Running rename hooks...");
+        let context = HookContext {
+            old_name: &args.old_name,
+            new_name,
+            old_dir: &old_dir,
+            new_dir: &new_dir,
+        };
+        if let Err(e) = run_hooks(&hooks, workspace_root, context, &mut txn) {
+            for (_, external_txn) in external {
+                external_txn.discard();
+            }
+            return handle_staging_error(e, txn, args.dry_run);
+        }
+        if !args.skip_category.is_empty() {
+            txn.drop_categories(&args.skip_category);
+        }
+    }
+
+    // `cargo metadata` and the verify command rewrite the lockfile for the
+    // new name, outside the transaction
+    let lockfile = workspace_root.join("Cargo.lock");
+    let lockfile_before = if args.verify.is_some() && !args.dry_run {
+        Some(std::fs::read(&lockfile).ok())
+    } else {
+        None
+    };
+
+    if let Err(e) = txn.commit() {
+        if args.record_stats && !args.dry_run {
+            stats::record(workspace_root, |s| s.rollbacks += 1);
+        }
+        return handle_commit_error(e);
+    }
+
+    if let Some(path) = &args.trace_edits {
+        write_trace(path, workspace_root, &txn)?;
+        log::info!("Wrote edit trace to {}", display_path(path));
+    }
+
+    let applied = |txn: &Transaction, verification| {
+        let stats = txn.stats();
+        AppliedRename {
+            files_updated: stats.files_updated,
+            dirs_moved: stats.dirs_moved,
+            verification,
+        }
+    };
+
+    if args.stop_after == Some(StopAfter::Commit) {
+        for (_, external_txn) in external {
+            external_txn.discard();
+        }
+        outln!(
+            "{} Committed {} operations; skipped verification, history and external manifests (--stop-after commit)",
+            symbols::info().blue().bold(),
+            txn.len()
+        );
+        return Ok(applied(&txn, None));
+    }
+
+    if !args.dry_run && !args.run_hooks.is_empty() {
+        report_hook_drift(&args.run_hooks, workspace_root, &txn);
+    }
+
+    let verification = if args.dry_run {
+        None
+    } else {
+        report_verification(workspace_root, old_dir != new_dir)
+    };
+
+    if !args.dry_run
+        && let Some(command) = args.verify
+        && let Err(e) = verify_or_roll_back(command, workspace_root, &mut txn)
+    {
+        if let Some(before) = lockfile_before {
+            restore_lockfile(&lockfile, before);
+        }
+        for (_, external_txn) in external {
+            external_txn.discard();
+        }
+        if let Some(path) = &args.trace_edits {
+            let _ = std::fs::remove_file(path);
+        }
+        if args.record_stats {
+            stats::record(workspace_root, |s| s.rollbacks += 1);
+        }
+        return Err(e);
+    }
+
+    if !args.dry_run && history::enabled(args.no_history) {
+        history::record(
+            workspace_root,
+            vec![HistoryEntry::new(
+                workspace_root,
+                &args.old_name,
+                new_name,
+                &old_dir,
+                &new_dir,
+            )],
+        );
+    }
+
+    if args.record_stats && !args.dry_run {
+        stats::record(workspace_root, |s| {
+            s.renames += 1;
+            s.add_transaction(txn.stats());
+        });
+    }
+
+    txn.print_summary(&args.old_name, new_name, workspace_root);
+
+    if !args.dry_run {
+        outln!(
+            "\n{} {} {} {}",
+            format!("{} Successfully renamed", symbols::check())
+                .green()
+                .bold(),
+            args.old_name.yellow(),
+            symbols::arrow(),
+            new_name.green().bold()
+        );
+    }
+
+    report_directory_name(&args, new_name, &new_dir);
+    commit_external_dependents(external);
+
+    if args.json {
+        let stats = txn.stats();
+        Summary {
+            schema_version: SCHEMA_VERSION,
+            status: if args.dry_run {
+                SummaryStatus::DryRun
+            } else {
+                SummaryStatus::Applied
+            },
+            old_name: &args.old_name,
+            new_name,
+            old_dir: display_path(&old_dir),
+            new_dir: display_path(&new_dir),
+            files_updated: stats.files_updated,
+            dirs_moved: stats.dirs_moved,
+            packages: PackageSummary::from_txn(&txn),
+            rules: rule_summary(&txn),
+        }
+        .print()?;
+    }
+
+    Ok(applied(&txn, verification))
+}
+
+/// Prints what a rename would do, for `--stop-after plan`.
+fn report_plan(args: &RenameArgs, workspace_root: &Path, old_dir: &Path, new_dir: &Path) {
+    outln!("{}", "Rename Plan:".bold().cyan());
+    outln!(
+        "  {} {}",
+        "Workspace:".bold(),
+        display_from_cwd(workspace_root)
+    );
+    outln!(
+        "  {} {}",
+        "Manifest:".bold(),
+        display_from_cwd(&args.manifest_used(workspace_root))
+    );
+    outln!(
+        "  {} {} {} {}",
+        "Package:".bold(),
+        args.old_name.yellow(),
+        symbols::arrow(),
+        args.effective_new_name().green()
+    );
+    if old_dir == new_dir {
+        outln!("  {} {}", "Directory:".bold(), display_path(old_dir));
+    } else {
+        outln!(
+            "  {} {} {} {}",
+            "Directory:".bold(),
+            display_path(old_dir).yellow(),
+            symbols::arrow(),
+            display_path(new_dir).green()
+        );
+    }
+    let scope = args.doc_options().scope;
+    outln!(
+        "  {} {} ({})",
+        "Docs:".bold(),
+        scope.name(),
+        scope.description()
+    );
+    outln!(
+        "\n{} Nothing was staged (--stop-after plan)",
+        symbols::info().blue().bold()
+    );
+}
+
+/// Fails if `--workspace-root` pins another root than `metadata` resolved.
+pub(crate) fn check_workspace_root(
+    args: &RenameArgs,
+    metadata: &cargo_metadata::Metadata,
+) -> Result<()> {
+    let Some(pinned) = &args.workspace_root else {
+        return Ok(());
+    };
+    let detected = metadata.workspace_root.as_std_path();
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| normalize_path(path));
+
+    if canonical(pinned) == canonical(detected) {
+        Ok(())
+    } else {
+        Err(RenameError::WorkspaceRootMismatch(
+            pinned.clone(),
+            detected.to_path_buf(),
+        ))
+    }
+}
+
+/// Returns `(name, directory)` of every workspace package.
+pub(crate) fn package_dirs(metadata: &cargo_metadata::Metadata) -> Vec<(String, PathBuf)> {
+    metadata
+        .workspace_packages()
+        .iter()
+        .map(|p| {
+            (
+                p.name.to_string(),
+                p.manifest_path
+                    .parent()
+                    .unwrap()
+                    .as_std_path()
+                    .to_path_buf(),
+            )
+        })
+        .collect()
+}
+
+/// Reports a rename that would change nothing.
+///
+/// Prints a note and succeeds, or fails with `NothingToDo` under
+/// `--fail-if-noop`. Either way, `--json` reports the status `noop`.
+fn report_noop(args: &RenameArgs, dir: &Path) -> Result<()> {
+    let message = format!(
+        "'{}' is already named '{}' at '{}'",
+        args.old_name,
+        args.effective_new_name(),
+        display_path(dir)
+    );
+
+    if !args.fail_if_noop {
+        outln!("{}", format!("No changes needed: {}", message).yellow());
+    }
+    if args.json {
+        Summary {
+            schema_version: SCHEMA_VERSION,
+            status: SummaryStatus::Noop,
+            old_name: &args.old_name,
+            new_name: args.effective_new_name(),
+            old_dir: display_path(dir),
+            new_dir: display_path(dir),
+            files_updated: 0,
+            dirs_moved: 0,
+            packages: Vec::new(),
+            rules: BTreeMap::new(),
+        }
+        .print()?;
+    }
+
+    if args.fail_if_noop {
+        return Err(RenameError::NothingToDo(message));
+    }
+    Ok(())
+}
+
+/// Replaces `NEW_NAME` with the name required by the workspace naming policy.
+///
+/// The policy command only runs with `--allow-commands`.
+///
+/// # Errors
+///
+/// `InvalidName` with the policy's reason if the name is rejected.
+pub(crate) fn apply_naming_policy(
+    args: &mut RenameArgs,
+    metadata: &cargo_metadata::Metadata,
+    config: &WorkspaceConfig,
+) -> Result<()> {
+    let Some(new_name) = args.new_name.as_deref() else {
+        return Ok(());
+    };
+    if new_name == args.old_name {
+        return Ok(());
+    }
+    let Some(mut policy) = config.naming.clone() else {
+        return Ok(());
+    };
+    if !policy.command.is_empty() && !args.allow_commands {
+        let msg = "Skipping the naming policy command configured in [workspace.metadata.cargo-rename]; pass --allow-commands to run it";
+        errln!("{} {}", symbols::warning().yellow().bold(), msg.yellow());
+        annotate::warning(None, None, msg);
+        policy.command.clear();
+    }
+
+    let name = policy.apply(
+        &args.old_name,
+        new_name,
+        metadata.workspace_root.as_std_path(),
+    )?;
+    if name != new_name {
+        outln!(
+            "{} Naming policy: {} {} {}",
+            symbols::info().blue().bold(),
+            new_name.yellow(),
+            symbols::arrow(),
+            name.green()
+        );
+        args.new_name = Some(name);
+    }
+
+    Ok(())
+}
+
+/// Turns `--move-relative DIR` into the equivalent `--move`, relative to the
+/// workspace root, so the move is validated and reported like any other.
+///
+/// `..` components are resolved against the package's parent directory.
+///
+/// # Errors
+///
+/// `InvalidPath` if DIR is absolute or leaves the workspace.
+pub(crate) fn resolve_relative_move(
+    args: &mut RenameArgs,
+    metadata: &cargo_metadata::Metadata,
+) -> Result<()> {
+    let Some(dir) = args.move_relative.clone() else {
+        return Ok(());
+    };
+    // A missing package is reported by the preflight checks
+    let Some(pkg) = metadata
+        .workspace_packages()
+        .into_iter()
+        .find(|p| p.name == args.old_name)
+    else {
+        return Ok(());
+    };
+
+    let invalid = |reason: &str| RenameError::InvalidPath(display_path(&dir), reason.to_string());
+    let workspace_root = metadata.workspace_root.as_std_path();
+    let parent = pkg.manifest_path.parent().unwrap().parent().unwrap();
+    let mut target = parent
+        .as_std_path()
+        .strip_prefix(workspace_root)
+        .map_err(|_| invalid("the package is outside the workspace root"))?
+        .to_path_buf();
+
+    for component in dir.components() {
+        match component {
+            Component::Normal(name) => target.push(name),
+            Component::CurDir => {}
+            Component::ParentDir if target.pop() => {}
+            Component::ParentDir => return Err(invalid("resolves outside workspace")),
+            Component::RootDir | Component::Prefix(_) => {
+                return Err(invalid("must be relative; use --move for workspace paths"));
+            }
+        }
+    }
+    if target.as_os_str().is_empty() {
+        return Err(invalid("resolves to the workspace root"));
+    }
+
+    log::info!(
+        "--move-relative {} resolves to {}",
+        display_path(&dir),
+        display_path(&target)
+    );
+    args.outdir = Some(Some(target));
+    args.move_relative = None;
+    Ok(())
+}
+
+/// Applies the rename to a copy of the workspace and runs `cargo check` there.
+///
+/// # Errors
+///
+/// - Any error of the rename itself
+/// - `InvalidPath` if a path flag points outside the workspace
+/// - `SandboxCheckFailed` if the renamed copy does not build
+fn rehearse_in_sandbox(args: &RenameArgs, metadata: &cargo_metadata::Metadata) -> Result<()> {
+    let workspace_root = metadata.workspace_root.as_std_path();
+    let sandbox = Sandbox::create(workspace_root)?;
+    outln!(
+        "{} Rehearsing in sandbox {}",
+        symbols::info().blue().bold(),
+        sandbox.root().display()
+    );
+
+    // Path flags are resolved against the workspace root, or absolute
+    let map = |path: &PathBuf| sandbox_path(&sandbox, workspace_root, path);
+    let outdir = match &args.outdir {
+        Some(Some(dir)) => Some(Some(map(dir)?)),
+        other => other.clone(),
+    };
+    let move_relative = match &args.move_relative {
+        Some(dir) if dir.is_absolute() => Some(map(dir)?),
+        other => other.clone(),
+    };
+    let tool_configs = args.tool_configs.iter().map(map).collect::<Result<_>>()?;
+    let workspace_fragments = args
+        .workspace_fragments
+        .iter()
+        .map(map)
+        .collect::<Result<_>>()?;
+
+    let sandbox_args = RenameArgs {
+        manifest_path: Some(sandbox.map(&workspace_root.join("Cargo.toml"))),
+        outdir,
+        move_relative,
+        tool_configs,
+        workspace_fragments,
+        trace_edits: None,
+        workspace_root: None,
+        at: None,
+        sandbox: false,
+        verify: None,
+        edit_conflicts: false,
+        dry_run: false,
+        skip_confirmation: true,
+        allow_dirty: true,
+        record_stats: false,
+        json: false,
+        notify_external: Vec::new(),
+        scan_dirs: Vec::new(),
+        include_parent_workspace: false,
+        // Hooks only run once the rename itself is confirmed
+        no_hooks: true,
+        ..args.clone()
+    };
+    let result = load_metadata(sandbox_args.manifest_path.as_deref())
+        .and_then(|sandbox_metadata| execute_resolved(sandbox_args, sandbox_metadata))
+        .and_then(|()| {
+            log::info!("Running cargo check in the sandbox...");
+            let output = process::output(
+                Command::new("cargo")
+                    .args(["check", "--workspace", "--all-targets"])
+                    .current_dir(sandbox.root()),
+            )?;
+            if output.status.success() {
+                Ok(())
+            } else {
+                Err(RenameError::SandboxCheckFailed(
+                    String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                ))
+            }
+        });
+    set_display_root(workspace_root);
+
+    match &result {
+        Ok(()) => outln!(
+            "\n{} Sandbox rename and cargo check succeeded",
+            symbols::check().green().bold()
+        ),
+        Err(_) => errln!(
+            "\n{} Sandbox rehearsal failed; the workspace was not changed",
+            symbols::cross().red().bold()
+        ),
+    }
+    result
+}
+
+/// Maps a path flag of the real workspace into the sandbox.
+///
+/// Fails if the path lies outside the workspace, so a rehearsal cannot write
+/// to the real tree.
+fn sandbox_path(sandbox: &Sandbox, workspace_root: &Path, path: &Path) -> Result<PathBuf> {
+    let mapped = normalize_path(&sandbox.map(&normalize_path(&workspace_root.join(path))));
+    if mapped.starts_with(sandbox.root()) {
+        Ok(mapped)
+    } else {
+        Err(RenameError::InvalidPath(
+            path.display().to_string(),
+            "outside the workspace, so it cannot be rehearsed with --sandbox".to_string(),
+        ))
+    }
+}
+
+/// Stages the `--notify-external` updates, one transaction per directory.
+///
+/// A workspace enclosing this one is staged the same way with
+/// `--include-parent-workspace`; otherwise its dependents are reported.
+/// Must run before the main transaction is committed, while path
+/// dependencies still resolve to the old package directory.
+fn stage_external_dependents(
+    args: &RenameArgs,
+    metadata: &cargo_metadata::Metadata,
+    new_name: &str,
+    old_dir: &Path,
+    new_dir: &Path,
+    txn: &Transaction,
+) -> Result<Vec<(PathBuf, Transaction)>> {
+    let mut skip = workspace_manifests(metadata);
+    skip.extend(txn.skipped().iter().map(|(path, _)| path.clone()));
+    // Already updated with --scan-dirs
+    skip.extend(txn.staged_files());
+    let update = ManifestUpdate {
+        path_style: args.path_style,
+        ..ManifestUpdate::new(&args.old_name, new_name, old_dir, new_dir, old_dir)
+    };
+
+    let mut staged = Vec::new();
+    for dir in &args.notify_external {
+        check_external_dir(dir)?;
+
+        let manifests = find_external_dependents(dir, old_dir, &skip)?;
+        let mut external = Transaction::new(args.dry_run);
+        stage_external_manifests(&manifests, &update, &mut external)?;
+        staged.push((dir.clone(), external));
+    }
+
+    let workspace_root = metadata.workspace_root.as_std_path();
+    let Some(parent) = find_parent_workspace(workspace_root) else {
+        return Ok(staged);
+    };
+    let parent_root = parent.parent().unwrap();
+    let scanned = args
+        .notify_external
+        .iter()
+        .any(|dir| std::fs::canonicalize(dir).ok() == std::fs::canonicalize(parent_root).ok());
+    if scanned {
+        return Ok(staged);
+    }
+
+    log::debug!("Workspace is nested in {}", display_path(&parent));
+    let manifests = find_external_dependents(parent_root, old_dir, &skip)?;
+    if args.include_parent_workspace {
+        let mut external = Transaction::new(args.dry_run);
+        stage_external_manifests(&manifests, &update, &mut external)?;
+        staged.push((parent_root.to_path_buf(), external));
+    } else if !manifests.is_empty() {
+        let msg = format!(
+            "{} manifest{} in the enclosing workspace {} depend{} on '{}' by path; pass --include-parent-workspace to update {}",
+            manifests.len(),
+            if manifests.len() == 1 { "" } else { "s" },
+            display_path(parent_root),
+            if manifests.len() == 1 { "s" } else { "" },
+            args.old_name,
+            if manifests.len() == 1 { "it" } else { "them" }
+        );
+        errln!("{} {}", symbols::warning().yellow().bold(), msg.yellow());
+        for manifest in &manifests {
+            errln!("   {}", display_path(manifest).dimmed());
+        }
+        annotate::warning(Some(&parent), None, &msg);
+    }
+
+    Ok(staged)
+}
+
+/// Returns the manifests of the workspace members and the root.
+fn workspace_manifests(metadata: &cargo_metadata::Metadata) -> Vec<PathBuf> {
+    let mut manifests: Vec<PathBuf> = metadata
+        .workspace_packages()
+        .iter()
+        .map(|p| p.manifest_path.clone().into_std_path_buf())
+        .collect();
+    manifests.push(metadata.workspace_root.as_std_path().join("Cargo.toml"));
+    manifests
+}
+
+/// Fails if a `--notify-external` or `--scan-dirs` directory is missing.
+fn check_external_dir(dir: &Path) -> Result<()> {
+    if dir.is_dir() {
+        Ok(())
+    } else {
+        Err(RenameError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("External directory does not exist: {}", display_path(dir)),
+        )))
+    }
+}
+
+/// Stages the dependency updates for external manifests into `txn`, for
+/// both `--scan-dirs` and `--notify-external`.
+///
+/// `--only-kinds` applies to the workspace only: every dependency kind of an
+/// external manifest is updated.
+fn stage_external_manifests(
+    manifests: &[PathBuf],
+    update: &ManifestUpdate,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let update = ManifestUpdate {
+        kinds: &[],
+        ..*update
+    };
+    for manifest in manifests {
+        log::info!("Updating external dependent {}", display_path(manifest));
+        update_external_manifest(manifest, &update, txn)?;
+    }
+    Ok(())
+}
+
+/// Commits the `--notify-external` transactions and lists their files.
+///
+/// The rename itself is already applied, so a failed directory is reported
+/// and rolled back on its own without failing the command.
+fn commit_external_dependents(external: Vec<(PathBuf, Transaction)>) {
+    if external.is_empty() {
+        return;
+    }
+
+    outln!("\n{}", "External dependents:".bold());
+    for (dir, mut txn) in external {
+        if txn.is_empty() {
+            outln!("   {} ({})", "no references".dimmed(), display_path(&dir));
+            continue;
+        }
+
+        let changes = txn.preview();
+        match txn.commit() {
+            Ok(()) => {
+                for change in changes {
+                    outln!("   {} {}", symbols::check().green(), change);
+                }
+            }
+            Err(e) => {
+                errln!(
+                    "   {} {}: {}",
+                    symbols::cross().red(),
+                    display_path(&dir),
+                    e
+                );
+                annotate::warning(Some(&dir), None, &format!("Not updated: {}", e));
+            }
+        }
+    }
+}
+
+/// Notes how the package directory relates to the new name.
+///
+/// With `--keep-dir-name`, prints the final mapping. Otherwise hints at
+/// `--move` if the directory is still named after the old package.
+fn report_directory_name(args: &RenameArgs, new_name: &str, dir: &Path) {
+    if args.keep_dir_name {
+        outln!(
+            "\n{} Package directory: {} {} {}",
+            symbols::info().blue().bold(),
+            new_name.green(),
+            symbols::arrow(),
+            display_path(dir)
+        );
+        return;
+    }
+
+    let dir_name = dir.file_name().map(|n| n.to_string_lossy());
+    if new_name != args.old_name && dir_name.as_deref() == Some(args.old_name.as_str()) {
+        outln!(
+            "\n{} Directory {} still has the old name. Pass --move to rename it, \
+             or --keep-dir-name to keep it without this note.",
+            symbols::info().blue().bold(),
+            display_path(dir)
+        );
+    }
+}
+
+/// Resolves `OLD_NAME` to a workspace member.
+///
+/// If the rename history shows the package was renamed, fails with its current
+/// name. Otherwise offers close matches interactively. Without a terminal (or
+/// with `--yes`), fails with the matches listed in the error.
+fn resolve_old_name(args: &RenameArgs, metadata: &cargo_metadata::Metadata) -> Result<String> {
+    if metadata
+        .workspace_packages()
+        .iter()
+        .any(|p| p.name == args.old_name)
+    {
+        return Ok(args.old_name.clone());
+    }
+
+    let history = RenameHistory::load(metadata.workspace_root.as_std_path()).unwrap_or_else(|e| {
+        log::debug!("Ignoring unreadable rename history: {}", e);
+        RenameHistory::default()
+    });
+    if let Some((entry, current)) = history.renamed_to(&args.old_name) {
+        return Err(RenameError::PackageRenamed(
+            args.old_name.clone(),
+            current,
+            history::age_of(entry),
+        ));
+    }
+
+    let members = metadata.workspace_packages();
+    let suggestions =
+        suggest_similar_names(&args.old_name, members.iter().map(|p| p.name.as_str()));
+
+    match pick_package(&args.old_name, &suggestions, args.skip_confirmation)? {
+        Some(name) => Ok(name),
+        None => Err(RenameError::PackageNotFound(
+            args.old_name.clone(),
+            suggestions,
+        )),
+    }
+}
+
+/// Returns the `Cargo.toml` for a package directory or manifest path.
+fn package_manifest(at: &Path) -> Result<PathBuf> {
+    let manifest = if at.is_dir() {
+        at.join("Cargo.toml")
+    } else {
+        at.to_path_buf()
+    };
+
+    if !manifest.is_file() {
+        return Err(RenameError::InvalidPath(
+            display_path(at),
+            "no Cargo.toml found".to_string(),
+        ));
+    }
+
+    Ok(manifest.canonicalize()?)
+}
+
+/// Returns the name of the workspace member selected with `--at`, by its
+/// directory or `Cargo.toml`.
+pub(crate) fn resolve_package_at(at: &Path, metadata: &cargo_metadata::Metadata) -> Result<String> {
+    let manifest = package_manifest(at)?;
+    metadata
+        .workspace_packages()
+        .into_iter()
+        .find(|p| {
+            p.manifest_path
+                .as_std_path()
+                .canonicalize()
+                .is_ok_and(|path| path == manifest)
+        })
+        .map(|p| p.name.to_string())
+        .ok_or_else(|| {
+            RenameError::InvalidPath(
+                display_path(&manifest),
+                "not a package in this workspace".to_string(),
+            )
+        })
+}
+
+/// Loads workspace metadata, optionally from an explicit manifest path.
+///
+/// Also makes the workspace root the base for displayed paths and drops
+/// file scans cached by a previous rename.
+pub(crate) fn load_metadata(manifest_path: Option<&Path>) -> Result<cargo_metadata::Metadata> {
+    let mut cmd = MetadataCommand::new();
+
+    if let Some(path) = manifest_path {
+        if !path.exists() {
+            return Err(RenameError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Manifest path does not exist: {}", display_path(path)),
+            )));
+        }
+
+        if path.is_dir() {
+            return Err(RenameError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Manifest path is a directory: {}", display_path(path)),
+            )));
+        }
+
+        cmd.manifest_path(path);
+    }
+
+    let failed = |reason: &dyn std::fmt::Display| {
+        RenameError::Other(anyhow::anyhow!(
+            "Failed to load workspace metadata: {}. Is this a valid Cargo workspace?",
+            reason
+        ))
+    };
+
+    // Run through `process` rather than `exec()` so a hung resolve is killed
+    let output = process::output(&mut cmd.cargo_command())?;
+    if !output.status.success() {
+        return Err(failed(&String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    let metadata =
+        MetadataCommand::parse(String::from_utf8_lossy(&output.stdout)).map_err(|e| failed(&e))?;
+
+    set_display_root(metadata.workspace_root.as_std_path());
+    workspace_scan::clear_cache();
+    Ok(metadata)
+}
+
+/// Previews a `--manifests-only` rename without `cargo metadata`.
+///
+/// Runs the checks of a full preflight that do not need resolved metadata
+/// and stages the same manifest edits, reading the member list from
+/// [`ManifestWorkspace`]. Only used for dry runs without `--move`.
+fn preview_manifests_only(args: &RenameArgs, workspace: &ManifestWorkspace) -> Result<()> {
+    log::debug!("Planning from manifests, without cargo metadata");
+    set_display_root(&workspace.root);
+    workspace_scan::clear_cache();
+
+    let new_name = args.effective_new_name();
+    let package = workspace.package(&args.old_name).unwrap();
+    let old_manifest_path = package.manifest_path.as_path();
+    let old_dir = old_manifest_path.parent().unwrap();
+
+    let mut errors = Vec::new();
+    if let Err(e) = validate_package_name(new_name) {
+        errors.push(e);
+    } else if new_name != args.old_name
+        && let Err(e) = validate_crate_identifier(new_name)
+    {
+        errors.push(e);
+    }
+    if new_name != args.old_name && workspace.package(new_name).is_some() {
+        errors.push(RenameError::InvalidName(
+            new_name.to_string(),
+            "a workspace package with this name already exists".to_string(),
+        ));
+    }
+    if !args.allow_dirty
+        && let Err(e) = check_git_status(&workspace.root)
+    {
+        log::info!("Hint: Use --allow-dirty to bypass this check");
+        errors.push(e);
+    }
+    RenameError::aggregate(errors)?;
+
+    if new_name == args.old_name {
+        return report_noop(args, old_dir);
+    }
+
+    let mut txn = Transaction::new(true);
+    txn.set_packages(
+        workspace
+            .members
+            .iter()
+            .map(|p| {
+                (
+                    p.name.clone(),
+                    p.manifest_path.parent().unwrap().to_path_buf(),
+                )
+            })
+            .collect(),
+    );
+    let update = args.manifest_update(new_name, old_dir, old_dir, &workspace.root);
+    txn.mark_package_manifest(old_manifest_path.to_path_buf());
+    stage_package_manifest(args, old_manifest_path, &update, &mut txn)?;
+
+    let mut dependents: Vec<PathBuf> = workspace
+        .members
+        .iter()
+        .filter(|p| p.name != args.old_name)
+        .map(|p| p.manifest_path.clone())
+        .collect();
+    for manifest in &dependents {
+        // Cheap filter in place of the resolved dependency list
+        if !txn.read_file(manifest)?.contains(args.old_name.as_str()) {
+            continue;
+        }
+        let replacements = update_dependent_manifest(manifest, &update, &mut txn)?;
+        if replacements.total() > 0 {
+            txn.add_note(manifest.clone(), replacements.to_string());
+        }
+    }
+    let root_manifest = workspace.root_manifest();
+    if root_manifest.exists() && !dependents.contains(&root_manifest) {
+        dependents.push(root_manifest.clone());
+    }
+
+    stage_workspace_manifests(
+        args,
+        old_manifest_path,
+        &update,
+        &WorkspaceManifests {
+            root_manifest,
+            members: workspace
+                .members
+                .iter()
+                .map(|p| p.manifest_path.clone())
+                .collect(),
+            dependents,
+            version: &package.version,
+        },
+        &mut txn,
+    )?;
+
+    if args.experimental_ast_manifest_check {
+        txn.check_manifests()?;
+    }
+
+    txn.commit()?;
+    txn.print_summary(&args.old_name, new_name, &workspace.root);
+
+    if args.json {
+        let stats = txn.stats();
+        Summary {
+            schema_version: SCHEMA_VERSION,
+            status: SummaryStatus::DryRun,
+            old_name: &args.old_name,
+            new_name,
+            old_dir: display_path(old_dir),
+            new_dir: display_path(old_dir),
+            files_updated: stats.files_updated,
+            dirs_moved: stats.dirs_moved,
+            packages: PackageSummary::from_txn(&txn),
+            rules: rule_summary(&txn),
+        }
+        .print()?;
+    }
+    Ok(())
+}
+
+/// Stages the edits to the renamed package's own manifest: name, targets,
+/// version, URLs and the metadata check.
+fn stage_package_manifest(
+    args: &RenameArgs,
+    old_manifest_path: &Path,
+    update: &ManifestUpdate,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let new_name = update.new_name;
+
+    if update.name_changed {
+        log::info!(
+            "Updating package name in {}",
+            display_path(old_manifest_path)
+        );
+        update_package_name(old_manifest_path, new_name, txn)?;
+
+        if args.rename_targets {
+            log::info!("Renaming targets...");
+            update_target_names(old_manifest_path, &args.old_name, new_name, txn)?;
+        }
+    }
+    set_package_version(args, old_manifest_path, txn)?;
+
+    let url_updates = args.url_updates();
+    if !url_updates.is_empty() {
+        log::info!("Updating package URLs...");
+        update_package_urls(
+            old_manifest_path,
+            &url_updates,
+            &args.old_name,
+            new_name,
+            txn,
+        )?;
+    }
+
+    if update.name_changed {
+        log::info!("Checking package metadata...");
+        let report = check_package_metadata(
+            old_manifest_path,
+            &args.old_name,
+            new_name,
+            args.update_description,
+            txn,
+        )?;
+        report_package_metadata(&report, old_manifest_path, args.update_description);
+    }
+
+    Ok(())
+}
+
+/// The manifests [`stage_workspace_manifests`] reads, from `cargo metadata`
+/// or from the manifests alone.
+struct WorkspaceManifests<'a> {
+    root_manifest: PathBuf,
+    /// Member manifests present on disk.
+    members: Vec<PathBuf>,
+    /// Manifests whose version requirements on the package are checked: the
+    /// other members and the root manifest.
+    dependents: Vec<PathBuf>,
+    /// Current version of the renamed package.
+    version: &'a cargo_metadata::semver::Version,
+}
+
+/// Stages the workspace-wide manifest edits: `[patch]` sections, the root
+/// workspace manifest and the dependents' version requirements.
+fn stage_workspace_manifests(
+    args: &RenameArgs,
+    old_manifest_path: &Path,
+    update: &ManifestUpdate,
+    workspace: &WorkspaceManifests,
+    txn: &mut Transaction,
+) -> Result<()> {
+    log::info!("Updating patch sections...");
+    let root_manifest = &workspace.root_manifest;
+    let mut manifests = vec![root_manifest.clone()];
+    for manifest in &workspace.members {
+        if !manifests.contains(manifest) {
+            manifests.push(manifest.clone());
+        }
+    }
+    for manifest in &manifests {
+        if !manifest.exists() || !txn.read_file(manifest)?.contains(args.old_name.as_str()) {
+            continue;
+        }
+        // The package's own manifest moves along with its patch paths
+        let replacements = update_patch_sections(
+            manifest,
+            &ManifestUpdate {
+                path_changed: update.path_changed && manifest != old_manifest_path,
+                ..*update
+            },
+            txn,
+        )?;
+        if replacements.total() > 0 {
+            txn.add_note(manifest.clone(), replacements.to_string());
+        }
+    }
+
+    log::info!("Updating workspace manifest...");
+    if root_manifest.exists() && (update.path_changed || update.name_changed) {
+        update_workspace_manifest(root_manifest, update, update.path_changed, txn)?;
+    }
+
+    log::info!("Checking dependency version requirements...");
+    let version = args.set_version.as_ref().unwrap_or(workspace.version);
+    let sync = args.sync_versions || args.set_version.is_some();
+    let mismatches =
+        check_dependency_versions(&workspace.dependents, update.new_name, version, sync, txn)?;
+    report_version_mismatches(&mismatches, version, sync);
+
+    Ok(())
+}
+
+pub(crate) fn stage_rename_operations(
+    args: &RenameArgs,
+    metadata: &cargo_metadata::Metadata,
+    config: &WorkspaceConfig,
+    old_manifest_path: &Path,
+    update: &ManifestUpdate,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let ManifestUpdate {
+        new_name: effective_new_name,
+        old_dir,
+        new_dir,
+        name_changed,
+        path_changed,
+        ..
+    } = *update;
+    if !name_changed && !path_changed {
+        outln!(
+            "No changes needed: '{}' is already at '{}'",
+            args.old_name,
+            display_path(old_dir)
+        );
+
+        return Ok(());
+    }
+
+    txn.mark_package_manifest(old_manifest_path.to_path_buf());
+
+    // Only stage directory move if paths are actually different
+    if path_changed && old_dir != new_dir {
+        log::info!(
+            "Staging directory move {} {} {}",
+            display_path(old_dir),
+            symbols::arrow(),
+            display_path(new_dir)
+        );
+        txn.move_directory(old_dir.to_path_buf(), new_dir.to_path_buf())?;
+
+        log::info!("Updating target paths...");
+        update_target_paths(old_manifest_path, old_dir, new_dir, txn)?;
+
+        log::info!("Updating include/exclude globs...");
+        let reviews = update_package_globs(old_manifest_path, old_dir, new_dir, txn)?;
+        report_glob_reviews(&reviews, old_manifest_path);
+    }
+
+    stage_package_manifest(args, old_manifest_path, update, txn)?;
+
+    // Sparse checkouts: members missing on disk are skipped unless strict
+    let missing = missing_member_manifests(metadata);
+    if args.strict_materialization
+        && let Some(path) = missing.first()
+    {
+        return Err(RenameError::NotMaterialized(path.clone()));
+    }
+    for path in &missing {
+        if !txn.skipped().iter().any(|(skipped, _)| skipped == path) {
+            txn.record_skipped(path.clone(), "not checked out");
+        }
+    }
+
+    log::info!("Updating dependent manifests...");
+    let target_pkg_id = metadata
+        .workspace_packages()
+        .into_iter()
+        .find(|p| p.name == args.old_name)
+        .map(|p| &p.id)
+        .unwrap();
+
+    let mut artifact_env_renames = Vec::new();
+    let mut build_scripts = Vec::new();
+    for member_id in &metadata.workspace_members {
+        if member_id == target_pkg_id {
+            continue;
+        }
+
+        let member = &metadata[member_id];
+
+        match resolve_dependency_name(member, &args.old_name, old_dir) {
+            NameResolution::Unrelated => {
+                log::debug!("Skipping {} (no dependency)", member.name);
+                continue;
+            }
+            NameResolution::External => {
+                log::info!(
+                    "Skipping {}: its '{}' is a different crate",
+                    member.name,
+                    args.old_name
+                );
+                continue;
+            }
+            NameResolution::Ambiguous => {
+                warn_ambiguous_dependency(member, &args.old_name);
+            }
+            NameResolution::Workspace => {}
+        }
+        build_scripts.extend(
+            member
+                .targets
+                .iter()
+                .filter(|target| target.is_custom_build())
+                .map(|target| target.src_path.clone().into_std_path_buf()),
+        );
+
+        let mut pin_unselected = false;
+        if !args.only_kinds.is_empty() {
+            let kinds = workspace_dependency_kinds(member, old_dir);
+            let (selected, left): (Vec<DepKind>, Vec<DepKind>) = kinds
+                .iter()
+                .partition(|kind| args.only_kinds.contains(kind));
+            pin_unselected = !left.is_empty();
+            if pin_unselected {
+                warn_unselected_kinds(member, &args.old_name, effective_new_name, &left);
+            }
+            if selected.is_empty() && !pin_unselected {
+                continue;
+            }
+        }
+
+        if missing
+            .iter()
+            .any(|m| m == member.manifest_path.as_std_path())
+        {
+            continue;
+        }
+
+        if pin_unselected {
+            pin_unselected_dependencies(member.manifest_path.as_std_path(), update, txn)?;
+        }
+
+        log::debug!(
+            "Updating: {}",
+            display_path(member.manifest_path.as_std_path())
+        );
+        let replacements =
+            update_dependent_manifest(member.manifest_path.as_std_path(), update, txn)?;
+        if replacements.total() > 0 {
+            txn.add_note(
+                member.manifest_path.clone().into_std_path_buf(),
+                replacements.to_string(),
+            );
+        }
+        if replacements.renames_artifact_key() && !args.skips_sources() {
+            let member_dir = member.manifest_path.parent().unwrap().as_std_path();
+            artifact_env_renames.extend(update_artifact_env_vars(
+                member_dir,
+                &args.old_name,
+                effective_new_name,
+                txn,
+            )?);
+        }
+    }
+    report_env_renames("Artifact dependency variables:", &artifact_env_renames);
+
+    // The loop skips the package itself, but it may dev-depend on itself
+    // (`old-crate = { path = ".", features = [...] }`) to enable features in
+    // tests. A self path is relative to the package, so it survives a move.
+    let target_pkg = &metadata[target_pkg_id];
+    if name_changed
+        && resolve_dependency_name(target_pkg, &args.old_name, old_dir) != NameResolution::Unrelated
+    {
+        let replacements = update_dependent_manifest(
+            old_manifest_path,
+            &ManifestUpdate {
+                path_changed: false,
+                ..*update
+            },
+            txn,
+        )?;
+        if replacements.total() > 0 {
+            warn_self_dependency(target_pkg, &replacements);
+            txn.add_note(old_manifest_path.to_path_buf(), replacements.to_string());
+        }
+    }
+
+    if name_changed && let Some(links) = &target_pkg.links {
+        if args.rename_links {
+            log::info!("Updating links key...");
+            rename_links(
+                args,
+                effective_new_name,
+                old_manifest_path,
+                links,
+                &build_scripts,
+                txn,
+            )?;
+        } else {
+            log::info!(
+                "Hint: '{}' has links = \"{}\"; use --rename-links to rename it",
+                args.old_name,
+                links
+            );
+        }
+    }
+
+    let mut dependents = dependent_manifests(metadata, &args.old_name);
+    dependents.retain(|m| !missing.contains(m));
+    stage_workspace_manifests(
+        args,
+        old_manifest_path,
+        update,
+        &WorkspaceManifests {
+            root_manifest: metadata.workspace_root.as_std_path().join("Cargo.toml"),
+            members: metadata
+                .workspace_packages()
+                .into_iter()
+                .map(|p| p.manifest_path.clone().into_std_path_buf())
+                .filter(|m| !missing.contains(m))
+                .collect(),
+            dependents,
+            version: &target_pkg.version,
+        },
+        txn,
+    )?;
+
+    if path_changed || name_changed {
+        let workspace_root = metadata.workspace_root.as_std_path();
+        let mut fragments = config.fragment_paths(workspace_root);
+        fragments.extend(
+            args.workspace_fragments
+                .iter()
+                .map(|p| workspace_root.join(p)),
+        );
+        for fragment in &fragments {
+            update_workspace_fragment(fragment, update, txn)?;
+        }
+    }
+
+    if name_changed && args.emit_ssr {
+        let plan = ssr_rules(metadata, &args.old_name, effective_new_name)?;
+        report_ssr_rules(&plan);
+    }
+
+    if name_changed && !args.skips_sources() {
+        log::info!("Updating source code references...");
+        let docs = DocOptions {
+            html: config.html.clone(),
+            ..args.doc_options()
+        };
+        let former_names = RenameHistory::load(metadata.workspace_root.as_std_path())
+            .map(|history| history.former_names(&args.old_name))
+            .unwrap_or_default();
+        let shims = update_source_code(
+            metadata,
+            &args.old_name,
+            effective_new_name,
+            &SourceOptions {
+                docs: &docs,
+                drop_shims: args.drop_shims,
+                force_unparsable: args.force_rewrite_unparsable,
+                disabled_rules: &args.disable_rules,
+                exclude: &args.exclude,
+                kinds: &args.only_kinds,
+                package_only: args.via_alias,
+                former_names: &former_names,
+            },
+            txn,
+        )?;
+        report_compat_shims(&shims, &args.old_name, effective_new_name);
+
+        if args.via_alias {
+            log::info!("Adding crate aliases to dependents...");
+            let aliases =
+                insert_crate_aliases(metadata, &args.old_name, effective_new_name, old_dir, txn)?;
+            report_crate_aliases(&aliases, &args.old_name, txn);
+        }
+    }
+
+    if (name_changed || path_changed) && !args.skips_sources() {
+        log::info!("Updating tool configs...");
+        let workspace_root = metadata.workspace_root.as_std_path();
+        let mut dirs = vec![workspace_root.to_path_buf()];
+        dirs.extend(
+            metadata
+                .workspace_packages()
+                .iter()
+                .filter_map(|p| p.manifest_path.parent())
+                .map(|p| p.as_std_path().to_path_buf()),
+        );
+        let extra: Vec<PathBuf> = args
+            .tool_configs
+            .iter()
+            .map(|p| workspace_root.join(p))
+            .collect();
+
+        let files = find_tool_configs(&dirs, &extra);
+        if name_changed {
+            update_tool_configs(&files, &args.old_name, effective_new_name, txn)?;
+
+            let mut manifests = vec![workspace_root.join("Cargo.toml")];
+            if old_manifest_path != manifests[0] {
+                manifests.push(old_manifest_path.to_path_buf());
+            }
+            update_release_metadata(&manifests, &args.old_name, effective_new_name, txn)?;
+        }
+        if name_changed
+            && args.changelog_entry
+            && let Some(changelog) =
+                add_changelog_entry(old_dir, &args.old_name, effective_new_name, txn)?
+        {
+            log::info!("Added a rename entry to {}", display_path(&changelog));
+        }
+        if path_changed {
+            update_tool_config_paths(&files, old_dir, new_dir, txn)?;
+        }
+    }
+
+    if name_changed && args.rename_env_prefix {
+        log::info!("Updating environment variable prefixes...");
+        let renames = update_env_prefix(old_dir, &args.old_name, effective_new_name, txn)?;
+        report_env_renames("Environment variables:", &renames);
+    }
+
+    if name_changed && args.update_log_targets {
+        log::info!("Updating log targets...");
+        let edits = update_log_targets(
+            metadata.workspace_root.as_std_path(),
+            &args.old_name,
+            effective_new_name,
+            txn,
+        )?;
+
+        if !edits.is_empty() {
+            outln!("\n{}", "Log targets:".bold());
+        }
+        for edit in &edits {
+            outln!(
+                "   {} ({} target{})",
+                display_path(&edit.file),
+                edit.count,
+                if edit.count == 1 { "" } else { "s" }
+            );
+        }
+    }
+
+    if name_changed && args.update_ffi {
+        log::info!("Updating FFI headers...");
+        update_ffi(old_dir, &args.old_name, effective_new_name, txn)?;
     }
 
-    /// Calculates the new directory path.
-    ///
-    /// Returns `None` if package stays in same directory.
-    ///
-    /// ## Behavior
-    /// - `--move`: Renames directory to `effective_new_name()` in same parent
-    /// - `--move <path>`: Moves to `workspace_root/<path>`
-    pub fn calculate_new_dir(&self, old_dir: &Path, workspace_root: &Path) -> Option<PathBuf> {
-        if !self.should_move() {
-            return None;
+    if !args.scan_dirs.is_empty() {
+        log::info!("Scanning directories outside the workspace...");
+        let skip = workspace_manifests(metadata);
+        for dir in &args.scan_dirs {
+            check_external_dir(dir)?;
+            let manifests = find_external_dependents(dir, old_dir, &skip)?;
+            stage_external_manifests(&manifests, update, txn)?;
         }
-
-        Some(match &self.outdir {
-            Some(Some(custom_path)) => workspace_root.join(custom_path),
-            Some(None) => {
-                // Use effective new name (handles rename-only and move-only cases)
-                old_dir
-                    .parent()
-                    .unwrap_or(workspace_root)
-                    .join(self.effective_new_name())
-            }
-            None => unreachable!(),
-        })
     }
 
-    /// Check if operation would actually change anything.
-    pub fn would_change_anything(&self, current_dir: &Path, workspace_root: &Path) -> Result<bool> {
-        let name_changed = self
-            .new_name
-            .as_ref()
-            .map(|n| n != &self.old_name)
-            .unwrap_or(false);
-
-        let path_changed =
-            if let Some(new_dir) = self.calculate_new_dir(current_dir, workspace_root) {
-                new_dir != current_dir
-            } else {
-                false
-            };
-
-        Ok(name_changed || path_changed)
+    if args.skip_category.contains(&Category::Dependencies) {
+        refuse_skipped_dependencies(txn)?;
+    }
+    if !args.skip_category.is_empty() {
+        let dropped = txn.drop_categories(&args.skip_category);
+        log::info!("Left out {} changes (--skip-category)", dropped);
     }
-}
 
-/// Executes a package rename operation.
-///
-/// ## Phases
-///
-/// 1. Validate arguments
-/// 2. Load metadata via `cargo metadata`
-/// 3. Pre-flight checks (validation, git status)
-/// 4. User confirmation (unless `--yes`)
-/// 5. Stage operations in transaction
-/// 6. Commit atomically
-/// 7. Verify workspace with `cargo metadata`
-///
-/// Returns error if any phase fails. Attempts rollback if commit fails.
-pub fn execute(args: RenameArgs) -> Result<()> {
-    args.validate()?;
+    if args.experimental_ast_manifest_check {
+        log::info!("Checking edited manifests...");
+        txn.check_manifests()?;
+    }
 
-    let metadata = load_metadata(&args)?;
-    preflight_checks(&args, &metadata)?;
+    log::debug!("Staged {} operations", txn.len());
+    Ok(())
+}
 
-    let target_pkg = metadata
-        .packages
+/// Fails if `--skip-category dependencies` would leave out manifest edits:
+/// the dependents and the workspace root would keep pointing at the old name
+/// or directory, breaking the build.
+fn refuse_skipped_dependencies(txn: &Transaction) -> Result<()> {
+    let manifests: Vec<String> = txn
+        .operations()
         .iter()
-        .find(|p| p.name == args.old_name)
-        .ok_or_else(|| RenameError::PackageNotFound(args.old_name.clone()))?;
+        .filter(|op| txn.category(op) == Category::Dependencies)
+        .filter_map(|op| match op {
+            Operation::UpdateFile { path, .. } => Some(display_path(path)),
+            _ => None,
+        })
+        .collect();
+    if manifests.is_empty() {
+        return Ok(());
+    }
 
-    let old_manifest_path = target_pkg.manifest_path.as_std_path();
-    let old_dir = old_manifest_path.parent().unwrap();
+    Err(anyhow::anyhow!(
+        "--skip-category dependencies would leave these manifests on the old package:\n  {}\n\
+         Keep the category, or use --only-kinds to retarget only some dependencies",
+        manifests.join("\n  ")
+    )
+    .into())
+}
+
+/// Applies `--set-version` to the renamed package's manifest.
+fn set_package_version(args: &RenameArgs, manifest: &Path, txn: &mut Transaction) -> Result<()> {
+    let Some(version) = &args.set_version else {
+        return Ok(());
+    };
+    let version = version.to_string();
+
+    if let Some(previous) = update_package_version(manifest, &version, txn)? {
+        outln!(
+            "\n{} {} {} {}",
+            "Package version:".bold(),
+            previous.yellow(),
+            symbols::arrow(),
+            version.green()
+        );
+    }
+    Ok(())
+}
 
-    log::debug!("Package '{}' at: {}", args.old_name, old_dir.display());
+/// Prints dependency version requirements that do not match the renamed package.
+fn report_version_mismatches(
+    mismatches: &[VersionMismatch],
+    version: &cargo_metadata::semver::Version,
+    synced: bool,
+) {
+    if mismatches.is_empty() {
+        return;
+    }
 
-    // Check if anything would change
-    if !args.would_change_anything(old_dir, metadata.workspace_root.as_std_path())? {
-        println!(
-            "{}",
+    outln!("\n{}", "Version requirements:".bold());
+    for mismatch in mismatches {
+        let message = if synced {
             format!(
-                "No changes needed: '{}' is already at '{}'",
-                args.old_name,
-                old_dir.display()
+                "{} \"{}\" {} \"{}\"",
+                mismatch.key,
+                mismatch.requirement,
+                symbols::arrow(),
+                version
+            )
+        } else {
+            format!(
+                "{} \"{}\" does not match version {}",
+                mismatch.key, mismatch.requirement, version
             )
-            .yellow()
+        };
+
+        outln!(
+            "   {} ({})",
+            message.yellow(),
+            display_path(&mismatch.manifest).dimmed()
         );
-        return Ok(());
+        if !synced {
+            annotate::warning(Some(&mismatch.manifest), None, &message);
+        }
     }
 
-    if !confirm_operation(&args, &metadata)? {
-        println!("\n{}", "Operation cancelled.".yellow());
-        return Err(RenameError::Cancelled);
+    if !synced {
+        outln!("   Pass --sync-versions to update them.");
     }
+}
 
-    let new_dir = args
-        .calculate_new_dir(old_dir, metadata.workspace_root.as_std_path())
-        .unwrap_or_else(|| old_dir.to_path_buf());
+/// Warns that dependencies of `member` outside `--only-kinds` still point at
+/// the old package.
+fn warn_unselected_kinds(
+    member: &cargo_metadata::Package,
+    old_name: &str,
+    new_name: &str,
+    kinds: &[DepKind],
+) {
+    let sections = kinds
+        .iter()
+        .map(|kind| kind.section())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let message = format!(
+        "{} keeps its {} on '{}' (outside --only-kinds), now with package = \"{}\"",
+        member.name, sections, old_name, new_name
+    );
+    errln!(
+        "{} {}",
+        symbols::warning().yellow().bold(),
+        message.yellow()
+    );
+    annotate::warning(Some(member.manifest_path.as_std_path()), None, &message);
+}
 
-    log::debug!("New directory: {}", new_dir.display());
+/// Warns that a member depends on both the renamed package and another
+/// crate of the same name, so its references cannot be told apart.
+fn warn_ambiguous_dependency(member: &cargo_metadata::Package, old_name: &str) {
+    let message = format!(
+        "{} depends on the workspace '{}' and on another crate named '{}' (crates.io, git or a different path); its manifest entries on that crate are kept, review its '{}::' references",
+        member.name,
+        old_name,
+        old_name,
+        old_name.replace('-', "_")
+    );
+    errln!(
+        "{} {}",
+        symbols::warning().yellow().bold(),
+        message.yellow()
+    );
+    annotate::warning(Some(member.manifest_path.as_std_path()), None, &message);
+}
 
-    let effective_new_name = args.effective_new_name();
-    let name_changed = effective_new_name != args.old_name.as_str();
-    let path_changed = old_dir != new_dir;
+/// Warns that the renamed package depends on itself, so the self-dependency
+/// was renamed along with it.
+fn warn_self_dependency(package: &cargo_metadata::Package, replacements: &DependencyReplacements) {
+    let message = format!(
+        "{} depends on itself ({}); the self-dependency was renamed too",
+        package.name, replacements
+    );
+    errln!(
+        "{} {}",
+        symbols::warning().yellow().bold(),
+        message.yellow()
+    );
+    annotate::warning(Some(package.manifest_path.as_std_path()), None, &message);
+}
 
-    let mut txn = Transaction::new(args.dry_run);
+/// Prints the publishing metadata of the renamed package and the
+/// `description`/`keywords` entries that still mention the old name.
+fn report_package_metadata(report: &PackageMetadata, manifest: &Path, updated: bool) {
+    if report.fields.is_empty() && report.stale.is_empty() {
+        return;
+    }
 
-    if let Err(e) = stage_rename_operations(
-        &args,
-        effective_new_name,
-        &metadata,
-        old_manifest_path,
-        old_dir,
-        &new_dir,
-        name_changed,
-        path_changed,
-        &mut txn,
-    ) {
-        return handle_staging_error(e, txn, &args);
+    outln!("\n{}", "Package metadata:".bold());
+    for (field, value) in &report.fields {
+        outln!("   {} {}", format!("{}:", field).dimmed(), value);
     }
 
-    if let Err(e) = txn.commit() {
-        return handle_commit_error(e, &mut txn, &args);
+    for entry in &report.stale {
+        let message = if updated {
+            format!("{} now uses the new name", entry)
+        } else {
+            format!("{} still mentions the old name", entry)
+        };
+        outln!(
+            "   {} ({})",
+            message.yellow(),
+            display_path(manifest).dimmed()
+        );
+        if !updated {
+            annotate::warning(Some(manifest), None, &message);
+        }
     }
 
-    if !args.dry_run {
-        verify_workspace(metadata.workspace_root.as_std_path(), path_changed)?;
+    if !updated && !report.stale.is_empty() {
+        outln!("   Pass --update-description to update them.");
     }
+}
 
-    txn.print_summary(
-        &args.old_name,
-        effective_new_name,
-        metadata.workspace_root.as_std_path(),
-    );
+/// Prints `include`/`exclude` patterns that need manual review after a move.
+fn report_glob_reviews(reviews: &[GlobReview], manifest: &Path) {
+    if reviews.is_empty() {
+        return;
+    }
 
-    if !args.dry_run {
-        println!(
-            "\n{} {} → {}",
-            "✓ Successfully renamed".green().bold(),
-            args.old_name.yellow(),
-            effective_new_name.green().bold()
+    outln!("\n{}", "Package globs:".bold());
+    for review in reviews {
+        let message = format!(
+            "{} \"{}\" reaches outside the package and was not rewritten",
+            review.key, review.pattern
         );
+        outln!(
+            "   {} ({})",
+            message.yellow(),
+            display_path(manifest).dimmed()
+        );
+        annotate::warning(Some(manifest), None, &message);
     }
-
-    Ok(())
 }
 
-fn load_metadata(args: &RenameArgs) -> Result<cargo_metadata::Metadata> {
-    let mut cmd = MetadataCommand::new();
-
-    if let Some(path) = &args.manifest_path {
-        if !path.exists() {
-            return Err(RenameError::Io(std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                format!("Manifest path does not exist: {}", path.display()),
-            )));
-        }
-
-        if path.is_dir() {
-            return Err(RenameError::Io(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!("Manifest path is a directory: {}", path.display()),
-            )));
-        }
-
-        cmd.manifest_path(path);
+/// Prints the SSR rules and the references they cannot express.
+fn report_ssr_rules(plan: &SsrPlan) {
+    outln!(
+        "\n{}",
+        "Structural search-replace rules (rust-analyzer):".bold()
+    );
+    for rule in &plan.rules {
+        outln!("{}", rule);
+    }
+    if plan.rules.is_empty() {
+        outln!("   {}", "(none)".dimmed());
     }
 
-    cmd.exec().map_err(|e| {
-        RenameError::Other(anyhow::anyhow!(
-            "Failed to load workspace metadata: {}. Is this a valid Cargo workspace?",
-            e
-        ))
-    })
+    if !plan.manual.is_empty() {
+        outln!(
+            "\n{}",
+            "Not expressible as SSR rules (update manually):".bold()
+        );
+    }
+    for edit in &plan.manual {
+        outln!("   {}:{}", display_path(&edit.path), edit.line);
+    }
 }
 
-#[allow(clippy::too_many_arguments)]
-fn stage_rename_operations(
+/// Renames `package.links` and the `DEP_<LINKS>_*` reads in `build_scripts`.
+fn rename_links(
     args: &RenameArgs,
-    effective_new_name: &str,
-    metadata: &cargo_metadata::Metadata,
-    old_manifest_path: &Path,
-    old_dir: &Path,
-    new_dir: &Path,
-    name_changed: bool,
-    path_changed: bool,
+    new_name: &str,
+    manifest: &Path,
+    links: &str,
+    build_scripts: &[PathBuf],
     txn: &mut Transaction,
 ) -> Result<()> {
-    if !name_changed && !path_changed {
-        println!(
-            "No changes needed: '{}' is already at '{}'",
-            args.old_name,
-            old_dir.display()
+    let Some((old_links, new_links)) =
+        update_package_links(manifest, &args.old_name, new_name, txn)?
+    else {
+        let message = format!(
+            "links = \"{}\" does not derive from the package name; left unchanged",
+            links
         );
-
+        errln!(
+            "{} {}",
+            symbols::warning().yellow().bold(),
+            message.yellow()
+        );
+        annotate::warning(Some(manifest), None, &message);
         return Ok(());
+    };
+    txn.add_note(
+        manifest.to_path_buf(),
+        format!(
+            "links = \"{}\" {} \"{}\"",
+            old_links,
+            symbols::arrow(),
+            new_links
+        ),
+    );
+
+    if !args.skips_sources() {
+        let mut renames = Vec::new();
+        for script in build_scripts {
+            renames.extend(update_links_env_vars(script, &old_links, &new_links, txn)?);
+        }
+        report_env_renames("Native library variables:", &renames);
     }
+    Ok(())
+}
 
-    // Only stage directory move if paths are actually different
-    if path_changed && old_dir != new_dir {
-        log::info!(
-            "Staging directory move {} → {}",
-            old_dir.display(),
-            new_dir.display()
-        );
-        txn.move_directory(old_dir.to_path_buf(), new_dir.to_path_buf())?;
+/// Lists the crate roots that got an alias, and warns about the flagged ones.
+fn report_crate_aliases(aliases: &AliasReport, old_name: &str, txn: &mut Transaction) {
+    if !aliases.inserted.is_empty() {
+        outln!("\n{}", "Crate aliases:".bold());
+    }
+    for root in &aliases.inserted {
+        outln!("   {}", display_path(root));
     }
 
-    if name_changed {
-        log::info!("Updating package name in {}", old_manifest_path.display());
-        update_package_name(old_manifest_path, effective_new_name, txn)?;
+    for (root, reason) in &aliases.flagged {
+        let message = format!(
+            "{}: crate root {}; add `{}` paths manually",
+            display_path(root),
+            reason,
+            old_name.replace('-', "_")
+        );
+        errln!(
+            "{} {}",
+            symbols::warning().yellow().bold(),
+            message.yellow()
+        );
+        annotate::warning(Some(root), None, &message);
+        txn.record_skipped(root.clone(), &format!("crate alias not added: {}", reason));
     }
+}
 
-    log::info!("Updating dependent manifests...");
-    let target_pkg_id = metadata
-        .packages
-        .iter()
-        .find(|p| p.name == args.old_name)
-        .map(|p| &p.id)
-        .unwrap();
+/// Prints renamed environment variables under `heading`.
+fn report_env_renames(heading: &str, renames: &[EnvVarRename]) {
+    if !renames.is_empty() {
+        outln!("\n{}", heading.bold());
+    }
+    for rename in renames {
+        outln!(
+            "   {} {} {} ({})",
+            rename.old.yellow(),
+            symbols::arrow(),
+            rename.new.green(),
+            display_path(&rename.file).dimmed()
+        );
+    }
+}
 
-    for member_id in &metadata.workspace_members {
-        if member_id == target_pkg_id {
-            continue;
-        }
+/// Lists `pub use old_crate as alias;` shims touched by the rename.
+fn report_compat_shims(shims: &[CompatShim], old_name: &str, new_name: &str) {
+    if shims.is_empty() {
+        return;
+    }
 
-        let member = &metadata[member_id];
+    let old_snake = old_name.replace('-', "_");
+    let new_snake = new_name.replace('-', "_");
 
-        let has_dependency = member
-            .dependencies
-            .iter()
-            .any(|d| d.name == args.old_name || d.rename.as_deref() == Some(&args.old_name));
+    outln!("\n{}", "Compatibility shims:".bold());
+    for shim in shims {
+        let location = format!("{}:{}", display_path(&shim.path), shim.line);
+        let message = if shim.dropped {
+            format!("removed `pub use {} as {};`", old_snake, shim.alias)
+        } else if shim.alias == new_snake {
+            format!(
+                "`pub use {} as {};` now re-exports the crate under its own name",
+                new_snake, shim.alias
+            )
+        } else {
+            format!("`{}` now re-exports `{}`", shim.alias, new_snake)
+        };
 
-        if !has_dependency {
-            log::debug!("Skipping {} (no dependency)", member.name);
-            continue;
+        outln!("   {} ({})", message.yellow(), location.dimmed());
+        if !shim.dropped {
+            annotate::warning(Some(&shim.path), Some(shim.line), &message);
         }
-
-        log::debug!("Updating: {}", member.manifest_path.as_std_path().display());
-        update_dependent_manifest(
-            member.manifest_path.as_std_path(),
-            &args.old_name,
-            effective_new_name,
-            new_dir,
-            path_changed,
-            name_changed,
-            txn,
-        )?;
     }
 
-    log::info!("Updating workspace manifest...");
-    let root_manifest = metadata.workspace_root.as_std_path().join("Cargo.toml");
-    if root_manifest.exists() {
-        let should_update_members = path_changed;
+    if shims.iter().any(|shim| !shim.dropped) {
+        outln!("   Pass --drop-shims to remove them.");
+    }
+}
 
-        if should_update_members || name_changed {
-            update_workspace_manifest(
-                &root_manifest,
-                &args.old_name,
-                effective_new_name,
-                old_dir,
-                new_dir,
-                should_update_members,
-                path_changed,
-                name_changed,
-                txn,
-            )?;
-        }
+pub(crate) fn handle_staging_error<T>(
+    e: RenameError,
+    txn: Transaction,
+    dry_run: bool,
+) -> Result<T> {
+    if error::json_errors() {
+        return Err(e);
     }
 
-    if name_changed {
-        log::info!("Updating source code references...");
-        update_source_code(metadata, &args.old_name, effective_new_name, txn)?;
+    errln!("{} {}", "Error during rename:".red().bold(), e);
+
+    if !dry_run && !txn.is_empty() {
+        errln!(
+            "{} No changes were committed.",
+            symbols::info().blue().bold()
+        );
     }
 
-    log::debug!("Staged {} operations", txn.len());
-    Ok(())
+    Err(e)
 }
 
-fn handle_staging_error(e: RenameError, txn: Transaction, args: &RenameArgs) -> Result<()> {
-    eprintln!("{} {}", "Error during rename:".red().bold(), e);
+/// Reports the rollback outcome of a failed commit.
+///
+/// The transaction rolls back executed operations itself; the returned error
+/// lists them in detail.
+pub(crate) fn handle_commit_error<T>(e: RenameError) -> Result<T> {
+    if error::json_errors() {
+        return Err(e);
+    }
 
-    if !args.dry_run && !txn.is_empty() {
-        eprintln!("{} No changes were committed.", "ℹ".blue().bold());
+    match &e {
+        RenameError::PartialFailure(report) if report.is_recovered() => {
+            errln!(
+                "{}",
+                format!(
+                    "{} Rollback successful. Workspace restored.",
+                    symbols::check()
+                )
+                .green()
+            );
+        }
+        RenameError::PartialFailure(report) => {
+            errln!(
+                "{} {} operations could not be rolled back",
+                symbols::cross().red().bold(),
+                report.unrecoverable.len()
+            );
+            errln!(
+                "{}",
+                format!(
+                    "{} Manual intervention may be required.",
+                    symbols::warning()
+                )
+                .yellow()
+                .bold()
+            );
+        }
+        _ => errln!("{} No changes were applied.", symbols::info().blue().bold()),
     }
 
     Err(e)
 }
 
-fn handle_commit_error(e: RenameError, txn: &mut Transaction, args: &RenameArgs) -> Result<()> {
-    eprintln!("{} {}", "Error during commit:".red().bold(), e);
-    eprintln!("Some operations may have been applied.");
+/// Verifies the workspace after commit, logs the outcome and returns it,
+/// unless `cargo metadata` could not run.
+///
+/// A failed verification is reported but does not fail the rename: the
+/// changes are already applied and usually need only small manual fixes.
+pub(crate) fn report_verification(
+    workspace_root: &Path,
+    structure_changed: bool,
+) -> Option<VerificationOutcome> {
+    match verify_workspace(workspace_root, structure_changed) {
+        Ok(outcome) if outcome.ok => {
+            log::info!("{} Workspace verification passed", symbols::check());
+            Some(outcome)
+        }
+        Ok(outcome) => {
+            log::error!("Workspace verification failed:\n{}", outcome.stderr);
+            annotate::error(
+                Some(&workspace_root.join("Cargo.toml")),
+                None,
+                &format!("Workspace verification failed:\n{}", outcome.stderr),
+            );
+            log::warn!("The rename completed but workspace may need manual fixes.");
+            for fix in &outcome.suggested_fixes {
+                log::warn!("  {} {}", symbols::bullet(), fix);
+            }
+            Some(outcome)
+        }
+        Err(e) => {
+            log::warn!("Could not verify workspace: {}", e);
+            None
+        }
+    }
+}
 
-    if !args.dry_run && txn.is_committed() {
-        eprintln!("{}", "Attempting to rollback changes...".yellow().bold());
+/// Lists the outcome of each `--edit-conflicts` file.
+fn report_conflict_edits(conflicts: &[Conflict], outcomes: &[EditOutcome]) {
+    if conflicts.is_empty() {
+        return;
+    }
 
-        match txn.rollback() {
-            Ok(_) => {
-                eprintln!("{}", "✓ Rollback successful. Workspace restored.".green());
+    outln!("\n{}", "Edited conflicts:".bold());
+    for (conflict, outcome) in conflicts.iter().zip(outcomes) {
+        let path = display_path(&conflict.path);
+        match outcome {
+            EditOutcome::Staged => outln!("   {} {}", symbols::check().green(), path),
+            EditOutcome::Unchanged => {
+                outln!("   {} {} ({})", "-".dimmed(), path, "unchanged".dimmed())
             }
-            Err(rollback_err) => {
-                eprintln!("{} {}", "✗ Rollback failed:".red().bold(), rollback_err);
-                eprintln!(
-                    "{}",
-                    "⚠ Manual intervention may be required.".yellow().bold()
-                );
-                eprintln!("Hint: Check your version control system.");
+            EditOutcome::Rejected(reason) => {
+                let message = format!("Edit discarded: {}", reason);
+                errln!("   {} {}: {}", symbols::cross().red(), path, message);
+                annotate::warning(Some(&conflict.path), None, &message);
             }
         }
     }
-
-    Err(e)
 }
 
-fn verify_workspace(workspace_root: &Path, structure_changed: bool) -> Result<()> {
-    log::info!("Verifying workspace structure...");
-
-    let output = std::process::Command::new("cargo")
-        .arg("metadata")
-        .arg("--format-version=1")
-        .arg("--no-deps")
-        .current_dir(workspace_root)
-        .output();
+/// Runs the `--verify` command and rolls `txn` back if it fails.
+///
+/// # Errors
+///
+/// - `VerificationFailed` if the command failed and the rename was rolled back
+/// - `PartialFailure` if the rollback itself failed
+fn verify_or_roll_back(
+    command: VerifyCommand,
+    workspace_root: &Path,
+    txn: &mut Transaction,
+) -> Result<()> {
+    let outcome = match run_verify_command(workspace_root, command) {
+        Ok(outcome) => outcome,
+        Err(e) => VerificationOutcome {
+            ok: false,
+            stderr: e.to_string(),
+            suggested_fixes: Vec::new(),
+        },
+    };
+    if outcome.ok {
+        log::info!("{} {} passed", symbols::check(), command.command_line());
+        return Ok(());
+    }
 
-    match output {
-        Ok(output) if output.status.success() => {
-            log::info!("✓ Workspace verification passed");
-            Ok(())
-        }
-        Ok(output) => {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            log::error!("Workspace verification failed:\n{}", stderr);
+    errln!(
+        "{} {} failed; rolling back the rename",
+        symbols::cross().red().bold(),
+        command.command_line()
+    );
+    txn.rollback()?;
+    errln!("{} No changes were applied.", symbols::info().blue().bold());
+
+    Err(RenameError::VerificationFailed(format!(
+        "`{}` failed after the rename, which was rolled back:\n{}",
+        command.command_line(),
+        outcome.stderr
+    )))
+}
 
-            if structure_changed {
-                log::warn!("The rename completed but workspace may need manual fixes.");
-                log::warn!("Try running 'cargo check' to diagnose.");
-            }
+/// Puts `Cargo.lock` back as it was before a rolled back rename; `None` if
+/// there was none.
+fn restore_lockfile(path: &Path, content: Option<Vec<u8>>) {
+    let restored = match content {
+        Some(content) => std::fs::write(path, content),
+        None if path.exists() => std::fs::remove_file(path),
+        None => Ok(()),
+    };
+    if let Err(e) = restored {
+        log::warn!("Could not restore {}: {}", display_path(path), e);
+    }
+}
 
-            Ok(())
-        }
+/// Runs the `--run-hooks` commands and lists written files they changed.
+///
+/// Advisory like [`report_verification`]: the rename stays applied.
+fn report_hook_drift(commands: &[String], workspace_root: &Path, txn: &Transaction) {
+    let drift = match check_hook_drift(commands, workspace_root, txn) {
+        Ok(drift) => drift,
         Err(e) => {
-            log::warn!("Could not verify workspace: {}", e);
-            Ok(())
+            log::warn!("Could not check hooks: {}", e);
+            return;
         }
+    };
+
+    for (command, reason) in &drift.failed {
+        errln!(
+            "{} Hook '{}' failed: {}",
+            symbols::warning().yellow().bold(),
+            command,
+            reason
+        );
+    }
+
+    if drift.files.is_empty() {
+        log::info!(
+            "{} Hooks left all renamed files unchanged",
+            symbols::check()
+        );
+        return;
+    }
+
+    errln!(
+        "{} {} renamed file(s) changed after running hooks:",
+        symbols::warning().yellow().bold(),
+        drift.files.len()
+    );
+    for file in &drift.files {
+        let what = match file.kind {
+            DriftKind::Modified => "modified",
+            DriftKind::Removed => "removed",
+        };
+        errln!(
+            "  {} {} ({})",
+            symbols::bullet(),
+            display_path(&file.path),
+            what
+        );
     }
+    errln!("  Review these files: a formatter or hook may have reverted part of the rename.");
 }
 
 #[cfg(test)]
@@ -434,10 +3129,7 @@ mod tests {
             old_name: "old".into(),
             new_name: None,
             outdir: None,
-            manifest_path: None,
-            dry_run: false,
-            skip_confirmation: false,
-            allow_dirty: false,
+            ..Default::default()
         };
 
         assert!(args.validate().is_err());
@@ -449,10 +3141,7 @@ mod tests {
             old_name: "old".into(),
             new_name: None,
             outdir: Some(None), // --move without DIR
-            manifest_path: None,
-            dry_run: false,
-            skip_confirmation: false,
-            allow_dirty: false,
+            ..Default::default()
         };
 
         assert!(args.validate().is_err());
@@ -464,10 +3153,7 @@ mod tests {
             old_name: "old".into(),
             new_name: Some("new".into()),
             outdir: None,
-            manifest_path: None,
-            dry_run: false,
-            skip_confirmation: false,
-            allow_dirty: false,
+            ..Default::default()
         };
 
         assert!(args.validate().is_ok());
@@ -479,10 +3165,7 @@ mod tests {
             old_name: "old".into(),
             new_name: None,
             outdir: Some(Some(PathBuf::from("new-location"))),
-            manifest_path: None,
-            dry_run: false,
-            skip_confirmation: false,
-            allow_dirty: false,
+            ..Default::default()
         };
 
         assert!(args.validate().is_ok());
@@ -494,10 +3177,7 @@ mod tests {
             old_name: "my-crate".into(),
             new_name: None,
             outdir: Some(Some(PathBuf::from("new-location"))),
-            manifest_path: None,
-            dry_run: false,
-            skip_confirmation: false,
-            allow_dirty: false,
+            ..Default::default()
         };
 
         assert_eq!(args.effective_new_name(), "my-crate");
@@ -509,10 +3189,7 @@ mod tests {
             old_name: "old-crate".into(),
             new_name: Some("new-crate".into()),
             outdir: None,
-            manifest_path: None,
-            dry_run: false,
-            skip_confirmation: false,
-            allow_dirty: false,
+            ..Default::default()
         };
 
         assert_eq!(args.effective_new_name(), "new-crate");
@@ -524,10 +3201,7 @@ mod tests {
             old_name: "old".into(),
             new_name: Some("new".into()),
             outdir: None,
-            manifest_path: None,
-            dry_run: false,
-            skip_confirmation: false,
-            allow_dirty: false,
+            ..Default::default()
         };
 
         assert!(!args.should_move());
@@ -548,10 +3222,7 @@ mod tests {
             old_name: "old-pkg".into(),
             new_name: Some("new-pkg".into()),
             outdir: None,
-            manifest_path: None,
-            dry_run: false,
-            skip_confirmation: false,
-            allow_dirty: false,
+            ..Default::default()
         };
 
         assert_eq!(args.calculate_new_dir(&old_dir, workspace), None);
@@ -566,10 +3237,7 @@ mod tests {
             old_name: "old-pkg".into(),
             new_name: Some("new-pkg".into()),
             outdir: Some(None), // --move without argument
-            manifest_path: None,
-            dry_run: false,
-            skip_confirmation: false,
-            allow_dirty: false,
+            ..Default::default()
         };
 
         assert_eq!(
@@ -587,10 +3255,7 @@ mod tests {
             old_name: "my-crate".into(),
             new_name: None, // Move only, no rename
             outdir: Some(None),
-            manifest_path: None,
-            dry_run: false,
-            skip_confirmation: false,
-            allow_dirty: false,
+            ..Default::default()
         };
 
         // Should use old_name as directory name since no new_name specified
@@ -609,10 +3274,7 @@ mod tests {
             old_name: "old-pkg".into(),
             new_name: Some("new-pkg".into()),
             outdir: Some(Some(PathBuf::from("libs/api"))),
-            manifest_path: None,
-            dry_run: false,
-            skip_confirmation: false,
-            allow_dirty: false,
+            ..Default::default()
         };
 
         assert_eq!(
@@ -630,10 +3292,7 @@ mod tests {
             old_name: "old-pkg".into(),
             new_name: Some("new-pkg".into()),
             outdir: Some(None),
-            manifest_path: None,
-            dry_run: false,
-            skip_confirmation: false,
-            allow_dirty: false,
+            ..Default::default()
         };
 
         assert_eq!(
@@ -651,10 +3310,7 @@ mod tests {
             old_name: "old-crate".into(),
             new_name: Some("new-crate".into()),
             outdir: None,
-            manifest_path: None,
-            dry_run: false,
-            skip_confirmation: false,
-            allow_dirty: false,
+            ..Default::default()
         };
 
         assert!(args.would_change_anything(&current_dir, workspace).unwrap());
@@ -669,10 +3325,7 @@ mod tests {
             old_name: "my-crate".into(),
             new_name: None,
             outdir: Some(Some(PathBuf::from("libs/my-crate"))),
-            manifest_path: None,
-            dry_run: false,
-            skip_confirmation: false,
-            allow_dirty: false,
+            ..Default::default()
         };
 
         assert!(args.would_change_anything(&current_dir, workspace).unwrap());
@@ -687,12 +3340,57 @@ mod tests {
             old_name: "my-crate".into(),
             new_name: Some("my-crate".into()), // Same name
             outdir: None,
-            manifest_path: None,
-            dry_run: false,
-            skip_confirmation: false,
-            allow_dirty: false,
+            ..Default::default()
         };
 
         assert!(!args.would_change_anything(&current_dir, workspace).unwrap());
     }
+
+    #[test]
+    fn test_unmaterialized_dependents_are_skipped_unless_strict() {
+        let temp = tempfile::TempDir::new().unwrap();
+        crate::fixture::generate(&Default::default(), temp.path()).unwrap();
+        let metadata = load_metadata(Some(&temp.path().join("Cargo.toml"))).unwrap();
+
+        // Simulate a sparse checkout: member-3 depends on member-2 but is absent
+        let missing = temp.path().join("crates/member-3");
+        std::fs::remove_dir_all(&missing).unwrap();
+
+        let manifest = temp.path().join("crates/member-2/Cargo.toml");
+        let dir = manifest.parent().unwrap();
+        let mut args = RenameArgs {
+            old_name: "member-2".into(),
+            new_name: Some("renamed".into()),
+            ..Default::default()
+        };
+
+        let mut txn = Transaction::new(true);
+        let root = metadata.workspace_root.as_std_path();
+        let update = args.manifest_update("renamed", dir, dir, root);
+        stage_rename_operations(
+            &args,
+            &metadata,
+            &WorkspaceConfig::default(),
+            &manifest,
+            &update,
+            &mut txn,
+        )
+        .unwrap();
+        let skipped: Vec<_> = txn.skipped().iter().map(|(path, _)| path).collect();
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].ends_with("crates/member-3/Cargo.toml"));
+
+        args.strict_materialization = true;
+        let mut txn = Transaction::new(true);
+        let update = args.manifest_update("renamed", dir, dir, root);
+        let result = stage_rename_operations(
+            &args,
+            &metadata,
+            &WorkspaceConfig::default(),
+            &manifest,
+            &update,
+            &mut txn,
+        );
+        assert!(matches!(result, Err(RenameError::NotMaterialized(_))));
+    }
 }
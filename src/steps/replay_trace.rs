@@ -0,0 +1,115 @@
+//! Orchestration logic for `rename replay-trace`.
+//!
+//! Re-applies or reverts the source edits recorded with `--trace-edits`, for
+//! tracking down a bad rewrite one file at a time. Every edit is checked
+//! against the file before anything is written.
+
+use crate::error::{RenameError, Result};
+use crate::fs::display_path;
+use crate::fs::transaction::Transaction;
+use crate::rewrite::{TracedEdit, load_trace, replay_edits};
+use crate::steps::rename::{handle_commit_error, handle_staging_error};
+use crate::symbols;
+
+use clap::Parser;
+use colored::Colorize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Arguments for the `rename replay-trace` subcommand.
+#[derive(Parser, Debug, Clone, Default)]
+pub struct ReplayTraceArgs {
+    /// Trace written by `--trace-edits`
+    #[arg(value_name = "TRACE")]
+    pub trace: PathBuf,
+
+    /// Only replay the edits of FILE, as named in the trace (repeatable)
+    #[arg(long, value_name = "FILE")]
+    pub file: Vec<PathBuf>,
+
+    /// Undo the edits instead of applying them
+    #[arg(long)]
+    pub revert: bool,
+
+    /// Directory the traced paths are relative to (the workspace root)
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    pub root: PathBuf,
+
+    /// Preview changes without applying them
+    #[arg(long, short = 'n')]
+    pub dry_run: bool,
+}
+
+/// Replays the traced edits of the selected files.
+pub fn execute(args: ReplayTraceArgs) -> Result<()> {
+    let edits = load_trace(&args.trace)?;
+    let mut by_file: BTreeMap<PathBuf, Vec<TracedEdit>> = BTreeMap::new();
+
+    for edit in edits {
+        if args.file.is_empty()
+            || args
+                .file
+                .iter()
+                .any(|f| names_file(f, &args.root, &edit.file))
+        {
+            by_file.entry(edit.file.clone()).or_default().push(edit);
+        }
+    }
+
+    if by_file.is_empty() {
+        return Err(RenameError::NothingToDo(format!(
+            "{} has no edits for the selected files",
+            display_path(&args.trace)
+        )));
+    }
+
+    let mut txn = Transaction::new(args.dry_run);
+    if let Err(e) = stage_replay(&args, &by_file, &mut txn) {
+        return handle_staging_error(e, txn, args.dry_run);
+    }
+    if let Err(e) = txn.commit() {
+        return handle_commit_error(e);
+    }
+
+    let verb = match (args.revert, args.dry_run) {
+        (true, true) => "Would revert",
+        (true, false) => "Reverted",
+        (false, true) => "Would apply",
+        (false, false) => "Applied",
+    };
+    for (file, edits) in &by_file {
+        println!(
+            "{} {} {} edit(s) in {}",
+            symbols::check().green(),
+            verb,
+            edits.len(),
+            display_path(&args.root.join(file))
+        );
+    }
+
+    Ok(())
+}
+
+/// Stages the replayed content of every file.
+fn stage_replay(
+    args: &ReplayTraceArgs,
+    by_file: &BTreeMap<PathBuf, Vec<TracedEdit>>,
+    txn: &mut Transaction,
+) -> Result<()> {
+    for (file, edits) in by_file {
+        let path = args.root.join(file);
+        let content = txn.read_file(&path)?;
+        let replayed = replay_edits(&content, edits, args.revert)?;
+
+        if replayed != content {
+            txn.update_file(path, replayed)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns `true` if `selected` (as passed to `--file`) names the traced `file`.
+fn names_file(selected: &Path, root: &Path, file: &Path) -> bool {
+    selected == file || selected == root.join(file)
+}
@@ -0,0 +1,12 @@
+//! Orchestration logic for `rename schema`.
+
+use crate::error::Result;
+use crate::steps::rename::summary_schema;
+
+/// Prints the JSON schema of the `--json` summary.
+pub fn execute() -> Result<()> {
+    let schema = serde_json::to_string_pretty(&summary_schema())
+        .map_err(|e| anyhow::anyhow!("Failed to serialize schema: {}", e))?;
+    println!("{}", schema);
+    Ok(())
+}
@@ -0,0 +1,35 @@
+//! Reporting of locally recorded usage statistics.
+
+use crate::error::Result;
+use crate::stats::{UsageStats, stats_path};
+use crate::steps::rename::load_metadata;
+
+use clap::Parser;
+use colored::Colorize;
+use std::path::PathBuf;
+
+/// Arguments for the `rename stats` subcommand.
+#[derive(Parser, Debug, Clone, Default)]
+pub struct StatsArgs {
+    /// Path to workspace Cargo.toml (searches upward if not specified)
+    #[arg(long, value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+}
+
+/// Prints the statistics recorded for the current workspace.
+pub fn execute(args: StatsArgs) -> Result<()> {
+    let metadata = load_metadata(args.manifest_path.as_deref())?;
+    let workspace_root = metadata.workspace_root.as_std_path();
+
+    if !stats_path(workspace_root).exists() {
+        println!(
+            "{}",
+            "No statistics recorded yet. Pass --record-stats to a rename to start collecting."
+                .yellow()
+        );
+        return Ok(());
+    }
+
+    UsageStats::load(workspace_root)?.print_report(workspace_root);
+    Ok(())
+}
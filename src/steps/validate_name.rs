@@ -0,0 +1,127 @@
+//! Validation of candidate package names without renaming anything.
+//!
+//! Runs the same checks as a rename's preflight, so scaffolding scripts can
+//! pick a name up front. Exits with status 1 if any check fails.
+
+use crate::error::{RenameError, Result};
+use crate::symbols;
+use crate::verify::{
+    crates_io_version, package_name_warnings, validate_crate_identifier, validate_package_name,
+};
+
+use clap::Parser;
+use colored::Colorize;
+use serde::Serialize;
+
+/// Arguments for the `rename validate-name` subcommand.
+#[derive(Parser, Debug, Clone)]
+pub struct ValidateNameArgs {
+    /// Candidate package name
+    #[arg(value_name = "NAME")]
+    pub name: String,
+
+    /// Also fail if the name is already published on crates.io (uses `cargo search`)
+    #[arg(long)]
+    pub check_crates_io: bool,
+
+    /// Print diagnostics as JSON on stdout
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Diagnostics for a candidate name.
+#[derive(Debug, Serialize)]
+struct Report {
+    name: String,
+    valid: bool,
+    errors: Vec<String>,
+    warnings: Vec<String>,
+    /// Only present with `--check-crates-io`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    crates_io: Option<CratesIo>,
+}
+
+#[derive(Debug, Serialize)]
+struct CratesIo {
+    available: bool,
+    version: Option<String>,
+}
+
+/// Checks the name and prints the diagnostics.
+///
+/// # Errors
+///
+/// `InvalidName` (or `Multiple`) if any check fails, so the process exits 1.
+pub fn execute(args: ValidateNameArgs) -> Result<()> {
+    let name = args.name.as_str();
+    let mut failures = Vec::new();
+
+    if let Err(e) = validate_package_name(name) {
+        failures.push(e);
+    } else if let Err(e) = validate_crate_identifier(name) {
+        failures.push(e);
+    }
+
+    let crates_io = if args.check_crates_io {
+        let version = crates_io_version(name)?;
+        if let Some(version) = &version {
+            failures.push(RenameError::InvalidName(
+                name.to_string(),
+                format!("already published on crates.io (latest {})", version),
+            ));
+        }
+        Some(CratesIo {
+            available: version.is_none(),
+            version,
+        })
+    } else {
+        None
+    };
+
+    let report = Report {
+        name: name.to_string(),
+        valid: failures.is_empty(),
+        errors: failures.iter().map(|e| e.to_string()).collect(),
+        warnings: package_name_warnings(name)
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        crates_io,
+    };
+
+    if args.json {
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| anyhow::anyhow!("Failed to serialize report: {}", e))?;
+        println!("{}", json);
+    } else {
+        print_report(&report);
+    }
+
+    RenameError::aggregate(failures)
+}
+
+/// Prints the warnings and the crates.io availability; failures are reported
+/// by the returned error.
+fn print_report(report: &Report) {
+    for warning in &report.warnings {
+        println!(
+            "  {} '{}' {}",
+            symbols::warning().yellow(),
+            report.name,
+            warning
+        );
+    }
+    if let Some(CratesIo {
+        available: true, ..
+    }) = report.crates_io
+    {
+        println!("  {} available on crates.io", symbols::check().green());
+    }
+    if report.valid {
+        println!(
+            "{} '{}' is a valid package name",
+            symbols::check().green().bold(),
+            report.name
+        );
+    }
+}
@@ -0,0 +1,403 @@
+//! Orchestration logic for renaming the workspace itself.
+//!
+//! Updates `[workspace.package]` URL fields, workspace-level documentation and,
+//! optionally, member packages carrying the workspace name as a prefix.
+//! All file system modifications go through a `Transaction` for atomicity.
+
+use crate::cargo::{RenameGraph, update_workspace_package_fields};
+use crate::config::WorkspaceConfig;
+use crate::error::{RenameError, Result};
+use crate::fs::display_path;
+use crate::fs::transaction::Transaction;
+use crate::history::{self, HistoryEntry};
+use crate::rewrite::{DocOptions, DocsCategory, update_workspace_docs};
+use crate::stats;
+use crate::steps::rename::{
+    RenameArgs, handle_commit_error, handle_staging_error, load_metadata, package_dirs,
+    report_verification, stage_rename_operations,
+};
+use crate::symbols;
+use crate::verify::{
+    check_git_status, confirm_workspace_operation, validate_crate_identifier, validate_package_name,
+};
+
+use cargo_metadata::Metadata;
+use clap::Parser;
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+/// Arguments for the `rename workspace` subcommand.
+#[derive(Parser, Debug, Clone, Default)]
+pub struct WorkspaceArgs {
+    /// New name for the workspace
+    pub new_name: String,
+
+    /// Current workspace name (defaults to the workspace root directory name)
+    #[arg(long = "from", value_name = "OLD_NAME")]
+    pub old_name: Option<String>,
+
+    /// Also rename member packages prefixed with the old workspace name
+    #[arg(long)]
+    pub members: bool,
+
+    /// Path to workspace Cargo.toml (searches upward if not specified)
+    #[arg(long, value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Preview changes without applying them
+    #[arg(long, short = 'n')]
+    pub dry_run: bool,
+
+    /// Skip interactive confirmation
+    #[arg(long = "yes", short = 'y')]
+    pub skip_confirmation: bool,
+
+    /// Allow operation with uncommitted git changes
+    #[arg(long)]
+    pub allow_dirty: bool,
+
+    /// Documentation categories to update [default: text,badges]
+    ///
+    /// text: prose, headings and link text
+    /// urls: link URLs other than badges
+    /// badges: crates.io, docs.rs and shields.io URLs
+    #[arg(
+        long,
+        value_name = "CATEGORIES",
+        value_delimiter = ',',
+        verbatim_doc_comment
+    )]
+    pub docs_update: Option<Vec<DocsCategory>>,
+
+    /// Match the crate name in documentation case-insensitively
+    ///
+    /// Preserves the casing of each occurrence (Old-crate → New-crate).
+    #[arg(long)]
+    pub ignore_case_docs: bool,
+
+    /// Check that every edited Cargo.toml still parses before committing
+    #[arg(long)]
+    pub experimental_ast_manifest_check: bool,
+
+    /// Keep the modification time of rewritten documentation files
+    ///
+    /// Avoids rebuilds for doc-only edits (.md, .markdown, .txt). Crates that
+    /// include such files via include_str! will not notice the change.
+    #[arg(long)]
+    pub preserve_mtime: bool,
+
+    /// Additional tool config file to update (repeatable)
+    ///
+    /// deny.toml, about.toml, release.toml, .config/nextest.toml, codecov.yml
+    /// and tarpaulin.toml are always checked.
+    #[arg(long = "tool-config", value_name = "PATH")]
+    pub tool_configs: Vec<PathBuf>,
+
+    /// Fail if a workspace member is not checked out
+    ///
+    /// By default, members missing on disk (sparse checkouts) are skipped
+    /// and listed in the summary.
+    #[arg(long)]
+    pub strict_materialization: bool,
+
+    /// Record local usage statistics in .cargo-rename/stats.json
+    #[arg(long)]
+    pub record_stats: bool,
+
+    /// Do not record the renames in .cargo-rename/history.json
+    ///
+    /// The history is never recorded when the CI environment variable is set.
+    #[arg(long)]
+    pub no_history: bool,
+
+    /// Exit with an error if the workspace already has the new name
+    #[arg(long)]
+    pub fail_if_noop: bool,
+}
+
+impl WorkspaceArgs {
+    /// Returns the current workspace name.
+    ///
+    /// Uses `--from` if given, otherwise the name of the workspace root directory.
+    pub fn resolve_old_name(&self, workspace_root: &Path) -> Result<String> {
+        if let Some(name) = &self.old_name {
+            return Ok(name.clone());
+        }
+
+        workspace_root
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                RenameError::Other(anyhow::anyhow!(
+                    "Cannot infer workspace name from '{}'. Use --from OLD_NAME",
+                    display_path(workspace_root)
+                ))
+            })
+    }
+
+    /// Returns the documentation rewrite options selected by `--docs-update`
+    /// and `--ignore-case-docs`.
+    pub fn doc_options(&self) -> DocOptions {
+        DocOptions {
+            ignore_case: self.ignore_case_docs,
+            ..DocOptions::from_categories(self.docs_update.as_deref())
+        }
+    }
+}
+
+/// Computes member renames for packages prefixed with the workspace name.
+///
+/// A member named `old` or `old-*`/`old_*` becomes `new`/`new-*`/`new_*`.
+pub fn member_renames(
+    metadata: &Metadata,
+    old_name: &str,
+    new_name: &str,
+) -> Vec<(String, String)> {
+    let mut renames: Vec<_> = metadata
+        .workspace_packages()
+        .iter()
+        .filter_map(|pkg| {
+            let name = pkg.name.as_str();
+            let rest = name.strip_prefix(old_name)?;
+
+            if rest.is_empty() || rest.starts_with('-') || rest.starts_with('_') {
+                Some((name.to_string(), format!("{}{}", new_name, rest)))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    renames.sort();
+    renames
+}
+
+/// Executes a workspace rename operation.
+///
+/// ## Phases
+///
+/// 1. Load metadata via `cargo metadata`
+/// 2. Validate names and git status
+/// 3. User confirmation (unless `--yes`)
+/// 4. Stage member renames, `[workspace.package]` fields and root docs
+/// 5. Commit atomically
+/// 6. Verify workspace with `cargo metadata`
+pub fn execute(args: WorkspaceArgs) -> Result<()> {
+    let metadata = load_metadata(args.manifest_path.as_deref())?;
+    let workspace_root = metadata.workspace_root.as_std_path();
+    let old_name = args.resolve_old_name(workspace_root)?;
+
+    if old_name == args.new_name {
+        if args.fail_if_noop {
+            return Err(RenameError::NothingToDo(format!(
+                "workspace is already named '{}'",
+                old_name
+            )));
+        }
+        println!(
+            "{}",
+            format!(
+                "No changes needed: workspace is already named '{}'",
+                old_name
+            )
+            .yellow()
+        );
+        return Ok(());
+    }
+
+    let renames = if args.members {
+        member_renames(&metadata, &old_name, &args.new_name)
+    } else {
+        Vec::new()
+    };
+
+    // Collect independent failures so they are reported together
+    let mut errors = Vec::new();
+
+    if let Err(e) = validate_package_name(&args.new_name) {
+        errors.push(e);
+    }
+    for (_, new_member) in &renames {
+        if let Err(e) =
+            validate_package_name(new_member).and_then(|_| validate_crate_identifier(new_member))
+        {
+            errors.push(e);
+        }
+    }
+    if !args.allow_dirty
+        && let Err(e) = check_git_status(workspace_root)
+    {
+        log::info!("Hint: Use --allow-dirty to bypass this check");
+        errors.push(e);
+    }
+
+    RenameError::aggregate(errors)?;
+
+    // Stage dependencies before dependents so shared manifests accumulate
+    // both new names before being written once.
+    let renames = RenameGraph::new(&metadata, &renames)?.ordered();
+
+    if !confirm_workspace_operation(&args, &old_name, &renames)? {
+        println!("\n{}", "Operation cancelled.".yellow());
+        return Err(RenameError::Cancelled);
+    }
+
+    let mut txn = Transaction::new(args.dry_run);
+    txn.preserve_doc_mtimes(args.preserve_mtime);
+    txn.set_packages(package_dirs(&metadata));
+
+    if let Err(e) = stage_workspace_operations(&args, &metadata, &old_name, &renames, &mut txn) {
+        return handle_staging_error(e, txn, args.dry_run);
+    }
+
+    if let Err(e) = txn.commit() {
+        if args.record_stats && !args.dry_run {
+            stats::record(workspace_root, |s| s.rollbacks += 1);
+        }
+        return handle_commit_error(e);
+    }
+
+    if !args.dry_run {
+        report_verification(workspace_root, false);
+    }
+
+    if !args.dry_run && history::enabled(args.no_history) {
+        let entries = renames
+            .iter()
+            .filter_map(|(old, new)| {
+                let pkg = metadata
+                    .workspace_packages()
+                    .into_iter()
+                    .find(|p| p.name == old.as_str())?;
+                let dir = pkg.manifest_path.parent()?.as_std_path();
+                Some(HistoryEntry::new(workspace_root, old, new, dir, dir))
+            })
+            .collect();
+        history::record(workspace_root, entries);
+    }
+
+    if args.record_stats && !args.dry_run {
+        stats::record(workspace_root, |s| {
+            s.workspace_renames += 1;
+            s.renames += renames.len() as u64;
+            s.add_transaction(txn.stats());
+        });
+    }
+
+    txn.print_summary(&old_name, &args.new_name, workspace_root);
+
+    if !args.dry_run {
+        println!(
+            "\n{} {} {} {}",
+            format!("{} Successfully renamed workspace", symbols::check())
+                .green()
+                .bold(),
+            old_name.yellow(),
+            symbols::arrow(),
+            args.new_name.green().bold()
+        );
+    }
+
+    Ok(())
+}
+
+fn stage_workspace_operations(
+    args: &WorkspaceArgs,
+    metadata: &Metadata,
+    old_name: &str,
+    renames: &[(String, String)],
+    txn: &mut Transaction,
+) -> Result<()> {
+    let new_name = args.new_name.as_str();
+    let config = WorkspaceConfig::load(metadata)?;
+
+    for (old_member, new_member) in renames {
+        let pkg = metadata
+            .workspace_packages()
+            .into_iter()
+            .find(|p| p.name == *old_member)
+            .ok_or_else(|| RenameError::PackageNotFound(old_member.clone(), Vec::new()))?;
+
+        let manifest_path = pkg.manifest_path.as_std_path();
+        let pkg_dir = manifest_path.parent().unwrap();
+
+        log::info!(
+            "Staging member rename {} {} {}",
+            old_member,
+            symbols::arrow(),
+            new_member
+        );
+
+        let member_args = RenameArgs {
+            old_name: old_member.clone(),
+            new_name: Some(new_member.clone()),
+            docs_update: args.docs_update.clone(),
+            ignore_case_docs: args.ignore_case_docs,
+            tool_configs: args.tool_configs.clone(),
+            strict_materialization: args.strict_materialization,
+            ..Default::default()
+        };
+
+        let update = member_args.manifest_update(
+            new_member,
+            pkg_dir,
+            pkg_dir,
+            metadata.workspace_root.as_std_path(),
+        );
+        stage_rename_operations(&member_args, metadata, &config, manifest_path, &update, txn)?;
+    }
+
+    let root_manifest = metadata.workspace_root.as_std_path().join("Cargo.toml");
+    if root_manifest.exists() {
+        log::info!("Updating [workspace.package] fields...");
+        update_workspace_package_fields(&root_manifest, old_name, new_name, txn)?;
+    }
+
+    log::info!("Updating workspace documentation...");
+    let docs = DocOptions {
+        html: config.html,
+        ..args.doc_options()
+    };
+    update_workspace_docs(metadata, old_name, new_name, &docs, txn)?;
+
+    if args.experimental_ast_manifest_check {
+        log::info!("Checking edited manifests...");
+        txn.check_manifests()?;
+    }
+
+    log::debug!("Staged {} operations", txn.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_old_name_prefers_explicit() {
+        let args = WorkspaceArgs {
+            new_name: "new-ws".into(),
+            old_name: Some("my-ws".into()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            args.resolve_old_name(Path::new("/repos/other")).unwrap(),
+            "my-ws"
+        );
+    }
+
+    #[test]
+    fn test_resolve_old_name_defaults_to_root_dir() {
+        let args = WorkspaceArgs {
+            new_name: "new-ws".into(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            args.resolve_old_name(Path::new("/repos/my-ws")).unwrap(),
+            "my-ws"
+        );
+    }
+}
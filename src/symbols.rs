@@ -0,0 +1,138 @@
+//! Status markers in user-facing output.
+//!
+//! Summaries and prompts use `✓`, `✗`, `⚠`, `→` and a few emoji, which render
+//! as boxes on legacy Windows consoles and clutter CI logs. With `--ascii`, or
+//! automatically when the terminal does not use UTF-8, every marker is swapped
+//! for a plain ASCII one (`[ok]`, `[error]`, `->`, ...), in summaries, log
+//! lines and error messages alike.
+//!
+//! Detection reads the locale (`LC_ALL`, `LC_CTYPE`, `LANG`): a locale that is
+//! set without UTF-8 selects ASCII. On Windows, where the locale is rarely set,
+//! ASCII is used unless the locale says UTF-8 or the console is Windows
+//! Terminal (`WT_SESSION`).
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// Unicode markers and their ASCII replacements.
+const MARKERS: &[(&str, &str)] = &[
+    ("✓", "[ok]"),
+    ("✗", "[error]"),
+    ("⚠", "[warn]"),
+    ("ℹ", "[info]"),
+    ("→", "->"),
+    ("•", "-"),
+    ("×", "x"),
+    ("📦", "#"),
+    ("📁", "#"),
+    ("🔗", "#"),
+    ("📝", "#"),
+    ("📄", "#"),
+    ("🔧", "#"),
+    ("🗂", "#"),
+];
+
+/// Enables ASCII markers if requested or the terminal is not UTF-8.
+pub fn setup(requested: bool) {
+    let detected = !utf8_terminal(|name| std::env::var(name).ok());
+    ASCII.store(requested || detected, Ordering::Relaxed);
+}
+
+/// Returns `true` if ASCII markers are used.
+pub fn is_ascii() -> bool {
+    ASCII.load(Ordering::Relaxed)
+}
+
+/// Decides from the environment whether the terminal renders UTF-8.
+fn utf8_terminal(var: impl Fn(&str) -> Option<String>) -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .find_map(|name| var(name).filter(|v| !v.is_empty()));
+
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        }
+        None if cfg!(windows) => var("WT_SESSION").is_some(),
+        None => true,
+    }
+}
+
+fn pick(unicode: &'static str) -> &'static str {
+    if !is_ascii() {
+        return unicode;
+    }
+    MARKERS
+        .iter()
+        .find(|(u, _)| *u == unicode)
+        .map_or(unicode, |(_, ascii)| ascii)
+}
+
+/// Success marker (`✓`).
+pub fn check() -> &'static str {
+    pick("✓")
+}
+
+/// Failure marker (`✗`).
+pub fn cross() -> &'static str {
+    pick("✗")
+}
+
+/// Warning marker (`⚠`).
+pub fn warning() -> &'static str {
+    pick("⚠")
+}
+
+/// Informational marker (`ℹ`).
+pub fn info() -> &'static str {
+    pick("ℹ")
+}
+
+/// Arrow between an old and a new value (`→`).
+pub fn arrow() -> &'static str {
+    pick("→")
+}
+
+/// List bullet (`•`).
+pub fn bullet() -> &'static str {
+    pick("•")
+}
+
+/// Multiplication sign in counts such as `dependencies ×2`.
+pub fn times() -> &'static str {
+    pick("×")
+}
+
+/// Icon in front of a summary section heading, e.g. `📦`.
+pub fn icon(emoji: &'static str) -> &'static str {
+    pick(emoji)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf8_terminal_detection() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+
+        assert!(utf8_terminal(env(&[("LANG", "en_US.UTF-8")])));
+        assert!(utf8_terminal(env(&[("LC_ALL", "C.utf8"), ("LANG", "C")])));
+        assert!(!utf8_terminal(env(&[("LANG", "C")])));
+        assert!(!utf8_terminal(env(&[("LC_CTYPE", "de_DE.ISO-8859-1")])));
+        assert!(!utf8_terminal(env(&[
+            ("LC_ALL", "POSIX"),
+            ("LANG", "en_US.UTF-8")
+        ])));
+        assert_eq!(utf8_terminal(env(&[])), !cfg!(windows));
+        assert!(utf8_terminal(env(&[("WT_SESSION", "1")])));
+    }
+}
@@ -0,0 +1,134 @@
+//! Post-commit check for edits undone by formatters and pre-commit hooks.
+//!
+//! Aggressive hooks (`cargo fmt`, `cargo clippy --fix`, the `pre-commit`
+//! framework) may reformat or revert the rename's edits before they are
+//! committed to git, which shows up as "the rename didn't stick". With
+//! `--run-hooks CMD`, the given commands run in the workspace root after the
+//! rename is applied, and every file the rename wrote is read back and compared
+//! with what was written. The check is advisory: it reports, never rolls back.
+
+use crate::error::Result;
+use crate::fs::transaction::Transaction;
+use crate::process;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// How a written file differs after the hooks ran.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriftKind {
+    /// The content no longer matches what the rename wrote.
+    Modified,
+    /// The file is gone.
+    Removed,
+}
+
+/// A file the rename wrote that a hook changed afterwards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DriftedFile {
+    pub path: PathBuf,
+    pub kind: DriftKind,
+}
+
+/// Outcome of running the hooks and re-reading the written files.
+#[derive(Debug, Default)]
+pub struct HookDrift {
+    /// Commands that failed to run or exited unsuccessfully, with the reason.
+    pub failed: Vec<(String, String)>,
+    pub files: Vec<DriftedFile>,
+}
+
+/// Runs `commands` in `workspace_root` and compares the files written by the
+/// committed `txn` with their current content.
+///
+/// Commands are split on whitespace; there is no shell quoting.
+pub fn check_hook_drift(
+    commands: &[String],
+    workspace_root: &Path,
+    txn: &Transaction,
+) -> Result<HookDrift> {
+    let mut drift = HookDrift::default();
+
+    for command in commands {
+        let mut words = command.split_whitespace();
+        let Some(program) = words.next() else {
+            continue;
+        };
+        log::info!("Running hook: {}", command);
+
+        let output = process::output(
+            Command::new(program)
+                .args(words)
+                .current_dir(workspace_root),
+        );
+        match output {
+            Ok(output) if output.status.success() => {}
+            Ok(output) => drift.failed.push((
+                command.clone(),
+                format!(
+                    "{}: {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            )),
+            Err(e) => drift.failed.push((command.clone(), e.to_string())),
+        }
+    }
+
+    for (path, written) in txn.written_files() {
+        let kind = match fs::read_to_string(&path) {
+            Ok(content) if content == written => continue,
+            Ok(_) => DriftKind::Modified,
+            Err(_) => DriftKind::Removed,
+        };
+        drift.files.push(DriftedFile { path, kind });
+    }
+
+    Ok(drift)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_reports_files_changed_by_hooks() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        for name in ["kept.rs", "formatted.rs", "removed.rs"] {
+            fs::write(root.join(name), "use old_crate;\n").unwrap();
+        }
+
+        let mut txn = Transaction::new(false);
+        for name in ["kept.rs", "formatted.rs", "removed.rs"] {
+            txn.update_file(root.join(name), "use new_crate;\n".to_string())
+                .unwrap();
+        }
+        txn.commit().unwrap();
+
+        let commands = [
+            "sed -i s/new_crate/old_crate/ formatted.rs".to_string(),
+            "rm removed.rs".to_string(),
+            "false".to_string(),
+        ];
+        let drift = check_hook_drift(&commands, root, &txn).unwrap();
+
+        assert_eq!(
+            drift.files,
+            vec![
+                DriftedFile {
+                    path: root.join("formatted.rs"),
+                    kind: DriftKind::Modified,
+                },
+                DriftedFile {
+                    path: root.join("removed.rs"),
+                    kind: DriftKind::Removed,
+                },
+            ]
+        );
+        assert_eq!(drift.failed.len(), 1);
+        assert_eq!(drift.failed[0].0, "false");
+    }
+}
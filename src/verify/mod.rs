@@ -1,9 +1,27 @@
 //! Validation and verification for rename operations.
 
+pub mod drift;
+pub mod policy;
+pub mod post;
 pub mod preflight;
 pub mod prompt;
+pub mod registry;
 pub mod rules;
 
-pub use preflight::{check_git_status, preflight_checks};
-pub use prompt::confirm_operation;
-pub use rules::{validate_directory_path, validate_package_name, validate_path_within_workspace};
+pub use drift::{DriftKind, DriftedFile, HookDrift, check_hook_drift};
+pub use policy::NamingPolicy;
+pub use post::{VerificationOutcome, VerifyCommand, run_verify_command, verify_workspace};
+pub use preflight::{
+    GitBoundary, check_git_status, collect_preflight_errors, find_git_boundary, preflight_checks,
+    validate_same_git_repository,
+};
+pub use prompt::{
+    confirm_migrate_imports, confirm_normalize_deps, confirm_operation,
+    confirm_workspace_operation, pick_package,
+};
+pub use registry::crates_io_version;
+pub use rules::{
+    package_name_warnings, suggest_similar_names, validate_crate_identifier,
+    validate_directory_path, validate_move_not_nested, validate_package_name,
+    validate_path_within_workspace,
+};
@@ -0,0 +1,148 @@
+//! Organization naming policies for new package names.
+//!
+//! Configured in the workspace root `Cargo.toml`:
+//!
+//! ```toml
+//! [workspace.metadata.cargo-rename.naming]
+//! pattern = "^acme-[a-z0-9-]+$"
+//! message = "package names must start with `acme-`"
+//! command = ["tools/name-policy.sh"]  # optional
+//! ```
+//!
+//! With `--allow-commands`, `command` runs first, in the workspace root, with
+//! `CARGO_RENAME_OLD_NAME` and `CARGO_RENAME_NEW_NAME` set. It may print a
+//! replacement name (e.g. with a team suffix added) or nothing to keep the
+//! requested one; a non-zero exit rejects the name with its stderr as the
+//! reason. The resulting name must then match `pattern`, or the rename fails
+//! with `message`.
+
+use crate::error::{RenameError, Result};
+use crate::process;
+use crate::symbols;
+use regex::Regex;
+use serde::Deserialize;
+use std::path::Path;
+use std::process::Command;
+
+/// The configured naming policy.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NamingPolicy {
+    /// Regex every new name must match.
+    pub pattern: Option<String>,
+    /// Reason shown when `pattern` does not match.
+    pub message: Option<String>,
+    /// Program and arguments that validate or transform the name.
+    pub command: Vec<String>,
+}
+
+impl NamingPolicy {
+    /// Returns the name to use for `new_name`, possibly transformed.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidName` if the command rejects the name or it does not match `pattern`
+    /// - `Io` if the command cannot be started
+    /// - `ExternalCommandTimeout` if it outlives `--timeout`
+    pub fn apply(&self, old_name: &str, new_name: &str, workspace_root: &Path) -> Result<String> {
+        let mut name = new_name.to_string();
+
+        if let Some((program, args)) = self.command.split_first() {
+            let program = if program.contains('/') {
+                workspace_root.join(program)
+            } else {
+                program.into()
+            };
+            let output = process::output(
+                Command::new(program)
+                    .args(args)
+                    .current_dir(workspace_root)
+                    .env("CARGO_RENAME_OLD_NAME", old_name)
+                    .env("CARGO_RENAME_NEW_NAME", new_name),
+            )?;
+
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+                return Err(RenameError::InvalidName(
+                    name,
+                    if stderr.is_empty() {
+                        "rejected by the naming policy".to_string()
+                    } else {
+                        stderr
+                    },
+                ));
+            }
+
+            let replacement = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if !replacement.is_empty() && replacement != name {
+                log::info!(
+                    "Naming policy: '{}' {} '{}'",
+                    name,
+                    symbols::arrow(),
+                    replacement
+                );
+                name = replacement;
+            }
+        }
+
+        if let Some(pattern) = &self.pattern
+            && !Regex::new(pattern)?.is_match(&name)
+        {
+            return Err(RenameError::InvalidName(
+                name,
+                self.message
+                    .clone()
+                    .unwrap_or_else(|| format!("does not match the naming policy `{}`", pattern)),
+            ));
+        }
+
+        Ok(name)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pattern_and_command() {
+        let temp = TempDir::new().unwrap();
+        let policy = NamingPolicy {
+            pattern: Some("^acme-".to_string()),
+            message: Some("must start with acme-".to_string()),
+            command: vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                r#"case "$CARGO_RENAME_NEW_NAME" in
+                     bad-*) echo "no bad names" >&2; exit 1 ;;
+                     acme-*) ;;
+                     *) echo "acme-$CARGO_RENAME_NEW_NAME" ;;
+                   esac"#
+                    .to_string(),
+            ],
+        };
+
+        assert_eq!(
+            policy.apply("old", "parser", temp.path()).unwrap(),
+            "acme-parser"
+        );
+        assert_eq!(
+            policy.apply("old", "acme-lexer", temp.path()).unwrap(),
+            "acme-lexer"
+        );
+        assert!(matches!(
+            policy.apply("old", "bad-name", temp.path()),
+            Err(RenameError::InvalidName(_, reason)) if reason == "no bad names"
+        ));
+
+        let pattern_only = NamingPolicy {
+            command: Vec::new(),
+            ..policy
+        };
+        assert!(matches!(
+            pattern_only.apply("old", "parser", temp.path()),
+            Err(RenameError::InvalidName(_, reason)) if reason == "must start with acme-"
+        ));
+    }
+}
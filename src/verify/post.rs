@@ -0,0 +1,185 @@
+//! Post-commit verification of the workspace.
+//!
+//! After a rename is applied, the workspace is re-parsed with `cargo metadata`
+//! to confirm every manifest still resolves. The result is returned rather than
+//! logged, so callers decide whether to warn, roll back or fail.
+//!
+//! `--verify check|test` additionally builds the workspace with
+//! [`run_verify_command`], which catches broken source rewrites that
+//! `cargo metadata` does not see.
+
+use crate::error::Result;
+use crate::process;
+use clap::ValueEnum;
+use std::path::Path;
+use std::process::Command;
+
+/// Cargo command run by `--verify` after the rename is committed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum VerifyCommand {
+    /// cargo check --workspace --all-targets
+    Check,
+    /// cargo test --workspace
+    Test,
+}
+
+impl VerifyCommand {
+    /// Arguments passed to `cargo`.
+    pub fn args(self) -> &'static [&'static str] {
+        match self {
+            VerifyCommand::Check => &["check", "--workspace", "--all-targets"],
+            VerifyCommand::Test => &["test", "--workspace"],
+        }
+    }
+
+    /// Command line for messages, e.g. `cargo check --workspace --all-targets`.
+    pub fn command_line(self) -> String {
+        format!("cargo {}", self.args().join(" "))
+    }
+}
+
+/// Result of verifying a workspace after a rename.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerificationOutcome {
+    /// `true` if `cargo metadata` accepted the workspace.
+    pub ok: bool,
+    /// Captured stderr of `cargo metadata` (empty on success).
+    pub stderr: String,
+    /// Human-readable hints for fixing a failed verification.
+    pub suggested_fixes: Vec<String>,
+}
+
+/// Verifies that the workspace still loads with `cargo metadata --no-deps`.
+///
+/// `structure_changed` indicates directories were moved, which widens the set
+/// of suggested fixes on failure. Callers decide whether a failure warns,
+/// rolls back or fails; [`api::apply`](crate::api::apply) already runs it.
+///
+/// ```no_run
+/// use cargo_rename::api::{self, RenameOptions, Workspace};
+///
+/// # fn main() -> cargo_rename::Result<()> {
+/// let workspace = Workspace::load(None)?;
+/// let plan = api::plan(&workspace, &RenameOptions::new("old-crate").new_name("new-crate"))?;
+/// // Without a move, every file stays where it is
+/// for file in plan.files() {
+///     std::fs::write(&file.path, &file.new)?;
+/// }
+///
+/// let outcome = api::verify_workspace(workspace.root(), false)?;
+/// if !outcome.ok {
+///     eprintln!("{}", outcome.stderr);
+///     for fix in &outcome.suggested_fixes {
+///         eprintln!("hint: {}", fix);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Returns an error only if `cargo` could not be run at all, or timed out.
+pub fn verify_workspace(
+    workspace_root: &Path,
+    structure_changed: bool,
+) -> Result<VerificationOutcome> {
+    log::info!("Verifying workspace structure...");
+
+    let output = process::output(
+        Command::new("cargo")
+            .arg("metadata")
+            .arg("--format-version=1")
+            .arg("--no-deps")
+            .current_dir(workspace_root),
+    )?;
+
+    if output.status.success() {
+        return Ok(VerificationOutcome {
+            ok: true,
+            ..Default::default()
+        });
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    let suggested_fixes = suggest_fixes(&stderr, structure_changed);
+
+    Ok(VerificationOutcome {
+        ok: false,
+        stderr,
+        suggested_fixes,
+    })
+}
+
+/// Runs `command` in `workspace_root`.
+///
+/// # Errors
+///
+/// Returns an error only if `cargo` could not be run at all, or timed out.
+pub fn run_verify_command(
+    workspace_root: &Path,
+    command: VerifyCommand,
+) -> Result<VerificationOutcome> {
+    log::info!("Running {}...", command.command_line());
+
+    let output = process::output(
+        Command::new("cargo")
+            .args(command.args())
+            .current_dir(workspace_root),
+    )?;
+
+    Ok(VerificationOutcome {
+        ok: output.status.success(),
+        stderr: if output.status.success() {
+            String::new()
+        } else {
+            String::from_utf8_lossy(&output.stderr).trim().to_string()
+        },
+        suggested_fixes: Vec::new(),
+    })
+}
+
+/// Derives fix suggestions from `cargo metadata` stderr.
+fn suggest_fixes(stderr: &str, structure_changed: bool) -> Vec<String> {
+    let mut fixes = Vec::new();
+
+    if structure_changed {
+        fixes.push(
+            "Check that workspace `members` and dependency `path` keys point at the moved directory"
+                .to_string(),
+        );
+    }
+
+    if stderr.contains("no matching package named") {
+        fixes.push(
+            "Update remaining dependency declarations that use the old package name".to_string(),
+        );
+    }
+
+    if stderr.contains("failed to read") || stderr.contains("failed to load manifest") {
+        fixes.push("Fix manifest paths that no longer exist after the rename".to_string());
+    }
+
+    fixes.push("Run 'cargo check' to diagnose".to_string());
+    fixes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest_fixes_for_moved_package() {
+        let fixes = suggest_fixes("error: failed to load manifest for workspace member", true);
+
+        assert_eq!(fixes.len(), 3);
+        assert!(fixes[0].contains("members"));
+        assert!(fixes.last().unwrap().contains("cargo check"));
+    }
+
+    #[test]
+    fn test_suggest_fixes_always_includes_cargo_check() {
+        let fixes = suggest_fixes("", false);
+        assert_eq!(fixes, vec!["Run 'cargo check' to diagnose".to_string()]);
+    }
+}
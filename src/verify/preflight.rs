@@ -3,16 +3,87 @@
 //! These checks validate the current workspace state and ensure the
 //! rename operation can proceed safely. Unlike `rules`, these functions
 //! may perform I/O (checking git status, verifying files exist, etc.).
+//!
+//! Moves are checked against the platform's path length limit up front:
+//! Windows fails with `MAX_PATH` (260 characters, including the terminator)
+//! partway through copying a deep tree, which rollback then has to undo.
 
 use crate::error::{RenameError, Result};
+use crate::fs::display_path;
+use crate::process;
 use crate::steps::rename::RenameArgs;
 use crate::verify::rules::{
-    validate_directory_path, validate_package_name, validate_path_within_workspace,
+    validate_crate_identifier, validate_directory_path, validate_move_not_nested,
+    validate_package_name, validate_path_within_workspace,
 };
 use cargo_metadata::Metadata;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// Longest path the platform reliably supports: `MAX_PATH` on Windows
+/// (UTF-16 units, without the terminator), `PATH_MAX` elsewhere (bytes).
+const MAX_PATH_LEN: usize = if cfg!(windows) { 259 } else { 4095 };
+
+/// Innermost git repository containing a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitBoundary {
+    /// Directory holding the `.git` entry.
+    pub root: PathBuf,
+    /// `true` if `.git` is a file (submodule or linked worktree).
+    pub is_submodule: bool,
+}
+
+/// Finds the innermost git repository containing `path`.
+///
+/// Walks upward looking for a `.git` entry. A `.git` *file* marks a submodule,
+/// a `.git` directory a regular repository. Returns `None` outside any repository.
+pub fn find_git_boundary(path: &Path) -> Option<GitBoundary> {
+    path.ancestors().find_map(|dir| {
+        let git = dir.join(".git");
+        if git.is_dir() {
+            Some(GitBoundary {
+                root: dir.to_path_buf(),
+                is_submodule: false,
+            })
+        } else if git.is_file() {
+            Some(GitBoundary {
+                root: dir.to_path_buf(),
+                is_submodule: true,
+            })
+        } else {
+            None
+        }
+    })
+}
+
+/// Validates that a directory move stays within one git repository.
+///
+/// Moving files out of (or into) a submodule silently transfers them between
+/// repositories, so such moves are refused.
+pub fn validate_same_git_repository(old_dir: &Path, new_dir: &Path) -> Result<()> {
+    let from = find_git_boundary(old_dir);
+    let to = find_git_boundary(new_dir);
+
+    if from == to {
+        return Ok(());
+    }
+
+    let describe = |boundary: &Option<GitBoundary>| match boundary {
+        Some(b) if b.is_submodule => format!("submodule '{}'", display_path(&b.root)),
+        Some(b) => format!("repository '{}'", display_path(&b.root)),
+        None => "no repository".to_string(),
+    };
+
+    Err(RenameError::InvalidPath(
+        display_path(new_dir),
+        format!(
+            "Move crosses a git repository boundary: source is in {}, destination is in {}",
+            describe(&from),
+            describe(&to)
+        ),
+    ))
+}
+
 /// Checks if the git working directory has uncommitted **tracked** changes.
 ///
 /// Untracked files (new files not in git) are ignored because they won't be
@@ -27,15 +98,12 @@ use std::process::Command;
 ///
 /// # Errors
 ///
-/// Only returns `DirtyWorkspace` if changes are detected. All other errors
-/// (git not found, not a repo) are logged but don't fail the check.
+/// Returns `DirtyWorkspace` if changes are detected, or `ExternalCommandTimeout`
+/// if git does not respond. All other errors (git not found, not a repo) are
+/// logged but don't fail the check.
 pub fn check_git_status(workspace_root: &Path) -> Result<()> {
     // Check if git is available
-    let git_available = Command::new("git")
-        .arg("--version")
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false);
+    let git_available = git_succeeds(Command::new("git").arg("--version"))?;
 
     if !git_available {
         log::debug!("Git not available, skipping git status check");
@@ -43,12 +111,11 @@ pub fn check_git_status(workspace_root: &Path) -> Result<()> {
     }
 
     // Check if this is a git repository
-    let is_git_repo = Command::new("git")
-        .args(["rev-parse", "--git-dir"])
-        .current_dir(workspace_root)
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false);
+    let is_git_repo = git_succeeds(
+        Command::new("git")
+            .args(["rev-parse", "--git-dir"])
+            .current_dir(workspace_root),
+    )?;
 
     if !is_git_repo {
         log::debug!("Not a git repository, skipping git status check");
@@ -56,11 +123,11 @@ pub fn check_git_status(workspace_root: &Path) -> Result<()> {
     }
 
     // Check for uncommitted changes (-uno = ignore untracked files)
-    match Command::new("git")
-        .args(["status", "--porcelain", "-uno"])
-        .current_dir(workspace_root)
-        .output()
-    {
+    match process::output(
+        Command::new("git")
+            .args(["status", "--porcelain", "-uno"])
+            .current_dir(workspace_root),
+    ) {
         Ok(output) if output.status.success() => {
             if !output.stdout.is_empty() {
                 let status = String::from_utf8_lossy(&output.stdout);
@@ -86,6 +153,7 @@ pub fn check_git_status(workspace_root: &Path) -> Result<()> {
             );
             Ok(())
         }
+        Err(e @ RenameError::ExternalCommandTimeout(..)) => Err(e),
         Err(e) => {
             log::warn!("Failed to execute git status: {}", e);
             Ok(())
@@ -93,6 +161,17 @@ pub fn check_git_status(workspace_root: &Path) -> Result<()> {
     }
 }
 
+/// Returns `true` if a git command exits successfully.
+///
+/// Spawn failures count as `false`; timeouts are returned as errors.
+fn git_succeeds(cmd: &mut Command) -> Result<bool> {
+    match process::output(cmd) {
+        Ok(output) => Ok(output.status.success()),
+        Err(e @ RenameError::ExternalCommandTimeout(..)) => Err(e),
+        Err(_) => Ok(false),
+    }
+}
+
 /// Performs comprehensive pre-flight validation before rename execution.
 ///
 /// # Checks Performed
@@ -101,70 +180,225 @@ pub fn check_git_status(workspace_root: &Path) -> Result<()> {
 /// 2. Directory path is valid (if `--move` specified)
 /// 3. Directory is within workspace bounds (if `--move` specified)
 /// 4. Old package exists in workspace
-/// 5. Git workspace is clean (unless `--allow-dirty`)
-/// 6. Operation would actually change something
+/// 5. New name doesn't collide with another workspace package
+/// 6. Git workspace is clean (unless `--allow-dirty`), including the
+///    package's own repository if it lives in a submodule
 /// 7. Target directory doesn't exist (if moving)
+/// 8. Move doesn't cross a git submodule boundary (if moving)
 ///
 /// # Errors
 ///
-/// Returns the first validation error encountered. No filesystem modifications
-/// are made during validation.
+/// Runs every independent check and returns all failures at once: the error
+/// itself if only one check failed, [`RenameError::Multiple`] otherwise. No
+/// filesystem modifications are made during validation.
 pub fn preflight_checks(args: &RenameArgs, metadata: &Metadata) -> Result<()> {
+    RenameError::aggregate(collect_preflight_errors(args, metadata))
+}
+
+/// Runs the checks of [`preflight_checks`], returning every failure.
+///
+/// Checks that depend on an earlier one (e.g. the target directory needs the
+/// package to exist) are skipped when it fails.
+pub fn collect_preflight_errors(args: &RenameArgs, metadata: &Metadata) -> Vec<RenameError> {
+    let mut errors = Vec::new();
+    let workspace_root = metadata.workspace_root.as_std_path();
+
     // Validate new package name
-    validate_package_name(args.effective_new_name())?;
+    if let Err(e) = validate_package_name(args.effective_new_name()) {
+        errors.push(e);
+    } else if args.effective_new_name() != args.old_name
+        && let Err(e) = validate_crate_identifier(args.effective_new_name())
+    {
+        errors.push(e);
+    }
 
     // Validate directory path (if --move specified)
+    let mut move_path_valid = true;
     if let Some(Some(custom_path)) = &args.outdir {
-        if let Some(path_str) = custom_path.to_str() {
-            validate_directory_path(path_str, metadata.workspace_root.as_std_path())?;
-            validate_path_within_workspace(custom_path, metadata.workspace_root.as_std_path())?;
-        } else {
-            return Err(RenameError::InvalidName(
-                custom_path.display().to_string(),
+        let result = match custom_path.to_str() {
+            Some(path_str) => validate_directory_path(path_str, workspace_root)
+                .and_then(|_| validate_path_within_workspace(custom_path, workspace_root)),
+            None => Err(RenameError::InvalidName(
+                display_path(custom_path),
                 "path contains invalid UTF-8".to_string(),
-            ));
+            )),
+        };
+        if let Err(e) = result {
+            move_path_valid = false;
+            errors.push(e);
         }
     }
 
+    // Verify new name isn't taken by another workspace package
+    if args.effective_new_name() != args.old_name
+        && metadata
+            .workspace_packages()
+            .iter()
+            .any(|p| p.name == args.effective_new_name())
+    {
+        errors.push(RenameError::InvalidName(
+            args.effective_new_name().to_string(),
+            "a workspace package with this name already exists".to_string(),
+        ));
+    }
+
     // Verify old package exists
     let pkg = metadata
-        .packages
-        .iter()
-        .find(|p| p.name == args.old_name)
-        .ok_or_else(|| RenameError::PackageNotFound(args.old_name.clone()))?;
+        .workspace_packages()
+        .into_iter()
+        .find(|p| p.name == args.old_name);
+    if pkg.is_none() {
+        errors.push(RenameError::PackageNotFound(
+            args.old_name.clone(),
+            Vec::new(),
+        ));
+    }
 
-    // Check git status (unless --allow-dirty)
-    if !args.allow_dirty
-        && let Err(e) = check_git_status(metadata.workspace_root.as_std_path())
+    let pkg_dir = pkg.map(|p| p.manifest_path.parent().unwrap().as_std_path());
+    let pkg_repo = pkg_dir.and_then(find_git_boundary);
+    let workspace_repo = find_git_boundary(workspace_root);
+
+    if let Some(repo) = &pkg_repo
+        && repo.is_submodule
+        && pkg_repo != workspace_repo
     {
-        log::error!("{}", e);
-        log::info!("Hint: Use --allow-dirty to bypass this check");
-        return Err(e);
+        log::info!(
+            "Package '{}' lives in submodule {}",
+            args.old_name,
+            display_path(&repo.root)
+        );
     }
 
-    // Check target directory doesn't exist (if moving)
-    if args.should_move() {
-        let old_dir = pkg.manifest_path.parent().unwrap().as_std_path();
-        let new_dir = args
-            .calculate_new_dir(old_dir, metadata.workspace_root.as_std_path())
-            .unwrap();
+    // Check git status (unless --allow-dirty), in the package's repository too
+    if !args.allow_dirty {
+        let mut roots = vec![workspace_root];
+        if let Some(repo) = &pkg_repo
+            && pkg_repo != workspace_repo
+        {
+            roots.push(repo.root.as_path());
+        }
+
+        for root in roots {
+            if let Err(e) = check_git_status(root) {
+                log::info!("Hint: Use --allow-dirty to bypass this check");
+                errors.push(e);
+                break;
+            }
+        }
+    }
+
+    // Check target directory (if moving)
+    if let Some(old_dir) = pkg_dir
+        && args.should_move()
+        && move_path_valid
+    {
+        let new_dir = args.calculate_new_dir(old_dir, workspace_root).unwrap();
+
+        if old_dir != new_dir {
+            if let Err(e) = validate_move_not_nested(old_dir, &new_dir) {
+                errors.push(e);
+            }
+
+            if new_dir.exists() {
+                errors.push(RenameError::DirectoryExists(new_dir.clone()));
+            }
+
+            if let Err(e) = validate_same_git_repository(old_dir, &new_dir) {
+                errors.push(e);
+            }
+
+            let shared = packages_inside(metadata, old_dir, &args.old_name);
+            if !shared.is_empty() {
+                if args.force_shared_dir {
+                    log::warn!(
+                        "Moving {} along with {} (--force-shared-dir)",
+                        shared.join(", "),
+                        display_path(old_dir)
+                    );
+                } else {
+                    errors.push(RenameError::SharedDirectory(old_dir.to_path_buf(), shared));
+                }
+            }
 
-        // Only check if target exists when actually moving to a different location
-        if old_dir != new_dir && new_dir.exists() {
-            return Err(RenameError::DirectoryExists(new_dir));
+            if !args.allow_long_paths
+                && let Err(e) = check_path_lengths(old_dir, &new_dir, MAX_PATH_LEN)
+            {
+                errors.push(e);
+            }
         }
 
         // Log if parent directory will be created
         if let Some(parent) = new_dir.parent()
             && !parent.exists()
         {
-            log::info!("Parent directory '{}' will be created", parent.display());
+            log::info!(
+                "Parent directory '{}' will be created",
+                display_path(parent)
+            );
+        }
+    }
+
+    errors
+}
+
+/// Returns the names of the workspace packages other than `name` whose
+/// manifests live in `dir` or below.
+fn packages_inside(metadata: &Metadata, dir: &Path, name: &str) -> Vec<String> {
+    metadata
+        .workspace_packages()
+        .iter()
+        .filter(|p| p.name != name && p.manifest_path.as_std_path().starts_with(dir))
+        .map(|p| p.name.to_string())
+        .collect()
+}
+
+/// Fails with `PathTooLong` if moving `old_dir` to `new_dir` would give any
+/// file a path longer than `limit`.
+///
+/// Lists the offending destination paths, longest first. A directory that is
+/// already too long is listed instead of its contents.
+pub fn check_path_lengths(old_dir: &Path, new_dir: &Path, limit: usize) -> Result<()> {
+    let mut too_long = Vec::new();
+    collect_long_paths(old_dir, old_dir, new_dir, limit, &mut too_long)?;
+
+    if too_long.is_empty() {
+        return Ok(());
+    }
+    too_long.sort_by_key(|path| std::cmp::Reverse(path_len(path)));
+    Err(RenameError::PathTooLong(too_long, limit))
+}
+
+fn collect_long_paths(
+    dir: &Path,
+    old_dir: &Path,
+    new_dir: &Path,
+    limit: usize,
+    too_long: &mut Vec<PathBuf>,
+) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let destination = new_dir.join(path.strip_prefix(old_dir).unwrap());
+
+        if path_len(&destination) > limit {
+            too_long.push(destination);
+        } else if entry.file_type()?.is_dir() {
+            collect_long_paths(&path, old_dir, new_dir, limit, too_long)?;
         }
     }
 
     Ok(())
 }
 
+/// Returns the length of `path` in the unit the platform limits.
+fn path_len(path: &Path) -> usize {
+    if cfg!(windows) {
+        path.as_os_str().to_string_lossy().encode_utf16().count()
+    } else {
+        path.as_os_str().len()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,4 +409,71 @@ mod tests {
         let temp = TempDir::new().unwrap();
         assert!(check_git_status(temp.path()).is_ok());
     }
+
+    #[test]
+    fn test_find_git_boundary_detects_submodule() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir(root.join(".git")).unwrap();
+        std::fs::create_dir_all(root.join("vendor/lib/src")).unwrap();
+        std::fs::write(
+            root.join("vendor/lib/.git"),
+            "gitdir: ../../.git/modules/lib",
+        )
+        .unwrap();
+
+        let inner = find_git_boundary(&root.join("vendor/lib/src")).unwrap();
+        assert_eq!(inner.root, root.join("vendor/lib"));
+        assert!(inner.is_submodule);
+
+        let outer = find_git_boundary(&root.join("vendor")).unwrap();
+        assert_eq!(outer.root, root);
+        assert!(!outer.is_submodule);
+    }
+
+    #[test]
+    fn test_move_across_submodule_boundary_rejected() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir(root.join(".git")).unwrap();
+        std::fs::create_dir_all(root.join("vendor/lib/crate-a")).unwrap();
+        std::fs::write(
+            root.join("vendor/lib/.git"),
+            "gitdir: ../../.git/modules/lib",
+        )
+        .unwrap();
+
+        let result = validate_same_git_repository(
+            &root.join("vendor/lib/crate-a"),
+            &root.join("crates/crate-a"),
+        );
+        assert!(matches!(result, Err(RenameError::InvalidPath(..))));
+
+        assert!(
+            validate_same_git_repository(
+                &root.join("vendor/lib/crate-a"),
+                &root.join("vendor/lib/crate-b"),
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_check_path_lengths_lists_deep_files() {
+        let temp = TempDir::new().unwrap();
+        let old_dir = temp.path().join("a");
+        std::fs::create_dir_all(old_dir.join("src/nested")).unwrap();
+        std::fs::write(old_dir.join("Cargo.toml"), "").unwrap();
+        std::fs::write(old_dir.join("src/nested/deep_module.rs"), "").unwrap();
+
+        let new_dir = temp.path().join("much-longer-name");
+        let limit = path_len(&new_dir.join("src/nested"));
+
+        assert!(check_path_lengths(&old_dir, &new_dir, limit + 20).is_ok());
+        let Err(RenameError::PathTooLong(paths, _)) = check_path_lengths(&old_dir, &new_dir, limit)
+        else {
+            panic!("expected PathTooLong");
+        };
+        assert_eq!(paths, vec![new_dir.join("src/nested/deep_module.rs")]);
+    }
 }
@@ -2,21 +2,41 @@
 //!
 //! Displays a plan and waits for confirmation. Skipped if `--yes` or `--dry-run`.
 
+use crate::console::{self, outln};
 use crate::error::Result;
+use crate::fs::transaction::Transaction;
+use crate::fs::{display_from_cwd, display_path};
+use crate::steps::migrate_imports::MigrateImportsArgs;
+use crate::steps::normalize_deps::NormalizeDepsArgs;
 use crate::steps::rename::RenameArgs;
+use crate::steps::workspace::WorkspaceArgs;
+use crate::symbols;
 
-use cargo_metadata::Metadata;
 use colored::Colorize;
-use std::io::{self, IsTerminal, Write};
+use std::io::{self, IsTerminal};
+use std::path::Path;
 
-/// Prompts user for confirmation before executing rename.
+/// Number of staged files listed in the confirmation prompt.
+const PROMPT_FILE_LIMIT: usize = 10;
+
+/// Prompts user for confirmation before committing a staged rename.
+///
+/// The plan shows the workspace root and manifest in use, relative to the
+/// current directory, the real number of manifests, source files and docs in
+/// `txn`, `external` extra manifests from `--notify-external`, and the first
+/// staged files. Declining discards the staged changes; nothing is written.
 ///
 /// ## Automatic Skip
 /// - `--yes` or `--dry-run` flag set
 /// - Non-interactive terminal
 ///
 /// Returns `true` if confirmed or skipped, `false` if declined.
-pub fn confirm_operation(args: &RenameArgs, metadata: &Metadata) -> Result<bool> {
+pub fn confirm_operation(
+    args: &RenameArgs,
+    workspace_root: &Path,
+    txn: &Transaction,
+    external: usize,
+) -> Result<bool> {
     if args.skip_confirmation || args.dry_run {
         return Ok(true);
     }
@@ -26,74 +46,228 @@ pub fn confirm_operation(args: &RenameArgs, metadata: &Metadata) -> Result<bool>
         return Ok(false);
     }
 
-    let pkg = metadata
-        .packages
-        .iter()
-        .find(|p| p.name == args.old_name)
-        .unwrap();
-
-    let dependents: Vec<_> = metadata
-        .packages
-        .iter()
-        .filter(|p| {
-            p.dependencies
-                .iter()
-                .any(|d| d.name == args.old_name || d.rename.as_deref() == Some(&args.old_name))
-        })
-        .collect();
-
-    println!("\n{}", "Rename Plan:".bold().cyan());
-    println!(
-        "  {} {} → {}",
+    outln!("\n{}", "Rename Plan:".bold().cyan());
+    outln!(
+        "  {} {}",
+        "Workspace:".bold(),
+        display_from_cwd(workspace_root)
+    );
+    outln!(
+        "  {} {}",
+        "Manifest:".bold(),
+        display_from_cwd(&args.manifest_used(workspace_root))
+    );
+    outln!(
+        "  {} {} {} {}",
         "Package:".bold(),
         args.old_name.yellow(),
+        symbols::arrow(),
         args.effective_new_name().green()
     );
+    let scope = args.doc_options().scope;
+    outln!(
+        "  {} {} ({})",
+        "Docs:".bold(),
+        scope.name(),
+        scope.description()
+    );
+    print_staged_changes(txn, external);
 
-    println!("  {} Update package name in Cargo.toml", "✓".green());
-    println!("  {} Update source code references", "✓".green());
-    println!("  {} Update workspace dependencies", "✓".green());
-
-    if args.should_move() {
-        let old_dir = pkg.manifest_path.parent().unwrap().as_std_path();
-        let new_dir = args
-            .calculate_new_dir(old_dir, metadata.workspace_root.as_std_path())
-            .unwrap();
-        let old_dir_name = old_dir.file_name().unwrap().to_string_lossy();
-        let new_dir_relative = new_dir
-            .strip_prefix(metadata.workspace_root.as_std_path())
-            .unwrap_or(&new_dir);
-
-        println!(
-            "  {} Move directory: {} → {}",
-            "✓".green(),
-            old_dir_name.yellow(),
-            new_dir_relative.display().to_string().green()
-        );
-        println!("  {} Update workspace members list", "✓".green());
+    read_confirmation()
+}
+
+/// Prompts user for confirmation before committing normalized dependencies.
+///
+/// Skipped under the same conditions as [`confirm_operation`].
+pub fn confirm_normalize_deps(args: &NormalizeDepsArgs, txn: &Transaction) -> Result<bool> {
+    if args.skip_confirmation || args.dry_run {
+        return Ok(true);
+    }
+
+    if !io::stdin().is_terminal() {
+        log::warn!("Non-interactive terminal detected. Use --yes to confirm automatically.");
+        return Ok(false);
+    }
+
+    outln!("\n{}", "Normalize Plan:".bold().cyan());
+    outln!("  {} {}", "Package:".bold(), args.name.yellow());
+    print_staged_changes(txn, 0);
+
+    read_confirmation()
+}
+
+/// Prompts user for confirmation before committing migrated imports.
+///
+/// Skipped under the same conditions as [`confirm_operation`].
+pub fn confirm_migrate_imports(args: &MigrateImportsArgs, txn: &Transaction) -> Result<bool> {
+    if args.skip_confirmation || args.dry_run {
+        return Ok(true);
+    }
+
+    if !io::stdin().is_terminal() {
+        log::warn!("Non-interactive terminal detected. Use --yes to confirm automatically.");
+        return Ok(false);
+    }
+
+    outln!("\n{}", "Migration Plan:".bold().cyan());
+    outln!(
+        "  {} {} {} {}",
+        "Dependency:".bold(),
+        args.old_name.yellow(),
+        symbols::arrow(),
+        args.new_name.green()
+    );
+    print_staged_changes(txn, 0);
+
+    read_confirmation()
+}
+
+/// Prints the number of staged files per kind and the first staged files.
+fn print_staged_changes(txn: &Transaction, external: usize) {
+    let counts = txn.change_counts();
+
+    let plural = |n: usize| if n == 1 { "" } else { "s" };
+    for (count, what) in [
+        (counts.manifests, "Cargo.toml file"),
+        (counts.sources, "Rust source file"),
+        (counts.docs, "documentation file"),
+        (counts.other, "other file"),
+        (external, "external manifest"),
+    ] {
+        if count > 0 {
+            outln!(
+                "  {} Update {} {}{}",
+                symbols::check().green(),
+                count,
+                what,
+                plural(count)
+            );
+        }
     }
 
-    if !dependents.is_empty() {
-        println!(
-            "  {} Update {} dependent package{}",
-            "✓".green(),
-            dependents.len(),
-            if dependents.len() == 1 { "" } else { "s" }
+    if let Some((from, to)) = txn.directory_moves().first() {
+        outln!(
+            "  {} Move directory: {} {} {}",
+            symbols::check().green(),
+            display_path(from).yellow(),
+            symbols::arrow(),
+            display_path(to).green()
         );
-        for (idx, dep) in dependents.iter().enumerate() {
-            if idx < 5 {
-                println!("    • {}", dep.name);
-            }
+    }
+
+    let files = txn.staged_files();
+    if !files.is_empty() {
+        outln!("\n  {}", "Files:".bold());
+        for path in files.iter().take(PROMPT_FILE_LIMIT) {
+            outln!("    {} {}", symbols::bullet(), display_path(path).dimmed());
         }
-        if dependents.len() > 5 {
-            println!("    • ... and {} more", dependents.len() - 5);
+        if files.len() > PROMPT_FILE_LIMIT {
+            outln!(
+                "    {} ... and {} more",
+                symbols::bullet(),
+                files.len() - PROMPT_FILE_LIMIT
+            );
         }
     }
 
-    println!();
+    outln!();
+}
+
+/// Prompts user for confirmation before renaming the workspace.
+///
+/// Skipped under the same conditions as [`confirm_operation`].
+pub fn confirm_workspace_operation(
+    args: &WorkspaceArgs,
+    old_name: &str,
+    member_renames: &[(String, String)],
+) -> Result<bool> {
+    if args.skip_confirmation || args.dry_run {
+        return Ok(true);
+    }
+
+    if !io::stdin().is_terminal() {
+        log::warn!("Non-interactive terminal detected. Use --yes to confirm automatically.");
+        return Ok(false);
+    }
+
+    outln!("\n{}", "Workspace Rename Plan:".bold().cyan());
+    outln!(
+        "  {} {} {} {}",
+        "Workspace:".bold(),
+        old_name.yellow(),
+        symbols::arrow(),
+        args.new_name.green()
+    );
+
+    outln!(
+        "  {} Update [workspace.package] URLs",
+        symbols::check().green()
+    );
+    outln!(
+        "  {} Update workspace documentation",
+        symbols::check().green()
+    );
+
+    if !member_renames.is_empty() {
+        outln!(
+            "  {} Rename {} member package{}",
+            symbols::check().green(),
+            member_renames.len(),
+            if member_renames.len() == 1 { "" } else { "s" }
+        );
+        for (old, new) in member_renames {
+            outln!(
+                "    {} {} {} {}",
+                symbols::bullet(),
+                old,
+                new,
+                symbols::arrow()
+            );
+        }
+    }
+
+    outln!();
+
+    read_confirmation()
+}
+
+/// Lets the user pick a package when `name` does not match exactly.
+///
+/// Returns `None` if the prompt is skipped (`--yes`, non-interactive terminal)
+/// or the user makes no valid selection.
+pub fn pick_package(name: &str, candidates: &[String], skip: bool) -> Result<Option<String>> {
+    if skip || candidates.is_empty() || !io::stdin().is_terminal() {
+        return Ok(None);
+    }
+
+    outln!(
+        "\n{} Package '{}' not found. Did you mean:",
+        "?".yellow().bold(),
+        name.yellow()
+    );
+    for (idx, candidate) in candidates.iter().enumerate() {
+        outln!("  {} {}", format!("{})", idx + 1).cyan(), candidate);
+    }
+
+    console::write_prompt(format!(
+        "{} ",
+        "Select a package (number, Enter to cancel):".bold()
+    ))?;
+
+    let mut response = String::new();
+    io::stdin().read_line(&mut response)?;
+
+    Ok(response
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|idx| candidates.get(idx).cloned()))
+}
 
-    print!("{} {} ", "Continue?".bold(), "(y/N)".dimmed());
-    io::stdout().flush()?;
+/// Reads a `y/N` answer from stdin.
+fn read_confirmation() -> Result<bool> {
+    console::write_prompt(format!("{} {} ", "Continue?".bold(), "(y/N)".dimmed()))?;
 
     let mut response = String::new();
     io::stdin().read_line(&mut response)?;
@@ -0,0 +1,69 @@
+//! Name availability on crates.io.
+//!
+//! Looked up with `cargo search`, so registry access follows the user's Cargo
+//! configuration (proxies, `net.offline`) and no HTTP client is needed.
+//! crates.io treats `-` and `_` as equivalent and compares case-insensitively,
+//! so `old_crate` is taken if `Old-Crate` is published.
+
+use crate::error::Result;
+use crate::process;
+use std::process::Command;
+
+/// Returns the latest published version of `name` on crates.io, if any.
+///
+/// # Errors
+///
+/// - `Other` if `cargo search` fails (e.g. no network)
+/// - `ExternalCommandTimeout` if it outlives `--timeout`
+pub fn crates_io_version(name: &str) -> Result<Option<String>> {
+    let output = process::output(
+        Command::new("cargo")
+            .args(["search", "--limit", "10"])
+            .arg(name),
+    )?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "cargo search failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    Ok(find_published(
+        &String::from_utf8_lossy(&output.stdout),
+        name,
+    ))
+}
+
+/// Finds `name` in `cargo search` output (`name = "1.2.3"    # description`).
+fn find_published(output: &str, name: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let (found, rest) = line.split_once(" = \"")?;
+        let (version, _) = rest.split_once('"')?;
+        (normalize(found) == normalize(name)).then(|| version.to_string())
+    })
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_ascii_lowercase().replace('_', "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finds_exact_match_only() {
+        let output = "serde_json = \"1.0.128\"    # A JSON serialization file format\n\
+                      serde = \"1.0.210\"         # A generic serialization framework\n\
+                      ... and 4210 crates more (use --limit N to see more)\n";
+
+        assert_eq!(find_published(output, "serde"), Some("1.0.210".to_string()));
+        assert_eq!(
+            find_published(output, "Serde-JSON"),
+            Some("1.0.128".to_string())
+        );
+        assert_eq!(find_published(output, "serde-yaml"), None);
+    }
+}
@@ -2,12 +2,34 @@
 //!
 //! Pure functions with no I/O or side effects.
 
+use crate::console::errln;
 use crate::error::{RenameError, Result};
+use crate::fs::display_path;
+use crate::symbols;
 use std::path::Path;
 
 const MAX_PACKAGE_NAME_LENGTH: usize = 64;
+const MAX_SUGGESTIONS: usize = 5;
 const RESERVED_PACKAGE_NAMES: &[&str] = &["test", "doc", "build", "bench"];
 
+/// Strict and reserved keywords of every edition, including `_`.
+const RUST_KEYWORDS: &[&str] = &[
+    "_", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if",
+    "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Keywords introduced after the 2015 edition.
+const EDITION_KEYWORDS: &[(&str, &str)] = &[
+    ("async", "2018"),
+    ("await", "2018"),
+    ("dyn", "2018"),
+    ("try", "2018"),
+    ("gen", "2024"),
+];
+
 /// Validates package name against Cargo rules.
 ///
 /// ## Rules
@@ -89,22 +111,59 @@ pub fn validate_package_name(name: &str) -> Result<()> {
         ));
     }
 
+    for warning in package_name_warnings(name) {
+        log::warn!("'{}' {}", name, warning);
+    }
+
+    Ok(())
+}
+
+/// Returns the non-fatal convention warnings for a package name.
+pub fn package_name_warnings(name: &str) -> Vec<&'static str> {
+    let mut warnings = Vec::new();
+
     if name.contains("--") {
-        log::warn!("'{}' has consecutive hyphens", name);
+        warnings.push("has consecutive hyphens");
     }
 
     if name.contains('_') && name.contains('-') {
-        log::warn!("'{}' mixes _ and - (may conflict on crates.io)", name);
+        warnings.push("mixes _ and - (may conflict on crates.io)");
     }
 
     if name.chars().any(|c| c.is_ascii_uppercase()) {
-        log::warn!(
-            "'{}' has uppercase (convention: lowercase-with-hyphens)",
-            name
-        );
+        warnings.push("has uppercase (convention: lowercase-with-hyphens)");
     }
 
-    Ok(())
+    warnings
+}
+
+/// Validates that the crate identifier derived from `name` is usable in Rust code.
+///
+/// The identifier is the snake_case form of the name (`match-gen` →
+/// `match_gen`). Names like `async` or `self` are valid package names, but
+/// their identifiers are keywords, so `use` statements rewritten to them would
+/// not compile. Keywords of every edition are rejected because dependents may
+/// use a newer edition than the package itself.
+pub fn validate_crate_identifier(name: &str) -> Result<()> {
+    let ident = name.replace('-', "_");
+
+    if !RUST_KEYWORDS.contains(&ident.as_str()) {
+        return Ok(());
+    }
+
+    let since = EDITION_KEYWORDS
+        .iter()
+        .find(|(keyword, _)| *keyword == ident)
+        .map(|(_, edition)| format!(" since edition {}", edition))
+        .unwrap_or_default();
+
+    Err(RenameError::InvalidName(
+        name.to_string(),
+        format!(
+            "crate identifier '{}' is a Rust keyword{} and cannot be used in paths",
+            ident, since
+        ),
+    ))
 }
 
 /// Validates directory path security and correctness.
@@ -128,7 +187,7 @@ pub fn validate_directory_path(path_str: &str, workspace_root: &Path) -> Result<
     for component in path.components() {
         if component == std::path::Component::ParentDir {
             return Err(RenameError::InvalidPath(
-                path.display().to_string(),
+                display_path(path),
                 format!("Contains '..': {}", path_str),
             ));
         }
@@ -138,8 +197,12 @@ pub fn validate_directory_path(path_str: &str, workspace_root: &Path) -> Result<
         let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
 
         if !canonical.starts_with(workspace_root) {
-            eprintln!("⚠️  Warning: Absolute path outside workspace: {}", path_str);
-            eprintln!("   This will move crate outside workspace.");
+            errln!(
+                "{} Warning: Absolute path outside workspace: {}",
+                symbols::warning(),
+                path_str
+            );
+            errln!("   This will move crate outside workspace.");
         }
     }
 
@@ -168,7 +231,7 @@ fn validate_windows_path_components(path: &Path) -> Result<()> {
 
             if WINDOWS_RESERVED_NAMES.contains(&base.as_str()) {
                 return Err(RenameError::InvalidPath(
-                    path.display().to_string(),
+                    display_path(&path),
                     format!("'{}' is Windows reserved name", name_str),
                 ));
             }
@@ -176,7 +239,7 @@ fn validate_windows_path_components(path: &Path) -> Result<()> {
             for &ch in INVALID_CHARS {
                 if name_str.contains(ch) {
                     return Err(RenameError::InvalidPath(
-                        path.display().to_string(),
+                        display_path(&path),
                         format!("'{}' contains invalid char '{}'", name_str, ch),
                     ));
                 }
@@ -187,6 +250,28 @@ fn validate_windows_path_components(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Validates that a directory move does not nest source and target.
+///
+/// Moving a directory into its own subtree, or onto one of its ancestors,
+/// cannot succeed and would leave a half-copied tree behind.
+pub fn validate_move_not_nested(from: &Path, to: &Path) -> Result<()> {
+    if to.starts_with(from) {
+        return Err(RenameError::InvalidPath(
+            display_path(to),
+            format!("Cannot move '{}' into itself", display_path(from)),
+        ));
+    }
+
+    if from.starts_with(to) {
+        return Err(RenameError::InvalidPath(
+            display_path(to),
+            format!("Cannot move '{}' onto its own ancestor", display_path(from)),
+        ));
+    }
+
+    Ok(())
+}
+
 /// Validates path resolves within workspace.
 pub fn validate_path_within_workspace(dir_path: &Path, workspace_root: &Path) -> Result<()> {
     let full_path = workspace_root.join(dir_path);
@@ -196,7 +281,7 @@ pub fn validate_path_within_workspace(dir_path: &Path, workspace_root: &Path) ->
 
         if !canonical.starts_with(&canonical_workspace) {
             return Err(RenameError::InvalidName(
-                dir_path.display().to_string(),
+                display_path(dir_path),
                 "resolves outside workspace".to_string(),
             ));
         }
@@ -205,6 +290,95 @@ pub fn validate_path_within_workspace(dir_path: &Path, workspace_root: &Path) ->
     Ok(())
 }
 
+/// Returns candidates similar to `name`, closest first.
+///
+/// Names are compared case-insensitively with `_` and `-` treated alike.
+/// A candidate is similar if its edit distance is at most a third of the
+/// name's length (minimum 2), or if one name contains the other.
+pub fn suggest_similar_names<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
+    let normalize = |s: &str| s.to_lowercase().replace('_', "-");
+    let target = normalize(name);
+    let max_distance = (target.len() / 3).max(2);
+
+    let mut scored: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let normalized = normalize(candidate);
+            let distance = levenshtein(&target, &normalized);
+
+            if distance <= max_distance
+                || normalized.contains(&target)
+                || target.contains(&normalized)
+            {
+                Some((distance, candidate))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    scored.sort();
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+/// Computes the Levenshtein edit distance between two strings.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("crate-a", "crate-a"), 0);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("abc", ""), 3);
+        assert_eq!(levenshtein("café", "cafe"), 1);
+    }
+
+    #[test]
+    fn test_suggest_similar_names() {
+        let suggest = |name: &str, candidates: &[&str]| {
+            suggest_similar_names(name, candidates.iter().copied())
+        };
+
+        // Closest first; `_`, `-` and case are not significant
+        assert_eq!(
+            suggest("crate_aa", &["other", "crate-b", "crate-a"]),
+            ["crate-a", "crate-b"]
+        );
+        assert_eq!(suggest("Crate-A", &["crate_a"]), ["crate_a"]);
+        // Containment counts regardless of distance
+        assert_eq!(suggest("core", &["acme-core-utils"]), ["acme-core-utils"]);
+        assert!(suggest("serde", &["tokio", "rand"]).is_empty());
+
+        let many = ["a1", "a2", "a3", "a4", "a5", "a6"];
+        assert_eq!(suggest("a", &many).len(), MAX_SUGGESTIONS);
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use super::*;
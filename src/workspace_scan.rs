@@ -0,0 +1,417 @@
+//! Discovery of the files considered by rename passes.
+//!
+//! Every pass that looks at files (source rewriting, docs, env prefixes, FFI
+//! headers) goes through this module, so ignore rules are the same everywhere:
+//!
+//! - `.gitignore`, `.git/info/exclude` and global git excludes are honored
+//! - `target` and `.git` directories are always skipped
+//! - Hidden files and directories are skipped, except `.env` / `.env.*` files.
+//!   `--include-hidden` scans all of them (`.github/`, `.config/nextest.toml`),
+//!   `--exclude-hidden` skips `.env` files too.
+//!
+//! "Hidden" means the name starts with a dot, on every platform; the Windows
+//! hidden attribute is not consulted, so results are the same everywhere.
+//!
+//! Scans are cached per root for the lifetime of a rename. A directory inside
+//! an already scanned root is served from that scan.
+
+use crate::error::Result;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::overrides::OverrideBuilder;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+static CACHE: Mutex<Vec<Arc<WorkspaceScan>>> = Mutex::new(Vec::new());
+static HIDDEN_FILES: RwLock<HiddenFiles> = RwLock::new(HiddenFiles::EnvOnly);
+
+/// Which hidden (dot-prefixed) files and directories are scanned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HiddenFiles {
+    /// Only `.env` / `.env.*` files (default).
+    #[default]
+    EnvOnly,
+    /// All hidden files and directories (`--include-hidden`).
+    Include,
+    /// No hidden files at all (`--exclude-hidden`).
+    Exclude,
+}
+
+impl HiddenFiles {
+    /// Selects the policy from the `--include-hidden` / `--exclude-hidden` flags.
+    pub fn from_flags(include: bool, exclude: bool) -> Self {
+        match (include, exclude) {
+            (true, _) => Self::Include,
+            (false, true) => Self::Exclude,
+            (false, false) => Self::EnvOnly,
+        }
+    }
+
+    /// Returns `true` if an entry with this file name is walked.
+    ///
+    /// The walk root (`depth == 0`) is always included.
+    fn allows(self, name: &str, depth: usize) -> bool {
+        // Skip target and .git directories
+        if depth > 0 && (name == "target" || name == ".git") {
+            return false;
+        }
+        if depth == 0 || !name.starts_with('.') {
+            return true;
+        }
+
+        match self {
+            Self::EnvOnly => name == ".env" || name.starts_with(".env."),
+            Self::Include => true,
+            Self::Exclude => false,
+        }
+    }
+}
+
+/// Sets which hidden files are scanned and drops cached scans.
+pub fn set_hidden_files(policy: HiddenFiles) {
+    if let Ok(mut guard) = HIDDEN_FILES.write() {
+        *guard = policy;
+    }
+    clear_cache();
+}
+
+fn hidden_files() -> HiddenFiles {
+    HIDDEN_FILES.read().map(|guard| *guard).unwrap_or_default()
+}
+
+/// Category of a discovered file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileKind {
+    /// `Cargo.toml`
+    Manifest,
+    /// `.rs` source files
+    Rust,
+    /// Markdown documentation (`.md`)
+    Doc,
+    /// Other `.toml` files (tool configs such as `cbindgen.toml`)
+    Config,
+    /// C/C++ headers (`.h`, `.hh`, `.hpp`)
+    Header,
+    /// Environment files (`.env`, `.env.*`)
+    Env,
+}
+
+impl FileKind {
+    /// Classifies a path, or returns `None` for files no pass looks at.
+    pub fn of(path: &Path) -> Option<Self> {
+        let file_name = path.file_name()?.to_str()?;
+
+        if file_name == "Cargo.toml" {
+            return Some(Self::Manifest);
+        }
+        if file_name == ".env" || file_name.starts_with(".env.") {
+            return Some(Self::Env);
+        }
+
+        match path.extension()?.to_str()? {
+            "rs" => Some(Self::Rust),
+            "md" => Some(Self::Doc),
+            "toml" => Some(Self::Config),
+            "h" | "hh" | "hpp" => Some(Self::Header),
+            _ => None,
+        }
+    }
+}
+
+/// A discovered file and its kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedFile {
+    pub path: PathBuf,
+    pub kind: FileKind,
+}
+
+/// All candidate files under a root directory.
+#[derive(Debug, Clone)]
+pub struct WorkspaceScan {
+    root: PathBuf,
+    files: Vec<ScannedFile>,
+}
+
+impl WorkspaceScan {
+    /// Walks `root` and classifies every candidate file. Not cached.
+    pub fn new(root: &Path) -> Self {
+        let mut files: Vec<ScannedFile> = walk(root)
+            .filter_map(|path| {
+                let kind = FileKind::of(&path)?;
+                Some(ScannedFile { path, kind })
+            })
+            .collect();
+
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Self {
+            root: root.to_path_buf(),
+            files,
+        }
+    }
+
+    /// Returns the scanned root.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns every discovered file, sorted by path.
+    pub fn files(&self) -> &[ScannedFile] {
+        &self.files
+    }
+
+    /// Returns discovered files of one kind.
+    pub fn of_kind(&self, kind: FileKind) -> impl Iterator<Item = &Path> {
+        self.files
+            .iter()
+            .filter(move |f| f.kind == kind)
+            .map(|f| f.path.as_path())
+    }
+}
+
+/// Returns the files under `root` matching any of `globs`, sorted by path.
+///
+/// Globs use gitignore syntax relative to `root` (`**/*.bzl`, `ci/*.yml`). The
+/// usual ignore rules apply. Not cached.
+pub fn files_matching(root: &Path, globs: &[String]) -> Result<Vec<PathBuf>> {
+    let mut builder = OverrideBuilder::new(root);
+    for glob in globs {
+        builder
+            .add(glob)
+            .map_err(|e| anyhow::anyhow!("Invalid glob '{}': {}", glob, e))?;
+    }
+    let overrides = builder
+        .build()
+        .map_err(|e| anyhow::anyhow!("Invalid globs: {}", e))?;
+
+    let mut files: Vec<PathBuf> = walk(root)
+        .filter(|path| overrides.matched(path, false).is_whitelist())
+        .collect();
+
+    files.sort();
+    Ok(files)
+}
+
+/// User-provided globs of files a pass must not touch (`--exclude`).
+///
+/// Globs use gitignore syntax and match relative to each of the roots they
+/// were built for, so with the workspace root and the package directories as
+/// roots, `benches/**` excludes the benches of every package.
+#[derive(Debug, Default)]
+pub struct ExcludeGlobs {
+    matchers: Vec<Gitignore>,
+}
+
+impl ExcludeGlobs {
+    /// Compiles `globs` relative to every directory in `roots`.
+    pub fn new<'a>(roots: impl IntoIterator<Item = &'a Path>, globs: &[String]) -> Result<Self> {
+        if globs.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let mut matchers = Vec::new();
+        for root in roots {
+            let mut builder = GitignoreBuilder::new(root);
+            for glob in globs {
+                builder
+                    .add_line(None, glob)
+                    .map_err(|e| anyhow::anyhow!("Invalid glob '{}': {}", glob, e))?;
+            }
+            matchers.push(
+                builder
+                    .build()
+                    .map_err(|e| anyhow::anyhow!("Invalid globs: {}", e))?,
+            );
+        }
+
+        Ok(Self { matchers })
+    }
+
+    /// Returns `true` if `path` or one of its parent directories matches.
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        self.matchers.iter().any(|m| {
+            path.starts_with(m.path()) && m.matched_path_or_any_parents(path, false).is_ignore()
+        })
+    }
+}
+
+/// Walks `root` with the shared ignore rules, yielding regular files.
+fn walk(root: &Path) -> impl Iterator<Item = PathBuf> {
+    let policy = hidden_files();
+
+    ignore::WalkBuilder::new(root)
+        .hidden(false)
+        .git_ignore(true)
+        .git_exclude(true)
+        .git_global(true)
+        .filter_entry(move |e| match e.file_name().to_str() {
+            Some(name) => policy.allows(name, e.depth()),
+            None => true,
+        })
+        .build()
+        .filter_map(|entry| match entry {
+            Ok(e) => Some(e),
+            Err(e) => {
+                log::debug!("Skipping entry due to error: {}", e);
+                None
+            }
+        })
+        .filter(|e| e.file_type().is_some_and(|ft| ft.is_file()))
+        .map(ignore::DirEntry::into_path)
+}
+
+/// Returns the candidate files under `dir`, using the scan cache.
+pub fn files_under(dir: &Path) -> Vec<ScannedFile> {
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+
+    let scan = match cache.iter().find(|s| dir.starts_with(&s.root)) {
+        Some(scan) => Arc::clone(scan),
+        None => {
+            let scan = Arc::new(WorkspaceScan::new(dir));
+            cache.push(Arc::clone(&scan));
+            scan
+        }
+    };
+
+    scan.files
+        .iter()
+        .filter(|f| f.path.starts_with(dir))
+        .cloned()
+        .collect()
+}
+
+/// Drops all cached scans.
+///
+/// Called at the start of each rename, since a previous one may have moved
+/// or rewritten files.
+pub fn clear_cache() {
+    CACHE.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_classifies_and_applies_ignore_rules() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+
+        for dir in ["src", "target/debug", ".github", "include"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in [
+            "Cargo.toml",
+            "README.md",
+            ".env.example",
+            "cbindgen.toml",
+            "src/lib.rs",
+            "include/api.h",
+            "target/debug/gen.rs",
+            ".github/README.md",
+            "notes.txt",
+        ] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let scan = WorkspaceScan::new(root);
+        let kinds: Vec<_> = scan
+            .files()
+            .iter()
+            .map(|f| (f.path.strip_prefix(root).unwrap().to_path_buf(), f.kind))
+            .collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                (PathBuf::from(".env.example"), FileKind::Env),
+                (PathBuf::from("Cargo.toml"), FileKind::Manifest),
+                (PathBuf::from("README.md"), FileKind::Doc),
+                (PathBuf::from("cbindgen.toml"), FileKind::Config),
+                (PathBuf::from("include/api.h"), FileKind::Header),
+                (PathBuf::from("src/lib.rs"), FileKind::Rust),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hidden_files_policy() {
+        for (policy, env, github) in [
+            (HiddenFiles::EnvOnly, true, false),
+            (HiddenFiles::Include, true, true),
+            (HiddenFiles::Exclude, false, false),
+        ] {
+            assert_eq!(policy.allows(".env.local", 1), env, "{:?}", policy);
+            assert_eq!(policy.allows(".github", 1), github, "{:?}", policy);
+            assert!(policy.allows("src", 1));
+            assert!(policy.allows(".hidden-root", 0));
+            assert!(!policy.allows(".git", 1));
+            assert!(!policy.allows("target", 2));
+        }
+    }
+
+    #[test]
+    fn test_files_matching_globs() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("ci/nested")).unwrap();
+        for file in ["BUILD.bazel", "ci/a.yml", "ci/nested/b.yml", "ci/c.txt"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let files =
+            files_matching(root, &["**/*.yml".to_string(), "BUILD.bazel".to_string()]).unwrap();
+        let relative: Vec<_> = files
+            .iter()
+            .map(|f| f.strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+
+        assert_eq!(
+            relative,
+            vec![
+                PathBuf::from("BUILD.bazel"),
+                PathBuf::from("ci/a.yml"),
+                PathBuf::from("ci/nested/b.yml"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exclude_globs() {
+        let root = Path::new("/ws");
+        let member = Path::new("/ws/crates/a");
+        let excludes = ExcludeGlobs::new(
+            [root, member],
+            &["benches/**".to_string(), "examples/legacy/*".to_string()],
+        )
+        .unwrap();
+
+        assert!(excludes.is_excluded(Path::new("/ws/crates/a/benches/generated/x.rs")));
+        assert!(excludes.is_excluded(Path::new("/ws/benches/b.rs")));
+        assert!(excludes.is_excluded(Path::new("/ws/examples/legacy/old.rs")));
+        assert!(!excludes.is_excluded(Path::new("/ws/crates/a/src/lib.rs")));
+        assert!(!excludes.is_excluded(Path::new("/ws/crates/a/examples/new.rs")));
+        assert!(!excludes.is_excluded(Path::new("/elsewhere/benches/b.rs")));
+
+        let none = ExcludeGlobs::new([root], &[]).unwrap();
+        assert!(!none.is_excluded(Path::new("/ws/benches/b.rs")));
+    }
+
+    #[test]
+    fn test_files_under_reuses_ancestor_scan() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("member/src")).unwrap();
+        fs::write(root.join("member/src/lib.rs"), "").unwrap();
+
+        assert_eq!(files_under(root).len(), 1);
+
+        // Files created after the scan are not seen until the cache is cleared
+        fs::write(root.join("member/src/new.rs"), "").unwrap();
+        assert_eq!(files_under(&root.join("member")).len(), 1);
+
+        clear_cache();
+        assert_eq!(files_under(&root.join("member")).len(), 2);
+    }
+}
@@ -0,0 +1,207 @@
+mod common;
+
+use std::cell::RefCell;
+use std::fs;
+use std::rc::Rc;
+
+use cargo_rename::api::{
+    self, RenameError, RenameOptions, RenamePlan, Report, Reporter, Step, Workspace,
+};
+use common::*;
+
+fn options(old_name: &str, new_name: &str) -> RenameOptions {
+    RenameOptions::new(old_name)
+        .new_name(new_name)
+        .allow_dirty(true)
+}
+
+#[test]
+fn test_plan_then_apply() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    let workspace = Workspace::load(Some(&workspace_root.join("Cargo.toml"))).unwrap();
+    let mut members = workspace.members();
+    members.sort();
+    assert_eq!(members, ["crate-a", "crate-b"]);
+
+    let plan = api::plan(&workspace, &options("crate-a", "new-crate")).unwrap();
+    assert_eq!(plan.new_name(), "new-crate");
+    assert!(
+        plan.changes()
+            .iter()
+            .any(|c| c.contains("crate-b") && c.contains("Cargo.toml"))
+    );
+
+    // Nothing is written before apply
+    let crate_b_toml = workspace_root.join("crate-b/Cargo.toml");
+    assert!(
+        fs::read_to_string(&crate_b_toml)
+            .unwrap()
+            .contains("crate-a")
+    );
+
+    let report = api::apply(plan).unwrap();
+    assert!(!report.dry_run);
+    assert!(report.files_updated >= 2);
+    assert!(report.verification.is_some_and(|v| v.ok));
+    assert!(
+        fs::read_to_string(&crate_b_toml)
+            .unwrap()
+            .contains("new-crate = ")
+    );
+}
+
+#[test]
+fn test_plan_lists_files_and_moves() {
+    let temp = create_test_workspace();
+    let workspace = Workspace::load(Some(&temp.path().join("Cargo.toml"))).unwrap();
+    let root = workspace.root().to_path_buf();
+
+    let plan = api::plan(&workspace, &options("crate-a", "new-crate").move_to(None)).unwrap();
+
+    assert_eq!(
+        plan.directory_moves(),
+        [(root.join("crate-a"), root.join("new-crate"))]
+    );
+    let files = plan.files();
+    let manifest = files
+        .iter()
+        .find(|f| f.path == root.join("crate-a/Cargo.toml"))
+        .unwrap();
+    assert_eq!(manifest.new_path, root.join("new-crate/Cargo.toml"));
+    let lib = files
+        .iter()
+        .find(|f| f.path == root.join("crate-b/src/lib.rs"))
+        .unwrap();
+    assert_eq!(
+        lib.diff(),
+        format!(
+            "--- {0}\n+++ {0}\n@@ -1 +1 @@\n-use crate_a;\n+use new_crate;\n",
+            lib.path.display()
+        )
+    );
+
+    let report = plan.apply().unwrap();
+    assert_eq!(report.dirs_moved, 1);
+    assert_eq!(
+        fs::read_to_string(root.join("crate-b/src/lib.rs")).unwrap(),
+        lib.new
+    );
+}
+
+#[test]
+fn test_plan_rejects_invalid_name_without_writing() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    let workspace = Workspace::load(Some(&workspace_root.join("Cargo.toml"))).unwrap();
+    let result = api::plan(&workspace, &options("crate-a", "9lives"));
+
+    assert!(matches!(result, Err(RenameError::InvalidName(..))));
+    assert!(
+        fs::read_to_string(workspace_root.join("crate-a/Cargo.toml"))
+            .unwrap()
+            .contains(r#"name = "crate-a""#)
+    );
+}
+
+#[test]
+fn test_plan_selects_package_by_directory() {
+    let temp = create_test_workspace();
+    let workspace = Workspace::load(Some(&temp.path().join("Cargo.toml"))).unwrap();
+
+    let options = RenameOptions::at(temp.path().join("crate-a"))
+        .new_name("new-crate")
+        .allow_dirty(true);
+    let plan = api::plan(&workspace, &options).unwrap();
+    assert_eq!(plan.old_name(), "crate-a");
+    assert_eq!(plan.new_name(), "new-crate");
+
+    let options = RenameOptions::at(temp.path().join("missing")).new_name("new-crate");
+    assert!(matches!(
+        api::plan(&workspace, &options),
+        Err(RenameError::InvalidPath(..))
+    ));
+}
+
+/// Records reporter calls as strings.
+struct Recorder(Rc<RefCell<Vec<String>>>);
+
+impl Reporter for Recorder {
+    fn progress(&mut self, step: Step) {
+        self.0.borrow_mut().push(format!("{:?}", step));
+    }
+
+    fn message(&mut self, text: &str, _warning: bool) {
+        self.0.borrow_mut().push(format!("message: {}", text));
+    }
+
+    fn plan(&mut self, plan: &RenamePlan) {
+        self.0
+            .borrow_mut()
+            .push(format!("plan: {}", plan.new_name()));
+    }
+
+    fn summary(&mut self, report: &Report) {
+        self.0
+            .borrow_mut()
+            .push(format!("summary: {}", report.new_name));
+    }
+}
+
+#[test]
+fn test_rename_options_report_and_confirm() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    let manifest = workspace_root.join("crate-a/Cargo.toml");
+    let content = fs::read_to_string(&manifest).unwrap();
+    fs::write(
+        &manifest,
+        content.replace(
+            "[package]\n",
+            "[package]\ndescription = \"The crate-a library\"\n",
+        ),
+    )
+    .unwrap();
+
+    let options = |events: &Rc<RefCell<Vec<String>>>| {
+        RenameOptions::new("crate-a")
+            .new_name("new-crate")
+            .manifest_path(workspace_root.join("Cargo.toml"))
+            .allow_dirty(true)
+            .reporter(Recorder(Rc::clone(events)))
+    };
+
+    // Declined: nothing is written
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let report = options(&events).confirm(|_| false).run().unwrap();
+    assert!(report.is_none());
+    assert!(
+        fs::read_to_string(&manifest)
+            .unwrap()
+            .contains(r#"name = "crate-a""#)
+    );
+    let recorded = events.borrow().clone();
+    assert_eq!(recorded[..2], ["LoadWorkspace", "Plan"]);
+    assert!(recorded.contains(&"message: Package metadata:".to_string()));
+    assert_eq!(recorded.last().unwrap(), "plan: new-crate");
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let report = options(&events)
+        .confirm(|plan| !plan.is_empty())
+        .run()
+        .unwrap()
+        .unwrap();
+    assert!(report.files_updated >= 2);
+    assert_eq!(events.borrow().last().unwrap(), "summary: new-crate");
+    assert!(events.borrow().contains(&"Apply".to_string()));
+    // The CLI's summary goes to the reporter too
+    assert!(
+        events
+            .borrow()
+            .iter()
+            .any(|e| e.starts_with("message: ") && e.contains("Successfully renamed"))
+    );
+    assert!(report.verification.is_some_and(|v| v.ok));
+}
@@ -75,6 +75,7 @@ pub fn verify_workspace_valid(workspace_root: &Path) -> bool {
 }
 
 /// Helper to run a rename command
+#[allow(unused)]
 pub fn run_rename(
     workspace_root: &Path,
     old_name: &str,
@@ -92,6 +93,7 @@ pub fn run_rename(
     cmd.arg("--yes")
         .arg("--allow-dirty")
         .args(extra_args)
+        .env_remove("CI")
         .current_dir(workspace_root);
 
     cmd.assert()
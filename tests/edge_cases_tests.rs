@@ -1,5 +1,6 @@
 mod common;
 
+use assert_cmd::cargo::cargo_bin_cmd;
 use std::fs;
 
 use common::*;
@@ -280,3 +281,405 @@ crate-a = { path = "../crate-a", optional = true }
         crate_b_toml
     );
 }
+
+#[test]
+fn test_dependency_in_multiple_sections() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    fs::write(
+        workspace_root.join("crate-b/Cargo.toml"),
+        r#"[package]
+name = "crate-b"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+crate-a = { path = "../crate-a", version = "0.1.0" }
+
+[dev-dependencies.crate-a] # tests need {braces
+path = "../crate-a"
+
+[lib]
+path = "src/lib.rs"
+
+[target.'cfg(unix)'.dependencies]
+crate-a.path = "../crate-a"
+
+[build-dependencies]
+crate-a = { path = "../crate-a" } # see {docs
+"#,
+    )
+    .unwrap();
+
+    let output = run_rename(
+        workspace_root,
+        "crate-a",
+        "new-crate",
+        &["--move", "libs/new-crate"],
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    let stdout = String::from_utf8_lossy(&output);
+
+    assert_eq!(
+        fs::read_to_string(workspace_root.join("crate-b/Cargo.toml")).unwrap(),
+        r#"[package]
+name = "crate-b"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+new-crate = { path = "../libs/new-crate", version = "0.1.0" }
+
+[dev-dependencies.new-crate] # tests need {braces
+path = "../libs/new-crate"
+
+[lib]
+path = "src/lib.rs"
+
+[target.'cfg(unix)'.dependencies]
+new-crate.path = "../libs/new-crate"
+
+[build-dependencies]
+new-crate = { path = "../libs/new-crate" } # see {docs
+"#
+    );
+    assert!(stdout.contains(
+        "dependencies ×1, dev-dependencies ×2, target.'cfg(unix)'.dependencies ×1, build-dependencies ×1"
+    ));
+    assert!(verify_workspace_valid(workspace_root));
+}
+
+#[test]
+fn test_annotate_reports_skipped_files() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    fs::write(
+        workspace_root.join("crate-b/src/broken.rs"),
+        "fn broken( {\n    crate_a::hello();\n",
+    )
+    .unwrap();
+
+    let output = run_rename(workspace_root, "crate-a", "new-crate", &["--annotate"])
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8_lossy(&output);
+
+    assert!(stdout.contains("::warning file=crate-b/src/broken.rs,line=2::"));
+}
+
+#[test]
+fn test_force_rewrite_unparsable() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    let broken = workspace_root.join("crate-b/src/broken.rs");
+
+    fs::write(
+        &broken,
+        "macro_rules! call {\n    ($f:ident) => { crate_a::$f() };\n}\nfn broken( {\n",
+    )
+    .unwrap();
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "core",
+        &["--force-rewrite-unparsable"],
+    )
+    .success();
+
+    assert_eq!(
+        fs::read_to_string(&broken).unwrap(),
+        "macro_rules! call {\n    ($f:ident) => { core::$f() };\n}\nfn broken( {\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_edit_conflicts() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    let broken = workspace_root.join("crate-b/src/broken.rs");
+    fs::write(&broken, "fn broken( {\n    crate_a::hello();\n").unwrap();
+
+    // Stands in for the user: checks the suggestion and applies it
+    let editor = workspace_root.join("editor.sh");
+    fs::write(
+        &editor,
+        "#!/bin/sh\n\
+         [ \"$1\" = +2 ] || exit 1\n\
+         grep -q '// cargo-rename: suggested: core::hello();' \"$2\" || exit 1\n\
+         sed 's/    crate_a::/    core::/' \"$2\" > \"$2.new\" && mv \"$2.new\" \"$2\"\n",
+    )
+    .unwrap();
+    fs::set_permissions(&editor, fs::Permissions::from_mode(0o755)).unwrap();
+
+    cargo_bin_cmd!("cargo-rename")
+        .args(["rename", "crate-a", "core", "--yes", "--allow-dirty"])
+        .arg("--edit-conflicts")
+        .env("VISUAL", &editor)
+        .current_dir(workspace_root)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Edited conflicts:"));
+
+    assert_eq!(
+        fs::read_to_string(&broken).unwrap(),
+        "fn broken( {\n    core::hello();\n"
+    );
+}
+
+#[test]
+fn test_timeout_zero_disables_limit() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    run_rename(workspace_root, "crate-a", "new-crate", &["--timeout", "0"]).success();
+
+    verify_workspace_valid(workspace_root);
+}
+
+#[test]
+fn test_hidden_directories_need_include_hidden() {
+    for (flags, expected) in [
+        (&[][..], "Run the crate-a tests first.\n"),
+        (
+            &["--include-hidden"][..],
+            "Run the new-crate tests first.\n",
+        ),
+    ] {
+        let temp = create_test_workspace();
+        let workspace_root = temp.path();
+
+        fs::create_dir(workspace_root.join("crate-a/.github")).unwrap();
+        fs::write(
+            workspace_root.join("crate-a/.github/CONTRIBUTING.md"),
+            "Run the crate-a tests first.\n",
+        )
+        .unwrap();
+
+        run_rename(workspace_root, "crate-a", "new-crate", flags).success();
+
+        let doc =
+            fs::read_to_string(workspace_root.join("crate-a/.github/CONTRIBUTING.md")).unwrap();
+        assert_eq!(doc, expected, "flags: {:?}", flags);
+    }
+}
+
+#[test]
+fn test_fixture_workspace_can_be_renamed() {
+    let temp = TempDir::new().unwrap();
+    let out_dir = temp.path().join("fixture");
+
+    let mut cmd = cargo_bin_cmd!("cargo-rename");
+    cmd.args(["rename", "fixture", "--members", "3"])
+        .args(["--with-target-deps", "--with-aliases"])
+        .arg(&out_dir)
+        .assert()
+        .success();
+    assert!(verify_workspace_valid(&out_dir));
+
+    run_rename(&out_dir, "member-1", "renamed-member", &[]).success();
+
+    let root = fs::read_to_string(out_dir.join("Cargo.toml")).unwrap();
+    assert!(root.contains("renamed-member = { path = \"crates/member-1\""));
+    let last = fs::read_to_string(out_dir.join("crates/member-3/Cargo.toml")).unwrap();
+    assert!(last.contains("first = { package = \"renamed-member\""));
+    let second = fs::read_to_string(out_dir.join("crates/member-2/src/lib.rs")).unwrap();
+    assert!(second.contains("renamed_member::name()"));
+    assert!(verify_workspace_valid(&out_dir));
+}
+
+#[test]
+fn test_normalize_deps_is_idempotent() {
+    let temp = TempDir::new().unwrap();
+    let out_dir = temp.path().join("fixture");
+
+    let mut cmd = cargo_bin_cmd!("cargo-rename");
+    cmd.args(["rename", "fixture", "--members", "3"])
+        .args(["--with-target-deps", "--with-aliases"])
+        .arg(&out_dir)
+        .assert()
+        .success();
+
+    let normalize = || {
+        let mut cmd = cargo_bin_cmd!("cargo-rename");
+        cmd.current_dir(&out_dir)
+            .args([
+                "rename",
+                "normalize-deps",
+                "member-1",
+                "--yes",
+                "--allow-dirty",
+            ])
+            .assert()
+            .success()
+    };
+
+    normalize();
+
+    let last = fs::read_to_string(out_dir.join("crates/member-3/Cargo.toml")).unwrap();
+    assert!(last.contains("member-1 = { workspace = true }"));
+    assert!(!last.contains("first"));
+    let source = fs::read_to_string(out_dir.join("crates/member-3/src/lib.rs")).unwrap();
+    assert!(source.contains("pub use member_1::name as first_name;"));
+    let second = fs::read_to_string(out_dir.join("crates/member-2/Cargo.toml")).unwrap();
+    assert!(!second.contains("path = \"../member-1\""));
+    assert!(verify_workspace_valid(&out_dir));
+
+    let output = normalize().get_output().stdout.clone();
+    assert!(String::from_utf8_lossy(&output).contains("No changes needed"));
+}
+
+#[test]
+fn test_migrate_imports_in_downstream_project() {
+    let temp = TempDir::new().unwrap();
+    let project = temp.path();
+    fs::create_dir_all(project.join("src")).unwrap();
+    fs::create_dir_all(project.join("sub/src")).unwrap();
+    fs::write(
+        project.join("Cargo.toml"),
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nold-crate = \"1.2\"\n\n[features]\nextra = [\"old-crate/extra\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        project.join("src/main.rs"),
+        "use old_crate::Parser;\n\nfn main() {\n    old_crate::run();\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        project.join("sub/Cargo.toml"),
+        "[package]\nname = \"sub\"\nversion = \"0.1.0\"\n\n[dependencies]\noc = { package = \"old-crate\", version = \"1\" }\n",
+    )
+    .unwrap();
+    fs::write(project.join("sub/src/lib.rs"), "use oc::Parser;\n").unwrap();
+
+    let migrate = || {
+        let mut cmd = cargo_bin_cmd!("cargo-rename");
+        cmd.args(["rename", "migrate-imports", "old-crate", "new-crate"])
+            .arg("--path")
+            .arg(project)
+            .args(["--yes", "--allow-dirty"])
+            .assert()
+            .success()
+    };
+
+    migrate();
+
+    let manifest = fs::read_to_string(project.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("new-crate = \"1.2\""));
+    assert!(manifest.contains("extra = [\"new-crate/extra\"]"));
+    assert_eq!(
+        fs::read_to_string(project.join("src/main.rs")).unwrap(),
+        "use new_crate::Parser;\n\nfn main() {\n    new_crate::run();\n}\n"
+    );
+    let sub = fs::read_to_string(project.join("sub/Cargo.toml")).unwrap();
+    assert!(sub.contains("oc = { package = \"new-crate\", version = \"1\" }"));
+    assert_eq!(
+        fs::read_to_string(project.join("sub/src/lib.rs")).unwrap(),
+        "use oc::Parser;\n"
+    );
+
+    let output = migrate().get_output().stdout.clone();
+    assert!(String::from_utf8_lossy(&output).contains("No changes needed"));
+}
+
+#[test]
+fn test_same_named_external_crate_is_not_rewritten() {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+
+    let write = |path: &str, content: &str| {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    };
+    write(
+        "Cargo.toml",
+        "[workspace]\nmembers = [\"rand\", \"app\", \"tool\"]\nexclude = [\"vendor\"]\nresolver = \"2\"\n",
+    );
+    write(
+        "rand/Cargo.toml",
+        "[package]\nname = \"rand\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    );
+    write("rand/src/lib.rs", "pub fn info() {}\n");
+    // Stands in for the crates.io `rand`: same name, different source
+    write(
+        "vendor/rand/Cargo.toml",
+        "[package]\nname = \"rand\"\nversion = \"0.4.0\"\nedition = \"2021\"\n",
+    );
+    write("vendor/rand/src/lib.rs", "pub fn info() {}\n");
+    write(
+        "app/Cargo.toml",
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nrand = { path = \"../rand\" }\nvrand = { package = \"rand\", path = \"../vendor/rand\" }\n",
+    );
+    write("app/src/lib.rs", "pub fn run() {\n    rand::info();\n}\n");
+    write(
+        "tool/Cargo.toml",
+        "[package]\nname = \"tool\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nrand = { path = \"../vendor/rand\" }\n",
+    );
+    write("tool/src/lib.rs", "pub fn run() {\n    rand::info();\n}\n");
+
+    run_rename(root, "rand", "rng", &[])
+        .success()
+        .stdout(predicates::str::contains(
+            "'rand' is a different crate in tool",
+        ));
+
+    assert_eq!(
+        fs::read_to_string(root.join("app/src/lib.rs")).unwrap(),
+        "pub fn run() {\n    rng::info();\n}\n"
+    );
+    assert!(
+        fs::read_to_string(root.join("app/Cargo.toml"))
+            .unwrap()
+            .contains(
+                "rng = { path = \"../rand\" }\nvrand = { package = \"rand\", path = \"../vendor/rand\" }"
+            )
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("tool/src/lib.rs")).unwrap(),
+        "pub fn run() {\n    rand::info();\n}\n"
+    );
+    assert!(
+        fs::read_to_string(root.join("tool/Cargo.toml"))
+            .unwrap()
+            .contains("rand = { path = \"../vendor/rand\" }")
+    );
+    assert!(verify_workspace_valid(root));
+}
+
+#[test]
+fn test_self_dev_dependency_is_renamed() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    let manifest = workspace_root.join("crate-a/Cargo.toml");
+    let content = fs::read_to_string(&manifest).unwrap();
+    fs::write(
+        &manifest,
+        format!(
+            "{}\n[features]\nextra = []\n\n[dev-dependencies]\ncrate-a = {{ path = \".\", features = [\"extra\"] }}\n",
+            content
+        ),
+    )
+    .unwrap();
+
+    run_rename(workspace_root, "crate-a", "crate-x", &["--move"])
+        .success()
+        .stderr(predicates::str::contains(
+            "crate-a depends on itself (dev-dependencies",
+        ));
+
+    let manifest = fs::read_to_string(workspace_root.join("crate-x/Cargo.toml")).unwrap();
+    assert!(manifest.contains(r#"crate-x = { path = ".", features = ["extra"] }"#));
+    assert!(!manifest.contains("crate-a"));
+    assert!(verify_workspace_valid(workspace_root));
+}
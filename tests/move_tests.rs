@@ -162,3 +162,286 @@ edition = "2021"
     assert!(workspace_toml.contains("\"api\""));
     assert!(!workspace_toml.contains("crates/backend/api"));
 }
+
+#[test]
+fn test_move_updates_explicit_target_paths() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    fs::create_dir(workspace_root.join("shared")).unwrap();
+    fs::write(workspace_root.join("shared/cli.rs"), "fn main() {}\n").unwrap();
+
+    let manifest = workspace_root.join("crate-a/Cargo.toml");
+    let mut content = fs::read_to_string(&manifest).unwrap();
+    content = content.replace("[package]\n", "[package]\nautobins = false\n");
+    content.push_str("\n[[bin]]\nname = \"cli\"\npath = \"../shared/cli.rs\"\n");
+    fs::write(&manifest, content).unwrap();
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-a",
+        &["--move", "crates/crate-a"],
+    )
+    .success();
+
+    let cargo_toml = fs::read_to_string(workspace_root.join("crates/crate-a/Cargo.toml")).unwrap();
+    assert!(cargo_toml.contains(r#"path = "../../shared/cli.rs""#));
+    assert!(verify_workspace_valid(workspace_root));
+}
+
+#[test]
+fn test_move_updates_test_runner_configs() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    fs::write(
+        workspace_root.join("codecov.yml"),
+        "flags:\n  crate-a:\n    paths:\n      - crate-a/\n",
+    )
+    .unwrap();
+    fs::create_dir(workspace_root.join(".config")).unwrap();
+    fs::write(
+        workspace_root.join(".config/nextest.toml"),
+        "[[profile.default.overrides]]\nfilter = \"package(crate-a)\"\nretries = 2\n",
+    )
+    .unwrap();
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "awesome-crate",
+        &["--move", "libs/awesome-crate"],
+    )
+    .success();
+
+    let codecov = fs::read_to_string(workspace_root.join("codecov.yml")).unwrap();
+    assert_eq!(
+        codecov,
+        "flags:\n  awesome-crate:\n    paths:\n      - libs/awesome-crate/\n"
+    );
+
+    let nextest = fs::read_to_string(workspace_root.join(".config/nextest.toml")).unwrap();
+    assert!(nextest.contains("filter = \"package(awesome-crate)\""));
+}
+
+#[test]
+fn test_move_out_of_glob_member_adds_explicit_member() {
+    let temp = TempDir::new().unwrap();
+    let workspace_root = temp.path();
+    fs::write(
+        workspace_root.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"crates/*\"]\nresolver = \"2\"\n",
+    )
+    .unwrap();
+    for (name, deps) in [("core", ""), ("app", "core = { path = \"../core\" }\n")] {
+        let dir = workspace_root.join("crates").join(name);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(
+            dir.join("Cargo.toml"),
+            format!(
+                "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\n{}",
+                name, deps
+            ),
+        )
+        .unwrap();
+        fs::write(dir.join("src/lib.rs"), "").unwrap();
+    }
+
+    run_rename(workspace_root, "core", "", &["--move", "libs/core"]).success();
+
+    let workspace_toml = fs::read_to_string(workspace_root.join("Cargo.toml")).unwrap();
+    assert!(workspace_toml.contains(r#"members = ["crates/*", "libs/core"]"#));
+    assert!(verify_workspace_valid(workspace_root));
+}
+
+#[test]
+fn test_move_refuses_directory_with_other_packages() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    fs::write(
+        workspace_root.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"crate-a\", \"crate-a/inner\", \"crate-b\"]\nresolver = \"2\"\n",
+    )
+    .unwrap();
+    let inner = workspace_root.join("crate-a/inner");
+    fs::create_dir_all(inner.join("src")).unwrap();
+    fs::write(
+        inner.join("Cargo.toml"),
+        "[package]\nname = \"inner\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+    fs::write(inner.join("src/lib.rs"), "").unwrap();
+
+    run_rename(workspace_root, "crate-a", "", &["--move", "libs/crate-a"])
+        .failure()
+        .stderr(predicates::str::contains("also contains inner"));
+    assert!(inner.join("Cargo.toml").exists());
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "",
+        &["--move", "libs/crate-a", "--force-shared-dir"],
+    )
+    .success();
+    assert!(
+        workspace_root
+            .join("libs/crate-a/inner/Cargo.toml")
+            .exists()
+    );
+}
+
+#[test]
+fn test_rename_and_move_updates_patch_sections() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    fs::write(
+        workspace_root.join("Cargo.toml"),
+        r#"[workspace]
+members = ["crate-a", "crate-b"]
+resolver = "2"
+
+[patch.crates-io.crate-a]
+path = "./crate-a"
+"#,
+    )
+    .unwrap();
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-c",
+        &["--move", "libs/crate-c"],
+    )
+    .success();
+
+    let manifest = fs::read_to_string(workspace_root.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("[patch.crates-io.crate-c]\npath = \"libs/crate-c\""));
+    assert!(verify_workspace_valid(workspace_root));
+}
+
+#[test]
+fn test_move_with_mixed_workspace_inheritance() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    fs::write(
+        workspace_root.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"crate-a\", \"crate-b\", \"crate-c\"]\nresolver = \"2\"\n\n\
+         [workspace.dependencies]\ncrate-a = { path = \"crate-a\" }\n",
+    )
+    .unwrap();
+    let crate_b_manifest = workspace_root.join("crate-b/Cargo.toml");
+    let content = fs::read_to_string(&crate_b_manifest).unwrap();
+    fs::write(
+        &crate_b_manifest,
+        content.replace(
+            "crate-a = { path = \"../crate-a\" }",
+            "crate-a = { workspace = true }",
+        ),
+    )
+    .unwrap();
+    fs::create_dir_all(workspace_root.join("crate-c/src")).unwrap();
+    fs::write(
+        workspace_root.join("crate-c/Cargo.toml"),
+        "[package]\nname = \"crate-c\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+         [dependencies]\ncrate-a = { path = \"../crate-a\" }\n",
+    )
+    .unwrap();
+    fs::write(workspace_root.join("crate-c/src/lib.rs"), "use crate_a;\n").unwrap();
+
+    run_rename(workspace_root, "crate-a", "crate-x", &["--move"]).success();
+
+    let root_manifest = fs::read_to_string(workspace_root.join("Cargo.toml")).unwrap();
+    assert!(root_manifest.contains("crate-x = { path = \"crate-x\" }"));
+    assert!(
+        fs::read_to_string(&crate_b_manifest)
+            .unwrap()
+            .contains("crate-x = { workspace = true }\n")
+    );
+    assert!(
+        fs::read_to_string(workspace_root.join("crate-c/Cargo.toml"))
+            .unwrap()
+            .contains("crate-x = { path = \"../crate-x\" }")
+    );
+    assert!(verify_workspace_valid(workspace_root));
+}
+
+#[test]
+fn test_move_relative_to_parent_directory() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-a",
+        &["--move", "crates/crate-a"],
+    )
+    .success();
+
+    // Resolved against crates/, not the workspace root
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-a",
+        &["--move-relative", "../libs/core"],
+    )
+    .success();
+
+    assert!(workspace_root.join("libs/core/Cargo.toml").exists());
+    assert!(!workspace_root.join("crates/crate-a").exists());
+    let crate_b_toml = fs::read_to_string(workspace_root.join("crate-b/Cargo.toml")).unwrap();
+    assert!(crate_b_toml.contains(r#"path = "../libs/core""#));
+    assert!(verify_workspace_valid(workspace_root));
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-a",
+        &["--move-relative", "../../.."],
+    )
+    .failure()
+    .stderr(predicates::str::contains("resolves outside workspace"));
+}
+
+#[test]
+fn test_move_rewrites_paths_leaving_the_workspace_and_back() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    let root_name = workspace_root.file_name().unwrap().to_str().unwrap();
+
+    // Both resolve to crate-a, but through the workspace's parent directory
+    let root_toml = fs::read_to_string(workspace_root.join("Cargo.toml")).unwrap();
+    fs::write(
+        workspace_root.join("Cargo.toml"),
+        format!(
+            "{}\n[workspace.dependencies]\ncrate-a = {{ path = \"../{}/crate-a\" }}\n",
+            root_toml, root_name
+        ),
+    )
+    .unwrap();
+    let crate_b_toml = fs::read_to_string(workspace_root.join("crate-b/Cargo.toml")).unwrap();
+    fs::write(
+        workspace_root.join("crate-b/Cargo.toml"),
+        crate_b_toml.replace(
+            r#"path = "../crate-a""#,
+            &format!(r#"path = "../../{}/crate-a""#, root_name),
+        ),
+    )
+    .unwrap();
+    assert!(verify_workspace_valid(workspace_root));
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-a",
+        &["--move", "libs/crate-a"],
+    )
+    .success();
+
+    let root_toml = fs::read_to_string(workspace_root.join("Cargo.toml")).unwrap();
+    assert!(root_toml.contains(r#"crate-a = { path = "libs/crate-a" }"#));
+    let crate_b_toml = fs::read_to_string(workspace_root.join("crate-b/Cargo.toml")).unwrap();
+    assert!(crate_b_toml.contains(r#"path = "../libs/crate-a""#));
+    assert!(verify_workspace_valid(workspace_root));
+}
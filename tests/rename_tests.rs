@@ -60,6 +60,36 @@ fn test_dry_run_does_not_modify() {
     assert!(!cargo_toml.contains("name = \"new-crate\""));
 }
 
+#[test]
+fn test_paths_are_workspace_relative_unless_absolute_requested() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    let absolute_root = fs::canonicalize(workspace_root).unwrap();
+    let absolute_root = absolute_root.to_string_lossy();
+
+    let output = run_rename(workspace_root, "crate-a", "new-crate", &["--dry-run"])
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8_lossy(&output);
+    assert!(stdout.contains("crate-a/Cargo.toml"));
+    assert!(!stdout.contains(absolute_root.as_ref()));
+
+    let output = run_rename(
+        workspace_root,
+        "crate-a",
+        "new-crate",
+        &["--dry-run", "--absolute-paths"],
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    let stdout = String::from_utf8_lossy(&output);
+    assert!(stdout.contains(absolute_root.as_ref()));
+}
+
 #[test]
 fn test_rename_with_workspace_dependencies() {
     let temp = TempDir::new().unwrap();
@@ -122,3 +152,1420 @@ crate-a = { workspace = true }
         fs::read_to_string(workspace_root.join("crates/crate-c/Cargo.toml")).unwrap();
     assert!(crate_c_toml.contains("crate-b = { workspace = true }"));
 }
+
+#[test]
+fn test_record_stats_and_report() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    run_rename(workspace_root, "crate-a", "new-crate", &["--record-stats"]).success();
+
+    let stats = fs::read_to_string(workspace_root.join(".cargo-rename/stats.json")).unwrap();
+    assert!(stats.contains("\"renames\": 1"));
+
+    let mut cmd = cargo_bin_cmd!("cargo-rename");
+    cmd.arg("rename")
+        .arg("stats")
+        .current_dir(workspace_root)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Renames performed"));
+}
+
+#[test]
+fn test_history_log_and_renamed_hint() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    run_rename(workspace_root, "crate-a", "mid-crate", &[]).success();
+    run_rename(workspace_root, "mid-crate", "new-crate", &[]).success();
+
+    let mut cmd = cargo_bin_cmd!("cargo-rename");
+    cmd.arg("rename")
+        .arg("log")
+        .current_dir(workspace_root)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("crate-a → mid-crate"))
+        .stdout(predicates::str::contains("mid-crate → new-crate"));
+
+    run_rename(workspace_root, "crate-a", "other-crate", &[])
+        .failure()
+        .stderr(predicates::str::contains(
+            "it was renamed to 'new-crate' just now",
+        ));
+
+    let history = workspace_root.join(".cargo-rename/history.json");
+    let recorded = fs::read_to_string(&history).unwrap();
+    run_rename(workspace_root, "new-crate", "last-crate", &["--no-history"]).success();
+    assert_eq!(fs::read_to_string(&history).unwrap(), recorded);
+
+    let mut cmd = cargo_bin_cmd!("cargo-rename");
+    cmd.args(["rename", "last-crate", "ci-crate", "--yes", "--allow-dirty"])
+        .env("CI", "true")
+        .current_dir(workspace_root)
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&history).unwrap(), recorded);
+}
+
+#[test]
+fn test_list_members_and_dependents() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    let output = cargo_bin_cmd!("cargo-rename")
+        .args(["rename", "list", "--output", "json"])
+        .current_dir(workspace_root)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let list: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(list[0]["name"], "crate-a");
+    assert_eq!(list[0]["directory"], "crate-a");
+    assert_eq!(list[0]["dependents"], serde_json::json!(["crate-b"]));
+    assert_eq!(
+        list[0]["transitive_dependents"],
+        serde_json::json!(["crate-b"])
+    );
+    assert_eq!(list[1]["name"], "crate-b");
+    assert_eq!(list[1]["dependents"], serde_json::json!([]));
+
+    cargo_bin_cmd!("cargo-rename")
+        .args(["rename", "list"])
+        .current_dir(workspace_root)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("DIRECTORY"))
+        .stdout(predicates::str::contains("crate-b"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_rename_hooks_rewrite_matching_files() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    let mut manifest = fs::read_to_string(workspace_root.join("Cargo.toml")).unwrap();
+    manifest.push_str(
+        r#"
+[[workspace.metadata.cargo-rename.hooks]]
+glob = "deploy/*.conf"
+command = ["sh", "-c", "cat > /dev/null; sed \"s/$CARGO_RENAME_OLD_NAME/$CARGO_RENAME_NEW_NAME/\" \"$CARGO_RENAME_FILE\""]
+"#,
+    );
+    fs::write(workspace_root.join("Cargo.toml"), manifest).unwrap();
+
+    fs::create_dir(workspace_root.join("deploy")).unwrap();
+    fs::write(workspace_root.join("deploy/app.conf"), "service crate-a\n").unwrap();
+
+    // Repository commands only run when allowed
+    run_rename(workspace_root, "crate-a", "new-crate", &[])
+        .success()
+        .stderr(predicates::str::contains("pass --allow-commands"));
+
+    let conf = fs::read_to_string(workspace_root.join("deploy/app.conf")).unwrap();
+    assert_eq!(conf, "service crate-a\n");
+
+    run_rename(workspace_root, "new-crate", "crate-a", &[]).success();
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "new-crate",
+        &["--allow-commands"],
+    )
+    .success();
+
+    let conf = fs::read_to_string(workspace_root.join("deploy/app.conf")).unwrap();
+    assert_eq!(conf, "service new-crate\n");
+
+    run_rename(
+        workspace_root,
+        "new-crate",
+        "third-crate",
+        &["--allow-commands", "--no-hooks"],
+    )
+    .success();
+
+    let conf = fs::read_to_string(workspace_root.join("deploy/app.conf")).unwrap();
+    assert_eq!(conf, "service new-crate\n");
+}
+
+#[test]
+fn test_rename_env_prefix() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    fs::write(
+        workspace_root.join("crate-a/src/lib.rs"),
+        "pub fn level() -> Option<String> { std::env::var(\"CRATE_A_LOG\").ok() }\n",
+    )
+    .unwrap();
+    fs::write(workspace_root.join("crate-a/.env"), "CRATE_A_LOG=info\n").unwrap();
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "new-crate",
+        &["--rename-env-prefix"],
+    )
+    .success()
+    .stdout(predicates::str::contains("CRATE_A_LOG"));
+
+    let lib = fs::read_to_string(workspace_root.join("crate-a/src/lib.rs")).unwrap();
+    assert!(lib.contains("\"NEW_CRATE_LOG\""));
+
+    let env = fs::read_to_string(workspace_root.join("crate-a/.env")).unwrap();
+    assert_eq!(env, "NEW_CRATE_LOG=info\n");
+}
+
+#[test]
+fn test_rename_updates_tool_configs() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    fs::write(
+        workspace_root.join("deny.toml"),
+        "[bans]\nskip = [{ name = \"crate-a\" }]\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace_root.join("custom-tool.toml"),
+        "crates = [\"crate-a\"]\n",
+    )
+    .unwrap();
+
+    let output = run_rename(
+        workspace_root,
+        "crate-a",
+        "new-crate",
+        &["--tool-config", "custom-tool.toml"],
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+
+    let deny = fs::read_to_string(workspace_root.join("deny.toml")).unwrap();
+    assert!(deny.contains(r#"{ name = "new-crate" }"#));
+
+    let custom = fs::read_to_string(workspace_root.join("custom-tool.toml")).unwrap();
+    assert!(custom.contains(r#"["new-crate"]"#));
+
+    assert!(String::from_utf8_lossy(&output).contains("Tool configs (2 files)"));
+}
+
+#[test]
+fn test_keep_dir_name() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    let output = run_rename(workspace_root, "crate-a", "new-crate", &[])
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert!(String::from_utf8_lossy(&output).contains("still has the old name"));
+
+    let output = run_rename(workspace_root, "new-crate", "core", &["--keep-dir-name"])
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8_lossy(&output);
+
+    assert!(!stdout.contains("--move"));
+    assert!(stdout.contains("Package directory: core → crate-a"));
+    // The package manifest is recognized although its directory name differs
+    let package_section = stdout.split("Package manifest").nth(1).unwrap();
+    assert!(package_section.starts_with("\n   ✓ crate-a/Cargo.toml"));
+    assert!(workspace_root.join("crate-a/Cargo.toml").exists());
+
+    run_rename(
+        workspace_root,
+        "core",
+        "other",
+        &["--keep-dir-name", "--move"],
+    )
+    .failure();
+}
+
+#[test]
+fn test_compat_shims_follow_renames() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    let lib = workspace_root.join("crate-b/src/lib.rs");
+
+    // A re-export under an unrelated alias is not a shim
+    fs::write(&lib, "pub use crate_a as facade;\n").unwrap();
+
+    let output = run_rename(workspace_root, "crate-a", "middle", &["--drop-shims"])
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert!(!String::from_utf8_lossy(&output).contains("Compatibility shims:"));
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "pub use middle as facade;\n"
+    );
+
+    fs::write(
+        &lib,
+        "pub use middle as facade;\n\n/// Old name of `middle`.\n#[doc(hidden)]\npub use middle as crate_a;\npub use middle as legacy; // cargo-rename: shim\n",
+    )
+    .unwrap();
+
+    run_rename(workspace_root, "middle", "last", &[])
+        .success()
+        .stdout(predicates::str::contains("Compatibility shims:"));
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "pub use last as facade;\n\n/// Old name of `last`.\n#[doc(hidden)]\npub use last as crate_a;\npub use last as legacy; // cargo-rename: shim\n"
+    );
+
+    run_rename(workspace_root, "last", "end", &["--drop-shims"])
+        .success()
+        .stdout(predicates::str::contains(
+            "removed `pub use last as crate_a;`",
+        ))
+        .stdout(predicates::str::contains(
+            "removed `pub use last as legacy;`",
+        ));
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "pub use end as facade;\n\n"
+    );
+    assert!(verify_workspace_valid(workspace_root));
+}
+
+#[test]
+fn test_select_package_by_directory() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    // A nested workspace with a member of the same name
+    let nested = workspace_root.join("tools/crate-a");
+    fs::create_dir_all(nested.join("src")).unwrap();
+    fs::write(
+        workspace_root.join("tools/Cargo.toml"),
+        "[workspace]\nmembers = [\"crate-a\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        nested.join("Cargo.toml"),
+        "[package]\nname = \"crate-a\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+    fs::write(nested.join("src/lib.rs"), "").unwrap();
+
+    let mut cmd = cargo_bin_cmd!("cargo-rename");
+    cmd.args(["rename", "--at", "tools/crate-a", "tool-crate", "--yes"])
+        .arg("--allow-dirty")
+        .current_dir(workspace_root)
+        .assert()
+        .success();
+
+    let nested_manifest = fs::read_to_string(nested.join("Cargo.toml")).unwrap();
+    assert!(nested_manifest.contains("name = \"tool-crate\""));
+    let outer_manifest = fs::read_to_string(workspace_root.join("crate-a/Cargo.toml")).unwrap();
+    assert!(outer_manifest.contains("name = \"crate-a\""));
+
+    let mut cmd = cargo_bin_cmd!("cargo-rename");
+    cmd.args(["rename", "--at", "crate-a/Cargo.toml", "new-crate", "--yes"])
+        .arg("--allow-dirty")
+        .current_dir(workspace_root)
+        .assert()
+        .success();
+
+    let dependent = fs::read_to_string(workspace_root.join("crate-b/Cargo.toml")).unwrap();
+    assert!(dependent.contains("new-crate = { path = \"../crate-a\" }"));
+    assert!(verify_workspace_valid(workspace_root));
+}
+
+#[test]
+fn test_set_repository_rewrites_url_segment() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    let manifest = workspace_root.join("crate-a/Cargo.toml");
+    let mut content = fs::read_to_string(&manifest).unwrap();
+    content.push_str("repository = \"https://github.com/acme/crate-a\"\n");
+    content.push_str("homepage = \"https://crate-a.example.com/crate-a-docs\"\n");
+    fs::write(&manifest, content).unwrap();
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-x",
+        &["--set-repository", "--set-homepage"],
+    )
+    .success();
+
+    let result = fs::read_to_string(&manifest).unwrap();
+    assert!(result.contains("repository = \"https://github.com/acme/crate-x\""));
+    assert!(result.contains("homepage = \"https://crate-a.example.com/crate-a-docs\""));
+}
+
+#[test]
+fn test_update_description_reports_and_rewrites_metadata() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    let manifest = workspace_root.join("crate-a/Cargo.toml");
+    let mut content = fs::read_to_string(&manifest).unwrap();
+    content.push_str("rust-version = \"1.70\"\n");
+    content.push_str("description = \"The crate-a parser\"\n");
+    content.push_str("keywords = [\"crate-a\", \"parser\"]\n");
+    fs::write(&manifest, content).unwrap();
+
+    run_rename(workspace_root, "crate-a", "crate-x", &["--dry-run"])
+        .success()
+        .stdout(predicates::str::contains("rust-version: 1.70"))
+        .stdout(predicates::str::contains(
+            "description \"The crate-a parser\" still mentions the old name",
+        ))
+        .stdout(predicates::str::contains("Pass --update-description"));
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-x",
+        &["--update-description"],
+    )
+    .success();
+
+    let result = fs::read_to_string(&manifest).unwrap();
+    assert!(result.contains("description = \"The crate-x parser\""));
+    assert!(result.contains("keywords = [\"crate-x\", \"parser\"]"));
+    assert!(verify_workspace_valid(workspace_root));
+}
+
+#[test]
+fn test_ascii_output() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    let assert = run_rename(workspace_root, "crate-a", "crate-x", &["--ascii", "--move"]).success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+
+    assert!(stdout.is_ascii(), "non-ASCII output:\n{}", stdout);
+    assert!(stdout.contains("[ok] Successfully renamed"));
+    assert!(stdout.contains("crate-a -> crate-x"));
+}
+
+#[test]
+fn test_manifests_only() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    run_rename(workspace_root, "crate-a", "core", &["--manifests-only"])
+        .success()
+        .stderr(predicates::str::contains(
+            "--manifests-only leaves Rust sources and docs referring to 'crate_a'",
+        ));
+    assert!(
+        fs::read_to_string(workspace_root.join("crate-b/Cargo.toml"))
+            .unwrap()
+            .contains("core = { path = \"../crate-a\" }")
+    );
+    assert_eq!(
+        fs::read_to_string(workspace_root.join("crate-b/src/lib.rs")).unwrap(),
+        "use crate_a;\npub fn greet() {}"
+    );
+
+    // A dry run reads the manifests directly, so an unresolvable dependency
+    // that would make `cargo metadata` fail does not matter
+    let manifest = workspace_root.join("crate-b/Cargo.toml");
+    let content = fs::read_to_string(&manifest).unwrap();
+    fs::write(
+        &manifest,
+        content.replace(
+            "[dependencies]",
+            "[dependencies]\nmissing = { path = \"../missing\" }",
+        ),
+    )
+    .unwrap();
+
+    let output = run_rename(
+        workspace_root,
+        "core",
+        "engine",
+        &["--manifests-only", "--dry-run"],
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    let stdout = String::from_utf8_lossy(&output);
+
+    assert!(stdout.contains("DRY RUN"));
+    assert!(stdout.contains("crate-b/Cargo.toml"));
+    assert!(fs::read_to_string(&manifest).unwrap().contains("core = {"));
+}
+
+#[test]
+fn test_notify_external_dependents() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    let external = TempDir::new().unwrap();
+    let manifest = external.path().join("Cargo.toml");
+    fs::write(
+        &manifest,
+        format!(
+            "[package]\nname = \"app\"\n\n[dependencies]\ncrate-a = {{ path = \"{}\" }}\n",
+            workspace_root.join("crate-a").display()
+        ),
+    )
+    .unwrap();
+
+    let output = run_rename(
+        workspace_root,
+        "crate-a",
+        "core",
+        &[
+            "--move",
+            "--notify-external",
+            &external.path().display().to_string(),
+        ],
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+
+    assert!(String::from_utf8_lossy(&output).contains("External dependents:"));
+    let updated = fs::read_to_string(&manifest).unwrap();
+    let dep = updated.lines().last().unwrap();
+    assert!(dep.starts_with("core = { path = \""));
+    assert!(dep.ends_with("/core\" }"));
+}
+
+#[test]
+fn test_scan_dirs_updates_sibling_projects() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    let ws_name = workspace_root.file_name().unwrap().to_str().unwrap();
+
+    // A sibling checkout, referencing the crate by relative path
+    let harness = TempDir::new().unwrap();
+    let manifest = harness.path().join("tests/Cargo.toml");
+    fs::create_dir_all(manifest.parent().unwrap()).unwrap();
+    let original = format!(
+        "[package]\nname = \"harness\"\n\n[dev-dependencies]\ncrate-a = {{ path = \"../../{}/crate-a\" }}\n",
+        ws_name
+    );
+    fs::write(&manifest, &original).unwrap();
+    let harness_dir = harness.path().display().to_string();
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "core",
+        &["--move", "--dry-run", "--scan-dirs", &harness_dir],
+    )
+    .success();
+    assert_eq!(fs::read_to_string(&manifest).unwrap(), original);
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "core",
+        &["--move", "--scan-dirs", &harness_dir],
+    )
+    .success();
+    assert!(
+        fs::read_to_string(&manifest)
+            .unwrap()
+            .contains(&format!("core = {{ path = \"../../{}/core\" }}", ws_name))
+    );
+}
+
+#[test]
+fn test_sandbox_rehearsal() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    let output = run_rename(
+        workspace_root,
+        "crate-a",
+        "core",
+        &["--sandbox", "--dry-run"],
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    assert!(String::from_utf8_lossy(&output).contains("Sandbox rename and cargo check succeeded"));
+    assert!(
+        fs::read_to_string(workspace_root.join("crate-a/Cargo.toml"))
+            .unwrap()
+            .contains("name = \"crate-a\"")
+    );
+
+    // Absolute paths are mapped into the copy, or refused outside the workspace
+    let target = workspace_root.canonicalize().unwrap().join("libs/core");
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "core",
+        &["--sandbox", "--dry-run", "--move", target.to_str().unwrap()],
+    )
+    .success();
+    assert!(!workspace_root.join("libs").exists());
+    assert!(workspace_root.join("crate-a/Cargo.toml").exists());
+
+    let outside = tempfile::TempDir::new().unwrap();
+    let tool_config = outside.path().join("deny.toml");
+    fs::write(&tool_config, "[bans]\nskip = [\"crate-a\"]\n").unwrap();
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "core",
+        &["--sandbox", "--tool-config", tool_config.to_str().unwrap()],
+    )
+    .failure()
+    .stderr(predicates::str::contains(
+        "cannot be rehearsed with --sandbox",
+    ));
+    assert!(
+        fs::read_to_string(&tool_config)
+            .unwrap()
+            .contains("crate-a")
+    );
+
+    // A copy that no longer builds leaves the workspace untouched
+    fs::write(
+        workspace_root.join("crate-b/src/lib.rs"),
+        "use crate_a;\npub fn greet() -> u32 { \"not a number\" }",
+    )
+    .unwrap();
+    run_rename(workspace_root, "crate-a", "core", &["--sandbox"])
+        .failure()
+        .stderr(predicates::str::contains(
+            "cargo check failed in the sandbox",
+        ));
+    assert!(
+        fs::read_to_string(workspace_root.join("crate-a/Cargo.toml"))
+            .unwrap()
+            .contains("name = \"crate-a\"")
+    );
+}
+
+#[test]
+fn test_verify_check_rolls_back() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    let crate_b_manifest = fs::read_to_string(workspace_root.join("crate-b/Cargo.toml")).unwrap();
+    assert!(
+        std::process::Command::new("cargo")
+            .args(["generate-lockfile", "--offline"])
+            .current_dir(workspace_root)
+            .status()
+            .unwrap()
+            .success()
+    );
+    let lockfile = fs::read_to_string(workspace_root.join("Cargo.lock")).unwrap();
+
+    // The excluded `use crate_a;` no longer resolves after the rename
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "core",
+        &["--verify", "check", "--exclude", "crate-b/src/lib.rs"],
+    )
+    .failure()
+    .stderr(predicates::str::contains(
+        "`cargo check --workspace --all-targets` failed after the rename, which was rolled back",
+    ));
+    assert!(
+        fs::read_to_string(workspace_root.join("crate-a/Cargo.toml"))
+            .unwrap()
+            .contains("name = \"crate-a\"")
+    );
+    assert_eq!(
+        fs::read_to_string(workspace_root.join("crate-b/Cargo.toml")).unwrap(),
+        crate_b_manifest
+    );
+    // `cargo check` rewrote the lockfile for the new name
+    assert_eq!(
+        fs::read_to_string(workspace_root.join("Cargo.lock")).unwrap(),
+        lockfile
+    );
+
+    run_rename(workspace_root, "crate-a", "core", &["--verify", "check"]).success();
+    assert!(verify_workspace_valid(workspace_root));
+}
+
+#[test]
+fn test_emit_ssr() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    fs::write(
+        workspace_root.join("crate-b/src/lib.rs"),
+        "use crate_a::{hello, util::Config as Cfg};\npub fn greet() { crate_a::hello(); }\n",
+    )
+    .unwrap();
+
+    let output = run_rename(workspace_root, "crate-a", "core", &["--emit-ssr"])
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8_lossy(&output);
+
+    assert!(stdout.contains("crate_a::hello ==>> core::hello"));
+    assert!(stdout.contains("crate_a::util::Config ==>> core::util::Config"));
+    assert!(
+        fs::read_to_string(workspace_root.join("crate-b/Cargo.toml"))
+            .unwrap()
+            .contains("core = { path = \"../crate-a\" }")
+    );
+    assert!(
+        fs::read_to_string(workspace_root.join("crate-b/src/lib.rs"))
+            .unwrap()
+            .starts_with("use crate_a::{")
+    );
+}
+
+#[test]
+fn test_summary_groups_by_package() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    let output = run_rename(workspace_root, "crate-a", "core", &["--dry-run", "--json"])
+        .success()
+        .get_output()
+        .clone();
+    // The report goes to stderr, keeping stdout for the summary
+    assert!(String::from_utf8_lossy(&output.stderr).contains("By package"));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let packages = json["packages"].as_array().unwrap();
+    let crate_b = packages
+        .iter()
+        .find(|p| p["name"] == "crate-b")
+        .expect("crate-b group");
+    assert!(
+        crate_b["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|f| f.as_str().unwrap().ends_with("crate-b/src/lib.rs"))
+    );
+}
+
+#[test]
+fn test_matched_and_disabled_rules() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    let lib = workspace_root.join("crate-b/src/lib.rs");
+    fs::write(
+        &lib,
+        "use crate_a;\npub fn greet() { crate_a::hello(); crate_a_log!(\"hi\"); }\n",
+    )
+    .unwrap();
+
+    let output = run_rename(
+        workspace_root,
+        "crate-a",
+        "core",
+        &["--dry-run", "--json", "--disable-rules", "macro"],
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let rules = json["rules"]
+        .as_object()
+        .unwrap()
+        .iter()
+        .find(|(path, _)| path.ends_with("crate-b/src/lib.rs"))
+        .map(|(_, rules)| rules.clone())
+        .expect("rules for crate-b/src/lib.rs");
+    assert_eq!(
+        rules,
+        serde_json::json!(["use-statement", "qualified-path"])
+    );
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "core",
+        &["--disable-rules", "macro"],
+    )
+    .success();
+    let content = fs::read_to_string(&lib).unwrap();
+    assert!(content.contains("core::hello()"));
+    assert!(content.contains("crate_a_log!"));
+}
+
+#[test]
+fn test_workspace_root_shown_and_pinned() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    // Run from a member: the plan names the root found by searching upward
+    cargo_bin_cmd!("cargo-rename")
+        .args(["rename", "crate-a", "crate-z", "--stop-after", "plan"])
+        .current_dir(workspace_root.join("crate-b"))
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Workspace: .."))
+        .stdout(predicates::str::contains("Manifest: ../Cargo.toml"));
+
+    // crate-b/Cargo.toml belongs to the workspace above it
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-z",
+        &["--workspace-root", "crate-b"],
+    )
+    .failure()
+    .stderr(predicates::str::contains("as pinned with --workspace-root"));
+    assert!(workspace_root.join("crate-a").exists());
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-z",
+        &["--workspace-root", "."],
+    )
+    .success();
+    assert!(
+        fs::read_to_string(workspace_root.join("crate-a/Cargo.toml"))
+            .unwrap()
+            .contains("name = \"crate-z\"")
+    );
+}
+
+#[test]
+fn test_nested_workspace_parent_dependents() {
+    let temp = TempDir::new().unwrap();
+    let outer = temp.path();
+    let inner = outer.join("inner");
+    for (path, content) in [
+        (
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"app\"]\nexclude = [\"inner\"]\nresolver = \"2\"\n",
+        ),
+        (
+            "app/Cargo.toml",
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+             [dependencies]\ncrate-a = { path = \"../inner/crate-a\" }\n",
+        ),
+        ("app/src/lib.rs", ""),
+        (
+            "inner/Cargo.toml",
+            "[workspace]\nmembers = [\"crate-a\"]\nresolver = \"2\"\n",
+        ),
+        (
+            "inner/crate-a/Cargo.toml",
+            "[package]\nname = \"crate-a\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        ),
+        ("inner/crate-a/src/lib.rs", ""),
+    ] {
+        let path = outer.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    // Reported, not updated
+    run_rename(&inner, "crate-a", "crate-z", &["--dry-run"])
+        .success()
+        .stderr(predicates::str::contains(
+            "1 manifest in the enclosing workspace .. depends on 'crate-a' by path",
+        ));
+
+    run_rename(
+        &inner,
+        "crate-a",
+        "crate-z",
+        &["--move", "--include-parent-workspace"],
+    )
+    .success();
+    let app_toml = fs::read_to_string(outer.join("app/Cargo.toml")).unwrap();
+    assert!(app_toml.contains(r#"crate-z = { path = "../inner/crate-z" }"#));
+    assert!(verify_workspace_valid(outer));
+    assert!(verify_workspace_valid(&inner));
+}
+
+#[test]
+fn test_check_files_for_renamed_crates() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    run_rename(workspace_root, "crate-a", "new-crate", &[]).success();
+    fs::write(
+        workspace_root.join("crate-b/src/stale.rs"),
+        "// crate_a is gone\nfn f() {\n    crate_a::hello();\n}\n",
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("cargo-rename")
+        .args(["rename", "check", "--files"])
+        .args(["crate-b/src/lib.rs", "crate-b/Cargo.toml", "README.md"])
+        .current_dir(workspace_root)
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("cargo-rename")
+        .args(["rename", "check", "--files"])
+        .args(["crate-b/src/lib.rs", "crate-b/src/stale.rs"])
+        .current_dir(workspace_root)
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains(
+            "crate-b/src/stale.rs:3:5: 'crate-a' was renamed to 'new-crate'",
+        ))
+        .stderr(predicates::str::contains(
+            "1 reference to renamed crates found",
+        ));
+}
+
+#[test]
+fn test_stop_after_phase() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    let manifest = fs::read_to_string(workspace_root.join("crate-a/Cargo.toml")).unwrap();
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-z",
+        &["--stop-after", "plan"],
+    )
+    .success();
+    assert!(!workspace_root.join(".cargo-rename").exists());
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-z",
+        &["--stop-after", "stage"],
+    )
+    .success();
+    assert_eq!(
+        fs::read_to_string(workspace_root.join("crate-a/Cargo.toml")).unwrap(),
+        manifest
+    );
+    let dump: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(workspace_root.join(".cargo-rename/transaction.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(dump["new_name"], "crate-z");
+    let operations = dump["operations"].as_array().unwrap();
+    assert!(operations.iter().any(|op| op["op"] == "update-file"
+        && op["path"] == "crate-a/Cargo.toml"
+        && op["new"].as_str().unwrap().contains("name = \"crate-z\"")));
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-z",
+        &["--stop-after", "commit"],
+    )
+    .success();
+    assert!(
+        fs::read_to_string(workspace_root.join("crate-a/Cargo.toml"))
+            .unwrap()
+            .contains("name = \"crate-z\"")
+    );
+    assert!(!workspace_root.join(".cargo-rename/history.json").exists());
+}
+
+#[test]
+fn test_paths_in_string_literals() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    fs::write(
+        workspace_root.join("crate-a/src/lib.rs"),
+        r#"#[deprecated(note = "use crate_a::v2::parse instead")]
+pub fn parse() {}
+
+pub fn hint() -> &'static str {
+    "see crate_a::Error; the crate_a crate explains it"
+}
+"#,
+    )
+    .unwrap();
+
+    run_rename(workspace_root, "crate-a", "crate-z", &[]).success();
+
+    let lib = fs::read_to_string(workspace_root.join("crate-a/src/lib.rs")).unwrap();
+    assert!(lib.contains(r#"note = "use crate_z::v2::parse instead""#));
+    assert!(lib.contains(r#""see crate_z::Error; the crate_a crate explains it""#));
+}
+
+#[test]
+fn test_visibility_paths() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    let lib = workspace_root.join("crate-b/src/lib.rs");
+    fs::write(
+        &lib,
+        r#"pub(in crate_a::internal) fn a() {}
+pub(in ::crate_a) fn b() {}
+pub( in crate_a ) fn c() {}
+pub(in crate::crate_a_compat) fn d() {}
+pub(crate) fn e() -> crate::Local { crate::Local }
+"#,
+    )
+    .unwrap();
+
+    run_rename(workspace_root, "crate-a", "crate-z", &[]).success();
+
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        r#"pub(in crate_z::internal) fn a() {}
+pub(in ::crate_z) fn b() {}
+pub( in crate_z ) fn c() {}
+pub(in crate::crate_a_compat) fn d() {}
+pub(crate) fn e() -> crate::Local { crate::Local }
+"#
+    );
+}
+
+#[test]
+fn test_exclude_globs_skip_source_files() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    let generated = workspace_root.join("crate-b/benches/generated");
+    fs::create_dir_all(&generated).unwrap();
+    fs::write(generated.join("bench.rs"), "use crate_a::Thing;\n").unwrap();
+    fs::write(
+        workspace_root.join("crate-b/benches/manual.rs"),
+        "use crate_a::Thing;\n",
+    )
+    .unwrap();
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-z",
+        &["--exclude", "benches/generated/**"],
+    )
+    .success();
+
+    assert_eq!(
+        fs::read_to_string(generated.join("bench.rs")).unwrap(),
+        "use crate_a::Thing;\n"
+    );
+    assert_eq!(
+        fs::read_to_string(workspace_root.join("crate-b/benches/manual.rs")).unwrap(),
+        "use crate_z::Thing;\n"
+    );
+}
+
+#[test]
+fn test_trace_edits_replay_and_revert() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    let lib_rs = workspace_root.join("crate-b/src/lib.rs");
+    fs::write(
+        &lib_rs,
+        "use crate_a;\npub fn greet() { crate_a::hello(); }\n",
+    )
+    .unwrap();
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-x",
+        &["--move", "--trace-edits", "trace.jsonl"],
+    )
+    .success();
+
+    let trace = fs::read_to_string(workspace_root.join("trace.jsonl")).unwrap();
+    assert_eq!(trace.lines().count(), 2, "trace:\n{}", trace);
+    assert!(trace.contains(r#""file":"crate-b/src/lib.rs","rule":"use-statement""#));
+    assert!(trace.contains(r#""before":"crate_a::","after":"crate_x::""#));
+
+    let renamed = fs::read_to_string(&lib_rs).unwrap();
+    cargo_bin_cmd!("cargo-rename")
+        .args(["rename", "replay-trace", "trace.jsonl", "--revert"])
+        .args(["--file", "crate-b/src/lib.rs"])
+        .current_dir(workspace_root)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Reverted 2 edit(s)"));
+    assert_eq!(
+        fs::read_to_string(&lib_rs).unwrap(),
+        "use crate_a;\npub fn greet() { crate_a::hello(); }\n"
+    );
+
+    // Reverting twice does not match the trace and leaves the file alone
+    cargo_bin_cmd!("cargo-rename")
+        .args(["rename", "replay-trace", "trace.jsonl", "--revert"])
+        .current_dir(workspace_root)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("does not match the trace"));
+
+    cargo_bin_cmd!("cargo-rename")
+        .args(["rename", "replay-trace", "trace.jsonl"])
+        .current_dir(workspace_root)
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&lib_rs).unwrap(), renamed);
+}
+
+#[test]
+fn test_docs_scope() {
+    let setup = || {
+        let temp = create_test_workspace();
+        let root = temp.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crate-a\", \"crate-b\", \"crate-c\"]\nresolver = \"2\"\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("crate-c/src")).unwrap();
+        fs::write(
+            root.join("crate-c/Cargo.toml"),
+            "[package]\nname = \"crate-c\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        fs::write(root.join("crate-c/src/lib.rs"), "").unwrap();
+        for member in ["crate-a", "crate-b", "crate-c"] {
+            fs::write(root.join(member).join("README.md"), "See crate-a.\n").unwrap();
+        }
+        temp
+    };
+    let rewritten = |root: &std::path::Path| {
+        ["crate-a", "crate-b", "crate-c"].map(|member| {
+            fs::read_to_string(root.join(member).join("README.md")).unwrap() == "See crate-x.\n"
+        })
+    };
+
+    let temp = setup();
+    run_rename(temp.path(), "crate-a", "crate-x", &[]).success();
+    assert_eq!(rewritten(temp.path()), [true, true, false]);
+
+    let temp = setup();
+    run_rename(
+        temp.path(),
+        "crate-a",
+        "crate-x",
+        &["--docs-scope", "package"],
+    )
+    .success();
+    assert_eq!(rewritten(temp.path()), [true, false, false]);
+
+    let temp = setup();
+    run_rename(
+        temp.path(),
+        "crate-a",
+        "crate-x",
+        &["--docs-scope", "workspace"],
+    )
+    .success();
+    assert_eq!(rewritten(temp.path()), [true, true, true]);
+
+    run_rename(temp.path(), "crate-x", "crate-y", &["--stop-after", "plan"])
+        .success()
+        .stdout(predicates::str::contains("Docs: dependents"));
+}
+
+#[test]
+fn test_only_kinds_dev() {
+    let temp = create_test_workspace();
+    let root = temp.path();
+    fs::write(
+        root.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"crate-a\", \"crate-b\", \"crate-c\"]\nresolver = \"2\"\n",
+    )
+    .unwrap();
+    fs::create_dir_all(root.join("crate-c/src")).unwrap();
+    fs::create_dir_all(root.join("crate-c/tests")).unwrap();
+    fs::write(
+        root.join("crate-c/Cargo.toml"),
+        "[package]\nname = \"crate-c\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+         [dev-dependencies]\ncrate-a = { path = \"../crate-a\" }\n",
+    )
+    .unwrap();
+    fs::write(root.join("crate-c/src/lib.rs"), "// crate_a::hello\n").unwrap();
+    fs::write(root.join("crate-c/tests/it.rs"), "use crate_a::hello;\n").unwrap();
+    let crate_b_lib = fs::read_to_string(root.join("crate-b/src/lib.rs")).unwrap();
+
+    run_rename(root, "crate-a", "crate-x", &["--only-kinds", "dev"])
+        .success()
+        .stderr(predicates::str::contains(
+            "crate-b keeps its [dependencies] on 'crate-a'",
+        ));
+
+    let crate_c_manifest = fs::read_to_string(root.join("crate-c/Cargo.toml")).unwrap();
+    assert!(crate_c_manifest.contains("crate-x = { path = \"../crate-a\" }"));
+    assert_eq!(
+        fs::read_to_string(root.join("crate-c/tests/it.rs")).unwrap(),
+        "use crate_x::hello;\n"
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("crate-c/src/lib.rs")).unwrap(),
+        "// crate_a::hello\n"
+    );
+    let crate_b_manifest = fs::read_to_string(root.join("crate-b/Cargo.toml")).unwrap();
+    assert!(
+        crate_b_manifest.contains("crate-a = { path = \"../crate-a\", package = \"crate-x\" }")
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("crate-b/src/lib.rs")).unwrap(),
+        crate_b_lib
+    );
+    assert!(verify_workspace_valid(root));
+}
+
+#[test]
+fn test_rename_links() {
+    let temp = create_test_workspace();
+    let root = temp.path();
+    let manifest = root.join("crate-a/Cargo.toml");
+    let content = fs::read_to_string(&manifest).unwrap();
+    fs::write(
+        &manifest,
+        content.replace("[package]\n", "[package]\nlinks = \"crate-a\"\n"),
+    )
+    .unwrap();
+    fs::write(root.join("crate-a/build.rs"), "fn main() {}\n").unwrap();
+    fs::write(
+        root.join("crate-b/build.rs"),
+        "fn main() {\n    let _ = std::env::var(\"DEP_CRATE_A_INCLUDE\");\n}\n",
+    )
+    .unwrap();
+
+    run_rename(root, "crate-a", "crate-x", &["--rename-links"]).success();
+
+    assert!(
+        fs::read_to_string(&manifest)
+            .unwrap()
+            .contains("links = \"crate-x\"")
+    );
+    assert!(
+        fs::read_to_string(root.join("crate-b/build.rs"))
+            .unwrap()
+            .contains("\"DEP_CRATE_X_INCLUDE\"")
+    );
+    assert!(verify_workspace_valid(root));
+}
+
+#[test]
+fn test_via_alias() {
+    let temp = create_test_workspace();
+    let root = temp.path();
+    fs::write(
+        root.join("crate-b/src/lib.rs"),
+        "//! Greetings\n#![allow(unused_imports)]\nuse crate_a::hello;\npub fn greet() {}\n",
+    )
+    .unwrap();
+    fs::create_dir(root.join("crate-b/examples")).unwrap();
+    fs::write(
+        root.join("crate-b/examples/demo.rs"),
+        "mod crate_a {}\nfn main() {}\n",
+    )
+    .unwrap();
+
+    run_rename(
+        root,
+        "crate-a",
+        "crate-x",
+        &["--via-alias", "--verify", "check"],
+    )
+    .success()
+    .stderr(predicates::str::contains("already declares `crate_a`"));
+
+    assert_eq!(
+        fs::read_to_string(root.join("crate-b/src/lib.rs")).unwrap(),
+        "//! Greetings\n#![allow(unused_imports)]\nextern crate crate_x as crate_a;\n\nuse crate_a::hello;\npub fn greet() {}\n"
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("crate-b/examples/demo.rs")).unwrap(),
+        "mod crate_a {}\nfn main() {}\n"
+    );
+    assert!(verify_workspace_valid(root));
+}
+
+#[test]
+fn test_non_utf8_files_are_skipped() {
+    let temp = create_test_workspace();
+    let root = temp.path();
+    let fixture = b"// \xff\xfe crate_a::hello()\n".to_vec();
+    let readme = b"# Caf\xe9\n\nUses crate-a.\n".to_vec();
+    fs::write(root.join("crate-b/src/fixture.rs"), &fixture).unwrap();
+    fs::write(root.join("crate-b/README.md"), &readme).unwrap();
+
+    run_rename(root, "crate-a", "crate-x", &[])
+        .success()
+        .stdout(predicates::str::contains("not valid UTF-8"));
+
+    assert_eq!(
+        fs::read(root.join("crate-b/src/fixture.rs")).unwrap(),
+        fixture
+    );
+    assert_eq!(fs::read(root.join("crate-b/README.md")).unwrap(), readme);
+    assert_eq!(
+        fs::read_to_string(root.join("crate-b/src/lib.rs")).unwrap(),
+        "use crate_x;\npub fn greet() {}"
+    );
+    assert!(verify_workspace_valid(root));
+}
+
+#[test]
+fn test_skip_category() {
+    let temp = create_test_workspace();
+    let root = temp.path();
+    fs::write(root.join("crate-b/README.md"), "Uses crate-a.\n").unwrap();
+
+    run_rename(root, "crate-a", "crate-x", &["--skip-category", "package"])
+        .failure()
+        .stderr(predicates::str::contains("invalid value 'package'"));
+
+    // Dependents would keep depending on `crate-a`
+    run_rename(
+        root,
+        "crate-a",
+        "crate-x",
+        &["--skip-category", "dependencies"],
+    )
+    .failure()
+    .stderr(predicates::str::contains(
+        "--skip-category dependencies would leave these manifests on the old package",
+    ));
+    assert!(
+        fs::read_to_string(root.join("crate-a/Cargo.toml"))
+            .unwrap()
+            .contains(r#"name = "crate-a""#)
+    );
+
+    run_rename(root, "crate-a", "crate-x", &["--skip-category", "docs"]).success();
+
+    assert_eq!(
+        fs::read_to_string(root.join("crate-b/README.md")).unwrap(),
+        "Uses crate-a.\n"
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("crate-b/src/lib.rs")).unwrap(),
+        "use crate_x;\npub fn greet() {}"
+    );
+    assert!(verify_workspace_valid(root));
+}
+
+#[test]
+fn test_set_version() {
+    let temp = create_test_workspace();
+    let root = temp.path();
+    let crate_b_toml = root.join("crate-b/Cargo.toml");
+    let content = fs::read_to_string(&crate_b_toml).unwrap();
+    fs::write(
+        &crate_b_toml,
+        content.replace(
+            r#"crate-a = { path = "../crate-a" }"#,
+            r#"crate-a = { path = "../crate-a", version = "0.1" }"#,
+        ),
+    )
+    .unwrap();
+
+    run_rename(root, "crate-a", "crate-x", &["--set-version", "1.0.0"])
+        .success()
+        .stdout(predicates::str::contains("Package version:"));
+
+    assert!(
+        fs::read_to_string(root.join("crate-a/Cargo.toml"))
+            .unwrap()
+            .contains(r#"version = "1.0.0""#)
+    );
+    assert!(
+        fs::read_to_string(&crate_b_toml)
+            .unwrap()
+            .contains(r#"crate-x = { path = "../crate-a", version = "1.0.0" }"#)
+    );
+    assert!(verify_workspace_valid(root));
+}
+
+/// All of stdout, parsed as the `--json` summary.
+fn json_summary(assert: &assert_cmd::assert::Assert) -> serde_json::Value {
+    serde_json::from_slice(&assert.get_output().stdout).unwrap()
+}
+
+#[test]
+fn test_dry_run_json_contract() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    let assert = run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-x",
+        &["--dry-run", "--json"],
+    )
+    .success();
+    let summary = json_summary(&assert);
+
+    assert_eq!(
+        summary,
+        serde_json::json!({
+            "schema_version": 1,
+            "status": "dry-run",
+            "old_name": "crate-a",
+            "new_name": "crate-x",
+            "old_dir": "crate-a",
+            "new_dir": "crate-a",
+            "files_updated": 3,
+            "dirs_moved": 0,
+            "packages": [
+                {"name": "crate-a", "files": ["crate-a/Cargo.toml"]},
+                {"name": "crate-b", "files": ["crate-b/Cargo.toml", "crate-b/src/lib.rs"]},
+            ],
+            "rules": {"crate-b/src/lib.rs": ["use-statement"]},
+        })
+    );
+
+    // Every field the schema requires is present
+    let schema: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/snapshots/summary.schema.json"
+        ))
+        .unwrap(),
+    )
+    .unwrap();
+    for field in schema["required"].as_array().unwrap() {
+        assert!(
+            summary.get(field.as_str().unwrap()).is_some(),
+            "missing {}",
+            field
+        );
+    }
+
+    let noop = run_rename(workspace_root, "crate-a", "crate-a", &["--json"]).success();
+    assert_eq!(json_summary(&noop)["status"], "noop");
+    assert_eq!(json_summary(&noop)["schema_version"], 1);
+}
+
+/// Compares `cargo rename schema` with the vendored snapshot. Run with
+/// `UPDATE_SNAPSHOTS=1` after an intended change (and bump `SCHEMA_VERSION`
+/// if it is incompatible).
+#[test]
+fn test_schema_snapshot() {
+    let snapshot = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/snapshots/summary.schema.json"
+    );
+    let assert = cargo_bin_cmd!("cargo-rename")
+        .args(["rename", "schema"])
+        .assert()
+        .success();
+    let schema = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(snapshot, &schema).unwrap();
+    }
+    assert_eq!(
+        schema,
+        fs::read_to_string(snapshot).unwrap(),
+        "schema changed; rerun with UPDATE_SNAPSHOTS=1 to accept it"
+    );
+}
@@ -4,6 +4,7 @@ use std::fs;
 
 use common::*;
 
+use assert_cmd::cargo::cargo_bin_cmd;
 use predicates::prelude::*;
 use tempfile::TempDir;
 
@@ -17,6 +18,88 @@ fn test_package_not_found() {
         .stderr(predicate::str::contains("not found"));
 }
 
+#[test]
+fn test_package_not_found_suggests_close_matches() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    run_rename(workspace_root, "crate_aa", "new-name", &[])
+        .failure()
+        .stderr(
+            predicate::str::contains("Did you mean").and(predicate::str::contains("'crate-a'")),
+        );
+}
+
+#[test]
+fn test_keyword_identifiers_rejected() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    for name in ["async", "self", "gen"] {
+        run_rename(workspace_root, "crate-a", name, &[])
+            .failure()
+            .stderr(predicate::str::contains("is a Rust keyword"));
+    }
+
+    // Keywords are fine as part of a longer name
+    run_rename(workspace_root, "crate-a", "match-gen", &[]).success();
+    verify_workspace_valid(workspace_root);
+}
+
+#[test]
+fn test_validate_name_subcommand() {
+    cargo_bin_cmd!("cargo-rename")
+        .args(["rename", "validate-name", "new-crate"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("is a valid package name"));
+
+    cargo_bin_cmd!("cargo-rename")
+        .args(["rename", "validate-name", "9lives", "--json"])
+        .assert()
+        .failure()
+        .stdout(
+            predicate::str::contains(r#""valid": false"#)
+                .and(predicate::str::contains("must start with letter")),
+        );
+
+    // Each failure is reported once, as the error
+    let output = cargo_bin_cmd!("cargo-rename")
+        .args(["rename", "validate-name", "fn"])
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let all = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(all.matches("Invalid package name 'fn'").count(), 1);
+}
+
+#[test]
+fn test_preflight_reports_all_problems() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    run_rename(workspace_root, "crate-a", "crate-b", &["--move", "crate-b"])
+        .failure()
+        .stderr(
+            predicate::str::contains("2 problems found")
+                .and(predicate::str::contains("with this name already exists"))
+                .and(predicate::str::contains("Target directory already exists")),
+        );
+
+    run_rename(workspace_root, "crate_aa", "9-invalid", &[])
+        .failure()
+        .stderr(
+            predicate::str::contains("2 problems found")
+                .and(predicate::str::contains("Did you mean"))
+                .and(predicate::str::contains("Invalid package name '9-invalid'")),
+        );
+}
+
 #[test]
 fn test_path_traversal_attempts() {
     let temp = create_test_workspace();
@@ -307,3 +390,153 @@ fn test_unix_absolute_paths() {
         "Expected services/unix-crate/Cargo.toml"
     );
 }
+
+#[test]
+fn test_move_into_own_subdirectory_rejected() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "new-crate",
+        &["--move", "crate-a/nested"],
+    )
+    .failure()
+    .stderr(predicate::str::contains("into itself"));
+
+    assert!(workspace_root.join("crate-a/Cargo.toml").exists());
+}
+
+#[test]
+fn test_move_out_of_submodule_rejected() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    // A `.git` file marks crate-a as a submodule checkout
+    fs::write(
+        workspace_root.join("crate-a/.git"),
+        "gitdir: ../.git/modules/crate-a",
+    )
+    .unwrap();
+
+    run_rename(workspace_root, "crate-a", "", &["--move", "libs/crate-a"])
+        .failure()
+        .stderr(predicate::str::contains("git repository boundary"));
+
+    assert!(workspace_root.join("crate-a/Cargo.toml").exists());
+}
+
+#[test]
+fn test_naming_policy() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    let root_manifest = workspace_root.join("Cargo.toml");
+    let content = fs::read_to_string(&root_manifest).unwrap();
+    fs::write(
+        &root_manifest,
+        format!(
+            "{}\n[workspace.metadata.cargo-rename.naming]\npattern = \"^acme-\"\nmessage = \"package names must start with acme-\"\n",
+            content
+        ),
+    )
+    .unwrap();
+
+    run_rename(workspace_root, "crate-a", "core", &[])
+        .failure()
+        .stderr(predicates::str::contains(
+            "Invalid package name 'core': package names must start with acme-",
+        ));
+
+    run_rename(workspace_root, "crate-a", "acme-core", &[]).success();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_naming_policy_command_needs_allow_commands() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+    let root_manifest = workspace_root.join("Cargo.toml");
+    let content = fs::read_to_string(&root_manifest).unwrap();
+    fs::write(
+        &root_manifest,
+        format!(
+            "{}\n[workspace.metadata.cargo-rename.naming]\npattern = \"^acme-\"\ncommand = [\"sh\", \"-c\", \"echo acme-$CARGO_RENAME_NEW_NAME\"]\n",
+            content
+        ),
+    )
+    .unwrap();
+
+    run_rename(workspace_root, "crate-a", "core", &[])
+        .failure()
+        .stderr(predicates::str::contains("pass --allow-commands"))
+        .stderr(predicates::str::contains("Invalid package name 'core'"));
+    let manifest = fs::read_to_string(workspace_root.join("crate-a/Cargo.toml")).unwrap();
+    assert!(manifest.contains("name = \"crate-a\""));
+
+    run_rename(workspace_root, "crate-a", "core", &["--allow-commands"]).success();
+    let manifest = fs::read_to_string(workspace_root.join("crate-a/Cargo.toml")).unwrap();
+    assert!(manifest.contains("name = \"acme-core\""));
+}
+
+#[test]
+fn test_noop_status_and_fail_if_noop() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    run_rename(workspace_root, "crate-a", "crate-a", &["--json"])
+        .success()
+        .stdout(predicate::str::contains(r#""status":"noop""#));
+
+    run_rename(workspace_root, "crate-a", "crate-a", &["--fail-if-noop"])
+        .failure()
+        .stderr(predicate::str::contains("Nothing to do"));
+
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-z",
+        &["--json", "--fail-if-noop"],
+    )
+    .success()
+    .stdout(predicate::str::contains(r#""status":"applied""#));
+}
+
+#[test]
+fn test_json_errors() {
+    let temp = create_test_workspace();
+    let workspace_root = temp.path();
+
+    let output = run_rename(workspace_root, "crate_aa", "new-name", &["--json-errors"])
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+    let report: serde_json::Value =
+        serde_json::from_str(String::from_utf8(output).unwrap().trim()).unwrap();
+
+    assert_eq!(report["code"], "package-not-found");
+    assert!(report["message"].as_str().unwrap().contains("crate_aa"));
+    assert!(report["remediation"].as_str().unwrap().contains("crate-a"));
+    assert_eq!(report["rollback"], serde_json::Value::Null);
+
+    fs::create_dir_all(workspace_root.join("taken")).unwrap();
+    run_rename(
+        workspace_root,
+        "crate-a",
+        "crate-z",
+        &["--json", "--move", "taken"],
+    )
+    .failure()
+    .stderr(predicate::str::contains(r#""code":"directory-exists""#));
+
+    let output = run_rename(workspace_root, "crate-a", "crate-z", &["--json", "--bogus"])
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+    let report: serde_json::Value =
+        serde_json::from_str(String::from_utf8(output).unwrap().trim()).unwrap();
+    assert_eq!(report["code"], "invalid-arguments");
+    assert!(report["message"].as_str().unwrap().contains("--bogus"));
+}
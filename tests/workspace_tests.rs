@@ -0,0 +1,111 @@
+mod common;
+
+use common::*;
+
+use std::fs;
+use tempfile::TempDir;
+
+/// Creates a workspace named `my-ws` with two prefixed members.
+fn create_named_workspace() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    let workspace_root = temp.path();
+
+    fs::write(
+        workspace_root.join("Cargo.toml"),
+        r#"[workspace]
+members = ["my-ws-core", "my-ws-cli"]
+resolver = "2"
+
+[workspace.package]
+repository = "https://github.com/acme/my-ws"
+"#,
+    )
+    .unwrap();
+
+    fs::write(
+        workspace_root.join("README.md"),
+        "# my-ws\n\nSee https://github.com/acme/my-ws\n",
+    )
+    .unwrap();
+
+    let core = workspace_root.join("my-ws-core");
+    fs::create_dir_all(core.join("src")).unwrap();
+    fs::write(
+        core.join("Cargo.toml"),
+        r#"[package]
+name = "my-ws-core"
+version = "0.1.0"
+edition = "2021"
+"#,
+    )
+    .unwrap();
+    fs::write(core.join("src/lib.rs"), "pub fn core() {}\n").unwrap();
+
+    let cli = workspace_root.join("my-ws-cli");
+    fs::create_dir_all(cli.join("src")).unwrap();
+    fs::write(
+        cli.join("Cargo.toml"),
+        r#"[package]
+name = "my-ws-cli"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+my-ws-core = { path = "../my-ws-core" }
+"#,
+    )
+    .unwrap();
+    fs::write(
+        cli.join("src/lib.rs"),
+        "pub fn run() {\n    my_ws_core::core();\n}\n",
+    )
+    .unwrap();
+
+    temp
+}
+
+#[test]
+fn test_rename_workspace_updates_root_docs_and_urls() {
+    let temp = create_named_workspace();
+    let workspace_root = temp.path();
+
+    run_rename(workspace_root, "workspace", "new-ws", &["--from", "my-ws"]).success();
+
+    let root_toml = fs::read_to_string(workspace_root.join("Cargo.toml")).unwrap();
+    assert!(root_toml.contains(r#"repository = "https://github.com/acme/new-ws""#));
+
+    let readme = fs::read_to_string(workspace_root.join("README.md")).unwrap();
+    assert!(readme.contains("# new-ws"));
+    // Plain link URLs are only rewritten with --docs-update urls
+    assert!(readme.contains("https://github.com/acme/my-ws"));
+
+    // Members are untouched without --members
+    let core_toml = fs::read_to_string(workspace_root.join("my-ws-core/Cargo.toml")).unwrap();
+    assert!(core_toml.contains(r#"name = "my-ws-core""#));
+}
+
+#[test]
+fn test_rename_workspace_with_members() {
+    let temp = create_named_workspace();
+    let workspace_root = temp.path();
+
+    run_rename(
+        workspace_root,
+        "workspace",
+        "new-ws",
+        &["--from", "my-ws", "--members"],
+    )
+    .success();
+
+    let core_toml = fs::read_to_string(workspace_root.join("my-ws-core/Cargo.toml")).unwrap();
+    assert!(core_toml.contains(r#"name = "new-ws-core""#));
+
+    let cli_toml = fs::read_to_string(workspace_root.join("my-ws-cli/Cargo.toml")).unwrap();
+    assert!(cli_toml.contains(r#"name = "new-ws-cli""#));
+    assert!(cli_toml.contains(r#"new-ws-core = { path = "../my-ws-core" }"#));
+
+    let cli_lib = fs::read_to_string(workspace_root.join("my-ws-cli/src/lib.rs")).unwrap();
+    assert!(cli_lib.contains("new_ws_core::core();"));
+
+    assert!(verify_workspace_valid(workspace_root));
+}